@@ -163,7 +163,7 @@ fn bench_filters(c: &mut Criterion) {
     fs::create_dir_all(&sqllog_dir).unwrap();
     fs::write(sqllog_dir.join("bench.log"), synthetic_log(RECORDS)).unwrap();
 
-    let scenarios: &[(&str, Config)] = &[
+    let scenarios: Vec<(&str, Config)> = vec![
         ("no_pipeline", cfg_no_pipeline(&sqllog_dir, &bench_dir)),
         (
             "pipeline_passthrough",
@@ -188,7 +188,7 @@ fn bench_filters(c: &mut Criterion) {
     let mut group = c.benchmark_group("filters");
     group.throughput(Throughput::Elements(RECORDS as u64));
 
-    for (name, cfg) in scenarios {
+    for (name, cfg) in &scenarios {
         group.bench_with_input(BenchmarkId::from_parameter(name), cfg, |b, cfg| {
             b.iter_with_setup(
                 || cfg.validate_and_compile().unwrap(),
@@ -0,0 +1,182 @@
+//! 库级高层接口：在其他 Rust 服务中以编程方式驱动一次导出。
+//!
+//! [`run_export`] 按 `run` 子命令的流程执行：校验配置、固定文件清单、预检、解析与导出、错误预算检查，
+//! 并按配置发送通知、写出运行报告；返回的 [`ExportReport`] 包含完整的运行统计。
+//!
+//! 与命令行不同，这里不初始化日志（`[logging]` 配置段被忽略），日志经 `log` 门面交给宿主程序；
+//! 也不输出进度条与摘要，预检警告以 `warn!` 记录。
+//!
+//! ```no_run
+//! use dm_database_sqllog2db::config::Config;
+//!
+//! let cfg = Config::from_file(std::path::Path::new("config.toml"))?;
+//! let report = dm_database_sqllog2db::run_export(&cfg)?;
+//! println!("exported {} records", report.summary.records);
+//! # Ok::<(), dm_database_sqllog2db::error::Error>(())
+//! ```
+use crate::cli::preflight;
+use crate::cli::run::{RunSummary, handle_run_until};
+use crate::config::Config;
+use crate::error::{ConfigError, Error, Result};
+use crate::parser::ListingSnapshot;
+use crate::run_report::RunReport;
+use chrono::{DateTime, Local};
+use log::warn;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+/// [`run_export_with`] 的运行选项，对应 `run` 子命令的同名参数
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct ExportOptions {
+    /// 最多导出的记录数（`--max-records`）
+    pub limit: Option<usize>,
+    /// 最长运行时间（`--max-duration`），到时处理完当前记录后正常收尾
+    pub max_duration: Option<Duration>,
+    /// 文件级并行线程数（覆盖 `pipeline.workers`）；两者均未设置时 CSV 按 CPU 核数并行，
+    /// 其他导出器按文件顺序写入
+    pub jobs: Option<usize>,
+    /// 只解析统计，不写出（`--dry-run`）
+    pub dry_run: bool,
+    /// 取消标志：置为 `true` 后导出尽快停止并返回 [`Error::Interrupted`]
+    pub interrupted: Option<Arc<AtomicBool>>,
+    /// 配置来源的描述（如配置文件路径），写入通知与运行报告
+    pub config_path: Option<String>,
+}
+
+/// 一次导出的结果
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ExportReport {
+    /// 运行统计（记录数、逐文件计数、解析错误、各导出器写出统计等）
+    pub summary: RunSummary,
+    pub started_at: DateTime<Local>,
+    pub config_path: String,
+    /// 日志输入路径（`sqllog.path`）
+    pub sqllog_path: String,
+}
+
+impl ExportReport {
+    /// 与 `report.file` 格式相同的运行报告，可直接序列化为 JSON
+    #[must_use]
+    pub fn run_report(&self) -> RunReport<'_> {
+        RunReport::from_summary(
+            &self.config_path,
+            &self.sqllog_path,
+            self.started_at,
+            &self.summary,
+        )
+    }
+}
+
+/// 按默认选项执行一次导出，见 [`run_export_with`]
+pub fn run_export(cfg: &Config) -> Result<ExportReport> {
+    run_export_with(cfg, &ExportOptions::default())
+}
+
+/// 执行一次导出：配置校验、文件发现、解析、错误记录与导出
+///
+/// 错误预算（`[error]`）超限时返回错误；配置了 `[notify]` / `[report]` 时无论成败都会通知并写出报告。
+pub fn run_export_with(cfg: &Config, opts: &ExportOptions) -> Result<ExportReport> {
+    let started = Local::now();
+    let config_path = opts.config_path.clone().unwrap_or_default();
+    let mut cfg = cfg.clone();
+    let compiled_filters = cfg.validate_and_compile()?;
+    if let Ok(listing) = ListingSnapshot::take(&cfg.sqllog) {
+        cfg.sqllog.listing = Some(Arc::new(listing));
+    }
+    if !opts.dry_run {
+        let pf = preflight::check(&cfg);
+        for w in &pf.warnings {
+            warn!("Preflight: {w}");
+        }
+        if pf.has_errors() {
+            return Err(Error::Config(ConfigError::PreflightFailed {
+                path: PathBuf::from(&cfg.sqllog.path),
+                reason: pf.errors.join("; "),
+            }));
+        }
+    }
+
+    let interrupted = opts.interrupted.clone().unwrap_or_default();
+    if opts.jobs.is_some() {
+        cfg.pipeline.workers = opts.jobs;
+    }
+    let jobs = cfg
+        .pipeline
+        .workers
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, std::num::NonZero::get));
+    let result = handle_run_until(
+        &cfg,
+        opts.limit,
+        opts.max_duration,
+        false,
+        opts.dry_run,
+        true,
+        &interrupted,
+        80,
+        false,
+        None,
+        jobs,
+        compiled_filters,
+    )
+    .and_then(|s| {
+        cfg.error.check(&s.metrics)?;
+        Ok(s)
+    });
+    if let Some(notify) = &cfg.notify {
+        crate::notify::notify_run(notify, &config_path, &cfg.sqllog.path, &result);
+    }
+    if let Some(report) = &cfg.report {
+        crate::run_report::report_run(report, &config_path, &cfg.sqllog.path, started, &result);
+    }
+    Ok(ExportReport {
+        summary: result?,
+        started_at: started,
+        config_path,
+        sqllog_path: cfg.sqllog.path,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CsvExporter;
+
+    #[test]
+    fn test_run_export_csv() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log_dir = dir.path().join("logs");
+        std::fs::create_dir(&log_dir).unwrap();
+        std::fs::write(log_dir.join("a.log"), crate::testing::fixture_log(5)).unwrap();
+        let csv_file = dir.path().join("out.csv");
+        let mut cfg = Config::default();
+        cfg.sqllog.path = log_dir.to_string_lossy().into_owned();
+        cfg.exporter.csv = Some(CsvExporter {
+            file: csv_file.to_string_lossy().into_owned(),
+            ..CsvExporter::default()
+        });
+
+        let report = run_export(&cfg).unwrap();
+        assert_eq!(report.summary.records, 5);
+        assert_eq!(report.summary.per_file.len(), 1);
+        let json = serde_json::to_value(report.run_report()).unwrap();
+        assert_eq!(json["status"], "success");
+        assert_eq!(json["totals"]["exported"], 5);
+        let lines = std::fs::read_to_string(&csv_file).unwrap().lines().count();
+        assert_eq!(lines, 6);
+        assert_eq!(json["run_id"], report.summary.run_id.as_str());
+
+        // 同一进程内的每次调用各自分配运行标识
+        let again = run_export(&cfg).unwrap();
+        if std::env::var(crate::run_id::RUN_ID_ENV).is_err() {
+            assert_ne!(again.summary.run_id, report.summary.run_id);
+        }
+
+        // 预检失败以错误返回，不打印
+        cfg.sqllog.path = dir.path().join("missing").to_string_lossy().into_owned();
+        assert!(run_export(&cfg).is_err());
+    }
+}
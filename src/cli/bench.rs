@@ -0,0 +1,210 @@
+//! `bench`：导出路径自测。用合成记录分别测量逐条导出（顺序路径）与攒批导出（并发路径写线程使用的
+//! [`RecordBatch`]）的吞吐，`--compare-paths` 时批量路径明显慢于逐条路径即告警，
+//! 防止批量路径的性能回退无人察觉。输出写入临时目录（沿用各导出器已有配置中的其余选项）。
+use super::capacity::sample_config;
+use crate::color;
+use crate::config::Config;
+use crate::error::{Error, FileError, Result};
+use crate::exporter::{ExporterManager, PRODUCER_BATCH_ROWS, RecordBatch};
+use crate::testing::fixture_log;
+use dm_database_parser_sqllog::LogParser;
+use indicatif::HumanCount;
+use log::warn;
+use serde::Serialize;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// 默认合成记录数
+pub const DEFAULT_BENCH_RECORDS: usize = 100_000;
+
+/// 可在本地测量的导出器（Elasticsearch 会写入远端集群，不参与）
+const BENCH_EXPORTERS: &[&str] = &["csv", "sqlite"];
+
+/// 每条路径测量的轮数，取最快一轮以压低缓存 / 调度抖动
+const ROUNDS: usize = 3;
+
+/// 批量路径慢于逐条路径超过该比例时告警
+const SLOWER_TOLERANCE: f64 = 0.10;
+
+/// 单个导出器的测量结果
+#[derive(Debug, Clone, Serialize)]
+pub struct PathTiming {
+    pub exporter: String,
+    pub records: usize,
+    /// 逐条导出的记录/秒
+    pub single_per_sec: f64,
+    /// 攒批导出的记录/秒（未指定 `--compare-paths` 时为空）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batch_per_sec: Option<f64>,
+    /// 批量路径是否明显慢于逐条路径
+    pub batch_slower: bool,
+}
+
+fn temp_dir() -> Result<tempfile::TempDir> {
+    tempfile::TempDir::new().map_err(|e| {
+        Error::File(FileError::CreateDirectoryFailed {
+            path: std::env::temp_dir(),
+            reason: e.to_string(),
+        })
+    })
+}
+
+/// 测量一轮导出（含初始化与 finalize）；`batched` 时先攒入 [`RecordBatch`] 再整批导出
+fn measure(cfg: &Config, exporter: &str, log: &Path, batched: bool) -> Result<Duration> {
+    let dir = temp_dir()?;
+    let target = sample_config(cfg, exporter, dir.path());
+    let mut manager = ExporterManager::from_config(&target)?;
+    let parser = LogParser::from_path(log).map_err(|e| {
+        Error::File(FileError::ReadFailed {
+            path: log.to_path_buf(),
+            reason: e.to_string(),
+        })
+    })?;
+
+    let start = Instant::now();
+    manager.initialize()?;
+    let mut batch = RecordBatch::with_capacity(PRODUCER_BATCH_ROWS, 0);
+    for record in parser.iter().filter_map(std::result::Result::ok) {
+        let meta = record.parse_meta();
+        let pm = record.parse_performance_metrics();
+        if !batched {
+            manager.export_one_preparsed(&record, &meta, &pm, None)?;
+            continue;
+        }
+        batch.push(&record, &meta, &pm, None, &[]);
+        if batch.len() >= PRODUCER_BATCH_ROWS {
+            batch.export_all(&mut manager)?;
+            batch = RecordBatch::with_capacity(PRODUCER_BATCH_ROWS, batch.text_bytes());
+        }
+    }
+    batch.export_all(&mut manager)?;
+    manager.finalize()?;
+    Ok(start.elapsed())
+}
+
+/// 多轮测量取最快一轮，返回记录/秒
+fn best_rate(
+    cfg: &Config,
+    exporter: &str,
+    log: &Path,
+    records: usize,
+    batched: bool,
+) -> Result<f64> {
+    let mut best = Duration::MAX;
+    for _ in 0..ROUNDS {
+        best = best.min(measure(cfg, exporter, log, batched)?);
+    }
+    #[allow(clippy::cast_precision_loss)]
+    Ok(records as f64 / best.as_secs_f64().max(1e-9))
+}
+
+/// 对配置中启用的每个本地导出器测量逐条路径（`compare_paths` 时同时测量批量路径）
+pub fn run_bench(cfg: &Config, records: usize, compare_paths: bool) -> Result<Vec<PathTiming>> {
+    let dir = temp_dir()?;
+    let log = dir.path().join("synthetic.log");
+    std::fs::write(&log, fixture_log(records)).map_err(|e| {
+        Error::File(FileError::WriteFailed {
+            path: log.clone(),
+            reason: e.to_string(),
+        })
+    })?;
+
+    let configured = cfg.exporter.configured();
+    if configured.contains(&"elasticsearch") {
+        warn!("bench: exporter.elasticsearch is skipped (it would write to the remote cluster)");
+    }
+    let mut results = Vec::new();
+    for exporter in BENCH_EXPORTERS.iter().filter(|e| configured.contains(e)) {
+        let single_per_sec = best_rate(cfg, exporter, &log, records, false)?;
+        let batch_per_sec = compare_paths
+            .then(|| best_rate(cfg, exporter, &log, records, true))
+            .transpose()?;
+        let batch_slower =
+            batch_per_sec.is_some_and(|b| b < single_per_sec * (1.0 - SLOWER_TOLERANCE));
+        if batch_slower {
+            warn!(
+                "bench: {exporter} batch export path is slower than the single-record path ({:.0} vs {single_per_sec:.0} records/s)",
+                batch_per_sec.unwrap_or_default()
+            );
+        }
+        results.push(PathTiming {
+            exporter: (*exporter).to_string(),
+            records,
+            single_per_sec,
+            batch_per_sec,
+            batch_slower,
+        });
+    }
+    Ok(results)
+}
+
+/// 打印测量结果（stderr，与 `stats` 的表格一致）
+pub fn print_bench(results: &[PathTiming]) {
+    let Some(first) = results.first() else {
+        eprintln!("No local exporter to benchmark (csv / sqlite)");
+        return;
+    };
+    eprintln!(
+        "\n{} Export path benchmark — {} synthetic records, best of {ROUNDS} rounds",
+        color::cyan("▶"),
+        HumanCount(first.records as u64),
+    );
+    eprintln!(
+        "  {:<8} {:>14} {:>14} {:>8}",
+        "Exporter", "Single rec/s", "Batch rec/s", "Ratio"
+    );
+    for r in results {
+        let (batch, ratio) = r.batch_per_sec.map_or_else(
+            || ("-".to_string(), "-".to_string()),
+            |b| (format!("{b:.0}"), format!("{:.2}x", b / r.single_per_sec)),
+        );
+        eprintln!(
+            "  {:<8} {:>14.0} {:>14} {:>8}",
+            r.exporter, r.single_per_sec, batch, ratio
+        );
+    }
+    for r in results.iter().filter(|r| r.batch_slower) {
+        eprintln!(
+            "{} {}: batch export path is slower than the single-record path",
+            color::yellow("⚠"),
+            r.exporter
+        );
+    }
+}
+
+/// `bench` 子命令入口
+pub fn handle_bench(cfg: &Config, records: usize, compare_paths: bool, json: bool) -> Result<()> {
+    let results = run_bench(cfg, records.max(1), compare_paths)?;
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&results).unwrap_or_default()
+        );
+    } else {
+        print_bench(&results);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bench_compares_paths_for_configured_exporters() {
+        let mut cfg = Config::default();
+        cfg.exporter.sqlite = Some(crate::config::SqliteExporter::default());
+        let results = run_bench(&cfg, 2_000, true).unwrap();
+        let names: Vec<&str> = results.iter().map(|r| r.exporter.as_str()).collect();
+        assert_eq!(names, ["csv", "sqlite"]);
+        for r in &results {
+            assert!(r.single_per_sec > 0.0);
+            assert!(r.batch_per_sec.is_some_and(|b| b > 0.0));
+        }
+
+        let single_only = run_bench(&Config::default(), 100, false).unwrap();
+        assert_eq!(single_only.len(), 1);
+        assert!(single_only[0].batch_per_sec.is_none());
+        assert!(!single_only[0].batch_slower);
+    }
+}
@@ -0,0 +1,254 @@
+//! `stats --capacity`：容量预估。对每种输出格式用前 N 条记录做一次采样导出（写入临时目录，
+//! 与 `run` 走同一处理流程），按每条记录的平均输出字节数外推全量输出大小与目标库体积，并给出写入吞吐，
+//! 供正式回灌前评估仓库容量。
+use super::run::handle_run;
+use crate::config::Config;
+use crate::error::{Error, FileError, Result};
+use indicatif::{HumanBytes, HumanCount, HumanDuration};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+/// 默认采样记录数
+pub const DEFAULT_CAPACITY_SAMPLE: usize = 10_000;
+
+/// 参与预估的输出格式
+const CAPACITY_FORMATS: &[&str] = &["csv", "sqlite"];
+
+/// 单个输出格式的预估结果
+#[derive(Debug, Clone, Serialize)]
+pub struct FormatEstimate {
+    pub format: String,
+    pub sample_records: u64,
+    pub sample_bytes: u64,
+    /// 按全量记录数外推的输出大小（SQLite 即目标库文件大小）
+    pub projected_bytes: u64,
+    /// 输出 / 输入字节比
+    pub output_input_ratio: f64,
+    /// 采样导出的吞吐（记录/秒，含解析）
+    pub records_per_sec: f64,
+    /// 按吞吐外推的全量导出耗时（秒）
+    pub projected_secs: f64,
+}
+
+/// `stats --json` 中的 `capacity` 段
+#[derive(Debug, Clone, Serialize)]
+pub struct CapacityEstimate {
+    pub input_bytes: u64,
+    pub total_records: u64,
+    pub sample_size: usize,
+    pub formats: Vec<FormatEstimate>,
+}
+
+/// 目录（含子目录）下所有文件的总字节数
+fn dir_size(dir: &Path) -> u64 {
+    std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(m) if m.is_dir() => dir_size(&entry.path()),
+            Ok(m) => m.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// 只保留目标格式的导出器，输出重定向到 `dir`（沿用该格式已有配置中的其余选项）
+pub(super) fn sample_config(cfg: &Config, format: &str, dir: &Path) -> Config {
+    let mut target = cfg.clone();
+    let path = |name: &str| dir.join(name).to_string_lossy().into_owned();
+    if format == "csv" {
+        let mut csv = cfg.exporter.csv.clone().unwrap_or_default();
+        csv.file = path("sample.csv");
+        csv.overwrite = true;
+        csv.append = false;
+        target.exporter.csv = Some(csv);
+        target.exporter.sqlite = None;
+    } else {
+        let mut sqlite = cfg.exporter.sqlite.clone().unwrap_or_default();
+        sqlite.database_url = path("sample.db");
+        sqlite.overwrite = true;
+        sqlite.append = false;
+        // 装载钩子面向正式目标库，采样库上不执行
+        sqlite.pre_sql.clear();
+        sqlite.post_sql.clear();
+        target.exporter.sqlite = Some(sqlite);
+        target.exporter.csv = None;
+    }
+    target
+}
+
+fn estimate_format(
+    cfg: &Config,
+    format: &str,
+    sample: usize,
+    input_bytes: u64,
+    total_records: u64,
+) -> Result<FormatEstimate> {
+    let dir = tempfile::TempDir::new().map_err(|e| {
+        Error::File(FileError::CreateDirectoryFailed {
+            path: std::env::temp_dir(),
+            reason: e.to_string(),
+        })
+    })?;
+    let target = sample_config(cfg, format, dir.path());
+    let compiled = target.validate_and_compile()?;
+    let summary = handle_run(
+        &target,
+        Some(sample),
+        false,
+        true,
+        &Arc::new(AtomicBool::new(false)),
+        80,
+        false,
+        None,
+        1,
+        compiled,
+    )?;
+    let sample_bytes = dir_size(dir.path());
+    let sample_records = summary.records as u64;
+
+    #[allow(clippy::cast_precision_loss)]
+    let (per_record, secs) = (
+        sample_bytes as f64 / sample_records.max(1) as f64,
+        summary.elapsed.as_secs_f64().max(1e-6),
+    );
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    let projected_bytes = (per_record * total_records as f64) as u64;
+    #[allow(clippy::cast_precision_loss)]
+    let records_per_sec = sample_records as f64 / secs;
+    #[allow(clippy::cast_precision_loss)]
+    let output_input_ratio = if input_bytes > 0 {
+        projected_bytes as f64 / input_bytes as f64
+    } else {
+        0.0
+    };
+    #[allow(clippy::cast_precision_loss)]
+    let projected_secs = if records_per_sec > 0.0 {
+        total_records as f64 / records_per_sec
+    } else {
+        0.0
+    };
+    Ok(FormatEstimate {
+        format: format.to_string(),
+        sample_records,
+        sample_bytes,
+        projected_bytes,
+        output_input_ratio,
+        records_per_sec,
+        projected_secs,
+    })
+}
+
+/// 对每种输出格式做采样导出并外推。`total_records` 为全量记录数（由 `stats` 统计得到）。
+/// 单个格式采样失败时记录告警并跳过。
+#[must_use]
+pub fn estimate_capacity(
+    cfg: &Config,
+    log_files: &[PathBuf],
+    total_records: u64,
+    sample: usize,
+) -> CapacityEstimate {
+    let input_bytes = log_files
+        .iter()
+        .filter_map(|f| std::fs::metadata(f).ok())
+        .map(|m| m.len())
+        .sum();
+    let formats = CAPACITY_FORMATS
+        .iter()
+        .filter_map(|format| {
+            estimate_format(cfg, format, sample, input_bytes, total_records)
+                .inspect_err(|e| log::warn!("capacity sample for {format} failed: {e}"))
+                .ok()
+        })
+        .collect();
+    CapacityEstimate {
+        input_bytes,
+        total_records,
+        sample_size: sample,
+        formats,
+    }
+}
+
+/// 打印容量预估表（stderr，与 `stats` 其余表格一致）
+pub fn print_capacity(est: &CapacityEstimate) {
+    eprintln!(
+        "\n{} Capacity estimate — input {} / {} records (sampled first {} records per format)",
+        crate::color::cyan("▶"),
+        HumanBytes(est.input_bytes),
+        HumanCount(est.total_records),
+        HumanCount(est.sample_size as u64),
+    );
+    eprintln!(
+        "  {:<8} {:>12} {:>14} {:>10} {:>14} {:>14}",
+        "Format", "Sample", "Projected", "Out/In", "Rec/s", "Est. time"
+    );
+    for f in &est.formats {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let rate = f.records_per_sec as u64;
+        eprintln!(
+            "  {:<8} {:>12} {:>14} {:>9.1}% {:>14} {:>14}",
+            f.format,
+            HumanBytes(f.sample_bytes).to_string(),
+            HumanBytes(f.projected_bytes).to_string(),
+            f.output_input_ratio * 100.0,
+            HumanCount(rate).to_string(),
+            HumanDuration(Duration::from_secs_f64(f.projected_secs)).to_string(),
+        );
+    }
+    if est.formats.is_empty() {
+        eprintln!("  {}", crate::color::dim("No format could be sampled."));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt::Write as _;
+
+    #[test]
+    fn test_estimate_capacity_projects_from_sample() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logdir = dir.path().join("logs");
+        std::fs::create_dir(&logdir).unwrap();
+        let mut log = String::new();
+        for i in 0..20 {
+            writeln!(
+                log,
+                "2025-01-15 10:30:28.001 (EP[0] sess:0x1 user:U trxid:{i} stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT {i} FROM t. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: {i}."
+            )
+            .unwrap();
+        }
+        let log_path = logdir.join("t.log");
+        std::fs::write(&log_path, log).unwrap();
+        let user_csv = dir.path().join("real.csv");
+        let toml = format!(
+            "[sqllog]\ndirectory = \"{}\"\n[exporter.csv]\nfile = \"{}\"\n",
+            logdir.to_string_lossy().replace('\\', "/"),
+            user_csv.to_string_lossy().replace('\\', "/"),
+        );
+        let cfg: Config = toml::from_str(&toml).unwrap();
+
+        let est = estimate_capacity(&cfg, &[log_path], 20, 5);
+        assert!(est.input_bytes > 0);
+        let names: Vec<&str> = est.formats.iter().map(|f| f.format.as_str()).collect();
+        assert_eq!(names, ["csv", "sqlite"]);
+        let csv = &est.formats[0];
+        assert_eq!(csv.sample_records, 5);
+        // 外推为采样的 4 倍（允许整数截断误差）
+        assert!(
+            csv.projected_bytes.abs_diff(csv.sample_bytes * 4) <= 4,
+            "{csv:?}"
+        );
+        assert!(csv.records_per_sec > 0.0);
+        // 采样写入临时目录，不触碰用户配置的输出
+        assert!(!user_csv.exists());
+    }
+}
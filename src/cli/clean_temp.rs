@@ -0,0 +1,109 @@
+//! `clean-temp`：查找并删除崩溃运行残留的临时产物（分片目录、落盘文件、原子写出的暂存文件），
+//! 按 `crate::temp` 的命名约定识别，只删除闲置超过 `--older-than` 的产物。
+use crate::color;
+use crate::config::Config;
+use crate::error::{Error, FileError, Result};
+use log::warn;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 默认只清理闲置超过 24 小时的产物，避免误删仍在运行的任务（标准输入落盘文件在整个运行期间不再修改）
+pub const DEFAULT_OLDER_THAN: Duration = Duration::from_secs(24 * 3600);
+
+/// 扫描目录：系统临时目录、配置中输出文件所在目录，以及命令行额外指定的目录
+#[must_use]
+pub fn scan_dirs(cfg: &Config, extra: &[String]) -> Vec<PathBuf> {
+    let parent = |file: &str| {
+        Path::new(file)
+            .parent()
+            .map(|p| {
+                if p.as_os_str().is_empty() {
+                    PathBuf::from(".")
+                } else {
+                    p.to_path_buf()
+                }
+            })
+            .filter(|_| !crate::exporter::sink::is_stream(Path::new(file)))
+    };
+    let mut dirs = vec![std::env::temp_dir()];
+    dirs.extend(cfg.exporter.csv.as_ref().and_then(|c| parent(&c.file)));
+    dirs.extend(
+        cfg.exporter
+            .sqlite
+            .as_ref()
+            .and_then(|s| parent(&s.database_url)),
+    );
+    dirs.extend(extra.iter().map(PathBuf::from));
+    dirs.sort();
+    dirs.dedup();
+    dirs
+}
+
+/// 清理 `dirs` 中的残留产物，返回删除（`dry_run` 时为将删除）的数量；
+/// 个别产物删除失败时继续处理其余产物，最后返回第一个错误
+pub fn handle_clean_temp(dirs: &[PathBuf], older_than: Duration, dry_run: bool) -> Result<usize> {
+    let stale = crate::temp::find_stale(dirs, older_than);
+    let mut removed = 0usize;
+    let mut first_err = None;
+    for path in &stale {
+        if dry_run {
+            println!("{} would remove {}", color::dim("·"), path.display());
+            removed += 1;
+            continue;
+        }
+        match crate::temp::remove(path) {
+            Ok(()) => {
+                println!("{} removed {}", color::green("✓"), path.display());
+                removed += 1;
+            }
+            Err(e) => {
+                warn!("Cannot remove {}: {e}", path.display());
+                first_err.get_or_insert(Error::File(FileError::WriteFailed {
+                    path: path.clone(),
+                    reason: e.to_string(),
+                }));
+            }
+        }
+    }
+    let verb = if dry_run {
+        "would be removed"
+    } else {
+        "removed"
+    };
+    println!(
+        "{removed} stale temp artifact(s) {verb} (idle for at least {}s)",
+        older_than.as_secs()
+    );
+    first_err.map_or(Ok(removed), Err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_temp_removes_stale_artifacts() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let out = dir.path().join("out.csv");
+        let mut cfg = Config::default();
+        cfg.exporter.csv.as_mut().unwrap().file = out.to_string_lossy().into_owned();
+        assert!(scan_dirs(&cfg, &[]).contains(&dir.path().to_path_buf()));
+
+        let extra = scan_dirs(&cfg, &["extra".to_string()]);
+        assert!(extra.contains(&PathBuf::from("extra")));
+
+        let parts = crate::temp::parts_dir(&out).unwrap().keep();
+        std::fs::write(&out, "kept\n").unwrap();
+        // 不扫描系统临时目录，避免影响并发运行的其他测试
+        let dirs = [dir.path().to_path_buf()];
+        assert_eq!(
+            handle_clean_temp(&dirs, DEFAULT_OLDER_THAN, false).unwrap(),
+            0
+        );
+        assert_eq!(handle_clean_temp(&dirs, Duration::ZERO, true).unwrap(), 1);
+        assert!(parts.exists());
+        assert_eq!(handle_clean_temp(&dirs, Duration::ZERO, false).unwrap(), 1);
+        assert!(!parts.exists());
+        assert!(out.exists());
+    }
+}
@@ -0,0 +1,330 @@
+//! `diff`：比较两次运行的摘要清单（`digest --json` 的输出），报告各日志文件 / 各 SQL 指纹的记录数变化
+//! 与新出现的慢查询，用于数据库升级前后对比负载变化。
+//!
+//! 清单中缺失的部分按空处理：`stats --json` 的输出同样可用于比较各文件记录数。
+//! 注意 `digest --top` / `--min-count` 截断过的清单只能比较其中保留的指纹。
+use crate::error::{Error, FileError, Result};
+use indicatif::HumanCount;
+use serde::Deserialize;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// 慢查询阈值默认值（毫秒）
+pub const DEFAULT_SLOW_MS: f64 = 1000.0;
+
+/// 指纹 SQL 在报告中的最大显示长度
+const SQL_DISPLAY_CHARS: usize = 100;
+
+#[derive(Debug, Default, Deserialize)]
+struct Manifest {
+    #[serde(default)]
+    records: u64,
+    #[serde(default)]
+    per_file: Vec<FileCount>,
+    #[serde(default)]
+    entries: Vec<FingerprintEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FileCount {
+    name: String,
+    records: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct FingerprintEntry {
+    fingerprint: String,
+    count: u64,
+    #[serde(default)]
+    max_exec_ms: f64,
+}
+
+/// 单项计数变化（文件或指纹）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delta {
+    pub key: String,
+    pub before: u64,
+    pub after: u64,
+}
+
+impl Delta {
+    fn change(&self) -> i128 {
+        i128::from(self.after) - i128::from(self.before)
+    }
+}
+
+/// 新出现的慢查询：B 中最大耗时达到阈值，而 A 中不存在或未达到阈值
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewSlow {
+    pub fingerprint: String,
+    pub count: u64,
+    pub max_exec_ms: f64,
+    /// A 中的最大耗时（指纹在 A 中不存在时为 `None`）
+    pub before_max_ms: Option<f64>,
+}
+
+/// 两份清单的比较结果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiffReport {
+    pub records_before: u64,
+    pub records_after: u64,
+    /// 记录数有变化的文件，按文件名排序
+    pub files: Vec<Delta>,
+    /// 计数有变化的指纹，按变化量绝对值降序
+    pub fingerprints: Vec<Delta>,
+    /// 按 B 中最大耗时降序
+    pub new_slow: Vec<NewSlow>,
+}
+
+fn load_manifest(path: &Path) -> Result<Manifest> {
+    let read_err = |reason: String| {
+        Error::File(FileError::ReadFailed {
+            path: path.to_path_buf(),
+            reason,
+        })
+    };
+    let content = std::fs::read_to_string(path).map_err(|e| read_err(e.to_string()))?;
+    serde_json::from_str(&content).map_err(|e| read_err(format!("invalid manifest: {e}")))
+}
+
+/// 键的并集上逐项比较，只保留有变化的项
+fn diff_counts(
+    before: impl IntoIterator<Item = (String, u64)>,
+    after: impl IntoIterator<Item = (String, u64)>,
+) -> Vec<Delta> {
+    let mut merged: BTreeMap<String, (u64, u64)> = BTreeMap::new();
+    for (k, n) in before {
+        merged.entry(k).or_default().0 += n;
+    }
+    for (k, n) in after {
+        merged.entry(k).or_default().1 += n;
+    }
+    merged
+        .into_iter()
+        .filter(|(_, (b, a))| b != a)
+        .map(|(key, (before, after))| Delta { key, before, after })
+        .collect()
+}
+
+fn diff_manifests(a: &Manifest, b: &Manifest, slow_ms: f64) -> DiffReport {
+    let files = diff_counts(
+        a.per_file.iter().map(|f| (f.name.clone(), f.records)),
+        b.per_file.iter().map(|f| (f.name.clone(), f.records)),
+    );
+    let mut fingerprints = diff_counts(
+        a.entries.iter().map(|e| (e.fingerprint.clone(), e.count)),
+        b.entries.iter().map(|e| (e.fingerprint.clone(), e.count)),
+    );
+    // 稳定排序：变化量相同时保持指纹字典序
+    fingerprints.sort_by_key(|d| Reverse(d.change().unsigned_abs()));
+
+    let before_max: BTreeMap<&str, f64> = a
+        .entries
+        .iter()
+        .map(|e| (e.fingerprint.as_str(), e.max_exec_ms))
+        .collect();
+    let mut new_slow: Vec<NewSlow> = b
+        .entries
+        .iter()
+        .filter(|e| e.max_exec_ms >= slow_ms)
+        .filter_map(|e| {
+            let before_max_ms = before_max.get(e.fingerprint.as_str()).copied();
+            (before_max_ms.is_none_or(|m| m < slow_ms)).then(|| NewSlow {
+                fingerprint: e.fingerprint.clone(),
+                count: e.count,
+                max_exec_ms: e.max_exec_ms,
+                before_max_ms,
+            })
+        })
+        .collect();
+    new_slow.sort_by(|x, y| y.max_exec_ms.total_cmp(&x.max_exec_ms));
+
+    DiffReport {
+        records_before: a.records,
+        records_after: b.records,
+        files,
+        fingerprints,
+        new_slow,
+    }
+}
+
+/// 比较两份清单文件
+pub fn diff_files(a: &Path, b: &Path, slow_ms: f64) -> Result<DiffReport> {
+    Ok(diff_manifests(
+        &load_manifest(a)?,
+        &load_manifest(b)?,
+        slow_ms,
+    ))
+}
+
+fn signed(n: i128) -> String {
+    let abs = HumanCount(u64::try_from(n.unsigned_abs()).unwrap_or(u64::MAX));
+    if n < 0 {
+        format!("-{abs}")
+    } else {
+        format!("+{abs}")
+    }
+}
+
+fn short_sql(s: &str) -> String {
+    if s.chars().count() > SQL_DISPLAY_CHARS {
+        let mut out: String = s.chars().take(SQL_DISPLAY_CHARS).collect();
+        out.push('…');
+        out
+    } else {
+        s.to_string()
+    }
+}
+
+/// 渲染为文本报告；`top` 限制指纹与慢查询列表的条数
+#[must_use]
+pub fn render_report(report: &DiffReport, a: &str, b: &str, slow_ms: f64, top: usize) -> String {
+    let mut out = String::new();
+    let total = i128::from(report.records_after) - i128::from(report.records_before);
+    let pct = if report.records_before > 0 {
+        #[allow(clippy::cast_precision_loss)]
+        let p = total as f64 * 100.0 / report.records_before as f64;
+        format!(", {p:+.1}%")
+    } else {
+        String::new()
+    };
+    let _ = writeln!(out, "sqllog2db diff: {a} → {b}");
+    let _ = writeln!(
+        out,
+        "records: {} → {} ({}{pct})",
+        HumanCount(report.records_before),
+        HumanCount(report.records_after),
+        signed(total)
+    );
+
+    let _ = writeln!(out, "\nFiles ({} changed):", report.files.len());
+    for d in &report.files {
+        let mark = match (d.before, d.after) {
+            (0, _) => '+',
+            (_, 0) => '-',
+            _ => '~',
+        };
+        let _ = writeln!(
+            out,
+            "  {mark} {:<40} {:>12} → {:<12} ({})",
+            d.key,
+            HumanCount(d.before).to_string(),
+            HumanCount(d.after).to_string(),
+            signed(d.change())
+        );
+    }
+
+    let _ = writeln!(
+        out,
+        "\nFingerprints ({} changed, top {} by change):",
+        report.fingerprints.len(),
+        top.min(report.fingerprints.len())
+    );
+    for d in report.fingerprints.iter().take(top) {
+        let _ = writeln!(
+            out,
+            "  {:>10}  {:>10} → {:<10}  {}",
+            signed(d.change()),
+            HumanCount(d.before).to_string(),
+            HumanCount(d.after).to_string(),
+            short_sql(&d.key)
+        );
+    }
+
+    let _ = writeln!(
+        out,
+        "\nNew slow queries (max ≥ {slow_ms} ms): {}",
+        report.new_slow.len()
+    );
+    for s in report.new_slow.iter().take(top) {
+        let before = s
+            .before_max_ms
+            .map_or_else(|| "new".to_string(), |m| format!("was {m:.1} ms"));
+        let _ = writeln!(
+            out,
+            "  {:>10.1} ms  ×{:<8} ({before})  {}",
+            s.max_exec_ms,
+            HumanCount(s.count).to_string(),
+            short_sql(&s.fingerprint)
+        );
+    }
+    out
+}
+
+/// `diff` 子命令入口：报告输出到 stdout
+pub fn handle_diff(a: &str, b: &str, slow_ms: f64, top: usize) -> Result<()> {
+    let report = diff_files(Path::new(a), Path::new(b), slow_ms)?;
+    print!("{}", render_report(&report, a, b, slow_ms, top));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(json: &str) -> Manifest {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_diff_files_and_fingerprints() {
+        let a = manifest(
+            r#"{"records": 30, "per_file": [{"name": "a.log", "records": 20}, {"name": "b.log", "records": 10}],
+                "entries": [{"fingerprint": "select ?", "count": 25, "max_exec_ms": 5.0},
+                            {"fingerprint": "update t set a = ?", "count": 5, "max_exec_ms": 1500.0}]}"#,
+        );
+        let b = manifest(
+            r#"{"records": 40, "per_file": [{"name": "a.log", "records": 20}, {"name": "c.log", "records": 20}],
+                "entries": [{"fingerprint": "select ?", "count": 10, "max_exec_ms": 2500.0},
+                            {"fingerprint": "update t set a = ?", "count": 5, "max_exec_ms": 1800.0},
+                            {"fingerprint": "delete from t", "count": 25, "max_exec_ms": 3000.0}]}"#,
+        );
+        let r = diff_manifests(&a, &b, 1000.0);
+        let keys: Vec<&str> = r.files.iter().map(|d| d.key.as_str()).collect();
+        assert_eq!(keys, ["b.log", "c.log"]);
+        // 按变化量降序：delete +25，select -15；update 未变化
+        let fps: Vec<(&str, i128)> = r
+            .fingerprints
+            .iter()
+            .map(|d| (d.key.as_str(), d.change()))
+            .collect();
+        assert_eq!(fps, [("delete from t", 25), ("select ?", -15)]);
+        // update 在 A 中已是慢查询，不算新增
+        let slow: Vec<&str> = r.new_slow.iter().map(|s| s.fingerprint.as_str()).collect();
+        assert_eq!(slow, ["delete from t", "select ?"]);
+        assert_eq!(r.new_slow[1].before_max_ms, Some(5.0));
+    }
+
+    #[test]
+    fn test_render_report() {
+        let a = manifest(r#"{"records": 1000, "per_file": [{"name": "x.log", "records": 1000}]}"#);
+        let b = manifest(
+            r#"{"records": 1200, "per_file": [{"name": "x.log", "records": 1200}],
+                "entries": [{"fingerprint": "select ?", "count": 1200, "max_exec_ms": 1200.0}]}"#,
+        );
+        let text = render_report(
+            &diff_manifests(&a, &b, 1000.0),
+            "a.json",
+            "b.json",
+            1000.0,
+            10,
+        );
+        assert!(
+            text.contains("records: 1,000 → 1,200 (+200, +20.0%)"),
+            "{text}"
+        );
+        assert!(text.contains("~ x.log"), "{text}");
+        assert!(text.contains("(new)  select ?"), "{text}");
+    }
+
+    #[test]
+    fn test_invalid_manifest_rejected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("bad.json");
+        std::fs::write(&path, "not json").unwrap();
+        let err = diff_files(&path, &path, DEFAULT_SLOW_MS).unwrap_err();
+        assert!(err.to_string().contains("invalid manifest"), "{err}");
+    }
+}
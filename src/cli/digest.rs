@@ -1,8 +1,7 @@
 use crate::color;
 use crate::config::Config;
 use crate::features::fingerprint;
-use crate::parser::SqllogParser;
-use dm_database_parser_sqllog::LogParser;
+use crate::parser::{SqllogParser, open_log};
 use indicatif::{HumanCount, ProgressBar, ProgressStyle};
 use serde::Serialize;
 use std::collections::HashMap;
@@ -31,6 +30,13 @@ pub struct DigestEntry {
     pub first_seen: String,
 }
 
+/// 单文件记录数（`--json` 输出，供 `diff` 比较两次运行）
+#[derive(Debug, Serialize)]
+struct FileCount {
+    name: String,
+    records: u64,
+}
+
 #[derive(Debug, Serialize)]
 struct DigestJson {
     files: usize,
@@ -40,6 +46,7 @@ struct DigestJson {
     rate_per_sec: u64,
     fingerprints: usize,
     skipped_files: usize,
+    per_file: Vec<FileCount>,
     entries: Vec<DigestEntry>,
 }
 
@@ -73,7 +80,7 @@ pub fn handle_digest(
     resume_state_file: Option<&str>,
 ) {
     let start = Instant::now();
-    let log_files = match SqllogParser::new(&cfg.sqllog.path).log_files() {
+    let log_files = match SqllogParser::from_config(&cfg.sqllog).and_then(|p| p.log_files()) {
         Ok(files) => files,
         Err(e) => {
             eprintln!("{} {e}", color::red("Error:"));
@@ -97,6 +104,7 @@ pub fn handle_digest(
     let mut total_errors: u64 = 0;
     let mut skipped_files = 0usize;
     let mut fp_map: HashMap<String, FingerprintAccumulator> = HashMap::new();
+    let mut per_file: Vec<FileCount> = Vec::with_capacity(total_files);
 
     for (idx, log_file) in log_files.iter().enumerate() {
         if let Some(state) = &resume_state {
@@ -120,9 +128,9 @@ pub fn handle_digest(
             .map_or_else(|| log_file.to_string_lossy(), |n| n.to_string_lossy())
             .into_owned();
         pb.set_prefix(format!("{}/{total_files}", idx + 1));
-        pb.set_message(file_name);
+        pb.set_message(file_name.clone());
 
-        let Ok(parser) = LogParser::from_path(log_file.as_path()) else {
+        let Ok(parser) = open_log(log_file) else {
             total_errors += 1;
             continue;
         };
@@ -157,6 +165,10 @@ pub fn handle_digest(
         }
 
         let file_records = total_records - records_before;
+        per_file.push(FileCount {
+            name: file_name,
+            records: file_records,
+        });
         if let (Some(state), Some(path)) = (&mut resume_state, &state_path_opt) {
             if let Err(e) = state.mark_processed(log_file, file_records) {
                 eprintln!(
@@ -228,6 +240,7 @@ pub fn handle_digest(
             rate,
             fp_map_len_before_filter(&entries),
             skipped_files,
+            per_file,
             entries,
         );
     } else {
@@ -331,6 +344,7 @@ fn print_json(
     rate: u64,
     fingerprints: usize,
     skipped_files: usize,
+    per_file: Vec<FileCount>,
     entries: Vec<DigestEntry>,
 ) {
     let output = DigestJson {
@@ -341,6 +355,7 @@ fn print_json(
         rate_per_sec: rate,
         fingerprints,
         skipped_files,
+        per_file,
         entries,
     };
     println!(
@@ -0,0 +1,331 @@
+//! `doctor --check-dm-config`：读取达梦服务器的 SQL 日志参数，与当前导出配置比对。
+//!
+//! `dm.ini` 的 `SVR_LOG` 决定是否写 SQL 日志，`SVR_LOG_NAME` 指定使用 `sqllog.ini`（与 `dm.ini` 同目录）
+//! 中的哪一节（默认 `SLOG_ALL`）。该节的 `SQL_TRACE_MASK`（`位号:位号` 形式）决定记录哪些语句、
+//! 是否附带执行时间与绑定参数，`ITEMS` 决定记录头中的字段，`MIN_EXEC_TIME` 只记录慢于该值的语句。
+//! 掩码未记录导出所需的内容（如执行时间）时给出警告，避免导出后才发现指标列全为空。
+use super::preflight::PreflightResult;
+use crate::color;
+use crate::config::Config;
+use crate::error::{Error, FileError, Result};
+use crate::features::FIELD_NAMES;
+use std::path::{Path, PathBuf};
+
+/// `SQL_TRACE_MASK` 位号：全部记录
+const MASK_ALL: u32 = 1;
+/// `SQL_TRACE_MASK` 位号：语句类型（DML、DDL、UPDATE …… 登录登出）
+const MASK_STATEMENT_TYPES: std::ops::RangeInclusive<u32> = 2..=13;
+/// `SQL_TRACE_MASK` 位号：记录绑定参数（`PARAMS` 记录）
+const MASK_PARAMS: u32 = 24;
+/// `SQL_TRACE_MASK` 位号：记录执行时间（`EXECTIME` / `ROWCOUNT` / `EXEC_ID` 后缀）
+const MASK_EXEC_TIME: u32 = 25;
+
+/// `ITEMS` 编号与依赖它的导出字段（0 表示记录全部字段）
+const ITEMS: &[(u32, &str, &[&str])] = &[
+    (1, "TIME", &["ts"]),
+    (3, "SESS", &["sess_id", "thrd_id"]),
+    (4, "USER", &["username"]),
+    (5, "TRXID", &["trx_id"]),
+    (6, "STMT", &["statement"]),
+    (7, "APPNAME", &["appname"]),
+    (8, "IP", &["client_ip"]),
+];
+
+/// 依赖执行时间后缀的导出字段
+const EXEC_TIME_FIELDS: &[&str] = &["exec_time_ms", "row_count", "exec_id"];
+
+/// 解析后的 INI：`(节名, [(键, 值)])`，键统一为大写；节名之前的键归入空节名
+#[derive(Debug, Default)]
+struct Ini(Vec<(String, Vec<(String, String)>)>);
+
+impl Ini {
+    fn parse(text: &str) -> Self {
+        let mut sections = vec![(String::new(), Vec::new())];
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                sections.push((name.trim().to_ascii_uppercase(), Vec::new()));
+            } else if let Some((k, v)) = line.split_once('=') {
+                if let Some((_, entries)) = sections.last_mut() {
+                    entries.push((k.trim().to_ascii_uppercase(), v.trim().to_string()));
+                }
+            }
+        }
+        Self(sections)
+    }
+
+    /// 任意节中第一次出现的 `key`
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .flat_map(|(_, entries)| entries)
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn section(&self, name: &str) -> Option<&[(String, String)]> {
+        self.0
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, entries)| entries.as_slice())
+    }
+}
+
+fn lookup<'a>(entries: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    entries
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.as_str())
+}
+
+fn read_ini(path: &Path) -> Result<Ini> {
+    let bytes = std::fs::read(crate::paths::for_io(path)).map_err(|e| {
+        Error::File(FileError::ReadFailed {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        })
+    })?;
+    Ok(Ini::parse(&String::from_utf8_lossy(&bytes)))
+}
+
+/// `位号:位号` 列表；无法解析的项原样返回在第二个列表中
+fn parse_bits(value: &str) -> (Vec<u32>, Vec<&str>) {
+    let (mut bits, mut invalid) = (Vec::new(), Vec::new());
+    for part in value.split(':').map(str::trim).filter(|p| !p.is_empty()) {
+        match part.parse() {
+            Ok(b) => bits.push(b),
+            Err(_) => invalid.push(part),
+        }
+    }
+    (bits, invalid)
+}
+
+/// 比对 `path`（`dm.ini`，或直接给出的 `sqllog.ini`）与导出配置，返回警告与错误
+pub fn check_dm_config(cfg: &Config, path: &Path) -> Result<PreflightResult> {
+    let mut result = PreflightResult::default();
+    let ini = read_ini(path)?;
+
+    // 直接给出 sqllog.ini 时跳过 dm.ini 的检查
+    let (sqllog_ini, section_name) = if ini.get("SQL_TRACE_MASK").is_some() {
+        (Some((path.to_path_buf(), ini)), None)
+    } else {
+        if ini.get("SVR_LOG").is_some_and(|v| v == "0") {
+            result.errors.push(format!(
+                "{}: SVR_LOG = 0, SQL logging is disabled and no dmsql logs are written",
+                path.display()
+            ));
+        }
+        let section = ini.get("SVR_LOG_NAME").map(str::to_string);
+        let sibling = path
+            .parent()
+            .map_or_else(|| PathBuf::from("sqllog.ini"), |d| d.join("sqllog.ini"));
+        if sibling.exists() {
+            (Some((sibling.clone(), read_ini(&sibling)?)), section)
+        } else {
+            result.warnings.push(format!(
+                "{} not found: SQL_TRACE_MASK / ITEMS cannot be checked",
+                sibling.display()
+            ));
+            (None, section)
+        }
+    };
+    let Some((sqllog_path, sqllog_ini)) = sqllog_ini else {
+        return Ok(result);
+    };
+
+    let name = section_name.unwrap_or_else(|| "SLOG_ALL".to_string());
+    let Some(entries) = sqllog_ini.section(&name).or_else(|| {
+        sqllog_ini
+            .0
+            .iter()
+            .find(|(_, e)| lookup(e, "SQL_TRACE_MASK").is_some())
+            .map(|(_, e)| e.as_slice())
+    }) else {
+        result.warnings.push(format!(
+            "{}: no [{name}] section with SQL_TRACE_MASK",
+            sqllog_path.display()
+        ));
+        return Ok(result);
+    };
+    let at = format!("{} [{name}]", sqllog_path.display());
+
+    let fields: Vec<&str> = cfg
+        .features
+        .ordered_field_indices()
+        .into_iter()
+        .map(|i| FIELD_NAMES[i])
+        .collect();
+    check_mask(
+        cfg,
+        &fields,
+        lookup(entries, "SQL_TRACE_MASK"),
+        &at,
+        &mut result,
+    );
+    check_items(&fields, lookup(entries, "ITEMS"), &at, &mut result);
+    if let Some(ms) = lookup(entries, "MIN_EXEC_TIME").and_then(|v| v.parse::<u64>().ok()) {
+        if ms > 0 {
+            result.warnings.push(format!(
+                "{at}: MIN_EXEC_TIME = {ms}, only statements slower than {ms} ms are logged; \
+                 record counts and template statistics cover slow statements only"
+            ));
+        }
+    }
+    Ok(result)
+}
+
+/// 打印检查结果；没有任何发现时输出一行确认，返回是否有致命错误
+#[must_use]
+pub fn print_result(result: &PreflightResult, path: &Path) -> bool {
+    if result.errors.is_empty() && result.warnings.is_empty() {
+        println!(
+            "{} {}: SQL log settings cover the configured export",
+            color::green("✓"),
+            path.display()
+        );
+    }
+    result.print_and_check()
+}
+
+fn check_mask(
+    cfg: &Config,
+    fields: &[&str],
+    mask: Option<&str>,
+    at: &str,
+    result: &mut PreflightResult,
+) {
+    let Some(mask) = mask else {
+        return;
+    };
+    let (bits, invalid) = parse_bits(mask);
+    if !invalid.is_empty() {
+        result.warnings.push(format!(
+            "{at}: SQL_TRACE_MASK = {mask} has unrecognized item(s): {}",
+            invalid.join(", ")
+        ));
+    }
+    if bits.contains(&MASK_ALL) {
+        return;
+    }
+    if !bits.iter().any(|b| MASK_STATEMENT_TYPES.contains(b)) {
+        result.warnings.push(format!(
+            "{at}: SQL_TRACE_MASK = {mask} selects no statement types (1 = all, 2 = DML, 3 = DDL, ...)"
+        ));
+    }
+    let needs_exec_time: Vec<&str> = fields
+        .iter()
+        .copied()
+        .filter(|f| EXEC_TIME_FIELDS.contains(f))
+        .collect();
+    let analysis = cfg
+        .features
+        .template_analysis
+        .as_ref()
+        .is_some_and(|t| t.enabled);
+    if !bits.contains(&MASK_EXEC_TIME) && (!needs_exec_time.is_empty() || analysis) {
+        let mut what = needs_exec_time.join(" / ");
+        if analysis {
+            if !what.is_empty() {
+                what.push_str(" and ");
+            }
+            what.push_str("template latency statistics");
+        }
+        result.warnings.push(format!(
+            "{at}: SQL_TRACE_MASK = {mask} does not record execution time ({MASK_EXEC_TIME}); \
+             {what} will be empty"
+        ));
+    }
+    let normalizes = cfg
+        .features
+        .replace_parameters
+        .as_ref()
+        .is_none_or(|r| r.enable)
+        && fields.contains(&"normalized_sql");
+    if normalizes && !bits.contains(&MASK_PARAMS) {
+        result.warnings.push(format!(
+            "{at}: SQL_TRACE_MASK = {mask} does not record bind parameters ({MASK_PARAMS}); \
+             normalized_sql keeps the placeholders"
+        ));
+    }
+}
+
+fn check_items(fields: &[&str], items: Option<&str>, at: &str, result: &mut PreflightResult) {
+    let Some(items) = items else {
+        return;
+    };
+    let (bits, _) = parse_bits(items);
+    if bits.is_empty() || bits.contains(&0) {
+        return;
+    }
+    for (n, item, deps) in ITEMS {
+        let missing: Vec<&str> = deps
+            .iter()
+            .copied()
+            .filter(|d| fields.contains(d))
+            .collect();
+        if !bits.contains(n) && !missing.is_empty() {
+            result.warnings.push(format!(
+                "{at}: ITEMS = {items} does not record {item} ({n}); {} will be empty",
+                missing.join(" / ")
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(dm_ini: &str, sqllog_ini: Option<&str>, cfg: &Config) -> PreflightResult {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("dm.ini");
+        std::fs::write(&path, dm_ini).unwrap();
+        if let Some(s) = sqllog_ini {
+            std::fs::write(dir.path().join("sqllog.ini"), s).unwrap();
+        }
+        check_dm_config(cfg, &path).unwrap()
+    }
+
+    #[test]
+    fn test_check_dm_config() {
+        let cfg = Config::default();
+        let r = check(
+            "SVR_LOG = 1 # on\nSVR_LOG_NAME = SLOG_ALL\n",
+            Some("BUF_SIZE = 10\n[SLOG_ALL]\nSQL_TRACE_MASK = 1\nITEMS = 0\n"),
+            &cfg,
+        );
+        assert!(r.errors.is_empty() && r.warnings.is_empty(), "{r:?}");
+
+        let r = check(
+            "SVR_LOG = 0\nSVR_LOG_NAME = SLOW\n",
+            Some(
+                "[SLOG_ALL]\nSQL_TRACE_MASK = 1\n[SLOW]\nSQL_TRACE_MASK = 2:3:23\n\
+                 ITEMS = 1:3:4:5:6\nMIN_EXEC_TIME = 100\n",
+            ),
+            &cfg,
+        );
+        assert_eq!(r.errors.len(), 1, "{r:?}");
+        let text = r.warnings.join("\n");
+        assert!(
+            text.contains("exec_time_ms / row_count / exec_id will be empty"),
+            "{text}"
+        );
+        assert!(text.contains("bind parameters"), "{text}");
+        assert!(
+            text.contains("APPNAME (7); appname will be empty"),
+            "{text}"
+        );
+        assert!(text.contains("IP (8); client_ip"), "{text}");
+        assert!(text.contains("MIN_EXEC_TIME = 100"), "{text}");
+        assert_eq!(r.warnings.len(), 5, "{text}");
+
+        // 只导出不依赖执行时间的字段时不告警
+        let mut narrow = Config::default();
+        narrow.features.fields = Some(vec!["ts".into(), "sql".into()]);
+        let r = check("", Some("[SLOG_ALL]\nSQL_TRACE_MASK = 2:3\n"), &narrow);
+        assert!(r.warnings.is_empty(), "{r:?}");
+
+        let r = check("SVR_LOG = 1\n", None, &cfg);
+        assert!(r.warnings[0].contains("not found"), "{r:?}");
+    }
+}
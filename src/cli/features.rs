@@ -0,0 +1,268 @@
+//! `features`：列出本二进制内置的导出器与输入格式（及其版本）、据此有效的 `[exporter.*]` 配置段，
+//! 以及常被问到但未内置的后端。配置了未内置导出器的段时加载即报错（见 `Config::from_file`），
+//! 传入 `-c` 时同时检查该配置文件，指出这些段、被覆盖的导出器与实际生效的导出器。
+use crate::color;
+use crate::config::EXPORTER_NAMES;
+use crate::error::{ConfigError, Error, Result};
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// 常被问到、但本二进制未内置的后端
+pub const NOT_BUILT: &[&str] = &["parquet", "duckdb", "postgres", "dm", "tui"];
+
+/// 一项能力
+#[derive(Debug, Serialize)]
+pub struct Capability {
+    pub name: &'static str,
+    /// 对应的配置段；输入格式无需配置
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+    pub detail: String,
+}
+
+/// 配置文件检查结果
+#[derive(Debug, Serialize)]
+pub struct ConfigCheck {
+    pub path: String,
+    /// 将被使用的导出器
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active: Option<&'static str>,
+    /// 已配置但被优先级更高的导出器覆盖、不会生效的导出器段
+    pub ignored: Vec<String>,
+    /// 未内置的导出器段（加载配置时报错）
+    pub unsupported: Vec<String>,
+}
+
+/// `features` 的完整输出
+#[derive(Debug, Serialize)]
+pub struct FeatureReport {
+    pub version: &'static str,
+    pub exporters: Vec<Capability>,
+    pub inputs: Vec<Capability>,
+    pub not_built: &'static [&'static str],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<ConfigCheck>,
+}
+
+fn exporters() -> Vec<Capability> {
+    vec![
+        Capability {
+            name: "csv",
+            section: Some("exporter.csv".to_string()),
+            detail: format!(
+                "dialects: {}; compression: {}; exectime formats: {}",
+                crate::config::CSV_DIALECTS.join(", "),
+                crate::exporter::compress::COMPRESSIONS.join(", "),
+                crate::config::EXECTIME_FORMATS.join(", ")
+            ),
+        },
+        Capability {
+            name: "sqlite",
+            section: Some("exporter.sqlite".to_string()),
+            detail: format!("SQLite {} (bundled)", rusqlite::version()),
+        },
+        Capability {
+            name: "elasticsearch",
+            section: Some("exporter.elasticsearch".to_string()),
+            detail: "Elasticsearch / OpenSearch _bulk API over HTTP(S)".to_string(),
+        },
+        Capability {
+            name: "custom",
+            section: Some("exporter.custom".to_string()),
+            detail: format!(
+                "third-party exporters registered by programs embedding the library (registered: {})",
+                crate::exporter::registry::registered_label()
+            ),
+        },
+    ]
+}
+
+fn inputs() -> Vec<Capability> {
+    vec![
+        Capability {
+            name: "plain",
+            section: None,
+            detail: ".log files, directories and glob patterns".to_string(),
+        },
+        Capability {
+            name: "gzip / zstd / xz",
+            section: None,
+            detail: ".log.gz / .log.zst / .log.xz, detected by magic bytes".to_string(),
+        },
+        Capability {
+            name: "stdin",
+            section: None,
+            detail: "--stdin or sqllog.path = \"-\"".to_string(),
+        },
+    ]
+}
+
+/// 检查配置文件中的 `[exporter.*]` 段：只看原始 TOML，未内置的段也照常列出
+pub fn check_config(path: &Path) -> Result<ConfigCheck> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|_| Error::Config(ConfigError::NotFound(path.to_path_buf())))?;
+    let table: toml::Table = toml::from_str(&content).map_err(|e| {
+        Error::Config(ConfigError::ParseFailed {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        })
+    })?;
+    let sections: Vec<&String> = table
+        .get("exporter")
+        .and_then(toml::Value::as_table)
+        .map(|t| t.keys().collect())
+        .unwrap_or_default();
+
+    let active = EXPORTER_NAMES
+        .iter()
+        .copied()
+        .find(|name| sections.iter().any(|s| s.as_str() == *name));
+    let (builtin, unsupported): (Vec<&String>, Vec<&String>) = sections
+        .iter()
+        .partition(|s| EXPORTER_NAMES.contains(&s.as_str()));
+    let ignored = builtin
+        .into_iter()
+        .filter(|s| Some(s.as_str()) != active)
+        .map(|s| format!("exporter.{s}"))
+        .collect();
+    let unsupported = unsupported
+        .into_iter()
+        .map(|s| format!("exporter.{s}"))
+        .collect();
+    Ok(ConfigCheck {
+        path: path.display().to_string(),
+        active,
+        ignored,
+        unsupported,
+    })
+}
+
+/// 汇总本二进制的能力；`config` 非空时附带配置文件检查
+pub fn feature_report(config: Option<&Path>) -> Result<FeatureReport> {
+    Ok(FeatureReport {
+        version: env!("CARGO_PKG_VERSION"),
+        exporters: exporters(),
+        inputs: inputs(),
+        not_built: NOT_BUILT,
+        config: config.map(check_config).transpose()?,
+    })
+}
+
+fn format_report(report: &FeatureReport) -> String {
+    let mut out = format!("sqllog2db {}\n", report.version);
+    let section = |out: &mut String, title: &str, items: &[Capability]| {
+        let _ = writeln!(out, "\n{}", color::bold(title));
+        for c in items {
+            let name = format!("{:<18}", c.name);
+            let section = c
+                .section
+                .as_ref()
+                .map_or_else(String::new, |s| format!("{:<20}", format!("[{s}]")));
+            let _ = writeln!(out, "  {} {name}{section}{}", color::green("✓"), c.detail);
+        }
+    };
+    section(&mut out, "Exporters", &report.exporters);
+    section(&mut out, "Inputs", &report.inputs);
+    let _ = writeln!(
+        out,
+        "\n{} {} {}",
+        color::bold("Not built:"),
+        report.not_built.join(", "),
+        color::dim("(configs naming them are rejected at load)")
+    );
+
+    if let Some(check) = &report.config {
+        let _ = writeln!(out, "\n{} {}", color::bold("Config"), check.path);
+        match check.active {
+            Some(name) => {
+                let _ = writeln!(out, "  {} exporter.{name} will be used", color::green("✓"));
+            }
+            None => {
+                let _ = writeln!(
+                    out,
+                    "  {} no built-in exporter configured ({})",
+                    color::red("✗"),
+                    EXPORTER_NAMES.join(", ")
+                );
+            }
+        }
+        for s in &check.unsupported {
+            let _ = writeln!(
+                out,
+                "  {} [{s}] is not built into this binary (the config fails to load)",
+                color::red("✗")
+            );
+        }
+        for s in &check.ignored {
+            let _ = writeln!(
+                out,
+                "  {} [{s}] is ignored (overridden by exporter.{})",
+                color::yellow("⚠"),
+                check.active.unwrap_or_default()
+            );
+        }
+    }
+    out
+}
+
+/// `features` 子命令入口
+pub fn handle_features(config: Option<&str>, json: bool) -> Result<()> {
+    let report = feature_report(config.map(Path::new))?;
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).unwrap_or_default()
+        );
+    } else {
+        print!("{}", format_report(&report));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feature_report_lists_builtin_exporters() {
+        let report = feature_report(None).unwrap();
+        let names: Vec<_> = report.exporters.iter().map(|c| c.name).collect();
+        assert_eq!(names, EXPORTER_NAMES);
+        assert!(report.exporters[1].detail.starts_with("SQLite 3."));
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["exporters"][0]["section"], "exporter.csv");
+        assert!(json.get("config").is_none());
+        assert!(format_report(&report).contains("[exporter.sqlite]"));
+    }
+
+    #[test]
+    fn test_check_config_reports_ignored_sections() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("c.toml");
+        std::fs::write(
+            &path,
+            "[exporter.postgres]\nurl = \"postgres://x\"\n[exporter.sqlite]\ndatabase_url = \"a.db\"\n",
+        )
+        .unwrap();
+        let check = check_config(&path).unwrap();
+        assert_eq!(check.active, Some("sqlite"));
+        assert_eq!(check.unsupported, ["exporter.postgres"]);
+        assert!(check.ignored.is_empty());
+
+        std::fs::write(&path, "[exporter.csv]\n[exporter.sqlite]\n").unwrap();
+        let check = check_config(&path).unwrap();
+        assert_eq!(check.active, Some("csv"));
+        assert_eq!(check.ignored, ["exporter.sqlite"]);
+
+        std::fs::write(&path, "[exporter.parquet]\n").unwrap();
+        let check = check_config(&path).unwrap();
+        assert_eq!(check.active, None);
+        let text = format_report(&FeatureReport {
+            config: Some(check),
+            ..feature_report(None).unwrap()
+        });
+        assert!(text.contains("no built-in exporter configured"), "{text}");
+        assert!(text.contains("[exporter.parquet] is not built into this binary"));
+    }
+}
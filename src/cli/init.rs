@@ -65,8 +65,25 @@ pub fn handle_init(output_path: &str, force: bool, lang: Lang) -> Result<()> {
 const CONFIG_TEMPLATE_ZH: &str = r#"# SQL 日志导出工具默认配置文件（请根据需要修改）
 
 [sqllog]
-# SQL 日志路径：目录、单文件或 glob 模式（如 "./logs/2025-*.log"）
+# SQL 日志路径：目录、单文件或 glob 模式（如 "./logs/2025-*.log"）；"-" 表示从标准输入读取
 path = "sqllogs"
+# 解析容错级别：lenient（默认，修复带垃圾前缀的记录、容忍缺失 EXECTIME 并标记，溢出的 EXEC_ID 饱和为 i64 最大值）| strict（不合规记录计为错误）
+# parse_mode = "lenient"
+# 按文件名中的日期（如 dmsql_DM_20250115_103028.log）筛选日志文件，含边界，格式 YYYY-MM-DD；
+# 按文件的起始日期判断，跨午夜的文件可能需要把起始日期提前一天
+# file_date_from = "2025-01-15"
+# file_date_to = "2025-01-16"
+# 文件处理顺序：name（默认，按路径）| mtime（最新在前）| size_asc（小文件在前）| size_desc
+# file_order = "name"
+# 仍在被达梦写入的文件（修改时间在 active_file_secs 秒内）：read（默认，照常读取）| skip（跳过并告警）
+# | wait（等待写入停止，最多 active_file_wait_secs 秒）| complete（只读到最后一条完整记录）
+# active_file = "read"
+# active_file_secs = 60
+# active_file_wait_secs = 300
+# 按目录下的 SHA256SUMS 或文件旁的 <文件名>.sha256 校验归档日志，不符的文件其记录计为错误、不导出
+# verify_checksums = true
+# 扫描时排除的文件 / 子目录（glob）：不含 / 的模式匹配任一级名称，含 / 的按相对日志目录的路径匹配
+# ignore = ["*_copy.log", "backup/**"]
 
 [logging]
 # 应用日志文件路径
@@ -76,6 +93,34 @@ level = "info"
 # 日志保留天数 (1-365)
 retention_days = 7
 
+# [features]
+# 追加在基础字段之后的派生列（默认不追加）
+# ts_epoch_ms：ts 对应的 Unix 毫秒时间戳（按 [features.time] input_timezone 解释）
+# client_zone：client_ip 所属网络区域（按 [features.client_zone] 子网表映射）
+# appname_normalized：归一后的应用名（按 [features.appname] 规则映射）
+# owner_team / app_service：username 归属团队 / 应用服务（按 [features.user_owner] 查找文件关联）
+# recovered：经 sqllog.parse_mode = "lenient" 容错修复的记录为 1
+# seq：导出顺序编号，ts 相同的记录可据此还原先后顺序
+# statement_id：SQL 指纹的稳定 ID，与 [features.statements] 字典表关联
+# trace_id / span_id：SQL 注释中的链路上下文（sqlcommenter traceparent 或 trace_id / span_id 标签），与 APM 追踪关联
+# session_id：拼接后的会话标识（EP + sess + 第几次出现），跨午夜轮转的会话保持同一标识
+# run_id：本次运行的标识（与应用日志每行的 run= 一致），多次运行追加到同一输出时可据此区分
+# sql_fingerprint / sql_hash：SQL 指纹（字面量替换为 ?）及其稳定哈希，见 [features.normalize_sql]
+# sql_length / param_count / line_count：SQL 字符数、参数占位符个数、行数（查询治理看板用）
+# sql_params：执行记录绑定的参数值（合并自 PARAMS 记录，启用后 PARAMS 记录不再单独导出）
+# extra_fields = ["ts_epoch_ms"]
+# seq 的编号范围：file（每个文件从 1 开始，默认）| session（每个会话从 1 开始，跨文件延续）
+# seq_scope = "file"
+# 会话拼接的空闲超时（秒）：同一 EP + sess 相邻记录间隔超过该值时视为新会话（会话地址被复用），
+# 未设置时一直延续
+# session_idle_secs = 1800
+
+# 按正则从 SQL 正文（source = "sql"，默认）或 appname 提取自定义列，追加在 extra_fields 之后；
+# 取名为 value 的捕获组，没有时取第一个捕获组；不匹配时为空。可重复配置多条
+# [[features.extract]]
+# name = "trace_id"
+# pattern = 'traceid=(\w+)'
+
 [features.replace_parameters]
 # 是否在导出结果中写入 normalized_sql 列（默认 true）
 # 对 INS/DEL/UPD/ORA 类型的记录，将 PARAMS 参数值填入 SQL 的占位符
@@ -87,11 +132,108 @@ enable = true
 # 默认 false（不影响热循环性能）
 enabled = false
 
+# [features.time]
+# 导出时的 ts 列时区归一化（默认不启用，ts 原样输出）
+# 日志时间所在时区：local（默认）| UTC | 固定偏移如 "+08:00"
+# input_timezone = "+08:00"
+# 输出时区：UTC（默认）| local | 固定偏移
+# output_timezone = "UTC"
+# 输出格式：iso8601（默认）| epoch_ms | original | 自定义 strftime 模式
+# output_format = "iso8601"
+
+# [features.redact]
+# 导出前抹去 SQL 中的明文凭据（IDENTIFIED BY 密码、连接串 PASSWORD=/PWD=、URL 中的 user:password@）
+# 默认 true；仅在确需保留原文时关闭
+# credentials = true
+
+# [features.client_zone]
+# client_zone 派生列的子网表：多个子网命中时取前缀最长的一条
+# subnets = [
+#   { cidr = "10.1.0.0/16", label = "app-tier" },
+#   { cidr = "192.168.10.0/24", label = "office-vpn" },
+# ]
+# 未命中任何子网时的标签（不设置则为空值）
+# default = "external"
+
+# [features.appname]
+# appname_normalized 派生列的归一规则，按顺序匹配、首条命中生效：
+# exact 与去除首尾空白后的 appname 比较（不区分大小写，"" 匹配空值），pattern 为正则
+# rules = [
+#   { exact = "JDBC Thin Client", name = "jdbc" },
+#   { pattern = '(?i)^jdbc', name = "jdbc" },
+#   { exact = "", name = "unknown" },
+# ]
+# 未命中任何规则时的应用名（不设置则保留原值）
+# default = "other"
+
+# [features.session_backfill]
+# 会话内补全：同一 EP + sess 后续记录的 appname / ip 为空时，用该会话最近出现的非空值补上
+# （状态跨文件延续，启用后只走顺序路径；补全在过滤之前生效）
+# enabled = true
+# fields = ["appname", "client_ip"]
+
+# [features.obfuscate_ids]
+# ID 编号：把 sess / thrd（可选 stmt）的内存地址替换为本次运行内稳定的小整数（按 EP + 原值编号，从 1 开始），
+# 输出更短，对外分享时不暴露内存布局（编号跨文件延续，启用后只走顺序路径；过滤仍按原值）
+# enabled = true
+# fields = ["sess_id", "thrd_id"]            # 可加 "statement"
+# dictionary_file = "outputs/id_dictionary.csv"  # 原值与编号对照表（field,ep,original,id），仅供内部反查
+
+# [features.identifier_case]
+# 大小写统一：不同版本 / 实例对同一用户名、语句类型的大小写不一致时统一为 upper / lower（preserve 为默认，保持原样），
+# 按用户分组的统计与导出使用统一后的值（过滤仍按原值，需要时用 (?i) 正则）
+# username = "upper"
+# tag = "upper"
+
+# [features.unmeasured]
+# 无性能指标记录（DDL、工具类语句、PARAMS 等没有 EXECTIME 后缀的记录）的处理方式：
+# include（默认，照常导出，指标列为空）/ exclude（不导出）/ separate（写入 file 指定的 CSV，启用后只走顺序路径）
+# policy = "separate"
+# file = "outputs/sqllog_unmeasured.csv"
+
+# [features.user_owner]
+# owner_team / app_service 派生列的查找文件：.csv（表头含 username,owner_team,app_service）
+# 或 .toml（每个用户一个表，如 [BILL_APP] owner_team = "Finance"）；用户名不区分大小写
+# file = "owners.csv"
+
+# [features.quota]
+# 限制单个用户 / 单个 SQL 指纹导出的记录数，避免批处理账号淹没导出结果（默认不限制）
+# 超出配额的记录计为跳过，运行结束时按用户汇总跳过数
+# max_records_per_user = 100000
+# max_records_per_fingerprint = 10000
+
+# [features.exec_id_audit]
+# 按会话追踪 EXEC_ID 连续性，运行结束时报告缺口（可能丢失的日志行）；启用时不走并行分片路径
+# enabled = true
+# 序列划分：session（EP + 会话，默认）| ep（每个 EP 一条序列）
+# scope = "session"
+
+# [features.dedup]
+# 跳过跨文件重复出现的记录（日志被重写 / 轮转后同一 EXEC_ID 出现在两个文件中），跳过数计入导出统计；
+# 启用时不走并行分片路径
+# enabled = true
+# 判定重复的字段组合，可选 ts / ep / sess_id / thrd_id / username / trx_id / statement / appname / client_ip / sql / exec_id
+# key = ["exec_id", "ts", "sess_id"]
+# 去重哈希表内存上限（MiB），只与最近约 max_memory_mb × 6.5 万条记录比较
+# max_memory_mb = 64
+
+# [features.statements]
+# 按 SQL 指纹维护语句登记表（首次 / 末次出现时间与次数），启用时不走并行分片路径；
+# 配合 extra_fields = ["statement_id"] 可与导出记录关联查询
+# enabled = true
+# 字典表输出路径（CSV）；不设置时只在运行摘要中报告语句数
+# file = "outputs/statements.csv"
+
+# [features.normalize_sql]
+# 追加 sql_fingerprint（字面量替换为 ?）与 sql_hash（指纹的 16 位十六进制哈希，跨运行稳定）两列，
+# 可在 DuckDB / PostgreSQL 中按 sql_hash GROUP BY 找出热点语句；PARAMS 记录为空
+# enabled = true
+
 [features.filters]
 # 是否启用过滤器
 enable = false
 
-# --- 元数据过滤器（Record-level：满足任一条件即保留该条记录）---
+# --- 元数据过滤器（Record-level：已配置的字段须全部命中，字段内任一匹配即可；exclude_* 任一命中即丢弃）---
 # 过滤指定的事务 ID
 # trxids = ["257809109", "257809110"]
 
@@ -105,7 +247,8 @@ enable = false
 # 排除指定的用户名（OR veto：任一命中则丢弃该记录）
 # exclude_usernames = ["guest", "^anon"]
 
-# 过滤时间范围（格式：2023-01-01 00:00:00）
+# 过滤时间范围（含两端，可写作 start_time / end_time；日志时间戳的前缀，
+# 如 "2023-01-01"、"2023-01-01 10:30"；终点按前缀包含，"10:30" 包含这一整分钟）
 # start_ts = "2023-01-01 00:00:00"
 # end_ts   = "2023-01-01 23:59:59"
 
@@ -119,20 +262,20 @@ enable = false
 # 排除指定的线程 ID（OR veto：任一命中则丢弃该记录）
 # exclude_thrd_ids = ["^0$"]
 
-# 过滤指定的语句类型（支持正则匹配）
-# statements = ["INS", "UPD", "DEL"]
-# 排除指定的语句类型（OR veto：任一命中则丢弃该记录）
-# exclude_statements = ["SEL", "SET"]
+# 过滤指定的语句句柄（stmt:0x...，支持正则匹配；按语句类型过滤请用 tags）
+# statements = ["0x7f41435437a8"]
+# 排除指定的语句句柄（OR veto：任一命中则丢弃该记录）
+# exclude_statements = ["^0x0$"]
 
 # 过滤指定的应用名称（支持正则匹配）
 # appnames = ["DMSQL"]
 # 排除指定的应用名称（OR veto：任一命中则丢弃该记录）
 # exclude_appnames = ["monitor", "health"]
 
-# 过滤指定的 tag（支持正则匹配）
-# tags = ["\\[SEL\\]"]
-# 排除指定的 tag（OR veto：任一命中则丢弃该记录）
-# exclude_tags = ["\\[SET\\]", "\\[OTH\\]"]
+# 过滤指定的语句类型 tag（日志中 [SEL] 的 SEL 部分，支持正则匹配）
+# tags = ["^(INS|UPD|DEL)$"]
+# 排除指定的语句类型 tag（OR veto：任一命中则丢弃该记录）
+# exclude_tags = ["^SET$", "^OTH$"]
 
 # --- 指标过滤器（Transaction-level：满足条件则保留包含该语句的整个事务，需要预扫描）---
 [features.filters.indicators]
@@ -155,14 +298,72 @@ enable = false
 # [resume]
 # state_file = ".sqllog2db_state.toml"
 
+# ===================== 处理管线 =====================
+# 每导出 chunk_size 条记录，导出器把已写数据交给下游（CSV 刷新缓冲、SQLite 提交并释放页缓存），
+# 处理数十 GB 的日志目录时内存占用保持稳定
+# 多个日志文件由 workers 个线程并发解析（默认 CPU 核数，命令行 -j/--jobs 优先）：
+# CSV 各文件写分片后按顺序拼接；SQLite 仅在显式设置 workers / --jobs 时经有界队列汇入单个写线程
+# （不同文件的记录交错），未设置时按文件顺序写入
+# [pipeline]
+# chunk_size = 100000
+# workers = 8
+
+# ===================== 运行通知 =====================
+# 运行结束后触发 webhook / 邮件 / 命令，成功与失败分别配置；通知失败只输出警告
+# [notify]
+# timeout_secs = 10
+# [notify.on_success]
+# webhook = "https://hooks.example.com/sqllog2db"
+# [notify.on_failure]
+# command = "logger -t sqllog2db \"[$SQLLOG2DB_RUN_ID] $SQLLOG2DB_STATUS: $SQLLOG2DB_ERROR\""
+# [notify.on_failure.email]
+# smtp_host = "smtp.example.com"
+# username = "alert@example.com"
+# password_env = "SQLLOG2DB_SMTP_PASSWORD"
+# from = "alert@example.com"
+# to = ["dba@example.com"]
+
+# ===================== 错误预算 =====================
+# 解析错误率（解析失败记录 / 全部记录，百分比）超过该值时运行以退出码 5 结束，通知与运行报告记为失败
+# [error]
+# max_parse_error_rate = 5.0
+
+# ===================== 运行报告 =====================
+# 运行结束（含失败与中断）时写出 JSON 报告：逐文件计数、解析错误、各导出器统计、各阶段耗时
+# [report]
+# file = "export/run_report.json"
+
 # ===================== 导出器配置 =====================
-# 只能配置一个导出器，同时配置多个时按优先级使用：csv > sqlite
+# 只能配置一个导出器，同时配置多个时按优先级使用：csv > sqlite > elasticsearch
 
 # 方案 1：CSV 导出（默认）
 [exporter.csv]
+# 输出文件；"-" 表示写到标准输出（进度与提示自动静默，便于接管道）；
+# "tcp://host:port" 连接到远端并以流写出（编码仍由 dialect 决定）
 file = "outputs/sqllog.csv"
 overwrite = true
 append = false
+# 输出方言：standard（默认）/ dmfldr（兼容达梦 dmfldr：字段不加引号，值内换行与分隔符替换为空格）
+#          / tsv（兼容 clickhouse-client --format TSV：制表符分隔，NULL 写为 \N，无 header）
+# dialect = "standard"
+# field_terminator = "|"    # 仅 dmfldr
+# row_terminator = "\n"     # 仅 dmfldr，须以换行结尾
+# 按用户名 / SQL 中的 schema 拆分为多个文件（sqllog.<分片>.csv）：username / schema
+# split_by = "username"
+# 输出压缩：gzip / zstd（文件名按原样使用，建议配合 .csv.gz / .csv.zst）
+# compression = "zstd"
+# compression_level = 1     # gzip 为 0-9（默认 6）；zstd 仅支持 1，其他级别校验时报错
+# 按大小 / 记录数分段输出（sqllog.csv.part-0001、part-0002……），任一上限达到即切换；不可与 split_by / 时间模式同用
+# max_file_size = "2GB"
+# max_records = 10000000
+# exec_time_ms 列格式：integer（默认，整数毫秒）/ decimal（保留 3 位小数）；小数点固定为 "."，不受系统区域设置影响
+# exectime_format = "decimal"
+# 写入失败时的处理：abort（默认，中止导出）/ skip_record 或 skip（丢弃失败的记录，继续导出）
+# / log（同 skip，另把失败记录原文写入应用日志）/ skip_batch（丢弃当前未提交的批次，从下一批继续）；
+# 跳过数计入完成摘要与运行警告，各导出器分别配置
+# on_error = "skip_record"
+# 输出列顺序（可含 features.extra_fields 派生列）；未列出的列按默认顺序接在其后
+# column_order = ["ts", "username", "exec_time_ms", "sql"]
 
 # 方案 2：SQLite 数据库导出
 # [exporter.sqlite]
@@ -170,13 +371,76 @@ append = false
 # table_name = "sqllog_records"
 # overwrite = true
 # append = false
+# 追加模式下检测同日数据是否已装载：off / warn（默认）/ abort
+# duplicate_check = "warn"
+# 自定义检测 SQL（首列返回行数），可用 :day（YYYY-MM-DD）与 :first_ts
+# duplicate_check_query = "SELECT COUNT(*) FROM sqllog_records WHERE ts >= :day AND ts < :day || '~'"
+# 按用户名 / schema 拆分为多张表（sqllog_records_<分片>）
+# split_by = "username"
+# 装载前后在目标库上执行的 SQL：pre_sql 在建表后、写入前执行，post_sql 在写入提交后执行
+# pre_sql = ["DROP INDEX IF EXISTS idx_sqllog_ts"]
+# post_sql = ["CREATE INDEX IF NOT EXISTS idx_sqllog_ts ON sqllog_records(ts)", "ANALYZE"]
+# 装载完成后创建友好视图：列重命名、executed_at_epoch_ms、duration_sec（秒）、statement_type（解码后的语句类型）
+# view_name = "sqllog"
+# 装载结束后在目标库 sqllog2db_loads 表记录本次装载：来源文件（路径、字节数、SHA-256）、记录数、错误数、版本、耗时
+# load_metadata = true
+# 追加 / 清空模式下目标表已存在但缺少本次导出的列（如旧版本建的表没有 exec_id）时 ALTER TABLE 补齐，
+# 未开启时报错并列出缺少的列（run --migrate 等同于开启）
+# migrate = true
+# 分块提交失败时回滚该批次并继续（见 exporter.csv 的 on_error）
+# on_error = "skip_batch"
+
+# 方案 3：Elasticsearch / OpenSearch 批量索引（_bulk API）
+# [exporter.elasticsearch]
+# url = "http://localhost:9200"
+# 索引名；含 strftime 说明符时按记录时间写入对应索引（如每天一个索引）
+# index = "sqllog-%Y.%m.%d"
+# 每个 _bulk 请求的文档数
+# batch_size = 5000
+# 认证：username + password（或 password_env 指定的环境变量），或 api_key
+# （任何配置项也可由环境变量覆盖，如 SQLLOG2DB_EXPORTER__ELASTICSEARCH__PASSWORD）
+# username = "elastic"
+# password_env = "ES_PASSWORD"
+# api_key = "..."
+# timeout_secs = 30
+# 磁盘溢出队列：集群暂时不可用（连接失败、超时、429 / 5xx）时批次写入该目录，恢复后或下次启动时按顺序补发；
+# 总大小超过 spill_max_mb（MiB）后中止导出。适合 run --watch 长期运行
+# spill_dir = "export/es-spill"
+# spill_max_mb = 1024
+# _bulk 请求失败或有文档被拒绝时丢弃该批次并继续（见 exporter.csv 的 on_error）
+# on_error = "skip_batch"
+
+# 方案 4：第三方导出器（仅在以库方式嵌入、并用 exporter::registry::register 注册后可用）
+# [exporter.custom]
+# name = "kafka"
+# 其余键原样交给注册的导出器工厂
+# topic = "sqllog"
 "#;
 
 const CONFIG_TEMPLATE_EN: &str = r#"# sqllog2db default configuration file (edit as needed)
 
 [sqllog]
-# SQL log path: directory, single file, or glob pattern (e.g. "./logs/2025-*.log")
+# SQL log path: directory, single file, or glob pattern (e.g. "./logs/2025-*.log"); "-" reads from stdin
 path = "sqllogs"
+# Parser tolerance: lenient (default; recovers garbage-prefixed records, tolerates missing EXECTIME, tags them, saturates overflowing EXEC_ID) | strict (nonconforming records count as errors)
+# parse_mode = "lenient"
+# Select log files by the date in their file name (e.g. dmsql_DM_20250115_103028.log), inclusive, YYYY-MM-DD;
+# this is the file's start date, so files spanning midnight may need a from date one day earlier
+# file_date_from = "2025-01-15"
+# file_date_to = "2025-01-16"
+# File processing order: name (default, by path) | mtime (newest first) | size_asc (smallest first) | size_desc
+# file_order = "name"
+# Files still being written by DM (modified within active_file_secs): read (default) | skip (warn and skip)
+# | wait (until writes stop, at most active_file_wait_secs) | complete (read up to the last complete record)
+# active_file = "read"
+# active_file_secs = 60
+# active_file_wait_secs = 300
+# Verify archived logs against SHA256SUMS in the directory or a <file>.sha256 sidecar; records from
+# mismatching files are counted as errors and not exported
+# verify_checksums = true
+# Files / subfolders to leave out of the scan (glob): patterns without / match any path component name,
+# patterns with / match the path relative to the log directory
+# ignore = ["*_copy.log", "backup/**"]
 
 [logging]
 # Application log file path
@@ -186,6 +450,35 @@ level = "info"
 # Log retention in days (1-365)
 retention_days = 7
 
+# [features]
+# Derived columns appended after the base fields (none by default)
+# ts_epoch_ms: Unix epoch milliseconds of ts (interpreted in [features.time] input_timezone)
+# client_zone: network zone of client_ip (mapped via the [features.client_zone] subnet table)
+# appname_normalized: normalized application name (mapped via the [features.appname] rules)
+# owner_team / app_service: owning team / application of username (joined from the [features.user_owner] lookup file)
+# recovered: 1 for records repaired by sqllog.parse_mode = "lenient"
+# seq: export order number, restores ordering among records sharing the same ts
+# statement_id: stable ID of the SQL fingerprint, joins against the [features.statements] dictionary
+# trace_id / span_id: trace context from SQL comments (sqlcommenter traceparent or trace_id / span_id tags), joins with APM traces
+# session_id: stitched session key (EP + sess + occurrence), stable across the midnight file rollover
+# run_id: identifier of this run (matches run= in every app log line), tells apart runs appending to one output
+# sql_fingerprint / sql_hash: SQL fingerprint (literals replaced with ?) and its stable hash, see [features.normalize_sql]
+# sql_length / param_count / line_count: SQL character count, placeholder count and line count (for query governance)
+# sql_params: bound parameter values of the statement (merged from PARAMS records, which are then no longer exported)
+# extra_fields = ["ts_epoch_ms"]
+# Numbering scope for seq: file (restarts at 1 per file, default) | session (per session, continues across files)
+# seq_scope = "file"
+# Idle timeout (seconds) for session stitching: a gap longer than this between records of the same
+# EP + sess starts a new session (the session address was reused); unset means sessions never expire
+# session_idle_secs = 1800
+
+# Extract custom columns from the SQL body (source = "sql", default) or appname with a regex, appended
+# after extra_fields; takes the capture group named value, else the first group; empty when unmatched.
+# Repeat the table for more rules
+# [[features.extract]]
+# name = "trace_id"
+# pattern = 'traceid=(\w+)'
+
 [features.replace_parameters]
 # Write a normalized_sql column in export output (default: true).
 # For INS/DEL/UPD/ORA records, parameter values are substituted into SQL placeholders.
@@ -197,11 +490,111 @@ enable = true
 # Default false (zero overhead in hot loop when disabled)
 enabled = false
 
+# [features.time]
+# Normalize the ts column's timezone at export time (disabled by default; ts is written as-is)
+# Timezone of log timestamps: local (default) | UTC | fixed offset such as "+08:00"
+# input_timezone = "+08:00"
+# Output timezone: UTC (default) | local | fixed offset
+# output_timezone = "UTC"
+# Output format: iso8601 (default) | epoch_ms | original | custom strftime pattern
+# output_format = "iso8601"
+
+# [features.redact]
+# Redact plaintext credentials in SQL before export (IDENTIFIED BY passwords, PASSWORD=/PWD= in connection strings, user:password@ in URLs)
+# Default true; only disable when the original text is really needed
+# credentials = true
+
+# [features.client_zone]
+# Subnet table for the client_zone derived column; the longest matching prefix wins
+# subnets = [
+#   { cidr = "10.1.0.0/16", label = "app-tier" },
+#   { cidr = "192.168.10.0/24", label = "office-vpn" },
+# ]
+# Label for IPs outside every subnet (NULL when unset)
+# default = "external"
+
+# [features.appname]
+# Normalization rules for the appname_normalized derived column, matched in order (first hit wins):
+# exact compares against the trimmed appname (case-insensitive, "" matches empty), pattern is a regex
+# rules = [
+#   { exact = "JDBC Thin Client", name = "jdbc" },
+#   { pattern = '(?i)^jdbc', name = "jdbc" },
+#   { exact = "", name = "unknown" },
+# ]
+# Name for appnames matching no rule (the original value is kept when unset)
+# default = "other"
+
+# [features.session_backfill]
+# Session backfill: fill empty appname / ip on later records of the same EP + sess with the session's latest
+# non-empty value (state carries across files, so the run stays sequential; applied before filtering)
+# enabled = true
+# fields = ["appname", "client_ip"]
+
+# [features.obfuscate_ids]
+# ID obfuscation: replace the sess / thrd (optionally stmt) memory addresses with small integers that are stable
+# within the run (numbered per EP + original value, starting at 1); shorter output that does not leak memory
+# layout when shared (numbering carries across files, so the run stays sequential; filters still see raw values)
+# enabled = true
+# fields = ["sess_id", "thrd_id"]            # "statement" is also accepted
+# dictionary_file = "outputs/id_dictionary.csv"  # original-to-id mapping (field,ep,original,id), for internal lookup only
+
+# [features.identifier_case]
+# Case normalization: when versions / instances log the same user name or statement type in different case,
+# fold them to upper / lower (preserve is the default); per-user grouping and exports use the folded value
+# (filters still see raw values; use a (?i) regex when needed)
+# username = "upper"
+# tag = "upper"
+
+# [features.unmeasured]
+# Records without performance indicators (DDL, utility statements, PARAMS records lacking the EXECTIME suffix):
+# include (default, exported with empty metric columns) / exclude (dropped) / separate (written to the CSV
+# given in file; the run stays sequential)
+# policy = "separate"
+# file = "outputs/sqllog_unmeasured.csv"
+
+# [features.user_owner]
+# Lookup file for the owner_team / app_service derived columns: .csv (header with username,owner_team,app_service)
+# or .toml (one table per user, e.g. [BILL_APP] owner_team = "Finance"); usernames are case-insensitive
+# file = "owners.csv"
+
+# [features.quota]
+# Cap exported records per user / per SQL fingerprint so noisy batch accounts don't dominate (unlimited by default)
+# Records over quota are counted as skipped; per-user skip counts are shown in the run summary
+# max_records_per_user = 100000
+# max_records_per_fingerprint = 10000
+
+# [features.exec_id_audit]
+# Track EXEC_ID continuity and report gaps (possibly dropped log lines) in the run summary; disables parallel CSV export
+# enabled = true
+# Sequence scope: session (EP + session, default) | ep (one sequence per EP)
+# scope = "session"
+
+# [features.dedup]
+# Skip records repeated across files (the same EXEC_ID in two files after DM rewrites / rolls a log);
+# the skip count is reported in the export stats. Disables parallel CSV export
+# enabled = true
+# Fields that identify a duplicate: ts / ep / sess_id / thrd_id / username / trx_id / statement / appname / client_ip / sql / exec_id
+# key = ["exec_id", "ts", "sess_id"]
+# Memory cap for the dedup hash set (MiB); records are compared with roughly the last max_memory_mb × 65k records
+# max_memory_mb = 64
+
+# [features.statements]
+# Registry of statements keyed by SQL fingerprint (first / last seen, counts); disables parallel CSV export.
+# Join it against exported rows via extra_fields = ["statement_id"]
+# enabled = true
+# Dictionary table output (CSV); when unset only the statement count is reported in the run summary
+# file = "outputs/statements.csv"
+
+# [features.normalize_sql]
+# Append sql_fingerprint (literals replaced with ?) and sql_hash (16-hex-digit hash of the fingerprint,
+# stable across runs); GROUP BY sql_hash in DuckDB / PostgreSQL to find hot statements. Empty for PARAMS records
+# enabled = true
+
 [features.filters]
 # Enable the filter pipeline
 enable = false
 
-# --- Meta filters (record-level: any match retains the record) ---
+# --- Meta filters (record-level: every configured field must match, any pattern within a field; any exclude_* match drops) ---
 # Filter by transaction IDs
 # trxids = ["257809109", "257809110"]
 
@@ -215,7 +608,9 @@ enable = false
 # Exclude by usernames (OR veto: any match drops the record)
 # exclude_usernames = ["guest", "^anon"]
 
-# Filter by time range (format: 2023-01-01 00:00:00)
+# Filter by time range (inclusive; also spelled start_time / end_time). Values are
+# prefixes of the log timestamp, e.g. "2023-01-01" or "2023-01-01 10:30"; the end
+# bound matches by prefix, so "10:30" covers that whole minute
 # start_ts = "2023-01-01 00:00:00"
 # end_ts   = "2023-01-01 23:59:59"
 
@@ -229,20 +624,20 @@ enable = false
 # Exclude by thread IDs (OR veto: any match drops the record)
 # exclude_thrd_ids = ["^0$"]
 
-# Filter by statement types (regex match)
-# statements = ["INS", "UPD", "DEL"]
-# Exclude by statement types (OR veto: any match drops the record)
-# exclude_statements = ["SEL", "SET"]
+# Filter by statement handles (stmt:0x..., regex match; use tags for statement types)
+# statements = ["0x7f41435437a8"]
+# Exclude by statement handles (OR veto: any match drops the record)
+# exclude_statements = ["^0x0$"]
 
 # Filter by application names (regex match)
 # appnames = ["DMSQL"]
 # Exclude by application names (OR veto: any match drops the record)
 # exclude_appnames = ["monitor", "health"]
 
-# Filter by tags (regex match)
-# tags = ["\\[SEL\\]"]
-# Exclude by tags (OR veto: any match drops the record)
-# exclude_tags = ["\\[SET\\]", "\\[OTH\\]"]
+# Filter by statement type tags (SEL for a [SEL] record, regex match)
+# tags = ["^(INS|UPD|DEL)$"]
+# Exclude by statement type tags (OR veto: any match drops the record)
+# exclude_tags = ["^SET$", "^OTH$"]
 
 # --- Indicator filters (transaction-level: match retains the whole transaction; requires pre-scan) ---
 [features.filters.indicators]
@@ -266,14 +661,79 @@ enable = false
 # [resume]
 # state_file = ".sqllog2db_state.toml"
 
+# ===================== Pipeline =====================
+# Every chunk_size exported records the exporter hands written data downstream (CSV flushes its
+# buffer, SQLite commits and releases its page cache), keeping memory flat on tens of GB of logs
+# Log files are parsed concurrently by `workers` threads (default: CPU count, -j/--jobs wins):
+# CSV writes per-file parts concatenated in order; SQLite funnels through a bounded queue to one writer
+# only when workers / --jobs is set explicitly (rows from different files interleave), else keeps file order
+# [pipeline]
+# chunk_size = 100000
+# workers = 8
+
+# ===================== Run Notifications =====================
+# Webhook / email / command hooks at run completion, configured separately for
+# success and failure; notification errors are reported as warnings only
+# [notify]
+# timeout_secs = 10
+# [notify.on_success]
+# webhook = "https://hooks.example.com/sqllog2db"
+# [notify.on_failure]
+# command = "logger -t sqllog2db \"[$SQLLOG2DB_RUN_ID] $SQLLOG2DB_STATUS: $SQLLOG2DB_ERROR\""
+# [notify.on_failure.email]
+# smtp_host = "smtp.example.com"
+# username = "alert@example.com"
+# password_env = "SQLLOG2DB_SMTP_PASSWORD"
+# from = "alert@example.com"
+# to = ["dba@example.com"]
+
+# ===================== Error Budget =====================
+# Runs whose parse error rate (failed records / all records, in percent)
+# exceeds this value exit with code 5 and are reported as failed
+# [error]
+# max_parse_error_rate = 5.0
+
+# ===================== Run Report =====================
+# JSON report written at completion (also on failure / interruption): per-file
+# counts, parse errors, per-exporter stats and per-stage durations
+# [report]
+# file = "export/run_report.json"
+
 # ===================== Exporter Configuration =====================
-# Only one exporter can be active at a time. Priority: csv > sqlite
+# Only one exporter can be active at a time. Priority: csv > sqlite > elasticsearch
 
 # Option 1: CSV export (default)
 [exporter.csv]
+# Output file; "-" writes to stdout (progress and console messages are suppressed for piping);
+# "tcp://host:port" streams to a remote listener (encoding still follows dialect)
 file = "outputs/sqllog.csv"
 overwrite = true
 append = false
+# Output dialect: standard (default) / dmfldr (DM dmfldr compatible: unquoted fields,
+# embedded line breaks and delimiters replaced with spaces) / tsv (clickhouse-client --format TSV:
+# tab-separated, escaped control chars, NULL as \N, no header row)
+# dialect = "standard"
+# field_terminator = "|"    # dmfldr only
+# row_terminator = "\n"     # dmfldr only, must end with a newline
+# Split into one file per user / per schema referenced in the SQL (sqllog.<partition>.csv)
+# split_by = "username"
+# Output compression: gzip / zstd (the file name is used as-is; pair with .csv.gz / .csv.zst)
+# compression = "zstd"
+# compression_level = 1     # gzip: 0-9 (default 6); zstd: only 1, other levels are rejected
+# Split the output into numbered parts (sqllog.csv.part-0001, part-0002, ...) once either limit is reached;
+# cannot be combined with split_by or a time-pattern file name
+# max_file_size = "2GB"
+# max_records = 10000000
+# exec_time_ms format: integer (default, whole milliseconds) / decimal (3 fractional digits);
+# the decimal separator is always "." regardless of the system locale
+# exectime_format = "decimal"
+# On write failure: abort (default, stop the export) / skip_record or skip (drop the failed record and continue)
+# / log (like skip, and also write the raw failed record to the application log)
+# / skip_batch (drop the current uncommitted batch and resume with the next); skipped counts appear in
+# the summary and run warnings; configured per exporter
+# on_error = "skip_record"
+# Output column order (may include features.extra_fields columns); unlisted columns follow in default order
+# column_order = ["ts", "username", "exec_time_ms", "sql"]
 
 # Option 2: SQLite database export
 # [exporter.sqlite]
@@ -281,4 +741,52 @@ append = false
 # table_name = "sqllog_records"
 # overwrite = true
 # append = false
+# In append mode, detect whether the same day was already loaded: off / warn (default) / abort
+# duplicate_check = "warn"
+# Custom detection query (first column = existing row count); binds :day (YYYY-MM-DD) and :first_ts
+# duplicate_check_query = "SELECT COUNT(*) FROM sqllog_records WHERE ts >= :day AND ts < :day || '~'"
+# Split into one table per user / schema (sqllog_records_<partition>)
+# split_by = "username"
+# SQL run on the target database around the load: pre_sql after table creation and before inserts,
+# post_sql after the final commit
+# pre_sql = ["DROP INDEX IF EXISTS idx_sqllog_ts"]
+# post_sql = ["CREATE INDEX IF NOT EXISTS idx_sqllog_ts ON sqllog_records(ts)", "ANALYZE"]
+# Create a friendly view after the load: renamed columns, executed_at_epoch_ms, duration_sec (seconds),
+# statement_type (decoded statement tag)
+# view_name = "sqllog"
+# Record each load in a sqllog2db_loads table in the target database: source files (path, bytes, SHA-256),
+# record and error counts, tool version and duration
+# load_metadata = true
+# When the existing target table lacks columns this export writes (e.g. created by an older
+# version without exec_id), add them with ALTER TABLE instead of failing (same as run --migrate)
+# migrate = true
+# Roll back a batch whose commit fails and continue (see on_error under exporter.csv)
+# on_error = "skip_batch"
+
+# Option 3: Elasticsearch / OpenSearch bulk indexing (_bulk API)
+# [exporter.elasticsearch]
+# url = "http://localhost:9200"
+# Index name; strftime specifiers pick the index from each record's timestamp (e.g. one index per day)
+# index = "sqllog-%Y.%m.%d"
+# Documents per _bulk request
+# batch_size = 5000
+# Authentication: username + password (or the environment variable named by password_env), or api_key
+# (any config key can also be overridden from the environment, e.g. SQLLOG2DB_EXPORTER__ELASTICSEARCH__PASSWORD)
+# username = "elastic"
+# password_env = "ES_PASSWORD"
+# api_key = "..."
+# timeout_secs = 30
+# Disk spill queue: while the cluster is unavailable (connection failure, timeout, 429 / 5xx), batches are
+# written to this directory and resent in order once it recovers or on the next start; the export aborts
+# once the queue exceeds spill_max_mb (MiB). Useful for long-running run --watch
+# spill_dir = "export/es-spill"
+# spill_max_mb = 1024
+# Drop a batch whose _bulk request fails or has rejected documents and continue (see on_error under exporter.csv)
+# on_error = "skip_batch"
+
+# Option 4: third-party exporter (only when embedding the library and registering it via exporter::registry::register)
+# [exporter.custom]
+# name = "kafka"
+# Remaining keys are passed as-is to the registered exporter factory
+# topic = "sqllog"
 "#;
@@ -1,9 +1,24 @@
+pub mod bench;
+pub mod capacity;
+pub mod clean_temp;
+pub mod diff;
 pub mod digest;
+pub mod doctor;
+pub mod features;
 pub mod init;
 pub mod opts;
 pub mod preflight;
+pub mod preview;
+pub mod report;
 pub mod run;
+pub mod run_all;
+pub mod sample;
+pub mod schema;
 pub mod show_config;
+pub mod stages;
 pub mod stats;
+pub mod top;
+pub mod tune;
 pub mod update;
 pub mod validate;
+pub mod watch;
@@ -48,23 +48,29 @@ pub enum Commands {
         )]
         config: String,
         /// Stop after processing N records (across all files)
-        #[arg(short = 'n', long = "limit")]
+        #[arg(short = 'n', long = "limit", visible_alias = "max-records")]
         limit: Option<usize>,
+        /// Stop cleanly after this much wall time, e.g. 90s, 10m, 2h (plain numbers are seconds)
+        #[arg(long = "max-duration", value_name = "DURATION", value_parser = parse_duration)]
+        max_duration: Option<std::time::Duration>,
         /// Parse and count records without writing output
         #[arg(long = "dry-run")]
         dry_run: bool,
         /// Override config values, e.g. --set exporter.csv.file=out.csv
         #[arg(long = "set", value_name = "KEY=VALUE")]
         set: Vec<String>,
-        /// Keep only records at or after this timestamp (requires filters feature)
-        #[arg(long = "from", value_name = "DATETIME")]
+        /// Keep only records at or after this timestamp, e.g. "2025-01-15 10:30"
+        #[arg(long = "from", visible_alias = "since", value_name = "DATETIME")]
         from: Option<String>,
-        /// Keep only records at or before this timestamp (requires filters feature)
-        #[arg(long = "to", value_name = "DATETIME")]
+        /// Keep only records at or before this timestamp (prefix match, inclusive)
+        #[arg(long = "to", visible_alias = "until", value_name = "DATETIME")]
         to: Option<String>,
         /// Write CSV output to this file (shorthand for `--set exporter.csv.file=<FILE>`)
         #[arg(short = 'o', long = "output", value_name = "FILE")]
         output: Option<String>,
+        /// Read log content from stdin (shorthand for `--set sqllog.path=-`)
+        #[arg(long = "stdin", conflicts_with = "resume")]
+        stdin: bool,
         /// Progress bar refresh interval in milliseconds
         #[arg(long = "progress-interval", default_value = "80", value_name = "MS")]
         progress_interval: u64,
@@ -74,9 +80,104 @@ pub enum Commands {
         /// Override the state file path used by --resume (default: `.sqllog2db_state.toml`)
         #[arg(long = "state-file", value_name = "PATH", requires = "resume")]
         state_file: Option<String>,
-        /// Number of parallel threads for processing multiple files (default: CPU count)
+        /// Number of parallel threads for processing multiple files (default: `pipeline.workers`, else CPU count)
         #[arg(short = 'j', long = "jobs", value_name = "N")]
         jobs: Option<usize>,
+        /// Measure time per pipeline stage (read, parse, filter, transform, write) and print a breakdown
+        #[arg(long = "profile-stages")]
+        profile_stages: bool,
+        /// Write final run counters to a Prometheus textfile (for the `node_exporter` textfile collector)
+        #[arg(long = "metrics-file", value_name = "PATH")]
+        metrics_file: Option<String>,
+        /// Write a JUnit-style XML report (one testcase per input file) for CI systems
+        #[arg(long = "report-junit", value_name = "PATH")]
+        report_junit: Option<String>,
+        /// Parse errors a file may have before its testcase fails in --report-junit
+        #[arg(
+            long = "junit-max-errors",
+            value_name = "N",
+            default_value = "0",
+            requires = "report_junit"
+        )]
+        junit_max_errors: u64,
+        /// Add columns missing from an existing `SQLite` target table instead of failing
+        /// (shorthand for `--set exporter.sqlite.migrate=true`)
+        #[arg(long = "migrate")]
+        migrate: bool,
+        /// Calibrate thread count, chunk size and `SQLite` batch size on a sample of the input first,
+        /// then run with the fastest values (--jobs becomes the upper bound)
+        #[arg(long = "auto-tune", conflicts_with_all = ["dry_run", "stdin"])]
+        auto_tune: bool,
+        /// Keep running: poll the log directory and export records appended to the log files
+        /// until Ctrl+C (or --max-duration)
+        #[arg(
+            long = "watch",
+            visible_alias = "follow",
+            conflicts_with_all = ["dry_run", "stdin", "resume", "limit", "auto_tune"]
+        )]
+        watch: bool,
+        /// Poll interval for --watch, e.g. 5s, 1m (plain numbers are seconds)
+        #[arg(
+            long = "watch-interval",
+            value_name = "DURATION",
+            default_value = "2",
+            value_parser = parse_duration,
+            requires = "watch"
+        )]
+        watch_interval: std::time::Duration,
+    },
+    /// Run the export task, then render a custom report from a minijinja template
+    Report {
+        /// Configuration file path
+        #[arg(
+            short = 'c',
+            long = "config",
+            default_value = "config.toml",
+            env = "SQLLOG2DB_CONFIG"
+        )]
+        config: String,
+        /// Override config values, e.g. --set exporter.csv.file=out.csv
+        #[arg(long = "set", value_name = "KEY=VALUE")]
+        set: Vec<String>,
+        /// Keep only records at or after this timestamp, e.g. "2025-01-15 10:30"
+        #[arg(long = "from", visible_alias = "since", value_name = "DATETIME")]
+        from: Option<String>,
+        /// Keep only records at or before this timestamp (prefix match, inclusive)
+        #[arg(long = "to", visible_alias = "until", value_name = "DATETIME")]
+        to: Option<String>,
+        /// Parse and count records without writing export output
+        #[arg(long = "dry-run")]
+        dry_run: bool,
+        /// Number of parallel threads for processing multiple files (default: `pipeline.workers`, else CPU count)
+        #[arg(short = 'j', long = "jobs", value_name = "N")]
+        jobs: Option<usize>,
+        /// Report template file (minijinja syntax)
+        #[arg(short = 't', long = "template", value_name = "FILE")]
+        template: String,
+        /// Write the rendered report to this file (default: stdout)
+        #[arg(short = 'o', long = "output", value_name = "FILE")]
+        output: Option<String>,
+    },
+    /// Run several export jobs (one config file each) and print a combined summary
+    RunAll {
+        /// Configuration files or glob patterns, e.g. configs/*.toml
+        #[arg(required = true, value_name = "CONFIG")]
+        configs: Vec<String>,
+        /// Run up to N jobs concurrently (default: 1, sequential)
+        #[arg(short = 'p', long = "parallel", default_value = "1", value_name = "N")]
+        parallel: usize,
+        /// Threads per job for multi-file processing (default: CPU count / parallel)
+        #[arg(short = 'j', long = "jobs", value_name = "N")]
+        jobs: Option<usize>,
+        /// Parse and count records without writing output
+        #[arg(long = "dry-run")]
+        dry_run: bool,
+        /// Stop starting new jobs after the first failure
+        #[arg(long = "fail-fast")]
+        fail_fast: bool,
+        /// Override config values in every job, e.g. --set features.filters.enable=false
+        #[arg(long = "set", value_name = "KEY=VALUE")]
+        set: Vec<String>,
     },
     /// Generate a default configuration file
     Init {
@@ -100,6 +201,9 @@ pub enum Commands {
         /// Override config values, e.g. --set sqllog.path=./logs
         #[arg(long = "set", value_name = "KEY=VALUE")]
         set: Vec<String>,
+        /// Also connect to the export target (round-trip query on `SQLite`, cluster root on Elasticsearch)
+        #[arg(long = "check-connectivity")]
+        check_connectivity: bool,
     },
     /// Show effective configuration (after loading and any --set overrides)
     ShowConfig {
@@ -118,6 +222,75 @@ pub enum Commands {
         #[arg(long = "diff")]
         diff: bool,
     },
+    /// Print the CREATE TABLE DDL the exporter would execute (for review before loading)
+    Schema {
+        /// Configuration file path
+        #[arg(
+            short = 'c',
+            long = "config",
+            default_value = "config.toml",
+            env = "SQLLOG2DB_CONFIG"
+        )]
+        config: String,
+        /// Override config values, e.g. --set sqllog.path=./logs
+        #[arg(long = "set", value_name = "KEY=VALUE")]
+        set: Vec<String>,
+        /// Target exporter (default: sqlite)
+        #[arg(long = "exporter", value_name = "NAME")]
+        exporter: Option<String>,
+    },
+    /// Measure exporter throughput on synthetic records (output goes to a temporary directory)
+    Bench {
+        /// Configuration file path
+        #[arg(
+            short = 'c',
+            long = "config",
+            default_value = "config.toml",
+            env = "SQLLOG2DB_CONFIG"
+        )]
+        config: String,
+        /// Override config values, e.g. --set sqllog.path=./logs
+        #[arg(long = "set", value_name = "KEY=VALUE")]
+        set: Vec<String>,
+        /// Also measure the batched export path and warn if it is slower than the single-record path
+        #[arg(long = "compare-paths")]
+        compare_paths: bool,
+        /// Number of synthetic records per measurement
+        #[arg(long = "records", value_name = "N", default_value_t = crate::cli::bench::DEFAULT_BENCH_RECORDS)]
+        records: usize,
+        /// Output results as JSON (goes to stdout)
+        #[arg(long = "json")]
+        json: bool,
+    },
+    /// Print the first N records after filters, masking and derived columns (no output written)
+    Preview {
+        /// Configuration file path
+        #[arg(
+            short = 'c',
+            long = "config",
+            default_value = "config.toml",
+            env = "SQLLOG2DB_CONFIG"
+        )]
+        config: String,
+        /// Override config values, e.g. --set sqllog.path=./logs
+        #[arg(long = "set", value_name = "KEY=VALUE")]
+        set: Vec<String>,
+        /// Keep only records at or after this timestamp
+        #[arg(long = "from", visible_alias = "since", value_name = "DATETIME")]
+        from: Option<String>,
+        /// Keep only records at or before this timestamp
+        #[arg(long = "to", visible_alias = "until", value_name = "DATETIME")]
+        to: Option<String>,
+        /// Number of records to show
+        #[arg(short = 'n', long = "limit", default_value_t = crate::cli::preview::DEFAULT_PREVIEW_LIMIT)]
+        limit: usize,
+        /// Output records as a JSON array (goes to stdout)
+        #[arg(long = "json")]
+        json: bool,
+        /// Truncate values longer than N characters in table output (0 = no truncation)
+        #[arg(long = "width", value_name = "N", default_value_t = crate::cli::preview::DEFAULT_PREVIEW_WIDTH)]
+        width: usize,
+    },
     /// Count records in log files without exporting
     Stats {
         /// Configuration file path
@@ -132,10 +305,10 @@ pub enum Commands {
         #[arg(long = "set", value_name = "KEY=VALUE")]
         set: Vec<String>,
         /// Keep only records at or after this timestamp
-        #[arg(long = "from", value_name = "DATETIME")]
+        #[arg(long = "from", visible_alias = "since", value_name = "DATETIME")]
         from: Option<String>,
         /// Keep only records at or before this timestamp
-        #[arg(long = "to", value_name = "DATETIME")]
+        #[arg(long = "to", visible_alias = "until", value_name = "DATETIME")]
         to: Option<String>,
         /// Show top N slowest queries ranked by execution time
         #[arg(long = "top", value_name = "N")]
@@ -155,6 +328,12 @@ pub enum Commands {
         /// Override the state file path used by --resume (default: `.sqllog2db_stats_state.toml`)
         #[arg(long = "state-file", value_name = "PATH", requires = "resume")]
         state_file: Option<String>,
+        /// Estimate output size and write throughput per format from a sampled export
+        #[arg(long = "capacity")]
+        capacity: bool,
+        /// Number of records to sample per format for --capacity
+        #[arg(long = "sample", value_name = "N", default_value_t = crate::cli::capacity::DEFAULT_CAPACITY_SAMPLE, requires = "capacity")]
+        sample: usize,
     },
     /// Fingerprint SQL queries and aggregate by structure
     Digest {
@@ -170,10 +349,10 @@ pub enum Commands {
         #[arg(long = "set", value_name = "KEY=VALUE")]
         set: Vec<String>,
         /// Keep only records at or after this timestamp
-        #[arg(long = "from", value_name = "DATETIME")]
+        #[arg(long = "from", visible_alias = "since", value_name = "DATETIME")]
         from: Option<String>,
         /// Keep only records at or before this timestamp
-        #[arg(long = "to", value_name = "DATETIME")]
+        #[arg(long = "to", visible_alias = "until", value_name = "DATETIME")]
         to: Option<String>,
         /// Show only top N fingerprints
         #[arg(long = "top", value_name = "N")]
@@ -194,6 +373,114 @@ pub enum Commands {
         #[arg(long = "state-file", value_name = "PATH", requires = "resume")]
         state_file: Option<String>,
     },
+    /// Query an existing export (`SQLite` database or CSV file) for the top statements, users or sessions
+    Top {
+        /// Export to analyse: `SQLite` database, or CSV file (comma-delimited, with header)
+        #[arg(long = "target", value_name = "PATH")]
+        target: String,
+        /// Ranking metric: `exec_time` (total execution time), `count` or `rows`
+        #[arg(long = "by", value_name = "METRIC", default_value = "exec_time")]
+        by: String,
+        /// Group records by: statement, user, session or app
+        #[arg(long = "group", value_name = "FIELD", default_value = "statement")]
+        group: String,
+        /// Table to query in a `SQLite` target (default: `sqllog_records`)
+        #[arg(long = "table", value_name = "NAME")]
+        table: Option<String>,
+        /// Show at most N entries
+        #[arg(short = 'n', long = "limit", value_name = "N", default_value = "20")]
+        limit: usize,
+        /// Output results as a JSON array (goes to stdout)
+        #[arg(long = "json")]
+        json: bool,
+    },
+    /// Compare two run manifests (`digest --json` output) and report workload changes
+    Diff {
+        /// Baseline manifest (e.g. before the upgrade)
+        #[arg(value_name = "RUN_A")]
+        run_a: String,
+        /// Manifest to compare against the baseline
+        #[arg(value_name = "RUN_B")]
+        run_b: String,
+        /// Report fingerprints whose max execution time reaches this threshold as slow
+        #[arg(long = "slow-ms", value_name = "MS", default_value_t = crate::cli::diff::DEFAULT_SLOW_MS)]
+        slow_ms: f64,
+        /// Show at most N fingerprints / slow queries
+        #[arg(long = "top", value_name = "N", default_value = "20")]
+        top: usize,
+    },
+    /// Remove stale temp files and directories left behind by crashed runs
+    CleanTemp {
+        /// Configuration file path (output directories are scanned too)
+        #[arg(
+            short = 'c',
+            long = "config",
+            default_value = "config.toml",
+            env = "SQLLOG2DB_CONFIG"
+        )]
+        config: String,
+        /// Override config values, e.g. --set exporter.csv.file=./out/a.csv
+        #[arg(long = "set", value_name = "KEY=VALUE")]
+        set: Vec<String>,
+        /// Additional directories to scan (e.g. the directory of --metrics-file)
+        #[arg(value_name = "DIR")]
+        dirs: Vec<String>,
+        /// Only remove artifacts idle for at least this long, e.g. 30m, 12h (default: 24h)
+        #[arg(long = "older-than", value_name = "DURATION", value_parser = parse_duration)]
+        older_than: Option<std::time::Duration>,
+        /// List what would be removed without deleting anything
+        #[arg(long = "dry-run")]
+        dry_run: bool,
+    },
+    /// Check the DM server's SQL log settings against the configured export
+    Doctor {
+        /// Configuration file path
+        #[arg(
+            short = 'c',
+            long = "config",
+            default_value = "config.toml",
+            env = "SQLLOG2DB_CONFIG"
+        )]
+        config: String,
+        /// Override config values, e.g. --set features.fields=ts,sql
+        #[arg(long = "set", value_name = "KEY=VALUE")]
+        set: Vec<String>,
+        /// dm.ini (sqllog.ini in the same directory is read too) or sqllog.ini itself
+        #[arg(long = "check-dm-config", value_name = "PATH")]
+        check_dm_config: String,
+    },
+    /// Write the first N records as a log snippet for bug reports
+    Sample {
+        /// Configuration file path
+        #[arg(
+            short = 'c',
+            long = "config",
+            default_value = "config.toml",
+            env = "SQLLOG2DB_CONFIG"
+        )]
+        config: String,
+        /// Override config values, e.g. --set sqllog.path=./logs
+        #[arg(long = "set", value_name = "KEY=VALUE")]
+        set: Vec<String>,
+        /// Number of records to sample (unparsable records included)
+        #[arg(long = "records", value_name = "N", default_value_t = crate::cli::sample::DEFAULT_SAMPLE_RECORDS)]
+        records: usize,
+        /// Output log file; defaults to stdout
+        #[arg(short = 'o', long = "output", value_name = "PATH")]
+        output: Option<String>,
+        /// Replace users, app names, IPs and SQL identifiers with pseudonyms and mask literals
+        #[arg(long = "anonymize")]
+        anonymize: bool,
+    },
+    /// List the exporters and input formats built into this binary, and check a config against them
+    Features {
+        /// Also check this configuration file for exporter sections that would be ignored
+        #[arg(short = 'c', long = "config", value_name = "PATH")]
+        config: Option<String>,
+        /// Output as JSON (goes to stdout)
+        #[arg(long = "json")]
+        json: bool,
+    },
     /// Generate shell completion scripts
     Completions {
         /// Shell type to generate completions for
@@ -218,3 +505,84 @@ impl Cli {
         generate(shell, &mut cmd, bin_name, &mut std::io::stdout());
     }
 }
+
+/// 解析 `--max-duration`：`90s` / `10m` / `2h`，不带单位按秒
+fn parse_duration(s: &str) -> Result<std::time::Duration, String> {
+    let s = s.trim();
+    let (num, unit) = s
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or((s, "s"), |i| s.split_at(i));
+    let n: u64 = num
+        .parse()
+        .map_err(|_| format!("invalid duration '{s}', expected e.g. 90s, 10m, 2h"))?;
+    let secs = match unit {
+        "s" => n,
+        "m" => n * 60,
+        "h" => n * 3600,
+        _ => {
+            return Err(format!(
+                "unknown duration unit '{unit}', expected s, m or h"
+            ));
+        }
+    };
+    if secs == 0 {
+        return Err("duration must be greater than zero".to_string());
+    }
+    Ok(std::time::Duration::from_secs(secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90").unwrap().as_secs(), 90);
+        assert_eq!(parse_duration("10m").unwrap().as_secs(), 600);
+        assert_eq!(parse_duration("2h").unwrap().as_secs(), 7200);
+        assert!(parse_duration("0s").is_err());
+        assert!(parse_duration("5d").is_err());
+        assert!(parse_duration("m").is_err());
+    }
+
+    #[test]
+    fn test_max_records_alias() {
+        let cli = Cli::try_parse_from([
+            "sqllog2db",
+            "run",
+            "--max-records",
+            "5",
+            "--max-duration",
+            "1m",
+        ])
+        .unwrap();
+        let Some(Commands::Run {
+            limit,
+            max_duration,
+            ..
+        }) = cli.command
+        else {
+            panic!("expected run");
+        };
+        assert_eq!(limit, Some(5));
+        assert_eq!(max_duration.map(|d| d.as_secs()), Some(60));
+    }
+
+    #[test]
+    fn test_since_until_alias() {
+        let cli = Cli::try_parse_from([
+            "sqllog2db",
+            "run",
+            "--since",
+            "2025-01-15 10:30",
+            "--until",
+            "2025-01-15 11:00",
+        ])
+        .unwrap();
+        let Some(Commands::Run { from, to, .. }) = cli.command else {
+            panic!("expected run");
+        };
+        assert_eq!(from.as_deref(), Some("2025-01-15 10:30"));
+        assert_eq!(to.as_deref(), Some("2025-01-15 11:00"));
+    }
+}
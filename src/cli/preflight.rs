@@ -1,5 +1,7 @@
 use crate::color;
-use crate::config::Config;
+use crate::config::{Config, SqllogConfig};
+use crate::exporter::rollover::{RolloverScheduler, is_time_pattern};
+use crate::exporter::sink::is_stream;
 use crate::parser::SqllogParser;
 use std::path::Path;
 
@@ -8,12 +10,17 @@ use std::path::Path;
 #[must_use]
 pub fn check(cfg: &Config) -> PreflightResult {
     let mut result = PreflightResult::default();
-    check_log_path(&cfg.sqllog.path, &mut result);
+    check_log_path(&cfg.sqllog, &mut result);
     check_output_writable(cfg, &mut result);
     result
 }
 
-fn check_log_path(path_str: &str, result: &mut PreflightResult) {
+fn check_log_path(sqllog: &SqllogConfig, result: &mut PreflightResult) {
+    let path_str = sqllog.path.as_str();
+    // 标准输入无需检查；内容在处理时才读取
+    if crate::parser::input::is_stdin(Path::new(path_str)) {
+        return;
+    }
     let has_glob = path_str.contains('*') || path_str.contains('?') || path_str.contains('[');
 
     // For non-glob paths, check existence before trying to scan
@@ -27,7 +34,7 @@ fn check_log_path(path_str: &str, result: &mut PreflightResult) {
         }
     }
 
-    match SqllogParser::new(path_str).log_files() {
+    match SqllogParser::from_config(sqllog).and_then(|p| p.log_files()) {
         Ok(files) if files.is_empty() => {
             result
                 .warnings
@@ -42,7 +49,19 @@ fn check_log_path(path_str: &str, result: &mut PreflightResult) {
 
 fn check_output_writable(cfg: &Config, result: &mut PreflightResult) {
     if let Some(csv) = &cfg.exporter.csv {
-        check_path_writable(&csv.file, result);
+        if is_time_pattern(&csv.file) {
+            // 时间模式：周期文件名取决于记录时间，只检查（并创建）输出目录，避免留下空文件
+            let now_path = RolloverScheduler::new(csv.file.clone()).format_now();
+            if let Some(parent) = now_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                if !parent.exists() && std::fs::create_dir_all(parent).is_err() {
+                    result
+                        .errors
+                        .push(format!("无法创建输出目录: {}", parent.display()));
+                }
+            }
+        } else if !is_stream(Path::new(&csv.file)) {
+            check_path_writable(&csv.file, result);
+        }
         return;
     }
     if let Some(sqlite) = &cfg.exporter.sqlite {
@@ -66,15 +85,21 @@ fn check_path_writable(file_path: &str, result: &mut PreflightResult) {
     }
 
     // 用单次 open（create + write）镜像导出器实际行为，消除 exists() → open() 的 TOCTOU 竞争。
-    // truncate(false)：preflight 仅验证可写性，不截断已有文件。
-    if std::fs::OpenOptions::new()
+    // truncate(false)：preflight 仅验证可写性，不截断已有文件；探测新建的空文件随即删除，
+    // `validate` 不留下输出文件。
+    let existed = path.exists();
+    match std::fs::OpenOptions::new()
         .create(true)
         .write(true)
         .truncate(false)
         .open(path)
-        .is_err()
     {
-        result.errors.push(format!("输出文件不可写: {file_path}"));
+        Err(_) => result.errors.push(format!("输出文件不可写: {file_path}")),
+        Ok(file) if !existed => {
+            drop(file);
+            let _ = std::fs::remove_file(path);
+        }
+        Ok(_) => {}
     }
 }
 
@@ -112,6 +137,7 @@ mod tests {
         Config {
             sqllog: SqllogConfig {
                 path: dir.to_string(),
+                ..Default::default()
             },
             ..Default::default()
         }
@@ -162,6 +188,24 @@ mod tests {
         assert!(!result.has_errors());
     }
 
+    #[test]
+    fn test_check_stdin_log_path_is_valid() {
+        let result = check(&config_with_log_dir("-"));
+        assert!(!result.has_errors());
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_check_csv_stdout_output_is_not_created() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.log"), "").unwrap();
+        let mut cfg = config_with_log_dir(dir.path().to_str().unwrap());
+        cfg.exporter.csv.as_mut().unwrap().file = "-".to_string();
+        let result = check(&cfg);
+        assert!(!result.has_errors());
+        assert!(!Path::new("-").exists());
+    }
+
     #[test]
     fn test_check_log_dir_empty_produces_warning() {
         let dir = tempfile::TempDir::new().unwrap();
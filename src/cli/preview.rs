@@ -0,0 +1,166 @@
+//! `preview`：按正式导出的处理流程（过滤、参数替换、时区归一化、派生列）抽取前 N 条记录并打印，
+//! 用于在长时间导出前确认配置效果。不写任何输出文件。
+use super::run::{PreviewRows, collect_preview};
+use crate::color;
+use crate::config::Config;
+use crate::error::Result;
+use crate::features::{CompiledMetaFilters, CompiledSqlFilters};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde_json::Value;
+use std::fmt::Write as _;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+
+pub const DEFAULT_PREVIEW_LIMIT: usize = 20;
+/// 表格模式下单个值的默认最大显示字符数
+pub const DEFAULT_PREVIEW_WIDTH: usize = 120;
+
+/// `preview` 子命令入口：表格输出到 stdout（逐条纵向展示），`json = true` 时输出 JSON 数组
+pub fn handle_preview(
+    cfg: &Config,
+    limit: usize,
+    json: bool,
+    width: usize,
+    quiet: bool,
+    interrupted: &Arc<AtomicBool>,
+    compiled_filters: Option<(CompiledMetaFilters, CompiledSqlFilters)>,
+) -> Result<()> {
+    let jobs = std::thread::available_parallelism().map_or(1, std::num::NonZero::get);
+    let preview = collect_preview(cfg, limit, jobs, interrupted, compiled_filters)?;
+    if json {
+        println!("{}", render_json(&preview));
+        return Ok(());
+    }
+    print!("{}", render_table(&preview, width));
+    if !quiet {
+        eprintln!(
+            "{} {} records from {} files (limit {limit})",
+            color::cyan("✔"),
+            preview.rows.len(),
+            preview.files_scanned
+        );
+    }
+    Ok(())
+}
+
+/// 单条记录的 JSON 对象：按列顺序序列化（`serde_json::Map` 默认按键排序）
+struct JsonRecord<'a> {
+    columns: &'a [String],
+    values: &'a [Value],
+}
+
+impl Serialize for JsonRecord<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.columns.len()))?;
+        for (name, value) in self.columns.iter().zip(self.values) {
+            map.serialize_entry(name, value)?;
+        }
+        map.end()
+    }
+}
+
+/// JSON 数组，每条记录为以列名为键的对象
+#[must_use]
+pub fn render_json(preview: &PreviewRows) -> String {
+    let records: Vec<JsonRecord<'_>> = preview
+        .rows
+        .iter()
+        .map(|row| JsonRecord {
+            columns: &preview.columns,
+            values: row,
+        })
+        .collect();
+    serde_json::to_string_pretty(&records).unwrap_or_default()
+}
+
+/// 纵向表格：每条记录一个块，左列为列名；`width = 0` 表示不截断
+#[must_use]
+pub fn render_table(preview: &PreviewRows, width: usize) -> String {
+    let mut out = String::new();
+    if preview.rows.is_empty() {
+        out.push_str("(no records matched)\n");
+        return out;
+    }
+    let name_width = preview
+        .columns
+        .iter()
+        .map(|c| c.chars().count())
+        .max()
+        .unwrap_or(0);
+    for (i, row) in preview.rows.iter().enumerate() {
+        let _ = writeln!(out, "-[ RECORD {} ]{}", i + 1, "-".repeat(name_width + 3));
+        for (name, value) in preview.columns.iter().zip(row) {
+            let _ = writeln!(out, "{name:<name_width$} | {}", cell(value, width));
+        }
+    }
+    out
+}
+
+/// 单元格文本：NULL 显式标出，换行 / 制表符折叠为空格，超出 `width` 时截断并以 `…` 结尾
+fn cell(value: &Value, width: usize) -> String {
+    let text = match value {
+        Value::Null => return "NULL".to_string(),
+        Value::String(s) => s.replace(['\r', '\n', '\t'], " "),
+        other => other.to_string(),
+    };
+    if width == 0 || text.chars().count() <= width {
+        return text;
+    }
+    let mut truncated: String = text.chars().take(width.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> PreviewRows {
+        PreviewRows {
+            columns: vec!["ts".into(), "sql".into(), "exec_time_ms".into()],
+            rows: vec![
+                vec![
+                    Value::from("2025-01-15 10:30:28.001"),
+                    Value::from("SELECT *\nFROM t"),
+                    Value::from(12),
+                ],
+                vec![
+                    Value::from("2025-01-15 10:30:29.001"),
+                    Value::from("COMMIT"),
+                    Value::Null,
+                ],
+            ],
+            files_scanned: 1,
+        }
+    }
+
+    #[test]
+    fn test_render_table_null_newline_and_truncate() {
+        let out = render_table(&sample(), 0);
+        assert!(out.contains("-[ RECORD 2 ]"));
+        assert!(out.contains("sql          | SELECT * FROM t\n"));
+        assert!(out.contains("exec_time_ms | NULL\n"));
+
+        let out = render_table(&sample(), 6);
+        assert!(out.contains("sql          | SELEC…\n"), "{out}");
+    }
+
+    #[test]
+    fn test_render_table_empty() {
+        assert_eq!(
+            render_table(&PreviewRows::default(), 40),
+            "(no records matched)\n"
+        );
+    }
+
+    #[test]
+    fn test_render_json_keeps_types() {
+        let v: Value = serde_json::from_str(&render_json(&sample())).unwrap();
+        assert_eq!(v[0]["exec_time_ms"], 12);
+        assert_eq!(v[0]["sql"], "SELECT *\nFROM t");
+        assert!(v[1]["exec_time_ms"].is_null());
+        // 键顺序与列顺序一致
+        let json = render_json(&sample());
+        assert!(json.find("\"ts\"").unwrap() < json.find("\"exec_time_ms\"").unwrap());
+    }
+}
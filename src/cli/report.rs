@@ -0,0 +1,237 @@
+//! `report`：以用户提供的 minijinja 模板渲染运行报告（Markdown / HTML / Slack blocks 等任意文本格式）。
+//!
+//! 模板可用变量：
+//! - `version` / `generated_at`（本地时间 RFC 3339）/ `config_path` / `sqllog_path`
+//! - `summary`：`run_id` `files` `skipped_files` `records` `elapsed_secs` `records_per_sec` `dry_run` `parallel`
+//!   `truncated`（因 `--max-records` / `--max-duration` 提前结束时的原因，否则为空）
+//! - `templates`：模板分析结果列表（需启用 `[features.template_analysis]`），
+//!   字段同 `TemplateStats`：`template_key` `count` `avg_us` `min_us` `max_us` `p50_us` `p95_us` `p99_us`
+//!   `first_seen` `last_seen`
+//! - `statements`：语句登记表（需启用 `[features.statements]`），按次数降序，
+//!   字段：`statement_id` `fingerprint` `count` `first_seen` `last_seen`
+//! - `warnings`：运行中登记的非致命警告，按类别分组，字段：`kind` `message`
+//!
+//! 除 minijinja 内置过滤器外，额外提供 `human_count`（千分位）与 `ms`（微秒 → 毫秒字符串）。
+use super::run::RunSummary;
+use crate::config::Config;
+use crate::error::{ConfigError, Error, FileError, Result};
+use crate::features::{StatementInfo, TemplateStats};
+use crate::warnings::Warning;
+use serde::Serialize;
+use std::path::Path;
+
+#[derive(Debug, Serialize)]
+struct SummaryView<'a> {
+    run_id: &'a str,
+    files: usize,
+    skipped_files: usize,
+    records: usize,
+    elapsed_secs: f64,
+    records_per_sec: f64,
+    dry_run: bool,
+    parallel: bool,
+    truncated: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ReportContext<'a> {
+    version: &'static str,
+    generated_at: String,
+    config_path: &'a str,
+    sqllog_path: &'a str,
+    summary: SummaryView<'a>,
+    templates: &'a [TemplateStats],
+    statements: &'a [StatementInfo],
+    warnings: &'a [Warning],
+}
+
+fn build_context<'a>(
+    cfg: &'a Config,
+    config_path: &'a str,
+    summary: &'a RunSummary,
+) -> ReportContext<'a> {
+    let elapsed_secs = summary.elapsed.as_secs_f64();
+    #[allow(clippy::cast_precision_loss)]
+    let records_per_sec = if elapsed_secs > 0.0 {
+        summary.records as f64 / elapsed_secs
+    } else {
+        0.0
+    };
+    ReportContext {
+        version: env!("CARGO_PKG_VERSION"),
+        generated_at: chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+        config_path,
+        sqllog_path: &cfg.sqllog.path,
+        summary: SummaryView {
+            run_id: &summary.run_id,
+            files: summary.files,
+            skipped_files: summary.skipped_files,
+            records: summary.records,
+            elapsed_secs,
+            records_per_sec,
+            dry_run: summary.dry_run,
+            parallel: summary.parallel,
+            truncated: summary.truncated.map(|t| t.to_string()),
+        },
+        templates: &summary.templates,
+        statements: &summary.statements,
+        warnings: &summary.warnings,
+    }
+}
+
+fn human_count(v: u64) -> String {
+    indicatif::HumanCount(v).to_string()
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn us_to_ms(v: u64) -> String {
+    format!("{:.3}", v as f64 / 1000.0)
+}
+
+/// 渲染模板源码；语法或渲染错误以 `ConfigError::InvalidValue`（字段 `report.template`）返回
+pub fn render_report(
+    template_name: &str,
+    source: &str,
+    cfg: &Config,
+    config_path: &str,
+    summary: &RunSummary,
+) -> Result<String> {
+    let invalid = |e: minijinja::Error| {
+        Error::Config(ConfigError::InvalidValue {
+            field: "report.template".to_string(),
+            value: template_name.to_string(),
+            reason: format!("{e:#}"),
+        })
+    };
+    let mut env = minijinja::Environment::new();
+    env.add_filter("human_count", human_count);
+    env.add_filter("ms", us_to_ms);
+    env.add_template(template_name, source).map_err(invalid)?;
+    let tmpl = env.get_template(template_name).map_err(invalid)?;
+    tmpl.render(minijinja::value::Serde(build_context(
+        cfg,
+        config_path,
+        summary,
+    )))
+    .map_err(invalid)
+}
+
+/// `report` 子命令的渲染输出步骤：读取模板 → 渲染 → 写入 `output`（`None` 时输出到 stdout）
+pub fn handle_report(
+    cfg: &Config,
+    config_path: &str,
+    template_path: &str,
+    output: Option<&str>,
+    summary: &RunSummary,
+) -> Result<()> {
+    let source = std::fs::read_to_string(template_path).map_err(|e| {
+        Error::File(FileError::ReadFailed {
+            path: template_path.into(),
+            reason: e.to_string(),
+        })
+    })?;
+    let rendered = render_report(template_path, &source, cfg, config_path, summary)?;
+    match output {
+        Some(path) => {
+            if let Some(parent) = Path::new(path).parent() {
+                if !parent.as_os_str().is_empty() {
+                    std::fs::create_dir_all(parent).map_err(|e| {
+                        Error::File(FileError::CreateDirectoryFailed {
+                            path: parent.to_path_buf(),
+                            reason: e.to_string(),
+                        })
+                    })?;
+                }
+            }
+            std::fs::write(path, rendered).map_err(|e| {
+                Error::File(FileError::WriteFailed {
+                    path: path.into(),
+                    reason: e.to_string(),
+                })
+            })?;
+            log::info!("Report written to {path}");
+        }
+        None => print!("{rendered}"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn summary() -> RunSummary {
+        RunSummary {
+            files: 3,
+            skipped_files: 1,
+            records: 12345,
+            elapsed: Duration::from_secs(2),
+            templates: vec![TemplateStats {
+                template_key: "SELECT * FROM t WHERE id = ?".into(),
+                count: 10,
+                avg_us: 1500,
+                min_us: 1000,
+                max_us: 2000,
+                p50_us: 1500,
+                p95_us: 1900,
+                p99_us: 2000,
+                first_seen: "2025-01-15 10:00:00.000".into(),
+                last_seen: "2025-01-15 11:00:00.000".into(),
+            }],
+            ..RunSummary::default()
+        }
+    }
+
+    #[test]
+    fn test_render_summary_and_filters() {
+        let cfg = Config::default();
+        let out = render_report(
+            "t.md",
+            "{{ summary.records | human_count }} records from {{ summary.files }} files, {{ summary.records_per_sec | round }}/s\n\
+             {% for t in templates %}- {{ t.template_key }}: p95 {{ t.p95_us | ms }} ms{% endfor %}",
+            &cfg,
+            "config.toml",
+            &summary(),
+        )
+        .unwrap();
+        assert!(
+            out.starts_with("12,345 records from 3 files, 6172.0/s"),
+            "{out}"
+        );
+        assert!(out.contains("- SELECT * FROM t WHERE id = ?: p95 1.900 ms"));
+    }
+
+    #[test]
+    fn test_render_syntax_error_is_config_error() {
+        let err = render_report(
+            "bad.j2",
+            "{% for x in %}",
+            &Config::default(),
+            "c",
+            &summary(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("report.template"), "{err}");
+    }
+
+    #[test]
+    fn test_handle_report_writes_output_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let tpl = dir.path().join("r.j2");
+        std::fs::write(&tpl, "v={{ version }} dry={{ summary.dry_run }}").unwrap();
+        let out = dir.path().join("sub/report.txt");
+        handle_report(
+            &Config::default(),
+            "config.toml",
+            tpl.to_str().unwrap(),
+            Some(out.to_str().unwrap()),
+            &summary(),
+        )
+        .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&out).unwrap(),
+            format!("v={} dry=False", env!("CARGO_PKG_VERSION"))
+        );
+    }
+}
@@ -1,24 +1,342 @@
+use super::stages::{Stage, StageClock, StageProfile};
 use crate::color;
 use crate::config::Config;
-use crate::error::ParserError;
 use crate::error::{Error, Result};
-use crate::exporter::{CsvExporter, ExporterManager};
+use crate::exporter::csv::TextDialect;
+use crate::exporter::{
+    ConcurrentExporterManager, CsvExporter, DEFAULT_QUEUE_BATCHES, ExportStats, ExporterManager,
+    FlushLatency,
+};
 use crate::features::filters::RecordMeta;
 use crate::features::replace_parameters::ParamBuffer;
 use crate::features::{
-    CompiledMetaFilters, CompiledSqlFilters, FieldMask, LogProcessor, Pipeline, TemplateAggregator,
+    CaseNormalizer, CompiledMetaFilters, CompiledSqlFilters, Deduplicator, DerivedColumns,
+    DerivedValue, ExecIdAudit, ExecIdTracker, FieldMask, IdObfuscator, LogProcessor, Pipeline,
+    QuotaTracker, RowInfo, SeqScope, SessionBackfill, SessionPos, SessionSeq, StatementInfo,
+    StatementRegistry, TemplateAggregator, TemplateStats, TsNormalizer, UnmeasuredPolicy,
 };
-use crate::parser::SqllogParser;
+use crate::metrics::{Counter, ErrorSample, ErrorSamples, FileCounts, Metrics, MetricsSnapshot};
+use crate::parser::{ActiveFilePolicy, ParseMode, Recovering, SqllogParser, open_log};
+use crate::warnings::{Warning, WarningKind, Warnings};
 use ahash::HashSet as AHashSet;
 use compact_str::CompactString;
-use dm_database_parser_sqllog::{LogParser, MetaParts};
+use dm_database_parser_sqllog::{MetaParts, ParseError};
 use indicatif::{HumanCount, ProgressBar, ProgressStyle};
-use log::{info, warn};
+use log::info;
+use serde::Serialize;
+use std::borrow::Cow;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
 use std::time::{Duration, Instant};
 
+/// 单次导出任务的结果摘要（供 `run-all` 汇总、报告与通知使用）
+#[derive(Debug, Clone, Default)]
+pub struct RunSummary {
+    /// 本次运行的标识（见 `crate::run_id`）
+    pub run_id: String,
+    /// 发现的日志文件数（含被 `--resume` 跳过的文件）
+    pub files: usize,
+    /// 因 `--resume` 跳过的文件数
+    pub skipped_files: usize,
+    /// 导出（或 dry-run 统计）的记录数
+    pub records: usize,
+    pub elapsed: Duration,
+    pub dry_run: bool,
+    pub parallel: bool,
+    /// 模板分析结果（未启用 `template_analysis` 时为空）
+    pub templates: Vec<TemplateStats>,
+    /// 因 `[features.quota]` 跳过的记录数（按用户，降序）
+    pub quota_skipped: Vec<(String, u64)>,
+    /// `EXEC_ID` 连续性审计结果（未启用 `[features.exec_id_audit]` 时为 `None`）
+    pub exec_id_audit: Option<ExecIdAudit>,
+    /// 语句登记表，按次数降序（未启用 `[features.statements]` 时为空）
+    pub statements: Vec<StatementInfo>,
+    /// 因 `--max-records` / `--max-duration` 提前结束时的原因
+    pub truncated: Option<Truncation>,
+    /// 各处理阶段耗时（`--profile-stages`）
+    pub stages: Option<StageProfile>,
+    /// 运行指标快照（`records` 即其中的导出数）
+    pub metrics: MetricsSnapshot,
+    /// 运行中登记的非致命警告，按类别分组（见 `crate::warnings`）
+    pub warnings: Vec<Warning>,
+    /// `--auto-tune` 选定的参数（由调用方在运行结束后填入）
+    pub tuning: Option<crate::cli::tune::Tuning>,
+    /// 各类解析错误的次数与样例，按次数降序
+    pub parse_errors: Vec<ErrorSample>,
+    /// 逐文件处理计数，按文件清单顺序（不含 `--resume` 跳过的文件）
+    pub per_file: Vec<FileCounts>,
+    /// 各导出器的写出统计（主导出器，及 `[features.unmeasured]` 的单独输出）
+    pub exporters: Vec<ExporterStats>,
+}
+
+/// 导出器写出统计
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ExporterStats {
+    pub name: String,
+    /// 输出目标：文件路径、数据库或集群地址
+    pub target: String,
+    pub exported: u64,
+    /// 分块刷新 / 批量提交次数；并行 CSV 路径按文件分片写出，不统计
+    pub flush_operations: Option<u64>,
+    /// 因 `[features.dedup]` 跳过的重复记录数
+    pub duplicates: u64,
+    /// 分块刷新 / 批量提交耗时（min / avg / p95）；未统计刷新时为空
+    pub flush_latency: Option<FlushLatency>,
+    /// 写入失败、按 `on_error` 跳过的记录数（不计入 `exported`）
+    pub skipped: u64,
+    /// 写入失败、按 `on_error = log` 写入错误日志的记录数（不计入 `exported`）
+    pub failed: u64,
+}
+
+impl ExporterStats {
+    fn new(cfg: &Config, name: &str, exported: u64, stats: Option<ExportStats>) -> Self {
+        let target = match name {
+            "CSV" => cfg.exporter.csv.as_ref().map(|c| c.file.clone()),
+            "SQLite" => cfg.exporter.sqlite.as_ref().map(|s| s.database_url.clone()),
+            "Elasticsearch" => cfg
+                .exporter
+                .elasticsearch
+                .as_ref()
+                .map(|e| format!("{}/{}", e.url.trim_end_matches('/'), e.index)),
+            _ => None,
+        };
+        let skipped = stats.map_or(0, |s| s.skipped as u64);
+        let failed = stats.map_or(0, |s| s.failed as u64);
+        Self {
+            name: name.to_string(),
+            target: target.unwrap_or_default(),
+            exported: exported.saturating_sub(skipped + failed),
+            flush_operations: stats.map(|s| s.flush_operations as u64),
+            duplicates: stats.map_or(0, |s| s.duplicates as u64),
+            flush_latency: stats.map(|s| s.flush_latency).filter(|l| l.count > 0),
+            skipped,
+            failed,
+        }
+    }
+}
+
+/// 运行被安全上限截断的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Truncation {
+    MaxRecords(usize),
+    MaxDuration(Duration),
+}
+
+impl std::fmt::Display for Truncation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MaxRecords(n) => write!(f, "max-records {} reached", HumanCount(*n as u64)),
+            Self::MaxDuration(d) => write!(f, "max-duration {}s reached", d.as_secs()),
+        }
+    }
+}
+
+/// `--max-duration` 看门狗：到时或调用方中断时置位 `stop`，处理循环把它当作取消标志；
+/// drop 时结束线程
+#[derive(Debug)]
+struct Watchdog {
+    stop: Arc<AtomicBool>,
+    expired: Arc<AtomicBool>,
+    done: Option<std::sync::mpsc::Sender<()>>,
+    handle: Option<std::thread::JoinHandle<()>>,
+}
+
+impl Watchdog {
+    /// 转发调用方中断信号的轮询间隔
+    const POLL: Duration = Duration::from_millis(50);
+
+    fn start(limit: Duration, interrupted: &Arc<AtomicBool>) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let expired = Arc::new(AtomicBool::new(false));
+        let (done, rx) = std::sync::mpsc::channel::<()>();
+        let deadline = Instant::now() + limit;
+        let (cancel, stop_flag, expired_flag) = (
+            Arc::clone(interrupted),
+            Arc::clone(&stop),
+            Arc::clone(&expired),
+        );
+        let handle = std::thread::spawn(move || {
+            loop {
+                let left = deadline.saturating_duration_since(Instant::now());
+                if left.is_zero() {
+                    expired_flag.store(true, Ordering::Relaxed);
+                    break;
+                }
+                if cancel.load(Ordering::Relaxed) {
+                    break;
+                }
+                if !matches!(
+                    rx.recv_timeout(left.min(Self::POLL)),
+                    Err(std::sync::mpsc::RecvTimeoutError::Timeout)
+                ) {
+                    return;
+                }
+            }
+            stop_flag.store(true, Ordering::Relaxed);
+        });
+        Self {
+            stop,
+            expired,
+            done: Some(done),
+            handle: Some(handle),
+        }
+    }
+
+    fn expired(&self) -> bool {
+        self.expired.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        drop(self.done.take());
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// 摘要中最多列出的配额跳过用户数
+const QUOTA_SUMMARY_TOP: usize = 5;
+
+/// 摘要中最多列出的 `EXEC_ID` 缺口序列数
+const EXEC_ID_SUMMARY_TOP: usize = 5;
+
+/// 摘要中最多逐条列出的警告数
+const WARNINGS_SUMMARY_TOP: usize = 10;
+
+/// 摘要中最多列出的解析错误类别数
+const PARSE_ERRORS_SUMMARY_TOP: usize = 5;
+
+/// `EXEC_ID` 审计摘要行，如 `exec_id: 14 missing in 2 of 40 sequences — EP[0] 0x7f01: 12 (1000..1250), ...`
+fn format_exec_id_audit(audit: &ExecIdAudit) -> String {
+    if audit.gaps.is_empty() {
+        return format!(
+            "{} exec_id: no gaps across {} sequences",
+            color::green("✓"),
+            HumanCount(audit.sequences as u64)
+        );
+    }
+    let mut seqs: Vec<String> = audit
+        .gaps
+        .iter()
+        .take(EXEC_ID_SUMMARY_TOP)
+        .map(|g| {
+            format!(
+                "{}: {} ({}..{})",
+                g.sequence,
+                HumanCount(g.missing),
+                g.first,
+                g.last
+            )
+        })
+        .collect();
+    if audit.gaps.len() > EXEC_ID_SUMMARY_TOP {
+        seqs.push(format!(
+            "+{} more sequences",
+            audit.gaps.len() - EXEC_ID_SUMMARY_TOP
+        ));
+    }
+    format!(
+        "{} exec_id: {} missing in {} of {} sequences — {}",
+        color::yellow("⚠"),
+        HumanCount(audit.missing()),
+        HumanCount(audit.gaps.len() as u64),
+        HumanCount(audit.sequences as u64),
+        seqs.join(", ")
+    )
+}
+
+/// 警告摘要：按类别计数的标题行 + 前若干条明细，如
+/// `3 warnings (skipped_file: 2, parse_errors: 1)`
+fn format_warnings(warnings: &[Warning]) -> String {
+    use std::fmt::Write as _;
+    let kinds: Vec<String> = crate::warnings::count_by_kind(warnings)
+        .iter()
+        .map(|(kind, n)| format!("{kind}: {n}"))
+        .collect();
+    let mut out = format!(
+        "{} {} warnings ({})",
+        color::yellow("⚠"),
+        HumanCount(warnings.len() as u64),
+        kinds.join(", ")
+    );
+    for w in warnings.iter().take(WARNINGS_SUMMARY_TOP) {
+        let _ = write!(out, "\n  - [{}] {}", w.kind, w.message);
+    }
+    if warnings.len() > WARNINGS_SUMMARY_TOP {
+        let _ = write!(
+            out,
+            "\n  +{} more (see the app log)",
+            warnings.len() - WARNINGS_SUMMARY_TOP
+        );
+    }
+    out
+}
+
+/// 解析错误的类别与原文；类别用于完成摘要中的分组
+fn parse_error_kind(e: &ParseError) -> (Cow<'static, str>, &str) {
+    match e {
+        ParseError::InvalidFormat { raw } => (Cow::Borrowed("invalid format"), raw),
+        ParseError::InvalidRecordStartLine { raw } => {
+            (Cow::Borrowed("invalid record start line"), raw)
+        }
+        ParseError::IntParseError { field, raw, .. } => {
+            (Cow::Owned(format!("invalid integer in {field}")), raw)
+        }
+        ParseError::FileNotFound { path } => (Cow::Borrowed("file not found"), path),
+        ParseError::IoError(msg) => (Cow::Borrowed("I/O error"), msg),
+    }
+}
+
+/// 解析错误预览：按类别的次数与一条样例原文，运维无需翻查应用日志即可判断问题
+fn format_parse_errors(errors: &[ErrorSample]) -> String {
+    use std::fmt::Write as _;
+    let mut out = format!(
+        "{} parse errors by kind ({} kinds)",
+        color::yellow("⚠"),
+        errors.len()
+    );
+    for e in errors.iter().take(PARSE_ERRORS_SUMMARY_TOP) {
+        let _ = write!(
+            out,
+            "\n  {:>8} × {} (first in {})\n           {}",
+            HumanCount(e.count).to_string(),
+            e.kind,
+            e.file,
+            color::dim(&e.example)
+        );
+    }
+    if errors.len() > PARSE_ERRORS_SUMMARY_TOP {
+        let _ = write!(
+            out,
+            "\n  +{} more kinds (see the app log)",
+            errors.len() - PARSE_ERRORS_SUMMARY_TOP
+        );
+    }
+    out
+}
+
+/// 配额跳过摘要行，如 `quota: 1,200 records skipped — BATCH: 1,000, ETL: 200`
+fn format_quota_skipped(skipped: &[(String, u64)]) -> String {
+    let total: u64 = skipped.iter().map(|(_, n)| n).sum();
+    let mut users: Vec<String> = skipped
+        .iter()
+        .take(QUOTA_SUMMARY_TOP)
+        .map(|(u, n)| format!("{u}: {}", HumanCount(*n)))
+        .collect();
+    if skipped.len() > QUOTA_SUMMARY_TOP {
+        users.push(format!("+{} more users", skipped.len() - QUOTA_SUMMARY_TOP));
+    }
+    format!(
+        "{} quota: {} records skipped — {}",
+        color::yellow("⚠"),
+        HumanCount(total),
+        users.join(", ")
+    )
+}
+
 /// 构建处理器管线。
 ///
 /// `compiled_meta` 由 `Config::validate_and_compile` 在主流程入口预编译；
@@ -109,10 +427,94 @@ impl LogProcessor for FilterProcessor {
     }
 }
 
+/// `policy = "separate"` 时无性能指标记录的输出（`[features.unmeasured]`）
+struct UnmeasuredOutput {
+    manager: ExporterManager,
+    file: String,
+    records: u64,
+}
+
+impl UnmeasuredOutput {
+    /// 沿用 `[exporter.csv]` 的编码设置（未配置时取默认值），只替换输出文件；不拆分
+    fn from_config(cfg: &Config) -> Result<Option<Self>> {
+        let Some(file) = cfg
+            .features
+            .unmeasured
+            .as_ref()
+            .filter(|u| u.policy() == UnmeasuredPolicy::Separate)
+            .and_then(|u| u.file.clone())
+        else {
+            return Ok(None);
+        };
+        let mut side = cfg.clone();
+        side.exporter = crate::config::ExporterConfig {
+            csv: Some(crate::config::CsvExporter {
+                file: file.clone(),
+                split_by: None,
+                ..cfg.exporter.csv.clone().unwrap_or_default()
+            }),
+            sqlite: None,
+            elasticsearch: None,
+            custom: None,
+        };
+        let mut manager = ExporterManager::from_config(&side)?;
+        manager.initialize()?;
+        Ok(Some(Self {
+            manager,
+            file,
+            records: 0,
+        }))
+    }
+}
+
+/// `exporter.sqlite.load_metadata`：finalize 之后在目标库写入本次装载记录（仅 `SQLite` 导出器生效）
+fn write_load_record<'a>(
+    exporter_manager: &mut ExporterManager,
+    cfg: &Config,
+    run_id: &str,
+    files: impl IntoIterator<Item = &'a PathBuf>,
+    metrics: &MetricsSnapshot,
+    elapsed: Duration,
+    complete: bool,
+) -> Result<()> {
+    let enabled = cfg
+        .exporter
+        .sqlite
+        .as_ref()
+        .is_some_and(|s| s.load_metadata);
+    if !enabled || exporter_manager.name() != "SQLite" {
+        return Ok(());
+    }
+    let load = crate::exporter::LoadRecord::collect(run_id, files, metrics, elapsed, complete)?;
+    exporter_manager.write_load_record(&load)
+}
+
+/// 无性能指标记录的去向
+enum Unmeasured<'a> {
+    /// 照常导出（默认）
+    Export,
+    /// 不导出
+    Drop,
+    /// 写入单独的输出
+    Separate(&'a mut UnmeasuredOutput),
+}
+
+impl Unmeasured<'_> {
+    /// 没有单独输出可写时（并行分片、预览）：`separate` 的记录同样不进入主输出
+    fn without_output(policy: UnmeasuredPolicy) -> Self {
+        match policy {
+            UnmeasuredPolicy::Include => Self::Export,
+            UnmeasuredPolicy::Exclude | UnmeasuredPolicy::Separate => Self::Drop,
+        }
+    }
+}
+
 /// 处理单个日志文件，返回本文件实际导出的记录数。
 ///
 /// `limit`: 最多再导出多少条记录（跨文件的剩余配额），`None` 表示不限制。
-/// `reset_pb`: 是否在文件开始时重置进度条计数；并行模式传 `false`，避免多线程互相重置。
+/// `label_pb`: 是否在文件开始时把进度条前缀与消息切换为当前文件；并行模式传 `false`，避免多线程互相覆盖。
+/// 进度条计数取自 `metrics` 中的累计导出数，与完成摘要一致。
+#[allow(clippy::fn_params_excessive_bools)]
 fn process_log_file(
     file_path: &str,
     file_index: usize,
@@ -120,21 +522,44 @@ fn process_log_file(
     exporter_manager: &mut ExporterManager,
     pipeline: &Pipeline,
     pb: &ProgressBar,
+    metrics: &Metrics,
+    warnings: &Warnings,
     limit: Option<usize>,
+    chunk_size: usize,
     interrupted: &Arc<AtomicBool>,
     do_normalize: bool,
     mut aggregator: Option<&mut TemplateAggregator>,
     placeholder_override: Option<bool>,
     params_buffer: &mut ParamBuffer,
     ns_scratch: &mut Vec<u8>,
-    reset_pb: bool,
+    label_pb: bool,
     sql_record_filter: Option<&CompiledSqlFilters>,
+    ts_normalizer: Option<&TsNormalizer>,
+    derived: Option<&DerivedColumns>,
+    mut quota: Option<&mut QuotaTracker>,
+    mut dedup: Option<&mut Deduplicator>,
+    mut exec_ids: Option<&mut ExecIdTracker>,
+    mut statements: Option<&mut StatementRegistry>,
+    session_seq: &mut SessionSeq,
+    mut backfill: Option<&mut SessionBackfill>,
+    mut obfuscator: Option<&mut IdObfuscator>,
+    case: Option<&CaseNormalizer>,
+    redact: bool,
+    parse_mode: ParseMode,
+    active_file: ActiveFilePolicy,
+    verify_checksums: bool,
+    mut unmeasured: Unmeasured<'_>,
+    profile: Option<&mut StageProfile>,
 ) -> Result<usize> {
     // 清除上一个文件留下的残余参数，同时复用已分配的 HashMap 容量。
     params_buffer.clear();
 
     // 从导出器读取性能指标标志：CSV 关闭时跳过 parse_performance_metrics()（D-05/D-06）
     let include_pm = exporter_manager.csv_include_performance_metrics();
+    // `sql_params` 列：PARAMS 记录的参数值合并到随后的执行记录，PARAMS 记录本身不导出
+    let merge_params = derived.is_some_and(DerivedColumns::merges_params);
+    // 无性能指标记录不照常导出时，需要真实的性能指标来识别
+    let routes_unmeasured = !matches!(unmeasured, Unmeasured::Export);
 
     let file_start = Instant::now();
 
@@ -143,52 +568,113 @@ fn process_log_file(
         |n| n.to_string_lossy().into_owned(),
     );
 
-    if reset_pb {
+    if label_pb {
         pb.set_prefix(format!("{file_index}/{total_files}"));
         pb.set_message(file_name.clone());
-        pb.reset();
     }
 
-    let parser = LogParser::from_path(file_path).map_err(|e| {
-        Error::Parser(ParserError::InvalidPath {
-            path: file_path.into(),
-            reason: format!("{e}"),
-        })
-    })?;
+    // 阶段计时（`--profile-stages`），未启用时为空操作
+    let mut clock = StageClock::new(profile);
+    let parser = open_log(Path::new(file_path))?;
+    let hold_tail = active_file.holds_tail(Path::new(file_path));
+    // 与校验清单不符的文件：逐条记录计为错误，不导出
+    let corruption = verify_checksums
+        .then(|| crate::parser::checksum::verify(Path::new(file_path)).err())
+        .flatten();
+    if let Some(e) = &corruption {
+        log::error!("{e}");
+        warnings.push(
+            WarningKind::ChecksumMismatch,
+            format!("{file_path}: checksum mismatch, all records rejected"),
+        );
+    }
+    clock.lap(Stage::Read);
 
     let mut records_in_file = 0usize;
     let mut errors_in_file = 0usize;
-    // 用于攒批更新进度条，避免每条记录都触发原子操作
+    // 经容错修复的记录数（lenient 模式）
+    let mut recovered_in_file = 0usize;
+    // 经容错修正的 EXEC_ID 数（溢出饱和、跳过杂散字符）
+    let mut coerced_in_file = 0u64;
+    // 时区归一化后的 ts 缓冲区：跨记录复用，导出时以 Cow::Borrowed 借出
+    let mut ts_scratch = String::new();
+    // 派生列值缓冲区：每条记录 clear() 后复用
+    let mut extras: Vec<DerivedValue> = Vec::with_capacity(derived.map_or(0, DerivedColumns::len));
+    // 攒批累加到指标登记表并刷新进度条，避免每条记录都触发原子操作
     let mut pb_pending: u64 = 0;
+    // 已解析记录数（含被过滤记录）：取消检查按解析进度而非导出数，高过滤率时也能及时响应
+    let mut seen = 0usize;
+    let mut parse_errors = ErrorSamples::default();
 
-    'outer: for result in parser.iter() {
+    let mut records = Recovering::new(parser.iter(), parse_mode).holding_tail(hold_tail);
+    'outer: for result in records.by_ref() {
+        clock.lap(Stage::Parse);
+        seen += 1;
+        // 每 1024 条检查一次取消信号
+        if seen.trailing_zeros() >= 10 && interrupted.load(Ordering::Relaxed) {
+            break;
+        }
         match result {
-            Ok(record) => {
+            Ok((record, _)) if corruption.is_some() => {
+                errors_in_file += 1;
+                log::warn!(
+                    "{file_path} | record at {} rejected: file failed checksum verification",
+                    record.ts
+                );
+            }
+            Ok((record, recovered)) => {
+                recovered_in_file += usize::from(recovered);
+                // EXEC_ID 审计覆盖全部已解析记录（过滤前），被过滤的记录不算丢失
+                if let Some(t) = exec_ids.as_deref_mut() {
+                    t.observe(&record);
+                }
                 // 管线为空：零开销快速路径，所有记录都通过，不提前解析 meta。
                 // 管线非空：提前解析 meta，与管线过滤器共享，消除 FilterProcessor
                 //           内部的重复 parse_meta() 调用（对 pipeline_passthrough
                 //           场景可减少约 50% 的 parse_meta 调用次数）。
-                let (passes, cached_meta) = if pipeline.is_empty() {
+                // 会话补全需观察全部记录（含被过滤的），且在过滤之前生效
+                let (passes, cached_meta) = if pipeline.is_empty() && backfill.is_none() {
                     (true, None)
                 } else {
-                    let meta = record.parse_meta();
-                    let ok = pipeline.run_with_meta(&record, &meta);
+                    let mut meta = record.parse_meta();
+                    if let Some(b) = backfill.as_deref_mut() {
+                        b.apply(&mut meta);
+                    }
+                    let ok = pipeline.is_empty() || pipeline.run_with_meta(&record, &meta);
                     (ok, Some(meta))
                 };
+                clock.lap(Stage::Filter);
 
-                // PARAMS 记录（无 tag）在 do_normalize 时无论是否通过过滤都必须
+                // PARAMS 记录（无 tag）在 do_normalize / merge_params 时无论是否通过过滤都必须
                 // 更新 params_buffer，以便后续匹配 DML 记录能正确替换参数。
-                let needs_pm = passes || (do_normalize && record.tag.is_none());
+                let needs_pm = passes || ((do_normalize || merge_params) && record.tag.is_none());
                 if needs_pm {
                     // 无管线时首次解析 meta；有管线时复用已解析结果，零额外开销。
-                    let meta = cached_meta.unwrap_or_else(|| record.parse_meta());
+                    let mut meta = cached_meta.unwrap_or_else(|| record.parse_meta());
+                    // ID 编号在过滤之后替换，导出与 PARAMS 参数匹配都使用编号
+                    if let Some(o) = obfuscator.as_deref_mut() {
+                        o.apply(&mut meta);
+                    }
+                    // 大小写统一同样在过滤之后，配额、模板统计与导出使用统一后的用户名
+                    if let Some(c) = case {
+                        c.apply(&mut meta);
+                    }
 
                     if passes {
                         // DML 或通过过滤的 PARAMS：CSV 关闭性能指标时合成空 pm，
                         // 跳过 find_indicators_split（D-05/D-06）；SQL 字段来自 record.body()。
                         // 若 aggregator 存在，无论 include_pm 如何都需要真实的 exectime（CR-01）。
-                        let pm = if include_pm || aggregator.is_some() {
-                            record.parse_performance_metrics()
+                        let pm = if include_pm || aggregator.is_some() || routes_unmeasured {
+                            let mut pm = record.parse_performance_metrics();
+                            if crate::parser::coerce_exec_id(&record, &mut pm) {
+                                coerced_in_file += 1;
+                                log::debug!(
+                                    "{file_path} | record at {}: EXEC_ID coerced to {}",
+                                    record.ts,
+                                    pm.exec_id
+                                );
+                            }
+                            pm
                         } else {
                             dm_database_parser_sqllog::PerformanceMetrics {
                                 sql: record.body(),
@@ -197,6 +683,7 @@ fn process_log_file(
                                 exec_id: 0,
                             }
                         };
+                        clock.lap(Stage::Parse);
 
                         // SQL 记录级过滤：只对 DML 记录（有 tag）生效，PARAMS 记录始终通过。
                         // 被过滤掉的 DML 直接丢弃，不影响 params_buffer。
@@ -204,7 +691,19 @@ fn process_log_file(
                             .is_some_and(|f| record.tag.is_some() && !f.matches(pm.sql.as_ref()))
                         {
                             // 记录 SQL 内容不匹配，跳过导出
+                            clock.lap(Stage::Filter);
+                        } else if merge_params
+                            && record.tag.is_none()
+                            && crate::features::replace_parameters::record_params(
+                                &meta,
+                                pm.sql.as_ref(),
+                                params_buffer,
+                            )
+                        {
+                            // PARAMS 记录已并入参数缓冲区，随后的执行记录写入 `sql_params` 列
+                            clock.lap(Stage::Transform);
                         } else {
+                            clock.lap(Stage::Filter);
                             // 快速路径：params_buffer 为空且当前是 DML 记录（有 tag），
                             // 则不可能存在待替换参数，完全跳过 compute_normalized。
                             let ns = if do_normalize
@@ -222,6 +721,15 @@ fn process_log_file(
                                 None
                             };
 
+                            // 无性能指标记录（`[features.unmeasured]`）：排除的在此丢弃，
+                            // 单独输出的不计入模板统计，写入单独的导出器
+                            let unmeasured_record = routes_unmeasured
+                                && crate::features::unmeasured::is_unmeasured(&pm);
+                            if unmeasured_record && matches!(unmeasured, Unmeasured::Drop) {
+                                clock.lap(Stage::Filter);
+                                continue;
+                            }
+
                             // 先检查配额，再聚合（CR-02：避免对未导出记录计入统计）
                             if let Some(remaining) = limit {
                                 if records_in_file >= remaining {
@@ -229,12 +737,52 @@ fn process_log_file(
                                 }
                             }
 
+                            // 明文凭据在配额判定 / 模板聚合 / 导出之前抹去（`[features.redact]`）
+                            let pm = match redact
+                                .then(|| crate::features::redact_credentials(pm.sql.as_ref()))
+                                .flatten()
+                            {
+                                Some(sql) => dm_database_parser_sqllog::PerformanceMetrics {
+                                    sql: Cow::Owned(sql),
+                                    ..pm
+                                },
+                                None => pm,
+                            };
+                            let redacted_ns;
+                            let ns = match ns
+                                .filter(|_| redact)
+                                .and_then(crate::features::redact_credentials)
+                            {
+                                Some(s) => {
+                                    redacted_ns = s;
+                                    Some(redacted_ns.as_str())
+                                }
+                                None => ns,
+                            };
+
+                            // 跨文件重复的记录（`[features.dedup]`）不导出，也不占用配额、不计入模板统计
+                            if let Some(d) = dedup.as_deref_mut() {
+                                if d.is_duplicate(&record, &meta, &pm) {
+                                    exporter_manager.record_duplicate();
+                                    clock.lap(Stage::Filter);
+                                    continue;
+                                }
+                            }
+
+                            // 超出用户 / 指纹配额的记录计为跳过，同样不计入模板统计
+                            if let Some(q) = quota.as_deref_mut() {
+                                if !q.admit(meta.username.as_ref(), pm.sql.as_ref()) {
+                                    clock.lap(Stage::Filter);
+                                    continue;
+                                }
+                            }
+
                             // 模板聚合：仅对 DML 记录（有 tag）生效；PARAMS 记录不计入统计。
                             if let Some(ref mut agg) = aggregator {
                                 // 防御性检查：外层 `passes=true` 已隐含 DML 路径，
                                 // 但 needs_pm 也可对无 tag 的 PARAMS 记录成立（do_normalize 时）。
                                 // 此处显式排除 tag.is_none() 的记录，防止重构时意外计入 PARAMS。
-                                if record.tag.is_some() {
+                                if record.tag.is_some() && !unmeasured_record {
                                     let tmpl_key =
                                         crate::features::normalize_template(pm.sql.as_ref());
                                     let exectime_us = if pm.exectime.is_finite()
@@ -261,34 +809,102 @@ fn process_log_file(
                                 }
                             }
 
-                            exporter_manager.export_one_preparsed(&record, &meta, &pm, ns)?;
+                            // 语句登记：与模板聚合一致，只登记实际导出的 DML 记录
+                            let statement_id = statements
+                                .as_deref_mut()
+                                .filter(|_| record.tag.is_some())
+                                .map(|s| s.observe(pm.sql.as_ref(), record.ts.as_ref()));
+
+                            // 派生列基于原始 ts 计算，时区归一化只影响 ts 列本身
+                            if let Some(d) = derived {
+                                let session = if d.tracks_sessions() {
+                                    session_seq.next(&meta, record.ts.as_ref())
+                                } else {
+                                    SessionPos::default()
+                                };
+                                let seq = match d.seq_scope() {
+                                    Some(SeqScope::Session) => session.seq,
+                                    _ => records_in_file as u64 + 1,
+                                };
+                                let params = if merge_params {
+                                    crate::features::replace_parameters::bound_params(
+                                        &record,
+                                        &meta,
+                                        pm.sql.as_ref(),
+                                        params_buffer,
+                                    )
+                                } else {
+                                    None
+                                };
+                                let row = RowInfo {
+                                    recovered,
+                                    seq,
+                                    statement_id,
+                                    session: session.session,
+                                    params,
+                                };
+                                d.compute_into(&record, &meta, &pm, row, &mut extras);
+                            }
+                            // tag 的大小写只在导出时统一，参数替换等内部逻辑仍按达梦原值匹配
+                            let shifted_ts = ts_normalizer.is_some_and(|tn| {
+                                tn.normalize_into(record.ts.as_ref(), &mut ts_scratch)
+                            });
+                            let cased_tag = case.and_then(|c| c.tag(record.tag.as_deref()));
+                            let shifted;
+                            let out_record = if shifted_ts || cased_tag.is_some() {
+                                let mut r = record.clone();
+                                if shifted_ts {
+                                    r.ts = Cow::Borrowed(ts_scratch.as_str());
+                                }
+                                if let Some(tag) = cased_tag {
+                                    r.tag = Some(Cow::Owned(tag));
+                                }
+                                shifted = r;
+                                &shifted
+                            } else {
+                                &record
+                            };
+                            clock.lap(Stage::Transform);
+                            let target = match &mut unmeasured {
+                                Unmeasured::Separate(out) if unmeasured_record => {
+                                    out.records += 1;
+                                    &mut out.manager
+                                }
+                                _ => &mut *exporter_manager,
+                            };
+                            // 按时间分割的输出按原始日志时间确定周期，改写后的格式未必能解析
+                            if shifted_ts {
+                                target.set_source_ts(record.ts.as_ref());
+                            }
+                            if derived.is_some() {
+                                target.export_one_derived(out_record, &meta, &pm, ns, &extras)?;
+                            } else {
+                                target.export_one_preparsed(out_record, &meta, &pm, ns)?;
+                            }
                             records_in_file += 1;
                             pb_pending += 1;
+                            // 分块边界：导出器交出已写数据，草稿缓冲区收缩回初始容量
+                            if records_in_file % chunk_size == 0 {
+                                exporter_manager.flush_chunk()?;
+                                ns_scratch.shrink_to(4096);
+                                ts_scratch.shrink_to(64);
+                            }
+                            clock.lap(Stage::Write);
 
                             // 每 4096 条更新一次进度条（减少原子操作频率）
                             if pb_pending >= 4096 {
-                                pb.inc(pb_pending);
+                                pb.set_position(metrics.add(Counter::Exported, pb_pending));
                                 pb_pending = 0;
                             }
-
-                            // 每 1024 条检查一次中断信号
-                            if records_in_file.trailing_zeros() >= 10
-                                && interrupted.load(Ordering::Relaxed)
-                            {
-                                break 'outer;
-                            }
                         }
                     } else {
-                        // 被过滤掉的 PARAMS 记录（needs_pm 成立说明 do_normalize &&
-                        // record.tag.is_none() 为真）：对 PARAMS 记录而言
+                        // 被过滤掉的 PARAMS 记录（needs_pm 成立说明 do_normalize / merge_params
+                        // 且 record.tag.is_none() 为真）：对 PARAMS 记录而言
                         // pm.sql ≡ record.body()，直接复用，省去 parse_performance_metrics()。
-                        crate::features::compute_normalized(
-                            &record,
+                        crate::features::replace_parameters::record_params(
                             &meta,
                             record.body().as_ref(),
                             params_buffer,
-                            placeholder_override,
-                            ns_scratch,
                         );
                     }
                 }
@@ -296,25 +912,76 @@ fn process_log_file(
             Err(e) => {
                 errors_in_file += 1;
                 log::warn!("{file_path} | {e:?}");
+                let (kind, raw) = parse_error_kind(&e);
+                parse_errors.add(&kind, file_path, raw);
             }
         }
+        clock.lap(Stage::Transform);
     }
 
-    // 将剩余未上报的进度刷新到进度条
-    if pb_pending > 0 {
-        pb.inc(pb_pending);
+    // 文件结束同样是分块边界
+    exporter_manager.flush_chunk()?;
+    if let Unmeasured::Separate(out) = &mut unmeasured {
+        out.manager.flush_chunk()?;
     }
+    clock.lap(Stage::Write);
+
+    // 将剩余未上报的进度刷新到登记表与进度条
+    pb.set_position(metrics.add(Counter::Exported, pb_pending));
+    metrics.add(Counter::Parsed, seen as u64);
+    metrics.add(Counter::Errors, errors_in_file as u64);
+    metrics.add(Counter::Recovered, recovered_in_file as u64);
+    metrics.add(Counter::ExecIdCoerced, coerced_in_file);
+    metrics.add(Counter::Files, 1);
+    metrics.record_parse_errors(parse_errors);
+    #[allow(clippy::cast_possible_truncation)]
+    metrics.record_file(FileCounts {
+        index: file_index,
+        path: file_path.to_string(),
+        records: records_in_file as u64,
+        parsed: seen as u64,
+        errors: errors_in_file as u64,
+        recovered: recovered_in_file as u64,
+        elapsed_ms: file_start.elapsed().as_millis() as u64,
+    });
 
     let elapsed = file_start.elapsed().as_secs_f64();
     info!(
-        "File {file_path}: {records_in_file} records, {errors_in_file} errors, total {elapsed:.2}s",
+        "File {file_path}: {records_in_file} records, {errors_in_file} errors, {recovered_in_file} recovered, total {elapsed:.2}s",
     );
 
-    let errors_label = if errors_in_file > 0 {
+    let mut errors_label = if errors_in_file > 0 {
         color::yellow(format!(", {errors_in_file} errors"))
     } else {
         String::new()
     };
+    if recovered_in_file > 0 {
+        errors_label.push_str(&color::yellow(format!(", {recovered_in_file} recovered")));
+    }
+    if corruption.is_some() {
+        errors_label.push_str(&color::red(" (checksum mismatch)"));
+    }
+    if errors_in_file > 0 && corruption.is_none() {
+        warnings.push(
+            WarningKind::ParseErrors,
+            format!("{file_path}: {errors_in_file} records failed to parse"),
+        );
+    }
+    if coerced_in_file > 0 {
+        warnings.push(
+            WarningKind::ExecIdCoerced,
+            format!(
+                "{file_path}: {coerced_in_file} EXEC_ID values out of range or malformed, coerced"
+            ),
+        );
+    }
+    if records.held_tail() {
+        warnings.push(
+            WarningKind::IncompleteRecord,
+            format!("{file_path}: incomplete trailing record held back (still being written)"),
+        );
+        errors_label.push_str(&color::dim(", incomplete tail held back"));
+    }
     pb.println(format!(
         "{} [{file_index}/{total_files}] {file_path} — {}{errors_label}, {elapsed:.2}s",
         color::green("✓"),
@@ -331,10 +998,17 @@ fn process_log_file(
 ///
 /// 结果在文件内去重：同一事务 ID 可能出现在数百条记录中，
 /// 提前去重可显著减少跨文件合并时的中间数据量。
-fn scan_log_file_for_matches(file_path: &str, cfg: &Config) -> Vec<CompactString> {
+fn scan_log_file_for_matches(
+    file_path: &str,
+    cfg: &Config,
+    interrupted: &AtomicBool,
+) -> Vec<CompactString> {
     use rayon::prelude::*;
 
-    let Ok(parser) = LogParser::from_path(file_path) else {
+    if interrupted.load(Ordering::Relaxed) {
+        return Vec::new();
+    }
+    let Ok(parser) = open_log(Path::new(file_path)) else {
         return Vec::new();
     };
     let filters = match &cfg.features.filters {
@@ -376,6 +1050,7 @@ fn scan_for_trxids_by_transaction_filters(
     log_files: &[std::path::PathBuf],
     cfg: &Config,
     jobs: usize,
+    interrupted: &AtomicBool,
 ) -> AHashSet<CompactString> {
     use rayon::prelude::*;
 
@@ -394,7 +1069,7 @@ fn scan_for_trxids_by_transaction_filters(
     let matched: Vec<CompactString> = pool.install(|| {
         log_files
             .par_iter()
-            .flat_map(|file| scan_log_file_for_matches(&file.to_string_lossy(), cfg))
+            .flat_map(|file| scan_log_file_for_matches(&file.to_string_lossy(), cfg, interrupted))
             .collect()
     });
 
@@ -482,6 +1157,8 @@ fn process_csv_parallel(
     pipeline: &Pipeline,
     jobs: usize,
     pb: &ProgressBar,
+    metrics: &Metrics,
+    warnings: &Warnings,
     interrupted: &Arc<AtomicBool>,
     resume_state: Option<&crate::resume::ResumeState>,
     quiet: bool,
@@ -491,9 +1168,20 @@ fn process_csv_parallel(
     field_mask: FieldMask,
     ordered_indices: &[usize],
     sql_record_filter: Option<&CompiledSqlFilters>,
+    ts_normalizer: Option<&TsNormalizer>,
+    derived: Option<&DerivedColumns>,
+    profile: Option<&Mutex<StageProfile>>,
 ) -> Result<(Vec<(PathBuf, usize)>, usize, Option<TemplateAggregator>)> {
     use rayon::prelude::*;
 
+    let redact = cfg.features.redacts_credentials();
+    let case = CaseNormalizer::from_config(cfg.features.identifier_case.as_ref());
+    let parse_mode = ParseMode::parse(&cfg.sqllog.parse_mode)?;
+    let active_file = ActiveFilePolicy::from_config(&cfg.sqllog)?;
+    let verify_checksums = cfg.sqllog.verify_checksums;
+    let unmeasured = cfg.features.unmeasured_policy();
+    let chunk_size = cfg.pipeline.chunk_size;
+
     let csv_cfg = cfg
         .exporter
         .csv
@@ -502,30 +1190,10 @@ fn process_csv_parallel(
     let output_path = Path::new(&csv_cfg.file);
     let append_to_existing = csv_cfg.append && output_path.exists();
 
-    // 临时目录与最终输出文件相邻，避免跨设备 copy；
-    // 若父目录不可写（如 /dev/null），退回到系统临时目录。
-    let parts_dir = {
-        let stem = output_path
-            .file_stem()
-            .unwrap_or_default()
-            .to_string_lossy();
-        let dir_name = format!(".{stem}_parts_{}", std::process::id());
-        let preferred = output_path
-            .parent()
-            .filter(|p| !p.as_os_str().is_empty())
-            .unwrap_or(Path::new("."));
-        if let Some(parent) = output_path.parent().filter(|p| !p.as_os_str().is_empty()) {
-            std::fs::create_dir_all(parent)?;
-        }
-        let candidate = preferred.join(&dir_name);
-        if std::fs::create_dir_all(&candidate).is_ok() {
-            candidate
-        } else {
-            let fallback = std::env::temp_dir().join(&dir_name);
-            std::fs::create_dir_all(&fallback)?;
-            fallback
-        }
-    };
+    // 分片目录与最终输出文件相邻（见 `crate::temp::parts_dir`）；句柄 drop 时连同残留分片一起删除，
+    // 出错提前返回或 panic 时也不会留下孤儿目录
+    let parts_guard = crate::temp::parts_dir(output_path)?;
+    let parts_dir = parts_guard.path();
 
     let total_files = log_files.len();
 
@@ -567,6 +1235,12 @@ fn process_csv_parallel(
                 exporter.field_mask = field_mask;
                 exporter.ordered_indices = ordered_indices.to_vec();
                 exporter.include_performance_metrics = csv_cfg.include_performance_metrics;
+                exporter.exectime_format = csv_cfg.exectime_format();
+                exporter.dialect = TextDialect::from_config(csv_cfg);
+                exporter.extra_columns = derived.map(DerivedColumns::specs).unwrap_or_default();
+                if let Some(names) = &csv_cfg.column_order {
+                    exporter.apply_column_order(names)?;
+                }
                 let mut em = ExporterManager::from_csv(exporter);
                 em.initialize()?;
 
@@ -575,6 +1249,7 @@ fn process_csv_parallel(
 
                 // 每个 rayon 任务持有独立聚合器，主线程 merge（map-reduce 模式）
                 let mut task_agg = do_template.then(TemplateAggregator::new);
+                let mut task_profile = profile.map(|_| StageProfile::default());
 
                 let count = process_log_file(
                     &file.to_string_lossy(),
@@ -583,18 +1258,45 @@ fn process_csv_parallel(
                     &mut em,
                     pipeline,
                     pb,
+                    metrics,
+                    warnings,
                     None,
+                    chunk_size,
                     interrupted,
                     do_normalize,
                     task_agg.as_mut(),
                     placeholder_override,
                     &mut params_buf,
                     &mut ns_scratch,
-                    false, // 并行模式：不重置进度条，避免多线程互相重置计数
+                    false, // 并行模式：不切换进度条前缀，避免多线程互相覆盖
                     sql_record_filter,
+                    ts_normalizer,
+                    derived,
+                    None,
+                    None,
+                    None,
+                    None,
+                    &mut SessionSeq::default(),
+                    None,
+                    None,
+                    case.as_ref(),
+                    redact,
+                    parse_mode,
+                    active_file,
+                    verify_checksums,
+                    Unmeasured::without_output(unmeasured),
+                    task_profile.as_mut(),
                 )?;
 
+                let finalize_start = Instant::now();
                 em.finalize()?;
+                if let (Some(shared), Some(mut task_profile)) = (profile, task_profile) {
+                    task_profile.add(Stage::Write, finalize_start.elapsed());
+                    shared
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .merge(&task_profile);
+                }
                 Ok(Some((file.clone(), temp_path, count, task_agg)))
             })
             .collect()
@@ -624,10 +1326,7 @@ fn process_csv_parallel(
         }
     }
     if let Some(e) = first_err {
-        for (_, temp, _) in &parts_info {
-            let _ = std::fs::remove_file(temp);
-        }
-        let _ = std::fs::remove_dir_all(&parts_dir);
+        // 已写出的分片随 parts_guard drop 一并删除
         return Err(e);
     }
 
@@ -636,14 +1335,21 @@ fn process_csv_parallel(
         .iter()
         .map(|(_, temp, count)| (temp.clone(), *count))
         .collect();
+    let concat_start = Instant::now();
     let concat_result = concat_csv_parts(
         &parts_for_concat,
         output_path,
         csv_cfg.overwrite,
         append_to_existing,
     );
+    if let Some(shared) = profile {
+        shared
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .add(Stage::Write, concat_start.elapsed());
+    }
     // 无论拼接成功与否都清理临时目录，避免磁盘满等错误导致残留
-    let _ = std::fs::remove_dir_all(&parts_dir);
+    drop(parts_guard);
     // 拼接失败且非追加模式时，删除已部分写入的输出文件，避免遗留截断的 CSV
     if concat_result.is_err() && !append_to_existing {
         let _ = std::fs::remove_file(output_path);
@@ -661,137 +1367,551 @@ fn process_csv_parallel(
     ))
 }
 
-/// pre-scan 完成后重新编译 `CompiledMetaFilters`。
+/// 并发解析 + 单写线程导出（非 CSV 导出器，如 SQLite）：各文件在 rayon 线程上解析，
+/// 记录经有界通道（[`ConcurrentExporterManager`]）汇入独占导出器的写线程；
+/// 写线程跟不上时解析线程阻塞，内存占用受通道容量约束。不同文件的记录交错落盘。
 ///
-/// 若 `final_cfg` 含有 `features.filters.enable == true`，则从 `final_cfg` 重新编译以包含
-/// 预扫描发现的 trxids；否则直接回传原始值（`compiled_meta` 来自入参）。
-/// 回传原始值的情形：无 filters 配置、filters 禁用、或调用方传 None 时走 None 路径。
-fn recompile_meta_if_needed(
-    final_cfg: &Config,
-    original: Option<CompiledMetaFilters>,
-) -> Result<Option<CompiledMetaFilters>> {
-    let filters = match &final_cfg.features.filters {
-        Some(f) if f.enable => f,
-        _ => return Ok(original),
-    };
-    // 重新从 final_cfg 编译，以捕获 merge_found_trxids 写入的 trxids
-    let recompiled = crate::features::CompiledMetaFilters::try_from_meta(&filters.meta)?;
-    Ok(Some(recompiled))
-}
-
-pub fn handle_run(
-    cfg: &Config,
-    limit: Option<usize>,
-    dry_run: bool,
-    quiet: bool,
-    interrupted: &Arc<AtomicBool>,
-    progress_interval: u64,
-    resume: bool,
-    state_file_override: Option<&str>,
+/// 返回：`(已处理文件列表, 跳过文件数, 合并后聚合器, 已 finalize 的导出器)`。
+/// 适用条件与 [`process_csv_parallel`] 相同，只是导出器不是 CSV。
+fn process_queued_parallel(
+    log_files: &[PathBuf],
+    ctx: &RunContext<'_>,
     jobs: usize,
-    compiled_filters: Option<(CompiledMetaFilters, CompiledSqlFilters)>,
-) -> Result<()> {
-    // 拆分入参：build_pipeline 消费 meta（Move），sql 保留供后续使用
-    let (compiled_meta, compiled_sql) = match compiled_filters {
-        Some((m, s)) => (Some(m), Some(s)),
-        None => (None, None),
-    };
-
-    let total_start = Instant::now();
-    let log_files = SqllogParser::new(&cfg.sqllog.path).log_files()?;
-    if log_files.is_empty() {
-        warn!("No log files found");
-        return Ok(());
-    }
+    pb: &ProgressBar,
+    metrics: &Metrics,
+    warnings: &Warnings,
+    interrupted: &Arc<AtomicBool>,
+    resume_state: Option<&crate::resume::ResumeState>,
+    quiet: bool,
+    profile: Option<&Mutex<StageProfile>>,
+) -> Result<(
+    Vec<(PathBuf, usize)>,
+    usize,
+    Option<TemplateAggregator>,
+    ExporterManager,
+)> {
+    use rayon::prelude::*;
 
-    let state_path =
-        std::path::PathBuf::from(state_file_override.unwrap_or(&cfg.resume.state_file));
-    let mut resume_state = if resume {
-        let state = crate::resume::ResumeState::load(&state_path);
-        info!(
-            "Resume mode: state file {}, {} files previously processed",
-            state_path.display(),
-            state.processed_count()
-        );
-        Some(state)
-    } else {
-        None
-    };
+    let total_files = log_files.len();
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs)
+        .build()
+        .map_err(|e| Error::Io(std::io::Error::other(e)))?;
+    let queue = ConcurrentExporterManager::start(
+        ExporterManager::from_config(&ctx.cfg)?,
+        DEFAULT_QUEUE_BATCHES,
+    )?;
 
-    // 仅当有事务级过滤器时才克隆配置（避免常规路径的额外分配）
-    let owned_cfg;
-    let final_cfg: &Config = if cfg
-        .features
-        .filters
-        .as_ref()
-        .is_some_and(crate::features::FiltersFeature::has_transaction_filters)
-    {
-        let extra_trxids = scan_for_trxids_by_transaction_filters(&log_files, cfg, jobs);
-        let mut tmp = cfg.clone();
-        if let Some(f) = &mut tmp.features.filters {
-            // into_iter() yields CompactString; merge_found_trxids 接受 Vec<CompactString>
-            f.merge_found_trxids(extra_trxids.into_iter().collect());
-        }
-        owned_cfg = tmp;
-        &owned_cfg
-    } else {
-        cfg
-    };
+    type TaskResult = Option<(PathBuf, usize, Option<TemplateAggregator>)>;
+    let results: Vec<Result<TaskResult>> = pool.install(|| {
+        log_files
+            .par_iter()
+            .enumerate()
+            .map(|(idx, file)| {
+                if let Some(state) = resume_state {
+                    if state.is_processed(file) {
+                        if !quiet {
+                            pb.println(format!(
+                                "{} [{}/{}] {} — skipped (already processed)",
+                                color::dim("⏭"),
+                                idx + 1,
+                                total_files,
+                                file.display(),
+                            ));
+                        }
+                        return Ok(None);
+                    }
+                }
 
-    // pre-scan 完成后重新从 final_cfg 编译 compiled_meta，确保合并后的 trxids 生效。
-    // 若无事务级过滤器（final_cfg == cfg），则复用原始 compiled_meta，零额外开销。
-    let compiled_meta_for_pipeline = recompile_meta_if_needed(final_cfg, compiled_meta)?;
+                if interrupted.load(Ordering::Relaxed) {
+                    return Ok(None);
+                }
+
+                let mut em = queue.producer()?;
+                let mut params_buf = ParamBuffer::default();
+                let mut ns_scratch = Vec::with_capacity(4096);
+                let mut task_agg = ctx.do_template.then(TemplateAggregator::new);
+                let mut task_profile = profile.map(|_| StageProfile::default());
+
+                let count = process_log_file(
+                    &file.to_string_lossy(),
+                    idx + 1,
+                    total_files,
+                    &mut em,
+                    &ctx.pipeline,
+                    pb,
+                    metrics,
+                    warnings,
+                    None,
+                    ctx.chunk_size,
+                    interrupted,
+                    ctx.do_normalize,
+                    task_agg.as_mut(),
+                    ctx.placeholder_override,
+                    &mut params_buf,
+                    &mut ns_scratch,
+                    false, // 并行模式：不切换进度条前缀，避免多线程互相覆盖
+                    ctx.record_sql.as_ref(),
+                    ctx.ts_normalizer.as_ref(),
+                    ctx.derived.as_ref(),
+                    None,
+                    None,
+                    None,
+                    None,
+                    &mut SessionSeq::default(),
+                    None,
+                    None,
+                    ctx.case.as_ref(),
+                    ctx.redact,
+                    ctx.parse_mode,
+                    ctx.active_file,
+                    ctx.verify_checksums,
+                    Unmeasured::without_output(ctx.cfg.features.unmeasured_policy()),
+                    task_profile.as_mut(),
+                )?;
+
+                // 推送不足一批的剩余记录
+                let finalize_start = Instant::now();
+                em.finalize()?;
+                if let (Some(shared), Some(mut task_profile)) = (profile, task_profile) {
+                    task_profile.add(Stage::Write, finalize_start.elapsed());
+                    shared
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .merge(&task_profile);
+                }
+                Ok(Some((file.clone(), count, task_agg)))
+            })
+            .collect()
+    });
+
+    let mut processed = Vec::with_capacity(log_files.len());
+    let mut merged_agg: Option<TemplateAggregator> = None;
+    let mut first_err: Option<Error> = None;
+    let mut skipped = 0usize;
+    for result in results {
+        match result {
+            Ok(Some((file, count, task_agg))) => {
+                processed.push((file, count));
+                if let Some(task_agg) = task_agg {
+                    match &mut merged_agg {
+                        Some(base) => base.merge(task_agg),
+                        None => merged_agg = Some(task_agg),
+                    }
+                }
+            }
+            Ok(None) => skipped += 1,
+            Err(e) if first_err.is_none() => first_err = Some(e),
+            Err(_) => {}
+        }
+    }
+
+    // 等待写线程落盘并 finalize；写线程自身出错时，解析线程只会看到 WorkerStopped，
+    // 优先返回写线程的原始错误
+    let finish_start = Instant::now();
+    let finished = queue.finish();
+    if let Some(shared) = profile {
+        shared
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .add(Stage::Write, finish_start.elapsed());
+    }
+    if let Some(e) = first_err {
+        return Err(finished.err().unwrap_or(e));
+    }
+    Ok((processed, skipped, merged_agg, finished?))
+}
+
+/// pre-scan 完成后重新编译 `CompiledMetaFilters`。
+///
+/// 若 `final_cfg` 含有 `features.filters.enable == true`，则从 `final_cfg` 重新编译以包含
+/// 预扫描发现的 trxids；否则直接回传原始值（`compiled_meta` 来自入参）。
+/// 回传原始值的情形：无 filters 配置、filters 禁用、或调用方传 None 时走 None 路径。
+fn recompile_meta_if_needed(
+    final_cfg: &Config,
+    original: Option<CompiledMetaFilters>,
+) -> Result<Option<CompiledMetaFilters>> {
+    let filters = match &final_cfg.features.filters {
+        Some(f) if f.enable => f,
+        _ => return Ok(original),
+    };
+    // 重新从 final_cfg 编译，以捕获 merge_found_trxids 写入的 trxids
+    let recompiled = crate::features::CompiledMetaFilters::try_from_meta(&filters.meta)?;
+    Ok(Some(recompiled))
+}
 
-    let pipeline = build_pipeline(final_cfg, compiled_meta_for_pipeline);
+/// `handle_run` 与 `preview` 共用的运行期上下文：事务级预扫描后的最终配置、
+/// 过滤管线、参数替换与派生列等，保证预览与正式导出的记录处理完全一致。
+#[allow(clippy::struct_excessive_bools)]
+struct RunContext<'a> {
+    cfg: Cow<'a, Config>,
+    /// 本次运行的标识（见 `crate::run_id`），写入 `run_id` 列与装载记录
+    run_id: Arc<str>,
+    pipeline: Pipeline,
+    field_mask: FieldMask,
+    ordered_indices: Vec<usize>,
+    do_normalize: bool,
+    do_template: bool,
+    placeholder_override: Option<bool>,
+    record_sql: Option<CompiledSqlFilters>,
+    ts_normalizer: Option<TsNormalizer>,
+    derived: Option<DerivedColumns>,
+    /// 用户名 / tag 的大小写统一（`[features.identifier_case]`）
+    case: Option<CaseNormalizer>,
+    /// 导出前抹去明文凭据（`[features.redact]`，默认启用）
+    redact: bool,
+    parse_mode: ParseMode,
+    active_file: ActiveFilePolicy,
+    verify_checksums: bool,
+    /// 分块大小（`pipeline.chunk_size`）
+    chunk_size: usize,
+}
 
-    let field_mask = final_cfg.features.field_mask();
-    let ordered_indices = final_cfg.features.ordered_field_indices();
-    // 如果字段投影排除了 normalized_sql（字段 14），则禁用参数替换计算
-    let do_normalize = field_mask.includes_normalized_sql()
-        && final_cfg
+impl<'a> RunContext<'a> {
+    fn prepare(
+        cfg: &'a Config,
+        run_id: Arc<str>,
+        log_files: &[PathBuf],
+        compiled_meta: Option<CompiledMetaFilters>,
+        compiled_sql: Option<CompiledSqlFilters>,
+        jobs: usize,
+        interrupted: &Arc<AtomicBool>,
+    ) -> Result<Self> {
+        // 仅当有事务级过滤器时才克隆配置（避免常规路径的额外分配）
+        let final_cfg: Cow<'a, Config> = if cfg
             .features
+            .filters
+            .as_ref()
+            .is_some_and(crate::features::FiltersFeature::has_transaction_filters)
+        {
+            let extra_trxids =
+                scan_for_trxids_by_transaction_filters(log_files, cfg, jobs, interrupted);
+            // 预扫描期间被取消：trxid 集合不完整，此时尚未打开任何导出器，直接返回
+            if interrupted.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+            let mut tmp = cfg.clone();
+            if let Some(f) = &mut tmp.features.filters {
+                // into_iter() yields CompactString; merge_found_trxids 接受 Vec<CompactString>
+                f.merge_found_trxids(extra_trxids.into_iter().collect());
+            }
+            Cow::Owned(tmp)
+        } else {
+            Cow::Borrowed(cfg)
+        };
+
+        // pre-scan 完成后重新从 final_cfg 编译 compiled_meta，确保合并后的 trxids 生效。
+        // 若无事务级过滤器（final_cfg == cfg），则复用原始 compiled_meta，零额外开销。
+        let compiled_meta_for_pipeline = recompile_meta_if_needed(&final_cfg, compiled_meta)?;
+
+        let pipeline = build_pipeline(&final_cfg, compiled_meta_for_pipeline);
+
+        let features = &final_cfg.features;
+        let field_mask = features.field_mask();
+        let ordered_indices = features.ordered_field_indices();
+        // 如果字段投影排除了 normalized_sql（字段 14），则禁用参数替换计算
+        let do_normalize = field_mask.includes_normalized_sql()
+            && features
+                .replace_parameters
+                .as_ref()
+                .is_none_or(|r| r.enable);
+        let do_template = features
+            .template_analysis
+            .as_ref()
+            .is_some_and(|t| t.enabled);
+        let placeholder_override = features
             .replace_parameters
             .as_ref()
-            .is_none_or(|r| r.enable);
-    let do_template = final_cfg
-        .features
-        .template_analysis
-        .as_ref()
-        .is_some_and(|t| t.enabled);
-    let placeholder_override = final_cfg
-        .features
-        .replace_parameters
-        .as_ref()
-        .and_then(crate::features::ReplaceParametersConfig::placeholder_override);
-    let compiled_record_sql: Option<CompiledSqlFilters> = compiled_sql.filter(|_| {
-        final_cfg
-            .features
-            .filters
+            .and_then(crate::features::ReplaceParametersConfig::placeholder_override);
+        let record_sql: Option<CompiledSqlFilters> = compiled_sql.filter(|_| {
+            features
+                .filters
+                .as_ref()
+                .is_some_and(|f| f.enable && f.record_sql.has_filters())
+        });
+        let ts_normalizer = features
+            .time
             .as_ref()
-            .is_some_and(|f| f.enable && f.record_sql.has_filters())
-    });
-    let sql_record_filter = compiled_record_sql.as_ref();
+            .map(crate::features::TimeConfig::compile)
+            .transpose()?;
+        let mut derived = Some(DerivedColumns::from_config(features)?).filter(|d| !d.is_empty());
+        if let Some(d) = derived.as_mut() {
+            d.set_run_id(&run_id);
+        }
+        let case = CaseNormalizer::from_config(features.identifier_case.as_ref());
+        let redact = features.redacts_credentials();
+        let parse_mode = ParseMode::parse(&final_cfg.sqllog.parse_mode)?;
+        let active_file = ActiveFilePolicy::from_config(&final_cfg.sqllog)?;
+
+        Ok(Self {
+            run_id,
+            pipeline,
+            field_mask,
+            ordered_indices,
+            do_normalize,
+            do_template,
+            placeholder_override,
+            record_sql,
+            ts_normalizer,
+            derived,
+            case,
+            redact,
+            parse_mode,
+            active_file,
+            verify_checksums: final_cfg.sqllog.verify_checksums,
+            chunk_size: final_cfg.pipeline.chunk_size,
+            cfg: final_cfg,
+        })
+    }
+}
+
+/// 执行一次完整的解析 + 导出。
+///
+/// `interrupted` 为协作式取消标志：Ctrl+C 处理器或库调用方将其置为 `true` 后，
+/// 解析循环在下一批记录（1024 条）处停止，导出器照常 `finalize()`（刷新缓冲、提交事务），
+/// 随后返回 `Error::Interrupted`。
+pub fn handle_run(
+    cfg: &Config,
+    limit: Option<usize>,
+    dry_run: bool,
+    quiet: bool,
+    interrupted: &Arc<AtomicBool>,
+    progress_interval: u64,
+    resume: bool,
+    state_file_override: Option<&str>,
+    jobs: usize,
+    compiled_filters: Option<(CompiledMetaFilters, CompiledSqlFilters)>,
+) -> Result<RunSummary> {
+    handle_run_until(
+        cfg,
+        limit,
+        None,
+        false,
+        dry_run,
+        quiet,
+        interrupted,
+        progress_interval,
+        resume,
+        state_file_override,
+        jobs,
+        compiled_filters,
+    )
+}
+
+/// 同 [`handle_run`]，另带运行时长上限（`--max-duration`）与阶段计时（`--profile-stages`）。
+///
+/// 达到 `limit` 或 `max_duration` 时与中断一样在批次边界停止并 `finalize()` 导出器，
+/// 但正常返回摘要，`RunSummary::truncated` 注明原因；被截断的文件不记入断点续传状态。
+#[allow(clippy::fn_params_excessive_bools)]
+pub fn handle_run_until(
+    cfg: &Config,
+    limit: Option<usize>,
+    max_duration: Option<Duration>,
+    profile_stages: bool,
+    dry_run: bool,
+    quiet: bool,
+    interrupted: &Arc<AtomicBool>,
+    progress_interval: u64,
+    resume: bool,
+    state_file_override: Option<&str>,
+    jobs: usize,
+    compiled_filters: Option<(CompiledMetaFilters, CompiledSqlFilters)>,
+) -> Result<RunSummary> {
+    handle_run_as(
+        crate::run_id::begin(),
+        cfg,
+        limit,
+        max_duration,
+        profile_stages,
+        dry_run,
+        quiet,
+        interrupted,
+        progress_interval,
+        resume,
+        state_file_override,
+        jobs,
+        compiled_filters,
+    )
+}
+
+/// 同 [`handle_run_until`]，以调用方分配的运行标识执行（`run --watch` 的各轮共用一个标识）
+#[allow(clippy::fn_params_excessive_bools)]
+pub(crate) fn handle_run_as(
+    run_id: Arc<str>,
+    cfg: &Config,
+    limit: Option<usize>,
+    max_duration: Option<Duration>,
+    profile_stages: bool,
+    dry_run: bool,
+    quiet: bool,
+    interrupted: &Arc<AtomicBool>,
+    progress_interval: u64,
+    resume: bool,
+    state_file_override: Option<&str>,
+    jobs: usize,
+    compiled_filters: Option<(CompiledMetaFilters, CompiledSqlFilters)>,
+) -> Result<RunSummary> {
+    // 调用方的中断标志；处理循环改看看门狗的停止标志（同时转发中断）
+    let cancelled = interrupted;
+    let watchdog = max_duration.map(|d| Watchdog::start(d, cancelled));
+    let interrupted = watchdog.as_ref().map_or(cancelled, |w| &w.stop);
+    // 拆分入参：build_pipeline 消费 meta（Move），sql 保留供后续使用
+    let (compiled_meta, compiled_sql) = match compiled_filters {
+        Some((m, s)) => (Some(m), Some(s)),
+        None => (None, None),
+    };
+
+    let total_start = Instant::now();
+    // 非致命问题汇总到完成摘要（见 `crate::warnings`）
+    let warnings = Warnings::default();
+    let log_files = SqllogParser::from_config(&cfg.sqllog)?.log_files_reporting(&warnings)?;
+    if log_files.is_empty() {
+        warnings.push(
+            WarningKind::Input,
+            format!("No log files found in {}", cfg.sqllog.path),
+        );
+        return Ok(RunSummary {
+            run_id: run_id.to_string(),
+            dry_run,
+            elapsed: total_start.elapsed(),
+            warnings: warnings.into_vec(),
+            ..RunSummary::default()
+        });
+    }
+
+    // 标准输入没有稳定的文件指纹，无法断点续传
+    let from_stdin = crate::parser::input::is_stdin(Path::new(&cfg.sqllog.path));
+    if resume && from_stdin {
+        warnings.push(
+            WarningKind::Input,
+            "--resume is ignored when reading from stdin",
+        );
+    }
+    // 同时配置多个导出器时只使用优先级最高的一个（CSV > SQLite > Elasticsearch > custom）
+    if !dry_run {
+        if let [active, ignored @ ..] = cfg.exporter.configured().as_slice() {
+            for name in ignored {
+                warnings.push(
+                    WarningKind::ExporterIgnored,
+                    format!(
+                        "exporter.{name} is configured but ignored: exporter.{active} takes precedence"
+                    ),
+                );
+            }
+        }
+    }
+    let resume = resume && !from_stdin;
+    let state_path =
+        std::path::PathBuf::from(state_file_override.unwrap_or(&cfg.resume.state_file));
+    let mut resume_state = if resume {
+        let state = crate::resume::ResumeState::load(&state_path);
+        info!(
+            "Resume mode: state file {}, {} files previously processed",
+            state_path.display(),
+            state.processed_count()
+        );
+        Some(state)
+    } else {
+        None
+    };
+
+    let ctx = RunContext::prepare(
+        cfg,
+        run_id,
+        &log_files,
+        compiled_meta,
+        compiled_sql,
+        jobs,
+        interrupted,
+    )?;
+    let final_cfg: &Config = &ctx.cfg;
+    let pipeline = &ctx.pipeline;
+    let field_mask = ctx.field_mask;
+    let ordered_indices = &ctx.ordered_indices;
+    let do_normalize = ctx.do_normalize;
+    let do_template = ctx.do_template;
+    let placeholder_override = ctx.placeholder_override;
+    let sql_record_filter = ctx.record_sql.as_ref();
+    let ts_normalizer = &ctx.ts_normalizer;
+    let derived = &ctx.derived;
 
+    // CSV 写到 stdout 时抑制进度条与完成提示，保证管道下游只收到数据
+    let csv_to_stdout = final_cfg
+        .exporter
+        .csv
+        .as_ref()
+        .is_some_and(|c| crate::exporter::is_stdout(Path::new(&c.file)));
+    let quiet = quiet || csv_to_stdout;
     let pb = make_progress_bar(quiet, progress_interval);
-    let mut total_records = 0usize;
+    // 进度条、完成摘要与导出统计共用的计数登记表
+    let metrics = Metrics::default();
+    let exported = || usize::try_from(metrics.get(Counter::Exported)).unwrap_or(usize::MAX);
     let mut skipped_files = 0usize;
+    let templates;
+    let mut exporters = Vec::new();
+    let mut quota = QuotaTracker::from_config(final_cfg.features.quota.as_ref());
+    let mut dedup = Deduplicator::from_config(final_cfg.features.dedup.as_ref());
+    let mut exec_ids = ExecIdTracker::from_config(final_cfg.features.exec_id_audit.as_ref());
+    let mut statements = StatementRegistry::from_config(final_cfg.features.statements.as_ref());
+    let mut backfill = SessionBackfill::from_config(final_cfg.features.session_backfill.as_ref());
+    let mut obfuscator = IdObfuscator::from_config(final_cfg.features.obfuscate_ids.as_ref());
+    let mut session_seq = derived
+        .as_ref()
+        .map(DerivedColumns::sessions)
+        .unwrap_or_default();
+    // 运行报告同样记录阶段耗时；分解表只在 --profile-stages 时打印
+    let mut stages = (profile_stages || final_cfg.report.is_some()).then(StageProfile::default);
 
-    // 并行 CSV 路径：多文件 + 无 limit + CSV 导出器 + jobs > 1
-    let use_parallel = !dry_run
+    // 并行路径：多文件 + 无 limit + jobs > 1（`pipeline.workers` / `--jobs`）
+    // 时间模式输出需按记录顺序滚动切换文件、stdout / TCP 输出无法合并分片，只走顺序路径；
+    // 配额按记录出现顺序先到先得、去重需跨文件比对、拆分导出需逐条分流到各分片文件，同样只走顺序路径；
+    // `EXEC_ID` 审计的序列、会话拼接（会话级 `seq` / `session_id`）、会话补全、ID 编号与语句登记表跨文件延续，也只走顺序路径；
+    // 无性能指标记录单独输出（`features.unmeasured.policy = "separate"`）写入第二个导出器，同样只走顺序路径
+    let csv_to_stream = final_cfg
+        .exporter
+        .csv
+        .as_ref()
+        .is_some_and(|c| crate::exporter::sink::is_stream(Path::new(&c.file)));
+    let parallel_ok = !dry_run
+        && !csv_to_stream
+        && quota.is_none()
+        && dedup.is_none()
+        && exec_ids.is_none()
+        && statements.is_none()
+        && backfill.is_none()
+        && obfuscator.is_none()
+        && final_cfg.features.unmeasured_policy() != UnmeasuredPolicy::Separate
+        && derived.as_ref().is_none_or(|d| !d.tracks_sessions())
         && jobs > 1
         && log_files.len() > 1
-        && limit.is_none()
-        && final_cfg.exporter.csv.is_some();
+        && limit.is_none();
+    // CSV：各文件写分片后按顺序拼接（拼接时需跳过各分片的 header，压缩 / 分段输出只走顺序路径；
+    // `on_error` 跳过写入失败时需在单个导出器上统计跳过数，同样只走顺序路径）
+    let use_parallel = parallel_ok
+        && final_cfg.exporter.csv.as_ref().is_some_and(|c| {
+            c.split_by.is_none()
+                && c.on_error == "abort"
+                && c.compression.is_none()
+                && c.rotation_limits() == (None, None)
+                && !crate::exporter::rollover::is_time_pattern(&c.file)
+        });
+    // 其他导出器（SQLite）：并发解析，经有界队列汇入单个写线程。不同文件的记录会交错写入，
+    // 只在显式设置线程数（`pipeline.workers` / `--jobs`）时启用，默认保持按文件顺序写入
+    let use_queue =
+        parallel_ok && final_cfg.exporter.csv.is_none() && final_cfg.pipeline.workers.is_some();
 
     if use_parallel {
         info!("Parsing and exporting SQL logs (parallel, {jobs} jobs)...");
 
+        let shared_stages = stages.take().map(Mutex::new);
         let (processed_files, parallel_skipped, parallel_agg) = process_csv_parallel(
             &log_files,
             final_cfg,
-            &pipeline,
+            pipeline,
             jobs,
             &pb,
+            &metrics,
+            &warnings,
             interrupted,
             resume_state.as_ref(),
             quiet,
@@ -799,11 +1919,24 @@ pub fn handle_run(
             do_template,
             placeholder_override,
             field_mask,
-            &ordered_indices,
+            ordered_indices,
             sql_record_filter,
+            ts_normalizer.as_ref(),
+            derived.as_ref(),
+            shared_stages.as_ref(),
         )?;
+        stages = shared_stages.map(|m| {
+            let mut p = m.into_inner().unwrap_or_else(PoisonError::into_inner);
+            p.exporter = "CSV".to_string();
+            p
+        });
+        exporters.push(ExporterStats::new(
+            final_cfg,
+            "CSV",
+            metrics.get(Counter::Exported),
+            None,
+        ));
 
-        total_records = processed_files.iter().map(|(_, c)| *c).sum();
         skipped_files = parallel_skipped;
 
         if let Some(ref agg) = parallel_agg {
@@ -823,6 +1956,7 @@ pub fn handle_run(
                 crate::exporter::csv::write_companion_rows(&companion, stats)?;
             }
         }
+        templates = template_stats.unwrap_or_default();
 
         // 更新断点续传状态（并行路径完成后统一写入）。
         // 若被中断则不写入：并行任务无法区分"完整处理"与"中途截断"，
@@ -835,6 +1969,70 @@ pub fn handle_run(
                 state.save(&state_path)?;
             }
         }
+    } else if use_queue {
+        info!("Parsing SQL logs with {jobs} workers, exporting through a single writer...");
+
+        let shared_stages = stages.take().map(Mutex::new);
+        let (processed_files, queue_skipped, queue_agg, mut exporter_manager) =
+            process_queued_parallel(
+                &log_files,
+                &ctx,
+                jobs,
+                &pb,
+                &metrics,
+                &warnings,
+                interrupted,
+                resume_state.as_ref(),
+                quiet,
+                shared_stages.as_ref(),
+            )?;
+        stages = shared_stages.map(|m| {
+            let mut p = m.into_inner().unwrap_or_else(PoisonError::into_inner);
+            p.exporter = exporter_manager.name().to_string();
+            p
+        });
+        skipped_files = queue_skipped;
+
+        if let Some(ref agg) = queue_agg {
+            if let Some(charts_cfg) = final_cfg.features.charts.as_ref() {
+                crate::charts::generate_charts(agg, charts_cfg)?;
+            }
+        }
+        if !quiet {
+            exporter_manager.log_stats(&metrics.snapshot());
+        }
+        exporters.push(ExporterStats::new(
+            final_cfg,
+            exporter_manager.name(),
+            metrics.get(Counter::Exported),
+            exporter_manager.stats_snapshot(),
+        ));
+
+        let template_stats = queue_agg.map(TemplateAggregator::finalize);
+        if let Some(ref stats) = template_stats {
+            info!("Template analysis: {} unique templates", stats.len());
+            exporter_manager.write_template_stats(stats, None)?;
+        }
+        templates = template_stats.unwrap_or_default();
+        write_load_record(
+            &mut exporter_manager,
+            final_cfg,
+            &ctx.run_id,
+            processed_files.iter().map(|(file, _)| file),
+            &metrics.snapshot(),
+            total_start.elapsed(),
+            !interrupted.load(Ordering::Relaxed),
+        )?;
+
+        // 与并行 CSV 路径一致：被中断时不标记任何文件为已完成
+        if !interrupted.load(Ordering::Relaxed) {
+            if let Some(state) = &mut resume_state {
+                for (file, count) in &processed_files {
+                    state.mark_processed(file, *count as u64)?;
+                }
+                state.save(&state_path)?;
+            }
+        }
     } else {
         // 顺序路径
         let mut exporter_manager = if dry_run {
@@ -850,6 +2048,14 @@ pub fn handle_run(
             info!("Parsing and exporting SQL logs...");
         }
 
+        // `[features.unmeasured] policy = "separate"` 的单独输出（dry-run 时不写）
+        let unmeasured_policy = final_cfg.features.unmeasured_policy();
+        let mut unmeasured_out = if dry_run {
+            None
+        } else {
+            UnmeasuredOutput::from_config(final_cfg)?
+        };
+
         // 跨文件复用分配：process_log_file 在每次调用时 clear() 而不是重建
         let mut params_buffer = ParamBuffer::default();
         // 预分配 1024 字节：避免首条参数化 SQL 触发初始堆分配
@@ -857,13 +2063,15 @@ pub fn handle_run(
 
         // 模板聚合器：do_template=true 时创建，Phase 14 负责将 finalize() 结果写出
         let mut template_agg = do_template.then(TemplateAggregator::new);
+        // 本次实际处理的文件（不含 --resume 跳过的），写入装载记录
+        let mut loaded_files: Vec<&PathBuf> = Vec::with_capacity(log_files.len());
 
         for (idx, log_file) in log_files.iter().enumerate() {
             if interrupted.load(Ordering::Relaxed) {
                 break;
             }
 
-            let remaining = limit.map(|l| l.saturating_sub(total_records));
+            let remaining = limit.map(|l| l.saturating_sub(exported()));
             if remaining == Some(0) {
                 break;
             }
@@ -887,28 +2095,54 @@ pub fn handle_run(
                 idx + 1,
                 log_files.len(),
                 &mut exporter_manager,
-                &pipeline,
+                pipeline,
                 &pb,
+                &metrics,
+                &warnings,
                 remaining,
+                ctx.chunk_size,
                 interrupted,
                 do_normalize,
                 template_agg.as_mut(),
                 placeholder_override,
                 &mut params_buffer,
                 &mut ns_scratch,
-                true, // 顺序模式：每个文件开始时重置进度条
+                true, // 顺序模式：每个文件开始时切换进度条前缀
                 sql_record_filter,
+                ts_normalizer.as_ref(),
+                derived.as_ref(),
+                quota.as_mut(),
+                dedup.as_mut(),
+                exec_ids.as_mut(),
+                statements.as_mut(),
+                &mut session_seq,
+                backfill.as_mut(),
+                obfuscator.as_mut(),
+                ctx.case.as_ref(),
+                ctx.redact,
+                ctx.parse_mode,
+                ctx.active_file,
+                ctx.verify_checksums,
+                unmeasured_out
+                    .as_mut()
+                    .map_or(Unmeasured::without_output(unmeasured_policy), |out| {
+                        Unmeasured::Separate(out)
+                    }),
+                stages.as_mut(),
             )?;
+            loaded_files.push(log_file);
 
-            if !dry_run {
+            // 中途停止（中断 / 时限 / 记录上限）的文件未处理完，不记入断点续传状态
+            let cut_short =
+                interrupted.load(Ordering::Relaxed) || remaining.is_some_and(|r| processed >= r);
+            if !dry_run && !cut_short {
                 if let Some(state) = &mut resume_state {
                     state.mark_processed(log_file, processed as u64)?;
                     state.save(&state_path)?;
                 }
             }
 
-            total_records += processed;
-            if limit.is_some_and(|l| total_records >= l) {
+            if limit.is_some_and(|l| exported() >= l) {
                 break;
             }
         }
@@ -919,9 +2153,46 @@ pub fn handle_run(
             }
         }
 
+        let finalize_start = Instant::now();
         exporter_manager.finalize()?;
+        if let Some(out) = &mut unmeasured_out {
+            out.manager.finalize()?;
+            info!(
+                "Unmeasured records: {} written to {}",
+                out.records, out.file
+            );
+        }
+        if let Some(p) = stages.as_mut() {
+            p.add(Stage::Write, finalize_start.elapsed());
+            p.exporter = exporter_manager.name().to_string();
+        }
         if !quiet {
-            exporter_manager.log_stats();
+            exporter_manager.log_stats(&metrics.snapshot());
+        }
+        exporters.push(ExporterStats::new(
+            final_cfg,
+            exporter_manager.name(),
+            metrics.get(Counter::Exported),
+            exporter_manager.stats_snapshot(),
+        ));
+        if let Some(out) = &unmeasured_out {
+            exporters.push(ExporterStats {
+                name: "CSV (unmeasured)".to_string(),
+                target: out.file.clone(),
+                exported: out.records,
+                flush_operations: out
+                    .manager
+                    .stats_snapshot()
+                    .map(|s| s.flush_operations as u64),
+                duplicates: 0,
+                flush_latency: out
+                    .manager
+                    .stats_snapshot()
+                    .map(|s| s.flush_latency)
+                    .filter(|l| l.count > 0),
+                skipped: 0,
+                failed: 0,
+            });
         }
 
         // Phase 14 将消费 finalize() 结果并写出报告；此处先记录聚合摘要。
@@ -930,66 +2201,1250 @@ pub fn handle_run(
             info!("Template analysis: {} unique templates", stats.len());
             exporter_manager.write_template_stats(stats, None)?;
         }
+        templates = template_stats.unwrap_or_default();
+        let complete = !interrupted.load(Ordering::Relaxed) && limit.is_none_or(|l| exported() < l);
+        write_load_record(
+            &mut exporter_manager,
+            final_cfg,
+            &ctx.run_id,
+            loaded_files,
+            &metrics.snapshot(),
+            total_start.elapsed(),
+            complete,
+        )?;
+    }
+
+    if let Some(b) = &backfill {
+        let (appname, client_ip) = b.filled();
+        info!("Session backfill: {appname} appname, {client_ip} client_ip values filled");
+    }
+    if let Some(o) = &obfuscator {
+        let counts: Vec<String> = o
+            .counts()
+            .iter()
+            .map(|(field, n)| format!("{n} {field}"))
+            .collect();
+        info!("ID obfuscation: {} values numbered", counts.join(", "));
+        if !dry_run {
+            if let Some(file) = o.write_dictionary()? {
+                info!("ID obfuscation dictionary written to {file}");
+            }
+        }
+    }
+
+    let statements = statements
+        .map(StatementRegistry::finalize)
+        .unwrap_or_default();
+    let statements_file = final_cfg
+        .features
+        .statements
+        .as_ref()
+        .filter(|s| s.enabled && !dry_run)
+        .and_then(|s| s.file.as_deref());
+    if let Some(file) = statements_file {
+        crate::features::statements::write_statements_csv(Path::new(file), &statements)?;
     }
 
     pb.finish_and_clear();
 
+    let total_records = exported();
+    let truncated = if watchdog.as_ref().is_some_and(Watchdog::expired) {
+        max_duration.map(Truncation::MaxDuration)
+    } else {
+        limit
+            .filter(|l| total_records >= *l)
+            .map(Truncation::MaxRecords)
+    };
+    if let Some(reason) = truncated {
+        warnings.push(
+            WarningKind::Truncated,
+            format!("stopped early: {reason} — output is partial"),
+        );
+    }
+    for e in exporters.iter().filter(|e| e.skipped > 0) {
+        warnings.push(
+            WarningKind::ExportSkipped,
+            format!(
+                "{}: {} records skipped after write errors (on_error) — output is incomplete",
+                e.name, e.skipped
+            ),
+        );
+    }
+    for e in exporters.iter().filter(|e| e.failed > 0) {
+        warnings.push(
+            WarningKind::ExportSkipped,
+            format!(
+                "{}: {} records failed to write and were logged (on_error = log) — output is incomplete",
+                e.name, e.failed
+            ),
+        );
+    }
+    if skipped_files > 0 {
+        warnings.push(
+            WarningKind::SkippedFile,
+            format!("{skipped_files} files skipped (already processed, --resume)"),
+        );
+    }
+    let summary = RunSummary {
+        run_id: ctx.run_id.to_string(),
+        files: log_files.len(),
+        skipped_files,
+        records: total_records,
+        elapsed: total_start.elapsed(),
+        dry_run,
+        parallel: use_parallel || use_queue,
+        templates,
+        quota_skipped: quota
+            .as_ref()
+            .map(QuotaTracker::skipped_by_user)
+            .unwrap_or_default(),
+        exec_id_audit: exec_ids.as_ref().map(ExecIdTracker::report),
+        statements,
+        truncated,
+        stages,
+        metrics: metrics.snapshot(),
+        warnings: warnings.into_vec(),
+        tuning: None,
+        per_file: metrics.file_counts(),
+        parse_errors: metrics.parse_errors(),
+        exporters,
+    };
+
     if !quiet {
-        let elapsed = total_start.elapsed().as_secs_f64();
+        let elapsed = summary.elapsed.as_secs_f64();
         let mode_label = if dry_run {
             " [dry-run]"
-        } else if use_parallel {
+        } else if summary.parallel {
             " [parallel]"
         } else {
             ""
         };
-        let skip_label = if skipped_files > 0 {
+        let mut counts_label = if skipped_files > 0 {
             format!(", {} skipped", color::dim(HumanCount(skipped_files as u64)))
         } else {
             String::new()
         };
+        if summary.metrics.errors > 0 {
+            counts_label.push_str(&color::yellow(format!(
+                ", {} errors",
+                HumanCount(summary.metrics.errors)
+            )));
+        }
+        if summary.metrics.exec_id_coerced > 0 {
+            counts_label.push_str(&color::yellow(format!(
+                ", {} exec_id coerced",
+                HumanCount(summary.metrics.exec_id_coerced)
+            )));
+        }
         eprintln!(
-            "\n{} SQL Log Export Task Completed{mode_label} in {elapsed:.2}s — {} records total{skip_label}",
+            "\n{} SQL Log Export Task Completed{mode_label} in {elapsed:.2}s — {} records total{counts_label}",
             color::green("✓"),
             color::green(HumanCount(total_records as u64)),
         );
-    }
-
-    if interrupted.load(Ordering::Relaxed) {
-        return Err(Error::Interrupted);
-    }
-    Ok(())
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::config::Config;
-
+        eprintln!("  run id: {}", color::dim(&summary.run_id));
+        if !summary.warnings.is_empty() {
+            eprintln!("{}", format_warnings(&summary.warnings));
+        }
+        if !summary.parse_errors.is_empty() {
+            eprintln!("{}", format_parse_errors(&summary.parse_errors));
+        }
+        if !summary.quota_skipped.is_empty() {
+            eprintln!("{}", format_quota_skipped(&summary.quota_skipped));
+        }
+        for e in &summary.exporters {
+            if let Some(latency) = &e.flush_latency {
+                eprintln!(
+                    "  {}",
+                    color::dim(format!("{} flush latency: {latency}", e.name))
+                );
+            }
+        }
+        let duplicates: u64 = summary.exporters.iter().map(|e| e.duplicates).sum();
+        if duplicates > 0 {
+            eprintln!(
+                "{} dedup: {} duplicate records skipped",
+                color::green("✓"),
+                HumanCount(duplicates)
+            );
+        }
+        for e in summary.exporters.iter().filter(|e| e.skipped > 0) {
+            eprintln!(
+                "{} {}: {} records skipped after write errors (on_error)",
+                color::yellow("⚠"),
+                e.name,
+                HumanCount(e.skipped)
+            );
+        }
+        for e in summary.exporters.iter().filter(|e| e.failed > 0) {
+            eprintln!(
+                "{} {}: {} records failed to write, logged to the app log (on_error = log)",
+                color::yellow("⚠"),
+                e.name,
+                HumanCount(e.failed)
+            );
+        }
+        if let Some(audit) = &summary.exec_id_audit {
+            eprintln!("{}", format_exec_id_audit(audit));
+        }
+        if final_cfg
+            .features
+            .statements
+            .as_ref()
+            .is_some_and(|s| s.enabled)
+        {
+            let target = statements_file.map_or_else(String::new, |f| format!(" → {f}"));
+            eprintln!(
+                "{} statements: {} distinct fingerprints{target}",
+                color::green("✓"),
+                HumanCount(summary.statements.len() as u64)
+            );
+        }
+    }
+    // 显式请求的分解表不受 --quiet 影响（stderr，不干扰 stdout 输出）
+    if let Some(p) = summary.stages.as_ref().filter(|_| profile_stages) {
+        p.print();
+    }
+
+    if cancelled.load(Ordering::Relaxed) {
+        return Err(Error::Interrupted);
+    }
+    Ok(summary)
+}
+
+/// `preview` 收集到的记录（列顺序与正式导出一致）
+#[derive(Debug, Clone, Default)]
+pub struct PreviewRows {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+    /// 为收集满 `limit` 条实际打开的日志文件数
+    pub files_scanned: usize,
+}
+
+/// 为 `preview` 收集前 `limit` 条记录：过滤、参数替换、时区归一化与派生列的处理与
+/// `handle_run` 完全一致（共用 `RunContext` 与 `process_log_file`），但不写任何输出。
+pub fn collect_preview(
+    cfg: &Config,
+    limit: usize,
+    jobs: usize,
+    interrupted: &Arc<AtomicBool>,
+    compiled_filters: Option<(CompiledMetaFilters, CompiledSqlFilters)>,
+) -> Result<PreviewRows> {
+    let (compiled_meta, compiled_sql) = match compiled_filters {
+        Some((m, s)) => (Some(m), Some(s)),
+        None => (None, None),
+    };
+    let log_files = SqllogParser::from_config(&cfg.sqllog)?.log_files()?;
+    let ctx = RunContext::prepare(
+        cfg,
+        crate::run_id::current(),
+        &log_files,
+        compiled_meta,
+        compiled_sql,
+        jobs,
+        interrupted,
+    )?;
+
+    let mut exporter_manager = ExporterManager::preview(&ctx.cfg)?;
+    exporter_manager.initialize()?;
+    let mut quota = QuotaTracker::from_config(ctx.cfg.features.quota.as_ref());
+    let mut dedup = Deduplicator::from_config(ctx.cfg.features.dedup.as_ref());
+    let mut backfill = SessionBackfill::from_config(ctx.cfg.features.session_backfill.as_ref());
+    let mut obfuscator = IdObfuscator::from_config(ctx.cfg.features.obfuscate_ids.as_ref());
+    let mut session_seq = ctx
+        .derived
+        .as_ref()
+        .map(DerivedColumns::sessions)
+        .unwrap_or_default();
+    let pb = ProgressBar::hidden();
+    let mut params_buffer = ParamBuffer::default();
+    let mut ns_scratch: Vec<u8> = Vec::with_capacity(4096);
+    let mut total = 0usize;
+    let mut files_scanned = 0usize;
+    for (idx, log_file) in log_files.iter().enumerate() {
+        if total >= limit || interrupted.load(Ordering::Relaxed) {
+            break;
+        }
+        files_scanned += 1;
+        total += process_log_file(
+            &log_file.to_string_lossy(),
+            idx + 1,
+            log_files.len(),
+            &mut exporter_manager,
+            &ctx.pipeline,
+            &pb,
+            &Metrics::default(),
+            &Warnings::default(),
+            Some(limit - total),
+            ctx.chunk_size,
+            interrupted,
+            ctx.do_normalize,
+            None,
+            ctx.placeholder_override,
+            &mut params_buffer,
+            &mut ns_scratch,
+            false,
+            ctx.record_sql.as_ref(),
+            ctx.ts_normalizer.as_ref(),
+            ctx.derived.as_ref(),
+            quota.as_mut(),
+            dedup.as_mut(),
+            None,
+            None,
+            &mut session_seq,
+            backfill.as_mut(),
+            obfuscator.as_mut(),
+            ctx.case.as_ref(),
+            ctx.redact,
+            ctx.parse_mode,
+            ctx.active_file,
+            ctx.verify_checksums,
+            Unmeasured::without_output(ctx.cfg.features.unmeasured_policy()),
+            None,
+        )?;
+    }
+    exporter_manager.finalize()?;
+
+    if interrupted.load(Ordering::Relaxed) {
+        return Err(Error::Interrupted);
+    }
+    let (columns, rows) = exporter_manager.take_preview().unwrap_or_default();
+    Ok(PreviewRows {
+        columns,
+        rows,
+        files_scanned,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_include_performance_metrics_false_csv_excludes_pm_columns() {
+        // 集成测试：include_performance_metrics=false 时 CSV header 不含性能指标列
+        let dir = tempfile::TempDir::new().unwrap();
+        let log_path = dir.path().join("t.log");
+        std::fs::write(
+            &log_path,
+            "2025-01-15 10:30:28.001 (EP[0] sess:0x0001 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n",
+        )
+        .unwrap();
+        let csv_path = dir.path().join("out.csv");
+        let error_log = dir.path().join("errors.log");
+        let app_log = dir.path().join("app.log");
+
+        let toml = format!(
+            "[sqllog]\ndirectory = \"{logdir}\"\n[error]\nfile = \"{errlog}\"\n[logging]\nfile = \"{applog}\"\nlevel = \"warn\"\nretention_days = 1\n[exporter.csv]\nfile = \"{csv}\"\noverwrite = true\nappend = false\ninclude_performance_metrics = false\n",
+            logdir = dir.path().to_string_lossy().replace('\\', "/"),
+            errlog = error_log.to_string_lossy().replace('\\', "/"),
+            applog = app_log.to_string_lossy().replace('\\', "/"),
+            csv = csv_path.to_string_lossy().replace('\\', "/"),
+        );
+        let cfg: Config = toml::from_str(&toml).unwrap();
+
+        handle_run(
+            &cfg,
+            None,
+            false,
+            true,
+            &Arc::new(AtomicBool::new(false)),
+            80,
+            false,
+            None,
+            1,
+            None, // compiled_filters
+        )
+        .unwrap();
+
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        let header = content.lines().next().unwrap();
+        assert!(
+            !header.contains("exec_time_ms"),
+            "header should skip exec_time_ms: {header}"
+        );
+        assert!(
+            !header.contains("row_count"),
+            "header should skip row_count: {header}"
+        );
+        assert!(
+            !header.contains("exec_id"),
+            "header should skip exec_id: {header}"
+        );
+        assert!(header.contains("sql"), "sql column should remain: {header}");
+    }
+
+    /// `[features.time]` 配置后，导出的 ts 列按目标时区/格式归一化
+    #[test]
+    fn test_time_normalization_rewrites_ts_column() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log_path = dir.path().join("t.log");
+        std::fs::write(
+            &log_path,
+            "2025-01-15 10:30:28.001 (EP[0] sess:0x0001 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n",
+        )
+        .unwrap();
+        let csv_path = dir.path().join("out.csv");
+        let app_log = dir.path().join("app.log");
+
+        let toml = format!(
+            "[sqllog]\ndirectory = \"{logdir}\"\n[logging]\nfile = \"{applog}\"\nlevel = \"warn\"\nretention_days = 1\n[features.time]\ninput_timezone = \"+08:00\"\noutput_timezone = \"UTC\"\n[exporter.csv]\nfile = \"{csv}\"\n",
+            logdir = dir.path().to_string_lossy().replace('\\', "/"),
+            applog = app_log.to_string_lossy().replace('\\', "/"),
+            csv = csv_path.to_string_lossy().replace('\\', "/"),
+        );
+        let cfg: Config = toml::from_str(&toml).unwrap();
+
+        handle_run(
+            &cfg,
+            None,
+            false,
+            true,
+            &Arc::new(AtomicBool::new(false)),
+            80,
+            false,
+            None,
+            1,
+            None,
+        )
+        .unwrap();
+
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        let row = content.lines().nth(1).unwrap();
+        assert!(
+            row.starts_with("2025-01-15T02:30:28.001Z,"),
+            "ts should be normalized to UTC: {row}"
+        );
+    }
+
+    /// `[features.time]` 改写 ts 后，按时间分割的 CSV 仍按原始日志时间确定周期文件
+    #[test]
+    fn test_time_normalization_with_rollover_uses_log_time() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("t.log"),
+            "2025-01-15 10:30:28.001 (EP[0] sess:0x0001 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n\
+             2025-01-15 11:05:00.002 (EP[0] sess:0x0001 user:U trxid:2 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 2. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 2.\n",
+        )
+        .unwrap();
+        let out = dir.path().join("out");
+        std::fs::create_dir(&out).unwrap();
+        let pattern = out.join("sqllog-%Y%m%d-%H.csv");
+
+        let toml = format!(
+            "[sqllog]\ndirectory = \"{logdir}\"\n[features.time]\ninput_timezone = \"+08:00\"\noutput_format = \"epoch_ms\"\n[exporter.csv]\nfile = \"{csv}\"\n",
+            logdir = dir.path().to_string_lossy().replace('\\', "/"),
+            csv = pattern.to_string_lossy().replace('\\', "/"),
+        );
+        let cfg: Config = toml::from_str(&toml).unwrap();
+        handle_run(
+            &cfg,
+            None,
+            false,
+            true,
+            &Arc::new(AtomicBool::new(false)),
+            80,
+            false,
+            None,
+            1,
+            None,
+        )
+        .unwrap();
+
+        let mut files: Vec<_> = std::fs::read_dir(&out)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        assert_eq!(files, ["sqllog-20250115-10.csv", "sqllog-20250115-11.csv"]);
+        let h10 = std::fs::read_to_string(out.join(&files[0])).unwrap();
+        assert!(
+            h10.lines().nth(1).unwrap().starts_with("1736908228001,"),
+            "{h10}"
+        );
+    }
+
+    /// `features.extra_fields = ["ts_epoch_ms"]` 在 CSV 末尾追加毫秒时间戳列
+    #[test]
+    fn test_extra_field_ts_epoch_ms_appended() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("t.log"),
+            "1970-01-01 08:00:01.250 (EP[0] sess:0x0001 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n",
+        )
+        .unwrap();
+        let csv_path = dir.path().join("out.csv");
+        let app_log = dir.path().join("app.log");
+
+        let toml = format!(
+            "[sqllog]\ndirectory = \"{logdir}\"\n[logging]\nfile = \"{applog}\"\nlevel = \"warn\"\nretention_days = 1\n[features]\nextra_fields = [\"ts_epoch_ms\"]\n[features.time]\ninput_timezone = \"+08:00\"\noutput_format = \"original\"\noutput_timezone = \"+08:00\"\n[exporter.csv]\nfile = \"{csv}\"\n",
+            logdir = dir.path().to_string_lossy().replace('\\', "/"),
+            applog = app_log.to_string_lossy().replace('\\', "/"),
+            csv = csv_path.to_string_lossy().replace('\\', "/"),
+        );
+        let cfg: Config = toml::from_str(&toml).unwrap();
+
+        handle_run(
+            &cfg,
+            None,
+            false,
+            true,
+            &Arc::new(AtomicBool::new(false)),
+            80,
+            false,
+            None,
+            1,
+            None,
+        )
+        .unwrap();
+
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        let mut lines = content.lines();
+        assert!(
+            lines
+                .next()
+                .unwrap()
+                .ends_with(",normalized_sql,ts_epoch_ms")
+        );
+        let row = lines.next().unwrap();
+        assert!(row.starts_with("1970-01-01 08:00:01.250,"), "{row}");
+        assert!(row.ends_with(",1250"), "{row}");
+    }
+
+    /// `extra_fields = ["client_zone"]` 按子网表在 CSV 末尾追加区域标签列
+    #[test]
+    fn test_extra_field_client_zone_appended() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("t.log"),
+            "2025-01-15 10:30:28.001 (EP[0] sess:0x0001 user:U trxid:1 stmt:0x1 appname:A ip:::ffff:10.1.0.7) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n\
+             2025-01-15 10:30:28.002 (EP[0] sess:0x0001 user:U trxid:1 stmt:0x1 appname:A ip:8.8.8.8) [SEL] SELECT 2. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n",
+        )
+        .unwrap();
+        let csv_path = dir.path().join("out.csv");
+
+        let toml = format!(
+            "[sqllog]\ndirectory = \"{logdir}\"\n[features]\nextra_fields = [\"client_zone\"]\n[features.client_zone]\nsubnets = [{{ cidr = \"10.1.0.0/16\", label = \"app-tier\" }}]\n[exporter.csv]\nfile = \"{csv}\"\n",
+            logdir = dir.path().to_string_lossy().replace('\\', "/"),
+            csv = csv_path.to_string_lossy().replace('\\', "/"),
+        );
+        let cfg: Config = toml::from_str(&toml).unwrap();
+        cfg.validate().unwrap();
+
+        handle_run(
+            &cfg,
+            None,
+            false,
+            true,
+            &Arc::new(AtomicBool::new(false)),
+            80,
+            false,
+            None,
+            1,
+            None,
+        )
+        .unwrap();
+
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert!(lines[0].ends_with(",normalized_sql,client_zone"));
+        assert!(lines[1].ends_with(",\"app-tier\""), "{}", lines[1]);
+        assert!(lines[2].ends_with(','), "{}", lines[2]);
+    }
+
+    /// `[[features.extract]]` 按正则提取 SQL 注释标签与 appname 片段，追加在 `extra_fields` 之后
+    #[test]
+    fn test_extract_rules_appended_after_extra_fields() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("t.log"),
+            "2025-01-15 10:30:28.001 (EP[0] sess:0x0001 user:U trxid:1 stmt:0x1 appname:order-svc ip:10.0.0.1) [SEL] /* traceid=4bf92f35 */ SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n\
+             2025-01-15 10:30:28.002 (EP[0] sess:0x0001 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 2. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 2.\n",
+        )
+        .unwrap();
+        let csv_path = dir.path().join("out.csv");
+        let toml = format!(
+            "[sqllog]\ndirectory = \"{logdir}\"\n[features]\nextra_fields = [\"seq\"]\n\
+             [[features.extract]]\nname = \"trace_id\"\npattern = 'traceid=(\\w+)'\n\
+             [[features.extract]]\nname = \"service\"\nsource = \"appname\"\npattern = '^(?P<value>[a-z]+)-svc$'\n\
+             [exporter.csv]\nfile = \"{csv}\"\n",
+            logdir = dir.path().to_string_lossy().replace('\\', "/"),
+            csv = csv_path.to_string_lossy().replace('\\', "/"),
+        );
+        let cfg: Config = toml::from_str(&toml).unwrap();
+        cfg.validate().unwrap();
+
+        handle_run(
+            &cfg,
+            None,
+            false,
+            true,
+            &Arc::new(AtomicBool::new(false)),
+            80,
+            false,
+            None,
+            1,
+            None,
+        )
+        .unwrap();
+
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert!(lines[0].ends_with(",seq,trace_id,service"), "{}", lines[0]);
+        assert!(
+            lines[1].ends_with(",1,\"4bf92f35\",\"order\""),
+            "{}",
+            lines[1]
+        );
+        assert!(lines[2].ends_with(",2,,"), "{}", lines[2]);
+    }
+
+    /// 凭据脱敏默认启用：IDENTIFIED BY 密码在 CSV 中替换为 `***`，关闭后保留原文
+    #[test]
+    fn test_credentials_redacted_by_default() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("t.log"),
+            "2025-01-15 10:30:28.001 (EP[0] sess:0x0001 user:SYSDBA trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [ORA] ALTER USER app IDENTIFIED BY \"Hunter2!\". EXECTIME: 1(ms) ROWCOUNT: 0(rows) EXEC_ID: 1.\n",
+        )
+        .unwrap();
+        let csv_path = dir.path().join("out.csv");
+        let toml = format!(
+            "[sqllog]\ndirectory = \"{logdir}\"\n[exporter.csv]\nfile = \"{csv}\"\n",
+            logdir = dir.path().to_string_lossy().replace('\\', "/"),
+            csv = csv_path.to_string_lossy().replace('\\', "/"),
+        );
+        let mut cfg: Config = toml::from_str(&toml).unwrap();
+        let run = |cfg: &Config| {
+            handle_run(
+                cfg,
+                None,
+                false,
+                true,
+                &Arc::new(AtomicBool::new(false)),
+                80,
+                false,
+                None,
+                1,
+                None,
+            )
+            .unwrap();
+            std::fs::read_to_string(&csv_path).unwrap()
+        };
+
+        let content = run(&cfg);
+        assert!(content.contains("IDENTIFIED BY \"\"***\"\""), "{content}");
+        assert!(!content.contains("Hunter2"));
+
+        cfg.apply_overrides(&["features.redact.credentials=false".into()])
+            .unwrap();
+        assert!(run(&cfg).contains("Hunter2!"));
+    }
+
+    /// 同一进程内的两次运行各自分配标识，`run_id` 列与完成摘要一致
+    #[test]
+    fn test_run_id_column_distinct_per_run() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("a.log"),
+            "2025-01-15 10:30:28.001 (EP[0] sess:0x0001 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n",
+        )
+        .unwrap();
+        let csv_path = dir.path().join("out.csv");
+        let toml = format!(
+            "[sqllog]\ndirectory = \"{logdir}\"\n[features]\nextra_fields = [\"run_id\"]\n[exporter.csv]\nfile = \"{csv}\"\n",
+            logdir = dir.path().to_string_lossy().replace('\\', "/"),
+            csv = csv_path.to_string_lossy().replace('\\', "/"),
+        );
+        let cfg: Config = toml::from_str(&toml).unwrap();
+        let run = || {
+            let summary = handle_run(
+                &cfg,
+                None,
+                false,
+                true,
+                &Arc::new(AtomicBool::new(false)),
+                80,
+                false,
+                None,
+                1,
+                None,
+            )
+            .unwrap();
+            let content = std::fs::read_to_string(&csv_path).unwrap();
+            let row = content.lines().nth(1).unwrap().to_string();
+            assert!(row.ends_with(&format!("\"{}\"", summary.run_id)), "{row}");
+            summary.run_id
+        };
+        let first = run();
+        if std::env::var(crate::run_id::RUN_ID_ENV).is_err() {
+            assert_ne!(first, run());
+        }
+    }
+
+    /// `[features.dedup]`：重叠的两个日志文件中重复的记录只导出一次，跳过数计入导出统计
+    #[test]
+    fn test_dedup_skips_records_repeated_across_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let line = |id: u32| {
+            format!(
+                "2025-01-15 10:30:28.{id:03} (EP[0] sess:0x0001 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT {id}. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: {id}.\n"
+            )
+        };
+        std::fs::write(
+            dir.path().join("a.log"),
+            (1..=4).map(line).collect::<String>(),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("b.log"),
+            (3..=6).map(line).collect::<String>(),
+        )
+        .unwrap();
+        let csv_path = dir.path().join("out.csv");
+
+        let toml = format!(
+            "[sqllog]\ndirectory = \"{logdir}\"\n[features.dedup]\nenabled = true\n[exporter.csv]\nfile = \"{csv}\"\n",
+            logdir = dir.path().to_string_lossy().replace('\\', "/"),
+            csv = csv_path.to_string_lossy().replace('\\', "/"),
+        );
+        let cfg: Config = toml::from_str(&toml).unwrap();
+
+        // 去重跨文件比对，--jobs 大于 1 时同样走顺序路径
+        let summary = handle_run(
+            &cfg,
+            None,
+            false,
+            true,
+            &Arc::new(AtomicBool::new(false)),
+            80,
+            false,
+            None,
+            4,
+            None,
+        )
+        .unwrap();
+
+        assert!(!summary.parallel);
+        assert_eq!(summary.records, 6);
+        assert_eq!(summary.exporters[0].duplicates, 2);
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        assert_eq!(content.lines().count(), 1 + 6);
+    }
+
+    /// 溢出的 `EXEC_ID` 饱和为 `i64::MAX` 照常导出，计入 `exec_id_coerced` 与运行警告
+    #[test]
+    fn test_exec_id_overflow_saturated_and_counted() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("a.log"),
+            "2025-01-15 10:30:28.001 (EP[0] sess:0x0001 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 99999999999999999999.\n\
+             2025-01-15 10:30:28.002 (EP[0] sess:0x0001 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 2. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 2.\n",
+        )
+        .unwrap();
+        let csv_path = dir.path().join("out.csv");
+        let toml = format!(
+            "[sqllog]\ndirectory = \"{logdir}\"\n[exporter.csv]\nfile = \"{csv}\"\n",
+            logdir = dir.path().to_string_lossy().replace('\\', "/"),
+            csv = csv_path.to_string_lossy().replace('\\', "/"),
+        );
+        let cfg: Config = toml::from_str(&toml).unwrap();
+        let summary = handle_run(
+            &cfg,
+            None,
+            false,
+            true,
+            &Arc::new(AtomicBool::new(false)),
+            80,
+            false,
+            None,
+            1,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(summary.records, 2);
+        assert_eq!(summary.metrics.errors, 0);
+        assert_eq!(summary.metrics.exec_id_coerced, 1);
+        assert!(
+            summary
+                .warnings
+                .iter()
+                .any(|w| w.kind == WarningKind::ExecIdCoerced)
+        );
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        assert!(content.contains(&i64::MAX.to_string()), "{content}");
+    }
+
+    /// `[features.quota]`：超出用户配额的记录不导出，按用户计入摘要的跳过数
+    #[test]
+    fn test_quota_caps_records_per_user() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let line = |user: &str, i: u32| {
+            format!(
+                "2025-01-15 10:30:28.{i:03} (EP[0] sess:0x0001 user:{user} trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT {i}. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n"
+            )
+        };
+        let log: String = (0..6)
+            .map(|i| line(if i < 5 { "BATCH" } else { "U" }, i))
+            .collect();
+        std::fs::write(dir.path().join("t.log"), log).unwrap();
+        let csv_path = dir.path().join("out.csv");
+
+        let toml = format!(
+            "[sqllog]\ndirectory = \"{logdir}\"\n[features.quota]\nmax_records_per_user = 2\n[exporter.csv]\nfile = \"{csv}\"\n",
+            logdir = dir.path().to_string_lossy().replace('\\', "/"),
+            csv = csv_path.to_string_lossy().replace('\\', "/"),
+        );
+        let cfg: Config = toml::from_str(&toml).unwrap();
+
+        let summary = handle_run(
+            &cfg,
+            None,
+            false,
+            true,
+            &Arc::new(AtomicBool::new(false)),
+            80,
+            false,
+            None,
+            1,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(summary.records, 3);
+        assert_eq!(summary.quota_skipped, vec![("BATCH".to_string(), 3)]);
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        assert_eq!(content.lines().count(), 1 + 3);
+        assert!(content.contains("SELECT 1"));
+        assert!(!content.contains("SELECT 2"));
+        assert!(content.contains("SELECT 5"));
+        assert!(
+            format_quota_skipped(&summary.quota_skipped).contains("3 records skipped — BATCH: 3")
+        );
+    }
+
+    /// `--max-records` 截断：摘要注明原因，被截断的文件不记入断点续传状态
+    #[test]
+    fn test_max_records_truncation_skips_resume_mark() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logdir = dir.path().join("logs");
+        std::fs::create_dir(&logdir).unwrap();
+        let line = |i: u32| {
+            format!(
+                "2025-01-15 10:30:28.{i:03} (EP[0] sess:0x0001 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT {i}. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: {i}.\n"
+            )
+        };
+        std::fs::write(logdir.join("a.log"), (0..3).map(line).collect::<String>()).unwrap();
+        std::fs::write(logdir.join("b.log"), (3..6).map(line).collect::<String>()).unwrap();
+        let state_path = dir.path().join("state.toml");
+        let toml = format!(
+            "[sqllog]\ndirectory = \"{logdir}\"\n[exporter.csv]\nfile = \"{csv}\"\n",
+            logdir = logdir.to_string_lossy().replace('\\', "/"),
+            csv = dir
+                .path()
+                .join("out.csv")
+                .to_string_lossy()
+                .replace('\\', "/"),
+        );
+        let cfg: Config = toml::from_str(&toml).unwrap();
+
+        let summary = handle_run_until(
+            &cfg,
+            Some(4),
+            Some(Duration::from_secs(3600)),
+            false,
+            false,
+            true,
+            &Arc::new(AtomicBool::new(false)),
+            80,
+            true,
+            Some(&state_path.to_string_lossy()),
+            1,
+            None,
+        )
+        .unwrap();
+        assert_eq!(summary.records, 4);
+        assert_eq!(summary.truncated, Some(Truncation::MaxRecords(4)));
+        assert_eq!(
+            summary.truncated.unwrap().to_string(),
+            "max-records 4 reached"
+        );
+        let state = crate::resume::ResumeState::load(&state_path);
+        assert!(state.is_processed(&logdir.join("a.log")));
+        assert!(!state.is_processed(&logdir.join("b.log")));
+    }
+
+    /// 截断、解析失败与被忽略的导出器登记为警告，按类别分组进入摘要
+    #[test]
+    fn test_run_summary_collects_warnings() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logdir = dir.path().join("logs");
+        std::fs::create_dir(&logdir).unwrap();
+        std::fs::write(
+            logdir.join("a.log"),
+            "2025-01-15 10:30:28.001 (EP[0] sess:0x0001 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n\
+             2025-01-15 10:30:28.002 (EP[0] sess:0x0001 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 2\n\
+             2025-01-15 10:30:28.003 (EP[0] sess:0x0001 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 3. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 3.\n",
+        )
+        .unwrap();
+        let path = |name: &str| dir.path().join(name).to_string_lossy().replace('\\', "/");
+        let toml = format!(
+            "[sqllog]\ndirectory = \"{logdir}\"\nparse_mode = \"strict\"\n[exporter.csv]\nfile = \"{csv}\"\n[exporter.sqlite]\ndatabase_url = \"{db}\"\n",
+            logdir = logdir.to_string_lossy().replace('\\', "/"),
+            csv = path("out.csv"),
+            db = path("out.db"),
+        );
+        let cfg: Config = toml::from_str(&toml).unwrap();
+
+        let summary = handle_run_until(
+            &cfg,
+            Some(1),
+            None,
+            false,
+            false,
+            true,
+            &Arc::new(AtomicBool::new(false)),
+            80,
+            false,
+            None,
+            1,
+            None,
+        )
+        .unwrap();
+        let kinds: Vec<WarningKind> = summary.warnings.iter().map(|w| w.kind).collect();
+        assert!(kinds.ends_with(&[WarningKind::Truncated, WarningKind::ExporterIgnored]));
+        let truncated = &summary.warnings[kinds.len() - 2];
+        assert!(
+            truncated.message.contains("output is partial"),
+            "{}",
+            truncated.message
+        );
+
+        let summary = handle_run(
+            &cfg,
+            None,
+            false,
+            true,
+            &Arc::new(AtomicBool::new(false)),
+            80,
+            false,
+            None,
+            1,
+            None,
+        )
+        .unwrap();
+        let kinds: Vec<WarningKind> = summary.warnings.iter().map(|w| w.kind).collect();
+        assert_eq!(
+            kinds,
+            [WarningKind::ParseErrors, WarningKind::ExporterIgnored]
+        );
+        assert!(
+            summary.warnings[0]
+                .message
+                .ends_with("a.log: 1 records failed to parse"),
+            "{}",
+            summary.warnings[0].message
+        );
+        let text = format_warnings(&summary.warnings);
+        assert!(
+            text.contains("2 warnings (parse_errors: 1, exporter_ignored: 1)"),
+            "{text}"
+        );
+    }
+
+    #[test]
+    fn test_active_file_complete_holds_back_truncated_tail() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logdir = dir.path().join("logs");
+        std::fs::create_dir(&logdir).unwrap();
+        std::fs::write(
+            logdir.join("a.log"),
+            "2025-01-15 10:30:28.001 (EP[0] sess:0x0001 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n\
+             2025-01-15 10:30:28.002 (EP[0] sess:0x0001 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 2 FR",
+        )
+        .unwrap();
+        let csv = dir.path().join("out.csv");
+        let toml = format!(
+            "[sqllog]\ndirectory = \"{logdir}\"\nactive_file = \"complete\"\n[exporter.csv]\nfile = \"{csv}\"\n",
+            logdir = logdir.to_string_lossy().replace('\\', "/"),
+            csv = csv.to_string_lossy().replace('\\', "/"),
+        );
+        let cfg: Config = toml::from_str(&toml).unwrap();
+
+        let summary = handle_run(
+            &cfg,
+            None,
+            false,
+            true,
+            &Arc::new(AtomicBool::new(false)),
+            80,
+            false,
+            None,
+            1,
+            None,
+        )
+        .unwrap();
+        assert_eq!(summary.records, 1);
+        let out = std::fs::read_to_string(&csv).unwrap();
+        assert!(out.contains("SELECT 1"));
+        assert!(!out.contains("SELECT 2"));
+    }
+
+    #[test]
+    fn test_checksum_mismatch_rejects_file_records() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logdir = dir.path().join("logs");
+        std::fs::create_dir(&logdir).unwrap();
+        let line = "2025-01-15 10:30:28.001 (EP[0] sess:0x0001 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n";
+        std::fs::write(logdir.join("a.log"), line).unwrap();
+        std::fs::write(logdir.join("b.log"), line).unwrap();
+        // a.log 的摘要对不上，b.log 未列出
+        std::fs::write(
+            logdir.join("SHA256SUMS"),
+            format!("{}  a.log\n", "0".repeat(64)),
+        )
+        .unwrap();
+        let toml = format!(
+            "[sqllog]\ndirectory = \"{logdir}\"\n[exporter.csv]\nfile = \"{csv}\"\n",
+            logdir = logdir.to_string_lossy().replace('\\', "/"),
+            csv = dir
+                .path()
+                .join("out.csv")
+                .to_string_lossy()
+                .replace('\\', "/"),
+        );
+        let mut cfg: Config = toml::from_str(&toml).unwrap();
+        let run = |cfg: &Config| {
+            handle_run(
+                cfg,
+                None,
+                false,
+                true,
+                &Arc::new(AtomicBool::new(false)),
+                80,
+                false,
+                None,
+                1,
+                None,
+            )
+            .unwrap()
+            .records
+        };
+        assert_eq!(run(&cfg), 1);
+        cfg.sqllog.verify_checksums = false;
+        assert_eq!(run(&cfg), 2);
+    }
+
+    #[test]
+    fn test_profile_stages_sequential_and_parallel() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logdir = dir.path().join("logs");
+        std::fs::create_dir(&logdir).unwrap();
+        let line = "2025-01-15 10:30:28.001 (EP[0] sess:0x0001 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n";
+        std::fs::write(logdir.join("a.log"), line.repeat(50)).unwrap();
+        std::fs::write(logdir.join("b.log"), line.repeat(50)).unwrap();
+        let toml = format!(
+            "[sqllog]\ndirectory = \"{logdir}\"\n[exporter.csv]\nfile = \"{csv}\"\n",
+            logdir = logdir.to_string_lossy().replace('\\', "/"),
+            csv = dir
+                .path()
+                .join("out.csv")
+                .to_string_lossy()
+                .replace('\\', "/"),
+        );
+        let cfg: Config = toml::from_str(&toml).unwrap();
+        let run = |profile: bool, jobs: usize| {
+            handle_run_until(
+                &cfg,
+                None,
+                None,
+                profile,
+                false,
+                true,
+                &Arc::new(AtomicBool::new(false)),
+                80,
+                false,
+                None,
+                jobs,
+                None,
+            )
+            .unwrap()
+        };
+
+        assert!(run(false, 1).stages.is_none());
+        for jobs in [1, 2] {
+            let summary = run(true, jobs);
+            assert_eq!(summary.parallel, jobs > 1);
+            let per_file: Vec<_> = summary
+                .per_file
+                .iter()
+                .map(|f| {
+                    (
+                        Path::new(&f.path).file_name().unwrap().to_owned(),
+                        f.records,
+                    )
+                })
+                .collect();
+            assert_eq!(per_file, [("a.log".into(), 50), ("b.log".into(), 50)]);
+            assert_eq!(summary.exporters.len(), 1);
+            assert_eq!(
+                (
+                    summary.exporters[0].name.as_str(),
+                    summary.exporters[0].exported
+                ),
+                ("CSV", 100)
+            );
+            // 并行 CSV 路径按文件分片写出，不统计刷新
+            let latency = summary.exporters[0].flush_latency;
+            assert_eq!(latency.is_some(), jobs == 1, "{latency:?}");
+            let stages = summary.stages.unwrap();
+            assert_eq!(stages.exporter, "CSV");
+            assert!(stages.get(Stage::Parse) > Duration::ZERO);
+            assert!(stages.get(Stage::Write) > Duration::ZERO);
+        }
+    }
+
+    #[test]
+    fn test_parse_error_preview() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logdir = dir.path().join("logs");
+        std::fs::create_dir(&logdir).unwrap();
+        let line = "2025-01-15 10:30:28.001 (EP[0] sess:0x0001 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n";
+        std::fs::write(
+            logdir.join("a.log"),
+            format!("garbled header line\n{}", line.repeat(2)),
+        )
+        .unwrap();
+        let toml = format!(
+            "[sqllog]\ndirectory = \"{logdir}\"\n[exporter.csv]\nfile = \"{csv}\"\n",
+            logdir = logdir.to_string_lossy().replace('\\', "/"),
+            csv = dir
+                .path()
+                .join("out.csv")
+                .to_string_lossy()
+                .replace('\\', "/"),
+        );
+        let cfg: Config = toml::from_str(&toml).unwrap();
+        let summary = handle_run(
+            &cfg,
+            None,
+            false,
+            true,
+            &Arc::new(AtomicBool::new(false)),
+            80,
+            false,
+            None,
+            1,
+            None,
+        )
+        .unwrap();
+        assert_eq!(summary.metrics.errors, 1);
+        assert_eq!(summary.parse_errors.len(), 1);
+        let e = &summary.parse_errors[0];
+        assert_eq!(e.count, 1);
+        assert!(e.file.ends_with("a.log"), "{e:?}");
+        assert_eq!(e.example, "garbled header line");
+        let text = format_parse_errors(&summary.parse_errors);
+        assert!(text.contains(&format!("× {}", e.kind)), "{text}");
+        assert!(text.contains("garbled header line"), "{text}");
+    }
+
     #[test]
-    fn test_include_performance_metrics_false_csv_excludes_pm_columns() {
-        // 集成测试：include_performance_metrics=false 时 CSV header 不含性能指标列
+    fn test_metrics_registry_matches_summary() {
         let dir = tempfile::TempDir::new().unwrap();
-        let log_path = dir.path().join("t.log");
+        let logdir = dir.path().join("logs");
+        std::fs::create_dir(&logdir).unwrap();
+        let line = "2025-01-15 10:30:28.001 (EP[0] sess:0x0001 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n";
+        std::fs::write(logdir.join("a.log"), line.repeat(3)).unwrap();
+        std::fs::write(logdir.join("b.log"), line.repeat(50)).unwrap();
+        // a.log 校验和不符：3 条记录计为错误
         std::fs::write(
-            &log_path,
-            "2025-01-15 10:30:28.001 (EP[0] sess:0x0001 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n",
+            logdir.join("SHA256SUMS"),
+            format!("{}  a.log\n", "0".repeat(64)),
         )
         .unwrap();
+        let toml = format!(
+            "[sqllog]\ndirectory = \"{logdir}\"\n[exporter.csv]\nfile = \"{csv}\"\n",
+            logdir = logdir.to_string_lossy().replace('\\', "/"),
+            csv = dir
+                .path()
+                .join("out.csv")
+                .to_string_lossy()
+                .replace('\\', "/"),
+        );
+        let cfg: Config = toml::from_str(&toml).unwrap();
+        for jobs in [1, 2] {
+            let summary = handle_run(
+                &cfg,
+                None,
+                false,
+                true,
+                &Arc::new(AtomicBool::new(false)),
+                80,
+                false,
+                None,
+                jobs,
+                None,
+            )
+            .unwrap();
+            assert_eq!(summary.parallel, jobs > 1);
+            assert_eq!(summary.records, 50);
+            assert_eq!(
+                summary.metrics,
+                MetricsSnapshot {
+                    files: 2,
+                    parsed: 53,
+                    exported: 50,
+                    errors: 3,
+                    recovered: 0,
+                    exec_id_coerced: 0,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_watchdog_expires_and_forwards_interrupt() {
+        let interrupted = Arc::new(AtomicBool::new(false));
+        let w = Watchdog::start(Duration::from_millis(10), &interrupted);
+        std::thread::sleep(Duration::from_millis(200));
+        assert!(w.stop.load(Ordering::Relaxed));
+        assert!(w.expired());
+
+        let w = Watchdog::start(Duration::from_secs(3600), &interrupted);
+        interrupted.store(true, Ordering::Relaxed);
+        std::thread::sleep(Duration::from_millis(200));
+        assert!(w.stop.load(Ordering::Relaxed));
+        assert!(!w.expired());
+        drop(w);
+    }
+
+    /// `[features.exec_id_audit]`：跨文件追踪 `EXEC_ID`，被过滤的记录不算缺口
+    #[test]
+    fn test_exec_id_audit_reports_gaps_across_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let line = |user: &str, id: u32| {
+            format!(
+                "2025-01-15 10:30:28.{id:03} (EP[0] sess:0x0001 user:{user} trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT {id}. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: {id}.\n"
+            )
+        };
+        // a.log: 1..=3（2 属于被过滤的用户）；b.log: 4, 7 → 缺失 5、6
+        let a: String = [line("U", 1), line("X", 2), line("U", 3)].concat();
+        let b: String = [line("U", 4), line("U", 7)].concat();
+        std::fs::write(dir.path().join("a.log"), a).unwrap();
+        std::fs::write(dir.path().join("b.log"), b).unwrap();
         let csv_path = dir.path().join("out.csv");
-        let error_log = dir.path().join("errors.log");
-        let app_log = dir.path().join("app.log");
 
         let toml = format!(
-            "[sqllog]\ndirectory = \"{logdir}\"\n[error]\nfile = \"{errlog}\"\n[logging]\nfile = \"{applog}\"\nlevel = \"warn\"\nretention_days = 1\n[exporter.csv]\nfile = \"{csv}\"\noverwrite = true\nappend = false\ninclude_performance_metrics = false\n",
+            "[sqllog]\ndirectory = \"{logdir}\"\n[features.exec_id_audit]\nenabled = true\n[features.filters]\nenable = true\nusernames = [\"U\"]\n[exporter.csv]\nfile = \"{csv}\"\n",
             logdir = dir.path().to_string_lossy().replace('\\', "/"),
-            errlog = error_log.to_string_lossy().replace('\\', "/"),
-            applog = app_log.to_string_lossy().replace('\\', "/"),
             csv = csv_path.to_string_lossy().replace('\\', "/"),
         );
         let cfg: Config = toml::from_str(&toml).unwrap();
+        let compiled = cfg.validate_and_compile().unwrap();
 
-        handle_run(
+        let summary = handle_run(
             &cfg,
             None,
             false,
@@ -998,26 +3453,504 @@ mod tests {
             80,
             false,
             None,
-            1,
-            None, // compiled_filters
+            4,
+            compiled,
         )
         .unwrap();
 
-        let content = std::fs::read_to_string(&csv_path).unwrap();
-        let header = content.lines().next().unwrap();
+        assert_eq!(summary.records, 4);
+        assert!(!summary.parallel);
+        let audit = summary.exec_id_audit.unwrap();
+        assert_eq!(audit.sequences, 1);
+        assert_eq!(audit.missing(), 2);
         assert!(
-            !header.contains("exec_time_ms"),
-            "header should skip exec_time_ms: {header}"
+            format_exec_id_audit(&audit)
+                .contains("2 missing in 1 of 1 sequences — EP[0] 0x0001: 2 (1..7)"),
+            "{}",
+            format_exec_id_audit(&audit)
         );
-        assert!(
-            !header.contains("row_count"),
-            "header should skip row_count: {header}"
+    }
+
+    /// `[features.statements]`：跨文件登记语句，字典表与导出行的 `statement_id` 可关联
+    #[test]
+    fn test_statement_registry_dictionary_joins_rows() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logdir = dir.path().join("logs");
+        std::fs::create_dir(&logdir).unwrap();
+        let line = |ms: u32, sql: &str| {
+            format!(
+                "2025-01-15 10:30:28.{ms:03} (EP[0] sess:0x1 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] {sql}. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: {ms}.\n"
+            )
+        };
+        let a: String = [line(1, "SELECT 1 FROM t"), line(2, "DELETE FROM t")].concat();
+        std::fs::write(logdir.join("a.log"), a).unwrap();
+        std::fs::write(logdir.join("b.log"), line(3, "SELECT 2 FROM t")).unwrap();
+        let csv_path = dir.path().join("out.csv");
+        let dict_path = dir.path().join("statements.csv");
+
+        let toml = format!(
+            "[sqllog]\ndirectory = \"{logdir}\"\n[features]\nextra_fields = [\"statement_id\"]\n[features.statements]\nenabled = true\nfile = \"{dict}\"\n[exporter.csv]\nfile = \"{csv}\"\n",
+            logdir = logdir.to_string_lossy().replace('\\', "/"),
+            dict = dict_path.to_string_lossy().replace('\\', "/"),
+            csv = csv_path.to_string_lossy().replace('\\', "/"),
+        );
+        let cfg: Config = toml::from_str(&toml).unwrap();
+        let compiled = cfg.validate_and_compile().unwrap();
+        let summary = handle_run(
+            &cfg,
+            None,
+            false,
+            true,
+            &Arc::new(AtomicBool::new(false)),
+            80,
+            false,
+            None,
+            4,
+            compiled,
+        )
+        .unwrap();
+
+        assert!(!summary.parallel);
+        assert_eq!(summary.statements.len(), 2);
+        let select = &summary.statements[0];
+        assert_eq!(select.count, 2);
+        assert_eq!(select.first_seen, "2025-01-15 10:30:28.001");
+        assert_eq!(select.last_seen, "2025-01-15 10:30:28.003");
+
+        let dict = std::fs::read_to_string(&dict_path).unwrap();
+        assert_eq!(dict.lines().count(), 3, "{dict}");
+        let rows = std::fs::read_to_string(&csv_path).unwrap();
+        let ids: Vec<&str> = rows
+            .lines()
+            .skip(1)
+            .map(|l| l.rsplit(',').next().unwrap())
+            .collect();
+        let select_id = select.statement_id.to_string();
+        assert_eq!(ids[0], select_id);
+        assert_eq!(ids[2], select_id);
+        assert_ne!(ids[1], select_id);
+    }
+
+    /// `[features.session_backfill]`：会话后续记录为空的 appname / ip 按会话补全，跨文件延续
+    #[test]
+    fn test_session_backfill_across_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logdir = dir.path().join("logs");
+        std::fs::create_dir(&logdir).unwrap();
+        let line = |ms: u32, sess: &str, app: &str, ip: &str| {
+            format!(
+                "2025-01-15 10:30:28.{ms:03} (EP[0] sess:{sess} user:U trxid:1 stmt:0x1 appname:{app} ip:{ip}) [SEL] SELECT {ms}. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: {ms}.\n"
+            )
+        };
+        std::fs::write(
+            logdir.join("a.log"),
+            [line(1, "0x1", "ERP", "10.0.0.1"), line(2, "0x2", "", "")].concat(),
+        )
+        .unwrap();
+        std::fs::write(logdir.join("b.log"), line(3, "0x1", "", "")).unwrap();
+        let csv_path = dir.path().join("out.csv");
+
+        let toml = format!(
+            "[sqllog]\ndirectory = \"{logdir}\"\n[features.session_backfill]\nenabled = true\n[exporter.csv]\nfile = \"{csv}\"\n",
+            logdir = logdir.to_string_lossy().replace('\\', "/"),
+            csv = csv_path.to_string_lossy().replace('\\', "/"),
+        );
+        let cfg: Config = toml::from_str(&toml).unwrap();
+        let compiled = cfg.validate_and_compile().unwrap();
+        let summary = handle_run(
+            &cfg,
+            None,
+            false,
+            true,
+            &Arc::new(AtomicBool::new(false)),
+            80,
+            false,
+            None,
+            4,
+            compiled,
+        )
+        .unwrap();
+
+        assert!(!summary.parallel);
+        let rows = std::fs::read_to_string(&csv_path).unwrap();
+        let rows: Vec<&str> = rows.lines().skip(1).collect();
+        assert_eq!(rows.len(), 3);
+        assert!(rows[2].contains(",ERP,10.0.0.1,"), "{}", rows[2]);
+        // 没有历史值的会话保持为空
+        assert!(!rows[1].contains("ERP"), "{}", rows[1]);
+    }
+
+    /// `[features.obfuscate_ids]`：指针值替换为跨文件稳定的小整数，对照表写出原值
+    #[test]
+    fn test_obfuscate_ids_across_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logdir = dir.path().join("logs");
+        std::fs::create_dir(&logdir).unwrap();
+        let line = |ms: u32, sess: &str, thrd: u32| {
+            format!(
+                "2025-01-15 10:30:28.{ms:03} (EP[0] sess:{sess} thrd:{thrd} user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT {ms}. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: {ms}.\n"
+            )
+        };
+        std::fs::write(
+            logdir.join("a.log"),
+            [
+                line(1, "0x7f3a5c012a88", 4411),
+                line(2, "0x7f3a5c019f10", 4412),
+            ]
+            .concat(),
+        )
+        .unwrap();
+        std::fs::write(logdir.join("b.log"), line(3, "0x7f3a5c012a88", 4411)).unwrap();
+        let csv_path = dir.path().join("out.csv");
+        let dict_path = dir.path().join("ids.csv");
+
+        let toml = format!(
+            "[sqllog]\ndirectory = \"{logdir}\"\n[features.obfuscate_ids]\nenabled = true\ndictionary_file = \"{dict}\"\n[exporter.csv]\nfile = \"{csv}\"\n",
+            logdir = logdir.to_string_lossy().replace('\\', "/"),
+            dict = dict_path.to_string_lossy().replace('\\', "/"),
+            csv = csv_path.to_string_lossy().replace('\\', "/"),
+        );
+        let cfg: Config = toml::from_str(&toml).unwrap();
+        let compiled = cfg.validate_and_compile().unwrap();
+        let summary = handle_run(
+            &cfg,
+            None,
+            false,
+            true,
+            &Arc::new(AtomicBool::new(false)),
+            80,
+            false,
+            None,
+            4,
+            compiled,
+        )
+        .unwrap();
+
+        assert!(!summary.parallel);
+        let out = std::fs::read_to_string(&csv_path).unwrap();
+        assert!(!out.contains("0x7f3a5c"), "{out}");
+        let header: Vec<&str> = out.lines().next().unwrap().split(',').collect();
+        let sess = header.iter().position(|c| *c == "sess_id").unwrap();
+        let thrd = header.iter().position(|c| *c == "thrd_id").unwrap();
+        let ids: Vec<(String, String)> = out
+            .lines()
+            .skip(1)
+            .map(|row| {
+                let cols: Vec<&str> = row.split(',').collect();
+                (cols[sess].to_string(), cols[thrd].to_string())
+            })
+            .collect();
+        let expected = [("1", "1"), ("2", "2"), ("1", "1")];
+        assert_eq!(ids.len(), expected.len());
+        for ((s, t), (want_s, want_t)) in ids.iter().zip(expected) {
+            assert_eq!((s.as_str(), t.as_str()), (want_s, want_t), "{out}");
+        }
+        let dict = std::fs::read_to_string(&dict_path).unwrap();
+        assert!(dict.contains("sess_id,0,0x7f3a5c012a88,1\n"), "{dict}");
+        assert!(dict.contains("thrd_id,0,4412,2\n"), "{dict}");
+    }
+
+    /// `[features.identifier_case]`：不同文件中大小写不一的用户名统一后导出，并行路径同样生效
+    #[test]
+    fn test_identifier_case_normalizes_username_and_tag() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logdir = dir.path().join("logs");
+        std::fs::create_dir(&logdir).unwrap();
+        let line = |ms: u32, user: &str| {
+            format!(
+                "2025-01-15 10:30:28.{ms:03} (EP[0] sess:0x1 thrd:1 user:{user} trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT {ms}. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: {ms}.\n"
+            )
+        };
+        std::fs::write(logdir.join("a.log"), line(1, "SysDba")).unwrap();
+        std::fs::write(logdir.join("b.log"), line(2, "SYSDBA")).unwrap();
+        let csv_path = dir.path().join("out.csv");
+
+        let toml = format!(
+            "[sqllog]\ndirectory = \"{logdir}\"\n[features.identifier_case]\nusername = \"lower\"\ntag = \"lower\"\n[exporter.csv]\nfile = \"{csv}\"\n",
+            logdir = logdir.to_string_lossy().replace('\\', "/"),
+            csv = csv_path.to_string_lossy().replace('\\', "/"),
         );
+        let cfg: Config = toml::from_str(&toml).unwrap();
+        let compiled = cfg.validate_and_compile().unwrap();
+        let summary = handle_run(
+            &cfg,
+            None,
+            false,
+            true,
+            &Arc::new(AtomicBool::new(false)),
+            80,
+            false,
+            None,
+            4,
+            compiled,
+        )
+        .unwrap();
+
+        assert!(summary.parallel);
+        let out = std::fs::read_to_string(&csv_path).unwrap();
+        let header: Vec<&str> = out.lines().next().unwrap().split(',').collect();
+        let user = header.iter().position(|c| *c == "username").unwrap();
+        let tag = header.iter().position(|c| *c == "tag").unwrap();
+        let rows: Vec<(String, String)> = out
+            .lines()
+            .skip(1)
+            .map(|row| {
+                let cols: Vec<&str> = row.split(',').collect();
+                (cols[user].to_string(), cols[tag].to_string())
+            })
+            .collect();
+        assert_eq!(rows.len(), 2, "{out}");
         assert!(
-            !header.contains("exec_id"),
-            "header should skip exec_id: {header}"
+            rows.iter().all(|(u, t)| u == "sysdba" && t == "sel"),
+            "{out}"
         );
-        assert!(header.contains("sql"), "sql column should remain: {header}");
+    }
+
+    /// `sql_params` 派生列：PARAMS 记录并入同一会话语句句柄随后的执行记录，本身不导出
+    #[test]
+    fn test_sql_params_merges_params_records() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = dir.path().join("a.log");
+        std::fs::write(
+            &log,
+            "2025-01-15 10:30:28.001 (EP[0] sess:0x1 user:U trxid:1 stmt:0x1 appname:App ip:10.0.0.1) PARAMS(SEQNO, TYPE, DATA)={(0, INT, 5), (1, VARCHAR, 'it''s'), (2, BLOB, )}\n\
+             2025-01-15 10:30:28.002 (EP[0] sess:0x1 user:U trxid:1 stmt:0x1 appname:App ip:10.0.0.1) [UPD] UPDATE t SET a = ?, b = ? WHERE id = ?. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n\
+             2025-01-15 10:30:28.003 (EP[0] sess:0x1 user:U trxid:1 stmt:0x2 appname:App ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 2.\n",
+        )
+        .unwrap();
+        let csv_path = dir.path().join("out.csv");
+
+        let toml = format!(
+            "[sqllog]\npath = \"{log}\"\n[features]\nextra_fields = [\"sql_params\"]\n[exporter.csv]\nfile = \"{csv}\"\n",
+            log = log.to_string_lossy().replace('\\', "/"),
+            csv = csv_path.to_string_lossy().replace('\\', "/"),
+        );
+        let cfg: Config = toml::from_str(&toml).unwrap();
+        let compiled = cfg.validate_and_compile().unwrap();
+        handle_run(
+            &cfg,
+            None,
+            false,
+            true,
+            &Arc::new(AtomicBool::new(false)),
+            80,
+            false,
+            None,
+            1,
+            compiled,
+        )
+        .unwrap();
+
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        let rows: Vec<&str> = content.lines().collect();
+        assert!(rows[0].ends_with(",sql_params"), "{}", rows[0]);
+        assert_eq!(rows.len(), 3, "{content}");
+        assert!(rows[1].ends_with(",\"5, 'it''s', NULL\""), "{}", rows[1]);
+        // 没有占位符的语句不关联参数
+        assert!(rows[2].ends_with(','), "{}", rows[2]);
+    }
+
+    /// `[features.unmeasured]`：无性能指标记录排除或写入单独的 CSV
+    #[test]
+    fn test_unmeasured_records_excluded_or_separated() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = dir.path().join("a.log");
+        std::fs::write(
+            &log,
+            "2025-01-15 10:30:28.001 (EP[0] sess:0x1 user:U trxid:1 stmt:0x1 appname:App ip:10.0.0.1) CREATE TABLE t (a INT)\n\
+             2025-01-15 10:30:28.002 (EP[0] sess:0x1 user:U trxid:1 stmt:0x1 appname:App ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 3(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n",
+        )
+        .unwrap();
+        let csv_path = dir.path().join("out.csv");
+        let side_path = dir.path().join("unmeasured.csv");
+        let run = |section: &str| {
+            let toml = format!(
+                "[sqllog]\npath = \"{log}\"\n[features.unmeasured]\n{section}\n[exporter.csv]\nfile = \"{csv}\"\n",
+                log = log.to_string_lossy().replace('\\', "/"),
+                csv = csv_path.to_string_lossy().replace('\\', "/"),
+            );
+            let cfg: Config = toml::from_str(&toml).unwrap();
+            let compiled = cfg.validate_and_compile().unwrap();
+            handle_run(
+                &cfg,
+                None,
+                false,
+                true,
+                &Arc::new(AtomicBool::new(false)),
+                80,
+                false,
+                None,
+                1,
+                compiled,
+            )
+            .unwrap();
+            std::fs::read_to_string(&csv_path).unwrap()
+        };
+
+        let included = run("policy = \"include\"");
+        assert_eq!(included.lines().count(), 3);
+
+        let excluded = run("policy = \"exclude\"");
+        assert_eq!(excluded.lines().count(), 2);
+        assert!(excluded.contains("SELECT 1"));
+
+        let separated = run(&format!(
+            "policy = \"separate\"\nfile = \"{}\"",
+            side_path.to_string_lossy().replace('\\', "/")
+        ));
+        assert_eq!(separated, excluded);
+        let side = std::fs::read_to_string(&side_path).unwrap();
+        assert_eq!(side.lines().count(), 2);
+        assert!(side.starts_with("ts,ep,"));
+        assert!(side.contains("CREATE TABLE t"));
+    }
+
+    /// `seq` 派生列：默认按文件编号，`seq_scope = "session"` 时按会话跨文件延续
+    #[test]
+    fn test_seq_column_scopes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let line = |sess: &str, i: u32| {
+            format!(
+                "2025-01-15 10:30:28.000 (EP[0] sess:{sess} user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT {i}. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: {i}.\n"
+            )
+        };
+        std::fs::write(
+            dir.path().join("a.log"),
+            [line("0x1", 1), line("0x2", 2), line("0x1", 3)].concat(),
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("b.log"), line("0x1", 4)).unwrap();
+        let csv_path = dir.path().join("out.csv");
+
+        let seqs = |scope: &str| {
+            let toml = format!(
+                "[sqllog]\ndirectory = \"{logdir}\"\n[features]\nextra_fields = [\"seq\"]\n{scope}\n[exporter.csv]\nfile = \"{csv}\"\n",
+                logdir = dir.path().to_string_lossy().replace('\\', "/"),
+                csv = csv_path.to_string_lossy().replace('\\', "/"),
+            );
+            let cfg: Config = toml::from_str(&toml).unwrap();
+            let compiled = cfg.validate_and_compile().unwrap();
+            let summary = handle_run(
+                &cfg,
+                None,
+                false,
+                true,
+                &Arc::new(AtomicBool::new(false)),
+                80,
+                false,
+                None,
+                4,
+                compiled,
+            )
+            .unwrap();
+            let content = std::fs::read_to_string(&csv_path).unwrap();
+            let seqs: Vec<String> = content
+                .lines()
+                .skip(1)
+                .map(|l| l.rsplit(',').next().unwrap().to_string())
+                .collect();
+            (seqs, summary.parallel)
+        };
+
+        assert_eq!(seqs("").0, ["1", "2", "3", "1"]);
+        let (session, parallel) = seqs("seq_scope = \"session\"");
+        assert_eq!(session, ["1", "1", "2", "3"]);
+        assert!(!parallel);
+    }
+
+    /// 跨午夜轮转的会话按 EP + sess 在下一个文件中接续，空闲超时后视为新会话
+    #[test]
+    fn test_session_stitching_across_midnight_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let line = |ts: &str, i: u32| {
+            format!(
+                "{ts} (EP[0] sess:0x1 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT {i}. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: {i}.\n"
+            )
+        };
+        std::fs::write(
+            dir.path().join("dmsql_20250115.log"),
+            line("2025-01-15 23:59:30.000", 1),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("dmsql_20250116.log"),
+            [
+                line("2025-01-16 00:00:10.000", 2),
+                line("2025-01-16 03:00:00.000", 3),
+            ]
+            .concat(),
+        )
+        .unwrap();
+        let csv_path = dir.path().join("out.csv");
+        let toml = format!(
+            "[sqllog]\ndirectory = \"{logdir}\"\n[features]\nextra_fields = [\"seq\", \"session_id\"]\nseq_scope = \"session\"\nsession_idle_secs = 1800\n[exporter.csv]\nfile = \"{csv}\"\n",
+            logdir = dir.path().to_string_lossy().replace('\\', "/"),
+            csv = csv_path.to_string_lossy().replace('\\', "/"),
+        );
+        let cfg: Config = toml::from_str(&toml).unwrap();
+        let compiled = cfg.validate_and_compile().unwrap();
+        let summary = handle_run(
+            &cfg,
+            None,
+            false,
+            true,
+            &Arc::new(AtomicBool::new(false)),
+            80,
+            false,
+            None,
+            4,
+            compiled,
+        )
+        .unwrap();
+        assert!(!summary.parallel);
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        let rows: Vec<&str> = content.lines().skip(1).collect();
+        assert_eq!(rows.len(), 3);
+        for (row, tail) in rows.iter().zip([
+            ",1,\"EP[0] 0x1 #1\"",
+            ",2,\"EP[0] 0x1 #1\"",
+            ",1,\"EP[0] 0x1 #2\"",
+        ]) {
+            assert!(row.ends_with(tail), "{row}");
+        }
+    }
+
+    /// `collect_preview` 走与 run 相同的过滤 / 派生列流程，达到 limit 即停止且不写 CSV
+    #[test]
+    fn test_collect_preview_limit_filters_and_derived() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let line = |user: &str, ms: u32| {
+            format!(
+                "2025-01-15 10:30:28.{ms:03} (EP[0] sess:0x0001 user:{user} trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n"
+            )
+        };
+        let log: String = (0..5)
+            .map(|i| line(if i % 2 == 0 { "A" } else { "B" }, i))
+            .collect();
+        std::fs::write(dir.path().join("t.log"), log).unwrap();
+        let csv_path = dir.path().join("out.csv");
+
+        let toml = format!(
+            "[sqllog]\ndirectory = \"{logdir}\"\n[features]\nextra_fields = [\"ts_epoch_ms\"]\n[features.filters]\nenable = true\nusernames = [\"^A$\"]\n[features.time]\ninput_timezone = \"+08:00\"\noutput_format = \"original\"\noutput_timezone = \"+08:00\"\n[exporter.csv]\nfile = \"{csv}\"\n",
+            logdir = dir.path().to_string_lossy().replace('\\', "/"),
+            csv = csv_path.to_string_lossy().replace('\\', "/"),
+        );
+        let cfg: Config = toml::from_str(&toml).unwrap();
+        let compiled = cfg.validate_and_compile().unwrap();
+
+        let preview =
+            collect_preview(&cfg, 2, 1, &Arc::new(AtomicBool::new(false)), compiled).unwrap();
+        assert_eq!(preview.files_scanned, 1);
+        assert_eq!(preview.rows.len(), 2);
+        assert_eq!(preview.columns.last().unwrap(), "ts_epoch_ms");
+        let user_col = preview
+            .columns
+            .iter()
+            .position(|c| c == "username")
+            .unwrap();
+        assert!(preview.rows.iter().all(|r| r[user_col] == "A"));
+        assert_eq!(preview.rows[1].last().unwrap(), 1_736_908_228_002_i64);
+        assert!(!csv_path.exists(), "preview must not write output");
     }
 
     /// 当 `features.template_analysis` 未配置时，`do_template=false`，
@@ -1136,6 +4069,84 @@ mod tests {
         assert_eq!(seq_lines, par_lines, "顺序与并行输出行数应一致");
     }
 
+    /// `SQLite` 导出器多文件并发解析：记录经有界队列汇入单个写线程，条数与顺序路径一致
+    #[test]
+    fn test_sqlite_workers_queue_matches_sequential() {
+        use std::fmt::Write as _;
+        let dir = tempfile::TempDir::new().unwrap();
+        for (f, name) in ["a.log", "b.log", "c.log"].iter().enumerate() {
+            let mut body = String::new();
+            for i in 0..500 {
+                writeln!(
+                    body,
+                    "2025-01-15 10:30:28.001 (EP[0] sess:0x0001 user:U{f} trxid:{i} stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT {i}. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: {i}."
+                )
+                .unwrap();
+            }
+            std::fs::write(dir.path().join(name), body).unwrap();
+        }
+        let run = |db: &str, jobs: usize, explicit: bool| {
+            let db = dir.path().join(db).to_string_lossy().replace('\\', "/");
+            let workers = if explicit {
+                format!("workers = {jobs}\n")
+            } else {
+                String::new()
+            };
+            let toml = format!(
+                "[sqllog]\ndirectory = \"{logdir}\"\n[logging]\nfile = \"{applog}\"\nlevel = \"warn\"\nretention_days = 1\n[exporter.sqlite]\ndatabase_url = \"{db}\"\noverwrite = true\nload_metadata = true\n[pipeline]\nchunk_size = 100\n{workers}",
+                logdir = dir.path().to_string_lossy().replace('\\', "/"),
+                applog = dir
+                    .path()
+                    .join("app.log")
+                    .to_string_lossy()
+                    .replace('\\', "/"),
+            );
+            let cfg: Config = toml::from_str(&toml).unwrap();
+            let summary = handle_run(
+                &cfg,
+                None,
+                false,
+                true,
+                &Arc::new(AtomicBool::new(false)),
+                80,
+                false,
+                None,
+                jobs,
+                None,
+            )
+            .unwrap();
+            let conn = rusqlite::Connection::open(&db).unwrap();
+            let (rows, users): (i64, i64) = conn
+                .query_row(
+                    "SELECT COUNT(*), COUNT(DISTINCT username) FROM sqllog_records",
+                    [],
+                    |r| Ok((r.get(0)?, r.get(1)?)),
+                )
+                .unwrap();
+            // 两条路径都在目标库写入装载记录
+            let (files, records, status): (i64, i64, String) = conn
+                .query_row(
+                    "SELECT file_count, records, status FROM sqllog2db_loads",
+                    [],
+                    |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+                )
+                .unwrap();
+            assert_eq!((files, records, status.as_str()), (3, 1500, "complete"));
+            (summary, rows, users)
+        };
+
+        let (seq, seq_rows, _) = run("seq.db", 1, true);
+        let (par, par_rows, par_users) = run("par.db", 3, true);
+        // 未显式设置线程数（jobs 取 CPU 核数）时不启用队列，保持按文件顺序写入
+        let (default, _, _) = run("default.db", 3, false);
+        assert!(!seq.parallel);
+        assert!(par.parallel);
+        assert!(!default.parallel);
+        assert_eq!(par.records, 1500);
+        assert_eq!(seq_rows, par_rows);
+        assert_eq!(par_users, 3);
+    }
+
     /// TMPL-04-D：template_analysis 未启用时不生成伴随文件
     #[test]
     fn test_no_template_stats_when_disabled() {
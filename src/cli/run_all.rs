@@ -0,0 +1,351 @@
+//! `run-all`：依次（或有限并发）执行多个配置文件对应的导出任务，结束后输出汇总。
+use super::run::{RunSummary, handle_run};
+use crate::color;
+use crate::config::Config;
+use crate::error::{ConfigError, Error, Result};
+use indicatif::HumanCount;
+use log::info;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// 单个任务的执行结果；`result = None` 表示任务未启动（被中断或 `--fail-fast` 跳过）
+#[derive(Debug)]
+pub struct JobOutcome {
+    pub config: PathBuf,
+    pub result: Option<Result<RunSummary>>,
+}
+
+impl JobOutcome {
+    #[must_use]
+    pub fn succeeded(&self) -> bool {
+        matches!(self.result, Some(Ok(_)))
+    }
+}
+
+/// 展开配置文件参数：含通配符（`*` `?` `[`）的按 glob 匹配并排序，其余按字面路径。
+/// 重复路径只保留第一次出现；任一参数无匹配时返回 `ConfigError::NotFound`。
+pub fn expand_config_paths(patterns: &[String]) -> Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for pattern in patterns {
+        let matched: Vec<PathBuf> = if pattern.contains(['*', '?', '[']) {
+            let entries = glob::glob(pattern).map_err(|e| {
+                Error::Config(ConfigError::InvalidValue {
+                    field: "run-all.configs".to_string(),
+                    value: pattern.clone(),
+                    reason: e.to_string(),
+                })
+            })?;
+            let mut v: Vec<PathBuf> = entries.filter_map(std::result::Result::ok).collect();
+            v.sort();
+            v
+        } else if Path::new(pattern).is_file() {
+            vec![PathBuf::from(pattern)]
+        } else {
+            Vec::new()
+        };
+        if matched.is_empty() {
+            return Err(Error::Config(ConfigError::NotFound(PathBuf::from(pattern))));
+        }
+        for p in matched {
+            if !paths.contains(&p) {
+                paths.push(p);
+            }
+        }
+    }
+    Ok(paths)
+}
+
+/// 执行单个任务：加载 → `--set` 覆盖 → 校验编译 → preflight → `handle_run`
+fn run_job(
+    path: &Path,
+    set: &[String],
+    jobs: usize,
+    dry_run: bool,
+    quiet: bool,
+    interrupted: &Arc<AtomicBool>,
+) -> Result<RunSummary> {
+    let started = chrono::Local::now();
+    let mut cfg = Config::from_file(path)?;
+    cfg.apply_overrides(set)?;
+    let compiled_filters = cfg.validate_and_compile()?;
+    let jobs = cfg.pipeline.workers.unwrap_or(jobs);
+    if !dry_run {
+        let pf = super::preflight::check(&cfg);
+        if pf.print_and_check() {
+            return Err(Error::Config(ConfigError::PreflightFailed {
+                path: path.to_path_buf(),
+                reason: pf.errors.join("; "),
+            }));
+        }
+    }
+    let result = handle_run(
+        &cfg,
+        None,
+        dry_run,
+        quiet,
+        interrupted,
+        80,
+        false,
+        None,
+        jobs,
+        compiled_filters,
+    )
+    .and_then(|s| {
+        cfg.error.check(&s.metrics)?;
+        Ok(s)
+    });
+    if let Some(notify) = &cfg.notify {
+        crate::notify::notify_run(notify, &path.to_string_lossy(), &cfg.sqllog.path, &result);
+    }
+    if let Some(report) = &cfg.report {
+        crate::run_report::report_run(
+            report,
+            &path.to_string_lossy(),
+            &cfg.sqllog.path,
+            started,
+            &result,
+        );
+    }
+    result
+}
+
+/// 执行全部任务，按输入顺序返回结果。
+///
+/// `parallel` 为同时运行的任务数上限（≥1）；`jobs` 为每个任务内部的文件级并行线程数。
+/// 并发运行时各任务的进度条与完成提示被抑制，仅输出最终汇总。
+pub fn run_jobs(
+    configs: &[PathBuf],
+    set: &[String],
+    parallel: usize,
+    jobs: usize,
+    dry_run: bool,
+    quiet: bool,
+    fail_fast: bool,
+    interrupted: &Arc<AtomicBool>,
+) -> Vec<JobOutcome> {
+    let workers = parallel.clamp(1, configs.len().max(1));
+    let job_quiet = quiet || workers > 1;
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let slots: Vec<Mutex<Option<Result<RunSummary>>>> =
+        configs.iter().map(|_| Mutex::new(None)).collect();
+
+    std::thread::scope(|s| {
+        for _ in 0..workers {
+            s.spawn(|| {
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    if i >= configs.len()
+                        || interrupted.load(Ordering::Relaxed)
+                        || (fail_fast && failed.load(Ordering::Relaxed))
+                    {
+                        break;
+                    }
+                    let path = &configs[i];
+                    info!(
+                        "run-all: starting job {}/{}: {}",
+                        i + 1,
+                        configs.len(),
+                        path.display()
+                    );
+                    if !job_quiet {
+                        eprintln!(
+                            "\n{} [{}/{}] {}",
+                            color::cyan("▶"),
+                            i + 1,
+                            configs.len(),
+                            path.display()
+                        );
+                    }
+                    let result = run_job(path, set, jobs, dry_run, job_quiet, interrupted);
+                    if result.is_err() {
+                        failed.store(true, Ordering::Relaxed);
+                    }
+                    *slots[i].lock().unwrap_or_else(PoisonError::into_inner) = Some(result);
+                }
+            });
+        }
+    });
+
+    configs
+        .iter()
+        .zip(slots)
+        .map(|(config, slot)| JobOutcome {
+            config: config.clone(),
+            result: slot.into_inner().unwrap_or_else(PoisonError::into_inner),
+        })
+        .collect()
+}
+
+/// 打印汇总表（stderr）
+pub fn print_summary(outcomes: &[JobOutcome], elapsed: Duration) {
+    let width = outcomes
+        .iter()
+        .map(|o| o.config.display().to_string().chars().count())
+        .max()
+        .unwrap_or(0);
+    eprintln!(
+        "\n{} ({} jobs)",
+        color::bold("Run-all summary"),
+        outcomes.len()
+    );
+    for o in outcomes {
+        let name = o.config.display().to_string();
+        match &o.result {
+            Some(Ok(s)) => eprintln!(
+                "  {} {name:<width$}  {} records in {:.2}s{}",
+                color::green("✓"),
+                HumanCount(s.records as u64),
+                s.elapsed.as_secs_f64(),
+                if s.warnings.is_empty() {
+                    String::new()
+                } else {
+                    color::yellow(format!(", {} warnings", s.warnings.len()))
+                }
+            ),
+            Some(Err(e)) => eprintln!("  {} {name:<width$}  {e}", color::red("✗")),
+            None => eprintln!("  {} {name:<width$}  not run", color::dim("-")),
+        }
+    }
+    let ok = outcomes.iter().filter(|o| o.succeeded()).count();
+    let records: usize = outcomes
+        .iter()
+        .filter_map(|o| match &o.result {
+            Some(Ok(s)) => Some(s.records),
+            _ => None,
+        })
+        .sum();
+    eprintln!(
+        "{} {ok}/{} jobs succeeded — {} records total in {:.2}s",
+        if ok == outcomes.len() {
+            color::green("✓")
+        } else {
+            color::red("✗")
+        },
+        outcomes.len(),
+        HumanCount(records as u64),
+        elapsed.as_secs_f64()
+    );
+}
+
+/// `run-all` 子命令入口：执行全部任务并打印汇总。
+///
+/// 被中断时返回 `Error::Interrupted`；否则若有任务失败，返回第一个失败任务的错误（决定退出码）。
+pub fn handle_run_all(
+    configs: &[PathBuf],
+    set: &[String],
+    parallel: usize,
+    jobs: usize,
+    dry_run: bool,
+    quiet: bool,
+    fail_fast: bool,
+    interrupted: &Arc<AtomicBool>,
+) -> Result<()> {
+    let start = Instant::now();
+    let outcomes = run_jobs(
+        configs,
+        set,
+        parallel,
+        jobs,
+        dry_run,
+        quiet,
+        fail_fast,
+        interrupted,
+    );
+    if !quiet {
+        print_summary(&outcomes, start.elapsed());
+    }
+    if interrupted.load(Ordering::Relaxed) {
+        return Err(Error::Interrupted);
+    }
+    outcomes
+        .into_iter()
+        .find_map(|o| o.result.and_then(std::result::Result::err))
+        .map_or(Ok(()), Err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_job(dir: &Path, name: &str, log_dir: &Path) -> PathBuf {
+        let cfg = dir.join(name);
+        let out = dir.join(format!("{name}.csv"));
+        std::fs::write(
+            &cfg,
+            format!(
+                "[sqllog]\npath = \"{}\"\n\n[exporter.csv]\nfile = \"{}\"\noverwrite = true\n",
+                log_dir.display(),
+                out.display()
+            ),
+        )
+        .unwrap();
+        cfg
+    }
+
+    fn log_dir(dir: &Path) -> PathBuf {
+        let logs = dir.join("logs");
+        std::fs::create_dir_all(&logs).unwrap();
+        std::fs::write(
+            logs.join("a.log"),
+            "2025-01-15 10:30:28.001 (EP[0] sess:0x0001 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n".repeat(3),
+        )
+        .unwrap();
+        logs
+    }
+
+    #[test]
+    fn test_expand_config_paths_glob_sorted_and_deduped() {
+        let dir = tempfile::TempDir::new().unwrap();
+        for n in ["b.toml", "a.toml", "c.txt"] {
+            std::fs::write(dir.path().join(n), "").unwrap();
+        }
+        let pattern = format!("{}/*.toml", dir.path().display());
+        let a = dir.path().join("a.toml").to_string_lossy().into_owned();
+        let paths = expand_config_paths(&[pattern, a]).unwrap();
+        assert_eq!(
+            paths,
+            vec![dir.path().join("a.toml"), dir.path().join("b.toml")]
+        );
+    }
+
+    #[test]
+    fn test_expand_config_paths_missing_is_error() {
+        let err = expand_config_paths(&["/no/such/config.toml".to_string()]).unwrap_err();
+        assert!(matches!(err, Error::Config(ConfigError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_run_jobs_parallel_combined_results() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logs = log_dir(dir.path());
+        let a = write_job(dir.path(), "a.toml", &logs);
+        let b = write_job(dir.path(), "b.toml", &logs);
+        let bad = dir.path().join("bad.toml");
+        std::fs::write(&bad, "[sqllog\n").unwrap();
+
+        let interrupted = Arc::new(AtomicBool::new(false));
+        let outcomes = run_jobs(&[a, bad, b], &[], 2, 1, false, true, false, &interrupted);
+        assert_eq!(outcomes.len(), 3);
+        assert!(matches!(&outcomes[0].result, Some(Ok(s)) if s.records == 3));
+        assert!(matches!(&outcomes[1].result, Some(Err(_))));
+        assert!(matches!(&outcomes[2].result, Some(Ok(s)) if s.records == 3));
+        assert!(dir.path().join("b.toml.csv").exists());
+    }
+
+    #[test]
+    fn test_run_jobs_fail_fast_skips_remaining() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logs = log_dir(dir.path());
+        let bad = dir.path().join("bad.toml");
+        std::fs::write(&bad, "[sqllog\n").unwrap();
+        let a = write_job(dir.path(), "a.toml", &logs);
+
+        let interrupted = Arc::new(AtomicBool::new(false));
+        let outcomes = run_jobs(&[bad, a], &[], 1, 1, false, true, true, &interrupted);
+        assert!(matches!(&outcomes[0].result, Some(Err(_))));
+        assert!(outcomes[1].result.is_none());
+    }
+}
@@ -0,0 +1,485 @@
+//! `sample`：从真实日志截取前 N 条记录，写成可附在 issue 中复现解析 / 导出问题的日志片段。
+//!
+//! `--anonymize` 时用户名、应用名、IP 与 SQL 标识符替换为稳定化名（同一原值始终得到同一化名），
+//! 字符串 / 数字字面量与注释内容逐字符抹去（保留长度、换行与标点）；时间戳、EP、会话 / 线程 / 事务 /
+//! 语句句柄、标签、SQL 关键字与性能指标原样保留，片段的记录结构与原日志一致。
+//! 解析失败的记录按错误中携带的原文输出（同样脱敏）。
+use crate::error::{Error, FileError, Result};
+use crate::parser::{SqllogParser, open_log};
+use dm_database_parser_sqllog::{ParseError, Sqllog};
+use std::collections::HashMap;
+use std::io::Write as _;
+use std::path::Path;
+
+pub const DEFAULT_SAMPLE_RECORDS: usize = 1000;
+
+/// 脱敏时原样保留的 SQL 关键字、常用函数、数据类型与参数记录字段名（大小写不敏感）
+const KEYWORDS: &[&str] = &[
+    "ADD",
+    "ALL",
+    "ALTER",
+    "AND",
+    "ANY",
+    "AS",
+    "ASC",
+    "AVG",
+    "BEGIN",
+    "BETWEEN",
+    "BIGINT",
+    "BLOB",
+    "BY",
+    "CALL",
+    "CASE",
+    "CAST",
+    "CHAR",
+    "CLOB",
+    "COALESCE",
+    "COMMIT",
+    "COUNT",
+    "CREATE",
+    "CROSS",
+    "DATA",
+    "DATE",
+    "DATETIME",
+    "DEC",
+    "DECIMAL",
+    "DECLARE",
+    "DEFAULT",
+    "DELETE",
+    "DESC",
+    "DISTINCT",
+    "DOUBLE",
+    "DROP",
+    "ELSE",
+    "END",
+    "EXEC",
+    "EXISTS",
+    "FETCH",
+    "FIRST",
+    "FLOAT",
+    "FOR",
+    "FROM",
+    "FULL",
+    "GROUP",
+    "HAVING",
+    "IF",
+    "IN",
+    "INDEX",
+    "INNER",
+    "INSERT",
+    "INT",
+    "INTEGER",
+    "INTERVAL",
+    "INTO",
+    "IS",
+    "JOIN",
+    "KEY",
+    "LEFT",
+    "LIKE",
+    "LIMIT",
+    "MAX",
+    "MERGE",
+    "MIN",
+    "NEXT",
+    "NOT",
+    "NOW",
+    "NULL",
+    "NUMBER",
+    "NUMERIC",
+    "NVL",
+    "OFFSET",
+    "ON",
+    "ONLY",
+    "OR",
+    "ORDER",
+    "OUTER",
+    "OVER",
+    "PARAMS",
+    "PARTITION",
+    "PRIMARY",
+    "REAL",
+    "RIGHT",
+    "ROLLBACK",
+    "ROW",
+    "ROWNUM",
+    "ROWS",
+    "SELECT",
+    "SEQNO",
+    "SET",
+    "SMALLINT",
+    "SUBSTR",
+    "SUM",
+    "SYSDATE",
+    "TABLE",
+    "THEN",
+    "TIME",
+    "TIMESTAMP",
+    "TINYINT",
+    "TOP",
+    "TO_CHAR",
+    "TO_DATE",
+    "TRUNCATE",
+    "TYPE",
+    "UNION",
+    "UPDATE",
+    "UPPER",
+    "USING",
+    "VALUES",
+    "VARCHAR",
+    "VARCHAR2",
+    "VIEW",
+    "WHEN",
+    "WHERE",
+    "WITH",
+];
+
+fn is_keyword(word: &str) -> bool {
+    KEYWORDS.iter().any(|k| k.eq_ignore_ascii_case(word))
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '$' | '#')
+}
+
+/// 逐字符抹去：字母 → `x`，数字 → `0`，空白与标点保留
+fn blank(text: &str, out: &mut String) {
+    out.extend(text.chars().map(|c| {
+        if c.is_numeric() {
+            '0'
+        } else if c.is_alphabetic() {
+            'x'
+        } else {
+            c
+        }
+    }));
+}
+
+/// 稳定化名表（单次运行内一致）
+#[derive(Debug, Default)]
+pub struct Anonymizer {
+    users: HashMap<String, String>,
+    apps: HashMap<String, String>,
+    ips: HashMap<String, String>,
+    idents: HashMap<String, String>,
+}
+
+fn pseudonym(
+    map: &mut HashMap<String, String>,
+    key: &str,
+    make: impl FnOnce(usize) -> String,
+) -> String {
+    let next = map.len() + 1;
+    map.entry(key.to_string())
+        .or_insert_with(|| make(next))
+        .clone()
+}
+
+impl Anonymizer {
+    /// 元数据：替换 `user:`、`appname:`、`ip:` 的值，其余字段原样保留
+    pub fn meta(&mut self, meta: &str) -> String {
+        let mut out = String::with_capacity(meta.len());
+        for (i, token) in meta.split(' ').enumerate() {
+            if i > 0 {
+                out.push(' ');
+            }
+            let Some((key, value)) = token.split_once(':') else {
+                out.push_str(token);
+                continue;
+            };
+            let masked = match key {
+                "user" => pseudonym(&mut self.users, value, |n| format!("user{n}")),
+                "appname" if !value.is_empty() => {
+                    pseudonym(&mut self.apps, value, |n| format!("app{n}"))
+                }
+                "ip" if !value.is_empty() => pseudonym(&mut self.ips, value, |n| {
+                    let prefix = if value.starts_with("::ffff:") {
+                        "::ffff:"
+                    } else {
+                        ""
+                    };
+                    format!(
+                        "{prefix}10.{}.{}.{}",
+                        (n >> 16) & 0xff,
+                        (n >> 8) & 0xff,
+                        n & 0xff
+                    )
+                }),
+                _ => {
+                    out.push_str(token);
+                    continue;
+                }
+            };
+            out.push_str(key);
+            out.push(':');
+            out.push_str(&masked);
+        }
+        out
+    }
+
+    /// SQL：标识符（含双引号标识符）替换为化名，字面量与注释内容抹去，关键字、运算符与空白保留
+    pub fn sql(&mut self, sql: &str) -> String {
+        let mut out = String::with_capacity(sql.len());
+        let mut rest = sql;
+        while let Some(c) = rest.chars().next() {
+            let end = match c {
+                '\'' | '"' => rest[1..].find(c).map_or(rest.len(), |i| i + 2),
+                '-' if rest.starts_with("--") => rest.find('\n').unwrap_or(rest.len()),
+                '/' if rest.starts_with("/*") => rest[2..].find("*/").map_or(rest.len(), |i| i + 4),
+                c if is_ident_char(c) => rest.find(|c| !is_ident_char(c)).unwrap_or(rest.len()),
+                c => c.len_utf8(),
+            };
+            let (token, tail) = rest.split_at(end);
+            rest = tail;
+            match c {
+                '\'' | '-' | '/' if token.len() > 1 => {
+                    let (open, close) = match c {
+                        '\'' => (1, usize::from(token.len() > 1 && token.ends_with('\''))),
+                        '-' => (2, 0),
+                        _ => (
+                            2,
+                            if token.ends_with("*/") && token.len() >= 4 {
+                                2
+                            } else {
+                                0
+                            },
+                        ),
+                    };
+                    out.push_str(&token[..open]);
+                    blank(&token[open..token.len() - close], &mut out);
+                    out.push_str(&token[token.len() - close..]);
+                }
+                '"' => {
+                    let name = token.trim_matches('"');
+                    let alias =
+                        pseudonym(&mut self.idents, &name.to_uppercase(), |n| format!("id{n}"));
+                    out.push('"');
+                    out.push_str(&alias);
+                    if token.len() > 1 && token.ends_with('"') {
+                        out.push('"');
+                    }
+                }
+                c if c.is_numeric() => blank(token, &mut out),
+                c if is_ident_char(c) && !is_keyword(token) => {
+                    let alias = pseudonym(&mut self.idents, &token.to_uppercase(), |n| {
+                        format!("id{n}")
+                    });
+                    out.push_str(&alias);
+                }
+                _ => out.push_str(token),
+            }
+        }
+        out
+    }
+
+    /// 解析失败记录的原文：能识别出时间戳 / 元数据时分段脱敏，否则整体按 SQL 处理
+    pub fn raw(&mut self, raw: &str) -> String {
+        let Some(after_ts) = raw.get(23..).filter(|_| looks_like_timestamp(raw)) else {
+            return self.sql(raw);
+        };
+        let mut out = raw[..23].to_string();
+        match after_ts
+            .find('(')
+            .and_then(|open| after_ts[open..].find(')').map(|close| (open, open + close)))
+        {
+            Some((open, close)) => {
+                out.push_str(&after_ts[..=open]);
+                out.push_str(&self.meta(&after_ts[open + 1..close]));
+                out.push(')');
+                out.push_str(&self.sql(&after_ts[close + 1..]));
+            }
+            None => out.push_str(&self.sql(after_ts)),
+        }
+        out
+    }
+}
+
+fn looks_like_timestamp(raw: &str) -> bool {
+    let b = raw.as_bytes();
+    b.len() >= 23
+        && b[4] == b'-'
+        && b[10] == b' '
+        && b[19] == b'.'
+        && b[..4].iter().all(u8::is_ascii_digit)
+}
+
+/// 还原一条记录的日志文本：`TS (META) [TAG] BODY INDICATORS`
+fn render(record: &Sqllog<'_>, anon: Option<&mut Anonymizer>) -> String {
+    let body = record.body();
+    let (meta, body) = match anon {
+        Some(a) => (a.meta(&record.meta_raw), a.sql(&body)),
+        None => (record.meta_raw.to_string(), body.into_owned()),
+    };
+    let mut line = format!("{} ({meta}) ", record.ts);
+    if let Some(tag) = &record.tag {
+        line.push('[');
+        line.push_str(tag);
+        line.push_str("] ");
+    }
+    line.push_str(&body);
+    if let Some(ind) = record.indicators_raw() {
+        line.push_str(&ind);
+    }
+    line
+}
+
+fn error_raw(e: &ParseError) -> Option<&str> {
+    match e {
+        ParseError::InvalidFormat { raw }
+        | ParseError::InvalidRecordStartLine { raw }
+        | ParseError::IntParseError { raw, .. } => Some(raw),
+        ParseError::FileNotFound { .. } | ParseError::IoError(_) => None,
+    }
+}
+
+/// 截取前 `records` 条记录（含解析失败的记录），写到 `output`（缺省或 `-` 为标准输出）；
+/// 返回写出的记录数
+pub fn handle_sample(
+    cfg: &crate::config::Config,
+    records: usize,
+    output: Option<&str>,
+    anonymize: bool,
+) -> Result<usize> {
+    let mut anon = anonymize.then(Anonymizer::default);
+    let mut text = String::new();
+    let mut written = 0usize;
+    let mut errors = 0usize;
+    'files: for file in SqllogParser::from_config(&cfg.sqllog)?.log_files()? {
+        let parser = open_log(&file)?;
+        for result in parser.iter() {
+            if written >= records {
+                break 'files;
+            }
+            let line = match &result {
+                Ok(record) => render(record, anon.as_mut()),
+                Err(e) => {
+                    let Some(raw) = error_raw(e) else { continue };
+                    errors += 1;
+                    match anon.as_mut() {
+                        Some(a) => a.raw(raw),
+                        None => raw.to_string(),
+                    }
+                }
+            };
+            text.push_str(&line);
+            text.push('\n');
+            written += 1;
+        }
+    }
+
+    match output
+        .map(Path::new)
+        .filter(|p| !crate::exporter::is_stdout(p))
+    {
+        Some(path) => std::fs::write(path, &text).map_err(|e| {
+            Error::File(FileError::WriteFailed {
+                path: path.to_path_buf(),
+                reason: e.to_string(),
+            })
+        })?,
+        None => std::io::stdout()
+            .lock()
+            .write_all(text.as_bytes())
+            .map_err(Error::Io)?,
+    }
+    eprintln!(
+        "{} {written} record(s) sampled ({errors} unparsable){}",
+        crate::color::green("✓"),
+        if anonymize { ", anonymized" } else { "" }
+    );
+    if !anonymize {
+        eprintln!(
+            "{} sample contains raw data; pass --anonymize before sharing it",
+            crate::color::yellow("Warning:")
+        );
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use dm_database_parser_sqllog::LogParser;
+
+    #[test]
+    fn test_anonymizer_masks_identifiers_and_literals() {
+        let mut a = Anonymizer::default();
+        assert_eq!(
+            a.meta("EP[0] sess:0x0001 thrd:7 user:ALICE trxid:9 stmt:0x1 appname:billing ip:::ffff:172.16.3.4"),
+            "EP[0] sess:0x0001 thrd:7 user:user1 trxid:9 stmt:0x1 appname:app1 ip:::ffff:10.0.0.1"
+        );
+        assert_eq!(
+            a.meta("EP[1] user:BOB appname: ip:"),
+            "EP[1] user:user2 appname: ip:"
+        );
+        assert_eq!(a.meta("user:ALICE"), "user:user1");
+
+        assert_eq!(
+            a.sql("SELECT name, \"Salary\" FROM emp WHERE id = 42 AND note = 'Zhang San 张三' -- vip\n"),
+            "SELECT id1, \"id2\" FROM id3 WHERE id4 = 00 AND id5 = 'xxxxx xxx xx' -- xxx\n"
+        );
+        // 同一标识符（大小写不敏感）得到同一化名
+        assert_eq!(
+            a.sql("select NAME from EMP /* a1 */"),
+            "select id1 from id3 /* x0 */"
+        );
+        assert_eq!(
+            a.sql("PARAMS(SEQNO, TYPE, DATA)={(0, VARCHAR, 'it''s')}"),
+            "PARAMS(SEQNO, TYPE, DATA)={(0, VARCHAR, 'xx''x')}"
+        );
+        assert_eq!(a.sql("x = 'unterminated"), "id6 = 'xxxxxxxxxxxx");
+
+        assert_eq!(
+            a.raw("2025-01-15 10:30:28.001 (EP[0] user:ALICE) broken 12"),
+            "2025-01-15 10:30:28.001 (EP[0] user:user1) id7 00"
+        );
+        assert_eq!(a.raw("garbage 'x'"), "id8 'x'");
+    }
+
+    #[test]
+    fn test_sample_round_trips_through_parser() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logs = dir.path().join("logs");
+        std::fs::create_dir(&logs).unwrap();
+        std::fs::write(
+            logs.join("a.log"),
+            "2025-01-15 10:30:28.001 (EP[0] sess:0x0001 thrd:1 user:ALICE trxid:1 stmt:0x1 appname:erp ip:192.168.1.9) [SEL] SELECT * FROM orders WHERE customer = 'ACME'. EXECTIME: 3(ms) ROWCOUNT: 2(rows) EXEC_ID: 7.\n\
+             2025-01-15 10:30:29.001 (EP[0] sess:0x0001 thrd:1 user:ALICE trxid:1 stmt:0x1 appname:erp ip:192.168.1.9) [UPD] UPDATE orders\nSET total = 10. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 8.\n\
+             2025-01-15 10:30:30.001 (EP[0] sess:0x0002 thrd:2 user:BOB trxid:2 stmt:0x2 appname:erp ip:192.168.1.10) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 9.\n",
+        )
+        .unwrap();
+        let mut cfg = Config::default();
+        cfg.sqllog.path = logs.to_string_lossy().into_owned();
+        let out = dir.path().join("repro.log");
+        let out_str = out.to_string_lossy().into_owned();
+
+        assert_eq!(handle_sample(&cfg, 2, Some(&out_str), true).unwrap(), 2);
+        let text = std::fs::read_to_string(&out).unwrap();
+        for secret in ["ALICE", "erp", "192.168", "orders", "ACME", "customer"] {
+            assert!(!text.contains(secret), "{secret} leaked: {text}");
+        }
+        let parser = LogParser::from_path(&out).unwrap();
+        let parsed: Vec<_> = parser.iter().map(|r| r.unwrap()).collect();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].ts, "2025-01-15 10:30:28.001");
+        assert_eq!(parsed[1].tag.as_deref(), Some("UPD"));
+        assert_eq!(parsed[1].body(), "UPDATE id1\nSET id3 = 00. ");
+        let meta = parsed[0].parse_meta();
+        assert_eq!(
+            (meta.username.as_ref(), meta.appname.as_ref()),
+            ("user1", "app1")
+        );
+        let ind = parsed[0].parse_indicators().unwrap();
+        assert_eq!((ind.rowcount, ind.exec_id), (2, 7));
+
+        // 不脱敏时逐字保留
+        assert_eq!(handle_sample(&cfg, 10, Some(&out_str), false).unwrap(), 3);
+        let parser = LogParser::from_path(&out).unwrap();
+        assert_eq!(parser.iter().filter_map(std::result::Result::ok).count(), 3);
+        assert!(
+            std::fs::read_to_string(&out)
+                .unwrap()
+                .contains("customer = 'ACME'")
+        );
+    }
+}
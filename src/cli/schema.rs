@@ -0,0 +1,100 @@
+//! `schema`：打印目标导出器将执行的建表 DDL（列映射、清表 / 重建语句），供 DBA 在正式导入前审阅。
+use crate::config::{self, Config};
+use crate::error::{ConfigError, Error, Result};
+use crate::exporter::ExporterManager;
+
+/// 支持输出 DDL 的导出器
+pub const SCHEMA_EXPORTERS: &[&str] = &["sqlite"];
+
+/// 生成 DDL 脚本：`exporter` 为空时使用配置中启用的数据库导出器（当前仅支持 `sqlite`）。
+/// 配置中未启用所选导出器时按其默认配置生成。
+pub fn render_schema(cfg: &Config, exporter: Option<&str>) -> Result<String> {
+    let name = exporter.unwrap_or("sqlite");
+    if !SCHEMA_EXPORTERS.contains(&name) {
+        return Err(Error::Config(ConfigError::InvalidValue {
+            field: "schema.exporter".to_string(),
+            value: name.to_string(),
+            reason: format!("must be one of: {}", SCHEMA_EXPORTERS.join(", ")),
+        }));
+    }
+
+    // 只保留目标导出器，复用 from_config 的列投影 / 派生列规则
+    let mut target = cfg.clone();
+    target.exporter.csv = None;
+    let sqlite = target
+        .exporter
+        .sqlite
+        .get_or_insert_with(config::SqliteExporter::default);
+    let header = format!(
+        "-- sqllog2db schema: SQLite {} (table \"{}\")",
+        sqlite.database_url, sqlite.table_name
+    );
+    let with_templates = cfg
+        .features
+        .template_analysis
+        .as_ref()
+        .is_some_and(|t| t.enabled);
+
+    let manager = ExporterManager::from_config(&target)?;
+    let statements = manager.ddl_statements(with_templates).unwrap_or_default();
+    let mut out = header;
+    out.push('\n');
+    for stmt in statements {
+        out.push_str(&stmt);
+        out.push_str(";\n");
+    }
+    Ok(out)
+}
+
+/// `schema` 子命令入口：DDL 输出到 stdout，便于重定向为 .sql 文件
+pub fn handle_schema(cfg: &Config, exporter: Option<&str>) -> Result<()> {
+    print!("{}", render_schema(cfg, exporter)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_default_sqlite_full_columns() {
+        let cfg = Config::default();
+        let ddl = render_schema(&cfg, None).unwrap();
+        assert!(ddl.starts_with("-- sqllog2db schema: SQLite"));
+        assert!(ddl.contains("DROP TABLE IF EXISTS \"sqllog_records\";\n"));
+        assert!(
+            ddl.contains(
+                "CREATE TABLE IF NOT EXISTS \"sqllog_records\" (\n    ts TEXT NOT NULL,\n"
+            )
+        );
+        assert!(ddl.contains("    normalized_sql TEXT\n);\n"));
+        assert!(!ddl.contains("sql_templates"));
+    }
+
+    #[test]
+    fn test_schema_follows_projection_append_and_templates() {
+        let mut cfg: Config = toml::from_str(
+            "[features]\nfields = [\"ts\", \"sql\"]\nextra_fields = [\"ts_epoch_ms\"]\n\
+             [features.template_analysis]\nenabled = true\n\
+             [exporter.sqlite]\ndatabase_url = \"x.db\"\ntable_name = \"t\"\noverwrite = false\nappend = true\n",
+        )
+        .unwrap();
+        cfg.exporter.csv = None;
+        let ddl = render_schema(&cfg, Some("sqlite")).unwrap();
+        assert!(!ddl.contains("DROP TABLE IF EXISTS \"t\""));
+        assert!(!ddl.contains("DELETE FROM"));
+        assert!(ddl.contains(
+            "CREATE TABLE IF NOT EXISTS \"t\" (\n    ts TEXT NOT NULL,\n    sql TEXT NOT NULL,\n    ts_epoch_ms INTEGER\n);"
+        ));
+        assert!(ddl.contains("CREATE TABLE IF NOT EXISTS sql_templates ("));
+    }
+
+    #[test]
+    fn test_schema_unknown_exporter_rejected() {
+        let err = render_schema(&Config::default(), Some("postgres")).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Config(ConfigError::InvalidValue { ref field, .. }) if field == "schema.exporter"
+        ));
+    }
+}
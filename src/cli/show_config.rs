@@ -23,6 +23,47 @@ pub fn handle_show_config(cfg: &Config, config_path: &str, diff: bool) {
         def.as_ref().map(|d| d.sqllog.path.as_str()),
         diff,
     );
+    kv(
+        "parse_mode",
+        &cfg.sqllog.parse_mode,
+        def.as_ref().map(|d| d.sqllog.parse_mode.as_str()),
+        diff,
+    );
+    kv(
+        "file_order",
+        &cfg.sqllog.file_order,
+        def.as_ref().map(|d| d.sqllog.file_order.as_str()),
+        diff,
+    );
+    kv(
+        "active_file",
+        &cfg.sqllog.active_file,
+        def.as_ref().map(|d| d.sqllog.active_file.as_str()),
+        diff,
+    );
+    if cfg.sqllog.active_file != "read" {
+        let secs = cfg.sqllog.active_file_secs.to_string();
+        let def_secs = def.as_ref().map(|d| d.sqllog.active_file_secs.to_string());
+        kv("active_file_secs", &secs, def_secs.as_deref(), diff);
+    }
+    let def_verify = def.as_ref().map(|d| d.sqllog.verify_checksums.to_string());
+    kv(
+        "verify_checksums",
+        &cfg.sqllog.verify_checksums.to_string(),
+        def_verify.as_deref(),
+        diff,
+    );
+    if !cfg.sqllog.ignore.is_empty() {
+        kv("ignore", &cfg.sqllog.ignore.join(", "), None, diff);
+    }
+    for (key, value) in [
+        ("file_date_from", &cfg.sqllog.file_date_from),
+        ("file_date_to", &cfg.sqllog.file_date_to),
+    ] {
+        if let Some(date) = value {
+            kv(key, date, None, diff);
+        }
+    }
     println!();
 
     // [logging]
@@ -57,6 +98,50 @@ pub fn handle_show_config(cfg: &Config, config_path: &str, diff: bool) {
         let def_ap = def_csv.map(|d| if d.append { "true" } else { "false" });
         kv("overwrite", &csv.overwrite.to_string(), def_ow, diff);
         kv("append", &csv.append.to_string(), def_ap, diff);
+        kv(
+            "dialect",
+            &csv.dialect,
+            def_csv.map(|d| d.dialect.as_str()),
+            diff,
+        );
+        if let Some(ft) = &csv.field_terminator {
+            kv(
+                "field_terminator",
+                &ft.escape_debug().to_string(),
+                None,
+                diff,
+            );
+        }
+        if let Some(rt) = &csv.row_terminator {
+            kv("row_terminator", &rt.escape_debug().to_string(), None, diff);
+        }
+        if let Some(by) = &csv.split_by {
+            kv("split_by", by, None, diff);
+        }
+        if let Some(c) = &csv.compression {
+            kv("compression", c, None, diff);
+        }
+        if let Some(level) = csv.compression_level {
+            kv("compression_level", &level.to_string(), None, diff);
+        }
+        if let Some(size) = &csv.max_file_size {
+            kv("max_file_size", size, None, diff);
+        }
+        if let Some(n) = csv.max_records {
+            kv("max_records", &n.to_string(), None, diff);
+        }
+        kv(
+            "exectime_format",
+            &csv.exectime_format,
+            def_csv.map(|d| d.exectime_format.as_str()),
+            diff,
+        );
+        if csv.on_error != "abort" {
+            kv("on_error", &csv.on_error, Some("abort"), diff);
+        }
+        if let Some(order) = &csv.column_order {
+            kv("column_order", &order.join(", "), None, diff);
+        }
         println!();
     }
 
@@ -80,6 +165,75 @@ pub fn handle_show_config(cfg: &Config, config_path: &str, diff: bool) {
         let def_ap = def_sqlite.map(|d| if d.append { "true" } else { "false" });
         kv("overwrite", &sqlite.overwrite.to_string(), def_ow, diff);
         kv("append", &sqlite.append.to_string(), def_ap, diff);
+        kv(
+            "duplicate_check",
+            &sqlite.duplicate_check,
+            def_sqlite.map(|d| d.duplicate_check.as_str()),
+            diff,
+        );
+        if let Some(q) = &sqlite.duplicate_check_query {
+            kv("duplicate_check_query", q, None, diff);
+        }
+        if let Some(by) = &sqlite.split_by {
+            kv("split_by", by, None, diff);
+        }
+        if let Some(view) = &sqlite.view_name {
+            kv("view_name", view, None, diff);
+        }
+        if sqlite.load_metadata {
+            kv("load_metadata", "true", Some("false"), diff);
+        }
+        if sqlite.migrate {
+            kv("migrate", "true", Some("false"), diff);
+        }
+        if sqlite.on_error != "abort" {
+            kv("on_error", &sqlite.on_error, Some("abort"), diff);
+        }
+        for (hook, statements) in [("pre_sql", &sqlite.pre_sql), ("post_sql", &sqlite.post_sql)] {
+            for (i, sql) in statements.iter().enumerate() {
+                kv(&format!("{hook}[{i}]"), sql, None, diff);
+            }
+        }
+        println!();
+    }
+
+    if let Some(es) = &cfg.exporter.elasticsearch {
+        println!("{}", color::cyan("[exporter.elasticsearch]"));
+        kv("url", &es.url, None, diff);
+        kv("index", &es.index, None, diff);
+        kv("batch_size", &es.batch_size.to_string(), None, diff);
+        if let Some(user) = &es.username {
+            kv("username", user, None, diff);
+        }
+        if es.password.is_some() {
+            kv("password", "***", None, diff);
+        }
+        if let Some(env) = &es.password_env {
+            kv("password_env", env, None, diff);
+        }
+        if es.api_key.is_some() {
+            kv("api_key", "***", None, diff);
+        }
+        kv("timeout_secs", &es.timeout_secs.to_string(), None, diff);
+        if let Some(dir) = &es.spill_dir {
+            kv("spill_dir", dir, None, diff);
+            kv("spill_max_mb", &es.spill_max_mb.to_string(), None, diff);
+        }
+        if es.on_error != "abort" {
+            kv("on_error", &es.on_error, Some("abort"), diff);
+        }
+        println!();
+    }
+
+    if let Some(custom) = &cfg.exporter.custom {
+        println!("{}", color::cyan("[exporter.custom]"));
+        kv("name", &custom.name, None, diff);
+        for (key, value) in &custom.options {
+            let value = value
+                .as_str()
+                .map_or_else(|| value.to_string(), str::to_string);
+            kv(key, &value, None, diff);
+        }
         println!();
     }
 
@@ -98,6 +252,149 @@ pub fn handle_show_config(cfg: &Config, config_path: &str, diff: bool) {
         println!();
     }
 
+    if let Some(t) = &cfg.features.time {
+        println!("{}", color::cyan("[features.time]"));
+        kv(
+            "input_timezone",
+            t.input_timezone.as_deref().unwrap_or("local"),
+            None,
+            diff,
+        );
+        kv(
+            "output_timezone",
+            t.output_timezone.as_deref().unwrap_or("UTC"),
+            None,
+            diff,
+        );
+        kv(
+            "output_format",
+            t.output_format.as_deref().unwrap_or("iso8601"),
+            None,
+            diff,
+        );
+        println!();
+    }
+
+    if let Some(z) = &cfg.features.client_zone {
+        println!("{}", color::cyan("[features.client_zone]"));
+        for subnet in &z.subnets {
+            kv(&subnet.cidr, &subnet.label, None, diff);
+        }
+        if let Some(d) = &z.default {
+            kv("default", d, None, diff);
+        }
+        println!();
+    }
+
+    if let Some(a) = &cfg.features.appname {
+        println!("{}", color::cyan("[features.appname]"));
+        for rule in &a.rules {
+            let matcher = match (&rule.exact, &rule.pattern) {
+                (Some(exact), _) => format!("exact \"{exact}\""),
+                (None, Some(pattern)) => format!("pattern {pattern}"),
+                (None, None) => String::new(),
+            };
+            kv(&matcher, &rule.name, None, diff);
+        }
+        if let Some(d) = &a.default {
+            kv("default", d, None, diff);
+        }
+        println!();
+    }
+
+    if let Some(r) = &cfg.features.redact {
+        println!("{}", color::cyan("[features.redact]"));
+        kv(
+            "credentials",
+            &r.credentials.to_string(),
+            Some("true"),
+            diff,
+        );
+        println!();
+    }
+
+    if let Some(o) = &cfg.features.user_owner {
+        println!("{}", color::cyan("[features.user_owner]"));
+        kv("file", &o.file, None, diff);
+        println!();
+    }
+
+    if let Some(q) = cfg.features.quota.as_ref().filter(|q| q.is_active()) {
+        println!("{}", color::cyan("[features.quota]"));
+        if let Some(n) = q.max_records_per_user {
+            kv("max_records_per_user", &n.to_string(), None, diff);
+        }
+        if let Some(n) = q.max_records_per_fingerprint {
+            kv("max_records_per_fingerprint", &n.to_string(), None, diff);
+        }
+        println!();
+    }
+
+    if let Some(a) = cfg.features.exec_id_audit.as_ref().filter(|a| a.enabled) {
+        println!("{}", color::cyan("[features.exec_id_audit]"));
+        kv("enabled", "true", None, diff);
+        kv("scope", &a.scope, None, diff);
+        println!();
+    }
+
+    if let Some(d) = cfg.features.dedup.as_ref().filter(|d| d.enabled) {
+        println!("{}", color::cyan("[features.dedup]"));
+        kv("enabled", "true", None, diff);
+        kv("key", &d.key.join(", "), None, diff);
+        kv("max_memory_mb", &d.max_memory_mb.to_string(), None, diff);
+        println!();
+    }
+
+    if let Some(s) = cfg.features.statements.as_ref().filter(|s| s.enabled) {
+        println!("{}", color::cyan("[features.statements]"));
+        kv("enabled", "true", None, diff);
+        if let Some(f) = &s.file {
+            kv("file", f, None, diff);
+        }
+        println!();
+    }
+
+    if cfg
+        .features
+        .normalize_sql
+        .as_ref()
+        .is_some_and(|n| n.enabled)
+    {
+        println!("{}", color::cyan("[features.normalize_sql]"));
+        kv("enabled", "true", None, diff);
+        println!();
+    }
+
+    if let Some(b) = cfg.features.session_backfill.as_ref().filter(|b| b.enabled) {
+        println!("{}", color::cyan("[features.session_backfill]"));
+        kv("enabled", "true", None, diff);
+        kv("fields", &b.fields.join(", "), None, diff);
+        println!();
+    }
+
+    if let Some(o) = cfg.features.obfuscate_ids.as_ref().filter(|o| o.enabled) {
+        println!("{}", color::cyan("[features.obfuscate_ids]"));
+        kv("enabled", "true", None, diff);
+        kv("fields", &o.fields.join(", "), None, diff);
+        if let Some(file) = &o.dictionary_file {
+            kv("dictionary_file", file, None, diff);
+        }
+        println!();
+    }
+
+    if let Some(c) = &cfg.features.identifier_case {
+        println!("{}", color::cyan("[features.identifier_case]"));
+        for (field, value) in [("username", &c.username), ("tag", &c.tag)] {
+            kv(
+                field,
+                value.as_deref().unwrap_or("preserve"),
+                Some("preserve"),
+                diff,
+            );
+        }
+        println!();
+    }
+
     if let Some(f) = &cfg.features.filters {
         println!("{}", color::cyan("[features.filters]"));
         kv("enable", &f.enable.to_string(), None, diff);
@@ -138,6 +435,42 @@ pub fn handle_show_config(cfg: &Config, config_path: &str, diff: bool) {
         kv("latency_hist", &charts.latency_hist.to_string(), None, diff);
         println!();
     }
+
+    if let Some(rate) = cfg.error.max_parse_error_rate {
+        println!("{}", color::cyan("[error]"));
+        kv("max_parse_error_rate", &format!("{rate}%"), None, diff);
+        println!();
+    }
+
+    if let Some(r) = &cfg.report {
+        println!("{}", color::cyan("[report]"));
+        kv("file", &r.file, None, diff);
+        println!();
+    }
+
+    if let Some(n) = &cfg.notify {
+        println!("{}", color::cyan("[notify]"));
+        kv("timeout_secs", &n.timeout_secs.to_string(), None, diff);
+        for (key, target) in [("on_success", &n.on_success), ("on_failure", &n.on_failure)] {
+            let Some(t) = target else { continue };
+            if let Some(url) = &t.webhook {
+                kv(&format!("{key}.webhook"), url, None, diff);
+            }
+            if let Some(cmd) = &t.command {
+                kv(&format!("{key}.command"), cmd, None, diff);
+            }
+            // 不回显密码
+            if let Some(e) = &t.email {
+                kv(
+                    &format!("{key}.email"),
+                    &format!("{} -> {}", e.smtp_host, e.to.join(", ")),
+                    None,
+                    diff,
+                );
+            }
+        }
+        println!();
+    }
 }
 
 /// Print a key=value line, optionally highlighting if the value differs from its default.
@@ -193,7 +526,10 @@ mod tests {
                     overwrite: false,
                     append: true,
                     batch_size: 10_000,
+                    ..SqliteExporter::default()
                 }),
+                elasticsearch: None,
+                custom: None,
             },
             ..Default::default()
         };
@@ -206,6 +542,8 @@ mod tests {
             exporter: ExporterConfig {
                 csv: None,
                 sqlite: Some(SqliteExporter::default()),
+                elasticsearch: None,
+                custom: None,
             },
             ..Default::default()
         };
@@ -225,6 +563,24 @@ mod tests {
                 fields: None,
                 template_analysis: None,
                 charts: None,
+                time: None,
+                extra_fields: None,
+                seq_scope: None,
+                session_idle_secs: None,
+                quota: None,
+                client_zone: None,
+                appname: None,
+                session_backfill: None,
+                obfuscate_ids: None,
+                identifier_case: None,
+                user_owner: None,
+                redact: None,
+                exec_id_audit: None,
+                dedup: None,
+                statements: None,
+                normalize_sql: None,
+                extract: None,
+                unmeasured: None,
             },
             ..Default::default()
         };
@@ -243,6 +599,24 @@ mod tests {
                 fields: None,
                 template_analysis: None,
                 charts: None,
+                time: None,
+                extra_fields: None,
+                seq_scope: None,
+                session_idle_secs: None,
+                quota: None,
+                client_zone: None,
+                appname: None,
+                session_backfill: None,
+                obfuscate_ids: None,
+                identifier_case: None,
+                user_owner: None,
+                redact: None,
+                exec_id_audit: None,
+                dedup: None,
+                statements: None,
+                normalize_sql: None,
+                extract: None,
+                unmeasured: None,
             },
             ..Default::default()
         };
@@ -0,0 +1,163 @@
+//! `run --profile-stages`：按处理阶段（读取、解析、过滤、转换、写出）统计耗时，运行结束时打印分解表，
+//! 调优时据此判断瓶颈。
+//!
+//! 计时采用"分段"方式：处理循环在阶段边界调用 [`StageClock::lap`]，把距上一次边界的时间记到指定阶段。
+//! 未启用时 `lap` 为空操作。并行模式下各线程的耗时累加，合计可能超过墙钟时间。
+use indicatif::HumanDuration;
+use std::time::{Duration, Instant};
+
+/// 处理阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// 打开文件：解压、mmap、校验和
+    Read,
+    /// 记录切分与字段解析
+    Parse,
+    /// 过滤管线、SQL 记录过滤、配额
+    Filter,
+    /// 参数替换、脱敏、模板聚合、派生列、时区归一化
+    Transform,
+    /// 导出器写入（含收尾刷新与分片拼接）
+    Write,
+}
+
+impl Stage {
+    const ALL: [Self; 5] = [
+        Self::Read,
+        Self::Parse,
+        Self::Filter,
+        Self::Transform,
+        Self::Write,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Parse => "parse",
+            Self::Filter => "filter",
+            Self::Transform => "transform",
+            Self::Write => "write",
+        }
+    }
+}
+
+/// 各阶段累计耗时
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StageProfile {
+    totals: [Duration; 5],
+    /// 写出阶段对应的导出器名称（如 `CSV`）
+    pub exporter: String,
+}
+
+impl StageProfile {
+    pub fn add(&mut self, stage: Stage, elapsed: Duration) {
+        self.totals[stage as usize] += elapsed;
+    }
+
+    #[must_use]
+    pub fn get(&self, stage: Stage) -> Duration {
+        self.totals[stage as usize]
+    }
+
+    #[must_use]
+    pub fn total(&self) -> Duration {
+        self.totals.iter().sum()
+    }
+
+    /// 各阶段名称与累计耗时，按处理顺序
+    pub fn breakdown(&self) -> impl Iterator<Item = (&'static str, Duration)> + '_ {
+        Stage::ALL.into_iter().map(|s| (s.name(), self.get(s)))
+    }
+
+    /// 合并另一份统计（并行任务汇总）
+    pub fn merge(&mut self, other: &Self) {
+        for (a, b) in self.totals.iter_mut().zip(other.totals) {
+            *a += b;
+        }
+    }
+
+    /// 打印阶段分解表（stderr）
+    pub fn print(&self) {
+        let total = self.total();
+        eprintln!(
+            "\n{} Stage breakdown ({} measured)",
+            crate::color::cyan("▶"),
+            HumanDuration(total)
+        );
+        for stage in Stage::ALL {
+            let t = self.get(stage);
+            let label = if stage == Stage::Write && !self.exporter.is_empty() {
+                format!("write ({})", self.exporter)
+            } else {
+                stage.name().to_string()
+            };
+            eprintln!(
+                "  {label:<16} {:>10.3}s {:>6.1}%",
+                t.as_secs_f64(),
+                percent(t, total)
+            );
+        }
+    }
+}
+
+fn percent(part: Duration, total: Duration) -> f64 {
+    if total.is_zero() {
+        0.0
+    } else {
+        part.as_secs_f64() / total.as_secs_f64() * 100.0
+    }
+}
+
+/// 分段计时器；持有 `None` 时所有操作为空
+#[derive(Debug)]
+pub struct StageClock<'a> {
+    profile: Option<&'a mut StageProfile>,
+    last: Instant,
+}
+
+impl<'a> StageClock<'a> {
+    #[must_use]
+    pub fn new(profile: Option<&'a mut StageProfile>) -> Self {
+        Self {
+            profile,
+            last: Instant::now(),
+        }
+    }
+
+    /// 把距上一次边界的时间记到 `stage`
+    #[inline]
+    pub fn lap(&mut self, stage: Stage) {
+        if let Some(p) = self.profile.as_deref_mut() {
+            let now = Instant::now();
+            p.add(stage, now - self.last);
+            self.last = now;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_attributes_laps() {
+        let mut profile = StageProfile::default();
+        let mut clock = StageClock::new(Some(&mut profile));
+        std::thread::sleep(Duration::from_millis(20));
+        clock.lap(Stage::Parse);
+        clock.lap(Stage::Write);
+        assert!(profile.get(Stage::Parse) >= Duration::from_millis(20));
+        assert!(profile.get(Stage::Parse) > profile.get(Stage::Write));
+        assert_eq!(profile.get(Stage::Read), Duration::ZERO);
+
+        let mut other = StageProfile::default();
+        other.add(Stage::Read, Duration::from_secs(1));
+        profile.merge(&other);
+        assert_eq!(profile.get(Stage::Read), Duration::from_secs(1));
+        assert!(profile.total() > Duration::from_secs(1));
+        assert!((percent(Duration::from_secs(1), Duration::from_secs(4)) - 25.0).abs() < 1e-9);
+
+        // 未启用时为空操作
+        StageClock::new(None).lap(Stage::Parse);
+    }
+}
@@ -1,8 +1,10 @@
+use super::capacity::{CapacityEstimate, estimate_capacity, print_capacity};
 use crate::color;
 use crate::config::Config;
+use crate::features::CaseNormalizer;
 use crate::features::filters::RecordMeta;
-use crate::parser::SqllogParser;
-use dm_database_parser_sqllog::{LogParser, MetaParts};
+use crate::parser::{SqllogParser, open_log};
+use dm_database_parser_sqllog::MetaParts;
 use indicatif::{HumanCount, ProgressBar, ProgressStyle};
 use serde::Serialize;
 use std::cmp::Reverse;
@@ -32,6 +34,8 @@ struct StatsJson {
     group_sections: Vec<GroupSection>,
     #[serde(skip_serializing_if = "Option::is_none")]
     time_buckets: Option<TimeBucketSection>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    capacity: Option<CapacityEstimate>,
 }
 
 #[derive(Debug, Serialize)]
@@ -206,6 +210,7 @@ impl PartialOrd for SlowEntry {
 pub const DEFAULT_STATS_STATE: &str = ".sqllog2db_stats_state.toml";
 
 /// `resume_state_file`: `None` 表示不启用增量模式；`Some(path)` 表示启用并使用该路径作为状态文件。
+/// `capacity_sample`: `Some(n)` 时追加容量预估（每种输出格式采样导出前 n 条记录）。
 pub fn handle_stats(
     cfg: &Config,
     quiet: bool,
@@ -215,6 +220,7 @@ pub fn handle_stats(
     group_by: &[String],
     bucket: Option<&str>,
     resume_state_file: Option<&str>,
+    capacity_sample: Option<usize>,
 ) {
     let Some(group_fields) = parse_group_fields(group_by) else {
         return;
@@ -236,7 +242,7 @@ pub fn handle_stats(
     };
 
     let start = Instant::now();
-    let log_files = match SqllogParser::new(&cfg.sqllog.path).log_files() {
+    let log_files = match SqllogParser::from_config(&cfg.sqllog).and_then(|p| p.log_files()) {
         Ok(files) => files,
         Err(e) => {
             eprintln!("{} {e}", color::red("Error:"));
@@ -290,7 +296,7 @@ pub fn handle_stats(
         pb.set_prefix(format!("{}/{total_files}", idx + 1));
         pb.set_message(file_name.clone());
 
-        let Ok(parser) = LogParser::from_path(log_file.as_path()) else {
+        let Ok(parser) = open_log(log_file) else {
             total_errors += 1;
             file_stats.push(FileStats {
                 name: file_name,
@@ -309,6 +315,7 @@ pub fn handle_stats(
                 slow_heap: &mut slow_heap,
                 group_fields: &group_fields,
                 group_maps: &mut group_maps,
+                case: CaseNormalizer::from_config(cfg.features.identifier_case.as_ref()),
                 bucket_field,
                 bucket_map: &mut bucket_map,
                 pb: &pb,
@@ -352,6 +359,7 @@ pub fn handle_stats(
 
     let group_sections = build_group_sections(&group_fields, group_maps);
     let time_bucket_section = build_bucket_section(bucket_field, bucket_map);
+    let capacity = capacity_sample.map(|n| estimate_capacity(cfg, &log_files, total_records, n));
 
     if json {
         let slow_queries = slow_entries
@@ -376,6 +384,7 @@ pub fn handle_stats(
             slow_queries,
             group_sections,
             time_buckets: time_bucket_section,
+            capacity,
         });
         return;
     }
@@ -411,6 +420,9 @@ pub fn handle_stats(
         print_bucket_table(section);
     }
     print_slow_queries(&slow_entries, top_n);
+    if let Some(est) = &capacity {
+        print_capacity(est);
+    }
 }
 
 // ── 解析辅助 ─────────────────────────────────────────────────────────────────
@@ -443,6 +455,8 @@ struct ProcessFileCtx<'a> {
     slow_heap: &'a mut BinaryHeap<Reverse<SlowEntry>>,
     group_fields: &'a [GroupBy],
     group_maps: &'a mut [HashMap<String, GroupAccumulator>],
+    /// 按 `[features.identifier_case]` 统一分组用的用户名
+    case: Option<CaseNormalizer>,
     bucket_field: Option<Bucket>,
     bucket_map: &'a mut BTreeMap<String, BucketAccumulator>,
     pb: &'a ProgressBar,
@@ -469,7 +483,7 @@ fn process_file(
     for result in parser.iter() {
         match result {
             Ok(record) => {
-                let meta: Option<MetaParts<'_>> = if need_meta {
+                let mut meta: Option<MetaParts<'_>> = if need_meta {
                     Some(record.parse_meta())
                 } else {
                     None
@@ -492,6 +506,9 @@ fn process_file(
                         continue;
                     }
                 }
+                if let (Some(c), Some(m)) = (ctx.case, meta.as_mut()) {
+                    c.apply(m);
+                }
 
                 let ind = if need_ind {
                     record.parse_indicators()
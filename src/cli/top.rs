@@ -0,0 +1,427 @@
+//! `top`：对已有导出结果做只读聚合，快速查看最耗时 / 最频繁的语句、用户或会话，无需重新解析日志。
+//!
+//! 目标为 `SQLite` 库时以只读方式打开；为 CSV 文件（逗号分隔、带表头）时通过 `SQLite` 的 CSV 虚拟表查询。
+//! 需要导出中包含分组列与 `exec_time_ms` / `row_count`（按 `count` 排名时可缺少）。
+use crate::color;
+use crate::error::{ConfigError, Error, ExportError, FileError, Result};
+use indicatif::HumanCount;
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+use std::path::Path;
+
+/// `--by` 可选值
+pub const TOP_METRICS: &[&str] = &["exec_time", "count", "rows"];
+
+/// `--group` 可选值
+pub const TOP_GROUPS: &[&str] = &["statement", "user", "session", "app"];
+
+/// 未指定 `--table` 时查询的表名（与 `exporter.sqlite.table_name` 默认值一致）
+pub const DEFAULT_TOP_TABLE: &str = "sqllog_records";
+
+/// 分组键在表格中的最大显示宽度
+const KEY_WIDTH: usize = 100;
+
+/// 排名依据
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    ExecTime,
+    Count,
+    Rows,
+}
+
+impl Metric {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "exec_time" => Ok(Self::ExecTime),
+            "count" => Ok(Self::Count),
+            "rows" => Ok(Self::Rows),
+            other => Err(invalid("top.by", other, TOP_METRICS)),
+        }
+    }
+
+    fn order_by(self) -> &'static str {
+        match self {
+            Self::ExecTime => "total_exec",
+            Self::Count => "cnt",
+            Self::Rows => "total_rows",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::ExecTime => "total exec time",
+            Self::Count => "count",
+            Self::Rows => "rows",
+        }
+    }
+}
+
+/// 分组字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Group {
+    Statement,
+    User,
+    Session,
+    App,
+}
+
+impl Group {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "statement" => Ok(Self::Statement),
+            "user" => Ok(Self::User),
+            "session" => Ok(Self::Session),
+            "app" => Ok(Self::App),
+            other => Err(invalid("top.group", other, TOP_GROUPS)),
+        }
+    }
+
+    fn column(self) -> &'static str {
+        match self {
+            Self::Statement => "sql",
+            Self::User => "username",
+            Self::Session => "sess_id",
+            Self::App => "appname",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Statement => "Statement",
+            Self::User => "User",
+            Self::Session => "Session",
+            Self::App => "App",
+        }
+    }
+}
+
+fn invalid(field: &str, value: &str, valid: &[&str]) -> Error {
+    Error::Config(ConfigError::InvalidValue {
+        field: field.to_string(),
+        value: value.to_string(),
+        reason: format!("must be one of: {}", valid.join(", ")),
+    })
+}
+
+fn db_error(e: &rusqlite::Error) -> Error {
+    Error::Export(ExportError::DatabaseFailed {
+        reason: e.to_string(),
+    })
+}
+
+/// 单个分组的聚合结果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TopEntry {
+    pub rank: usize,
+    pub key: String,
+    pub count: u64,
+    pub total_exec_ms: f64,
+    pub avg_exec_ms: f64,
+    pub max_exec_ms: f64,
+    pub rows: i64,
+}
+
+fn is_csv(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("csv"))
+}
+
+/// 打开导出目标，返回连接与待查询的表名
+fn open_target(target: &Path, table: &str) -> Result<(Connection, String)> {
+    if !target.is_file() {
+        return Err(Error::File(FileError::ReadFailed {
+            path: target.to_path_buf(),
+            reason: "export not found".to_string(),
+        }));
+    }
+    if is_csv(target) {
+        let conn = Connection::open_in_memory().map_err(|e| db_error(&e))?;
+        rusqlite::vtab::csvtab::load_module(&conn).map_err(|e| db_error(&e))?;
+        let filename = target.to_string_lossy().replace('\'', "''");
+        conn.execute_batch(&format!(
+            "CREATE VIRTUAL TABLE temp.export USING csv(filename='{filename}', header=yes)"
+        ))
+        .map_err(|e| db_error(&e))?;
+        return Ok((conn, "export".to_string()));
+    }
+    let conn = Connection::open_with_flags(
+        crate::paths::for_io(target),
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .map_err(|e| db_error(&e))?;
+    Ok((conn, table.to_string()))
+}
+
+/// 查询导出中按 `group` 分组、按 `metric` 降序的前 `limit` 项
+pub fn query_top(
+    target: &Path,
+    table: &str,
+    metric: Metric,
+    group: Group,
+    limit: usize,
+) -> Result<Vec<TopEntry>> {
+    let (conn, table) = open_target(target, table)?;
+    let table = table.replace('"', "\"\"");
+    let columns: Vec<String> = conn
+        .prepare(&format!("SELECT * FROM \"{table}\" LIMIT 0"))
+        .map_err(|e| db_error(&e))?
+        .column_names()
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    let has = |c: &str| columns.iter().any(|n| n == c);
+    let missing = |field: &str, value: &str, column: &str| {
+        Error::Config(ConfigError::InvalidValue {
+            field: field.to_string(),
+            value: value.to_string(),
+            reason: format!("column `{column}` is not in the export (see features.fields)"),
+        })
+    };
+
+    let key = group.column();
+    if !has(key) {
+        return Err(missing("top.group", key, key));
+    }
+    let needed = match metric {
+        Metric::ExecTime => Some("exec_time_ms"),
+        Metric::Rows => Some("row_count"),
+        Metric::Count => None,
+    };
+    if let Some(column) = needed.filter(|c| !has(c)) {
+        let by = match metric {
+            Metric::ExecTime => "exec_time",
+            _ => "rows",
+        };
+        return Err(missing("top.by", by, column));
+    }
+    // 语句分组优先使用归一化 SQL，让只有参数不同的语句归为一组
+    let key_expr = if group == Group::Statement && has("normalized_sql") {
+        "COALESCE(NULLIF(normalized_sql, ''), sql)".to_string()
+    } else {
+        format!("\"{key}\"")
+    };
+    let exec = if has("exec_time_ms") {
+        "CAST(exec_time_ms AS REAL)"
+    } else {
+        "NULL"
+    };
+    let rows = if has("row_count") {
+        "CAST(row_count AS INTEGER)"
+    } else {
+        "NULL"
+    };
+    let sql = format!(
+        "SELECT k, COUNT(*) AS cnt, COALESCE(SUM(et), 0) AS total_exec, COALESCE(AVG(et), 0), \
+         COALESCE(MAX(et), 0), COALESCE(SUM(rc), 0) AS total_rows \
+         FROM (SELECT {key_expr} AS k, {exec} AS et, {rows} AS rc FROM \"{table}\") \
+         GROUP BY k ORDER BY {} DESC, cnt DESC LIMIT ?1",
+        metric.order_by()
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| db_error(&e))?;
+    let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+    let entries = stmt
+        .query_map([limit], |row| {
+            Ok(TopEntry {
+                rank: 0,
+                key: row.get::<_, Option<String>>(0)?.unwrap_or_default(),
+                count: row.get::<_, i64>(1)?.unsigned_abs(),
+                total_exec_ms: row.get(2)?,
+                avg_exec_ms: row.get(3)?,
+                max_exec_ms: row.get(4)?,
+                rows: row.get(5)?,
+            })
+        })
+        .and_then(Iterator::collect::<rusqlite::Result<Vec<_>>>)
+        .map_err(|e| db_error(&e))?;
+    Ok(entries
+        .into_iter()
+        .enumerate()
+        .map(|(i, e)| TopEntry { rank: i + 1, ..e })
+        .collect())
+}
+
+/// 分组键单行显示：换行 / 制表符折叠为空格，超宽截断
+fn display_key(key: &str) -> String {
+    if key.is_empty() {
+        return "(unknown)".to_string();
+    }
+    let flat: String = key
+        .chars()
+        .map(|c| if c.is_whitespace() { ' ' } else { c })
+        .collect();
+    if flat.chars().count() <= KEY_WIDTH {
+        return flat;
+    }
+    let mut truncated: String = flat.chars().take(KEY_WIDTH - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+fn print_table(entries: &[TopEntry], target: &str, metric: Metric, group: Group) {
+    if entries.is_empty() {
+        eprintln!("{}", color::dim("No records found."));
+        return;
+    }
+    eprintln!(
+        "\n{} Top {} by {} ({target}):",
+        color::cyan("▶"),
+        entries.len(),
+        metric.label()
+    );
+    eprintln!(
+        "  {:<4} {:>8} {:>12} {:>10} {:>10} {:>10}  {}",
+        color::cyan("Rank"),
+        color::cyan("Count"),
+        color::cyan("Total(ms)"),
+        color::cyan("Avg(ms)"),
+        color::cyan("Max(ms)"),
+        color::cyan("Rows"),
+        color::cyan(group.label()),
+    );
+    eprintln!("  {}", color::dim("─".repeat(110)));
+    for entry in entries {
+        eprintln!(
+            "  #{:<3} {:>8} {:>12.1} {:>10.1} {:>10.1} {:>10}  {}",
+            entry.rank,
+            HumanCount(entry.count),
+            entry.total_exec_ms,
+            entry.avg_exec_ms,
+            entry.max_exec_ms,
+            entry.rows,
+            color::yellow(display_key(&entry.key)),
+        );
+    }
+}
+
+/// `top` 子命令入口：表格输出到 stderr，`--json` 时 JSON 数组输出到 stdout
+pub fn handle_top(
+    target: &str,
+    by: &str,
+    group: &str,
+    table: Option<&str>,
+    limit: usize,
+    json: bool,
+) -> Result<()> {
+    let metric = Metric::parse(by)?;
+    let group = Group::parse(group)?;
+    let entries = query_top(
+        Path::new(target),
+        table.unwrap_or(DEFAULT_TOP_TABLE),
+        metric,
+        group,
+        limit,
+    )?;
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&entries).unwrap_or_default()
+        );
+    } else {
+        print_table(&entries, target, metric, group);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "ts,username,sess_id,sql,exec_time_ms,row_count,normalized_sql\n";
+
+    fn rows() -> String {
+        let mut csv = HEADER.to_string();
+        csv.push_str("t1,ALICE,0x1,SELECT 1,10.5,1,SELECT ?\n");
+        csv.push_str("t2,ALICE,0x1,SELECT 2,20,1,SELECT ?\n");
+        csv.push_str("t3,BOB,0x2,UPDATE t SET a = 1,100,7,\n");
+        csv.push_str("t4,BOB,0x3,\"DELETE FROM t\nWHERE a = 1\",1,3,\n");
+        csv
+    }
+
+    #[test]
+    fn test_top_over_csv_export() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let csv = dir.path().join("out.csv");
+        std::fs::write(&csv, rows()).unwrap();
+
+        let top = query_top(&csv, DEFAULT_TOP_TABLE, Metric::ExecTime, Group::User, 10).unwrap();
+        assert_eq!(top.len(), 2);
+        assert_eq!((top[0].rank, top[0].key.as_str()), (1, "BOB"));
+        assert!((top[0].total_exec_ms - 101.0).abs() < 1e-9);
+        assert!((top[1].max_exec_ms - 20.0).abs() < 1e-9);
+
+        // 语句分组优先归一化 SQL
+        let top = query_top(&csv, DEFAULT_TOP_TABLE, Metric::Count, Group::Statement, 1).unwrap();
+        assert_eq!(top.len(), 1);
+        assert_eq!((top[0].key.as_str(), top[0].count), ("SELECT ?", 2));
+
+        let top = query_top(&csv, DEFAULT_TOP_TABLE, Metric::Rows, Group::Session, 10).unwrap();
+        assert_eq!((top[0].key.as_str(), top[0].rows), ("0x2", 7));
+        assert_eq!(
+            display_key("DELETE FROM t\nWHERE a = 1"),
+            "DELETE FROM t WHERE a = 1"
+        );
+    }
+
+    #[test]
+    fn test_top_over_sqlite_export_and_errors() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("out.db");
+        let conn = Connection::open(&db).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE sqllog_records (username TEXT, sql TEXT, exec_time_ms REAL);
+             INSERT INTO sqllog_records VALUES ('A', 'SELECT 1', 5), ('A', 'SELECT 1', 7), ('B', 'SELECT 2', 50);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let top = query_top(
+            &db,
+            DEFAULT_TOP_TABLE,
+            Metric::ExecTime,
+            Group::Statement,
+            10,
+        )
+        .unwrap();
+        assert_eq!(top[0].key, "SELECT 2");
+        assert_eq!(top[1].count, 2);
+        assert_eq!(top[1].rows, 0);
+
+        let field = |r: Result<Vec<TopEntry>>| match r.unwrap_err() {
+            Error::Config(ConfigError::InvalidValue { field, .. }) => field,
+            e => panic!("{e}"),
+        };
+        assert_eq!(
+            field(query_top(
+                &db,
+                DEFAULT_TOP_TABLE,
+                Metric::Rows,
+                Group::User,
+                1
+            )),
+            "top.by"
+        );
+        assert_eq!(
+            field(query_top(
+                &db,
+                DEFAULT_TOP_TABLE,
+                Metric::Count,
+                Group::App,
+                1
+            )),
+            "top.group"
+        );
+        assert!(query_top(&db, "missing", Metric::Count, Group::User, 1).is_err());
+        assert!(
+            query_top(
+                &dir.path().join("nope.db"),
+                DEFAULT_TOP_TABLE,
+                Metric::Count,
+                Group::User,
+                1
+            )
+            .is_err()
+        );
+        assert!(Metric::parse("time").is_err());
+        assert!(Group::parse("ip").is_err());
+    }
+}
@@ -0,0 +1,381 @@
+//! `run --auto-tune`：吞吐自动调优。从每个输入文件开头截取一小段（最多 [`SAMPLE_FILES`] 个文件，
+//! 每个 [`SAMPLE_BYTES_PER_FILE`] 字节，按记录边界截断）写入临时目录，用与正式运行相同的配置
+//! （输出重定向到临时目录）依次试跑不同的线程数、分块大小与 `SQLite` 批大小，逐项保留吞吐最高的取值，
+//! 再用于全量运行并记入完成摘要，便于下次用固定参数复现。
+//!
+//! 本二进制未内置 Parquet 导出器，没有 row group 大小可调。
+use super::capacity::sample_config;
+use super::run::handle_run;
+use crate::config::Config;
+use crate::error::{Error, FileError, Result};
+use crate::parser::SqllogParser;
+use crate::parser::active::record_boundary;
+use crate::parser::input::{Compression, is_stdin};
+use log::{info, warn};
+use serde::Serialize;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 参与采样的输入文件数上限
+pub const SAMPLE_FILES: usize = 8;
+
+/// 每个文件的采样字节数上限
+pub const SAMPLE_BYTES_PER_FILE: u64 = 4 * 1024 * 1024;
+
+/// 每组参数试跑的轮数，取最快一轮以压低缓存 / 调度抖动
+const ROUNDS: usize = 2;
+
+/// 分块大小候选（`pipeline.chunk_size`）
+const CHUNK_SIZES: &[usize] = &[20_000, 100_000, 500_000];
+
+/// `SQLite` 批大小候选（`exporter.sqlite.batch_size`）
+const SQLITE_BATCH_SIZES: &[usize] = &[1_000, 10_000, 50_000];
+
+/// 一次试跑的参数与吞吐
+#[derive(Debug, Clone, Serialize)]
+pub struct TuneTrial {
+    pub jobs: usize,
+    pub chunk_size: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sqlite_batch_size: Option<usize>,
+    pub records_per_sec: f64,
+}
+
+/// 调优结果：选定的参数与全部试跑记录
+#[derive(Debug, Clone, Serialize)]
+pub struct Tuning {
+    pub jobs: usize,
+    pub chunk_size: usize,
+    /// 未以 `SQLite` 为导出目标时为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sqlite_batch_size: Option<usize>,
+    /// 样本记录数
+    pub sample_records: usize,
+    pub trials: Vec<TuneTrial>,
+}
+
+impl Tuning {
+    /// 把选定的分块大小与批大小写回配置（线程数由调用方传给 `handle_run`）
+    pub fn apply(&self, cfg: &mut Config) {
+        cfg.pipeline.chunk_size = self.chunk_size;
+        if let (Some(sqlite), Some(batch)) = (cfg.exporter.sqlite.as_mut(), self.sqlite_batch_size)
+        {
+            sqlite.batch_size = batch;
+        }
+    }
+
+    /// 不经调优复现本次参数的命令行选项
+    #[must_use]
+    pub fn reproduce_args(&self) -> String {
+        let args = format!(
+            "--jobs {} --set pipeline.chunk_size={}",
+            self.jobs, self.chunk_size
+        );
+        match self.sqlite_batch_size {
+            Some(batch) => format!("{args} --set exporter.sqlite.batch_size={batch}"),
+            None => args,
+        }
+    }
+}
+
+/// 线程数候选：1、2、4…… 直至 `max_jobs`（含）
+fn job_candidates(max_jobs: usize) -> Vec<usize> {
+    let mut jobs: Vec<usize> = std::iter::successors(Some(1usize), |j| j.checked_mul(2))
+        .take_while(|&j| j < max_jobs)
+        .collect();
+    jobs.push(max_jobs.max(1));
+    jobs
+}
+
+/// 候选值去重，`current` 排在最前（吞吐相同时保留当前值）
+fn with_current(current: usize, candidates: &[usize]) -> Vec<usize> {
+    std::iter::once(current)
+        .chain(candidates.iter().copied().filter(|&c| c != current))
+        .collect()
+}
+
+fn temp_dir() -> Result<tempfile::TempDir> {
+    tempfile::TempDir::new().map_err(|e| {
+        Error::File(FileError::CreateDirectoryFailed {
+            path: std::env::temp_dir(),
+            reason: e.to_string(),
+        })
+    })
+}
+
+/// 把各输入文件开头的完整记录复制到 `dir`（保留原文件名，供按文件名日期过滤），返回样本文件数。
+/// 压缩输入无法按字节截断，不参与采样
+fn write_sample(files: &[PathBuf], dir: &Path) -> Result<usize> {
+    let mut written = 0;
+    for file in files {
+        if written == SAMPLE_FILES {
+            break;
+        }
+        let read_err = |e: std::io::Error| {
+            Error::File(FileError::ReadFailed {
+                path: file.clone(),
+                reason: e.to_string(),
+            })
+        };
+        if Compression::sniff(file).map_err(read_err)? != Compression::Plain {
+            continue;
+        }
+        let Some(name) = file.file_name() else {
+            continue;
+        };
+        let target = dir.join(name);
+        if target.exists() {
+            continue;
+        }
+        let mut buf = Vec::new();
+        std::fs::File::open(file)
+            .and_then(|f| f.take(SAMPLE_BYTES_PER_FILE).read_to_end(&mut buf))
+            .map_err(read_err)?;
+        if buf.len() as u64 == SAMPLE_BYTES_PER_FILE {
+            let Some(end) = record_boundary(&buf) else {
+                continue;
+            };
+            buf.truncate(end);
+        }
+        std::fs::write(&target, &buf).map_err(|e| {
+            Error::File(FileError::WriteFailed {
+                path: target.clone(),
+                reason: e.to_string(),
+            })
+        })?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+/// 试跑配置：输入换成样本目录，输出与旁路文件都写入 `out`
+fn trial_config(cfg: &Config, exporter: Option<&str>, sample: &Path, out: &Path) -> Config {
+    let mut target = exporter.map_or_else(|| cfg.clone(), |e| sample_config(cfg, e, out));
+    target.sqllog.path = sample.to_string_lossy().into_owned();
+    // 样本文件刚写入，不能按"仍在写入"跳过或等待
+    target.sqllog.active_file = "read".to_string();
+    if let Some(statements) = target.features.statements.as_mut() {
+        statements.file = None;
+    }
+    if let Some(unmeasured) = target
+        .features
+        .unmeasured
+        .as_mut()
+        .filter(|u| u.file.is_some())
+    {
+        unmeasured.file = Some(out.join("unmeasured.csv").to_string_lossy().into_owned());
+    }
+    target
+}
+
+/// 单组参数试跑，返回（记录数，记录/秒）
+fn measure(
+    cfg: &Config,
+    exporter: Option<&str>,
+    sample: &Path,
+    jobs: usize,
+    interrupted: &Arc<AtomicBool>,
+) -> Result<(usize, f64)> {
+    let mut best = (0, 0.0_f64);
+    for _ in 0..ROUNDS {
+        let out = temp_dir()?;
+        let target = trial_config(cfg, exporter, sample, out.path());
+        let compiled = target.validate_and_compile()?;
+        let summary = handle_run(
+            &target,
+            None,
+            exporter.is_none(),
+            true,
+            interrupted,
+            80,
+            false,
+            None,
+            jobs,
+            compiled,
+        )?;
+        #[allow(clippy::cast_precision_loss)]
+        let rate = summary.records as f64 / summary.elapsed.as_secs_f64().max(1e-9);
+        if rate > best.1 {
+            best = (summary.records, rate);
+        }
+    }
+    Ok(best)
+}
+
+/// 在输入样本上试跑，逐项（线程数 → 分块大小 → `SQLite` 批大小）选出吞吐最高的参数。
+///
+/// `max_jobs` 为线程数上限（`--jobs` / `pipeline.workers` / CPU 核数）。标准输入、没有可采样的
+/// 未压缩文件或调优被中断时返回 `None`，按原参数运行。
+pub fn auto_tune(
+    cfg: &Config,
+    max_jobs: usize,
+    interrupted: &Arc<AtomicBool>,
+) -> Result<Option<Tuning>> {
+    if is_stdin(Path::new(&cfg.sqllog.path)) {
+        warn!("auto-tune: stdin input cannot be sampled, using configured parameters");
+        return Ok(None);
+    }
+    let log_files = SqllogParser::from_config(&cfg.sqllog)?.log_files()?;
+    let sample = temp_dir()?;
+    let files = write_sample(&log_files, sample.path())?;
+    if files == 0 {
+        warn!("auto-tune: no uncompressed log file to sample, using configured parameters");
+        return Ok(None);
+    }
+
+    // 只在本地导出目标上试跑；仅配置 Elasticsearch 时以 dry-run 测量解析吞吐
+    let configured = cfg.exporter.configured();
+    let exporter = ["csv", "sqlite"]
+        .into_iter()
+        .find(|e| configured.contains(e));
+    if exporter.is_none() && !configured.is_empty() {
+        warn!(
+            "auto-tune: remote exporters are not written during calibration; measuring parse throughput only"
+        );
+    }
+
+    let mut trial_cfg = cfg.clone();
+    let mut chosen_jobs = max_jobs.max(1);
+    let mut trials = Vec::new();
+    let mut sample_records = 0;
+    let mut run_trial = |trial_cfg: &Config, jobs: usize| -> Result<Option<f64>> {
+        if interrupted.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+        let (records, rate) = measure(trial_cfg, exporter, sample.path(), jobs, interrupted)?;
+        sample_records = records;
+        let sqlite_batch_size = (exporter == Some("sqlite"))
+            .then(|| trial_cfg.exporter.sqlite.as_ref().map(|s| s.batch_size))
+            .flatten();
+        info!(
+            "auto-tune: jobs {jobs}, chunk_size {}, sqlite batch_size {sqlite_batch_size:?} → {rate:.0} records/s",
+            trial_cfg.pipeline.chunk_size
+        );
+        trials.push(TuneTrial {
+            jobs,
+            chunk_size: trial_cfg.pipeline.chunk_size,
+            sqlite_batch_size,
+            records_per_sec: rate,
+        });
+        Ok(Some(rate))
+    };
+
+    // 线程数只影响多文件并发路径，单个样本文件时保持原值
+    if files > 1 {
+        let mut best = 0.0;
+        for jobs in with_current(chosen_jobs, &job_candidates(chosen_jobs)) {
+            let Some(rate) = run_trial(&trial_cfg, jobs)? else {
+                return Ok(None);
+            };
+            if rate > best {
+                (best, chosen_jobs) = (rate, jobs);
+            }
+        }
+    }
+
+    let mut best = 0.0;
+    let mut chosen_chunk = trial_cfg.pipeline.chunk_size;
+    for chunk in with_current(chosen_chunk, CHUNK_SIZES) {
+        trial_cfg.pipeline.chunk_size = chunk;
+        let Some(rate) = run_trial(&trial_cfg, chosen_jobs)? else {
+            return Ok(None);
+        };
+        if rate > best {
+            (best, chosen_chunk) = (rate, chunk);
+        }
+    }
+    trial_cfg.pipeline.chunk_size = chosen_chunk;
+
+    let mut chosen_batch = None;
+    if exporter == Some("sqlite") {
+        let current = trial_cfg
+            .exporter
+            .sqlite
+            .as_ref()
+            .map_or(SQLITE_BATCH_SIZES[1], |s| s.batch_size);
+        let mut best = 0.0;
+        for batch in with_current(current, SQLITE_BATCH_SIZES) {
+            if let Some(sqlite) = trial_cfg.exporter.sqlite.as_mut() {
+                sqlite.batch_size = batch;
+            }
+            let Some(rate) = run_trial(&trial_cfg, chosen_jobs)? else {
+                return Ok(None);
+            };
+            if rate > best {
+                (best, chosen_batch) = (rate, Some(batch));
+            }
+        }
+    }
+
+    let tuning = Tuning {
+        jobs: chosen_jobs,
+        chunk_size: chosen_chunk,
+        sqlite_batch_size: chosen_batch,
+        sample_records,
+        trials,
+    };
+    info!(
+        "auto-tune: chose {} after {} trials on {sample_records} sample records",
+        tuning.reproduce_args(),
+        tuning.trials.len()
+    );
+    Ok(Some(tuning))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidates_and_boundary() {
+        assert_eq!(job_candidates(1), [1]);
+        assert_eq!(job_candidates(6), [1, 2, 4, 6]);
+        assert_eq!(job_candidates(8), [1, 2, 4, 8]);
+        assert_eq!(
+            with_current(100_000, CHUNK_SIZES),
+            [100_000, 20_000, 500_000]
+        );
+        assert_eq!(with_current(7, &[1, 2]), [7, 1, 2]);
+
+        // 多行 SQL 的续行不是记录起点；末尾被截断的记录不保留
+        let complete: &[u8] = b"2025-01-15 10:00:00.000 (EP[0]) SELECT 1\nAND 2\n";
+        let buf = [complete, b"2025-01-15 10:00:01.000 (EP[0]) SEL"].concat();
+        assert_eq!(record_boundary(&buf), Some(complete.len()));
+        assert_eq!(record_boundary(b"no records here\n"), None);
+    }
+
+    #[test]
+    fn test_auto_tune_on_sample() {
+        let dir = temp_dir().unwrap();
+        let logs = dir.path().join("logs");
+        std::fs::create_dir_all(&logs).unwrap();
+        for name in ["a.log", "b.log"] {
+            std::fs::write(logs.join(name), crate::testing::fixture_log(200)).unwrap();
+        }
+        let mut cfg = Config::default();
+        cfg.sqllog.path = logs.to_string_lossy().into_owned();
+        cfg.exporter.csv.as_mut().unwrap().file =
+            dir.path().join("out.csv").to_string_lossy().into_owned();
+
+        let tuning = auto_tune(&cfg, 2, &Arc::new(AtomicBool::new(false)))
+            .unwrap()
+            .unwrap();
+        assert!([1, 2].contains(&tuning.jobs));
+        assert!(CHUNK_SIZES.contains(&tuning.chunk_size));
+        assert_eq!(tuning.sqlite_batch_size, None);
+        assert_eq!(tuning.sample_records, 400);
+        // 线程数 2 组 + 分块大小 3 组
+        assert_eq!(tuning.trials.len(), 5);
+        assert!(
+            !dir.path().join("out.csv").exists(),
+            "trials must not touch the real output"
+        );
+        assert!(tuning.reproduce_args().starts_with("--jobs "));
+
+        let mut applied = cfg.clone();
+        tuning.apply(&mut applied);
+        assert_eq!(applied.pipeline.chunk_size, tuning.chunk_size);
+    }
+}
@@ -1,5 +1,8 @@
+use super::preflight::PreflightResult;
 use crate::config::Config;
+use crate::exporter::{ElasticsearchExporter, SqliteExporter};
 use log::info;
+use std::path::Path;
 
 pub fn handle_validate(cfg: &Config) {
     info!("SQL日志输入路径: {}", cfg.sqllog.path);
@@ -74,4 +77,90 @@ pub fn handle_validate(cfg: &Config) {
             if sqlite.overwrite { "yes" } else { "no" }
         );
     }
+    if let Some(es) = &cfg.exporter.elasticsearch {
+        info!(
+            "Elasticsearch export: {} / {} (batch size: {})",
+            es.url, es.index, es.batch_size
+        );
+    }
+    if let Some(custom) = &cfg.exporter.custom {
+        info!("Custom export: {}", custom.name);
+    }
+}
+
+/// 配置解析之外的深度检查：日志路径存在且含 `.log` 文件、输出目录可写（与 `run` 的预检相同）；
+/// `check_connectivity` 时再连接实际使用的导出目标，配置错误在长时间运行之前暴露
+#[must_use]
+pub fn probe(cfg: &Config, check_connectivity: bool) -> PreflightResult {
+    let mut result = super::preflight::check(cfg);
+    if check_connectivity {
+        check_targets(cfg, &mut result);
+    }
+    result
+}
+
+/// 按导出器优先级（csv > sqlite > elasticsearch > custom）只探测实际使用的目标
+fn check_targets(cfg: &Config, result: &mut PreflightResult) {
+    let exporter = &cfg.exporter;
+    if let Some(csv) = &exporter.csv {
+        // tcp:// 目标不预先连接：一次性的监听端（如 nc -l）会被探测连接占用
+        if crate::exporter::sink::tcp_addr(Path::new(&csv.file)).is_some() {
+            result
+                .warnings
+                .push(format!("{}: TCP 输出目标不做连通性检查", csv.file));
+        }
+    } else if let Some(sqlite) = &exporter.sqlite {
+        match SqliteExporter::probe(&sqlite.database_url) {
+            Ok(target) => info!("SQLite target: {target}"),
+            Err(e) => result.errors.push(e.to_string()),
+        }
+    } else if let Some(es) = &exporter.elasticsearch {
+        match ElasticsearchExporter::from_config(es).probe() {
+            Ok(target) => info!("Elasticsearch target: {} ({target})", es.url),
+            Err(e) => result.errors.push(e.to_string()),
+        }
+    } else if let Some(custom) = &exporter.custom {
+        result
+            .warnings
+            .push(format!("{}: 第三方导出器不做连通性检查", custom.name));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ExporterConfig, SqliteExporter as SqliteConfig, SqllogConfig};
+
+    #[test]
+    fn test_probe_checks_log_dir_and_sqlite_target() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("out").join("export.db");
+        let mut cfg = Config {
+            sqllog: SqllogConfig {
+                path: dir.path().join("missing").to_string_lossy().into_owned(),
+                ..Default::default()
+            },
+            exporter: ExporterConfig {
+                csv: None,
+                sqlite: Some(SqliteConfig {
+                    database_url: db.to_string_lossy().into_owned(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(probe(&cfg, false).has_errors());
+
+        std::fs::write(dir.path().join("a.log"), "").unwrap();
+        cfg.sqllog.path = dir.path().to_string_lossy().into_owned();
+        let result = probe(&cfg, true);
+        assert!(!result.has_errors(), "{:?}", result.errors);
+        // 探测不留下输出文件
+        assert!(!db.exists());
+
+        // 目标库路径是目录：打开失败
+        std::fs::create_dir_all(&db).unwrap();
+        assert!(probe(&cfg, true).has_errors());
+    }
 }
@@ -0,0 +1,517 @@
+//! `run --watch`：持续跟踪日志目录，把新追加到 dmsql 日志文件末尾的记录增量导出。
+//!
+//! 每隔 `--watch-interval` 秒扫描一次 `sqllog.path`，按文件记录已导出的字节位置；文件变长时把新增部分
+//! （截至最后一条完整记录）复制到临时目录，沿用正式运行的配置导出一轮。首轮导出目录中已有的全部内容，
+//! 之后各轮以追加方式写入同一目标（`SQLite` 关闭同日重复装载检测）。
+//!
+//! 末条记录带 `EXEC_ID` 后缀、或文件静默超过 `sqllog.active_file_secs` 时才视为完整，否则留待下一轮；
+//! 文件变短（被截断或轮转替换）时从头读取。压缩文件只在首次出现时整体导出一次。
+//! 模板统计、语句登记表与图表等伴随输出按轮生成，反映最近一轮的数据。
+//! 读取位置只保存在内存中，进程重启后从头导出。不依赖文件系统事件通知，网络文件系统上同样可用。
+use super::run::{RunSummary, handle_run_as};
+use crate::color;
+use crate::config::Config;
+use crate::error::{Error, FileError, Result};
+use crate::parser::SqllogParser;
+use crate::parser::active::record_boundary;
+use crate::parser::input::Compression;
+use ahash::HashMap as AHashMap;
+use log::info;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime};
+
+/// 等待下一轮扫描时检查中断标志的间隔
+const POLL: Duration = Duration::from_millis(200);
+
+/// 定位末条记录时首次读取的尾部窗口；找不到记录起点时倍增
+const TAIL_WINDOW: u64 = 64 * 1024;
+
+/// 各文件已导出到的字节位置
+#[derive(Debug, Default)]
+struct Cursors(AHashMap<PathBuf, u64>);
+
+/// 本轮待导出的一段新增内容
+#[derive(Debug)]
+struct Delta {
+    file: PathBuf,
+    /// 临时目录中的副本文件名
+    copy: std::ffi::OsString,
+    end: u64,
+}
+
+fn read_err(path: &Path, e: &std::io::Error) -> Error {
+    Error::File(FileError::ReadFailed {
+        path: path.to_path_buf(),
+        reason: e.to_string(),
+    })
+}
+
+/// `buf` 中可以导出的长度：末条记录完整（带 `EXEC_ID` 后缀并以换行结尾）或文件已静默时取全部，
+/// 否则截至末条记录之前
+fn complete_len(buf: &[u8], settled: bool) -> usize {
+    let last = record_boundary(buf).unwrap_or(0);
+    let finished =
+        buf.ends_with(b"\n") && memchr::memmem::find(&buf[last..], b"EXEC_ID:").is_some();
+    if settled || finished { buf.len() } else { last }
+}
+
+/// 文件 `[start, len)` 中可以导出的字节数（规则同 [`complete_len`]）。
+/// 只读取末尾窗口定位最后一条记录，窗口内找不到记录起点时倍增，不把整段新增内容读入内存
+fn exportable_len(f: &mut File, start: u64, len: u64, settled: bool) -> std::io::Result<u64> {
+    if settled {
+        return Ok(len - start);
+    }
+    let mut window = TAIL_WINDOW;
+    let mut buf = Vec::new();
+    loop {
+        let from = len.saturating_sub(window).max(start);
+        buf.clear();
+        f.seek(SeekFrom::Start(from))?;
+        f.by_ref().take(len - from).read_to_end(&mut buf)?;
+        if from == start || record_boundary(&buf).is_some() {
+            return Ok(from - start + complete_len(&buf, false) as u64);
+        }
+        window *= 2;
+    }
+}
+
+impl Cursors {
+    /// 把各文件的新增内容复制到 `dir`，返回待提交的位置。副本以文件序号为前缀（`00001-dmsql_1.log`），
+    /// 不同子目录下的同名文件互不覆盖，顺序与原文件一致
+    fn collect(&self, files: &[PathBuf], dir: &Path, quiet: Duration) -> Result<Vec<Delta>> {
+        let mut deltas = Vec::new();
+        for (i, file) in files.iter().enumerate() {
+            let Some(name) = file.file_name() else {
+                continue;
+            };
+            let mut copy = std::ffi::OsString::from(format!("{i:05}-"));
+            copy.push(name);
+            let target = dir.join(&copy);
+            let meta = std::fs::metadata(file).map_err(|e| read_err(file, &e))?;
+            let len = meta.len();
+            let seen = self.0.get(file).copied();
+            if Compression::sniff(file).map_err(|e| read_err(file, &e))? != Compression::Plain {
+                if seen.is_none() {
+                    std::fs::copy(file, &target).map_err(|e| read_err(file, &e))?;
+                    deltas.push(Delta {
+                        file: file.clone(),
+                        copy,
+                        end: len,
+                    });
+                }
+                continue;
+            }
+            // 文件变短：被截断或轮转替换，从头读取
+            let start = seen.filter(|&s| s <= len).unwrap_or(0);
+            if start == len {
+                continue;
+            }
+            let settled = meta
+                .modified()
+                .ok()
+                .and_then(|t| SystemTime::now().duration_since(t).ok())
+                .is_some_and(|age| age >= quiet);
+            let mut f = File::open(file).map_err(|e| read_err(file, &e))?;
+            let n = exportable_len(&mut f, start, len, settled).map_err(|e| read_err(file, &e))?;
+            if n == 0 {
+                continue;
+            }
+            // 流式复制到最后一条完整记录为止
+            f.seek(SeekFrom::Start(start))
+                .map_err(|e| read_err(file, &e))?;
+            File::create(&target)
+                .and_then(|mut out| std::io::copy(&mut f.by_ref().take(n), &mut out))
+                .map_err(|e| {
+                    Error::File(FileError::WriteFailed {
+                        path: target.clone(),
+                        reason: e.to_string(),
+                    })
+                })?;
+            deltas.push(Delta {
+                file: file.clone(),
+                copy,
+                end: start + n,
+            });
+        }
+        Ok(deltas)
+    }
+
+    fn commit(&mut self, deltas: Vec<Delta>) {
+        for d in deltas {
+            self.0.insert(d.file, d.end);
+        }
+    }
+}
+
+/// 一轮导出的配置：输入换成临时目录；首轮之后改为追加写入
+fn cycle_config(cfg: &Config, dir: &Path, first: bool) -> Config {
+    let mut c = cfg.clone();
+    c.sqllog.path = dir.to_string_lossy().into_owned();
+    // 不完整的末条记录已在复制时截掉；新增片段与校验清单对不上
+    c.sqllog.active_file = "read".to_string();
+    c.sqllog.verify_checksums = false;
+    if !first {
+        if let Some(csv) = c.exporter.csv.as_mut() {
+            csv.append = true;
+        }
+        if let Some(sqlite) = c.exporter.sqlite.as_mut() {
+            sqlite.overwrite = false;
+            sqlite.append = true;
+            sqlite.duplicate_check = "off".to_string();
+        }
+    }
+    c
+}
+
+/// 汇总各轮结果：计数（含逐文件与各导出器明细）累加，其余字段取最后一轮
+fn merge(total: &mut Option<RunSummary>, cycle: RunSummary) {
+    let Some(t) = total else {
+        *total = Some(cycle);
+        return;
+    };
+    let m = cycle.metrics;
+    t.records += cycle.records;
+    t.metrics.files += m.files;
+    t.metrics.parsed += m.parsed;
+    t.metrics.exported += m.exported;
+    t.metrics.errors += m.errors;
+    t.metrics.recovered += m.recovered;
+    t.metrics.exec_id_coerced += m.exec_id_coerced;
+    t.warnings.extend(cycle.warnings);
+    for f in cycle.per_file {
+        match t.per_file.iter_mut().find(|x| x.path == f.path) {
+            Some(x) => {
+                x.records += f.records;
+                x.parsed += f.parsed;
+                x.errors += f.errors;
+                x.recovered += f.recovered;
+                x.elapsed_ms += f.elapsed_ms;
+            }
+            None => t.per_file.push(f),
+        }
+    }
+    for e in cycle.exporters {
+        match t.exporters.iter_mut().find(|x| x.name == e.name) {
+            Some(x) => {
+                x.exported += e.exported;
+                x.duplicates += e.duplicates;
+                x.skipped += e.skipped;
+                x.failed += e.failed;
+                match (&mut x.flush_latency, e.flush_latency) {
+                    (Some(total), Some(latency)) => total.merge(&latency),
+                    (total @ None, latency) => *total = latency,
+                    (Some(_), None) => {}
+                }
+                x.flush_operations = x
+                    .flush_operations
+                    .zip(e.flush_operations)
+                    .map(|(a, b)| a + b);
+            }
+            None => t.exporters.push(e),
+        }
+    }
+    for e in cycle.parse_errors {
+        match t.parse_errors.iter_mut().find(|x| x.kind == e.kind) {
+            Some(x) => x.count += e.count,
+            None => t.parse_errors.push(e),
+        }
+    }
+    t.parse_errors.sort_by_key(|e| std::cmp::Reverse(e.count));
+    t.templates = cycle.templates;
+    t.statements = cycle.statements;
+    t.exec_id_audit = cycle.exec_id_audit;
+}
+
+/// 持续跟踪直到中断（Ctrl+C）或达到 `max_duration`；返回各轮汇总
+pub fn watch(
+    cfg: &Config,
+    interval: Duration,
+    max_duration: Option<Duration>,
+    quiet: bool,
+    interrupted: &Arc<AtomicBool>,
+    progress_interval: u64,
+    jobs: usize,
+) -> Result<RunSummary> {
+    let start = Instant::now();
+    // 各轮导出共用一个运行标识
+    let run_id = crate::run_id::begin();
+    // 每轮重新扫描，不使用运行开始时的文件清单快照
+    let mut sqllog = cfg.sqllog.clone();
+    sqllog.listing = None;
+    let quiet_after = Duration::from_secs(cfg.sqllog.active_file_secs);
+    let mut cursors = Cursors::default();
+    let mut total: Option<RunSummary> = None;
+    let mut cycles = 0usize;
+    if !quiet {
+        eprintln!(
+            "{} Watching {} (every {}s, Ctrl+C to stop)...",
+            color::cyan("▶"),
+            cfg.sqllog.path,
+            interval.as_secs_f64()
+        );
+    }
+    loop {
+        let remaining = max_duration.map(|d| d.saturating_sub(start.elapsed()));
+        if interrupted.load(Ordering::Relaxed) || remaining.is_some_and(|r| r.is_zero()) {
+            break;
+        }
+        let files = SqllogParser::from_config(&sqllog)?.log_files()?;
+        let dir = tempfile::TempDir::new().map_err(|e| {
+            Error::File(FileError::CreateDirectoryFailed {
+                path: std::env::temp_dir(),
+                reason: e.to_string(),
+            })
+        })?;
+        let deltas = cursors.collect(&files, dir.path(), quiet_after)?;
+        if !deltas.is_empty() {
+            let target = cycle_config(cfg, dir.path(), cycles == 0);
+            let compiled = target.validate_and_compile()?;
+            let mut summary = handle_run_as(
+                Arc::clone(&run_id),
+                &target,
+                None,
+                remaining,
+                false,
+                false,
+                true,
+                interrupted,
+                progress_interval,
+                false,
+                None,
+                jobs,
+                compiled,
+            )?;
+            // 逐文件明细记回原始路径
+            for f in &mut summary.per_file {
+                if let Some(d) = deltas
+                    .iter()
+                    .find(|d| Some(d.copy.as_os_str()) == Path::new(&f.path).file_name())
+                {
+                    f.path = d.file.to_string_lossy().into_owned();
+                }
+            }
+            info!(
+                "Watch batch {}: {} records from {} files",
+                cycles + 1,
+                summary.records,
+                deltas.len()
+            );
+            if !quiet {
+                eprintln!(
+                    "  {} +{} records from {} files",
+                    color::dim(chrono::Local::now().format("%H:%M:%S").to_string()),
+                    summary.records,
+                    deltas.len()
+                );
+            }
+            cursors.commit(deltas);
+            merge(&mut total, summary);
+            cycles += 1;
+            continue;
+        }
+        let wake = Instant::now() + interval;
+        while Instant::now() < wake && !interrupted.load(Ordering::Relaxed) {
+            std::thread::sleep(POLL.min(wake.saturating_duration_since(Instant::now())));
+        }
+    }
+
+    let mut summary = total.unwrap_or_else(|| RunSummary {
+        run_id: run_id.to_string(),
+        ..RunSummary::default()
+    });
+    summary.files = cursors.0.len();
+    summary.elapsed = start.elapsed();
+    if !quiet {
+        eprintln!(
+            "\n{} Watch stopped after {:.0}s — {} records in {cycles} batches from {} files",
+            color::green("✓"),
+            summary.elapsed.as_secs_f64(),
+            color::green(summary.records),
+            summary.files
+        );
+        eprintln!("  run id: {}", color::dim(&summary.run_id));
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINE: &str = "2025-01-15 10:30:28.001 (EP[0] sess:0x0001 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n";
+
+    #[test]
+    fn test_complete_len_holds_unfinished_tail() {
+        let head = LINE.as_bytes();
+        let partial = [
+            head,
+            b"2025-01-15 10:30:29.001 (EP[0] sess:0x0001) [DDL] CREATE",
+        ]
+        .concat();
+        assert_eq!(complete_len(head, false), head.len());
+        assert_eq!(complete_len(&partial, false), head.len());
+        assert_eq!(complete_len(&partial, true), partial.len());
+        // 没有性能指标的末条记录等下一条记录或文件静默
+        let ddl = [
+            head,
+            b"2025-01-15 10:30:29.001 (EP[0]) [DDL] CREATE TABLE t (x INT)\n",
+        ]
+        .concat();
+        assert_eq!(complete_len(&ddl, false), head.len());
+    }
+
+    #[test]
+    fn test_exportable_len_reads_back_past_tail_window() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("dmsql_1.log");
+        // 末条记录长于尾部窗口：需要倍增窗口才能找到它的起点
+        let long = format!(
+            "2025-01-15 10:30:29.001 (EP[0] sess:0x0001) [SEL] SELECT '{}'",
+            "x".repeat(200_000)
+        );
+        let content = format!("{}{long}", LINE.repeat(2));
+        std::fs::write(&path, &content).unwrap();
+        let len = content.len() as u64;
+        let mut f = File::open(&path).unwrap();
+        assert_eq!(
+            exportable_len(&mut f, 0, len, false).unwrap(),
+            2 * LINE.len() as u64
+        );
+        assert_eq!(
+            exportable_len(&mut f, LINE.len() as u64, len, false).unwrap(),
+            LINE.len() as u64
+        );
+        assert_eq!(exportable_len(&mut f, 0, len, true).unwrap(), len);
+
+        let finished = format!("{content}. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 2.\n");
+        std::fs::write(&path, &finished).unwrap();
+        let mut f = File::open(&path).unwrap();
+        let len = finished.len() as u64;
+        assert_eq!(exportable_len(&mut f, 0, len, false).unwrap(), len);
+    }
+
+    #[test]
+    fn test_cursors_follow_appends_and_truncation() {
+        let logs = tempfile::TempDir::new().unwrap();
+        let file = logs.path().join("dmsql_1.log");
+        std::fs::write(&file, LINE.repeat(2)).unwrap();
+        let files = vec![file.clone()];
+        let mut cursors = Cursors::default();
+        let quiet = Duration::from_secs(3600);
+
+        let round = |cursors: &mut Cursors| {
+            let dir = tempfile::TempDir::new().unwrap();
+            let deltas = cursors.collect(&files, dir.path(), quiet).unwrap();
+            let copied =
+                std::fs::read_to_string(dir.path().join("00000-dmsql_1.log")).unwrap_or_default();
+            cursors.commit(deltas);
+            copied.lines().count()
+        };
+        assert_eq!(round(&mut cursors), 2);
+        assert_eq!(round(&mut cursors), 0);
+
+        let mut f = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&file)
+            .unwrap();
+        std::io::Write::write_all(&mut f, LINE.repeat(3).as_bytes()).unwrap();
+        assert_eq!(round(&mut cursors), 3);
+
+        std::fs::write(&file, LINE).unwrap();
+        assert_eq!(round(&mut cursors), 1);
+    }
+
+    /// 不同子目录下的同名文件每轮都复制，互不覆盖
+    #[test]
+    fn test_collect_same_name_in_different_dirs() {
+        let logs = tempfile::TempDir::new().unwrap();
+        let files: Vec<PathBuf> = ["a", "b"]
+            .iter()
+            .map(|d| {
+                std::fs::create_dir(logs.path().join(d)).unwrap();
+                let file = logs.path().join(d).join("x.log");
+                std::fs::write(&file, LINE).unwrap();
+                file
+            })
+            .collect();
+        let mut cursors = Cursors::default();
+        let quiet = Duration::from_secs(3600);
+
+        for appended in [1, 2] {
+            let dir = tempfile::TempDir::new().unwrap();
+            let deltas = cursors.collect(&files, dir.path(), quiet).unwrap();
+            assert_eq!(deltas.len(), 2);
+            for (i, name) in ["00000-x.log", "00001-x.log"].iter().enumerate() {
+                let copied = std::fs::read_to_string(dir.path().join(name)).unwrap();
+                assert_eq!(copied.lines().count(), appended, "{name}");
+                assert_eq!(deltas[i].file, files[i]);
+            }
+            cursors.commit(deltas);
+            for file in &files {
+                let mut f = std::fs::OpenOptions::new().append(true).open(file).unwrap();
+                std::io::Write::write_all(&mut f, LINE.repeat(2).as_bytes()).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_watch_exports_appended_records_until_interrupted() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logs = dir.path().join("logs");
+        std::fs::create_dir(&logs).unwrap();
+        let file = logs.join("dmsql_1.log");
+        std::fs::write(&file, LINE.repeat(4)).unwrap();
+        let db = dir
+            .path()
+            .join("out.db")
+            .to_string_lossy()
+            .replace('\\', "/");
+        let toml = format!(
+            "[sqllog]\npath = \"{logdir}\"\n[logging]\nfile = \"{applog}\"\nlevel = \"warn\"\nretention_days = 1\n[exporter.sqlite]\ndatabase_url = \"{db}\"\n",
+            logdir = logs.to_string_lossy().replace('\\', "/"),
+            applog = dir
+                .path()
+                .join("app.log")
+                .to_string_lossy()
+                .replace('\\', "/"),
+        );
+        let cfg: Config = toml::from_str(&toml).unwrap();
+
+        let interrupted = Arc::new(AtomicBool::new(false));
+        let writer = {
+            let (file, interrupted) = (file.clone(), Arc::clone(&interrupted));
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(400));
+                let mut f = std::fs::OpenOptions::new()
+                    .append(true)
+                    .open(&file)
+                    .unwrap();
+                std::io::Write::write_all(&mut f, LINE.repeat(3).as_bytes()).unwrap();
+                std::thread::sleep(Duration::from_millis(600));
+                interrupted.store(true, Ordering::Relaxed);
+            })
+        };
+        let summary = watch(
+            &cfg,
+            Duration::from_millis(100),
+            Some(Duration::from_secs(30)),
+            true,
+            &interrupted,
+            80,
+            1,
+        )
+        .unwrap();
+        writer.join().unwrap();
+
+        assert_eq!(summary.records, 7);
+        let rows: i64 = rusqlite::Connection::open(&db)
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM sqllog_records", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(rows, 7);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::error::{ConfigError, Error, Result};
+use chrono::NaiveDate;
 
 pub const LOG_LEVELS: &[&str] = &["trace", "debug", "info", "warn", "error"];
 pub use crate::features::FeaturesConfig;
@@ -6,6 +7,7 @@ use serde::Deserialize;
 use std::path::Path;
 
 #[derive(Debug, Deserialize, Clone, Default)]
+#[non_exhaustive]
 pub struct Config {
     #[serde(default)]
     pub sqllog: SqllogConfig,
@@ -17,9 +19,18 @@ pub struct Config {
     pub exporter: ExporterConfig,
     #[serde(default)]
     pub resume: ResumeConfig,
+    #[serde(default)]
+    pub pipeline: PipelineConfig,
+    #[serde(default)]
+    pub error: ErrorConfig,
+    #[serde(default)]
+    pub notify: Option<crate::notify::NotifyConfig>,
+    #[serde(default)]
+    pub report: Option<crate::run_report::RunReportConfig>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
+#[non_exhaustive]
 pub struct ResumeConfig {
     /// 状态文件路径，`--resume` 模式下用于记录已处理文件的指纹
     #[serde(default = "default_state_file")]
@@ -38,23 +49,449 @@ impl Default for ResumeConfig {
     }
 }
 
+/// 处理管线配置（`[pipeline]`）
+#[derive(Debug, Deserialize, Clone)]
+#[non_exhaustive]
+pub struct PipelineConfig {
+    /// 分块大小（条）：每导出这么多条记录，导出器把已写数据交给下游（CSV 刷新缓冲、SQLite 提交并释放页缓存），
+    /// 草稿缓冲区收缩回初始容量；内存占用与输入总量无关
+    #[serde(default = "default_chunk_size")]
+    pub chunk_size: usize,
+    /// 多文件并发解析的工作线程数；未设置时取 CPU 核数，命令行 `--jobs` 优先
+    #[serde(default)]
+    pub workers: Option<usize>,
+}
+
+fn default_chunk_size() -> usize {
+    100_000
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            chunk_size: default_chunk_size(),
+            workers: None,
+        }
+    }
+}
+
+impl PipelineConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.chunk_size == 0 {
+            return Err(Error::Config(ConfigError::InvalidValue {
+                field: "pipeline.chunk_size".to_string(),
+                value: "0".to_string(),
+                reason: "chunk_size must be greater than 0".to_string(),
+            }));
+        }
+        if self.workers == Some(0) {
+            return Err(Error::Config(ConfigError::InvalidValue {
+                field: "pipeline.workers".to_string(),
+                value: "0".to_string(),
+                reason: "workers must be greater than 0".to_string(),
+            }));
+        }
+        Ok(())
+    }
+}
+
+/// 错误预算（`[error]`）：超过阈值的运行以独立退出码结束，脚本可区分"干净运行"与"跑完但大量记录解析失败"
+#[derive(Debug, Deserialize, Clone, Default)]
+#[non_exhaustive]
+pub struct ErrorConfig {
+    /// 解析错误率上限（百分比，0–100）：解析失败记录数 / 已解析记录数（含失败）超过该值时运行失败，退出码 5；
+    /// 未设置时不检查
+    #[serde(default)]
+    pub max_parse_error_rate: Option<f64>,
+}
+
+impl ErrorConfig {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(rate) = self.max_parse_error_rate {
+            if !(0.0..=100.0).contains(&rate) {
+                return Err(Error::Config(ConfigError::InvalidValue {
+                    field: "error.max_parse_error_rate".to_string(),
+                    value: rate.to_string(),
+                    reason: "expected a percentage between 0 and 100".to_string(),
+                }));
+            }
+        }
+        Ok(())
+    }
+
+    /// 按运行计数检查错误预算；未解析到任何记录时不判失败
+    pub fn check(&self, metrics: &crate::metrics::MetricsSnapshot) -> Result<()> {
+        let Some(max) = self.max_parse_error_rate else {
+            return Ok(());
+        };
+        if metrics.parsed == 0 {
+            return Ok(());
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let rate = metrics.errors as f64 / metrics.parsed as f64 * 100.0;
+        if rate > max {
+            return Err(Error::BudgetExceeded {
+                errors: metrics.errors,
+                parsed: metrics.parsed,
+                rate,
+                max,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// 本二进制内置的导出器（`[exporter.*]` 段名），顺序即同时配置时的选择优先级
+pub const EXPORTER_NAMES: &[&str] = &["csv", "sqlite", "elasticsearch", "custom"];
+
+/// 未内置导出器的 `[exporter.*]` 段会被反序列化静默丢弃，运行时落到其他导出器或报"未配置导出器"；
+/// 加载时按原始 TOML（含环境变量覆盖）检查段名，直接报错
+fn check_exporter_sections(path: &Path, table: &toml::Table) -> Result<()> {
+    let Some(exporters) = table.get("exporter").and_then(toml::Value::as_table) else {
+        return Ok(());
+    };
+    match exporters
+        .keys()
+        .find(|name| !EXPORTER_NAMES.contains(&name.as_str()))
+    {
+        Some(name) => Err(Error::Config(ConfigError::UnsupportedExporter {
+            path: path.to_path_buf(),
+            name: name.clone(),
+            built_in: EXPORTER_NAMES.join(", "),
+        })),
+        None => Ok(()),
+    }
+}
+
+/// 覆盖配置项的环境变量前缀
+pub const ENV_PREFIX: &str = "SQLLOG2DB_";
+
+/// 配置路径的分隔符：`SQLLOG2DB_EXPORTER__ELASTICSEARCH__PASSWORD` → `exporter.elasticsearch.password`
+const ENV_SEPARATOR: &str = "__";
+
+/// 取出环境变量中的配置项（`std::env::vars()` 遇到非 UTF-8 的变量会 panic）：
+/// 与本工具无关的非 UTF-8 变量跳过；`SQLLOG2DB_` 开头的变量名或值不是 UTF-8 时报错
+fn env_vars(
+    vars: impl IntoIterator<Item = (std::ffi::OsString, std::ffi::OsString)>,
+) -> Result<Vec<(String, String)>> {
+    let mut out = Vec::new();
+    for (name, value) in vars {
+        let lossy = name.to_string_lossy();
+        if !lossy.starts_with(ENV_PREFIX) {
+            continue;
+        }
+        let invalid = |reason: &str| {
+            Error::Config(ConfigError::InvalidValue {
+                field: lossy.to_string(),
+                value: String::new(),
+                reason: reason.to_string(),
+            })
+        };
+        let name = name
+            .to_str()
+            .ok_or_else(|| invalid("environment variable name is not valid UTF-8"))?;
+        let value = value
+            .into_string()
+            .map_err(|_| invalid("environment variable value is not valid UTF-8"))?;
+        out.push((name.to_string(), value));
+    }
+    Ok(out)
+}
+
+/// 把 `SQLLOG2DB_<SECTION>__<KEY>` 形式的环境变量叠加到配置表上，返回被覆盖的键（按变量名排序）
+///
+/// 变量名不含 `__` 的（`SQLLOG2DB_CONFIG`、`SQLLOG2DB_RUN_ID` 等）不是配置项，忽略。
+/// 目标字段是字符串（按 [`field_kind`] 探测，或文件中该键已是字符串）时原样作为字符串，
+/// 纯数字的密码不会被误读为整数；其余按 TOML 字面量解析（`true`、`42`、`["a", "b"]`），解析失败时作为字符串。
+fn apply_env_overrides(
+    table: &mut toml::Table,
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<Vec<String>> {
+    let mut vars: Vec<(String, String)> = vars
+        .into_iter()
+        .filter(|(name, _)| {
+            name.strip_prefix(ENV_PREFIX)
+                .is_some_and(|rest| rest.contains(ENV_SEPARATOR))
+        })
+        .collect();
+    vars.sort();
+    let mut applied = Vec::with_capacity(vars.len());
+    for (name, raw) in vars {
+        let invalid = |reason: String| {
+            Error::Config(ConfigError::InvalidValue {
+                field: name.clone(),
+                value: String::new(),
+                reason,
+            })
+        };
+        let path: Vec<String> = name[ENV_PREFIX.len()..]
+            .split(ENV_SEPARATOR)
+            .map(str::to_ascii_lowercase)
+            .collect();
+        if path.iter().any(String::is_empty) {
+            return Err(invalid(format!(
+                "expected {ENV_PREFIX}<SECTION>{ENV_SEPARATOR}<KEY>, e.g. {ENV_PREFIX}EXPORTER{ENV_SEPARATOR}CSV{ENV_SEPARATOR}FILE"
+            )));
+        }
+        let key = path.join(".");
+        let (leaf, parents) = path.split_last().unwrap_or_else(|| unreachable!());
+        let mut node = &mut *table;
+        for (depth, segment) in parents.iter().enumerate() {
+            node = node
+                .entry(segment.clone())
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+                .as_table_mut()
+                .ok_or_else(|| invalid(format!("'{}' is not a table", path[..=depth].join("."))))?;
+        }
+        let is_string = matches!(node.get(leaf), Some(toml::Value::String(_)))
+            || field_kind(&path) == FieldKind::String;
+        let value = if is_string {
+            toml::Value::String(raw)
+        } else {
+            toml::from_str::<toml::Table>(&format!("v = {raw}"))
+                .ok()
+                .and_then(|mut t| t.remove("v"))
+                .unwrap_or(toml::Value::String(raw))
+        };
+        node.insert(leaf.clone(), value);
+        applied.push(key);
+    }
+    Ok(applied)
+}
+
+/// 配置项的值类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldKind {
+    String,
+    /// 数值或布尔
+    Scalar,
+    /// 列表、表、枚举、自定义反序列化或未知键
+    Other,
+}
+
+/// 按 `Config` 的反序列化定义探测 `path` 处字段的类型，环境变量据此决定是否按字符串处理。
+///
+/// 用 [`KindProbe`] 驱动 `Config::deserialize`：沿路径每层只给出下一段键，到达叶子时记录字段请求的类型后中止。
+/// `flatten` 段内的键与 `deserialize_with` 字段经由 `deserialize_any`，探测结果为 [`FieldKind::Other`]。
+fn field_kind(path: &[String]) -> FieldKind {
+    let kind = std::cell::Cell::new(FieldKind::Other);
+    let _ = Config::deserialize(KindProbe { path, kind: &kind });
+    kind.get()
+}
+
+/// 探测结束（含到达叶子）时返回的错误
+#[derive(Debug)]
+struct ProbeDone;
+
+impl std::fmt::Display for ProbeDone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("field kind probe finished")
+    }
+}
+
+impl std::error::Error for ProbeDone {}
+
+impl serde::de::Error for ProbeDone {
+    fn custom<T: std::fmt::Display>(_msg: T) -> Self {
+        Self
+    }
+}
+
+/// 见 [`field_kind`]
+struct KindProbe<'a> {
+    path: &'a [String],
+    kind: &'a std::cell::Cell<FieldKind>,
+}
+
+impl KindProbe<'_> {
+    fn leaf<T>(self, kind: FieldKind) -> std::result::Result<T, ProbeDone> {
+        if self.path.is_empty() {
+            self.kind.set(kind);
+        }
+        Err(ProbeDone)
+    }
+}
+
+macro_rules! probe_scalar {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: serde::de::Visitor<'de>>(
+                self,
+                _visitor: V,
+            ) -> std::result::Result<V::Value, ProbeDone> {
+                self.leaf(FieldKind::Scalar)
+            }
+        )*
+    };
+}
+
+impl<'de> serde::Deserializer<'de> for KindProbe<'_> {
+    type Error = ProbeDone;
+
+    fn deserialize_any<V: serde::de::Visitor<'de>>(
+        self,
+        _visitor: V,
+    ) -> std::result::Result<V::Value, ProbeDone> {
+        self.leaf(FieldKind::Other)
+    }
+
+    probe_scalar! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+        deserialize_f32 deserialize_f64
+    }
+
+    fn deserialize_str<V: serde::de::Visitor<'de>>(
+        self,
+        _visitor: V,
+    ) -> std::result::Result<V::Value, ProbeDone> {
+        self.leaf(FieldKind::String)
+    }
+
+    fn deserialize_string<V: serde::de::Visitor<'de>>(
+        self,
+        _visitor: V,
+    ) -> std::result::Result<V::Value, ProbeDone> {
+        self.leaf(FieldKind::String)
+    }
+
+    fn deserialize_option<V: serde::de::Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> std::result::Result<V::Value, ProbeDone> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: serde::de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> std::result::Result<V::Value, ProbeDone> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_map<V: serde::de::Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> std::result::Result<V::Value, ProbeDone> {
+        if self.path.is_empty() {
+            return self.leaf(FieldKind::Other);
+        }
+        visitor.visit_map(ProbeKey(Some(self)))
+    }
+
+    fn deserialize_struct<V: serde::de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> std::result::Result<V::Value, ProbeDone> {
+        self.deserialize_map(visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        i128 u128 char bytes byte_buf unit unit_struct seq tuple tuple_struct enum identifier
+        ignored_any
+    }
+}
+
+/// 只含路径下一段键的单键映射
+struct ProbeKey<'a>(Option<KindProbe<'a>>);
+
+impl<'de> serde::de::MapAccess<'de> for ProbeKey<'_> {
+    type Error = ProbeDone;
+
+    fn next_key_seed<K: serde::de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> std::result::Result<Option<K::Value>, ProbeDone> {
+        let Some(probe) = &self.0 else {
+            return Ok(None);
+        };
+        let key = serde::de::value::StrDeserializer::new(probe.path[0].as_str());
+        seed.deserialize(key).map(Some)
+    }
+
+    fn next_value_seed<V: serde::de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> std::result::Result<V::Value, ProbeDone> {
+        let probe = self.0.take().ok_or(ProbeDone)?;
+        seed.deserialize(KindProbe {
+            path: &probe.path[1..],
+            kind: probe.kind,
+        })
+    }
+}
+
 impl Config {
+    /// 以代码方式构造配置（库嵌入场景），见 [`ConfigBuilder`]
+    #[must_use]
+    #[allow(dead_code)]
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+
+    /// 读取配置文件，叠加 `SQLLOG2DB_<SECTION>__<KEY>` 环境变量后反序列化（见 [`ENV_PREFIX`]）
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         let path = path.as_ref();
         let content = std::fs::read_to_string(path)
             .map_err(|_| Error::Config(ConfigError::NotFound(path.to_path_buf())))?;
-        toml::from_str(&content).map_err(|e| {
+        let table = toml::from_str::<toml::Table>(&content).map_err(|e| {
             Error::Config(ConfigError::ParseFailed {
                 path: path.to_path_buf(),
                 reason: e.to_string(),
             })
-        })
+        })?;
+        Self::from_table(path, table, env_vars(std::env::vars_os())?)
+    }
+
+    /// 没有配置文件时的配置：默认值叠加环境变量
+    pub fn from_env() -> Result<Self> {
+        Self::from_table(
+            Path::new("<environment>"),
+            toml::Table::new(),
+            env_vars(std::env::vars_os())?,
+        )
+    }
+
+    fn from_table(
+        path: &Path,
+        mut table: toml::Table,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self> {
+        let overridden = apply_env_overrides(&mut table, vars)?;
+        check_exporter_sections(path, &table)?;
+        let cfg = toml::Value::Table(table)
+            .try_into()
+            .map_err(|e: toml::de::Error| {
+                let mut reason = e.to_string().trim_end().to_string();
+                if !overridden.is_empty() {
+                    reason = format!(
+                        "{reason} (after environment overrides: {})",
+                        overridden.join(", ")
+                    );
+                }
+                Error::Config(ConfigError::ParseFailed {
+                    path: path.to_path_buf(),
+                    reason,
+                })
+            })?;
+        if !overridden.is_empty() {
+            log::info!(
+                "Config keys overridden from environment: {}",
+                overridden.join(", ")
+            );
+        }
+        Ok(cfg)
     }
 
     pub fn validate(&self) -> Result<()> {
         self.logging.validate()?;
         self.exporter.validate()?;
         self.sqllog.validate()?;
+        self.pipeline.validate()?;
+        self.error.validate()?;
         if let Some(filters) = &self.features.filters {
             if filters.enable {
                 crate::features::filters::CompiledMetaFilters::try_from_meta(&filters.meta)?;
@@ -77,6 +514,46 @@ impl Config {
                 }
             }
         }
+        if let Some(time) = &self.features.time {
+            time.compile()?;
+        }
+        if let Some(quota) = &self.features.quota {
+            quota.validate()?;
+        }
+        if let Some(audit) = &self.features.exec_id_audit {
+            audit.validate()?;
+        }
+        if let Some(dedup) = &self.features.dedup {
+            dedup.validate()?;
+        }
+        if let Some(statements) = &self.features.statements {
+            statements.validate()?;
+        }
+        if let Some(zones) = &self.features.client_zone {
+            zones.compile()?;
+        }
+        if let Some(appname) = &self.features.appname {
+            appname.compile()?;
+        }
+        if let Some(backfill) = &self.features.session_backfill {
+            backfill.validate()?;
+        }
+        if let Some(obfuscate) = &self.features.obfuscate_ids {
+            obfuscate.validate()?;
+        }
+        if let Some(case) = &self.features.identifier_case {
+            case.validate()?;
+        }
+        if let Some(unmeasured) = &self.features.unmeasured {
+            unmeasured.validate()?;
+        }
+        self.validate_column_order()?;
+        if let Some(notify) = &self.notify {
+            notify.validate()?;
+        }
+        if let Some(report) = &self.report {
+            report.validate()?;
+        }
         if let Some(charts) = &self.features.charts {
             let ta_enabled = self
                 .features
@@ -108,6 +585,24 @@ impl Config {
         Ok(())
     }
 
+    /// 校验派生列定义，以及 `exporter.csv.column_order` 中的列名均为本次输出的列
+    fn validate_column_order(&self) -> Result<()> {
+        let derived = crate::features::DerivedColumns::from_config(&self.features)?;
+        if let Some(names) = self
+            .exporter
+            .csv
+            .as_ref()
+            .and_then(|c| c.column_order.as_deref())
+        {
+            crate::exporter::csv::column_order(
+                names,
+                &self.features.ordered_field_indices(),
+                &derived.specs(),
+            )?;
+        }
+        Ok(())
+    }
+
     /// 等价于 `validate()` 但额外返回已编译的过滤器对，供调用方复用，
     /// 消除 `run` 子命令路径中 regex 的双重编译（per ROADMAP SC-2 / PERF-11）。
     ///
@@ -126,6 +621,8 @@ impl Config {
         self.logging.validate()?;
         self.exporter.validate()?;
         self.sqllog.validate()?;
+        self.pipeline.validate()?;
+        self.error.validate()?;
 
         let compiled = if let Some(filters) = &self.features.filters {
             if filters.enable {
@@ -154,6 +651,46 @@ impl Config {
                 }
             }
         }
+        if let Some(time) = &self.features.time {
+            time.compile()?;
+        }
+        if let Some(quota) = &self.features.quota {
+            quota.validate()?;
+        }
+        if let Some(audit) = &self.features.exec_id_audit {
+            audit.validate()?;
+        }
+        if let Some(dedup) = &self.features.dedup {
+            dedup.validate()?;
+        }
+        if let Some(statements) = &self.features.statements {
+            statements.validate()?;
+        }
+        if let Some(zones) = &self.features.client_zone {
+            zones.compile()?;
+        }
+        if let Some(appname) = &self.features.appname {
+            appname.compile()?;
+        }
+        if let Some(backfill) = &self.features.session_backfill {
+            backfill.validate()?;
+        }
+        if let Some(obfuscate) = &self.features.obfuscate_ids {
+            obfuscate.validate()?;
+        }
+        if let Some(case) = &self.features.identifier_case {
+            case.validate()?;
+        }
+        if let Some(unmeasured) = &self.features.unmeasured {
+            unmeasured.validate()?;
+        }
+        self.validate_column_order()?;
+        if let Some(notify) = &self.notify {
+            notify.validate()?;
+        }
+        if let Some(report) = &self.report {
+            report.validate()?;
+        }
         if let Some(charts) = &self.features.charts {
             let ta_enabled = self
                 .features
@@ -224,6 +761,34 @@ impl Config {
 
         match key {
             "sqllog.path" | "sqllog.directory" => self.sqllog.path = value.to_string(),
+            "sqllog.parse_mode" => self.sqllog.parse_mode = value.to_string(),
+            "sqllog.file_date_from" => self.sqllog.file_date_from = Some(value.to_string()),
+            "sqllog.file_date_to" => self.sqllog.file_date_to = Some(value.to_string()),
+            "sqllog.file_order" => self.sqllog.file_order = value.to_string(),
+            "sqllog.active_file" => self.sqllog.active_file = value.to_string(),
+            "sqllog.verify_checksums" => self.sqllog.verify_checksums = parse_bool(value)?,
+            "sqllog.ignore" => {
+                self.sqllog.ignore = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|p| !p.is_empty())
+                    .map(str::to_string)
+                    .collect();
+            }
+            "sqllog.active_file_secs" | "sqllog.active_file_wait_secs" => {
+                let parsed = value.parse::<u64>().map_err(|_| {
+                    Error::Config(ConfigError::InvalidValue {
+                        field: key.to_string(),
+                        value: value.to_string(),
+                        reason: "expected a non-negative integer".to_string(),
+                    })
+                })?;
+                if key.ends_with("wait_secs") {
+                    self.sqllog.active_file_wait_secs = parsed;
+                } else {
+                    self.sqllog.active_file_secs = parsed;
+                }
+            }
             "logging.level" => self.logging.level = value.to_string(),
             "logging.file" => self.logging.file = value.to_string(),
             "logging.retention_days" => {
@@ -257,6 +822,93 @@ impl Config {
                     .get_or_insert_with(Default::default)
                     .include_performance_metrics = parse_bool(value)?;
             }
+            "exporter.csv.dialect" => {
+                self.exporter
+                    .csv
+                    .get_or_insert_with(Default::default)
+                    .dialect = value.to_string();
+            }
+            "exporter.csv.field_terminator" => {
+                self.exporter
+                    .csv
+                    .get_or_insert_with(Default::default)
+                    .field_terminator = Some(value.to_string());
+            }
+            "exporter.csv.row_terminator" => {
+                self.exporter
+                    .csv
+                    .get_or_insert_with(Default::default)
+                    .row_terminator = Some(value.to_string());
+            }
+            "exporter.csv.split_by" => {
+                self.exporter
+                    .csv
+                    .get_or_insert_with(Default::default)
+                    .split_by = Some(value.to_string());
+            }
+            "exporter.csv.compression" => {
+                self.exporter
+                    .csv
+                    .get_or_insert_with(Default::default)
+                    .compression = Some(value.to_string());
+            }
+            "exporter.csv.compression_level" => {
+                let parsed = value.parse::<u32>().map_err(|_| {
+                    Error::Config(ConfigError::InvalidValue {
+                        field: "exporter.csv.compression_level".to_string(),
+                        value: value.to_string(),
+                        reason: "expected a non-negative integer".to_string(),
+                    })
+                })?;
+                self.exporter
+                    .csv
+                    .get_or_insert_with(Default::default)
+                    .compression_level = Some(parsed);
+            }
+            "exporter.csv.max_file_size" => {
+                self.exporter
+                    .csv
+                    .get_or_insert_with(Default::default)
+                    .max_file_size = Some(value.to_string());
+            }
+            "exporter.csv.max_records" => {
+                let parsed = value.parse::<u64>().map_err(|_| {
+                    Error::Config(ConfigError::InvalidValue {
+                        field: "exporter.csv.max_records".to_string(),
+                        value: value.to_string(),
+                        reason: "expected a positive integer".to_string(),
+                    })
+                })?;
+                self.exporter
+                    .csv
+                    .get_or_insert_with(Default::default)
+                    .max_records = Some(parsed);
+            }
+            "exporter.csv.exectime_format" => {
+                self.exporter
+                    .csv
+                    .get_or_insert_with(Default::default)
+                    .exectime_format = value.to_string();
+            }
+            "exporter.csv.on_error" => {
+                self.exporter
+                    .csv
+                    .get_or_insert_with(Default::default)
+                    .on_error = value.to_string();
+            }
+            "exporter.csv.column_order" => {
+                self.exporter
+                    .csv
+                    .get_or_insert_with(Default::default)
+                    .column_order = Some(
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|c| !c.is_empty())
+                        .map(str::to_string)
+                        .collect(),
+                );
+            }
 
             "exporter.sqlite.database_url" => {
                 self.exporter
@@ -282,6 +934,49 @@ impl Config {
                     .get_or_insert_with(Default::default)
                     .append = parse_bool(value)?;
             }
+            "exporter.sqlite.load_metadata" => {
+                self.exporter
+                    .sqlite
+                    .get_or_insert_with(Default::default)
+                    .load_metadata = parse_bool(value)?;
+            }
+            // 不单独创建 [exporter.sqlite]：`run --migrate` 对其他导出器不生效
+            "exporter.sqlite.migrate" => {
+                let migrate = parse_bool(value)?;
+                if let Some(sqlite) = self.exporter.sqlite.as_mut() {
+                    sqlite.migrate = migrate;
+                }
+            }
+            "pipeline.chunk_size" => {
+                self.pipeline.chunk_size = value.parse::<usize>().map_err(|_| {
+                    Error::Config(ConfigError::InvalidValue {
+                        field: "pipeline.chunk_size".to_string(),
+                        value: value.to_string(),
+                        reason: "expected a positive integer".to_string(),
+                    })
+                })?;
+            }
+            "pipeline.workers" => {
+                self.pipeline.workers = Some(value.parse::<usize>().map_err(|_| {
+                    Error::Config(ConfigError::InvalidValue {
+                        field: "pipeline.workers".to_string(),
+                        value: value.to_string(),
+                        reason: "expected a positive integer".to_string(),
+                    })
+                })?);
+            }
+            "error.max_parse_error_rate" => {
+                self.error.max_parse_error_rate = Some(value.parse::<f64>().map_err(|_| {
+                    Error::Config(ConfigError::InvalidValue {
+                        field: key.to_string(),
+                        value: value.to_string(),
+                        reason: "expected a percentage between 0 and 100".to_string(),
+                    })
+                })?);
+            }
+            "report.file" => {
+                self.report.get_or_insert_with(Default::default).file = value.to_string();
+            }
             "exporter.sqlite.batch_size" => {
                 let parsed = value.parse::<usize>().map_err(|_| {
                     Error::Config(ConfigError::InvalidValue {
@@ -295,16 +990,77 @@ impl Config {
                     .get_or_insert_with(Default::default)
                     .batch_size = parsed;
             }
-
-            "features.filters.enable" => {
-                self.features
-                    .filters
+            "exporter.sqlite.duplicate_check" => {
+                self.exporter
+                    .sqlite
                     .get_or_insert_with(Default::default)
-                    .enable = parse_bool(value)?;
+                    .duplicate_check = value.to_string();
             }
-            "features.replace_parameters.enable" => {
-                self.features
-                    .replace_parameters
+            "exporter.sqlite.duplicate_check_query" => {
+                self.exporter
+                    .sqlite
+                    .get_or_insert_with(Default::default)
+                    .duplicate_check_query = Some(value.to_string());
+            }
+            "exporter.sqlite.split_by" => {
+                self.exporter
+                    .sqlite
+                    .get_or_insert_with(Default::default)
+                    .split_by = Some(value.to_string());
+            }
+            "exporter.sqlite.on_error" => {
+                self.exporter
+                    .sqlite
+                    .get_or_insert_with(Default::default)
+                    .on_error = value.to_string();
+            }
+            "exporter.elasticsearch.url" => {
+                self.exporter
+                    .elasticsearch
+                    .get_or_insert_with(Default::default)
+                    .url = value.to_string();
+            }
+            "exporter.elasticsearch.index" => {
+                self.exporter
+                    .elasticsearch
+                    .get_or_insert_with(Default::default)
+                    .index = value.to_string();
+            }
+            "exporter.elasticsearch.batch_size" => {
+                let parsed = value.parse::<usize>().map_err(|_| {
+                    Error::Config(ConfigError::InvalidValue {
+                        field: "exporter.elasticsearch.batch_size".to_string(),
+                        value: value.to_string(),
+                        reason: "expected a positive integer".to_string(),
+                    })
+                })?;
+                self.exporter
+                    .elasticsearch
+                    .get_or_insert_with(Default::default)
+                    .batch_size = parsed;
+            }
+            "exporter.elasticsearch.spill_dir" => {
+                self.exporter
+                    .elasticsearch
+                    .get_or_insert_with(Default::default)
+                    .spill_dir = Some(value.to_string());
+            }
+            "exporter.elasticsearch.on_error" => {
+                self.exporter
+                    .elasticsearch
+                    .get_or_insert_with(Default::default)
+                    .on_error = value.to_string();
+            }
+
+            "features.filters.enable" => {
+                self.features
+                    .filters
+                    .get_or_insert_with(Default::default)
+                    .enable = parse_bool(value)?;
+            }
+            "features.replace_parameters.enable" => {
+                self.features
+                    .replace_parameters
                     .get_or_insert_with(Default::default)
                     .enable = parse_bool(value)?;
             }
@@ -315,6 +1071,126 @@ impl Config {
                     .enabled = parse_bool(value)?;
             }
 
+            "features.time.input_timezone" => {
+                self.features
+                    .time
+                    .get_or_insert_with(Default::default)
+                    .input_timezone = Some(value.to_string());
+            }
+            "features.time.output_timezone" => {
+                self.features
+                    .time
+                    .get_or_insert_with(Default::default)
+                    .output_timezone = Some(value.to_string());
+            }
+            "features.time.output_format" => {
+                self.features
+                    .time
+                    .get_or_insert_with(Default::default)
+                    .output_format = Some(value.to_string());
+            }
+
+            "features.redact.credentials" => {
+                self.features
+                    .redact
+                    .get_or_insert_with(Default::default)
+                    .credentials = parse_bool(value)?;
+            }
+
+            "features.exec_id_audit.enabled" => {
+                self.features
+                    .exec_id_audit
+                    .get_or_insert_with(Default::default)
+                    .enabled = parse_bool(value)?;
+            }
+            "features.exec_id_audit.scope" => {
+                self.features
+                    .exec_id_audit
+                    .get_or_insert_with(Default::default)
+                    .scope = value.to_string();
+            }
+            "features.dedup.enabled" => {
+                self.features
+                    .dedup
+                    .get_or_insert_with(Default::default)
+                    .enabled = parse_bool(value)?;
+            }
+
+            "features.statements.enabled" => {
+                self.features
+                    .statements
+                    .get_or_insert_with(Default::default)
+                    .enabled = parse_bool(value)?;
+            }
+            "features.statements.file" => {
+                self.features
+                    .statements
+                    .get_or_insert_with(Default::default)
+                    .file = Some(value.to_string());
+            }
+
+            "features.normalize_sql.enabled" => {
+                self.features
+                    .normalize_sql
+                    .get_or_insert_with(Default::default)
+                    .enabled = parse_bool(value)?;
+            }
+
+            "features.unmeasured.policy" => {
+                self.features
+                    .unmeasured
+                    .get_or_insert_with(Default::default)
+                    .policy = value.to_string();
+            }
+            "features.unmeasured.file" => {
+                self.features
+                    .unmeasured
+                    .get_or_insert_with(Default::default)
+                    .file = Some(value.to_string());
+            }
+
+            "features.session_backfill.enabled" => {
+                self.features
+                    .session_backfill
+                    .get_or_insert_with(Default::default)
+                    .enabled = parse_bool(value)?;
+            }
+            "features.obfuscate_ids.enabled" => {
+                self.features
+                    .obfuscate_ids
+                    .get_or_insert_with(Default::default)
+                    .enabled = parse_bool(value)?;
+            }
+            "features.identifier_case.username" => {
+                self.features
+                    .identifier_case
+                    .get_or_insert_with(Default::default)
+                    .username = Some(value.to_string());
+            }
+            "features.identifier_case.tag" => {
+                self.features
+                    .identifier_case
+                    .get_or_insert_with(Default::default)
+                    .tag = Some(value.to_string());
+            }
+
+            "features.quota.max_records_per_user"
+            | "features.quota.max_records_per_fingerprint" => {
+                let parsed = value.parse::<u64>().map_err(|_| {
+                    Error::Config(ConfigError::InvalidValue {
+                        field: key.to_string(),
+                        value: value.to_string(),
+                        reason: "expected a positive integer".to_string(),
+                    })
+                })?;
+                let quota = self.features.quota.get_or_insert_with(Default::default);
+                if key.ends_with("per_user") {
+                    quota.max_records_per_user = Some(parsed);
+                } else {
+                    quota.max_records_per_fingerprint = Some(parsed);
+                }
+            }
+
             "features.charts.output_dir" => {
                 if value.trim().is_empty() {
                     return Err(Error::Config(ConfigError::InvalidValue {
@@ -380,17 +1256,83 @@ impl Config {
 }
 
 #[derive(Debug, Deserialize, Clone)]
+#[non_exhaustive]
 pub struct SqllogConfig {
     /// 日志文件路径：目录、单文件或 glob 模式（e.g. `sqllogs/*.log`）
     /// 旧配置中的 `directory` 键仍被接受。
-    #[serde(alias = "directory")]
+    #[serde(alias = "directory", default = "default_sqllog_path")]
     pub path: String,
+    /// 解析容错级别：`lenient`（默认，修复垃圾前缀 / 缺失 EXECTIME 并标记）或 `strict`（不合规即报错）
+    #[serde(default = "default_parse_mode")]
+    pub parse_mode: String,
+    /// 按文件名中的日期（如 `dmsql_DMSERVER_20250115_103028.log`）在打开文件前筛选，`YYYY-MM-DD`，含边界；
+    /// 文件名不含日期的文件不受影响
+    #[serde(default)]
+    pub file_date_from: Option<String>,
+    #[serde(default)]
+    pub file_date_to: Option<String>,
+    /// 文件处理顺序：`name`（默认）| `mtime`（最新在前）| `size_asc` | `size_desc`
+    #[serde(default = "default_file_order")]
+    pub file_order: String,
+    /// 仍在写入的文件：`read`（默认）| `skip` | `wait` | `complete`（见 `parser::active`）
+    #[serde(default = "default_active_file")]
+    pub active_file: String,
+    /// 修改时间距今不足该秒数即视为仍在写入
+    #[serde(default = "default_active_file_secs")]
+    pub active_file_secs: u64,
+    /// `wait` 模式的最长等待秒数，超时后按 `complete` 处理
+    #[serde(default = "default_active_file_wait_secs")]
+    pub active_file_wait_secs: u64,
+    /// 按目录下的 `SHA256SUMS` / 文件旁的 `.sha256` 校验日志文件，不符的文件其记录计为错误、不导出
+    #[serde(default = "default_true")]
+    pub verify_checksums: bool,
+    /// 扫描时排除的文件 / 子目录（glob）：不含 `/` 的模式匹配路径中任一级名称（如 `*.tmp`、`backup`），
+    /// 含 `/` 的模式按相对扫描根目录的路径匹配（如 `backup/**`）
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    /// 运行开始时固定的文件清单（非配置项，见 [`crate::parser::ListingSnapshot`]）
+    #[serde(skip)]
+    pub listing: Option<std::sync::Arc<crate::parser::ListingSnapshot>>,
+}
+
+fn default_active_file() -> String {
+    "read".to_string()
+}
+
+fn default_active_file_secs() -> u64 {
+    60
+}
+
+fn default_active_file_wait_secs() -> u64 {
+    300
+}
+
+fn default_file_order() -> String {
+    "name".to_string()
+}
+
+fn default_sqllog_path() -> String {
+    "sqllogs".to_string()
+}
+
+fn default_parse_mode() -> String {
+    "lenient".to_string()
 }
 
 impl Default for SqllogConfig {
     fn default() -> Self {
         Self {
-            path: "sqllogs".to_string(),
+            path: default_sqllog_path(),
+            parse_mode: default_parse_mode(),
+            file_date_from: None,
+            file_date_to: None,
+            file_order: default_file_order(),
+            active_file: default_active_file(),
+            active_file_secs: default_active_file_secs(),
+            active_file_wait_secs: default_active_file_wait_secs(),
+            verify_checksums: true,
+            ignore: Vec::new(),
+            listing: None,
         }
     }
 }
@@ -404,11 +1346,46 @@ impl SqllogConfig {
                 reason: "Input path cannot be empty".to_string(),
             }));
         }
+        crate::parser::ParseMode::parse(&self.parse_mode)?;
+        self.file_date_range()?;
+        crate::parser::FileOrder::parse(&self.file_order)?;
+        crate::parser::ActiveFilePolicy::from_config(self)?;
+        crate::parser::ignore_patterns(&self.ignore)?;
         Ok(())
     }
+
+    /// 解析 `file_date_from` / `file_date_to`
+    pub fn file_date_range(&self) -> Result<(Option<NaiveDate>, Option<NaiveDate>)> {
+        let parse = |field: &str, value: Option<&String>| {
+            value
+                .map(|v| {
+                    NaiveDate::parse_from_str(v, "%Y-%m-%d").map_err(|_| {
+                        Error::Config(ConfigError::InvalidValue {
+                            field: format!("sqllog.{field}"),
+                            value: v.clone(),
+                            reason: "expected a date in YYYY-MM-DD format".to_string(),
+                        })
+                    })
+                })
+                .transpose()
+        };
+        let from = parse("file_date_from", self.file_date_from.as_ref())?;
+        let to = parse("file_date_to", self.file_date_to.as_ref())?;
+        if let (Some(f), Some(t)) = (from, to) {
+            if f > t {
+                return Err(Error::Config(ConfigError::InvalidValue {
+                    field: "sqllog.file_date_to".to_string(),
+                    value: t.to_string(),
+                    reason: format!("must not be earlier than file_date_from ({f})"),
+                }));
+            }
+        }
+        Ok((from, to))
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
+#[non_exhaustive]
 pub struct LoggingConfig {
     #[serde(default = "default_logging_file")]
     pub file: String,
@@ -468,14 +1445,35 @@ impl LoggingConfig {
 }
 
 #[derive(Debug, Deserialize, Clone)]
+#[non_exhaustive]
 pub struct ExporterConfig {
     pub csv: Option<CsvExporter>,
     pub sqlite: Option<SqliteExporter>,
+    pub elasticsearch: Option<ElasticsearchExporter>,
+    pub custom: Option<CustomExporter>,
 }
 
 impl ExporterConfig {
     fn has_any(&self) -> bool {
-        self.csv.is_some() || self.sqlite.is_some()
+        self.csv.is_some()
+            || self.sqlite.is_some()
+            || self.elasticsearch.is_some()
+            || self.custom.is_some()
+    }
+
+    /// 已配置的导出器段名，按选择优先级排列（首项即实际使用的导出器）
+    #[must_use]
+    pub fn configured(&self) -> Vec<&'static str> {
+        [
+            self.csv.is_some(),
+            self.sqlite.is_some(),
+            self.elasticsearch.is_some(),
+            self.custom.is_some(),
+        ]
+        .into_iter()
+        .zip(EXPORTER_NAMES)
+        .filter_map(|(set, name)| set.then_some(*name))
+        .collect()
     }
 
     pub fn validate(&self) -> Result<()> {
@@ -488,6 +1486,12 @@ impl ExporterConfig {
         if let Some(sqlite) = &self.sqlite {
             sqlite.validate()?;
         }
+        if let Some(es) = &self.elasticsearch {
+            es.validate()?;
+        }
+        if let Some(custom) = &self.custom {
+            custom.validate()?;
+        }
         Ok(())
     }
 }
@@ -497,12 +1501,17 @@ impl Default for ExporterConfig {
         Self {
             csv: Some(CsvExporter::default()),
             sqlite: None,
+            elasticsearch: None,
+            custom: None,
         }
     }
 }
 
 #[derive(Debug, Deserialize, Clone)]
+#[non_exhaustive]
 pub struct CsvExporter {
+    /// 输出文件路径；含 strftime 说明符（如 `sqllog-%Y%m%d-%H.csv`）时按记录时间滚动切换文件
+    #[serde(default = "default_csv_file")]
     pub file: String,
     #[serde(default = "default_true")]
     pub overwrite: bool,
@@ -512,15 +1521,84 @@ pub struct CsvExporter {
     /// 默认 true，保持现有行为不变（D-06）。
     #[serde(default = "default_true")]
     pub include_performance_metrics: bool,
+    /// 输出方言：`standard`（默认，SQL 列加双引号）/ `dmfldr`（兼容达梦 dmfldr 装载：
+    /// 字段不加引号，值内的换行与字段分隔符替换为空格，保证每行列数一致）/ `tsv`（兼容
+    /// `clickhouse-client --format TSV`：制表符分隔、控制字符反斜杠转义、NULL 写为 `\N`，无 header）
+    #[serde(default = "default_csv_dialect")]
+    pub dialect: String,
+    /// `dmfldr` 方言的字段分隔符，默认 `|`
+    #[serde(default)]
+    pub field_terminator: Option<String>,
+    /// `dmfldr` 方言的行结束符，默认 `\n`；必须以 `\n` 结尾
+    #[serde(default)]
+    pub row_terminator: Option<String>,
+    /// 按 `username` / `schema` 拆分为多个文件：`sqllog.csv` → `sqllog.<分片>.csv`
+    #[serde(default)]
+    pub split_by: Option<String>,
+    /// 输出压缩：`gzip` / `zstd`；文件名按原样使用（建议 `.csv.gz` / `.csv.zst`）
+    #[serde(default)]
+    pub compression: Option<String>,
+    /// 压缩级别：gzip 为 0–9（默认 6）；zstd 仅支持 1（内置编码器只有最快一档），其他值校验时报错
+    #[serde(default)]
+    pub compression_level: Option<u32>,
+    /// `exec_time_ms` 列格式：`integer`（默认，截断为整数毫秒）/ `decimal`（保留 3 位小数）。
+    /// 两种格式都与系统区域设置无关，小数点固定为 `.`
+    #[serde(default = "default_exectime_format")]
+    pub exectime_format: String,
+    /// 单个输出文件的大小上限（如 `"2GB"`，按压缩前字节数计算），达到后切换到下一个分段文件
+    #[serde(default)]
+    pub max_file_size: Option<String>,
+    /// 单个输出文件的记录数上限，达到后切换到下一个分段文件
+    #[serde(default)]
+    pub max_records: Option<u64>,
+    /// 写入失败时的处理：`abort`（默认）/ `skip_batch` / `skip_record`（`skip`）/ `log`（见 [`crate::exporter::OnError`]）
+    #[serde(default = "default_on_error")]
+    pub on_error: String,
+    /// 输出列顺序（列名，可含派生列）；未列出的列按默认顺序接在其后，便于按固定列位装载
+    #[serde(default)]
+    pub column_order: Option<Vec<String>>,
+}
+
+fn default_on_error() -> String {
+    "abort".to_string()
+}
+
+fn default_csv_file() -> String {
+    "outputs/sqllog.csv".to_string()
+}
+
+fn default_csv_dialect() -> String {
+    "standard".to_string()
+}
+
+/// `exporter.csv.dialect` 可选值
+pub const CSV_DIALECTS: &[&str] = &["standard", "dmfldr", "tsv"];
+
+fn default_exectime_format() -> String {
+    "integer".to_string()
 }
 
+/// `exporter.csv.exectime_format` 可选值
+pub const EXECTIME_FORMATS: &[&str] = &["integer", "decimal"];
+
 impl Default for CsvExporter {
     fn default() -> Self {
         Self {
-            file: "outputs/sqllog.csv".to_string(),
+            file: default_csv_file(),
             overwrite: true,
             append: false,
             include_performance_metrics: true,
+            dialect: default_csv_dialect(),
+            field_terminator: None,
+            row_terminator: None,
+            split_by: None,
+            compression: None,
+            compression_level: None,
+            exectime_format: default_exectime_format(),
+            max_file_size: None,
+            max_records: None,
+            on_error: default_on_error(),
+            column_order: None,
         }
     }
 }
@@ -534,12 +1612,189 @@ impl CsvExporter {
                 reason: "CSV output file path cannot be empty".to_string(),
             }));
         }
+        if let Some(addr) = crate::exporter::sink::tcp_addr(std::path::Path::new(&self.file)) {
+            crate::exporter::sink::validate_tcp_addr(addr).map_err(|reason| {
+                Error::Config(ConfigError::InvalidValue {
+                    field: "exporter.csv.file".to_string(),
+                    value: self.file.clone(),
+                    reason,
+                })
+            })?;
+        }
+        if crate::exporter::rollover::is_time_pattern(&self.file) {
+            crate::exporter::rollover::validate_rollover_pattern(&self.file).map_err(|reason| {
+                Error::Config(ConfigError::InvalidValue {
+                    field: "exporter.csv.file".to_string(),
+                    value: self.file.clone(),
+                    reason,
+                })
+            })?;
+        }
+        crate::exporter::OnError::parse("exporter.csv.on_error", &self.on_error)?;
+        if let Some(v) = &self.split_by {
+            crate::features::SplitBy::parse("exporter.csv.split_by", v)?;
+            let conflict = if crate::exporter::sink::is_stream(std::path::Path::new(&self.file)) {
+                Some("cannot split when writing to stdout or a TCP target")
+            } else if crate::exporter::rollover::is_time_pattern(&self.file) {
+                Some("cannot be combined with a time-pattern file name")
+            } else {
+                None
+            };
+            if let Some(reason) = conflict {
+                return Err(Error::Config(ConfigError::InvalidValue {
+                    field: "exporter.csv.split_by".to_string(),
+                    value: v.clone(),
+                    reason: reason.to_string(),
+                }));
+            }
+        }
+        if let Some(c) = &self.compression {
+            crate::exporter::compress::Compression::parse(
+                "exporter.csv",
+                c,
+                self.compression_level,
+            )?;
+        } else if let Some(level) = self.compression_level {
+            return Err(Error::Config(ConfigError::InvalidValue {
+                field: "exporter.csv.compression_level".to_string(),
+                value: level.to_string(),
+                reason: "requires exporter.csv.compression".to_string(),
+            }));
+        }
+        self.validate_rotation()?;
+        if !EXECTIME_FORMATS.contains(&self.exectime_format.as_str()) {
+            return Err(Error::Config(ConfigError::InvalidValue {
+                field: "exporter.csv.exectime_format".to_string(),
+                value: self.exectime_format.clone(),
+                reason: format!("must be one of: {}", EXECTIME_FORMATS.join(", ")),
+            }));
+        }
+        self.validate_dialect()
+    }
+
+    /// 已校验的压缩设置；未启用时为 `None`
+    #[must_use]
+    pub fn compression(&self) -> Option<crate::exporter::compress::Compression> {
+        self.compression.as_deref().and_then(|c| {
+            crate::exporter::compress::Compression::parse("exporter.csv", c, self.compression_level)
+                .ok()
+        })
+    }
+
+    /// 已校验的分段上限：（字节数，记录数）；均为 `None` 时不分段
+    #[must_use]
+    pub fn rotation_limits(&self) -> (Option<u64>, Option<u64>) {
+        let bytes = self
+            .max_file_size
+            .as_deref()
+            .and_then(|v| crate::exporter::rollover::parse_size(v).ok());
+        (bytes, self.max_records)
+    }
+
+    fn validate_rotation(&self) -> Result<()> {
+        let invalid = |field: &str, value: String, reason: &str| {
+            Err(Error::Config(ConfigError::InvalidValue {
+                field: format!("exporter.csv.{field}"),
+                value,
+                reason: reason.to_string(),
+            }))
+        };
+        if let Some(v) = &self.max_file_size {
+            match crate::exporter::rollover::parse_size(v) {
+                Ok(0) => return invalid("max_file_size", v.clone(), "must be greater than 0"),
+                Ok(_) => {}
+                Err(reason) => return invalid("max_file_size", v.clone(), &reason),
+            }
+        }
+        if self.max_records == Some(0) {
+            return invalid("max_records", "0".to_string(), "must be greater than 0");
+        }
+        let (field, value) = match (&self.max_file_size, self.max_records) {
+            (Some(v), _) => ("max_file_size", v.clone()),
+            (None, Some(n)) => ("max_records", n.to_string()),
+            (None, None) => return Ok(()),
+        };
+        let conflict = if crate::exporter::sink::is_stream(std::path::Path::new(&self.file)) {
+            Some("cannot rotate when writing to stdout or a TCP target")
+        } else if crate::exporter::rollover::is_time_pattern(&self.file) {
+            Some("cannot be combined with a time-pattern file name")
+        } else if self.split_by.is_some() {
+            Some("cannot be combined with split_by")
+        } else {
+            None
+        };
+        match conflict {
+            Some(reason) => invalid(field, value, reason),
+            None => Ok(()),
+        }
+    }
+
+    /// 已校验的 `exec_time_ms` 列格式
+    #[must_use]
+    pub fn exectime_format(&self) -> crate::exporter::ExecTimeFormat {
+        if self.exectime_format == "decimal" {
+            crate::exporter::ExecTimeFormat::Decimal
+        } else {
+            crate::exporter::ExecTimeFormat::Integer
+        }
+    }
+
+    fn validate_dialect(&self) -> Result<()> {
+        let invalid = |field: &str, value: &str, reason: &str| {
+            Err(Error::Config(ConfigError::InvalidValue {
+                field: format!("exporter.csv.{field}"),
+                value: value.to_string(),
+                reason: reason.to_string(),
+            }))
+        };
+        if !CSV_DIALECTS.contains(&self.dialect.as_str()) {
+            return invalid(
+                "dialect",
+                &self.dialect,
+                &format!("must be one of: {}", CSV_DIALECTS.join(", ")),
+            );
+        }
+        let is_dmfldr = self.dialect == "dmfldr";
+        if let Some(ft) = &self.field_terminator {
+            if !is_dmfldr {
+                return invalid(
+                    "field_terminator",
+                    ft,
+                    "only applies to dialect = \"dmfldr\"",
+                );
+            }
+            if ft.is_empty() || ft.contains(['\r', '\n']) {
+                return invalid(
+                    "field_terminator",
+                    ft,
+                    "must be non-empty and cannot contain line breaks",
+                );
+            }
+        }
+        if let Some(rt) = &self.row_terminator {
+            if !is_dmfldr {
+                return invalid("row_terminator", rt, "only applies to dialect = \"dmfldr\"");
+            }
+            if !rt.ends_with('\n') {
+                return invalid("row_terminator", rt, "must end with a newline (\\n)");
+            }
+            if self
+                .field_terminator
+                .as_deref()
+                .is_some_and(|ft| rt.contains(ft))
+            {
+                return invalid("row_terminator", rt, "cannot contain the field terminator");
+            }
+        }
         Ok(())
     }
 }
 
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Deserialize, Clone)]
+#[non_exhaustive]
 pub struct SqliteExporter {
+    #[serde(default = "default_database_url")]
     pub database_url: String,
     #[serde(default = "default_table_name")]
     pub table_name: String,
@@ -549,6 +1804,46 @@ pub struct SqliteExporter {
     pub append: bool,
     #[serde(default = "default_sqlite_batch_size")]
     pub batch_size: usize,
+    /// 追加模式下的重复装载检测：`off` / `warn`（默认）/ `abort`。
+    /// 首条记录到达时按其日期查询目标表，已有同日数据则告警或中止。
+    #[serde(default = "default_duplicate_check")]
+    pub duplicate_check: String,
+    /// 自定义检测 SQL（首列返回已存在行数）；可用命名参数 `:day`（`YYYY-MM-DD`）与 `:first_ts`
+    #[serde(default)]
+    pub duplicate_check_query: Option<String>,
+    /// 按 `username` / `schema` 拆分为多张表：`<table_name>_<分片>`
+    #[serde(default)]
+    pub split_by: Option<String>,
+    /// 建表之后、写入之前在目标连接上依次执行的 SQL（如删除索引 / 触发器）
+    #[serde(default)]
+    pub pre_sql: Vec<String>,
+    /// 写入提交之后依次执行的 SQL（如重建索引、刷新汇总表）
+    #[serde(default)]
+    pub post_sql: Vec<String>,
+    /// 装载完成后在目标表上创建的友好视图名（列重命名、时间戳转换、耗时换算为秒、语句类型解码）
+    #[serde(default)]
+    pub view_name: Option<String>,
+    /// 装载结束后在目标库的 `sqllog2db_loads` 表写入一行装载记录（来源文件摘要、记录数、版本、耗时）
+    #[serde(default)]
+    pub load_metadata: bool,
+    /// 目标表已存在但缺少本次导出的列（如旧版本建的表没有 `exec_id`）时 `ALTER TABLE` 补齐；
+    /// 关闭时报错并列出缺少的列（`run --migrate` 等同于开启）
+    #[serde(default)]
+    pub migrate: bool,
+    /// 写入失败时的处理：`abort`（默认）/ `skip_batch` / `skip_record`（`skip`）/ `log`（见 [`crate::exporter::OnError`]）
+    #[serde(default = "default_on_error")]
+    pub on_error: String,
+}
+
+fn default_duplicate_check() -> String {
+    "warn".to_string()
+}
+
+/// `exporter.sqlite.duplicate_check` 可选值
+pub const DUPLICATE_CHECK_MODES: &[&str] = &["off", "warn", "abort"];
+
+fn default_database_url() -> String {
+    "export/sqllog2db.db".to_string()
 }
 
 fn default_table_name() -> String {
@@ -559,66 +1854,444 @@ fn default_sqlite_batch_size() -> usize {
     10_000
 }
 
-impl Default for SqliteExporter {
+impl Default for SqliteExporter {
+    fn default() -> Self {
+        Self {
+            database_url: default_database_url(),
+            table_name: "sqllog_records".to_string(),
+            overwrite: true,
+            append: false,
+            batch_size: 10_000,
+            duplicate_check: default_duplicate_check(),
+            duplicate_check_query: None,
+            split_by: None,
+            pre_sql: Vec::new(),
+            post_sql: Vec::new(),
+            view_name: None,
+            load_metadata: false,
+            migrate: false,
+            on_error: default_on_error(),
+        }
+    }
+}
+
+impl SqliteExporter {
+    pub fn validate(&self) -> Result<()> {
+        if self.database_url.trim().is_empty() {
+            return Err(Error::Config(ConfigError::InvalidValue {
+                field: "exporter.sqlite.database_url".to_string(),
+                value: self.database_url.clone(),
+                reason: "SQLite database URL cannot be empty".to_string(),
+            }));
+        }
+        if self.table_name.trim().is_empty() {
+            return Err(Error::Config(ConfigError::InvalidValue {
+                field: "exporter.sqlite.table_name".to_string(),
+                value: self.table_name.clone(),
+                reason: "SQLite table name cannot be empty".to_string(),
+            }));
+        }
+        if !is_ascii_ident(&self.table_name) {
+            return Err(Error::Config(ConfigError::InvalidValue {
+                field: "exporter.sqlite.table_name".to_string(),
+                value: self.table_name.clone(),
+                reason: "table name must match ^[a-zA-Z_][a-zA-Z0-9_]*$ (ASCII identifiers only)"
+                    .to_string(),
+            }));
+        }
+        if self.batch_size == 0 {
+            return Err(ConfigError::InvalidValue {
+                field: "exporter.sqlite.batch_size".to_string(),
+                value: "0".to_string(),
+                reason: "batch_size must be greater than 0".to_string(),
+            }
+            .into());
+        }
+        if !DUPLICATE_CHECK_MODES.contains(&self.duplicate_check.as_str()) {
+            return Err(ConfigError::InvalidValue {
+                field: "exporter.sqlite.duplicate_check".to_string(),
+                value: self.duplicate_check.clone(),
+                reason: format!("must be one of: {}", DUPLICATE_CHECK_MODES.join(", ")),
+            }
+            .into());
+        }
+        if let Some(q) = &self.duplicate_check_query {
+            if q.trim().is_empty() {
+                return Err(ConfigError::InvalidValue {
+                    field: "exporter.sqlite.duplicate_check_query".to_string(),
+                    value: q.clone(),
+                    reason: "query cannot be empty".to_string(),
+                }
+                .into());
+            }
+        }
+        if let Some(v) = &self.split_by {
+            crate::features::SplitBy::parse("exporter.sqlite.split_by", v)?;
+        }
+        crate::exporter::OnError::parse("exporter.sqlite.on_error", &self.on_error)?;
+        if let Some(view) = &self.view_name {
+            let reason = if !is_ascii_ident(view) {
+                Some("view name must match ^[a-zA-Z_][a-zA-Z0-9_]*$ (ASCII identifiers only)")
+            } else if view.eq_ignore_ascii_case(&self.table_name) {
+                Some("view name must differ from table_name")
+            } else if self.split_by.is_some() {
+                Some("cannot be combined with split_by")
+            } else {
+                None
+            };
+            if let Some(reason) = reason {
+                return Err(ConfigError::InvalidValue {
+                    field: "exporter.sqlite.view_name".to_string(),
+                    value: view.clone(),
+                    reason: reason.to_string(),
+                }
+                .into());
+            }
+        }
+        for (hook, statements) in [("pre_sql", &self.pre_sql), ("post_sql", &self.post_sql)] {
+            if let Some((i, sql)) = statements
+                .iter()
+                .enumerate()
+                .find(|(_, s)| s.trim().is_empty())
+            {
+                return Err(ConfigError::InvalidValue {
+                    field: format!("exporter.sqlite.{hook}[{i}]"),
+                    value: sql.clone(),
+                    reason: "SQL statement cannot be empty".to_string(),
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[non_exhaustive]
+pub struct ElasticsearchExporter {
+    /// 集群地址（Elasticsearch / `OpenSearch`），如 `http://localhost:9200`
+    #[serde(default = "default_es_url")]
+    pub url: String,
+    /// 索引名；含 strftime 说明符（如 `sqllog-%Y.%m.%d`）时按记录时间写入对应日期的索引
+    #[serde(default = "default_es_index")]
+    pub index: String,
+    /// 每个 `_bulk` 请求携带的文档数
+    #[serde(default = "default_es_batch_size")]
+    pub batch_size: usize,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// 从该环境变量读取密码（优先于 `password`）
+    #[serde(default)]
+    pub password_env: Option<String>,
+    /// API key 认证（`Authorization: ApiKey <key>`），与 `username` 二选一
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// 单个请求的超时秒数
+    #[serde(default = "default_es_timeout_secs")]
+    pub timeout_secs: u64,
+    /// 磁盘溢出队列目录：集群暂时不可用时批次写入该目录，恢复后（含下次启动时）按顺序重放
+    #[serde(default)]
+    pub spill_dir: Option<String>,
+    /// 溢出队列总大小上限（MiB），写满后中止导出
+    #[serde(default = "default_es_spill_max_mb")]
+    pub spill_max_mb: u64,
+    /// 写入失败时的处理：`abort`（默认）/ `skip_batch` / `skip_record`（`skip`）/ `log`（见 [`crate::exporter::OnError`]）
+    #[serde(default = "default_on_error")]
+    pub on_error: String,
+}
+
+fn default_es_url() -> String {
+    "http://localhost:9200".to_string()
+}
+
+fn default_es_index() -> String {
+    "sqllog".to_string()
+}
+
+fn default_es_batch_size() -> usize {
+    5_000
+}
+
+fn default_es_timeout_secs() -> u64 {
+    30
+}
+
+fn default_es_spill_max_mb() -> u64 {
+    1024
+}
+
+impl Default for ElasticsearchExporter {
+    fn default() -> Self {
+        Self {
+            url: default_es_url(),
+            index: default_es_index(),
+            batch_size: default_es_batch_size(),
+            username: None,
+            password: None,
+            password_env: None,
+            api_key: None,
+            timeout_secs: default_es_timeout_secs(),
+            spill_dir: None,
+            spill_max_mb: default_es_spill_max_mb(),
+            on_error: default_on_error(),
+        }
+    }
+}
+
+impl ElasticsearchExporter {
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |field: &str, value: &str, reason: &str| {
+            Err(Error::Config(ConfigError::InvalidValue {
+                field: format!("exporter.elasticsearch.{field}"),
+                value: value.to_string(),
+                reason: reason.to_string(),
+            }))
+        };
+        if !(self.url.starts_with("http://") || self.url.starts_with("https://")) {
+            return invalid("url", &self.url, "must start with http:// or https://");
+        }
+        if let Err(reason) = validate_index_name(&self.index) {
+            return invalid("index", &self.index, &reason);
+        }
+        if self.batch_size == 0 {
+            return invalid("batch_size", "0", "batch_size must be greater than 0");
+        }
+        if self.timeout_secs == 0 {
+            return invalid("timeout_secs", "0", "timeout_secs must be greater than 0");
+        }
+        if self.spill_dir.as_deref().is_some_and(str::is_empty) {
+            return invalid("spill_dir", "", "spill_dir must not be empty");
+        }
+        if self.spill_max_mb == 0 {
+            return invalid("spill_max_mb", "0", "spill_max_mb must be greater than 0");
+        }
+        if self.api_key.is_some() && self.username.is_some() {
+            return invalid(
+                "api_key",
+                "<redacted>",
+                "cannot be combined with username / password",
+            );
+        }
+        if self.username.is_none() && (self.password.is_some() || self.password_env.is_some()) {
+            return invalid("username", "", "required when a password is configured");
+        }
+        crate::exporter::OnError::parse("exporter.elasticsearch.on_error", &self.on_error)?;
+        Ok(())
+    }
+
+    /// 实际使用的密码：`password_env` 指向的环境变量优先
+    #[must_use]
+    pub fn password(&self) -> Option<String> {
+        self.password_env
+            .as_deref()
+            .and_then(|k| std::env::var(k).ok())
+            .or_else(|| self.password.clone())
+    }
+}
+
+/// `[exporter.custom]`：由宿主程序经 [`crate::exporter::registry::register`] 注册的第三方导出器
+#[derive(Debug, Deserialize, Clone)]
+#[non_exhaustive]
+pub struct CustomExporter {
+    /// 注册名
+    pub name: String,
+    /// 写入失败时的处理：`abort`（默认）/ `skip_batch` / `skip_record`（`skip`）/ `log`（见 [`crate::exporter::OnError`]）
+    #[serde(default = "default_on_error")]
+    pub on_error: String,
+    /// 段内其余键，原样交给导出器工厂
+    #[serde(flatten)]
+    pub options: toml::Table,
+}
+
+impl Default for CustomExporter {
     fn default() -> Self {
         Self {
-            database_url: "export/sqllog2db.db".to_string(),
-            table_name: "sqllog_records".to_string(),
-            overwrite: true,
-            append: false,
-            batch_size: 10_000,
+            name: String::new(),
+            on_error: default_on_error(),
+            options: toml::Table::new(),
         }
     }
 }
 
-impl SqliteExporter {
+impl CustomExporter {
     pub fn validate(&self) -> Result<()> {
-        if self.database_url.trim().is_empty() {
-            return Err(Error::Config(ConfigError::InvalidValue {
-                field: "exporter.sqlite.database_url".to_string(),
-                value: self.database_url.clone(),
-                reason: "SQLite database URL cannot be empty".to_string(),
-            }));
-        }
-        if self.table_name.trim().is_empty() {
+        if self.name.trim().is_empty() {
             return Err(Error::Config(ConfigError::InvalidValue {
-                field: "exporter.sqlite.table_name".to_string(),
-                value: self.table_name.clone(),
-                reason: "SQLite table name cannot be empty".to_string(),
-            }));
-        }
-        // ASCII 标识符校验：^[a-zA-Z_][a-zA-Z0-9_]*$（不引入 regex crate）
-        let is_valid_ident = {
-            let mut chars = self.table_name.chars();
-            chars
-                .next()
-                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
-                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
-        };
-        if !is_valid_ident {
-            return Err(Error::Config(ConfigError::InvalidValue {
-                field: "exporter.sqlite.table_name".to_string(),
-                value: self.table_name.clone(),
-                reason: "table name must match ^[a-zA-Z_][a-zA-Z0-9_]*$ (ASCII identifiers only)"
-                    .to_string(),
+                field: "exporter.custom.name".to_string(),
+                value: self.name.clone(),
+                reason: "name cannot be empty".to_string(),
             }));
         }
-        if self.batch_size == 0 {
-            return Err(ConfigError::InvalidValue {
-                field: "exporter.sqlite.batch_size".to_string(),
-                value: "0".to_string(),
-                reason: "batch_size must be greater than 0".to_string(),
-            }
-            .into());
+        if !crate::exporter::registry::is_registered(&self.name) {
+            return Err(crate::exporter::registry::unregistered(&self.name));
         }
+        crate::exporter::OnError::parse("exporter.custom.on_error", &self.on_error)?;
         Ok(())
     }
 }
 
+/// 索引名（含时间模式）校验：按一个样例日期展开后检查 Elasticsearch 的命名规则
+fn validate_index_name(index: &str) -> std::result::Result<(), String> {
+    if index.trim().is_empty() {
+        return Err("index name cannot be empty".to_string());
+    }
+    let name = if crate::exporter::rollover::is_time_pattern(index) {
+        crate::exporter::rollover::validate_rollover_pattern(index)?;
+        crate::exporter::rollover::RolloverScheduler::new(index)
+            .format_path("2025-01-15 10:30:00.000")
+            .to_string_lossy()
+            .into_owned()
+    } else {
+        index.to_string()
+    };
+    if name.chars().any(|c| c.is_ascii_uppercase()) {
+        return Err("index names must be lowercase".to_string());
+    }
+    if let Some(c) = name.chars().find(|c| {
+        matches!(
+            c,
+            '\\' | '/' | '*' | '?' | '"' | '<' | '>' | '|' | ' ' | ',' | '#' | ':'
+        )
+    }) {
+        return Err(format!("index names cannot contain '{c}'"));
+    }
+    if name.starts_with(['-', '_', '+']) || name == "." || name == ".." {
+        return Err("index names cannot start with '-', '_' or '+', or be '.' / '..'".to_string());
+    }
+    Ok(())
+}
+
+/// ASCII 标识符校验：^[a-zA-Z_][a-zA-Z0-9_]*$（不引入 regex crate）
+fn is_ascii_ident(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
 fn default_true() -> bool {
     true
 }
 
+/// 类型化的配置构造器：从默认配置出发逐项设置，`build()` 时统一校验，
+/// 库调用方无需手写完整的结构体字面量。二进制目标不直接使用。
+#[derive(Debug, Clone, Default)]
+#[allow(dead_code)]
+pub struct ConfigBuilder {
+    config: Config,
+    /// 点路径覆盖，`build()` 时按 `--set` 的规则依次应用
+    overrides: Vec<(String, String)>,
+}
+
+#[allow(dead_code)]
+impl ConfigBuilder {
+    /// 日志路径：目录、单文件、glob 模式或 `-`
+    #[must_use]
+    pub fn sqllog_dir(mut self, path: impl Into<String>) -> Self {
+        self.config.sqllog.path = path.into();
+        self
+    }
+
+    /// 导出为 CSV 文件（其余选项取默认值），替换已设置的导出器
+    #[must_use]
+    pub fn csv_output(self, file: impl Into<String>) -> Self {
+        self.csv(CsvExporter {
+            file: file.into(),
+            ..CsvExporter::default()
+        })
+    }
+
+    /// 使用完整的 CSV 导出器配置，替换已设置的导出器
+    #[must_use]
+    pub fn csv(mut self, csv: CsvExporter) -> Self {
+        self.config.exporter = ExporterConfig {
+            csv: Some(csv),
+            sqlite: None,
+            elasticsearch: None,
+            custom: None,
+        };
+        self
+    }
+
+    /// 导出到 `SQLite` 数据库文件（其余选项取默认值），替换已设置的导出器
+    #[must_use]
+    pub fn sqlite_output(self, database_url: impl Into<String>) -> Self {
+        self.sqlite(SqliteExporter {
+            database_url: database_url.into(),
+            ..SqliteExporter::default()
+        })
+    }
+
+    /// 使用完整的 `SQLite` 导出器配置，替换已设置的导出器
+    #[must_use]
+    pub fn sqlite(mut self, sqlite: SqliteExporter) -> Self {
+        self.config.exporter = ExporterConfig {
+            csv: None,
+            sqlite: Some(sqlite),
+            elasticsearch: None,
+            custom: None,
+        };
+        self
+    }
+
+    /// 使用 Elasticsearch 导出器配置，替换已设置的导出器
+    #[must_use]
+    pub fn elasticsearch(mut self, elasticsearch: ElasticsearchExporter) -> Self {
+        self.config.exporter = ExporterConfig {
+            csv: None,
+            sqlite: None,
+            elasticsearch: Some(elasticsearch),
+            custom: None,
+        };
+        self
+    }
+
+    /// 使用已注册的第三方导出器（见 [`crate::exporter::registry`]），替换已设置的导出器
+    #[must_use]
+    pub fn custom(mut self, custom: CustomExporter) -> Self {
+        self.config.exporter = ExporterConfig {
+            csv: None,
+            sqlite: None,
+            elasticsearch: None,
+            custom: Some(custom),
+        };
+        self
+    }
+
+    #[must_use]
+    pub fn features(mut self, features: FeaturesConfig) -> Self {
+        self.config.features = features;
+        self
+    }
+
+    #[must_use]
+    pub fn logging(mut self, logging: LoggingConfig) -> Self {
+        self.config.logging = logging;
+        self
+    }
+
+    /// 按点路径设置单个字段，键与 `--set` 相同（如 `exporter.csv.dialect`）；
+    /// 未知键或非法值在 `build()` 时报错
+    #[must_use]
+    pub fn set(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.overrides.push((key.into(), value.into()));
+        self
+    }
+
+    /// 应用覆盖并校验，返回可直接交给 `handle_run` 的配置
+    pub fn build(self) -> Result<Config> {
+        let mut config = self.config;
+        for (key, value) in &self.overrides {
+            config.apply_one(key, value)?;
+        }
+        config.validate()?;
+        Ok(config)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -650,6 +2323,258 @@ mod tests {
         assert!(cfg.validate().is_err());
     }
 
+    #[test]
+    fn test_validate_sqlite_hook_sql() {
+        let mut sqlite = SqliteExporter {
+            post_sql: vec!["ANALYZE".into(), "  ".into()],
+            ..SqliteExporter::default()
+        };
+        let err = sqlite.validate().unwrap_err();
+        assert!(
+            err.to_string().contains("exporter.sqlite.post_sql[1]"),
+            "{err}"
+        );
+        sqlite.post_sql.pop();
+        assert!(sqlite.validate().is_ok());
+
+        sqlite.view_name = Some("SQLLOG_RECORDS".into());
+        assert!(sqlite.validate().is_err(), "view must differ from table");
+        sqlite.view_name = Some("sqllog".into());
+        assert!(sqlite.validate().is_ok());
+        sqlite.split_by = Some("username".into());
+        assert!(
+            sqlite.validate().is_err(),
+            "view cannot be combined with split_by"
+        );
+    }
+
+    #[test]
+    fn test_validate_csv_dialect() {
+        let mut cfg = Config::default();
+        let csv = cfg.exporter.csv.as_mut().unwrap();
+        csv.dialect = "excel".into();
+        assert!(cfg.validate().is_err());
+
+        let csv = cfg.exporter.csv.as_mut().unwrap();
+        csv.dialect = "standard".into();
+        csv.field_terminator = Some("|".into());
+        assert!(cfg.validate().is_err(), "terminators require dmfldr");
+
+        let csv = cfg.exporter.csv.as_mut().unwrap();
+        csv.dialect = "dmfldr".into();
+        csv.row_terminator = Some("$$".into());
+        assert!(
+            cfg.validate().is_err(),
+            "row terminator must end with newline"
+        );
+
+        let csv = cfg.exporter.csv.as_mut().unwrap();
+        csv.row_terminator = Some("\r\n".into());
+        assert!(cfg.validate().is_ok());
+
+        let csv = cfg.exporter.csv.as_mut().unwrap();
+        csv.dialect = "tsv".into();
+        assert!(cfg.validate().is_err(), "terminators require dmfldr");
+        let csv = cfg.exporter.csv.as_mut().unwrap();
+        csv.field_terminator = None;
+        csv.row_terminator = None;
+        assert!(cfg.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_csv_compression() {
+        let mut cfg = Config::default();
+        cfg.apply_overrides(&[
+            "exporter.csv.compression=gzip".into(),
+            "exporter.csv.compression_level=9".into(),
+        ])
+        .unwrap();
+        assert!(cfg.validate().is_ok());
+
+        let csv = cfg.exporter.csv.as_mut().unwrap();
+        csv.compression = Some("zstd".into());
+        assert!(cfg.validate().is_err(), "zstd only supports level 1");
+
+        let csv = cfg.exporter.csv.as_mut().unwrap();
+        csv.compression = None;
+        assert!(cfg.validate().is_err(), "level requires compression");
+    }
+
+    #[test]
+    fn test_validate_csv_rotation() {
+        let mut cfg = Config::default();
+        cfg.apply_overrides(&[
+            "exporter.csv.max_file_size=2GB".into(),
+            "exporter.csv.max_records=1000000".into(),
+        ])
+        .unwrap();
+        assert!(cfg.validate().is_ok());
+        assert_eq!(
+            cfg.exporter.csv.as_ref().unwrap().rotation_limits(),
+            (Some(2 << 30), Some(1_000_000))
+        );
+
+        let csv = cfg.exporter.csv.as_mut().unwrap();
+        csv.split_by = Some("username".into());
+        assert!(cfg.validate().is_err(), "rotation conflicts with split_by");
+
+        let csv = cfg.exporter.csv.as_mut().unwrap();
+        csv.split_by = None;
+        csv.max_file_size = Some("2PB".into());
+        assert!(cfg.validate().is_err());
+
+        let csv = cfg.exporter.csv.as_mut().unwrap();
+        csv.max_file_size = None;
+        csv.max_records = Some(0);
+        assert!(cfg.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_csv_exectime_format() {
+        let mut cfg = Config::default();
+        assert_eq!(
+            cfg.exporter.csv.as_ref().unwrap().exectime_format(),
+            crate::exporter::ExecTimeFormat::Integer
+        );
+        cfg.apply_one("exporter.csv.exectime_format", "decimal")
+            .unwrap();
+        assert!(cfg.validate().is_ok());
+        assert_eq!(
+            cfg.exporter.csv.as_ref().unwrap().exectime_format(),
+            crate::exporter::ExecTimeFormat::Decimal
+        );
+        cfg.apply_one("exporter.csv.exectime_format", "comma")
+            .unwrap();
+        assert!(cfg.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_parse_mode() {
+        let mut cfg = Config::default();
+        assert_eq!(cfg.sqllog.parse_mode, "lenient");
+        cfg.apply_one("sqllog.parse_mode", "strict").unwrap();
+        assert!(cfg.validate().is_ok());
+        cfg.sqllog.parse_mode = "loose".into();
+        let err = cfg.validate().unwrap_err();
+        assert!(err.to_string().contains("sqllog.parse_mode"), "{err}");
+        cfg.sqllog.parse_mode = "strict".into();
+        cfg.apply_one("sqllog.file_order", "size_desc").unwrap();
+        assert!(cfg.validate().is_ok());
+        cfg.sqllog.file_order = "random".into();
+        let err = cfg.validate().unwrap_err();
+        assert!(err.to_string().contains("sqllog.file_order"), "{err}");
+    }
+
+    /// 每个字段都有 serde 默认值：只写表头的配置段也能解析，取值与 `Default` 一致
+    #[test]
+    fn test_empty_sections_use_defaults() {
+        let cfg: Config = toml::from_str(
+            "[sqllog]\n[logging]\n[resume]\n[exporter.csv]\n[exporter.sqlite]\n\
+             [features.filters]\n[features.charts]\n[features.user_owner]\n\
+             [[features.client_zone.subnets]]\n[notify.on_failure.email]\n",
+        )
+        .unwrap();
+        assert_eq!(cfg.sqllog.path, SqllogConfig::default().path);
+        assert_eq!(cfg.exporter.csv.unwrap().file, CsvExporter::default().file);
+        assert_eq!(
+            cfg.exporter.sqlite.unwrap().database_url,
+            SqliteExporter::default().database_url
+        );
+        assert!(!cfg.features.filters.unwrap().enable);
+        assert_eq!(cfg.features.charts.unwrap().output_dir, "charts/");
+    }
+
+    #[test]
+    fn test_config_builder() {
+        let cfg = Config::builder()
+            .sqllog_dir("logs")
+            .csv_output("out.csv")
+            .sqlite_output("out.db")
+            .set("exporter.sqlite.table_name", "t")
+            .build()
+            .unwrap();
+        assert_eq!(cfg.sqllog.path, "logs");
+        assert!(cfg.exporter.csv.is_none());
+        let sqlite = cfg.exporter.sqlite.unwrap();
+        assert_eq!(
+            (sqlite.database_url.as_str(), sqlite.table_name.as_str()),
+            ("out.db", "t")
+        );
+
+        let err = Config::builder()
+            .set("exporter.nope", "1")
+            .build()
+            .unwrap_err();
+        assert!(err.to_string().contains("exporter.nope"), "{err}");
+        let err = Config::builder().sqllog_dir(" ").build().unwrap_err();
+        assert!(err.to_string().contains("sqllog.path"), "{err}");
+    }
+
+    #[test]
+    fn test_validate_file_date_range() {
+        let mut cfg = Config::default();
+        cfg.apply_one("sqllog.file_date_from", "2025-01-15")
+            .unwrap();
+        cfg.apply_one("sqllog.file_date_to", "2025-01-15").unwrap();
+        assert!(cfg.validate().is_ok());
+        cfg.sqllog.file_date_to = Some("2025-01-14".into());
+        let err = cfg.validate().unwrap_err();
+        assert!(err.to_string().contains("sqllog.file_date_to"), "{err}");
+        cfg.sqllog.file_date_from = Some("20250115".into());
+        let err = cfg.validate().unwrap_err();
+        assert!(err.to_string().contains("sqllog.file_date_from"), "{err}");
+    }
+
+    #[test]
+    fn test_validate_split_by() {
+        let mut cfg = Config::default();
+        let csv = cfg.exporter.csv.as_mut().unwrap();
+        csv.split_by = Some("appname".into());
+        assert!(cfg.validate().is_err());
+
+        let csv = cfg.exporter.csv.as_mut().unwrap();
+        csv.split_by = Some("username".into());
+        assert!(cfg.validate().is_ok());
+
+        let csv = cfg.exporter.csv.as_mut().unwrap();
+        csv.file = "-".into();
+        let err = cfg.validate().unwrap_err();
+        assert!(err.to_string().contains("stdout"), "{err}");
+
+        let csv = cfg.exporter.csv.as_mut().unwrap();
+        csv.file = "outputs/sqllog-%Y%m%d.csv".into();
+        assert!(cfg.validate().is_err(), "time pattern cannot be split");
+
+        cfg.exporter.csv = None;
+        cfg.exporter.sqlite = Some(SqliteExporter::default());
+        cfg.apply_overrides(&["exporter.sqlite.split_by=schema".into()])
+            .unwrap();
+        assert!(cfg.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_csv_file_rejects_sub_second_pattern() {
+        let mut cfg = default_config();
+        cfg.exporter.csv.as_mut().unwrap().file = "outputs/sqllog-%Y-W%V.csv".into();
+        assert!(cfg.validate().is_ok());
+        cfg.exporter.csv.as_mut().unwrap().file = "outputs/sqllog-%H%M%S%.3f.csv".into();
+        let err = cfg.validate().unwrap_err();
+        assert!(err.to_string().contains("sub-second"), "{err}");
+        cfg.exporter.csv.as_mut().unwrap().file = "outputs/sqllog-%Y%m%d%z.csv".into();
+        let err = cfg.validate().unwrap_err();
+        assert!(err.to_string().contains("timezone specifier"), "{err}");
+    }
+
+    /// 索引名含时区说明符时在展开样例名之前报错，不会 panic
+    #[test]
+    fn test_validate_index_name_rejects_timezone_specifier() {
+        for index in ["sqllog-%Y.%m.%d%z", "sqllog-%Y.%m.%d-%Z", "sqllog-%:z"] {
+            let err = validate_index_name(index).unwrap_err();
+            assert!(err.contains("timezone specifier"), "{index}: {err}");
+        }
+        assert!(validate_index_name("sqllog-%Y.%m.%d").is_ok());
+    }
+
     #[test]
     fn test_validate_empty_sqlite_database_url() {
         let mut cfg = default_config();
@@ -756,6 +2681,75 @@ mod tests {
         assert_eq!(cfg.exporter.sqlite.unwrap().database_url, "/tmp/out.db");
     }
 
+    #[test]
+    fn test_apply_overrides_quota() {
+        let mut cfg = default_config();
+        cfg.apply_overrides(&["features.quota.max_records_per_user=500".into()])
+            .unwrap();
+        let quota = cfg.features.quota.as_ref().unwrap();
+        assert_eq!(quota.max_records_per_user, Some(500));
+        assert_eq!(quota.max_records_per_fingerprint, None);
+        assert!(
+            cfg.apply_overrides(&["features.quota.max_records_per_fingerprint=-1".into()])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_pipeline_chunk_size() {
+        let mut cfg = default_config();
+        assert_eq!(cfg.pipeline.chunk_size, 100_000);
+        cfg.apply_overrides(&["pipeline.chunk_size=500".into()])
+            .unwrap();
+        assert_eq!(cfg.pipeline.chunk_size, 500);
+        cfg.validate().unwrap();
+        cfg.apply_overrides(&["pipeline.chunk_size=0".into()])
+            .unwrap();
+        let err = cfg.validate().unwrap_err();
+        assert!(err.to_string().contains("pipeline.chunk_size"), "{err}");
+        assert!(
+            cfg.apply_overrides(&["pipeline.chunk_size=x".into()])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_pipeline_workers() {
+        let mut cfg = default_config();
+        assert_eq!(cfg.pipeline.workers, None);
+        cfg.apply_overrides(&["pipeline.workers=4".into()]).unwrap();
+        assert_eq!(cfg.pipeline.workers, Some(4));
+        cfg.validate().unwrap();
+        cfg.apply_overrides(&["pipeline.workers=0".into()]).unwrap();
+        let err = cfg.validate().unwrap_err();
+        assert!(err.to_string().contains("pipeline.workers"), "{err}");
+        assert!(cfg.apply_overrides(&["pipeline.workers=x".into()]).is_err());
+    }
+
+    #[test]
+    fn test_error_budget() {
+        let mut cfg = default_config();
+        let metrics = crate::metrics::MetricsSnapshot {
+            parsed: 10,
+            errors: 4,
+            ..Default::default()
+        };
+        cfg.error.check(&metrics).unwrap();
+        cfg.apply_overrides(&["error.max_parse_error_rate=40".into()])
+            .unwrap();
+        cfg.validate().unwrap();
+        cfg.error.check(&metrics).unwrap();
+        cfg.error.max_parse_error_rate = Some(39.9);
+        let err = cfg.error.check(&metrics).unwrap_err();
+        assert!(err.to_string().contains("40.00%"), "{err}");
+        cfg.error
+            .check(&crate::metrics::MetricsSnapshot::default())
+            .unwrap();
+        cfg.apply_overrides(&["error.max_parse_error_rate=150".into()])
+            .unwrap();
+        assert!(cfg.validate().is_err());
+    }
+
     #[test]
     fn test_apply_overrides_unknown_key_returns_error() {
         let mut cfg = default_config();
@@ -825,6 +2819,138 @@ file = "out.csv"
         assert_eq!(cfg.exporter.csv.unwrap().file, "out.csv");
     }
 
+    #[test]
+    fn test_from_file_rejects_unsupported_exporter() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("pg.toml");
+        std::fs::write(
+            &path,
+            "[exporter.csv]\nfile = \"out.csv\"\n[exporter.postgres]\nurl = \"postgres://db\"\n",
+        )
+        .unwrap();
+        let err = Config::from_file(&path).unwrap_err();
+        assert!(
+            matches!(
+                &err,
+                Error::Config(ConfigError::UnsupportedExporter { name, .. }) if name == "postgres"
+            ),
+            "{err}"
+        );
+        let msg = err.to_string();
+        assert!(msg.contains("[exporter.postgres]") && msg.contains("built-in: csv, sqlite"));
+    }
+
+    #[test]
+    fn test_env_overrides_layer_over_file() {
+        let table: toml::Table = toml::from_str(
+            "[sqllog]\ndirectory = \"sqllogs\"\n[exporter.elasticsearch]\nurl = \"http://es:9200\"\nusername = \"elastic\"\npassword = \"in-file\"\n",
+        )
+        .unwrap();
+        let vars = [
+            ("SQLLOG2DB_EXPORTER__ELASTICSEARCH__PASSWORD", "123456"),
+            ("SQLLOG2DB_PIPELINE__WORKERS", "3"),
+            ("SQLLOG2DB_FEATURES__FIELDS", r#"["ts", "sql"]"#),
+            ("SQLLOG2DB_EXPORTER__CSV__FILE", "out.csv"),
+            // 不含 `__` 的不是配置项
+            ("SQLLOG2DB_RUN_ID", "nightly"),
+            ("HOME", "/root"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+        let cfg = Config::from_table(Path::new("c.toml"), table, vars).unwrap();
+        assert_eq!(cfg.sqllog.path, "sqllogs");
+        // 文件中是字符串的键保持字符串，纯数字密码不会变成整数
+        let es = cfg.exporter.elasticsearch.unwrap();
+        assert_eq!(es.password.as_deref(), Some("123456"));
+        assert_eq!(es.url, "http://es:9200");
+        assert_eq!(cfg.pipeline.workers, Some(3));
+        assert_eq!(cfg.features.fields.unwrap(), ["ts", "sql"]);
+        assert_eq!(cfg.exporter.csv.unwrap().file, "out.csv");
+
+        let bad = |name: &str, value: &str| {
+            Config::from_table(
+                Path::new("c.toml"),
+                toml::from_str("[sqllog]\ndirectory = \"sqllogs\"\n").unwrap(),
+                [(name.to_string(), value.to_string())],
+            )
+            .unwrap_err()
+            .to_string()
+        };
+        let err = bad("SQLLOG2DB_PIPELINE__WORKERS", "many");
+        assert!(
+            err.contains("environment overrides: pipeline.workers"),
+            "{err}"
+        );
+        let err = bad("SQLLOG2DB_SQLLOG__DIRECTORY__X", "a");
+        assert!(err.contains("'sqllog.directory' is not a table"), "{err}");
+        let err = bad("SQLLOG2DB_EXPORTER__POSTGRES__PASSWORD", "secret");
+        assert!(err.contains("[exporter.postgres]"), "{err}");
+    }
+
+    /// 无关的非 UTF-8 环境变量跳过；本工具的变量名或值不是 UTF-8 时报错而非 panic
+    #[cfg(unix)]
+    #[test]
+    fn test_env_vars_skips_or_rejects_non_utf8() {
+        use std::ffi::OsString;
+        use std::os::unix::ffi::OsStringExt;
+        let bytes = |b: &[u8]| OsString::from_vec(b.to_vec());
+        let vars = env_vars([
+            (bytes(b"LANG_\xff"), bytes(b"x")),
+            (bytes(b"OTHER"), bytes(b"\xfe\xff")),
+            (bytes(b"SQLLOG2DB_EXPORTER__CSV__FILE"), bytes(b"out.csv")),
+        ])
+        .unwrap();
+        assert_eq!(
+            vars,
+            [(
+                "SQLLOG2DB_EXPORTER__CSV__FILE".to_string(),
+                "out.csv".to_string()
+            )]
+        );
+
+        let err = env_vars([(
+            bytes(b"SQLLOG2DB_EXPORTER__CSV__FILE"),
+            bytes(b"out\xff.csv"),
+        )])
+        .unwrap_err();
+        assert!(err.to_string().contains("not valid UTF-8"), "{err}");
+        let err = env_vars([(bytes(b"SQLLOG2DB_\xff__X"), bytes(b"1"))]).unwrap_err();
+        assert!(err.to_string().contains("not valid UTF-8"), "{err}");
+    }
+
+    #[test]
+    fn test_env_overrides_coerce_by_field_type() {
+        // 文件中没有的键按目标字段类型解析
+        let table: toml::Table = toml::from_str(
+            "[sqllog]\ndirectory = \"sqllogs\"\n[exporter.elasticsearch]\nurl = \"http://es:9200\"\n",
+        )
+        .unwrap();
+        let vars = [
+            ("SQLLOG2DB_EXPORTER__ELASTICSEARCH__PASSWORD", "123456"),
+            ("SQLLOG2DB_EXPORTER__ELASTICSEARCH__USERNAME", "true"),
+            ("SQLLOG2DB_EXPORTER__ELASTICSEARCH__BATCH_SIZE", "200"),
+            ("SQLLOG2DB_LOGGING__FILE", "2025"),
+            ("SQLLOG2DB_EXPORTER__CSV__APPEND", "true"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+        let cfg = Config::from_table(Path::new("c.toml"), table, vars).unwrap();
+        let es = cfg.exporter.elasticsearch.unwrap();
+        assert_eq!(es.password.as_deref(), Some("123456"));
+        assert_eq!(es.username.as_deref(), Some("true"));
+        assert_eq!(es.batch_size, 200);
+        assert_eq!(cfg.logging.file, "2025");
+        assert!(cfg.exporter.csv.unwrap().append);
+
+        let path = |p: &str| p.split('.').map(str::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            field_kind(&path("exporter.elasticsearch.password")),
+            FieldKind::String
+        );
+        assert_eq!(field_kind(&path("sqllog.directory")), FieldKind::String);
+        assert_eq!(field_kind(&path("pipeline.workers")), FieldKind::Scalar);
+        assert_eq!(field_kind(&path("features.fields")), FieldKind::Other);
+        assert_eq!(field_kind(&path("sqllog.no_such_key")), FieldKind::Other);
+    }
+
     #[test]
     fn test_from_file_invalid_toml_returns_error() {
         let dir = tempfile::TempDir::new().unwrap();
@@ -980,6 +3106,7 @@ append = false
             overwrite: true,
             append: false,
             batch_size: 10_000,
+            ..SqliteExporter::default()
         });
         cfg.exporter.csv = None;
         assert!(cfg.validate().is_ok());
@@ -994,6 +3121,7 @@ append = false
             overwrite: true,
             append: false,
             batch_size: 10_000,
+            ..SqliteExporter::default()
         });
         cfg.exporter.csv = None;
         assert!(cfg.validate().is_ok());
@@ -1008,6 +3136,7 @@ append = false
             overwrite: true,
             append: false,
             batch_size: 10_000,
+            ..SqliteExporter::default()
         });
         cfg.exporter.csv = None;
         assert!(cfg.validate().is_ok());
@@ -1022,6 +3151,7 @@ append = false
             overwrite: true,
             append: false,
             batch_size: 10_000,
+            ..SqliteExporter::default()
         });
         cfg.exporter.csv = None;
         let err = cfg.validate().unwrap_err();
@@ -1039,6 +3169,7 @@ append = false
             overwrite: true,
             append: false,
             batch_size: 10_000,
+            ..SqliteExporter::default()
         });
         cfg.exporter.csv = None;
         let err = cfg.validate().unwrap_err();
@@ -1055,6 +3186,7 @@ append = false
             overwrite: true,
             append: false,
             batch_size: 10_000,
+            ..SqliteExporter::default()
         });
         cfg.exporter.csv = None;
         let err = cfg.validate().unwrap_err();
@@ -1071,6 +3203,7 @@ append = false
             overwrite: true,
             append: false,
             batch_size: 10_000,
+            ..SqliteExporter::default()
         });
         cfg.exporter.csv = None;
         let err = cfg.validate().unwrap_err();
@@ -1087,6 +3220,7 @@ append = false
             overwrite: true,
             append: false,
             batch_size: 10_000,
+            ..SqliteExporter::default()
         });
         cfg.exporter.csv = None;
         let err = cfg.validate().unwrap_err();
@@ -26,6 +26,16 @@ pub enum Error {
 
     #[error("Interrupted by user")]
     Interrupted,
+
+    #[error(
+        "Parse error rate {rate:.2}% exceeds error.max_parse_error_rate = {max}% ({errors} of {parsed} records failed to parse)"
+    )]
+    BudgetExceeded {
+        errors: u64,
+        parsed: u64,
+        rate: f64,
+        max: f64,
+    },
 }
 
 #[derive(Debug, Error)]
@@ -60,6 +70,18 @@ pub enum ConfigError {
 
     #[error("At least one exporter must be configured (csv/sqlite)")]
     NoExporters,
+
+    #[error(
+        "Unsupported exporter [exporter.{name}] in {path}: not built into this binary (built-in: {built_in}); run `sqllog2db features` for details"
+    )]
+    UnsupportedExporter {
+        path: PathBuf,
+        name: String,
+        built_in: String,
+    },
+
+    #[error("Preflight check failed for {path}: {reason}")]
+    PreflightFailed { path: PathBuf, reason: String },
 }
 
 #[derive(Debug, Error)]
@@ -87,6 +109,13 @@ pub enum ParserError {
 
     #[error("Failed to read directory {path}: {reason}")]
     ReadDirFailed { path: PathBuf, reason: String },
+
+    #[error("Invalid record in {path}: {reason}")]
+    InvalidRecord { path: PathBuf, reason: String },
+
+    /// 与校验清单不符的日志文件
+    #[error("Corrupted file {path}: {reason}")]
+    Corrupted { path: PathBuf, reason: String },
 }
 
 #[derive(Debug, Error)]
@@ -98,4 +127,26 @@ pub enum ExportError {
     /// `SQLite` 操作失败
     #[error("Database error: {reason}")]
     DatabaseFailed { reason: String },
+
+    /// 追加装载前检测到目标表已有同一装载窗口的数据（`duplicate_check = "abort"`）
+    #[error(
+        "Table {table} already contains {rows} rows for {window}; refusing to load again (set exporter.sqlite.duplicate_check = \"warn\" or \"off\" to override)"
+    )]
+    DuplicateLoad {
+        table: String,
+        window: String,
+        rows: i64,
+    },
+
+    /// Elasticsearch `_bulk` 请求失败或有文档被拒绝
+    #[error("Bulk indexing failed {url}: {reason}")]
+    BulkFailed { url: String, reason: String },
+
+    /// 并发导出的写线程已退出（原始错误由 `ConcurrentExporterManager::finish` 返回）
+    #[error("Export worker stopped: {reason}")]
+    WorkerStopped { reason: String },
+
+    /// 输出到 stdout 时下游已关闭管道（如 `| head`）
+    #[error("Output pipe closed by reader")]
+    OutputClosed,
 }
@@ -0,0 +1,291 @@
+//! 自有记录批次：一批记录的全部文本追加进同一块字符串 arena，逐行只保存偏移量，
+//! 可整体 `Send` 到其他线程；攒批时每行只有几次 `push_str`，不为每个字段分配 `String`。
+//!
+//! 导出时按行重建借用 arena 的 `Sqllog` / `MetaParts` / `PerformanceMetrics`，
+//! 沿用导出器的逐条导出接口（见 [`RecordBatch::export_all`]）。
+use super::ExporterManager;
+use crate::error::Result;
+use crate::features::DerivedValue;
+use crate::parser::OwnedSqllog;
+use dm_database_parser_sqllog::{MetaParts, PerformanceMetrics, Sqllog};
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// arena 中的一段文本
+#[derive(Debug, Clone, Copy, Default)]
+struct Span {
+    start: usize,
+    end: usize,
+}
+
+/// 一行的定长部分：文本字段为 arena 偏移，派生列为 `extras` 中的区间
+#[derive(Debug, Clone)]
+struct RowSpans {
+    ts: Span,
+    tag: Option<Span>,
+    ep: u8,
+    sess_id: Span,
+    thrd_id: Span,
+    username: Span,
+    trx_id: Span,
+    statement: Span,
+    appname: Span,
+    client_ip: Span,
+    sql: Span,
+    normalized: Option<Span>,
+    exectime: f32,
+    rowcount: u32,
+    exec_id: i64,
+    extras: Range<usize>,
+    /// `[features.time]` 改写 `ts` 前的原始日志时间
+    source_ts: Option<Span>,
+}
+
+/// 自有记录批次（字符串 arena + 偏移）
+#[derive(Debug, Clone, Default)]
+pub struct RecordBatch {
+    arena: String,
+    rows: Vec<RowSpans>,
+    extras: Vec<DerivedValue>,
+    /// 下一行的原始日志时间（[`RecordBatch::set_source_ts`]）
+    pending_source_ts: Option<Span>,
+}
+
+impl RecordBatch {
+    /// 预留 `rows` 行、`text_bytes` 字节的文本空间
+    #[must_use]
+    pub fn with_capacity(rows: usize, text_bytes: usize) -> Self {
+        Self {
+            arena: String::with_capacity(text_bytes),
+            rows: Vec::with_capacity(rows),
+            extras: Vec::new(),
+            pending_source_ts: None,
+        }
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// arena 已用的文本字节数（用于为下一批预留容量）
+    #[must_use]
+    pub fn text_bytes(&self) -> usize {
+        self.arena.len()
+    }
+
+    fn put(&mut self, s: &str) -> Span {
+        let start = self.arena.len();
+        self.arena.push_str(s);
+        Span {
+            start,
+            end: self.arena.len(),
+        }
+    }
+
+    fn get(&self, span: Span) -> &str {
+        &self.arena[span.start..span.end]
+    }
+
+    /// 下一行的原始日志时间（`[features.time]` 改写了 `ts` 时），导出时交给导出器的周期滚动
+    pub fn set_source_ts(&mut self, ts: &str) {
+        self.pending_source_ts = Some(self.put(ts));
+    }
+
+    /// 追加一行；`extras` 与导出器的派生列一一对应，为空时导出走无派生列路径
+    pub fn push(
+        &mut self,
+        sqllog: &Sqllog<'_>,
+        meta: &MetaParts<'_>,
+        pm: &PerformanceMetrics<'_>,
+        normalized: Option<&str>,
+        extras: &[DerivedValue],
+    ) {
+        let start = self.extras.len();
+        self.extras.extend_from_slice(extras);
+        let row = RowSpans {
+            ts: self.put(&sqllog.ts),
+            tag: sqllog.tag.as_deref().map(|t| self.put(t)),
+            ep: meta.ep,
+            sess_id: self.put(&meta.sess_id),
+            thrd_id: self.put(&meta.thrd_id),
+            username: self.put(&meta.username),
+            trx_id: self.put(&meta.trxid),
+            statement: self.put(&meta.statement),
+            appname: self.put(&meta.appname),
+            client_ip: self.put(&meta.client_ip),
+            sql: self.put(&pm.sql),
+            normalized: normalized.map(|n| self.put(n)),
+            exectime: pm.exectime,
+            rowcount: pm.rowcount,
+            exec_id: pm.exec_id,
+            extras: start..self.extras.len(),
+            source_ts: self.pending_source_ts.take(),
+        };
+        self.rows.push(row);
+    }
+
+    /// 追加一条已拥有的记录（缺失的性能指标按 0 导出）
+    #[allow(dead_code)] // 库用法；二进制目标经 `push()` 追加
+    pub fn push_owned(&mut self, record: &OwnedSqllog) {
+        let mut sqllog = Sqllog::default();
+        sqllog.ts = Cow::Borrowed(&record.ts);
+        sqllog.tag = record.tag.as_deref().map(Cow::Borrowed);
+        let meta = MetaParts {
+            ep: record.ep,
+            sess_id: Cow::Borrowed(&record.sess_id),
+            thrd_id: Cow::Borrowed(&record.thrd_id),
+            username: Cow::Borrowed(&record.username),
+            trxid: Cow::Borrowed(&record.trx_id),
+            statement: Cow::Borrowed(&record.statement),
+            appname: Cow::Borrowed(&record.appname),
+            client_ip: Cow::Borrowed(&record.client_ip),
+        };
+        let pm = PerformanceMetrics {
+            exectime: record.exec_time_ms.unwrap_or(0.0),
+            rowcount: record.row_count.unwrap_or(0),
+            exec_id: record.exec_id.unwrap_or(0),
+            sql: Cow::Borrowed(&record.sql),
+        };
+        self.push(&sqllog, &meta, &pm, None, &[]);
+    }
+
+    /// 按追加顺序逐行交给导出器
+    pub fn export_all(&self, manager: &mut ExporterManager) -> Result<()> {
+        for row in &self.rows {
+            let mut sqllog = Sqllog::default();
+            sqllog.ts = Cow::Borrowed(self.get(row.ts));
+            sqllog.tag = row.tag.map(|t| Cow::Borrowed(self.get(t)));
+            let meta = MetaParts {
+                ep: row.ep,
+                sess_id: Cow::Borrowed(self.get(row.sess_id)),
+                thrd_id: Cow::Borrowed(self.get(row.thrd_id)),
+                username: Cow::Borrowed(self.get(row.username)),
+                trxid: Cow::Borrowed(self.get(row.trx_id)),
+                statement: Cow::Borrowed(self.get(row.statement)),
+                appname: Cow::Borrowed(self.get(row.appname)),
+                client_ip: Cow::Borrowed(self.get(row.client_ip)),
+            };
+            let pm = PerformanceMetrics {
+                exectime: row.exectime,
+                rowcount: row.rowcount,
+                exec_id: row.exec_id,
+                sql: Cow::Borrowed(self.get(row.sql)),
+            };
+            let normalized = row.normalized.map(|n| self.get(n));
+            let extras = &self.extras[row.extras.clone()];
+            if let Some(source) = row.source_ts {
+                manager.set_source_ts(self.get(source));
+            }
+            if extras.is_empty() {
+                manager.export_one_preparsed(&sqllog, &meta, &pm, normalized)?;
+            } else {
+                manager.export_one_derived(&sqllog, &meta, &pm, normalized, extras)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporter::CsvExporter;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_batch_round_trips_rows_through_exporter() {
+        let mut batch = RecordBatch::with_capacity(2, 256);
+        let mut sqllog = Sqllog::default();
+        sqllog.ts = Cow::Borrowed("2025-01-15 10:30:28.001");
+        sqllog.tag = Some(Cow::Borrowed("SEL"));
+        let meta = MetaParts {
+            ep: 3,
+            username: Cow::Borrowed("APP"),
+            client_ip: Cow::Borrowed("10.0.0.1"),
+            ..MetaParts::default()
+        };
+        let pm = PerformanceMetrics {
+            exectime: 2.5,
+            rowcount: 7,
+            exec_id: 42,
+            sql: Cow::Borrowed("SELECT 1"),
+        };
+        batch.push(&sqllog, &meta, &pm, Some("SELECT 1 /*n*/"), &[]);
+        batch.push_owned(&OwnedSqllog {
+            ts: "2025-01-15 10:30:28.002".into(),
+            sql: "SELECT 2".into(),
+            ..OwnedSqllog::default()
+        });
+        assert_eq!(batch.len(), 2);
+        assert_eq!(
+            batch.text_bytes(),
+            "2025-01-15 10:30:28.001SELAPP10.0.0.1SELECT 1SELECT 1 /*n*/2025-01-15 10:30:28.002SELECT 2".len()
+        );
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let out = dir.path().join("out.csv");
+        let mut manager = ExporterManager::from_csv(CsvExporter::new(&out));
+        manager.initialize().unwrap();
+        batch.export_all(&mut manager).unwrap();
+        manager.finalize().unwrap();
+        let content = std::fs::read_to_string(&out).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[1],
+            "2025-01-15 10:30:28.001,3,,,APP,,,,10.0.0.1,SEL,\"SELECT 1\",2,7,42,\"SELECT 1 /*n*/\""
+        );
+        assert!(lines[2].contains("\"SELECT 2\""), "{}", lines[2]);
+
+        // 派生列按行切分
+        let mut derived = RecordBatch::default();
+        let extra = [DerivedValue::Int(1), DerivedValue::Text(Arc::from("x"))];
+        derived.push(&sqllog, &meta, &pm, None, &extra);
+        derived.push(&sqllog, &meta, &pm, None, &extra[..1]);
+        assert_eq!(derived.extras.len(), 3);
+        assert_eq!(derived.rows[1].extras, 2..3);
+    }
+
+    /// 原始日志时间随行跨线程传递，写线程一侧的周期滚动按原始时间确定文件
+    #[test]
+    fn test_batch_carries_source_ts_to_rollover() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let pattern = dir.path().join("out-%Y%m%d-%H.csv");
+        let mut manager =
+            ExporterManager::from_csv(CsvExporter::from_config(&crate::config::CsvExporter {
+                file: pattern.to_string_lossy().into(),
+                ..crate::config::CsvExporter::default()
+            }));
+        manager.initialize().unwrap();
+
+        let mut batch = RecordBatch::default();
+        let mut sqllog = Sqllog::default();
+        let pm = PerformanceMetrics {
+            sql: Cow::Borrowed("SELECT 1"),
+            ..PerformanceMetrics::default()
+        };
+        for (raw, shifted) in [
+            ("2025-01-15 10:30:28.001", "1736908228001"),
+            ("2025-01-15 11:00:00.000", "1736910000000"),
+        ] {
+            sqllog.ts = Cow::Borrowed(shifted);
+            batch.set_source_ts(raw);
+            batch.push(&sqllog, &MetaParts::default(), &pm, None, &[]);
+        }
+        batch.export_all(&mut manager).unwrap();
+        manager.finalize().unwrap();
+
+        for (hour, shifted) in [("10", "1736908228001"), ("11", "1736910000000")] {
+            let content =
+                std::fs::read_to_string(dir.path().join(format!("out-20250115-{hour}.csv")))
+                    .unwrap();
+            assert!(content.lines().nth(1).unwrap().starts_with(shifted));
+        }
+    }
+}
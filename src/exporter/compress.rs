@@ -0,0 +1,235 @@
+//! 文本导出器的输出压缩（`exporter.csv.compression`）：位于编码（CSV 各方言）与输出目标（[`super::sink`]）之间。
+//!
+//! - `gzip`：flate2，级别 0–9（默认 6）
+//! - `zstd`：纯 Rust 编码器（ruzstd），目前只实现最快一档（约等于 zstd 级别 1），其他级别在配置校验时报错
+//!
+//! 分块刷新（`flush`）会把已写入的数据完整编码交给下游：gzip 做一次 sync flush，zstd 结束当前帧。
+//! 追加写入与滚动切换产生的多个 gzip member / zstd 帧前后拼接，`gzip -d` / `zstd -d` 均按一个文件解压。
+use crate::error::{ConfigError, Error, Result};
+use flate2::write::GzEncoder;
+use ruzstd::encoding::CompressionLevel;
+use std::io::{self, Write};
+
+/// `exporter.csv.compression` 可选值
+pub const COMPRESSIONS: &[&str] = &["gzip", "zstd"];
+
+/// 默认 gzip 级别
+const DEFAULT_GZIP_LEVEL: u32 = 6;
+
+/// zstd 单帧最多缓冲的未压缩字节数（攒满即输出一帧）
+const ZSTD_FRAME_BYTES: usize = 8 * 1024 * 1024;
+
+/// 压缩方式与级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip(u32),
+    Zstd,
+}
+
+impl Compression {
+    /// 解析 `compression` / `compression_level`；`field` 为配置段前缀（如 `exporter.csv`）
+    pub fn parse(field: &str, name: &str, level: Option<u32>) -> Result<Self> {
+        let invalid = |key: &str, value: String, reason: &str| {
+            Err(Error::Config(ConfigError::InvalidValue {
+                field: format!("{field}.{key}"),
+                value,
+                reason: reason.to_string(),
+            }))
+        };
+        match (name, level) {
+            ("gzip", None) => Ok(Self::Gzip(DEFAULT_GZIP_LEVEL)),
+            ("gzip", Some(l)) if l <= 9 => Ok(Self::Gzip(l)),
+            ("gzip", Some(l)) => invalid(
+                "compression_level",
+                l.to_string(),
+                "gzip level must be between 0 and 9",
+            ),
+            ("zstd", None | Some(1)) => Ok(Self::Zstd),
+            ("zstd", Some(l)) => invalid(
+                "compression_level",
+                l.to_string(),
+                "zstd only supports level 1: the built-in pure-Rust encoder has no higher levels; omit compression_level or set it to 1",
+            ),
+            _ => invalid(
+                "compression",
+                name.to_string(),
+                &format!("must be one of: {}", COMPRESSIONS.join(", ")),
+            ),
+        }
+    }
+}
+
+/// 按块输出独立 zstd 帧的写入器
+#[derive(Debug)]
+pub(crate) struct ZstdFrames<W: Write> {
+    inner: W,
+    buf: Vec<u8>,
+    frame: Vec<u8>,
+}
+
+impl<W: Write> ZstdFrames<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            buf: Vec::new(),
+            frame: Vec::new(),
+        }
+    }
+
+    /// 把缓冲的数据编码为一帧写出
+    fn emit_frame(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        self.frame.clear();
+        // 先编码到内存：ruzstd 写 drain 出错时会 panic，I/O 错误须由这里的 write_all 返回
+        ruzstd::encoding::compress(
+            self.buf.as_slice(),
+            &mut self.frame,
+            CompressionLevel::Fastest,
+        );
+        self.buf.clear();
+        self.inner.write_all(&self.frame)
+    }
+}
+
+impl<W: Write> Write for ZstdFrames<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(data);
+        if self.buf.len() >= ZSTD_FRAME_BYTES {
+            self.emit_frame()?;
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.emit_frame()?;
+        self.inner.flush()
+    }
+}
+
+/// 可选压缩层
+#[derive(Debug)]
+pub(crate) enum Encoder<W: Write> {
+    Plain(W),
+    Gzip(GzEncoder<W>),
+    Zstd(ZstdFrames<W>),
+}
+
+impl<W: Write> Encoder<W> {
+    pub(crate) fn new(inner: W, compression: Option<Compression>) -> Self {
+        match compression {
+            None => Self::Plain(inner),
+            Some(Compression::Gzip(level)) => {
+                Self::Gzip(GzEncoder::new(inner, flate2::Compression::new(level)))
+            }
+            Some(Compression::Zstd) => Self::Zstd(ZstdFrames::new(inner)),
+        }
+    }
+
+    /// 写出压缩尾部（gzip trailer / 最后一帧）并刷新下游
+    pub(crate) fn finish(self) -> io::Result<W> {
+        let mut inner = match self {
+            Self::Plain(w) => w,
+            Self::Gzip(e) => e.finish()?,
+            Self::Zstd(mut z) => {
+                z.emit_frame()?;
+                z.inner
+            }
+        };
+        inner.flush()?;
+        Ok(inner)
+    }
+}
+
+impl<W: Write> Write for Encoder<W> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(w) => w.write(buf),
+            Self::Gzip(e) => e.write(buf),
+            Self::Zstd(z) => z.write(buf),
+        }
+    }
+
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        match self {
+            Self::Plain(w) => w.write_all(buf),
+            Self::Gzip(e) => e.write_all(buf),
+            Self::Zstd(z) => z.write_all(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Plain(w) => w.flush(),
+            Self::Gzip(e) => e.flush(),
+            Self::Zstd(z) => z.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_parse_compression() {
+        assert_eq!(
+            Compression::parse("exporter.csv", "gzip", None).unwrap(),
+            Compression::Gzip(6)
+        );
+        assert_eq!(
+            Compression::parse("exporter.csv", "zstd", Some(1)).unwrap(),
+            Compression::Zstd
+        );
+        let err = Compression::parse("exporter.csv", "gzip", Some(10))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("exporter.csv.compression_level"), "{err}");
+        assert_eq!(
+            Compression::parse("exporter.csv", "zstd", None).unwrap(),
+            Compression::Zstd
+        );
+        let err = Compression::parse("exporter.csv", "zstd", Some(3))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("exporter.csv.compression_level"), "{err}");
+        assert!(err.contains("only supports level 1"), "{err}");
+        assert!(Compression::parse("exporter.csv", "lz4", None).is_err());
+    }
+
+    #[test]
+    fn test_flushed_members_concatenate() {
+        for compression in [Compression::Gzip(6), Compression::Zstd] {
+            let mut enc = Encoder::new(Vec::new(), Some(compression));
+            enc.write_all(b"a,b\n1,2\n").unwrap();
+            enc.flush().unwrap();
+            enc.write_all(b"3,4\n").unwrap();
+            let mut out = enc.finish().unwrap();
+            // 追加写入：同一文件中再接一段独立压缩的数据
+            let mut more = Encoder::new(Vec::new(), Some(compression));
+            more.write_all(b"5,6\n").unwrap();
+            out.extend(more.finish().unwrap());
+
+            let mut text = String::new();
+            match compression {
+                Compression::Gzip(_) => {
+                    flate2::read::MultiGzDecoder::new(out.as_slice())
+                        .read_to_string(&mut text)
+                        .unwrap();
+                }
+                Compression::Zstd => {
+                    let mut src = out.as_slice();
+                    while !src.is_empty() {
+                        let mut frame = ruzstd::decoding::StreamingDecoder::new(&mut src).unwrap();
+                        frame.read_to_string(&mut text).unwrap();
+                    }
+                }
+            }
+            assert_eq!(text, "a,b\n1,2\n3,4\n5,6\n", "{compression:?}");
+        }
+    }
+}
@@ -0,0 +1,309 @@
+//! 多线程导出：多个解析线程并发推送记录批次，由专用写线程独占 `ExporterManager` 顺序落盘。
+//!
+//! 生产方只持有 `&ConcurrentExporterManager`（`Sync`），推送时仅发生一次有界通道发送，
+//! 不在每条记录上竞争锁；写线程出错后停止接收，后续推送立即失败，原始错误由 `finish()` 返回。
+//!
+//! 多文件并发解析（`pipeline.workers`）时，每个解析线程通过 [`ConcurrentExporterManager::producer`]
+//! 取得一个攒批转发的 `ExporterManager`，沿用与顺序路径相同的逐条导出调用。
+//! 批次为 [`RecordBatch`]：文本集中在一块 arena 中，跨线程传递时没有逐字段的 `String` 分配。
+use super::batch::RecordBatch;
+use super::{ExportStats, Exporter, ExporterKind, ExporterManager};
+use crate::error::{Error, ExportError, Result};
+use crate::features::DerivedValue;
+use dm_database_parser_sqllog::{MetaParts, PerformanceMetrics, Sqllog};
+use std::sync::mpsc::{SyncSender, sync_channel};
+use std::thread::JoinHandle;
+use std::time::Instant;
+
+/// 通道中最多积压的批次数（背压：写线程跟不上时阻塞生产方）
+pub const DEFAULT_QUEUE_BATCHES: usize = 16;
+
+/// 生产方导出器每攒够这么多行推送一批
+pub const PRODUCER_BATCH_ROWS: usize = 1024;
+
+/// 可在线程间共享的导出管理器
+#[derive(Debug)]
+pub struct ConcurrentExporterManager {
+    tx: Option<SyncSender<RecordBatch>>,
+    worker: Option<JoinHandle<Result<ExporterManager>>>,
+    include_pm: bool,
+}
+
+impl ConcurrentExporterManager {
+    /// 初始化导出器并启动写线程；`queue_batches` 为通道容量（≥1）
+    pub fn start(mut manager: ExporterManager, queue_batches: usize) -> Result<Self> {
+        manager.initialize()?;
+        let include_pm = manager.csv_include_performance_metrics();
+        let (tx, rx) = sync_channel::<RecordBatch>(queue_batches.max(1));
+        let worker = std::thread::Builder::new()
+            .name("sqllog2db-export".to_string())
+            .spawn(move || -> Result<ExporterManager> {
+                for batch in rx {
+                    batch.export_all(&mut manager)?;
+                }
+                manager.finalize()?;
+                Ok(manager)
+            })
+            .map_err(|e| worker_stopped(format!("cannot spawn export thread: {e}")))?;
+        Ok(Self {
+            tx: Some(tx),
+            worker: Some(worker),
+            include_pm,
+        })
+    }
+
+    /// 为一个生产线程创建导出器：记录追加进 [`RecordBatch`] 攒批推送，`flush_chunk()` / `finalize()`
+    /// 推送剩余行；写线程已因错误退出时导出调用返回 `ExportError::WorkerStopped`
+    pub fn producer(&self) -> Result<ExporterManager> {
+        let tx = self
+            .tx
+            .as_ref()
+            .ok_or_else(|| worker_stopped("already finished".to_string()))?;
+        Ok(ExporterManager::wrap(ExporterKind::Queue(QueueExporter {
+            tx: tx.clone(),
+            batch: RecordBatch::with_capacity(PRODUCER_BATCH_ROWS, 0),
+            include_pm: self.include_pm,
+            stats: ExportStats::new(),
+        })))
+    }
+
+    /// 推送一批记录；写线程已因错误退出时返回 `ExportError::WorkerStopped`
+    #[allow(dead_code)] // 库用法；二进制目标经 `producer()` 推送
+    pub fn push_batch(&self, batch: RecordBatch) -> Result<()> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+        let tx = self
+            .tx
+            .as_ref()
+            .ok_or_else(|| worker_stopped("already finished".to_string()))?;
+        tx.send(batch)
+            .map_err(|_| worker_stopped("export thread exited early".to_string()))
+    }
+
+    /// 关闭通道并等待写线程完成 finalize，返回导出器（用于 `log_stats` / `write_template_stats`）
+    pub fn finish(mut self) -> Result<ExporterManager> {
+        self.tx.take();
+        let worker = self
+            .worker
+            .take()
+            .ok_or_else(|| worker_stopped("already finished".to_string()))?;
+        worker
+            .join()
+            .map_err(|_| worker_stopped("export thread panicked".to_string()))?
+    }
+}
+
+/// 生产线程一侧的导出器（见 [`ConcurrentExporterManager::producer`]）
+#[derive(Debug)]
+pub struct QueueExporter {
+    tx: SyncSender<RecordBatch>,
+    batch: RecordBatch,
+    /// 与写线程导出器一致，决定调用方是否需要解析性能指标
+    pub(super) include_pm: bool,
+    stats: ExportStats,
+}
+
+impl QueueExporter {
+    fn pushed(&mut self) -> Result<()> {
+        self.stats.record_success();
+        if self.batch.len() >= PRODUCER_BATCH_ROWS {
+            self.send()?;
+        }
+        Ok(())
+    }
+
+    fn send(&mut self) -> Result<()> {
+        if self.batch.is_empty() {
+            return Ok(());
+        }
+        // 下一批按本批的文本量预留 arena，避免反复扩容
+        let next = RecordBatch::with_capacity(PRODUCER_BATCH_ROWS, self.batch.text_bytes());
+        let batch = std::mem::replace(&mut self.batch, next);
+        // 写线程跟不上时 send 阻塞：耗时反映下游背压
+        let started = Instant::now();
+        self.tx
+            .send(batch)
+            .map_err(|_| worker_stopped("export thread exited early".to_string()))?;
+        self.stats.record_flush(started.elapsed());
+        Ok(())
+    }
+}
+
+impl Exporter for QueueExporter {
+    fn initialize(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn export(&mut self, sqllog: &Sqllog<'_>) -> Result<()> {
+        let meta = sqllog.parse_meta();
+        let pm = sqllog.parse_performance_metrics();
+        self.batch.push(sqllog, &meta, &pm, None, &[]);
+        self.pushed()
+    }
+
+    fn export_one_preparsed(
+        &mut self,
+        sqllog: &Sqllog<'_>,
+        meta: &MetaParts<'_>,
+        pm: &PerformanceMetrics<'_>,
+        normalized: Option<&str>,
+    ) -> Result<()> {
+        self.batch.push(sqllog, meta, pm, normalized, &[]);
+        self.pushed()
+    }
+
+    fn export_one_derived(
+        &mut self,
+        sqllog: &Sqllog<'_>,
+        meta: &MetaParts<'_>,
+        pm: &PerformanceMetrics<'_>,
+        normalized: Option<&str>,
+        extras: &[DerivedValue],
+    ) -> Result<()> {
+        self.batch.push(sqllog, meta, pm, normalized, extras);
+        self.pushed()
+    }
+
+    fn set_source_ts(&mut self, ts: &str) {
+        self.batch.set_source_ts(ts);
+    }
+
+    fn flush_chunk(&mut self) -> Result<()> {
+        self.send()
+    }
+
+    fn finalize(&mut self) -> Result<()> {
+        self.send()
+    }
+
+    fn stats_snapshot(&self) -> Option<ExportStats> {
+        Some(self.stats)
+    }
+}
+
+impl Drop for ConcurrentExporterManager {
+    fn drop(&mut self) {
+        self.tx.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn worker_stopped(reason: String) -> Error {
+    Error::Export(ExportError::WorkerStopped { reason })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exporter::CsvExporter;
+    use crate::parser::OwnedSqllog;
+    use std::borrow::Cow;
+
+    fn row(i: usize) -> OwnedSqllog {
+        OwnedSqllog {
+            ts: format!("2025-01-15 10:30:28.{:03}", i % 1000),
+            username: "U".into(),
+            trx_id: i.to_string(),
+            sql: format!("SELECT {i}"),
+            exec_time_ms: Some(1.0),
+            row_count: Some(1),
+            exec_id: Some(i64::try_from(i).unwrap()),
+            ..OwnedSqllog::default()
+        }
+    }
+
+    #[test]
+    fn test_concurrent_manager_is_sync() {
+        fn assert_sync<T: Sync + Send>() {}
+        assert_sync::<ConcurrentExporterManager>();
+    }
+
+    #[test]
+    fn test_parallel_producers_all_rows_written() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let out = dir.path().join("out.csv");
+        let mut csv = CsvExporter::new(&out);
+        csv.normalize = false;
+        let cm = ConcurrentExporterManager::start(ExporterManager::from_csv(csv), 2).unwrap();
+
+        std::thread::scope(|s| {
+            for t in 0..4 {
+                let cm = &cm;
+                s.spawn(move || {
+                    for chunk in 0..5 {
+                        let mut batch = RecordBatch::default();
+                        for i in 0..50 {
+                            batch.push_owned(&row(t * 1000 + chunk * 50 + i));
+                        }
+                        cm.push_batch(batch).unwrap();
+                    }
+                });
+            }
+        });
+        let manager = cm.finish().unwrap();
+        assert_eq!(manager.name(), "CSV");
+
+        let content = std::fs::read_to_string(&out).unwrap();
+        assert_eq!(content.lines().count(), 1 + 4 * 5 * 50);
+        assert!(content.contains(",\"SELECT 3249\","));
+    }
+
+    #[test]
+    fn test_producers_batch_and_flush_remaining() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let out = dir.path().join("out.csv");
+        let mut csv = CsvExporter::new(&out);
+        csv.normalize = false;
+        let cm = ConcurrentExporterManager::start(ExporterManager::from_csv(csv), 1).unwrap();
+
+        std::thread::scope(|s| {
+            for t in 0..3 {
+                let cm = &cm;
+                s.spawn(move || {
+                    let mut em = cm.producer().unwrap();
+                    // 不足一批的尾部由 finalize() 推送
+                    for i in 0..PRODUCER_BATCH_ROWS + 7 {
+                        let r = row(t * 10_000 + i);
+                        let mut sqllog = Sqllog::default();
+                        sqllog.ts = Cow::Borrowed(&r.ts);
+                        let meta = MetaParts {
+                            username: Cow::Borrowed(&r.username),
+                            trxid: Cow::Borrowed(&r.trx_id),
+                            ..MetaParts::default()
+                        };
+                        let pm = PerformanceMetrics {
+                            exectime: 1.0,
+                            rowcount: 1,
+                            exec_id: 1,
+                            sql: Cow::Borrowed(&r.sql),
+                        };
+                        em.export_one_preparsed(&sqllog, &meta, &pm, None).unwrap();
+                    }
+                    em.finalize().unwrap();
+                });
+            }
+        });
+        cm.finish().unwrap();
+
+        let content = std::fs::read_to_string(&out).unwrap();
+        assert_eq!(content.lines().count(), 1 + 3 * (PRODUCER_BATCH_ROWS + 7));
+        assert!(content.contains(",\"SELECT 21030\","));
+    }
+
+    #[test]
+    fn test_start_propagates_initialize_error() {
+        let dir = tempfile::TempDir::new().unwrap();
+        // 父路径是普通文件，无法创建输出目录
+        let blocker = dir.path().join("blocker");
+        std::fs::write(&blocker, "").unwrap();
+        let csv = CsvExporter::new(blocker.join("out.csv"));
+        let err = ConcurrentExporterManager::start(ExporterManager::from_csv(csv), 1).unwrap_err();
+        assert!(
+            matches!(err, Error::Export(ExportError::WriteFailed { .. })),
+            "{err}"
+        );
+    }
+}
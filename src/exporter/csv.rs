@@ -1,12 +1,18 @@
+use super::compress::{Compression, Encoder};
+use super::rollover::{PartRotation, RolloverScheduler, is_time_pattern};
+use super::sink::{Sink, is_stream};
+use super::{ExecTimeFormat, ensure_parent_dir, is_stdout, strip_ip_prefix};
 use super::{ExportStats, Exporter};
-use super::{ensure_parent_dir, f32_ms_to_i64, strip_ip_prefix};
 use crate::config;
-use crate::error::{Error, ExportError, Result};
+use crate::error::{ConfigError, Error, ExportError, Result};
+use crate::features::{DerivedColumnSpec, DerivedValue, SplitBy};
+use ahash::HashMap as AHashMap;
 use dm_database_parser_sqllog::{MetaParts, PerformanceMetrics, Sqllog};
-use log::info;
+use log::{info, warn};
 use std::fs::{File, OpenOptions};
-use std::io::{BufWriter, Write};
+use std::io::{self, BufWriter, Write};
 use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 /// 将字节序列写入 `buf`，对其中的 `"` 字符进行 CSV 转义（变为 `""`）。
 /// 使用 memchr 跳过无引号的大段内容，避免逐字节循环。
@@ -21,6 +27,204 @@ fn write_csv_escaped(buf: &mut Vec<u8>, bytes: &[u8]) {
     buf.extend_from_slice(remaining);
 }
 
+/// 派生列值：整数直接写入，文本加引号转义，Null 为空
+#[inline]
+fn write_derived(line_buf: &mut Vec<u8>, itoa_buf: &mut itoa::Buffer, v: &DerivedValue) {
+    match v {
+        DerivedValue::Null => {}
+        DerivedValue::Int(n) => line_buf.extend_from_slice(itoa_buf.format(*n).as_bytes()),
+        DerivedValue::Text(t) => {
+            line_buf.push(b'"');
+            write_csv_escaped(line_buf, t.as_bytes());
+            line_buf.push(b'"');
+        }
+    }
+}
+
+/// 非标准输出方言：将标准 CSV 行转写为目标装载工具可直接读取的格式。
+///
+/// - `dmfldr`：dmfldr 无法正确处理引号内的换行与分隔符，因此字段不加引号（`""` 还原为 `"`），
+///   值内的 `\r` / `\n` 及字段分隔符替换为等长空格，保证每行列数与 header 一致；
+///   行尾使用自定义行结束符。
+/// - `tsv`：`clickhouse-client --format TSV` 的输入格式。字段以 `\t` 分隔、不加引号，
+///   `\\` 与控制字符按 `ClickHouse` 规则反斜杠转义，未加引号的空字段（NULL）写为 `\N`；
+///   不写 header。
+#[derive(Debug, Clone)]
+pub(crate) struct TextDialect {
+    kind: DialectKind,
+    buf: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+enum DialectKind {
+    Dmfldr {
+        field_terminator: Vec<u8>,
+        row_terminator: Vec<u8>,
+    },
+    ClickhouseTsv,
+}
+
+impl TextDialect {
+    pub(crate) fn dmfldr(field_terminator: &str, row_terminator: &str) -> Self {
+        Self::with_kind(DialectKind::Dmfldr {
+            field_terminator: field_terminator.as_bytes().to_vec(),
+            row_terminator: row_terminator.as_bytes().to_vec(),
+        })
+    }
+
+    pub(crate) fn clickhouse_tsv() -> Self {
+        Self::with_kind(DialectKind::ClickhouseTsv)
+    }
+
+    fn with_kind(kind: DialectKind) -> Self {
+        Self {
+            kind,
+            buf: Vec::with_capacity(2048),
+        }
+    }
+
+    /// 按 `dialect` 配置返回方言实例；`standard` 返回 `None`
+    pub(crate) fn from_config(config: &config::CsvExporter) -> Option<Self> {
+        match config.dialect.as_str() {
+            "dmfldr" => Some(Self::dmfldr(
+                config.field_terminator.as_deref().unwrap_or("|"),
+                config.row_terminator.as_deref().unwrap_or("\n"),
+            )),
+            "tsv" => Some(Self::clickhouse_tsv()),
+            _ => None,
+        }
+    }
+
+    /// 是否写 header 行（`clickhouse-client --format TSV` 会把首行当作数据）
+    fn writes_header(&self) -> bool {
+        matches!(self.kind, DialectKind::Dmfldr { .. })
+    }
+
+    /// 转写一行标准 CSV（以 `\n` 结尾）到 `out`
+    fn transcode_into(&self, line: &[u8], out: &mut Vec<u8>) {
+        let line = line.strip_suffix(b"\n").unwrap_or(line);
+        let mut field_start = out.len();
+        let mut quoted = false;
+        let mut in_quotes = false;
+        let mut i = 0;
+        while i < line.len() {
+            let b = line[i];
+            if in_quotes {
+                if b == b'"' {
+                    if line.get(i + 1) == Some(&b'"') {
+                        out.push(b'"');
+                        i += 1;
+                    } else {
+                        in_quotes = false;
+                    }
+                } else {
+                    self.push_byte(out, b);
+                }
+            } else if b == b'"' {
+                in_quotes = true;
+                quoted = true;
+            } else if b == b',' {
+                self.end_field(out, field_start, quoted);
+                self.push_field_terminator(out);
+                field_start = out.len();
+                quoted = false;
+            } else {
+                self.push_byte(out, b);
+            }
+            i += 1;
+        }
+        self.end_field(out, field_start, quoted);
+        match &self.kind {
+            DialectKind::Dmfldr { row_terminator, .. } => out.extend_from_slice(row_terminator),
+            DialectKind::ClickhouseTsv => out.push(b'\n'),
+        }
+    }
+
+    /// 热路径：转写到内部缓冲区并返回
+    #[inline]
+    fn transcode(&mut self, line: &[u8]) -> &[u8] {
+        let mut buf = std::mem::take(&mut self.buf);
+        buf.clear();
+        self.transcode_into(line, &mut buf);
+        self.buf = buf;
+        &self.buf
+    }
+
+    #[inline]
+    fn push_byte(&self, out: &mut Vec<u8>, b: u8) {
+        if !matches!(self.kind, DialectKind::ClickhouseTsv) {
+            out.push(b);
+            return;
+        }
+        let escaped: &[u8] = match b {
+            b'\\' => b"\\\\",
+            b'\t' => b"\\t",
+            b'\n' => b"\\n",
+            b'\r' => b"\\r",
+            b'\0' => b"\\0",
+            0x08 => b"\\b",
+            0x0c => b"\\f",
+            _ => {
+                out.push(b);
+                return;
+            }
+        };
+        out.extend_from_slice(escaped);
+    }
+
+    fn push_field_terminator(&self, out: &mut Vec<u8>) {
+        match &self.kind {
+            DialectKind::Dmfldr {
+                field_terminator, ..
+            } => out.extend_from_slice(field_terminator),
+            DialectKind::ClickhouseTsv => out.push(b'\t'),
+        }
+    }
+
+    /// 字段收尾：dmfldr 原地清洗值；TSV 下未加引号的空字段写为 `\N`
+    fn end_field(&self, out: &mut Vec<u8>, field_start: usize, quoted: bool) {
+        match &self.kind {
+            DialectKind::Dmfldr {
+                field_terminator, ..
+            } => sanitize_dmfldr(&mut out[field_start..], field_terminator),
+            DialectKind::ClickhouseTsv => {
+                if !quoted && out.len() == field_start {
+                    out.extend_from_slice(b"\\N");
+                }
+            }
+        }
+    }
+}
+
+/// 值内换行与字段分隔符原地替换为空格
+fn sanitize_dmfldr(value: &mut [u8], ft: &[u8]) {
+    let mut i = 0;
+    while i < value.len() {
+        if value[i..].starts_with(ft) {
+            value[i..i + ft.len()].fill(b' ');
+            i += ft.len();
+        } else {
+            if matches!(value[i], b'\r' | b'\n') {
+                value[i] = b' ';
+            }
+            i += 1;
+        }
+    }
+}
+
+/// 拆分导出时单个分片文件的写缓冲区大小（分片可能上百个，不使用主输出的 16 MiB）
+const SPLIT_BUFFER_CAPACITY: usize = 256 * 1024;
+
+/// 拆分导出的分片文件路径：`outputs/sqllog.csv` + `BILL_APP` → `outputs/sqllog.BILL_APP.csv`
+pub(crate) fn build_split_path(base_path: &Path, partition: &str) -> PathBuf {
+    let stem = base_path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match base_path.extension() {
+        Some(ext) => format!("{stem}.{partition}.{}", ext.to_string_lossy()),
+        None => format!("{stem}.{partition}"),
+    };
+    base_path.with_file_name(name)
+}
+
 /// 根据主 CSV 路径推导伴随文件路径（D-09）：`<stem>_templates.csv`
 pub(crate) fn build_companion_path(base_path: &Path) -> PathBuf {
     let stem = base_path.file_stem().unwrap_or_default();
@@ -62,6 +266,15 @@ fn format_companion_row(
     buf.push(b'\n');
 }
 
+/// 写入 / 刷新失败：stdout 被下游关闭时返回 `ExportError::OutputClosed`，其余包装为 `WriteFailed`
+#[cold]
+fn write_err(path: &Path, what: &str, e: &io::Error) -> Error {
+    if e.kind() == io::ErrorKind::BrokenPipe && is_stdout(path) {
+        return Error::Export(ExportError::OutputClosed);
+    }
+    io_err(path, format!("{what} failed: {e}"))
+}
+
 /// 将 I/O 错误包装为 `ExportError::WriteFailed`
 #[inline]
 fn io_err(path: &Path, reason: String) -> Error {
@@ -77,8 +290,8 @@ pub(crate) fn write_companion_rows(
     stats: &[crate::features::TemplateStats],
 ) -> Result<()> {
     ensure_parent_dir(path).map_err(|e| io_err(path, format!("create dir failed: {e}")))?;
-    let file =
-        File::create(path).map_err(|e| io_err(path, format!("create companion failed: {e}")))?;
+    let file = File::create(crate::paths::for_io(path))
+        .map_err(|e| io_err(path, format!("create companion failed: {e}")))?;
     let mut writer = BufWriter::new(file);
     writer
         .write_all(
@@ -99,12 +312,76 @@ pub(crate) fn write_companion_rows(
     Ok(())
 }
 
+/// 输出写入器：缓冲 → 可选压缩层 → 输出目标
+pub(crate) type CsvWriter = BufWriter<Encoder<Sink>>;
+
+/// 刷出缓冲并写完压缩尾部；压缩输出在切换 / 关闭文件时必须调用，否则文件末尾不完整
+fn close_writer(writer: CsvWriter, path: &Path) -> Result<()> {
+    writer
+        .into_inner()
+        .map_err(|e| write_err(path, "flush", e.error()))?
+        .finish()
+        .map_err(|e| write_err(path, "flush", &e))?;
+    Ok(())
+}
+
+/// `exporter.csv.column_order`：按列名重排输出列，返回新的 `ordered_indices`
+/// （派生列记为 `FIELD_NAMES.len() + i`）。未列出的列按原顺序接在其后；
+/// 列名须为本次输出的基础字段（`features.fields`）或派生列，且不能重复
+pub fn column_order(
+    names: &[String],
+    ordered_indices: &[usize],
+    extra_columns: &[DerivedColumnSpec],
+) -> Result<Vec<usize>> {
+    let invalid = |reason: String| {
+        Error::Config(ConfigError::InvalidValue {
+            field: "exporter.csv.column_order".to_string(),
+            value: names.join(","),
+            reason,
+        })
+    };
+    let base = crate::features::FIELD_NAMES.len();
+    let available: Vec<(usize, &str)> = ordered_indices
+        .iter()
+        .map(|&i| (i, crate::features::FIELD_NAMES[i]))
+        .chain(
+            extra_columns
+                .iter()
+                .enumerate()
+                .map(|(i, c)| (base + i, c.name.as_str())),
+        )
+        .collect();
+    let mut order = Vec::with_capacity(available.len());
+    for name in names {
+        let Some(&(idx, _)) = available.iter().find(|(_, n)| n == name) else {
+            return Err(invalid(format!(
+                "'{name}' is not an output column; available: {}",
+                available
+                    .iter()
+                    .map(|(_, n)| *n)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )));
+        };
+        if order.contains(&idx) {
+            return Err(invalid(format!("column '{name}' is listed more than once")));
+        }
+        order.push(idx);
+    }
+    for (idx, _) in available {
+        if !order.contains(&idx) {
+            order.push(idx);
+        }
+    }
+    Ok(order)
+}
+
 #[allow(clippy::struct_excessive_bools)]
 pub struct CsvExporter {
     path: PathBuf,
     overwrite: bool,
     append: bool,
-    writer: Option<BufWriter<File>>,
+    writer: Option<CsvWriter>,
     stats: ExportStats,
     itoa_buf: itoa::Buffer,
     line_buf: Vec<u8>,
@@ -114,6 +391,26 @@ pub struct CsvExporter {
     /// 是否在输出中包含性能指标列（`exec_time_ms`/`row_count`/`exec_id`）。
     /// 关闭时 header 和数据行都跳过这三列；调用方（`cli/run.rs`）也应跳过 `parse_performance_metrics()`。
     pub(crate) include_performance_metrics: bool,
+    /// `exec_time_ms` 列格式（`exporter.csv.exectime_format`）
+    pub(crate) exectime_format: ExecTimeFormat,
+    /// 追加在基础字段之后的派生列（`features.extra_fields`）
+    pub(crate) extra_columns: Vec<DerivedColumnSpec>,
+    /// `exporter.csv.column_order`：`ordered_indices` 含派生列（记为 `FIELD_NAMES.len() + i`），
+    /// 派生列按其位置穿插输出，不再统一追加在末尾
+    pub(crate) column_order: bool,
+    /// 非标准方言（`dmfldr` / `tsv`）的转写器；`None` 为标准 CSV
+    pub(crate) dialect: Option<TextDialect>,
+    /// 输出压缩（`exporter.csv.compression`）；`None` 为不压缩
+    pub(crate) compression: Option<Compression>,
+    /// 输出路径为 strftime 模式时的周期滚动调度器；`path` 随之切换为当前周期文件
+    rollover: Option<RolloverScheduler>,
+    /// 本次运行已打开过的周期文件（再次进入时追加写入，不重复写 header）
+    rolled_paths: Vec<PathBuf>,
+    /// 按大小 / 记录数分段输出（`max_file_size` / `max_records`）；`path` 为当前分段文件
+    rotation: Option<PartRotation>,
+    /// `split_by` 拆分导出：按分片名写入各自的文件，`path` 仅作为命名基准
+    pub(crate) split_by: Option<SplitBy>,
+    split_outputs: AHashMap<String, (PathBuf, CsvWriter)>,
 }
 
 impl std::fmt::Debug for CsvExporter {
@@ -142,6 +439,16 @@ impl CsvExporter {
             field_mask: crate::features::FieldMask::ALL,
             ordered_indices: (0..crate::features::FIELD_NAMES.len()).collect(),
             include_performance_metrics: true,
+            exectime_format: ExecTimeFormat::Integer,
+            extra_columns: Vec::new(),
+            column_order: false,
+            dialect: None,
+            compression: None,
+            rollover: None,
+            rolled_paths: Vec::new(),
+            rotation: None,
+            split_by: None,
+            split_outputs: AHashMap::default(),
         }
     }
 
@@ -154,10 +461,182 @@ impl CsvExporter {
             e.overwrite = config.overwrite;
         }
         e.include_performance_metrics = config.include_performance_metrics;
+        e.exectime_format = config.exectime_format();
+        e.dialect = TextDialect::from_config(config);
+        e.compression = config.compression();
+        if is_time_pattern(&config.file) {
+            e.rollover = Some(RolloverScheduler::new(config.file.clone()));
+        }
+        let (max_bytes, max_records) = config.rotation_limits();
+        if max_bytes.is_some() || max_records.is_some() {
+            e.rotation = Some(PartRotation::new(&config.file, max_bytes, max_records));
+        }
+        e.split_by = config
+            .split_by
+            .as_deref()
+            .and_then(|v| SplitBy::parse("exporter.csv.split_by", v).ok());
         e
     }
 
-    /// 热路径：使用预解析的 `MetaParts` 和 `PerformanceMetrics` 直接格式化并写入。
+    /// 按 `exporter.csv.column_order` 重排输出列；须在设置 `ordered_indices` 与 `extra_columns` 之后调用
+    pub fn apply_column_order(&mut self, names: &[String]) -> Result<()> {
+        self.ordered_indices = column_order(names, &self.ordered_indices, &self.extra_columns)?;
+        self.column_order = true;
+        Ok(())
+    }
+
+    /// 打开 `self.path` 并按需写入 header；`-` / `tcp://host:port` 为流式目标（见 [`super::sink`]）
+    fn open_writer(&self, append_mode: bool) -> Result<CsvWriter> {
+        self.open_writer_at(&self.path, append_mode, 16 * 1024 * 1024)
+    }
+
+    fn open_writer_at(&self, path: &Path, append_mode: bool, capacity: usize) -> Result<CsvWriter> {
+        if let Some(sink) = Sink::open_stream(path) {
+            let sink = sink.map_err(|e| {
+                Error::Export(ExportError::WriteFailed {
+                    path: path.to_path_buf(),
+                    reason: format!("connect failed: {e}"),
+                })
+            })?;
+            let mut writer =
+                BufWriter::with_capacity(1024 * 1024, Encoder::new(sink, self.compression));
+            self.write_header(&mut writer, path)?;
+            return Ok(writer);
+        }
+        ensure_parent_dir(path).map_err(|e| {
+            Error::Export(ExportError::WriteFailed {
+                path: path.to_path_buf(),
+                reason: format!("create dir failed: {e}"),
+            })
+        })?;
+
+        let file_exists = path.exists();
+
+        let io_path = crate::paths::for_io(path);
+        let file = if append_mode {
+            OpenOptions::new().create(true).append(true).open(&io_path)
+        } else {
+            OpenOptions::new()
+                .create(true)
+                .write(true)
+                .truncate(self.overwrite)
+                .open(&io_path)
+        }
+        .map_err(|e| {
+            Error::Export(ExportError::WriteFailed {
+                path: path.to_path_buf(),
+                reason: format!("open failed: {e}"),
+            })
+        })?;
+
+        let mut writer =
+            BufWriter::with_capacity(capacity, Encoder::new(Sink::File(file), self.compression));
+
+        if !append_mode || !file_exists {
+            self.write_header(&mut writer, path)?;
+        }
+        Ok(writer)
+    }
+
+    fn write_header(&self, writer: &mut CsvWriter, path: &Path) -> Result<()> {
+        let mut header = self.build_header();
+        if let Some(d) = &self.dialect {
+            if !d.writes_header() {
+                return Ok(());
+            }
+            let mut out = Vec::with_capacity(header.len());
+            d.transcode_into(&header, &mut out);
+            header = out;
+        }
+        writer
+            .write_all(&header)
+            .map_err(|e| write_err(path, "write header", &e))
+    }
+
+    /// 拆分导出：写入记录所属分片文件，分片首次出现时打开（追加模式沿用 `append`）
+    fn export_split(
+        &mut self,
+        by: SplitBy,
+        sqllog: &Sqllog<'_>,
+        meta: &MetaParts<'_>,
+        pm: &PerformanceMetrics<'_>,
+        normalized: Option<&str>,
+        extras: &[DerivedValue],
+    ) -> Result<()> {
+        let partition = by.partition(meta, pm.sql.as_ref());
+        if !self.split_outputs.contains_key(&partition) {
+            let path = build_split_path(&self.path, &partition);
+            let writer = self.open_writer_at(&path, self.append, SPLIT_BUFFER_CAPACITY)?;
+            info!("CSV split output opened: {}", path.display());
+            self.split_outputs.insert(partition.clone(), (path, writer));
+        }
+        let (path, writer) = self.split_outputs.get_mut(&partition).ok_or_else(|| {
+            Error::Export(ExportError::WriteFailed {
+                path: self.path.clone(),
+                reason: format!("split output for {partition} not open"),
+            })
+        })?;
+        Self::write_record_preparsed(
+            &mut self.itoa_buf,
+            &mut self.line_buf,
+            sqllog,
+            meta,
+            pm,
+            writer,
+            path,
+            self.normalize,
+            normalized,
+            self.field_mask,
+            &self.ordered_indices,
+            self.column_order,
+            self.include_performance_metrics,
+            self.exectime_format,
+            extras,
+            self.dialect.as_mut(),
+        )?;
+        self.stats.record_success();
+        Ok(())
+    }
+
+    /// 时间模式输出：记录进入新周期时 flush 当前文件并切换到新周期文件
+    #[inline]
+    fn roll_if_needed(&mut self, ts: &str) -> Result<()> {
+        if self.rotation.as_ref().is_some_and(PartRotation::is_full) {
+            return self.roll_part();
+        }
+        let Some(next) = self.rollover.as_mut().and_then(|r| r.next_path(ts)) else {
+            return Ok(());
+        };
+        if let Some(writer) = self.writer.take() {
+            close_writer(writer, &self.path)?;
+        }
+        let revisited = self.rolled_paths.contains(&next);
+        self.path = next;
+        info!("CSV output rolled over to: {}", self.path.display());
+        let writer = self.open_writer(self.append || revisited)?;
+        if !revisited {
+            self.rolled_paths.push(self.path.clone());
+        }
+        self.writer = Some(writer);
+        Ok(())
+    }
+
+    /// 分段输出：当前分段达到上限，关闭并切换到下一个分段
+    #[cold]
+    fn roll_part(&mut self) -> Result<()> {
+        let Some(next) = self.rotation.as_mut().map(PartRotation::advance) else {
+            return Ok(());
+        };
+        if let Some(writer) = self.writer.take() {
+            close_writer(writer, &self.path)?;
+        }
+        self.path = next;
+        info!("CSV output rolled over to: {}", self.path.display());
+        self.writer = Some(self.open_writer(self.append)?);
+        Ok(())
+    }
+
+    /// 热路径：使用预解析的 `MetaParts` 和 `PerformanceMetrics` 直接格式化并写入，返回写入的字节数。
     /// 接收各字段的独立可变引用，允许 Rust 同时分开借用 self 的多个字段。
     #[inline]
     pub(crate) fn write_record_preparsed(
@@ -166,14 +645,18 @@ impl CsvExporter {
         sqllog: &Sqllog<'_>,
         meta: &MetaParts<'_>,
         pm: &PerformanceMetrics<'_>,
-        writer: &mut BufWriter<File>,
+        writer: &mut CsvWriter,
         path: &Path,
         normalize: bool,
         normalized_sql: Option<&str>,
         field_mask: crate::features::FieldMask,
         ordered_indices: &[usize],
+        column_order: bool,
         include_performance_metrics: bool,
-    ) -> Result<()> {
+        exectime_format: ExecTimeFormat,
+        extras: &[DerivedValue],
+        dialect: Option<&mut TextDialect>,
+    ) -> Result<usize> {
         line_buf.clear();
         let sql_len = pm.sql.len();
         let ns_len = if normalize {
@@ -187,7 +670,7 @@ impl CsvExporter {
         }
 
         // 全量掩码快速路径：所有字段直接顺序写入，无分支判断
-        if field_mask == crate::features::FieldMask::ALL {
+        let need_sep = if field_mask == crate::features::FieldMask::ALL && !column_order {
             line_buf.extend_from_slice(sqllog.ts.as_ref().as_bytes());
             line_buf.push(b',');
             line_buf.extend_from_slice(itoa_buf.format(meta.ep).as_bytes());
@@ -216,8 +699,7 @@ impl CsvExporter {
             if include_performance_metrics {
                 line_buf.push(b',');
                 if pm.exec_id != 0 || pm.exectime > 0.0 {
-                    line_buf
-                        .extend_from_slice(itoa_buf.format(f32_ms_to_i64(pm.exectime)).as_bytes());
+                    exectime_format.write(line_buf, itoa_buf, pm.exectime);
                     line_buf.push(b',');
                     line_buf.extend_from_slice(itoa_buf.format(i64::from(pm.rowcount)).as_bytes());
                     line_buf.push(b',');
@@ -234,6 +716,7 @@ impl CsvExporter {
                     line_buf.push(b'"');
                 }
             }
+            true
         } else {
             // 投影路径：按 ordered_indices 指定的字段顺序写入
             let mut need_sep = false;
@@ -305,9 +788,7 @@ impl CsvExporter {
                         }
                         w_sep!();
                         if has_metrics {
-                            line_buf.extend_from_slice(
-                                itoa_buf.format(f32_ms_to_i64(pm.exectime)).as_bytes(),
-                            );
+                            exectime_format.write(line_buf, itoa_buf, pm.exectime);
                         }
                     }
                     12 => {
@@ -339,21 +820,38 @@ impl CsvExporter {
                             line_buf.push(b'"');
                         }
                     }
+                    // column_order 穿插的派生列；未计算派生列的兼容路径写为空
+                    15.. => {
+                        w_sep!();
+                        if let Some(v) = extras.get(idx - crate::features::FIELD_NAMES.len()) {
+                            write_derived(line_buf, itoa_buf, v);
+                        }
+                    }
                     _ => {}
                 }
             }
-            // 消费 need_sep，避免"最后一次赋值从未被读取"的编译警告
-            let _ = need_sep;
+            need_sep
+        };
+
+        if !column_order {
+            for (i, v) in extras.iter().enumerate() {
+                if need_sep || i > 0 {
+                    line_buf.push(b',');
+                }
+                write_derived(line_buf, itoa_buf, v);
+            }
         }
 
         line_buf.push(b'\n');
 
-        writer.write_all(line_buf).map_err(|e| {
-            Error::Export(ExportError::WriteFailed {
-                path: path.to_path_buf(),
-                reason: format!("write failed: {e}"),
-            })
-        })
+        let bytes: &[u8] = match dialect {
+            Some(d) => d.transcode(line_buf),
+            None => line_buf,
+        };
+        writer
+            .write_all(bytes)
+            .map_err(|e| write_err(path, "write", &e))?;
+        Ok(bytes.len())
     }
 
     /// 兼容路径：从 `Sqllog` 内部解析再转调热路径（测试/批量导出使用）。
@@ -362,14 +860,17 @@ impl CsvExporter {
         itoa_buf: &mut itoa::Buffer,
         line_buf: &mut Vec<u8>,
         sqllog: &Sqllog<'_>,
-        writer: &mut BufWriter<File>,
+        writer: &mut CsvWriter,
         path: &Path,
         normalize: bool,
         normalized_sql: Option<&str>,
         field_mask: crate::features::FieldMask,
         ordered_indices: &[usize],
+        column_order: bool,
         include_performance_metrics: bool,
-    ) -> Result<()> {
+        exectime_format: ExecTimeFormat,
+        dialect: Option<&mut TextDialect>,
+    ) -> Result<usize> {
         let meta = sqllog.parse_meta();
         let pm = if include_performance_metrics {
             sqllog.parse_performance_metrics()
@@ -393,7 +894,11 @@ impl CsvExporter {
             normalized_sql,
             field_mask,
             ordered_indices,
+            column_order,
             include_performance_metrics,
+            exectime_format,
+            &[],
+            dialect,
         )
     }
 
@@ -415,7 +920,20 @@ impl CsvExporter {
                 header.push(b',');
             }
             first = false;
-            header.extend_from_slice(FIELD_NAMES[idx].as_bytes());
+            match FIELD_NAMES.get(idx) {
+                Some(name) => header.extend_from_slice(name.as_bytes()),
+                None => header
+                    .extend_from_slice(self.extra_columns[idx - FIELD_NAMES.len()].name.as_bytes()),
+            }
+        }
+        if !self.column_order {
+            for col in &self.extra_columns {
+                if !first {
+                    header.push(b',');
+                }
+                first = false;
+                header.extend_from_slice(col.name.as_bytes());
+            }
         }
         header.push(b'\n');
         header
@@ -424,59 +942,30 @@ impl CsvExporter {
 
 impl Exporter for CsvExporter {
     fn initialize(&mut self) -> Result<()> {
-        ensure_parent_dir(&self.path).map_err(|e| {
-            Error::Export(ExportError::WriteFailed {
-                path: self.path.clone(),
-                reason: format!("create dir failed: {e}"),
-            })
-        })?;
-
-        let append_mode = self.append;
-        let file_exists = self.path.exists();
-
-        let file = if append_mode {
-            OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(&self.path)
-        } else {
-            OpenOptions::new()
-                .create(true)
-                .write(true)
-                .truncate(self.overwrite)
-                .open(&self.path)
+        // 时间模式：首条记录到达时才能确定周期文件，延迟打开
+        // 拆分导出：分片文件在首次出现时打开
+        if self.rollover.is_some() || self.split_by.is_some() {
+            return Ok(());
         }
-        .map_err(|e| {
-            Error::Export(ExportError::WriteFailed {
-                path: self.path.clone(),
-                reason: format!("open failed: {e}"),
-            })
-        })?;
-
-        let mut writer = BufWriter::with_capacity(16 * 1024 * 1024, file);
-
-        if !append_mode || !file_exists {
-            let header = self.build_header();
-            writer.write_all(&header).map_err(|e| {
-                Error::Export(ExportError::WriteFailed {
-                    path: self.path.clone(),
-                    reason: format!("write header failed: {e}"),
-                })
-            })?;
+        if let Some(r) = self.rotation.as_mut() {
+            self.path = r.first_path(self.append);
         }
-
-        self.writer = Some(writer);
+        self.writer = Some(self.open_writer(self.append)?);
         Ok(())
     }
 
     fn export(&mut self, sqllog: &Sqllog<'_>) -> Result<()> {
+        if self.split_by.is_some() {
+            return self.export_one_normalized(sqllog, None);
+        }
+        self.roll_if_needed(sqllog.ts.as_ref())?;
         let writer = self.writer.as_mut().ok_or_else(|| {
             Error::Export(ExportError::WriteFailed {
                 path: self.path.clone(),
                 reason: "not initialized".to_string(),
             })
         })?;
-        Self::write_record(
+        let written = Self::write_record(
             &mut self.itoa_buf,
             &mut self.line_buf,
             sqllog,
@@ -486,8 +975,14 @@ impl Exporter for CsvExporter {
             None,
             self.field_mask,
             &self.ordered_indices,
+            self.column_order,
             self.include_performance_metrics,
+            self.exectime_format,
+            self.dialect.as_mut(),
         )?;
+        if let Some(r) = self.rotation.as_mut() {
+            r.add(written);
+        }
         self.stats.record_success();
         Ok(())
     }
@@ -497,13 +992,19 @@ impl Exporter for CsvExporter {
         sqllog: &Sqllog<'_>,
         normalized: Option<&str>,
     ) -> Result<()> {
+        if self.split_by.is_some() {
+            let meta = sqllog.parse_meta();
+            let pm = sqllog.parse_performance_metrics();
+            return self.export_one_derived(sqllog, &meta, &pm, normalized, &[]);
+        }
+        self.roll_if_needed(sqllog.ts.as_ref())?;
         let writer = self.writer.as_mut().ok_or_else(|| {
             Error::Export(ExportError::WriteFailed {
                 path: self.path.clone(),
                 reason: "not initialized".to_string(),
             })
         })?;
-        Self::write_record(
+        let written = Self::write_record(
             &mut self.itoa_buf,
             &mut self.line_buf,
             sqllog,
@@ -513,8 +1014,14 @@ impl Exporter for CsvExporter {
             normalized,
             self.field_mask,
             &self.ordered_indices,
+            self.column_order,
             self.include_performance_metrics,
+            self.exectime_format,
+            self.dialect.as_mut(),
         )?;
+        if let Some(r) = self.rotation.as_mut() {
+            r.add(written);
+        }
         self.stats.record_success();
         Ok(())
     }
@@ -526,13 +1033,28 @@ impl Exporter for CsvExporter {
         pm: &PerformanceMetrics<'_>,
         normalized: Option<&str>,
     ) -> Result<()> {
+        self.export_one_derived(sqllog, meta, pm, normalized, &[])
+    }
+
+    fn export_one_derived(
+        &mut self,
+        sqllog: &Sqllog<'_>,
+        meta: &MetaParts<'_>,
+        pm: &PerformanceMetrics<'_>,
+        normalized: Option<&str>,
+        extras: &[DerivedValue],
+    ) -> Result<()> {
+        if let Some(by) = self.split_by {
+            return self.export_split(by, sqllog, meta, pm, normalized, extras);
+        }
+        self.roll_if_needed(sqllog.ts.as_ref())?;
         let writer = self.writer.as_mut().ok_or_else(|| {
             Error::Export(ExportError::WriteFailed {
                 path: self.path.clone(),
                 reason: "not initialized".to_string(),
             })
         })?;
-        Self::write_record_preparsed(
+        let written = Self::write_record_preparsed(
             &mut self.itoa_buf,
             &mut self.line_buf,
             sqllog,
@@ -544,24 +1066,67 @@ impl Exporter for CsvExporter {
             normalized,
             self.field_mask,
             &self.ordered_indices,
+            self.column_order,
             self.include_performance_metrics,
+            self.exectime_format,
+            extras,
+            self.dialect.as_mut(),
         )?;
+        if let Some(r) = self.rotation.as_mut() {
+            r.add(written);
+        }
         self.stats.record_success();
         Ok(())
     }
 
     fn finalize(&mut self) -> Result<()> {
-        if let Some(mut writer) = self.writer.take() {
-            writer.flush().map_err(|e| {
-                Error::Export(ExportError::WriteFailed {
-                    path: self.path.clone(),
-                    reason: format!("flush failed: {e}"),
-                })
-            })?;
+        if let Some(writer) = self.writer.take() {
+            close_writer(writer, &self.path)?;
+            if let Some(r) = self
+                .rotation
+                .as_ref()
+                .filter(|_| !self.append && self.overwrite)
+            {
+                let removed = r
+                    .remove_stale()
+                    .map_err(|e| write_err(&self.path, "remove stale part", &e))?;
+                if removed > 0 {
+                    info!("Removed {removed} stale CSV part files from a previous run");
+                }
+            }
+        }
+        if !self.split_outputs.is_empty() {
+            let count = self.split_outputs.len();
+            for (_, (path, writer)) in self.split_outputs.drain() {
+                close_writer(writer, &path)?;
+            }
+            info!("CSV split export finished: {count} partition files");
         }
         Ok(())
     }
 
+    fn flush_chunk(&mut self) -> Result<()> {
+        let started = Instant::now();
+        if let Some(writer) = self.writer.as_mut() {
+            writer
+                .flush()
+                .map_err(|e| write_err(&self.path, "flush", &e))?;
+        }
+        for (path, writer) in self.split_outputs.values_mut() {
+            writer.flush().map_err(|e| write_err(path, "flush", &e))?;
+        }
+        // 超长 SQL 撑大的行缓冲收缩回初始容量
+        self.line_buf.shrink_to(2048);
+        self.stats.record_flush(started.elapsed());
+        Ok(())
+    }
+
+    fn set_source_ts(&mut self, ts: &str) {
+        if let Some(r) = self.rollover.as_mut() {
+            r.set_source_ts(ts);
+        }
+    }
+
     fn stats_snapshot(&self) -> Option<ExportStats> {
         Some(self.stats)
     }
@@ -571,7 +1136,13 @@ impl Exporter for CsvExporter {
         stats: &[crate::features::TemplateStats],
         final_path: Option<&std::path::Path>,
     ) -> Result<()> {
-        let base_path: &Path = final_path.unwrap_or(self.path.as_path());
+        let base_path: &Path = final_path
+            .or(self.rotation.as_ref().map(PartRotation::base))
+            .unwrap_or(self.path.as_path());
+        if is_stream(base_path) {
+            warn!("Template companion CSV skipped: output is not a local file");
+            return Ok(());
+        }
         let companion = build_companion_path(base_path);
         write_companion_rows(&companion, stats)?;
         info!("Template companion CSV written: {}", companion.display());
@@ -581,7 +1152,7 @@ impl Exporter for CsvExporter {
 
 impl Drop for CsvExporter {
     fn drop(&mut self) {
-        if self.writer.is_some() {
+        if self.writer.is_some() || !self.split_outputs.is_empty() {
             let _ = self.finalize();
         }
     }
@@ -782,6 +1353,87 @@ mod tests {
         exporter.finalize().unwrap();
     }
 
+    #[test]
+    fn test_csv_flush_chunk_makes_rows_visible() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logfile = dir.path().join("test.log");
+        let outfile = dir.path().join("out.csv");
+        write_test_log(&logfile, 3);
+
+        let parser = LogParser::from_path(logfile.to_str().unwrap()).unwrap();
+        let mut exporter = CsvExporter::new(&outfile);
+        exporter.initialize().unwrap();
+        for r in parser.iter().filter_map(std::result::Result::ok) {
+            exporter.export(&r).unwrap();
+        }
+        assert_eq!(
+            std::fs::read_to_string(&outfile).unwrap().lines().count(),
+            0
+        );
+        exporter.flush_chunk().unwrap();
+        // 未 finalize 时数据已交给下游
+        assert_eq!(
+            std::fs::read_to_string(&outfile).unwrap().lines().count(),
+            4
+        );
+        // 无新写入时不计刷新次数
+        exporter.flush_chunk().unwrap();
+        let snap = exporter.stats_snapshot().unwrap();
+        assert_eq!((snap.flush_operations, snap.last_flush_size), (1, 3));
+        exporter.finalize().unwrap();
+    }
+
+    #[test]
+    fn test_csv_tcp_sink_streams_dialect_output() {
+        use std::io::Read as _;
+        let dir = tempfile::TempDir::new().unwrap();
+        let logfile = dir.path().join("test.log");
+        write_test_log(&logfile, 3);
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let reader = std::thread::spawn(move || {
+            let (mut conn, _) = listener.accept().unwrap();
+            let mut received = String::new();
+            conn.read_to_string(&mut received).unwrap();
+            received
+        });
+
+        let cfg = config::CsvExporter {
+            file: format!("tcp://{addr}"),
+            dialect: "tsv".to_string(),
+            ..config::CsvExporter::default()
+        };
+        let mut exporter = CsvExporter::from_config(&cfg);
+        exporter.initialize().unwrap();
+        let parser = LogParser::from_path(logfile.to_str().unwrap()).unwrap();
+        for r in parser.iter().filter_map(std::result::Result::ok) {
+            exporter.export(&r).unwrap();
+        }
+        exporter.finalize().unwrap();
+        // 模板伴随文件只对本地文件生成
+        exporter.write_template_stats(&[], None).unwrap();
+        drop(exporter);
+
+        let received = reader.join().unwrap();
+        // tsv 不写 header，每行一条记录
+        assert_eq!(received.lines().count(), 3);
+        assert!(received.lines().all(|l| l.contains('\t')), "{received}");
+    }
+
+    #[test]
+    fn test_write_err_broken_pipe_on_stdout_is_output_closed() {
+        let pipe = io::Error::from(io::ErrorKind::BrokenPipe);
+        assert!(matches!(
+            write_err(Path::new("-"), "write", &pipe),
+            Error::Export(ExportError::OutputClosed)
+        ));
+        assert!(matches!(
+            write_err(Path::new("out.csv"), "write", &pipe),
+            Error::Export(ExportError::WriteFailed { .. })
+        ));
+    }
+
     #[test]
     fn test_write_csv_escaped_with_quotes() {
         // write_csv_escaped handles '"' characters by doubling them
@@ -895,6 +1547,57 @@ mod tests {
         assert!(data.ends_with(",testuser"), "data line: {data}");
     }
 
+    #[test]
+    fn test_csv_column_order_interleaves_derived_columns() {
+        use crate::features::FieldMask;
+        use crate::features::derived::DerivedKind;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = dir.path().join("t.log");
+        std::fs::write(
+            &log,
+            "2025-01-15 10:30:28.001 (EP[0] sess:0x0001 user:testuser trxid:1 stmt:0x1 appname:App ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n",
+        )
+        .unwrap();
+
+        let out = dir.path().join("out.csv");
+        let mut exporter = CsvExporter::new(&out);
+        exporter.normalize = false;
+        exporter.field_mask =
+            FieldMask::from_names(&["ts".to_string(), "username".to_string(), "sql".to_string()])
+                .unwrap();
+        exporter.ordered_indices = vec![0, 4, 10];
+        exporter.extra_columns = vec![DerivedColumnSpec {
+            name: "ts_ms".into(),
+            kind: DerivedKind::Integer,
+        }];
+        exporter
+            .apply_column_order(&["username".to_string(), "ts_ms".to_string()])
+            .unwrap();
+        exporter.initialize().unwrap();
+
+        let parser = LogParser::from_path(log.to_str().unwrap()).unwrap();
+        for record in parser.iter().flatten() {
+            let meta = record.parse_meta();
+            let pm = record.parse_performance_metrics();
+            exporter
+                .export_one_derived(&record, &meta, &pm, None, &[DerivedValue::Int(42)])
+                .unwrap();
+        }
+        exporter.finalize().unwrap();
+
+        let content = std::fs::read_to_string(&out).unwrap();
+        let mut lines = content.lines();
+        assert_eq!(lines.next().unwrap(), "username,ts_ms,ts,sql");
+        let data = lines.next().unwrap();
+        assert!(data.starts_with("testuser,42,"), "data line: {data}");
+
+        let unknown = column_order(&["nope".to_string()], &[0, 4], &[]);
+        assert!(unknown.is_err());
+        let dup = column_order(&["ts".to_string(), "ts".to_string()], &[0, 4], &[]);
+        assert!(dup.is_err());
+    }
+
     #[test]
     fn test_csv_field_order_normalized_sql_skipped_when_normalize_false() {
         use crate::features::FieldMask;
@@ -1054,6 +1757,50 @@ mod tests {
         assert!(header.contains("exec_id"));
     }
 
+    #[test]
+    fn test_csv_decimal_exectime_format() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logfile = dir.path().join("test.log");
+        std::fs::write(
+            &logfile,
+            "2025-01-15 10:30:28.001 (EP[0] sess:0x1 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 0.521(ms) ROWCOUNT: 1(rows) EXEC_ID: 7.\n\
+             2025-01-15 10:30:28.002 (EP[0] sess:0x1 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 2. EXECTIME: 1234.5(ms) ROWCOUNT: 2(rows) EXEC_ID: 8.\n",
+        )
+        .unwrap();
+        let parser = LogParser::from_path(logfile.to_str().unwrap()).unwrap();
+        let records: Vec<_> = parser.iter().filter_map(std::result::Result::ok).collect();
+
+        // 全量列与投影列两条写出路径
+        for projected in [false, true] {
+            let outfile = dir.path().join(format!("out-{projected}.csv"));
+            let mut exporter = CsvExporter::new(&outfile);
+            exporter.exectime_format = ExecTimeFormat::Decimal;
+            if projected {
+                exporter.field_mask = crate::features::FieldMask(1 << 11);
+                exporter.ordered_indices = vec![11];
+            }
+            exporter.initialize().unwrap();
+            for r in &records {
+                exporter.export(r).unwrap();
+            }
+            exporter.finalize().unwrap();
+
+            let content = std::fs::read_to_string(&outfile).unwrap();
+            let values: Vec<_> = content
+                .lines()
+                .skip(1)
+                .map(|l| {
+                    if projected {
+                        l.to_string()
+                    } else {
+                        l.rsplit(',').nth(3).unwrap().to_string()
+                    }
+                })
+                .collect();
+            assert_eq!(values, ["0.521", "1234.500"], "{content}");
+        }
+    }
+
     /// TMPL-04-B：验证 `write_template_stats` 写入伴随文件，含 CSV 转义
     #[test]
     fn test_csv_write_template_stats() {
@@ -1186,4 +1933,282 @@ mod tests {
             "output_templates.csv 不应存在（应使用 final_path 推导）"
         );
     }
+
+    #[test]
+    fn test_csv_rollover_by_hour() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logfile = dir.path().join("test.log");
+        std::fs::write(
+            &logfile,
+            "2025-01-15 10:59:59.001 (EP[0] sess:0x1 user:U trxid:1 stmt:0x1 appname:App ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n\
+             2025-01-15 11:00:00.001 (EP[0] sess:0x1 user:U trxid:2 stmt:0x1 appname:App ip:10.0.0.1) [SEL] SELECT 2. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 2.\n\
+             2025-01-15 11:30:00.001 (EP[0] sess:0x1 user:U trxid:3 stmt:0x1 appname:App ip:10.0.0.1) [SEL] SELECT 3. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 3.\n",
+        )
+        .unwrap();
+        let pattern = dir.path().join("sqllog-%Y%m%d-%H.csv");
+
+        let parser = LogParser::from_path(logfile.to_str().unwrap()).unwrap();
+        let records: Vec<_> = parser.iter().filter_map(std::result::Result::ok).collect();
+        assert_eq!(records.len(), 3);
+
+        let mut exporter = CsvExporter::from_config(&crate::config::CsvExporter {
+            file: pattern.to_string_lossy().into(),
+            ..crate::config::CsvExporter::default()
+        });
+        exporter.initialize().unwrap();
+        for r in &records {
+            exporter.export_one_normalized(r, None).unwrap();
+        }
+        exporter.finalize().unwrap();
+
+        let h10 = std::fs::read_to_string(dir.path().join("sqllog-20250115-10.csv")).unwrap();
+        let h11 = std::fs::read_to_string(dir.path().join("sqllog-20250115-11.csv")).unwrap();
+        assert_eq!(h10.lines().count(), 2);
+        assert_eq!(h11.lines().count(), 3);
+        assert!(h11.starts_with("ts,ep,"));
+    }
+
+    #[test]
+    fn test_csv_rotation_by_records_and_size() {
+        use super::super::rollover::part_path;
+        let dir = tempfile::TempDir::new().unwrap();
+        let logfile = dir.path().join("test.log");
+        write_test_log(&logfile, 5);
+        let parser = LogParser::from_path(logfile.to_str().unwrap()).unwrap();
+        let records: Vec<_> = parser.iter().filter_map(std::result::Result::ok).collect();
+        let base = dir.path().join("out.csv");
+        let export = |cfg: crate::config::CsvExporter| {
+            let mut exporter = CsvExporter::from_config(&crate::config::CsvExporter {
+                file: base.to_string_lossy().into(),
+                ..cfg
+            });
+            exporter.initialize().unwrap();
+            for r in &records {
+                exporter.export_one_normalized(r, None).unwrap();
+            }
+            exporter.finalize().unwrap();
+        };
+        let data_rows = |part: u32| {
+            let content = std::fs::read_to_string(part_path(&base, part)).unwrap();
+            assert!(content.starts_with("ts,ep,"), "part {part} lacks header");
+            content.lines().count() - 1
+        };
+
+        // 每段 2 条：5 条记录写成 3 个分段，各自带 header
+        export(crate::config::CsvExporter {
+            max_records: Some(2),
+            ..Default::default()
+        });
+        assert_eq!((data_rows(1), data_rows(2), data_rows(3)), (2, 2, 1));
+        assert!(!base.exists());
+
+        // 大小上限 1 字节：每条记录一个分段；追加模式接在已有分段之后
+        export(crate::config::CsvExporter {
+            max_file_size: Some("1".into()),
+            append: true,
+            ..Default::default()
+        });
+        assert_eq!(data_rows(4), 1);
+        assert_eq!(data_rows(8), 1);
+        assert!(!part_path(&base, 9).exists());
+
+        // 覆盖写入时清理上次运行多出的旧分段
+        export(crate::config::CsvExporter {
+            max_records: Some(3),
+            ..Default::default()
+        });
+        assert_eq!((data_rows(1), data_rows(2)), (3, 2));
+        assert!(!part_path(&base, 3).exists());
+    }
+
+    #[test]
+    fn test_csv_compressed_export_and_append() {
+        use std::io::Read;
+        let dir = tempfile::TempDir::new().unwrap();
+        let logfile = dir.path().join("test.log");
+        write_test_log(&logfile, 4);
+        let parser = LogParser::from_path(logfile.to_str().unwrap()).unwrap();
+        let records: Vec<_> = parser.iter().filter_map(std::result::Result::ok).collect();
+
+        for (name, file) in [("gzip", "out.csv.gz"), ("zstd", "out.csv.zst")] {
+            let outfile = dir.path().join(file);
+            // 第二轮追加：压缩数据前后拼接，header 只写一次
+            for append in [false, true] {
+                let mut exporter = CsvExporter::from_config(&crate::config::CsvExporter {
+                    file: outfile.to_string_lossy().into(),
+                    append,
+                    compression: Some(name.to_string()),
+                    ..crate::config::CsvExporter::default()
+                });
+                exporter.initialize().unwrap();
+                for (i, r) in records.iter().enumerate() {
+                    exporter.export_one_normalized(r, None).unwrap();
+                    if i == 1 {
+                        exporter.flush_chunk().unwrap();
+                    }
+                }
+                exporter.finalize().unwrap();
+            }
+
+            let raw = std::fs::read(&outfile).unwrap();
+            let mut text = String::new();
+            if name == "gzip" {
+                flate2::read::MultiGzDecoder::new(raw.as_slice())
+                    .read_to_string(&mut text)
+                    .unwrap();
+            } else {
+                let mut src = raw.as_slice();
+                while !src.is_empty() {
+                    ruzstd::decoding::StreamingDecoder::new(&mut src)
+                        .unwrap()
+                        .read_to_string(&mut text)
+                        .unwrap();
+                }
+            }
+            assert!(text.starts_with("ts,ep,"), "{name}");
+            assert_eq!(text.matches("ts,ep,").count(), 1, "{name}");
+            assert_eq!(text.lines().count(), 9, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_dmfldr_transcode_unquotes_and_sanitizes() {
+        let d = TextDialect::dmfldr("|", "\r\n");
+        let mut out = Vec::new();
+        d.transcode_into(b"a|b,1,\"SELECT 'x|y'\nFROM \"\"T\"\"\",\n", &mut out);
+        assert_eq!(out, b"a b|1|SELECT 'x y' FROM \"T\"|\r\n");
+    }
+
+    #[test]
+    fn test_clickhouse_tsv_transcode_escapes_and_nulls() {
+        let d = TextDialect::clickhouse_tsv();
+        let mut out = Vec::new();
+        d.transcode_into(
+            b"a\\b,1,\"SELECT 'x'\tFROM \"\"T\"\"\nWHERE\r\",,\"\"\n",
+            &mut out,
+        );
+        assert_eq!(
+            out,
+            b"a\\\\b\t1\tSELECT 'x'\\tFROM \"T\"\\nWHERE\\r\t\\N\t\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_dmfldr_dialect_export() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logfile = dir.path().join("test.log");
+        std::fs::write(
+            &logfile,
+            "2025-01-15 10:30:28.001 (EP[0] sess:0x1 user:U trxid:1 stmt:0x1 appname:App ip:10.0.0.1) [SEL] SELECT a,\nb FROM t WHERE c = 'x|y'. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n",
+        )
+        .unwrap();
+        let outfile = dir.path().join("out.csv");
+        let parser = LogParser::from_path(logfile.to_str().unwrap()).unwrap();
+        let records: Vec<_> = parser.iter().filter_map(std::result::Result::ok).collect();
+
+        let mut exporter = CsvExporter::from_config(&crate::config::CsvExporter {
+            file: outfile.to_string_lossy().into(),
+            dialect: "dmfldr".into(),
+            ..crate::config::CsvExporter::default()
+        });
+        exporter.initialize().unwrap();
+        for r in &records {
+            exporter.export_one_normalized(r, None).unwrap();
+        }
+        exporter.finalize().unwrap();
+
+        let content = std::fs::read_to_string(&outfile).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2, "{content}");
+        assert!(lines[0].starts_with("ts|ep|sess_id|"));
+        let header_cols = lines[0].split('|').count();
+        assert_eq!(lines[1].split('|').count(), header_cols);
+        assert!(lines[1].contains("SELECT a, b FROM t WHERE c = 'x y'"));
+        assert!(!content.contains('"'));
+    }
+
+    #[test]
+    fn test_csv_clickhouse_tsv_dialect_export() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logfile = dir.path().join("test.log");
+        std::fs::write(
+            &logfile,
+            "2025-01-15 10:30:28.001 (EP[0] sess:0x1 user:U trxid:1 stmt:0x1 appname:App ip:10.0.0.1) [SEL] SELECT a,\n\tb FROM t WHERE c = 'x\\y'. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n",
+        )
+        .unwrap();
+        let outfile = dir.path().join("out.tsv");
+        let parser = LogParser::from_path(logfile.to_str().unwrap()).unwrap();
+        let records: Vec<_> = parser.iter().filter_map(std::result::Result::ok).collect();
+
+        let mut exporter = CsvExporter::from_config(&crate::config::CsvExporter {
+            file: outfile.to_string_lossy().into(),
+            dialect: "tsv".into(),
+            ..crate::config::CsvExporter::default()
+        });
+        exporter.initialize().unwrap();
+        for r in &records {
+            exporter.export_one_normalized(r, None).unwrap();
+        }
+        exporter.finalize().unwrap();
+
+        let content = std::fs::read_to_string(&outfile).unwrap();
+        // 无 header，每条记录一行
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 1, "{content}");
+        let cols: Vec<&str> = lines[0].split('\t').collect();
+        let header = exporter.build_header();
+        assert_eq!(cols.len(), header.split(|b| *b == b',').count());
+        assert_eq!(cols[0], "2025-01-15 10:30:28.001");
+        assert!(lines[0].contains("SELECT a,\\n\\tb FROM t WHERE c = 'x\\\\y'"));
+        assert!(cols.contains(&"\\N"), "{content}");
+        assert!(!content.contains('"'));
+    }
+
+    #[test]
+    fn test_csv_split_by_username() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logfile = dir.path().join("test.log");
+        let line = |user: &str, id: u32| {
+            format!(
+                "2025-01-15 10:30:28.001 (EP[0] sess:0x1 user:{user} trxid:{id} stmt:0x1 appname:App ip:10.0.0.1) [SEL] SELECT {id}. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: {id}.\n"
+            )
+        };
+        let log = [line("BILL_APP", 1), line("ETL", 2), line("BILL_APP", 3)].concat();
+        std::fs::write(&logfile, log).unwrap();
+        let parser = LogParser::from_path(logfile.to_str().unwrap()).unwrap();
+        let records: Vec<_> = parser.iter().filter_map(std::result::Result::ok).collect();
+
+        let outfile = dir.path().join("sqllog.csv");
+        let mut exporter = CsvExporter::from_config(&crate::config::CsvExporter {
+            file: outfile.to_string_lossy().into(),
+            split_by: Some("username".into()),
+            ..crate::config::CsvExporter::default()
+        });
+        exporter.initialize().unwrap();
+        for r in &records {
+            exporter.export(r).unwrap();
+        }
+        exporter.finalize().unwrap();
+
+        assert!(!outfile.exists());
+        let bill = std::fs::read_to_string(dir.path().join("sqllog.BILL_APP.csv")).unwrap();
+        let etl = std::fs::read_to_string(dir.path().join("sqllog.ETL.csv")).unwrap();
+        assert_eq!(bill.lines().count(), 3, "{bill}");
+        assert_eq!(etl.lines().count(), 2, "{etl}");
+        assert!(bill.starts_with("ts,ep,") && etl.starts_with("ts,ep,"));
+        assert!(!bill.contains("SELECT 2"));
+        assert_eq!(exporter.stats_snapshot().unwrap().exported, 3);
+    }
+
+    #[test]
+    fn test_build_split_path() {
+        assert_eq!(
+            build_split_path(Path::new("out/sqllog.csv"), "ETL"),
+            Path::new("out/sqllog.ETL.csv")
+        );
+        assert_eq!(
+            build_split_path(Path::new("out/sqllog"), "ETL"),
+            Path::new("out/sqllog.ETL")
+        );
+    }
 }
@@ -0,0 +1,857 @@
+//! `[exporter.elasticsearch]`：通过 `_bulk` API 批量写入 Elasticsearch / `OpenSearch`。
+//!
+//! 每条记录成为一个文档，字段名与 CSV header 一致（投影、`normalized_sql`、派生列规则相同），
+//! 另附 `@timestamp`（日志原始记录时间，ISO 8601，不带时区，不受 `[features.time]` 改写影响）供 Kibana 作为时间字段。
+//! 索引名含 strftime 说明符时按记录自身的 `ts` 决定写入哪个索引（如 `sqllog-%Y.%m.%d` 每天一个索引）。
+//! 文档攒满 `batch_size` 条、分块边界与结束时各发送一次 `_bulk` 请求；任何文档被拒绝都会中止导出。
+//! 配置 `spill_dir` 时，集群暂时不可用的批次写入磁盘队列（[`super::spill`]）稍后重放，而不是中止导出。
+use super::rollover::{RolloverScheduler, is_time_pattern};
+use super::spill::SpillQueue;
+use super::{ExportStats, Exporter, f32_ms_to_i64, strip_ip_prefix};
+use crate::error::{Error, ExportError, Result};
+use crate::features::{DerivedColumnSpec, DerivedValue, FIELD_NAMES};
+use dm_database_parser_sqllog::{MetaParts, PerformanceMetrics, Sqllog};
+use log::{info, warn};
+use std::io::Write as _;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// 报错时最多列出的被拒绝文档原因条数
+const MAX_REPORTED_FAILURES: usize = 3;
+
+/// 集群不可用后，在这段时间内新批次直接溢出到磁盘，不再逐批等待请求超时
+const SPILL_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+/// `_bulk` 请求失败的两类原因
+enum BulkError {
+    /// 集群暂时不可用（连接失败、超时、HTTP 429 / 5xx），批次可溢出后重试
+    Unavailable(String),
+    /// 认证失败、响应无法解析等，重试无意义
+    Fatal(Error),
+    /// 请求成功但有文档被拒绝（其余文档已写入），附被拒绝的文档数
+    Rejected(Error, usize),
+}
+
+pub struct ElasticsearchExporter {
+    url: String,
+    index: String,
+    batch_size: usize,
+    username: Option<String>,
+    password: Option<String>,
+    api_key: Option<String>,
+    timeout: Duration,
+    pub(super) normalize: bool,
+    pub(super) ordered_indices: Vec<usize>,
+    /// 追加在基础字段之后的派生列（`features.extra_fields`）
+    pub(super) extra_columns: Vec<DerivedColumnSpec>,
+    client: Option<reqwest::blocking::Client>,
+    /// 索引名为时间模式时按记录时间切换索引
+    scheduler: Option<RolloverScheduler>,
+    /// `[features.time]` 改写 `ts` 时下一条记录的原始日志时间，用于生成 `@timestamp`
+    source_ts: String,
+    has_source_ts: bool,
+    /// 溢出队列目录与大小上限（`spill_dir` / `spill_max_mb`）
+    spill_config: Option<(PathBuf, u64)>,
+    spill: Option<SpillQueue>,
+    /// 集群不可用时，下次尝试发送的时间
+    retry_at: Option<Instant>,
+    /// 本次运行溢出到磁盘的文档数
+    spilled: u64,
+    /// 本次运行重放时被拒绝、移出队列的批次数
+    spill_rejected: usize,
+    /// 当前索引对应的 action 行（`{"index":{"_index":"..."}}\n`）
+    action: Vec<u8>,
+    /// 待发送的 NDJSON 请求体
+    body: Vec<u8>,
+    pending: usize,
+    stats: ExportStats,
+}
+
+impl std::fmt::Debug for ElasticsearchExporter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ElasticsearchExporter")
+            .field("url", &self.url)
+            .field("index", &self.index)
+            .field("batch_size", &self.batch_size)
+            .field("pending", &self.pending)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ElasticsearchExporter {
+    #[must_use]
+    pub fn from_config(config: &crate::config::ElasticsearchExporter) -> Self {
+        let scheduler =
+            is_time_pattern(&config.index).then(|| RolloverScheduler::new(&config.index));
+        let mut exporter = Self {
+            url: config.url.trim_end_matches('/').to_string(),
+            index: config.index.clone(),
+            batch_size: config.batch_size,
+            username: config.username.clone(),
+            password: config.password(),
+            api_key: config.api_key.clone(),
+            timeout: Duration::from_secs(config.timeout_secs),
+            normalize: true,
+            ordered_indices: (0..FIELD_NAMES.len()).collect(),
+            extra_columns: Vec::new(),
+            client: None,
+            scheduler,
+            source_ts: String::new(),
+            has_source_ts: false,
+            spill_config: config.spill_dir.as_ref().map(|d| {
+                (
+                    PathBuf::from(d),
+                    config.spill_max_mb.saturating_mul(1024 * 1024),
+                )
+            }),
+            spill: None,
+            retry_at: None,
+            spilled: 0,
+            spill_rejected: 0,
+            action: Vec::new(),
+            body: Vec::new(),
+            pending: 0,
+            stats: ExportStats::new(),
+        };
+        if exporter.scheduler.is_none() {
+            exporter.set_index(&config.index);
+        }
+        exporter
+    }
+
+    fn bulk_err(&self, reason: impl Into<String>) -> Error {
+        Error::Export(ExportError::BulkFailed {
+            url: self.url.clone(),
+            reason: reason.into(),
+        })
+    }
+
+    fn set_index(&mut self, index: &str) {
+        self.action.clear();
+        self.action.extend_from_slice(b"{\"index\":{\"_index\":");
+        // 字符串序列化到 Vec 不会失败
+        let _ = serde_json::to_writer(&mut self.action, index);
+        self.action.extend_from_slice(b"}}\n");
+    }
+
+    fn request(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+    ) -> Result<reqwest::blocking::RequestBuilder> {
+        let client = self
+            .client
+            .as_ref()
+            .ok_or_else(|| self.bulk_err("not initialized"))?;
+        let req = client.request(method, format!("{}{path}", self.url));
+        Ok(match (&self.api_key, &self.username) {
+            (Some(key), _) => req.header(reqwest::header::AUTHORIZATION, format!("ApiKey {key}")),
+            (None, Some(user)) => req.basic_auth(user, self.password.as_deref()),
+            (None, None) => req,
+        })
+    }
+
+    fn connect(&mut self) -> Result<()> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(self.timeout)
+            .build()
+            .map_err(|e| self.bulk_err(format!("create HTTP client failed: {e}")))?;
+        self.client = Some(client);
+        Ok(())
+    }
+
+    /// 请求集群根路径，返回发行版与版本号
+    fn check_cluster(&self) -> Result<String> {
+        let info: serde_json::Value = self
+            .request(reqwest::Method::GET, "/")?
+            .send()
+            .and_then(reqwest::blocking::Response::error_for_status)
+            .and_then(reqwest::blocking::Response::json)
+            .map_err(|e| self.bulk_err(format!("connection check failed: {e}")))?;
+        Ok(format!(
+            "{} {}",
+            info["version"]["distribution"]
+                .as_str()
+                .unwrap_or("elasticsearch"),
+            info["version"]["number"].as_str().unwrap_or("unknown")
+        ))
+    }
+
+    /// `validate --check-connectivity`：只确认集群可达、认证有效，不打开溢出队列、不写入文档
+    pub fn probe(&mut self) -> Result<String> {
+        self.connect()?;
+        self.check_cluster()
+    }
+
+    /// 实际输出的基础字段索引（与 CSV header 规则一致）
+    fn field_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.ordered_indices
+            .iter()
+            .copied()
+            .filter(|&i| i != 14 || self.normalize)
+    }
+
+    /// 把一条记录序列化为 action 行 + 文档行，追加到请求体
+    fn append_document(
+        &mut self,
+        sqllog: &Sqllog<'_>,
+        meta: &MetaParts<'_>,
+        pm: &PerformanceMetrics<'_>,
+        normalized: Option<&str>,
+        extras: &[DerivedValue],
+    ) {
+        let ts = sqllog.ts.as_ref();
+        if let Some(index) = self.scheduler.as_mut().and_then(|s| s.next_path(ts)) {
+            self.set_index(&index.to_string_lossy());
+        }
+        // `ts` 可能已被改写成毫秒时间戳或自定义格式，`@timestamp` 始终取原始日志时间
+        let source = if std::mem::take(&mut self.has_source_ts) {
+            self.source_ts.as_str()
+        } else {
+            ts
+        };
+        let timestamp = source.replacen(' ', "T", 1);
+        let has_metrics = pm.exec_id != 0 || pm.exectime > 0.0;
+        let mut doc = std::mem::take(&mut self.body);
+        doc.extend_from_slice(&self.action);
+        doc.extend_from_slice(b"{\"@timestamp\":");
+        let _ = serde_json::to_writer(&mut doc, &timestamp);
+        for i in self.field_indices() {
+            let _ = write!(doc, ",\"{}\":", FIELD_NAMES[i]);
+            let text = match i {
+                0 => Some(ts),
+                2 => Some(meta.sess_id.as_ref()),
+                3 => Some(meta.thrd_id.as_ref()),
+                4 => Some(meta.username.as_ref()),
+                5 => Some(meta.trxid.as_ref()),
+                6 => Some(meta.statement.as_ref()),
+                7 => Some(meta.appname.as_ref()),
+                8 => Some(strip_ip_prefix(meta.client_ip.as_ref())),
+                9 => sqllog.tag.as_deref(),
+                10 => Some(pm.sql.as_ref()),
+                14 => normalized,
+                _ => None,
+            };
+            let _ = match i {
+                1 => write!(doc, "{}", meta.ep),
+                11 if has_metrics => write!(doc, "{}", f32_ms_to_i64(pm.exectime)),
+                12 if has_metrics => write!(doc, "{}", pm.rowcount),
+                13 if has_metrics => write!(doc, "{}", pm.exec_id),
+                _ => match text {
+                    Some(t) => serde_json::to_writer(&mut doc, t).map_err(std::io::Error::other),
+                    None => doc.write_all(b"null"),
+                },
+            };
+        }
+        for (spec, value) in self.extra_columns.iter().zip(extras) {
+            doc.push(b',');
+            let _ = serde_json::to_writer(&mut doc, &spec.name);
+            doc.push(b':');
+            let _ = match value {
+                DerivedValue::Null => doc.write_all(b"null"),
+                DerivedValue::Int(n) => write!(doc, "{n}"),
+                DerivedValue::Text(t) => {
+                    serde_json::to_writer(&mut doc, t.as_ref()).map_err(std::io::Error::other)
+                }
+            };
+        }
+        doc.extend_from_slice(b"}\n");
+        self.body = doc;
+        self.pending += 1;
+    }
+
+    /// 发送一个 `_bulk` 请求体（含 `docs` 个文档）
+    fn post_bulk(&self, body: Vec<u8>, docs: usize) -> std::result::Result<(), BulkError> {
+        let response = self
+            .request(reqwest::Method::POST, "/_bulk")
+            .map_err(BulkError::Fatal)?
+            .header(reqwest::header::CONTENT_TYPE, "application/x-ndjson")
+            .body(body)
+            .send()
+            .map_err(|e| BulkError::Unavailable(e.to_string()))?;
+        let status = response.status();
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+            return Err(BulkError::Unavailable(format!("HTTP {status}")));
+        }
+        let result: serde_json::Value = response
+            .error_for_status()
+            .map_err(|e| BulkError::Fatal(self.bulk_err(e.to_string())))?
+            .json()
+            .map_err(|e| BulkError::Fatal(self.bulk_err(format!("invalid _bulk response: {e}"))))?;
+        if result["errors"].as_bool() == Some(true) {
+            let (rejected, reason) = bulk_failures(&result, docs);
+            return Err(BulkError::Rejected(self.bulk_err(reason), rejected));
+        }
+        Ok(())
+    }
+
+    /// 发送已攒下的文档；有文档被拒绝时返回前几条拒绝原因。
+    /// 启用溢出队列时，集群不可用或仍有积压的批次写入队列
+    fn send_bulk(&mut self) -> Result<()> {
+        if self.pending == 0 {
+            return Ok(());
+        }
+        let body = std::mem::take(&mut self.body);
+        let docs = std::mem::take(&mut self.pending);
+        let started = Instant::now();
+        if self.spill.is_none() {
+            return match self.post_bulk(body, docs) {
+                Ok(()) => {
+                    self.stats.record_flush(started.elapsed());
+                    Ok(())
+                }
+                Err(e) => Err(self.bulk_failed(e, docs, started)),
+            };
+        }
+        // 先清空积压，保持批次顺序
+        let drained = self
+            .drain_spill()
+            .inspect_err(|_| self.stats.record_skipped(docs))?;
+        if drained {
+            let started = Instant::now();
+            match self.post_bulk(body.clone(), docs) {
+                Ok(()) => {
+                    self.stats.record_flush(started.elapsed());
+                    return Ok(());
+                }
+                Err(BulkError::Unavailable(reason)) => self.mark_unavailable(&reason),
+                Err(e) => return Err(self.bulk_failed(e, docs, started)),
+            }
+        }
+        self.spill_batch(&body, docs)
+            .inspect_err(|_| self.stats.record_skipped(docs))
+    }
+
+    /// 批次未能写入：被拒绝的文档（或整批）从导出计数改计为跳过，是否中止由 `on_error` 决定
+    fn bulk_failed(&mut self, e: BulkError, docs: usize, started: Instant) -> Error {
+        match e {
+            BulkError::Rejected(e, rejected) => {
+                self.stats.record_flush(started.elapsed());
+                self.stats.record_skipped(rejected);
+                e
+            }
+            BulkError::Fatal(e) => {
+                self.stats.record_skipped(docs);
+                e
+            }
+            BulkError::Unavailable(reason) => {
+                self.stats.record_skipped(docs);
+                self.bulk_err(reason)
+            }
+        }
+    }
+
+    fn mark_unavailable(&mut self, reason: &str) {
+        if self.retry_at.is_none() {
+            warn!(
+                "Elasticsearch {} unavailable ({reason}); spilling batches to disk",
+                self.url
+            );
+        }
+        self.retry_at = Some(Instant::now() + SPILL_RETRY_AFTER);
+    }
+
+    /// 按顺序重放积压的批次；返回队列是否已清空（集群可用）
+    fn drain_spill(&mut self) -> Result<bool> {
+        if self.retry_at.is_some_and(|t| Instant::now() < t) {
+            return Ok(false);
+        }
+        let Some(queue) = &self.spill else {
+            return Ok(true);
+        };
+        let files = queue.pending().map_err(|e| self.spill_err(&e))?;
+        for file in files {
+            let body = std::fs::read(&file).map_err(|e| self.spill_err(&e))?;
+            let docs = memchr::memchr_iter(b'\n', &body).count() / 2;
+            match self.post_bulk(body, docs) {
+                Ok(()) => {}
+                Err(BulkError::Unavailable(reason)) => {
+                    self.mark_unavailable(&reason);
+                    return Ok(false);
+                }
+                // 批次已部分写入（文档无 `_id`，重放会产生重复）或无法写入：移出队列，继续补发其余批次
+                Err(BulkError::Rejected(e, rejected)) => {
+                    self.reject_spilled(&file, &e, rejected, docs)?;
+                    continue;
+                }
+                // 认证失败等错误同样会拒绝之后的批次：只移出当前批次，不再继续重放
+                Err(BulkError::Fatal(e)) => {
+                    self.reject_spilled(&file, &e, docs, docs)?;
+                    return Err(e);
+                }
+            }
+            if let Some(queue) = self.spill.as_mut() {
+                queue.remove(&file).map_err(|e| self.spill_err(&e))?;
+            }
+            info!("Resent {docs} spilled documents from {}", file.display());
+        }
+        if self.retry_at.take().is_some() {
+            info!("Elasticsearch {} available again", self.url);
+        }
+        Ok(true)
+    }
+
+    /// 重放被拒绝的批次改名移出队列（`*.spill.rejected`），保留供人工排查
+    fn reject_spilled(
+        &mut self,
+        file: &std::path::Path,
+        e: &Error,
+        rejected: usize,
+        docs: usize,
+    ) -> Result<()> {
+        let Some(queue) = self.spill.as_mut() else {
+            return Ok(());
+        };
+        let moved = queue.reject(file).map_err(|e| self.spill_err(&e))?;
+        self.spill_rejected += 1;
+        warn!(
+            "Spilled batch {} not resent ({rejected} of {docs} documents refused), moved to {}: {e}",
+            file.display(),
+            moved.display()
+        );
+        Ok(())
+    }
+
+    /// 把批次写入溢出队列；队列已满时按集群不可用报错
+    fn spill_batch(&mut self, body: &[u8], docs: usize) -> Result<()> {
+        let Some(queue) = self.spill.as_mut() else {
+            return Ok(());
+        };
+        let pushed = queue.push(body).map_err(|e| self.spill_err(&e))?;
+        if !pushed {
+            return Err(self.bulk_err(format!(
+                "cluster unavailable and spill queue {} is full (spill_max_mb)",
+                self.spill_config
+                    .as_ref()
+                    .map_or_else(String::new, |(d, _)| d.display().to_string())
+            )));
+        }
+        self.spilled += docs as u64;
+        Ok(())
+    }
+
+    fn spill_err(&self, e: &std::io::Error) -> Error {
+        Error::Export(ExportError::WriteFailed {
+            path: self
+                .spill_config
+                .as_ref()
+                .map(|(d, _)| d.clone())
+                .unwrap_or_default(),
+            reason: e.to_string(),
+        })
+    }
+}
+
+/// 汇总 `_bulk` 响应中被拒绝的文档：返回被拒绝数与 `N of M documents rejected: <type>: <reason>; ...`
+fn bulk_failures(result: &serde_json::Value, sent: usize) -> (usize, String) {
+    let failures: Vec<String> = result["items"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|item| {
+            let error = item.as_object()?.values().next()?.get("error")?;
+            Some(format!(
+                "{}: {}",
+                error["type"].as_str().unwrap_or("unknown"),
+                error["reason"].as_str().unwrap_or("")
+            ))
+        })
+        .collect();
+    let shown: Vec<&str> = failures
+        .iter()
+        .take(MAX_REPORTED_FAILURES)
+        .map(String::as_str)
+        .collect();
+    let reason = format!(
+        "{} of {sent} documents rejected: {}",
+        failures.len(),
+        shown.join("; ")
+    );
+    (failures.len(), reason)
+}
+
+impl Exporter for ElasticsearchExporter {
+    fn initialize(&mut self) -> Result<()> {
+        info!(
+            "Initializing Elasticsearch exporter: {} / {}",
+            self.url, self.index
+        );
+        self.connect()?;
+        if let Some((dir, max_bytes)) = &self.spill_config {
+            let queue = SpillQueue::open(dir, *max_bytes).map_err(|e| self.spill_err(&e))?;
+            self.spill = Some(queue);
+        }
+
+        // 连接预热：确认集群可达、认证有效，并把版本写入应用日志
+        match self.check_cluster() {
+            Ok(target) => info!("Elasticsearch target: {target}"),
+            // 启用溢出队列时允许集群暂时不可用：先写入队列，稍后重放
+            Err(e) if self.spill.is_some() => {
+                self.mark_unavailable(&e.to_string());
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        }
+        // 补发上次运行遗留的批次
+        self.drain_spill()?;
+        Ok(())
+    }
+
+    fn export(&mut self, sqllog: &Sqllog<'_>) -> Result<()> {
+        let meta = sqllog.parse_meta();
+        let pm = sqllog.parse_performance_metrics();
+        self.export_one_derived(sqllog, &meta, &pm, None, &[])
+    }
+
+    fn export_one_preparsed(
+        &mut self,
+        sqllog: &Sqllog<'_>,
+        meta: &MetaParts<'_>,
+        pm: &PerformanceMetrics<'_>,
+        normalized: Option<&str>,
+    ) -> Result<()> {
+        self.export_one_derived(sqllog, meta, pm, normalized, &[])
+    }
+
+    fn export_one_derived(
+        &mut self,
+        sqllog: &Sqllog<'_>,
+        meta: &MetaParts<'_>,
+        pm: &PerformanceMetrics<'_>,
+        normalized: Option<&str>,
+        extras: &[DerivedValue],
+    ) -> Result<()> {
+        self.append_document(sqllog, meta, pm, normalized, extras);
+        self.stats.record_success();
+        if self.pending >= self.batch_size {
+            self.send_bulk()?;
+        }
+        Ok(())
+    }
+
+    fn flush_chunk(&mut self) -> Result<()> {
+        self.send_bulk()
+    }
+
+    fn set_source_ts(&mut self, ts: &str) {
+        self.source_ts.clear();
+        self.source_ts.push_str(ts);
+        self.has_source_ts = true;
+        if let Some(s) = self.scheduler.as_mut() {
+            s.set_source_ts(ts);
+        }
+    }
+
+    fn discard_batch(&mut self) -> Result<usize> {
+        let docs = std::mem::take(&mut self.pending);
+        self.body.clear();
+        self.stats.record_skipped(docs);
+        Ok(docs)
+    }
+
+    fn finalize(&mut self) -> Result<()> {
+        self.send_bulk()?;
+        info!(
+            "Elasticsearch export finished: {} documents in {} bulk requests",
+            self.stats.exported, self.stats.flush_operations
+        );
+        if let Some(queue) = &self.spill {
+            if self.spill_rejected > 0 {
+                warn!(
+                    "{} spilled batches were refused by Elasticsearch and moved aside as *.spill.rejected in {}",
+                    self.spill_rejected,
+                    queue.dir().display()
+                );
+            }
+            let waiting = queue.pending().map_err(|e| self.spill_err(&e))?.len();
+            if waiting > 0 {
+                warn!(
+                    "{} documents spilled this run; {waiting} batches ({} bytes) wait in {} and are resent on the next run",
+                    self.spilled,
+                    queue.bytes(),
+                    queue.dir().display()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn stats_snapshot(&self) -> Option<ExportStats> {
+        Some(self.stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    type Requests = Arc<Mutex<Vec<(String, String)>>>;
+
+    /// 极简 HTTP 服务：记录 `(请求行, 请求体)`，`GET /` 返回集群信息，`_bulk` 返回 `bulk_response`
+    fn mock_server(bulk_response: &'static str) -> (String, Requests) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests: Requests = Arc::default();
+        let seen = Arc::clone(&requests);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { break };
+                let seen = Arc::clone(&seen);
+                std::thread::spawn(move || {
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut out = stream;
+                    loop {
+                        let mut line = String::new();
+                        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                            return;
+                        }
+                        let request_line = line.trim_end().to_string();
+                        let mut len = 0;
+                        loop {
+                            line.clear();
+                            reader.read_line(&mut line).unwrap();
+                            if line.trim_end().is_empty() {
+                                break;
+                            }
+                            if let Some((k, v)) = line.split_once(':') {
+                                if k.eq_ignore_ascii_case("content-length") {
+                                    len = v.trim().parse().unwrap();
+                                }
+                            }
+                        }
+                        let mut body = vec![0; len];
+                        reader.read_exact(&mut body).unwrap();
+                        let response = if request_line.starts_with("GET / ") {
+                            r#"{"version":{"number":"8.13.0"}}"#
+                        } else {
+                            bulk_response
+                        };
+                        seen.lock()
+                            .unwrap()
+                            .push((request_line, String::from_utf8(body).unwrap()));
+                        write!(
+                            out,
+                            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{response}",
+                            response.len()
+                        )
+                        .unwrap();
+                    }
+                });
+            }
+        });
+        (url, requests)
+    }
+
+    /// 写入跨越午夜的三条记录
+    fn write_log(dir: &std::path::Path) -> dm_database_parser_sqllog::LogParser {
+        let log = dir.join("test.log");
+        let mut buf = String::new();
+        for ts in [
+            "2025-01-15 23:59:59.000",
+            "2025-01-16 00:00:01.000",
+            "2025-01-16 00:00:02.000",
+        ] {
+            buf.push_str(ts);
+            buf.push_str(" (EP[1] sess:0x1 user:SYSDBA trxid:9 stmt:0x1 appname:App ip:::ffff:10.0.0.1) [SEL] SELECT \"x\". EXECTIME: 12.5(ms) ROWCOUNT: 3(rows) EXEC_ID: 7.\n");
+        }
+        std::fs::write(&log, buf).unwrap();
+        dm_database_parser_sqllog::LogParser::from_path(log.to_str().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_bulk_index_per_day() {
+        let (url, requests) = mock_server(r#"{"errors":false,"items":[]}"#);
+        let cfg = crate::config::ElasticsearchExporter {
+            url: format!("{url}/"),
+            index: "sqllog-%Y.%m.%d".to_string(),
+            batch_size: 2,
+            ..Default::default()
+        };
+        let mut exporter = ElasticsearchExporter::from_config(&cfg);
+        exporter.initialize().unwrap();
+        let dir = tempfile::TempDir::new().unwrap();
+        let parser = write_log(dir.path());
+        for r in parser.iter().filter_map(std::result::Result::ok) {
+            exporter.export(&r).unwrap();
+        }
+        exporter.finalize().unwrap();
+        assert_eq!(exporter.stats_snapshot().unwrap().flush_operations, 2);
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 3, "{requests:?}");
+        assert!(requests[1].0.starts_with("POST /_bulk "), "{requests:?}");
+        let lines: Vec<serde_json::Value> = requests[1]
+            .1
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0]["index"]["_index"], "sqllog-2025.01.15");
+        assert_eq!(lines[2]["index"]["_index"], "sqllog-2025.01.16");
+        let doc = &lines[1];
+        assert_eq!(doc["@timestamp"], "2025-01-15T23:59:59.000");
+        assert_eq!(doc["ep"], 1);
+        assert_eq!(doc["client_ip"], "10.0.0.1");
+        assert!(doc["sql"].as_str().unwrap().starts_with("SELECT \"x\""));
+        assert_eq!(doc["exec_time_ms"], 12);
+        assert_eq!(doc["tag"], "SEL");
+        assert!(requests[2].1.ends_with("}\n"));
+    }
+
+    #[test]
+    fn test_timestamp_uses_source_ts() {
+        let (url, requests) = mock_server(r#"{"errors":false,"items":[]}"#);
+        let cfg = crate::config::ElasticsearchExporter {
+            url,
+            ..Default::default()
+        };
+        let mut exporter = ElasticsearchExporter::from_config(&cfg);
+        exporter.initialize().unwrap();
+        let dir = tempfile::TempDir::new().unwrap();
+        let parser = write_log(dir.path());
+        let record = parser.iter().find_map(std::result::Result::ok).unwrap();
+        // 模拟 `[features.time] output_format = "epoch_ms"` 改写后的记录
+        let mut shifted = record.clone();
+        shifted.ts = std::borrow::Cow::Borrowed("1736985599000");
+        exporter.set_source_ts(record.ts.as_ref());
+        exporter.export(&shifted).unwrap();
+        exporter.export(&record).unwrap();
+        exporter.finalize().unwrap();
+
+        let requests = requests.lock().unwrap();
+        let lines: Vec<serde_json::Value> = requests[1]
+            .1
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines[1]["@timestamp"], "2025-01-15T23:59:59.000");
+        assert_eq!(lines[1]["ts"], "1736985599000");
+        // 原始时间只作用于下一条记录
+        assert_eq!(lines[3]["@timestamp"], "2025-01-15T23:59:59.000");
+        assert_eq!(lines[3]["ts"], "2025-01-15 23:59:59.000");
+    }
+
+    #[test]
+    fn test_bulk_rejected_documents() {
+        let (url, _) = mock_server(
+            r#"{"errors":true,"items":[{"index":{"status":400,"error":{"type":"mapper_parsing_exception","reason":"failed to parse field [ep]"}}}]}"#,
+        );
+        let cfg = crate::config::ElasticsearchExporter {
+            url,
+            ..Default::default()
+        };
+        let mut exporter = ElasticsearchExporter::from_config(&cfg);
+        exporter.initialize().unwrap();
+        let dir = tempfile::TempDir::new().unwrap();
+        let parser = write_log(dir.path());
+        let record = parser.iter().next().unwrap().unwrap();
+        exporter.export(&record).unwrap();
+        let err = exporter.finalize().unwrap_err().to_string();
+        assert!(err.contains("1 of 1 documents rejected"), "{err}");
+        assert!(err.contains("mapper_parsing_exception"), "{err}");
+    }
+
+    #[test]
+    fn test_spill_when_unavailable_and_resend_on_startup() {
+        // 绑定后立即释放的端口：连接被拒绝
+        let down = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}", listener.local_addr().unwrap())
+        };
+        let dir = tempfile::TempDir::new().unwrap();
+        let spill = dir.path().join("spill");
+        let mut cfg = crate::config::ElasticsearchExporter {
+            url: down,
+            batch_size: 2,
+            spill_dir: Some(spill.to_string_lossy().into_owned()),
+            ..Default::default()
+        };
+        let mut exporter = ElasticsearchExporter::from_config(&cfg);
+        exporter.initialize().unwrap();
+        let parser = write_log(dir.path());
+        for r in parser.iter().filter_map(std::result::Result::ok) {
+            exporter.export(&r).unwrap();
+        }
+        exporter.finalize().unwrap();
+        assert_eq!(exporter.spilled, 3);
+        assert_eq!(std::fs::read_dir(&spill).unwrap().count(), 2);
+
+        let (url, requests) = mock_server(r#"{"errors":false,"items":[]}"#);
+        cfg.url = url;
+        let mut exporter = ElasticsearchExporter::from_config(&cfg);
+        exporter.initialize().unwrap();
+        exporter.finalize().unwrap();
+        assert_eq!(std::fs::read_dir(&spill).unwrap().count(), 0);
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 3, "{requests:?}");
+        assert_eq!(requests[1].1.lines().count(), 4);
+        assert_eq!(requests[2].1.lines().count(), 2);
+    }
+
+    /// 重放的批次被拒绝（HTTP 200 + `errors: true`）：移出队列继续补发，之后的运行不再卡在同一批次上
+    #[test]
+    fn test_spill_rejected_batch_moved_aside() {
+        let down = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}", listener.local_addr().unwrap())
+        };
+        let dir = tempfile::TempDir::new().unwrap();
+        let spill = dir.path().join("spill");
+        let mut cfg = crate::config::ElasticsearchExporter {
+            url: down,
+            batch_size: 2,
+            spill_dir: Some(spill.to_string_lossy().into_owned()),
+            ..Default::default()
+        };
+        let mut exporter = ElasticsearchExporter::from_config(&cfg);
+        exporter.initialize().unwrap();
+        let parser = write_log(dir.path());
+        for r in parser.iter().filter_map(std::result::Result::ok) {
+            exporter.export(&r).unwrap();
+        }
+        exporter.finalize().unwrap();
+
+        let (url, requests) = mock_server(
+            r#"{"errors":true,"items":[{"index":{"status":400,"error":{"type":"mapper_parsing_exception","reason":"failed to parse field [ep]"}}}]}"#,
+        );
+        cfg.url = url;
+        let mut exporter = ElasticsearchExporter::from_config(&cfg);
+        exporter.initialize().unwrap();
+        exporter.finalize().unwrap();
+        assert_eq!(exporter.spill_rejected, 2);
+        let mut names: Vec<_> = std::fs::read_dir(&spill)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names.len(), 2);
+        assert!(
+            names.iter().all(|n| n.ends_with(".spill.rejected")),
+            "{names:?}"
+        );
+        assert_eq!(requests.lock().unwrap().len(), 3);
+
+        // 下一次运行不再重放已移出的批次
+        let mut exporter = ElasticsearchExporter::from_config(&cfg);
+        exporter.initialize().unwrap();
+        exporter.finalize().unwrap();
+        assert_eq!(requests.lock().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_index_name_validation() {
+        let mut cfg = crate::config::ElasticsearchExporter::default();
+        assert!(cfg.validate().is_ok());
+        for bad in ["SqlLog", "sqllog-%D", "_sqllog", "a b"] {
+            cfg.index = bad.to_string();
+            assert!(cfg.validate().is_err(), "{bad}");
+        }
+        cfg.index = "sqllog-%Y.%m.%d".to_string();
+        assert!(cfg.validate().is_ok());
+        cfg.url = "localhost:9200".to_string();
+        assert!(cfg.validate().is_err());
+    }
+}
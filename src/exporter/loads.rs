@@ -0,0 +1,169 @@
+//! 装载记录（`exporter.sqlite.load_metadata`）：每次运行结束后在目标库的 `sqllog2db_loads` 表写入一行，
+//! 记录来源文件（路径、字节数、SHA-256）、记录数、错误数、工具版本与耗时。
+//!
+//! 数仓侧审核可直接查询目标库，无需访问导出主机上的校验清单。`checksum` 为按 `sha256sum`
+//! 输出格式（`<hex>  <文件名>`，每个文件一行）拼接后的 SHA-256，可在归档目录上复算比对。
+use crate::error::{Error, ParserError, Result};
+use crate::metrics::MetricsSnapshot;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 一个来源文件；标准输入无法复读，不计算字节数与摘要
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct SourceFile {
+    pub path: String,
+    pub bytes: Option<u64>,
+    pub sha256: Option<String>,
+}
+
+/// 一次运行的装载记录
+#[derive(Debug, Clone)]
+pub struct LoadRecord {
+    pub run_id: String,
+    pub tool_version: &'static str,
+    pub files: Vec<SourceFile>,
+    pub records: u64,
+    pub errors: u64,
+    pub duration: Duration,
+    /// 是否完整处理了全部输入（中断 / 时限 / 记录上限时为 false）
+    pub complete: bool,
+}
+
+impl LoadRecord {
+    /// 读取并校验本次处理的来源文件，汇总为装载记录
+    pub fn collect<'a>(
+        run_id: &str,
+        files: impl IntoIterator<Item = &'a PathBuf>,
+        metrics: &MetricsSnapshot,
+        duration: Duration,
+        complete: bool,
+    ) -> Result<Self> {
+        let files = files
+            .into_iter()
+            .map(|p| source_file(p))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            run_id: run_id.to_string(),
+            tool_version: env!("CARGO_PKG_VERSION"),
+            files,
+            records: metrics.exported,
+            errors: metrics.errors,
+            duration,
+            complete,
+        })
+    }
+
+    /// `complete` / `partial`
+    #[must_use]
+    pub fn status(&self) -> &'static str {
+        if self.complete { "complete" } else { "partial" }
+    }
+
+    /// 来源文件列表（JSON 数组）
+    #[must_use]
+    pub fn files_json(&self) -> String {
+        serde_json::to_string(&self.files).unwrap_or_else(|_| "[]".to_string())
+    }
+
+    /// 各文件摘要按 `sha256sum` 输出格式拼接后的 SHA-256；含无法摘要的文件（标准输入）时为 `None`
+    #[must_use]
+    pub fn checksum(&self) -> Option<String> {
+        let mut sums = String::new();
+        for f in &self.files {
+            let name = Path::new(&f.path)
+                .file_name()
+                .map_or_else(|| f.path.clone(), |n| n.to_string_lossy().into_owned());
+            let _ = writeln!(sums, "{}  {name}", f.sha256.as_deref()?);
+        }
+        Some(hex(&Sha256::digest(sums.as_bytes())))
+    }
+}
+
+fn source_file(path: &Path) -> Result<SourceFile> {
+    let display = path.to_string_lossy().into_owned();
+    if crate::parser::input::is_stdin(path) {
+        return Ok(SourceFile {
+            path: display,
+            bytes: None,
+            sha256: None,
+        });
+    }
+    let read_err = |e: std::io::Error| {
+        Error::Parser(ParserError::InvalidPath {
+            path: path.to_path_buf(),
+            reason: format!("cannot read for load metadata: {e}"),
+        })
+    };
+    let bytes = std::fs::metadata(crate::paths::for_io(path))
+        .map_err(read_err)?
+        .len();
+    let sha256 = crate::parser::checksum::sha256_file(path).map_err(read_err)?;
+    Ok(SourceFile {
+        path: display,
+        bytes: Some(bytes),
+        sha256: Some(sha256),
+    })
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .fold(String::with_capacity(bytes.len() * 2), |mut s, b| {
+            let _ = write!(s, "{b:02x}");
+            s
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_hashes_files_and_checksum_matches_sha256sums() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let (a, b) = (dir.path().join("a.log"), dir.path().join("b.log"));
+        std::fs::write(&a, "abc").unwrap();
+        std::fs::write(&b, "").unwrap();
+        let metrics = MetricsSnapshot {
+            exported: 7,
+            errors: 1,
+            ..MetricsSnapshot::default()
+        };
+        let load = LoadRecord::collect("run-1", [&a, &b], &metrics, Duration::from_millis(5), true)
+            .unwrap();
+        assert_eq!(load.run_id, "run-1");
+
+        assert_eq!(load.files[0].bytes, Some(3));
+        assert_eq!(
+            load.files[0].sha256.as_deref(),
+            Some("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+        assert_eq!(
+            (load.records, load.errors, load.status()),
+            (7, 1, "complete")
+        );
+        let sums = format!(
+            "{}  a.log\n{}  b.log\n",
+            load.files[0].sha256.as_deref().unwrap(),
+            load.files[1].sha256.as_deref().unwrap()
+        );
+        assert_eq!(
+            load.checksum().unwrap(),
+            hex(&Sha256::digest(sums.as_bytes()))
+        );
+        assert!(load.files_json().contains("\"bytes\":3"));
+
+        let stdin = LoadRecord::collect(
+            "run-1",
+            [&PathBuf::from("-")],
+            &metrics,
+            Duration::ZERO,
+            false,
+        )
+        .unwrap();
+        assert_eq!((stdin.checksum(), stdin.status()), (None, "partial"));
+    }
+}
@@ -1,11 +1,35 @@
 use crate::config::Config;
 use crate::error::{ConfigError, Error, Result};
+use crate::features::{DerivedColumns, DerivedValue};
 use dm_database_parser_sqllog::{MetaParts, PerformanceMetrics, Sqllog};
-use log::info;
+use log::{info, warn};
+use std::time::Duration;
 
+// 多线程导出：库用法的推送 API，以及 `run` 多文件并发解析时的写线程
+mod batch;
+pub mod compress;
+mod concurrent;
 pub mod csv;
+mod elasticsearch;
+pub mod loads;
+mod on_error;
+mod preview;
+pub mod registry;
+pub mod rollover;
+pub mod schema;
+pub mod sink;
+pub mod spill;
 pub mod sqlite;
+#[allow(unused_imports)]
+pub use batch::RecordBatch;
+pub use concurrent::{
+    ConcurrentExporterManager, DEFAULT_QUEUE_BATCHES, PRODUCER_BATCH_ROWS, QueueExporter,
+};
 pub use csv::CsvExporter;
+pub use elasticsearch::ElasticsearchExporter;
+pub use loads::LoadRecord;
+pub use on_error::{ErrorLogger, OnError};
+pub use preview::PreviewExporter;
 pub use sqlite::SqliteExporter;
 
 /// 所有导出器必须实现的接口
@@ -38,8 +62,40 @@ pub trait Exporter {
         self.export_one_normalized(sqllog, normalized)
     }
 
+    /// 在 `export_one_preparsed` 基础上附带派生列值（`features.extra_fields`），
+    /// `extras` 与导出器的 `extra_columns` 一一对应。默认实现忽略派生列。
+    fn export_one_derived(
+        &mut self,
+        sqllog: &Sqllog<'_>,
+        meta: &MetaParts<'_>,
+        pm: &PerformanceMetrics<'_>,
+        normalized: Option<&str>,
+        extras: &[DerivedValue],
+    ) -> Result<()> {
+        let _ = extras;
+        self.export_one_preparsed(sqllog, meta, pm, normalized)
+    }
+
     fn finalize(&mut self) -> Result<()>;
 
+    /// 分块边界（`pipeline.chunk_size`）：把已写入的数据交给下游并释放可回收的缓冲。
+    /// 默认实现为 no-op
+    fn flush_chunk(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// `[features.time]` 改写了下一条记录的 `ts` 时，传入其原始日志时间；按时间分割输出的导出器
+    /// （CSV 文件名、Elasticsearch 索引名含 strftime 模式）据此确定周期。默认实现忽略
+    fn set_source_ts(&mut self, ts: &str) {
+        let _ = ts;
+    }
+
+    /// 丢弃当前批次中已写入、尚未提交的记录（`on_error = "skip_batch"`），返回丢弃的条数。
+    /// 默认实现为 no-op：逐行写出的导出器无法撤回已写入的行
+    fn discard_batch(&mut self) -> Result<usize> {
+        Ok(0)
+    }
+
     fn stats_snapshot(&self) -> Option<ExportStats> {
         None
     }
@@ -54,6 +110,13 @@ pub trait Exporter {
         let _ = (stats, final_path);
         Ok(())
     }
+
+    /// 将本次装载记录写入导出目标（`exporter.sqlite.load_metadata`），在 finalize 之后调用。
+    /// 默认实现为 no-op：文件型导出器的审核信息见校验清单与完成摘要
+    fn write_load_record(&mut self, load: &LoadRecord) -> Result<()> {
+        let _ = load;
+        Ok(())
+    }
 }
 
 /// 具体导出器的枚举包装，消除 `Box<dyn Exporter>` 的虚表分发开销，
@@ -62,15 +125,25 @@ pub trait Exporter {
 pub enum ExporterKind {
     Csv(CsvExporter),
     Sqlite(SqliteExporter),
+    Elasticsearch(ElasticsearchExporter),
     DryRun(DryRunExporter),
+    Preview(PreviewExporter),
+    /// 并发解析线程一侧：攒批推送给 [`ConcurrentExporterManager`] 的写线程
+    Queue(QueueExporter),
+    /// `[exporter.custom]`：经 [`registry`] 注册的第三方导出器
+    Custom(registry::PluginExporter),
 }
 
 impl ExporterKind {
-    fn kind_name(&self) -> &'static str {
+    fn kind_name(&self) -> &str {
         match self {
             Self::Csv(_) => "CSV",
             Self::Sqlite(_) => "SQLite",
+            Self::Elasticsearch(_) => "Elasticsearch",
             Self::DryRun(_) => "dry-run",
+            Self::Preview(_) => "preview",
+            Self::Queue(_) => "queue",
+            Self::Custom(e) => e.name(),
         }
     }
 
@@ -79,7 +152,9 @@ impl ExporterKind {
     pub fn csv_include_performance_metrics(&self) -> bool {
         match self {
             Self::Csv(exporter) => exporter.include_performance_metrics,
-            // SQLite/DryRun 永远需要完整 pm（schema 固定）
+            Self::Preview(exporter) => exporter.include_performance_metrics,
+            Self::Queue(exporter) => exporter.include_pm,
+            // SQLite/Elasticsearch/DryRun 永远需要完整 pm（schema 固定）
             _ => true,
         }
     }
@@ -88,7 +163,11 @@ impl ExporterKind {
         match self {
             Self::Csv(e) => e.initialize(),
             Self::Sqlite(e) => e.initialize(),
+            Self::Elasticsearch(e) => e.initialize(),
             Self::DryRun(e) => e.initialize(),
+            Self::Preview(e) => e.initialize(),
+            Self::Queue(e) => e.initialize(),
+            Self::Custom(e) => e.initialize(),
         }
     }
 
@@ -103,7 +182,31 @@ impl ExporterKind {
         match self {
             Self::Csv(e) => e.export_one_preparsed(sqllog, meta, pm, normalized),
             Self::Sqlite(e) => e.export_one_preparsed(sqllog, meta, pm, normalized),
+            Self::Elasticsearch(e) => e.export_one_preparsed(sqllog, meta, pm, normalized),
             Self::DryRun(e) => e.export_one_preparsed(sqllog, meta, pm, normalized),
+            Self::Preview(e) => e.export_one_preparsed(sqllog, meta, pm, normalized),
+            Self::Queue(e) => e.export_one_preparsed(sqllog, meta, pm, normalized),
+            Self::Custom(e) => e.export_one_preparsed(sqllog, meta, pm, normalized),
+        }
+    }
+
+    #[inline]
+    fn export_one_derived(
+        &mut self,
+        sqllog: &Sqllog<'_>,
+        meta: &MetaParts<'_>,
+        pm: &PerformanceMetrics<'_>,
+        normalized: Option<&str>,
+        extras: &[DerivedValue],
+    ) -> Result<()> {
+        match self {
+            Self::Csv(e) => e.export_one_derived(sqllog, meta, pm, normalized, extras),
+            Self::Sqlite(e) => e.export_one_derived(sqllog, meta, pm, normalized, extras),
+            Self::Elasticsearch(e) => e.export_one_derived(sqllog, meta, pm, normalized, extras),
+            Self::DryRun(e) => e.export_one_derived(sqllog, meta, pm, normalized, extras),
+            Self::Preview(e) => e.export_one_derived(sqllog, meta, pm, normalized, extras),
+            Self::Queue(e) => e.export_one_derived(sqllog, meta, pm, normalized, extras),
+            Self::Custom(e) => e.export_one_derived(sqllog, meta, pm, normalized, extras),
         }
     }
 
@@ -111,7 +214,23 @@ impl ExporterKind {
         match self {
             Self::Csv(e) => e.finalize(),
             Self::Sqlite(e) => e.finalize(),
+            Self::Elasticsearch(e) => e.finalize(),
             Self::DryRun(e) => e.finalize(),
+            Self::Preview(e) => e.finalize(),
+            Self::Queue(e) => e.finalize(),
+            Self::Custom(e) => e.finalize(),
+        }
+    }
+
+    fn flush_chunk(&mut self) -> Result<()> {
+        match self {
+            Self::Csv(e) => e.flush_chunk(),
+            Self::Sqlite(e) => e.flush_chunk(),
+            Self::Elasticsearch(e) => e.flush_chunk(),
+            Self::DryRun(e) => e.flush_chunk(),
+            Self::Preview(e) => e.flush_chunk(),
+            Self::Queue(e) => e.flush_chunk(),
+            Self::Custom(e) => e.flush_chunk(),
         }
     }
 
@@ -124,7 +243,48 @@ impl ExporterKind {
         match self {
             Self::Csv(e) => e.write_template_stats(stats, final_path),
             Self::Sqlite(e) => e.write_template_stats(stats, final_path),
+            Self::Elasticsearch(e) => e.write_template_stats(stats, final_path),
             Self::DryRun(e) => e.write_template_stats(stats, final_path),
+            Self::Preview(e) => e.write_template_stats(stats, final_path),
+            Self::Queue(e) => e.write_template_stats(stats, final_path),
+            Self::Custom(e) => e.write_template_stats(stats, final_path),
+        }
+    }
+
+    fn write_load_record(&mut self, load: &LoadRecord) -> Result<()> {
+        match self {
+            Self::Csv(e) => e.write_load_record(load),
+            Self::Sqlite(e) => e.write_load_record(load),
+            Self::Elasticsearch(e) => e.write_load_record(load),
+            Self::DryRun(e) => e.write_load_record(load),
+            Self::Preview(e) => e.write_load_record(load),
+            Self::Queue(e) => e.write_load_record(load),
+            Self::Custom(e) => e.write_load_record(load),
+        }
+    }
+
+    #[inline]
+    fn set_source_ts(&mut self, ts: &str) {
+        match self {
+            Self::Csv(e) => e.set_source_ts(ts),
+            Self::Sqlite(e) => e.set_source_ts(ts),
+            Self::Elasticsearch(e) => e.set_source_ts(ts),
+            Self::DryRun(e) => e.set_source_ts(ts),
+            Self::Preview(e) => e.set_source_ts(ts),
+            Self::Queue(e) => e.set_source_ts(ts),
+            Self::Custom(e) => e.set_source_ts(ts),
+        }
+    }
+
+    fn discard_batch(&mut self) -> Result<usize> {
+        match self {
+            Self::Csv(e) => e.discard_batch(),
+            Self::Sqlite(e) => e.discard_batch(),
+            Self::Elasticsearch(e) => e.discard_batch(),
+            Self::DryRun(e) => e.discard_batch(),
+            Self::Preview(e) => e.discard_batch(),
+            Self::Queue(e) => e.discard_batch(),
+            Self::Custom(e) => e.discard_batch(),
         }
     }
 
@@ -132,19 +292,31 @@ impl ExporterKind {
         match self {
             Self::Csv(e) => e.stats_snapshot(),
             Self::Sqlite(e) => e.stats_snapshot(),
+            Self::Elasticsearch(e) => e.stats_snapshot(),
             Self::DryRun(e) => e.stats_snapshot(),
+            Self::Preview(e) => e.stats_snapshot(),
+            Self::Queue(e) => e.stats_snapshot(),
+            Self::Custom(e) => e.stats_snapshot(),
         }
     }
 }
 
-/// 导出统计
+/// 导出器内部统计（写入条数与刷新次数）；运行级计数见 [`crate::metrics::Metrics`]
 #[derive(Debug, Default, Clone, Copy)]
 pub struct ExportStats {
     pub exported: usize,
-    pub skipped: usize,
-    pub failed: usize,
     pub flush_operations: usize,
     pub last_flush_size: usize,
+    /// 截至上一次分块刷新已交给下游的条数
+    pub flushed: usize,
+    /// 因 `[features.dedup]` 跳过、未交给导出器的重复记录数
+    pub duplicates: usize,
+    /// 分块刷新 / 批量提交的耗时分布
+    pub flush_latency: FlushLatency,
+    /// 写入失败、按 `on_error` 跳过的记录数（不计入 `exported`）
+    pub skipped: usize,
+    /// 写入失败、按 `on_error = log` 写入错误日志的记录数（不计入 `exported`）
+    pub failed: usize,
 }
 
 impl ExportStats {
@@ -157,10 +329,135 @@ impl ExportStats {
         self.exported += 1;
     }
 
+    /// 交给导出器的记录总数：成功、跳过与失败之和（去重跳过的记录未交给导出器，不计入）
     #[must_use]
     pub fn total(&self) -> usize {
         self.exported + self.skipped + self.failed
     }
+
+    /// 记录一次分块刷新及其耗时（自上次刷新以来没有新写入时不计）
+    pub fn record_flush(&mut self, elapsed: Duration) {
+        if self.exported > self.flushed {
+            self.flush_operations += 1;
+            self.last_flush_size = self.exported - self.flushed;
+            self.flushed = self.exported;
+            self.flush_latency.record(elapsed);
+        }
+    }
+
+    /// 已计入 `exported` 的记录未能写入目标（批次提交失败、文档被拒绝），改计为跳过
+    pub fn record_skipped(&mut self, n: usize) {
+        let n = n.min(self.exported);
+        self.exported -= n;
+        self.flushed = self.flushed.min(self.exported);
+        self.skipped += n;
+    }
+}
+
+/// 直方图桶数：第 i 个桶覆盖 `[2^i, 2^(i+1))` 微秒（首桶从 0 开始），最后一个桶收纳更长的耗时
+const LATENCY_BUCKETS: usize = 32;
+
+/// 刷新耗时直方图（按 2 的幂分桶，定长、可复制），用于区分吞吐下降来自目标库还是解析
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FlushLatency {
+    pub count: u64,
+    pub total_us: u64,
+    pub min_us: u64,
+    pub max_us: u64,
+    buckets: [u32; LATENCY_BUCKETS],
+}
+
+impl FlushLatency {
+    pub fn record(&mut self, elapsed: Duration) {
+        let us = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+        self.min_us = if self.count == 0 {
+            us
+        } else {
+            self.min_us.min(us)
+        };
+        self.max_us = self.max_us.max(us);
+        self.count += 1;
+        self.total_us = self.total_us.saturating_add(us);
+        let bucket = (u64::BITS - us.leading_zeros()).saturating_sub(1) as usize;
+        self.buckets[bucket.min(LATENCY_BUCKETS - 1)] += 1;
+    }
+
+    /// 合并另一份直方图（`run --watch` 各轮汇总）
+    pub fn merge(&mut self, other: &Self) {
+        if other.count == 0 {
+            return;
+        }
+        self.min_us = if self.count == 0 {
+            other.min_us
+        } else {
+            self.min_us.min(other.min_us)
+        };
+        self.max_us = self.max_us.max(other.max_us);
+        self.count += other.count;
+        self.total_us = self.total_us.saturating_add(other.total_us);
+        for (a, b) in self.buckets.iter_mut().zip(other.buckets) {
+            *a += b;
+        }
+    }
+
+    #[must_use]
+    pub fn avg_us(&self) -> u64 {
+        self.total_us.checked_div(self.count).unwrap_or(0)
+    }
+
+    /// 第 95 百分位的估计值：在所在桶内按线性插值，不超过最大值
+    #[must_use]
+    pub fn p95_us(&self) -> u64 {
+        let rank = self.count.saturating_mul(95).div_ceil(100);
+        let mut seen = 0u64;
+        for (i, &n) in self.buckets.iter().enumerate() {
+            let n = u64::from(n);
+            if n == 0 {
+                continue;
+            }
+            if seen + n >= rank {
+                let low = if i == 0 { 0 } else { 1u64 << i };
+                let high = 1u64 << (i + 1);
+                let estimate = low + (high - low) * (rank - seen) / n;
+                return estimate.clamp(self.min_us, self.max_us);
+            }
+            seen += n;
+        }
+        self.max_us
+    }
+}
+
+/// 以毫秒输出的耗时统计（运行报告 JSON）
+impl serde::Serialize for FlushLatency {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct as _;
+        #[allow(clippy::cast_precision_loss)]
+        let ms = |us: u64| us as f64 / 1000.0;
+        let mut st = serializer.serialize_struct("FlushLatency", 5)?;
+        st.serialize_field("count", &self.count)?;
+        st.serialize_field("min_ms", &ms(self.min_us))?;
+        st.serialize_field("avg_ms", &ms(self.avg_us()))?;
+        st.serialize_field("p95_ms", &ms(self.p95_us()))?;
+        st.serialize_field("max_ms", &ms(self.max_us))?;
+        st.end()
+    }
+}
+
+impl std::fmt::Display for FlushLatency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        #[allow(clippy::cast_precision_loss)]
+        let ms = |us: u64| us as f64 / 1000.0;
+        write!(
+            f,
+            "min {:.1} ms / avg {:.1} ms / p95 {:.1} ms",
+            ms(self.min_us),
+            ms(self.avg_us()),
+            ms(self.p95_us())
+        )
+    }
 }
 
 /// 空运行导出器：只计数，不写任何文件（用于 --dry-run 模式）
@@ -213,34 +510,121 @@ impl Exporter for DryRunExporter {
     }
 }
 
+/// 写入失败日志逐条输出的上限，之后只计数
+const MAX_LOGGED_SKIPS: usize = 10;
+
 /// 导出器管理器
 pub struct ExporterManager {
     exporter: ExporterKind,
+    /// 去重跳过的记录数，并入 [`ExportStats::duplicates`]
+    duplicates: usize,
+    /// 写入失败的处理策略（`exporter.<name>.on_error`）
+    on_error: OnError,
+    /// 按 `on_error` 跳过的单条记录数，并入 [`ExportStats::skipped`]；整批丢弃的记录由导出器自行计数
+    skipped: usize,
+    /// 上次处理写入失败时导出器已自行计数的跳过数，用于判断本次失败是否已整批丢弃
+    seen_exporter_skipped: usize,
+    /// 已跳过的写入失败次数（控制日志输出）
+    failures: usize,
+    /// `on_error = log` 写入错误日志的单条记录数，并入 [`ExportStats::failed`]
+    failed: usize,
+    /// `on_error = log` 的失败记录日志
+    error_log: ErrorLogger,
 }
 
 impl std::fmt::Debug for ExporterManager {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ExporterManager")
             .field("exporter", &self.exporter.kind_name())
-            .finish()
+            .field("duplicates", &self.duplicates)
+            .field("on_error", &self.on_error)
+            .field("skipped", &self.skipped)
+            .field("failed", &self.failed)
+            .finish_non_exhaustive()
     }
 }
 
 impl ExporterManager {
+    pub(super) fn wrap(exporter: ExporterKind) -> Self {
+        Self {
+            exporter,
+            duplicates: 0,
+            on_error: OnError::Abort,
+            skipped: 0,
+            seen_exporter_skipped: 0,
+            failures: 0,
+            failed: 0,
+            error_log: ErrorLogger::default(),
+        }
+    }
+
+    /// 按 `exporter.<name>.on_error` 设置写入失败的处理策略（取值已在配置校验阶段检查）
+    fn with_on_error(mut self, field: &str, value: &str) -> Self {
+        self.on_error = OnError::parse(field, value).unwrap_or_default();
+        self
+    }
+
     /// 从已构建的 `CsvExporter` 创建管理器（并行处理时每个任务独立调用）。
     #[must_use]
     pub fn from_csv(exporter: CsvExporter) -> Self {
-        Self {
-            exporter: ExporterKind::Csv(exporter),
-        }
+        Self::wrap(ExporterKind::Csv(exporter))
     }
 
     /// 创建空运行导出器，只统计记录数不写文件
     #[must_use]
     pub fn dry_run() -> Self {
         info!("Dry-run mode: no output will be written");
-        Self {
-            exporter: ExporterKind::DryRun(DryRunExporter::default()),
+        Self::wrap(ExporterKind::DryRun(DryRunExporter::default()))
+    }
+
+    /// 创建预览导出器：列规则（投影、`normalized_sql`、派生列、CSV 性能指标开关与列顺序）与正式导出一致
+    pub fn preview(config: &Config) -> Result<Self> {
+        let mut exporter = PreviewExporter::default();
+        exporter.normalize = config
+            .features
+            .replace_parameters
+            .as_ref()
+            .is_none_or(|r| r.enable);
+        exporter.ordered_indices = config.features.ordered_field_indices();
+        exporter.include_performance_metrics = config
+            .exporter
+            .csv
+            .as_ref()
+            .is_none_or(|c| c.include_performance_metrics);
+        exporter.exectime_format = config
+            .exporter
+            .csv
+            .as_ref()
+            .map(crate::config::CsvExporter::exectime_format)
+            .unwrap_or_default();
+        exporter.extra_columns = DerivedColumns::from_config(&config.features)?.specs();
+        if let Some(names) = config
+            .exporter
+            .csv
+            .as_ref()
+            .and_then(|c| c.column_order.as_deref())
+        {
+            exporter.ordered_indices =
+                csv::column_order(names, &exporter.ordered_indices, &exporter.extra_columns)?;
+            exporter.column_order = true;
+        }
+        Ok(Self::wrap(ExporterKind::Preview(exporter)))
+    }
+
+    /// 导出器将执行的 DDL（按执行顺序）；不涉及建表的导出器返回 `None`
+    #[must_use]
+    pub fn ddl_statements(&self, with_templates: bool) -> Option<Vec<String>> {
+        match &self.exporter {
+            ExporterKind::Sqlite(e) => Some(e.ddl_statements(with_templates)),
+            _ => None,
+        }
+    }
+
+    /// 取出预览导出器收集的列名与行；非预览导出器返回 `None`
+    pub fn take_preview(&mut self) -> Option<(Vec<String>, Vec<Vec<serde_json::Value>>)> {
+        match &mut self.exporter {
+            ExporterKind::Preview(e) => Some((e.columns(), e.take_rows())),
+            _ => None,
         }
     }
 
@@ -255,6 +639,7 @@ impl ExporterManager {
 
         let field_mask = config.features.field_mask();
         let ordered_indices = config.features.ordered_field_indices();
+        let extra_columns = DerivedColumns::from_config(&config.features)?.specs();
 
         if let Some(cfg) = &config.exporter.csv {
             info!("Using CSV exporter: {}", cfg.file);
@@ -262,9 +647,12 @@ impl ExporterManager {
             exporter.normalize = normalize;
             exporter.field_mask = field_mask;
             exporter.ordered_indices.clone_from(&ordered_indices);
-            return Ok(Self {
-                exporter: ExporterKind::Csv(exporter),
-            });
+            exporter.extra_columns = extra_columns;
+            if let Some(names) = &cfg.column_order {
+                exporter.apply_column_order(names)?;
+            }
+            return Ok(Self::wrap(ExporterKind::Csv(exporter))
+                .with_on_error("exporter.csv.on_error", &cfg.on_error));
         }
 
         if let Some(cfg) = &config.exporter.sqlite {
@@ -273,9 +661,26 @@ impl ExporterManager {
             exporter.normalize = normalize;
             exporter.field_mask = field_mask;
             exporter.ordered_indices = ordered_indices;
-            return Ok(Self {
-                exporter: ExporterKind::Sqlite(exporter),
-            });
+            exporter.extra_columns = extra_columns;
+            return Ok(Self::wrap(ExporterKind::Sqlite(exporter))
+                .with_on_error("exporter.sqlite.on_error", &cfg.on_error));
+        }
+
+        if let Some(cfg) = &config.exporter.elasticsearch {
+            info!("Using Elasticsearch exporter: {} / {}", cfg.url, cfg.index);
+            let mut exporter = ElasticsearchExporter::from_config(cfg);
+            exporter.normalize = normalize;
+            exporter.ordered_indices = ordered_indices;
+            exporter.extra_columns = extra_columns;
+            return Ok(Self::wrap(ExporterKind::Elasticsearch(exporter))
+                .with_on_error("exporter.elasticsearch.on_error", &cfg.on_error));
+        }
+
+        // 第三方导出器自行决定输出格式，不套用字段投影
+        if let Some(cfg) = &config.exporter.custom {
+            info!("Using custom exporter: {}", cfg.name);
+            return Ok(Self::wrap(ExporterKind::Custom(registry::create(cfg)?))
+                .with_on_error("exporter.custom.on_error", &cfg.on_error));
         }
 
         Err(Error::Config(ConfigError::NoExporters))
@@ -303,8 +708,115 @@ impl ExporterManager {
         pm: &PerformanceMetrics<'_>,
         normalized: Option<&str>,
     ) -> Result<()> {
-        self.exporter
-            .export_one_preparsed(sqllog, meta, pm, normalized)
+        let result = self
+            .exporter
+            .export_one_preparsed(sqllog, meta, pm, normalized);
+        match result {
+            Ok(()) => Ok(()),
+            Err(e) => self.skip_failed_record(e, sqllog, meta, pm),
+        }
+    }
+
+    /// 带派生列的热路径（仅在配置了 `features.extra_fields` 时使用）
+    #[inline]
+    pub fn export_one_derived(
+        &mut self,
+        sqllog: &Sqllog<'_>,
+        meta: &MetaParts<'_>,
+        pm: &PerformanceMetrics<'_>,
+        normalized: Option<&str>,
+        extras: &[DerivedValue],
+    ) -> Result<()> {
+        let result = self
+            .exporter
+            .export_one_derived(sqllog, meta, pm, normalized, extras);
+        match result {
+            Ok(()) => Ok(()),
+            Err(e) => self.skip_failed_record(e, sqllog, meta, pm),
+        }
+    }
+
+    /// 分块边界：导出器刷新缓冲 / 提交事务，保持内存占用与输入总量无关。
+    /// 提交失败时，导出器已丢弃该批次的按 `on_error` 继续，否则中止
+    pub fn flush_chunk(&mut self) -> Result<()> {
+        match self.exporter.flush_chunk() {
+            Err(e)
+                if self.on_error.tolerates(&e)
+                    && self.exporter_skipped() > self.seen_exporter_skipped =>
+            {
+                let dropped = self.exporter_skipped() - self.seen_exporter_skipped;
+                self.seen_exporter_skipped += dropped;
+                self.log_skip(&e, dropped);
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    /// 导出器自行计数的跳过记录数（整批丢弃、文档被拒绝）
+    fn exporter_skipped(&self) -> usize {
+        self.exporter.stats_snapshot().map_or(0, |s| s.skipped)
+    }
+
+    fn log_skip(&mut self, e: &Error, dropped: usize) {
+        self.failures += 1;
+        if self.failures <= MAX_LOGGED_SKIPS {
+            warn!(
+                "{}: write failed, {dropped} records skipped (on_error = {}): {e}",
+                self.name(),
+                self.on_error
+            );
+            if self.failures == MAX_LOGGED_SKIPS {
+                warn!(
+                    "{}: further write failures are counted without logging",
+                    self.name()
+                );
+            }
+        } else {
+            log::debug!(
+                "{}: write failed, {dropped} records skipped: {e}",
+                self.name()
+            );
+        }
+    }
+
+    /// 单条记录写入失败：`abort` 中止；`skip_record` 跳过该记录；`skip_batch` 连同当前批次一起丢弃；
+    /// `log` 把该记录计为失败，并经 [`ErrorLogger`] 写入其导出字段（不受 [`MAX_LOGGED_SKIPS`] 限制）。
+    /// 写入触发的批量发送失败时，导出器已丢弃该批次，跳过策略都不再重复丢弃
+    #[cold]
+    fn skip_failed_record(
+        &mut self,
+        e: Error,
+        sqllog: &Sqllog<'_>,
+        meta: &MetaParts<'_>,
+        pm: &PerformanceMetrics<'_>,
+    ) -> Result<()> {
+        if !self.on_error.tolerates(&e) {
+            return Err(e);
+        }
+        let dropped_by_exporter = self.exporter_skipped() - self.seen_exporter_skipped;
+        if dropped_by_exporter > 0 {
+            // 导出器已整批丢弃并计为跳过；`log` 仍记录触发失败的那条
+            self.seen_exporter_skipped += dropped_by_exporter;
+            self.log_skip(&e, dropped_by_exporter);
+            if self.on_error == OnError::Log {
+                let name = self.exporter.kind_name();
+                self.error_log.log(name, &e, sqllog, meta, pm);
+            }
+        } else if self.on_error == OnError::Log {
+            self.failed += 1;
+            let name = self.exporter.kind_name();
+            self.error_log.log(name, &e, sqllog, meta, pm);
+        } else {
+            self.skipped += 1;
+            let mut dropped = 1;
+            if self.on_error == OnError::SkipBatch {
+                dropped += self.exporter.discard_batch()?;
+            }
+            self.seen_exporter_skipped = self.exporter_skipped();
+            self.log_skip(&e, dropped);
+        }
+        Ok(())
     }
 
     pub fn finalize(&mut self) -> Result<()> {
@@ -322,24 +834,66 @@ impl ExporterManager {
         self.exporter.write_template_stats(stats, final_path)
     }
 
+    pub fn write_load_record(&mut self, load: &LoadRecord) -> Result<()> {
+        self.exporter.write_load_record(load)
+    }
+
     #[must_use]
     pub fn name(&self) -> &str {
         self.exporter.kind_name()
     }
 
-    pub fn log_stats(&self) {
-        if let Some(s) = self.exporter.stats_snapshot() {
+    #[must_use]
+    pub fn stats_snapshot(&self) -> Option<ExportStats> {
+        self.exporter.stats_snapshot().map(|s| ExportStats {
+            duplicates: self.duplicates,
+            skipped: s.skipped + self.skipped,
+            failed: s.failed + self.failed,
+            ..s
+        })
+    }
+
+    /// 下一条记录的原始日志时间（`[features.time]` 改写了导出的 `ts` 时）
+    #[inline]
+    pub fn set_source_ts(&mut self, ts: &str) {
+        self.exporter.set_source_ts(ts);
+    }
+
+    /// 记录一条被去重跳过的记录
+    pub fn record_duplicate(&mut self) {
+        self.duplicates += 1;
+    }
+
+    /// 记录数取自运行指标登记表（与进度条、完成摘要一致），刷新统计取自导出器
+    pub fn log_stats(&self, metrics: &crate::metrics::MetricsSnapshot) {
+        if let Some(s) = self.stats_snapshot() {
             info!(
-                "Export stats: {} => success: {}, failed: {}, skipped: {} (total: {}){}",
+                "Export stats: {} => exported: {}, errors: {}, recovered: {} (parsed: {}){}{}{}",
                 self.name(),
-                s.exported,
-                s.failed,
-                s.skipped,
-                s.total(),
+                metrics.exported,
+                metrics.errors,
+                metrics.recovered,
+                metrics.parsed,
                 if s.flush_operations > 0 {
                     format!(
-                        " | flushed: {} times (recent {} entries)",
-                        s.flush_operations, s.last_flush_size
+                        " | flushed: {} times (recent {} entries; {})",
+                        s.flush_operations, s.last_flush_size, s.flush_latency
+                    )
+                } else {
+                    String::new()
+                },
+                if s.duplicates > 0 {
+                    format!(" | duplicates skipped: {}", s.duplicates)
+                } else {
+                    String::new()
+                },
+                if s.skipped + s.failed > 0 {
+                    format!(
+                        " | write failures: {} skipped, {} logged of {} records ({})",
+                        s.skipped,
+                        s.failed,
+                        s.total(),
+                        self.on_error
                     )
                 } else {
                     String::new()
@@ -365,6 +919,46 @@ pub(super) fn strip_ip_prefix(ip: &str) -> &str {
     }
 }
 
+/// `exec_time_ms` 列的数值格式（`exporter.csv.exectime_format`）。
+///
+/// 两种格式都由 itoa 逐位拼出，不经过任何区域设置：小数点固定为 `.`，不含千分位分隔符，
+/// 在 zh-CN / de-DE 等以逗号作小数点的主机上输出也完全相同。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExecTimeFormat {
+    /// 截断为整数毫秒（默认，与历史输出一致）
+    #[default]
+    Integer,
+    /// 毫秒保留 3 位小数（微秒精度，四舍五入），如 `0.521`
+    Decimal,
+}
+
+impl ExecTimeFormat {
+    /// 按格式把毫秒值追加写入 `buf`
+    #[inline]
+    pub(crate) fn write(self, buf: &mut Vec<u8>, itoa_buf: &mut itoa::Buffer, ms: f32) {
+        match self {
+            Self::Integer => buf.extend_from_slice(itoa_buf.format(f32_ms_to_i64(ms)).as_bytes()),
+            Self::Decimal => {
+                let us = f32_ms_to_i64((ms * 1000.0).round());
+                if us < 0 {
+                    buf.push(b'-');
+                }
+                let us = us.unsigned_abs();
+                buf.extend_from_slice(itoa_buf.format(us / 1000).as_bytes());
+                buf.push(b'.');
+                let frac = us % 1000;
+                if frac < 100 {
+                    buf.push(b'0');
+                }
+                if frac < 10 {
+                    buf.push(b'0');
+                }
+                buf.extend_from_slice(itoa_buf.format(frac).as_bytes());
+            }
+        }
+    }
+}
+
 /// Saturating cast from f32 milliseconds to i64 milliseconds without precision-loss warnings
 #[inline]
 #[must_use]
@@ -393,10 +987,19 @@ pub(super) fn f32_ms_to_i64(ms: f32) -> i64 {
     }
 }
 
+/// 表示标准输出的导出文件路径
+pub const STDOUT_PATH: &str = "-";
+
+/// 导出文件路径是否指向标准输出
+#[must_use]
+pub fn is_stdout(path: &std::path::Path) -> bool {
+    path.as_os_str() == STDOUT_PATH
+}
+
 /// 确保输出文件的父目录存在
 pub(super) fn ensure_parent_dir(path: &std::path::Path) -> std::io::Result<()> {
     if let Some(parent) = path.parent().filter(|p| !p.exists()) {
-        std::fs::create_dir_all(parent)?;
+        std::fs::create_dir_all(crate::paths::for_io(parent))?;
     }
     Ok(())
 }
@@ -404,6 +1007,7 @@ pub(super) fn ensure_parent_dir(path: &std::path::Path) -> std::io::Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::borrow::Cow;
 
     // ── ExportStats ────────────────────────────────────────────
     #[test]
@@ -411,6 +1015,29 @@ mod tests {
         let s = ExportStats::new();
         assert_eq!(s.exported, 0);
         assert_eq!(s.total(), 0);
+        assert_eq!(s.flush_operations, 0);
+    }
+
+    #[test]
+    fn test_flush_latency_min_avg_p95() {
+        let mut s = ExportStats::new();
+        s.record_flush(Duration::from_millis(5));
+        assert_eq!(s.flush_latency.count, 0, "no new rows since the last flush");
+        for ms in (1..=19).chain([200]) {
+            s.record_success();
+            s.record_flush(Duration::from_millis(ms));
+        }
+        let l = s.flush_latency;
+        assert_eq!((l.count, l.min_us, l.max_us), (20, 1_000, 200_000));
+        assert_eq!(l.avg_us(), 19_500);
+        // 第 19 个样本（19 ms）落在 16.4 ~ 32.8 ms 的桶内，估计值不超出该桶
+        assert!((16_384..=32_768).contains(&l.p95_us()), "{}", l.p95_us());
+
+        let mut merged = FlushLatency::default();
+        merged.merge(&l);
+        merged.merge(&FlushLatency::default());
+        assert_eq!(merged, l);
+        assert!(l.to_string().starts_with("min 1.0 ms / avg 19.5 ms / p95 "));
     }
 
     #[test]
@@ -428,9 +1055,20 @@ mod tests {
         s.exported = 5;
         s.skipped = 2;
         s.failed = 1;
+        s.duplicates = 4;
         assert_eq!(s.total(), 8);
     }
 
+    #[test]
+    fn test_export_stats_record_skipped_keeps_total() {
+        let mut s = ExportStats::new();
+        for _ in 0..5 {
+            s.record_success();
+        }
+        s.record_skipped(2);
+        assert_eq!((s.exported, s.skipped, s.total()), (3, 2, 5));
+    }
+
     // ── strip_ip_prefix ────────────────────────────────────────
     #[test]
     fn test_strip_ip_prefix_with_prefix() {
@@ -458,6 +1096,21 @@ mod tests {
     }
 
     // ── f32_ms_to_i64 ──────────────────────────────────────────
+    #[test]
+    fn test_exectime_format_uses_dot_decimal() {
+        let fmt = |f: ExecTimeFormat, ms: f32| {
+            let mut buf = Vec::new();
+            f.write(&mut buf, &mut itoa::Buffer::new(), ms);
+            String::from_utf8(buf).unwrap()
+        };
+        assert_eq!(fmt(ExecTimeFormat::Integer, 1234.567), "1234");
+        assert_eq!(fmt(ExecTimeFormat::Decimal, 1234.567), "1234.567");
+        assert_eq!(fmt(ExecTimeFormat::Decimal, 0.521), "0.521");
+        assert_eq!(fmt(ExecTimeFormat::Decimal, 12.0), "12.000");
+        assert_eq!(fmt(ExecTimeFormat::Decimal, 0.0456), "0.046");
+        assert_eq!(fmt(ExecTimeFormat::Decimal, f32::NAN), "0.000");
+    }
+
     #[test]
     fn test_f32_ms_to_i64_normal() {
         assert_eq!(f32_ms_to_i64(100.0_f32), 100);
@@ -543,10 +1196,14 @@ mod tests {
                     overwrite: true,
                     append: false,
                     batch_size: 10_000,
+                    ..SqliteExporterCfg::default()
                 }),
+                elasticsearch: None,
+                custom: None,
             },
             sqllog: SqllogConfig {
                 path: "sqllogs".to_string(),
+                ..Default::default()
             },
             ..Default::default()
         };
@@ -561,9 +1218,12 @@ mod tests {
             exporter: ExporterConfig {
                 csv: None,
                 sqlite: None,
+                elasticsearch: None,
+                custom: None,
             },
             sqllog: SqllogConfig {
                 path: "sqllogs".to_string(),
+                ..Default::default()
             },
             ..Default::default()
         };
@@ -588,7 +1248,7 @@ mod tests {
     fn test_exporter_manager_log_stats_no_panic() {
         let manager = ExporterManager::dry_run();
         // Just verify it doesn't panic
-        manager.log_stats();
+        manager.log_stats(&crate::metrics::MetricsSnapshot::default());
     }
 
     #[test]
@@ -598,6 +1258,103 @@ mod tests {
         assert!(s.contains("ExporterManager"));
     }
 
+    /// 写入失败的导出器（每条都失败）
+    #[derive(Debug)]
+    struct Failing;
+
+    impl Exporter for Failing {
+        fn initialize(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn export(&mut self, _sqllog: &Sqllog<'_>) -> Result<()> {
+            Err(Error::Export(crate::error::ExportError::WriteFailed {
+                path: "failing".into(),
+                reason: "disk full".into(),
+            }))
+        }
+
+        fn finalize(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn stats_snapshot(&self) -> Option<ExportStats> {
+            Some(ExportStats::new())
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct SharedBuf(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// `on_error = log` 记录交给导出器的字段（脱敏、编号后的值），不使用原文；
+    /// 并行写线程收到的记录不带原文，同样完整记录；计为失败而非跳过
+    #[test]
+    fn test_on_error_log_writes_exported_fields() {
+        let buf = SharedBuf::default();
+        registry::register("test-failing", |_: &crate::config::CustomExporter| {
+            Ok(Box::new(Failing) as Box<dyn Exporter + Send>)
+        });
+        let plugin = registry::create(&toml::from_str("name = \"test-failing\"").unwrap()).unwrap();
+        let mut manager = ExporterManager::wrap(ExporterKind::Custom(plugin));
+        manager.on_error = OnError::Log;
+        manager.error_log = ErrorLogger::to_writer(Box::new(buf.clone()));
+
+        let mut record = crate::testing::sqllog_fixture();
+        record.content_raw = Cow::Borrowed(b"ALTER USER bob IDENTIFIED BY \"s3cret\"");
+        let mut meta = record.parse_meta();
+        meta.sess_id = Cow::Borrowed("7");
+        let pm = PerformanceMetrics {
+            sql: Cow::Borrowed("ALTER USER bob IDENTIFIED BY ***"),
+            ..PerformanceMetrics::default()
+        };
+        manager
+            .export_one_preparsed(&record, &meta, &pm, None)
+            .unwrap();
+
+        // 队列路径：记录不带原始元数据与正文
+        let mut queued = Sqllog::default();
+        queued.ts = record.ts.clone();
+        queued.tag = record.tag.clone();
+        manager
+            .export_one_preparsed(&queued, &meta, &pm, None)
+            .unwrap();
+
+        let logged = String::from_utf8(buf.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<_> = logged.lines().collect();
+        assert_eq!(lines.len(), 2, "{logged}");
+        for line in lines {
+            assert!(
+                line.starts_with(
+                    "test-failing: failed record: 2025-01-15 10:00:00.000 (EP[0] sess:7 "
+                ),
+                "{line}"
+            );
+            assert!(line.contains("user:USER0"), "{line}");
+            assert!(
+                line.contains("[SEL] ALTER USER bob IDENTIFIED BY ***: "),
+                "{line}"
+            );
+            assert!(line.ends_with("disk full"), "{line}");
+            assert!(
+                !line.contains("s3cret") && !line.contains("0x0000"),
+                "{line}"
+            );
+        }
+        let stats = manager.stats_snapshot().unwrap();
+        assert_eq!((stats.failed, stats.skipped), (2, 0));
+    }
+
     #[test]
     fn test_dry_run_export_via_trait() {
         use dm_database_parser_sqllog::LogParser;
@@ -728,6 +1485,7 @@ mod tests {
             overwrite: true,
             append: false,
             batch_size: 10_000,
+            ..SqliteExporterCfg::default()
         };
         let mut sqlite = SqliteExporter::from_config(&sqlite_cfg);
         sqlite.initialize().unwrap();
@@ -0,0 +1,135 @@
+//! `exporter.<name>.on_error`：导出器写入失败时的处理策略。
+//!
+//! 默认 `abort`，任何写入错误都中止导出（退出码 4）。同一份配置中审计用的数据库装载需要严格中止，
+//! 而顺带输出的 CSV 副本只求尽力而为，因此策略按导出器分别配置：
+//!
+//! - `skip_record`（或 `skip`）：只丢弃写入失败的那条记录，继续导出；
+//! - `log`：同 `skip_record`，另把每条失败记录实际导出的字段（已按 `[features.redact]` 抹去凭据、
+//!   按 `[features.obfuscate_ids]` 替换编号）逐条写入应用日志，便于事后补录，计为失败而非跳过；
+//! - `skip_batch`：丢弃当前批次（自上次分块提交以来写入、尚未提交的记录），从下一批继续，
+//!   批次要么完整写入要么整体丢弃。
+//!
+//! 整批失败的错误（`SQLite` 分块提交失败、Elasticsearch `_bulk` 请求失败或有文档被拒绝）在两种策略下
+//! 都丢弃该批次。导出器无法确定丢弃范围的错误（CSV 分块刷新失败、输出管道关闭、重复装载检测）
+//! 以及收尾阶段的错误仍然中止。跳过的记录数计入完成摘要、运行警告与运行报告。
+use crate::error::{ConfigError, Error, ExportError, Result};
+use dm_database_parser_sqllog::{MetaParts, PerformanceMetrics, Sqllog};
+use std::io::Write;
+
+/// `exporter.<name>.on_error` 可选值
+pub const ON_ERROR_POLICIES: &[&str] = &["abort", "skip", "log", "skip_record", "skip_batch"];
+
+/// 写入失败的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnError {
+    #[default]
+    Abort,
+    SkipBatch,
+    SkipRecord,
+    /// 跳过失败的记录并把导出字段写入应用日志
+    Log,
+}
+
+impl OnError {
+    /// 解析配置值，`field` 用于错误信息
+    pub fn parse(field: &str, value: &str) -> Result<Self> {
+        match value {
+            "abort" => Ok(Self::Abort),
+            "skip_batch" => Ok(Self::SkipBatch),
+            "skip_record" | "skip" => Ok(Self::SkipRecord),
+            "log" => Ok(Self::Log),
+            _ => Err(Error::Config(ConfigError::InvalidValue {
+                field: field.to_string(),
+                value: value.to_string(),
+                reason: format!("must be one of: {}", ON_ERROR_POLICIES.join(", ")),
+            })),
+        }
+    }
+
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Abort => "abort",
+            Self::SkipBatch => "skip_batch",
+            Self::SkipRecord => "skip_record",
+            Self::Log => "log",
+        }
+    }
+
+    /// 该错误能否按策略跳过：只有目标端的写入失败可以跳过，
+    /// 下游关闭管道、写线程退出、重复装载检测等与单条记录无关的错误始终中止
+    #[must_use]
+    pub fn tolerates(self, err: &Error) -> bool {
+        self != Self::Abort
+            && matches!(
+                err,
+                Error::Export(
+                    ExportError::WriteFailed { .. }
+                        | ExportError::DatabaseFailed { .. }
+                        | ExportError::BulkFailed { .. }
+                )
+            )
+    }
+}
+
+impl std::fmt::Display for OnError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// `log` 策略的失败记录日志：每条一行，内容取自交给导出器的字段而非原始日志文本，
+/// 并行写线程收到的记录不带原文，同样能完整记录
+#[derive(Default)]
+pub struct ErrorLogger {
+    /// 未设置时写入应用日志（warn 级别）
+    out: Option<Box<dyn Write + Send>>,
+}
+
+impl std::fmt::Debug for ErrorLogger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ErrorLogger")
+            .field("app_log", &self.out.is_none())
+            .finish()
+    }
+}
+
+impl ErrorLogger {
+    #[cfg(test)]
+    pub(crate) fn to_writer(out: Box<dyn Write + Send>) -> Self {
+        Self { out: Some(out) }
+    }
+
+    pub fn log(
+        &mut self,
+        exporter: &str,
+        err: &Error,
+        sqllog: &Sqllog<'_>,
+        meta: &MetaParts<'_>,
+        pm: &PerformanceMetrics<'_>,
+    ) {
+        let tag = sqllog
+            .tag
+            .as_ref()
+            .map_or_else(String::new, |t| format!("[{t}] "));
+        let line = format!(
+            "{exporter}: failed record: {} (EP[{}] sess:{} thrd:{} user:{} trxid:{} stmt:{} appname:{} ip:{}) {tag}{}: {err}",
+            sqllog.ts,
+            meta.ep,
+            meta.sess_id,
+            meta.thrd_id,
+            meta.username,
+            meta.trxid,
+            meta.statement,
+            meta.appname,
+            meta.client_ip,
+            pm.sql
+        );
+        match &mut self.out {
+            Some(out) => {
+                let _ = writeln!(out, "{line}");
+            }
+            None => log::warn!("{line}"),
+        }
+    }
+}
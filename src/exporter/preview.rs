@@ -0,0 +1,151 @@
+use super::{ExecTimeFormat, ExportStats, Exporter, f32_ms_to_i64, strip_ip_prefix};
+use crate::error::Result;
+use crate::features::{DerivedColumnSpec, DerivedValue, FIELD_NAMES};
+use dm_database_parser_sqllog::{MetaParts, PerformanceMetrics, Sqllog};
+use serde_json::Value;
+
+/// 预览导出器：按与 CSV 相同的列规则把记录收集到内存（`preview` 子命令使用），不写任何文件
+#[derive(Debug)]
+pub struct PreviewExporter {
+    pub(super) normalize: bool,
+    pub(super) ordered_indices: Vec<usize>,
+    pub(super) include_performance_metrics: bool,
+    pub(super) exectime_format: ExecTimeFormat,
+    pub(super) extra_columns: Vec<DerivedColumnSpec>,
+    /// `ordered_indices` 已按 `exporter.csv.column_order` 重排并含派生列
+    pub(super) column_order: bool,
+    rows: Vec<Vec<Value>>,
+    stats: ExportStats,
+}
+
+impl Default for PreviewExporter {
+    fn default() -> Self {
+        Self {
+            normalize: true,
+            ordered_indices: (0..FIELD_NAMES.len()).collect(),
+            include_performance_metrics: true,
+            exectime_format: ExecTimeFormat::Integer,
+            extra_columns: Vec::new(),
+            column_order: false,
+            rows: Vec::new(),
+            stats: ExportStats::new(),
+        }
+    }
+}
+
+impl PreviewExporter {
+    /// 实际输出的列索引（与 CSV header 规则一致；设置 `column_order` 时含派生列）
+    fn field_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.ordered_indices.iter().copied().filter(|&i| {
+            (i != 14 || self.normalize)
+                && (!matches!(i, 11..=13) || self.include_performance_metrics)
+        })
+    }
+
+    /// 列名：基础字段 + 派生列
+    #[must_use]
+    pub fn columns(&self) -> Vec<String> {
+        let trailing = if self.column_order {
+            &[][..]
+        } else {
+            &self.extra_columns[..]
+        };
+        self.field_indices()
+            .map(|i| match FIELD_NAMES.get(i) {
+                Some(name) => (*name).to_string(),
+                None => self.extra_columns[i - FIELD_NAMES.len()].name.clone(),
+            })
+            .chain(trailing.iter().map(|c| c.name.clone()))
+            .collect()
+    }
+
+    /// 取出已收集的行（列顺序与 `columns()` 一致）
+    pub fn take_rows(&mut self) -> Vec<Vec<Value>> {
+        std::mem::take(&mut self.rows)
+    }
+}
+
+impl Exporter for PreviewExporter {
+    fn initialize(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn export(&mut self, sqllog: &Sqllog<'_>) -> Result<()> {
+        let meta = sqllog.parse_meta();
+        let pm = sqllog.parse_performance_metrics();
+        self.export_one_derived(sqllog, &meta, &pm, None, &[])
+    }
+
+    fn export_one_preparsed(
+        &mut self,
+        sqllog: &Sqllog<'_>,
+        meta: &MetaParts<'_>,
+        pm: &PerformanceMetrics<'_>,
+        normalized: Option<&str>,
+    ) -> Result<()> {
+        self.export_one_derived(sqllog, meta, pm, normalized, &[])
+    }
+
+    fn export_one_derived(
+        &mut self,
+        sqllog: &Sqllog<'_>,
+        meta: &MetaParts<'_>,
+        pm: &PerformanceMetrics<'_>,
+        normalized: Option<&str>,
+        extras: &[DerivedValue],
+    ) -> Result<()> {
+        let has_metrics = pm.exec_id != 0 || pm.exectime > 0.0;
+        let text = |s: &str| Value::String(s.to_string());
+        let metric = |v: i64| {
+            if has_metrics {
+                Value::from(v)
+            } else {
+                Value::Null
+            }
+        };
+        let derived = |v: &DerivedValue| match v {
+            DerivedValue::Null => Value::Null,
+            DerivedValue::Int(n) => Value::from(*n),
+            DerivedValue::Text(t) => Value::String(t.to_string()),
+        };
+        let trailing = if self.column_order { &[][..] } else { extras };
+        let row = self
+            .field_indices()
+            .map(|i| match i {
+                0 => text(sqllog.ts.as_ref()),
+                1 => Value::from(meta.ep),
+                2 => text(meta.sess_id.as_ref()),
+                3 => text(meta.thrd_id.as_ref()),
+                4 => text(meta.username.as_ref()),
+                5 => text(meta.trxid.as_ref()),
+                6 => text(meta.statement.as_ref()),
+                7 => text(meta.appname.as_ref()),
+                8 => text(strip_ip_prefix(meta.client_ip.as_ref())),
+                9 => sqllog.tag.as_deref().map_or(Value::Null, text),
+                10 => text(pm.sql.as_ref()),
+                11 if self.exectime_format == ExecTimeFormat::Decimal && has_metrics => {
+                    Value::from((f64::from(pm.exectime) * 1000.0).round() / 1000.0)
+                }
+                11 => metric(f32_ms_to_i64(pm.exectime)),
+                12 => metric(i64::from(pm.rowcount)),
+                13 => metric(pm.exec_id),
+                14 => normalized.map_or(Value::Null, text),
+                i => extras
+                    .get(i - FIELD_NAMES.len())
+                    .map_or(Value::Null, derived),
+            })
+            .chain(trailing.iter().map(derived))
+            .collect();
+        self.rows.push(row);
+        self.stats.record_success();
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn stats_snapshot(&self) -> Option<ExportStats> {
+        Some(self.stats)
+    }
+}
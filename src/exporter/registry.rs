@@ -0,0 +1,365 @@
+//! 第三方导出器注册表（`[exporter.custom]`）。
+//!
+//! 内置导出器在 [`ExporterManager::from_config`](super::ExporterManager::from_config) 中按配置段直接构造；
+//! 以库方式嵌入的下游 crate 可在运行前调用 [`register`] 登记自己的 [`Exporter`] 实现，
+//! 配置中以 `[exporter.custom]` 的 `name` 选用，段内其余键原样交给工厂函数解析。
+//! 注册表为进程级全局表，同名重复注册时后者覆盖前者。
+//!
+//! ```no_run
+//! use dm_database_sqllog2db::config::CustomExporter;
+//! use dm_database_sqllog2db::exporter::registry;
+//! # fn build(_: &CustomExporter) -> dm_database_sqllog2db::error::Result<Box<dyn dm_database_sqllog2db::Exporter + Send>> { unimplemented!() }
+//!
+//! registry::register("kafka", build);
+//! ```
+use super::{ExportStats, Exporter, LoadRecord};
+use crate::config::CustomExporter;
+use crate::error::{ConfigError, Error, Result};
+use crate::features::DerivedValue;
+use dm_database_parser_sqllog::{MetaParts, PerformanceMetrics, Sqllog};
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, PoisonError, RwLock};
+
+/// 导出器工厂：由 `[exporter.custom]` 配置段构造导出器实例
+pub type ExporterFactory =
+    dyn Fn(&CustomExporter) -> Result<Box<dyn Exporter + Send>> + Send + Sync;
+
+static REGISTRY: LazyLock<RwLock<HashMap<String, Arc<ExporterFactory>>>> =
+    LazyLock::new(RwLock::default);
+
+/// 以 `name` 登记导出器工厂；二进制目标不注册任何第三方导出器
+#[allow(dead_code)]
+pub fn register<F>(name: impl Into<String>, factory: F)
+where
+    F: Fn(&CustomExporter) -> Result<Box<dyn Exporter + Send>> + Send + Sync + 'static,
+{
+    REGISTRY
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(name.into(), Arc::new(factory));
+}
+
+#[must_use]
+pub fn is_registered(name: &str) -> bool {
+    REGISTRY
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .contains_key(name)
+}
+
+/// 已注册的名称（按字母序）
+#[must_use]
+pub fn registered() -> Vec<String> {
+    let mut names: Vec<String> = REGISTRY
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .keys()
+        .cloned()
+        .collect();
+    names.sort();
+    names
+}
+
+/// 已注册名称的展示文本（`features` 与配置报错），没有时为 `none`
+pub(crate) fn registered_label() -> String {
+    let names = registered();
+    if names.is_empty() {
+        "none".to_string()
+    } else {
+        names.join(", ")
+    }
+}
+
+/// 未注册名称的配置错误
+pub(crate) fn unregistered(name: &str) -> Error {
+    Error::Config(ConfigError::InvalidValue {
+        field: "exporter.custom.name".to_string(),
+        value: name.to_string(),
+        reason: format!(
+            "no exporter registered under this name (registered: {}); custom exporters are provided by programs embedding sqllog2db as a library",
+            registered_label()
+        ),
+    })
+}
+
+/// 按配置构造已注册的导出器
+pub(crate) fn create(config: &CustomExporter) -> Result<PluginExporter> {
+    let factory = REGISTRY
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&config.name)
+        .cloned()
+        .ok_or_else(|| unregistered(&config.name))?;
+    Ok(PluginExporter {
+        name: config.name.clone(),
+        inner: factory(config)?,
+    })
+}
+
+/// 注册表构造的导出器实例（[`super::ExporterKind::Custom`]）
+pub struct PluginExporter {
+    name: String,
+    inner: Box<dyn Exporter + Send>,
+}
+
+impl PluginExporter {
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl std::fmt::Debug for PluginExporter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PluginExporter")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Exporter for PluginExporter {
+    fn initialize(&mut self) -> Result<()> {
+        self.inner.initialize()
+    }
+
+    fn export(&mut self, sqllog: &Sqllog<'_>) -> Result<()> {
+        self.inner.export(sqllog)
+    }
+
+    fn export_one_normalized(
+        &mut self,
+        sqllog: &Sqllog<'_>,
+        normalized: Option<&str>,
+    ) -> Result<()> {
+        self.inner.export_one_normalized(sqllog, normalized)
+    }
+
+    fn export_one_preparsed(
+        &mut self,
+        sqllog: &Sqllog<'_>,
+        meta: &MetaParts<'_>,
+        pm: &PerformanceMetrics<'_>,
+        normalized: Option<&str>,
+    ) -> Result<()> {
+        self.inner
+            .export_one_preparsed(sqllog, meta, pm, normalized)
+    }
+
+    fn export_one_derived(
+        &mut self,
+        sqllog: &Sqllog<'_>,
+        meta: &MetaParts<'_>,
+        pm: &PerformanceMetrics<'_>,
+        normalized: Option<&str>,
+        extras: &[DerivedValue],
+    ) -> Result<()> {
+        self.inner
+            .export_one_derived(sqllog, meta, pm, normalized, extras)
+    }
+
+    fn finalize(&mut self) -> Result<()> {
+        self.inner.finalize()
+    }
+
+    fn flush_chunk(&mut self) -> Result<()> {
+        self.inner.flush_chunk()
+    }
+
+    fn set_source_ts(&mut self, ts: &str) {
+        self.inner.set_source_ts(ts);
+    }
+
+    fn discard_batch(&mut self) -> Result<usize> {
+        self.inner.discard_batch()
+    }
+
+    fn stats_snapshot(&self) -> Option<ExportStats> {
+        self.inner.stats_snapshot()
+    }
+
+    fn write_template_stats(
+        &mut self,
+        stats: &[crate::features::TemplateStats],
+        final_path: Option<&std::path::Path>,
+    ) -> Result<()> {
+        self.inner.write_template_stats(stats, final_path)
+    }
+
+    fn write_load_record(&mut self, load: &LoadRecord) -> Result<()> {
+        self.inner.write_load_record(load)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    /// 只计数的导出器，计数经共享原子量交回测试
+    struct Counting {
+        seen: Arc<AtomicUsize>,
+        stats: ExportStats,
+    }
+
+    impl Exporter for Counting {
+        fn initialize(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn export(&mut self, _sqllog: &Sqllog<'_>) -> Result<()> {
+            self.seen.fetch_add(1, Ordering::Relaxed);
+            self.stats.record_success();
+            Ok(())
+        }
+
+        fn finalize(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn stats_snapshot(&self) -> Option<ExportStats> {
+            Some(self.stats)
+        }
+    }
+
+    #[test]
+    fn test_registered_exporter_runs_from_custom_section() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.log"), crate::testing::fixture_log(7)).unwrap();
+        let toml = format!(
+            "[sqllog]\ndirectory = \"{}\"\n[exporter.custom]\nname = \"test-counting\"\ntopic = \"sqllog\"\n",
+            dir.path().to_string_lossy().replace('\\', "/"),
+        );
+        let cfg: Config = toml::from_str(&toml).unwrap();
+        let err = cfg.validate().unwrap_err().to_string();
+        assert!(err.contains("exporter.custom.name"), "{err}");
+
+        let seen = Arc::new(AtomicUsize::new(0));
+        let shared = Arc::clone(&seen);
+        register("test-counting", move |cfg: &CustomExporter| {
+            assert_eq!(cfg.options["topic"].as_str(), Some("sqllog"));
+            Ok(Box::new(Counting {
+                seen: Arc::clone(&shared),
+                stats: ExportStats::new(),
+            }) as Box<dyn Exporter + Send>)
+        });
+        assert!(registered().contains(&"test-counting".to_string()));
+        cfg.validate().unwrap();
+
+        let summary = crate::cli::run::handle_run(
+            &cfg,
+            None,
+            false,
+            true,
+            &Arc::new(AtomicBool::new(false)),
+            80,
+            false,
+            None,
+            1,
+            None,
+        )
+        .unwrap();
+        assert_eq!(seen.load(Ordering::Relaxed), 7);
+        assert_eq!(summary.records, 7);
+        assert_eq!(summary.exporters[0].name, "test-counting");
+    }
+
+    /// 每第三条记录写入失败的导出器
+    struct Flaky {
+        calls: usize,
+        stats: ExportStats,
+    }
+
+    impl Exporter for Flaky {
+        fn initialize(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn export(&mut self, _sqllog: &Sqllog<'_>) -> Result<()> {
+            self.calls += 1;
+            if self.calls % 3 == 0 {
+                return Err(Error::Export(crate::error::ExportError::WriteFailed {
+                    path: "flaky".into(),
+                    reason: "broken".into(),
+                }));
+            }
+            self.stats.record_success();
+            Ok(())
+        }
+
+        fn finalize(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn stats_snapshot(&self) -> Option<ExportStats> {
+            Some(self.stats)
+        }
+    }
+
+    /// `on_error = "skip_record"` / `skip` / `log` 跳过失败的记录并计入摘要与警告，默认 `abort` 中止
+    #[test]
+    fn test_on_error_skip_record_continues_and_abort_stops() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.log"), crate::testing::fixture_log(7)).unwrap();
+        register("test-flaky", |_: &CustomExporter| {
+            Ok(Box::new(Flaky {
+                calls: 0,
+                stats: ExportStats::new(),
+            }) as Box<dyn Exporter + Send>)
+        });
+        let run = |on_error: &str| {
+            let toml = format!(
+                "[sqllog]\ndirectory = \"{}\"\n[exporter.custom]\nname = \"test-flaky\"\non_error = \"{on_error}\"\n",
+                dir.path().to_string_lossy().replace('\\', "/"),
+            );
+            let cfg: Config = toml::from_str(&toml).unwrap();
+            cfg.validate()?;
+            crate::cli::run::handle_run(
+                &cfg,
+                None,
+                false,
+                true,
+                &Arc::new(AtomicBool::new(false)),
+                80,
+                false,
+                None,
+                1,
+                None,
+            )
+        };
+
+        let summary = run("skip_record").unwrap();
+        assert_eq!(summary.exporters[0].exported, 5);
+        assert_eq!(summary.exporters[0].skipped, 2);
+        assert!(
+            summary
+                .warnings
+                .iter()
+                .any(|w| w.kind == crate::warnings::WarningKind::ExportSkipped)
+        );
+
+        // `skip` 为 `skip_record` 的别名
+        let summary = run("skip").unwrap();
+        assert_eq!(summary.exporters[0].exported, 5);
+        assert_eq!(summary.exporters[0].skipped, 2);
+
+        // `log` 把失败记录写入错误日志，计为失败而非跳过
+        let summary = run("log").unwrap();
+        assert_eq!(summary.exporters[0].exported, 5);
+        assert_eq!(
+            (summary.exporters[0].skipped, summary.exporters[0].failed),
+            (0, 2)
+        );
+        assert!(
+            summary
+                .warnings
+                .iter()
+                .any(|w| w.kind == crate::warnings::WarningKind::ExportSkipped)
+        );
+
+        let err = run("abort").unwrap_err();
+        assert!(err.to_string().contains("broken"), "{err}");
+        assert!(run("skip_everything").is_err());
+    }
+}
@@ -0,0 +1,512 @@
+//! 基于 strftime 模式的输出文件命名与周期滚动。
+//!
+//! 文件型导出器的输出路径含 `%` 时视为时间模式（如 `outputs/sqllog-%Y%m%d-%H.csv`）：
+//! 以记录自身的 `ts` 决定所属周期，`ts` 跨越周期边界时切换到新文件。
+//! 周期粒度由模式中最细的时间说明符推导（`%M` → 分钟，`%H` → 小时，`%d` → 天，`%V` → 周……）。
+//!
+//! 另有按大小 / 记录数的分段切换（[`PartRotation`]）：`sqllog.csv` → `sqllog.csv.part-0001`、`part-0002`……
+use chrono::NaiveDateTime;
+use chrono::format::{Item, StrftimeItems};
+use std::path::{Path, PathBuf};
+
+/// 滚动周期粒度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RolloverPeriod {
+    /// 模式中无时间说明符：整个运行只产生一个文件
+    Once,
+    Year,
+    Month,
+    /// 周编号（`%U` / `%W` / `%V`）与 ISO 周年（`%G`）：周边界不落在时间戳前缀上，
+    /// 按天检查、文件名变化时才切换
+    Week,
+    Day,
+    Hour,
+    Minute,
+    Second,
+}
+
+impl RolloverPeriod {
+    /// 周期 key 在 DM 时间戳（`YYYY-MM-DD HH:MM:SS.mmm`）中的前缀长度。
+    /// 同一周期内的记录前缀相同，热路径只需一次字符串比较。
+    #[must_use]
+    pub fn ts_prefix_len(self) -> usize {
+        match self {
+            Self::Once => 0,
+            Self::Year => 4,
+            Self::Month => 7,
+            Self::Week | Self::Day => 10,
+            Self::Hour => 13,
+            Self::Minute => 16,
+            Self::Second => 19,
+        }
+    }
+
+    /// 从 strftime 模式推导最细粒度
+    #[must_use]
+    pub fn from_pattern(pattern: &str) -> Self {
+        specifiers(pattern)
+            .map(|spec| match spec {
+                'Y' | 'y' | 'C' => Self::Year,
+                'm' | 'b' | 'B' | 'h' => Self::Month,
+                'U' | 'W' | 'V' | 'G' | 'g' => Self::Week,
+                'd' | 'e' | 'j' | 'F' | 'D' | 'x' | 'a' | 'A' | 'u' | 'w' => Self::Day,
+                'H' | 'k' | 'I' | 'l' | 'p' | 'P' => Self::Hour,
+                'M' | 'R' => Self::Minute,
+                'S' | 'T' | 'X' | 'c' | 's' => Self::Second,
+                _ => Self::Once,
+            })
+            .max()
+            .unwrap_or(Self::Once)
+    }
+}
+
+/// 模式中各 `%` 说明符的首字符（跳过 `%-d` / `%_H` / `%0M` 的 padding 修饰符）
+fn specifiers(pattern: &str) -> impl Iterator<Item = char> + '_ {
+    let mut chars = pattern.chars();
+    std::iter::from_fn(move || {
+        loop {
+            if chars.next()? != '%' {
+                continue;
+            }
+            let mut spec = chars.next()?;
+            if matches!(spec, '-' | '_' | '0') {
+                spec = chars.next()?;
+            }
+            return Some(spec);
+        }
+    })
+}
+
+/// 输出路径是否为 strftime 时间模式
+#[must_use]
+pub fn is_time_pattern(path: &str) -> bool {
+    path.contains('%')
+}
+
+/// 校验 strftime 模式是否合法，返回错误描述
+pub fn validate_pattern(pattern: &str) -> std::result::Result<(), String> {
+    let items = StrftimeItems::new(pattern)
+        .parse()
+        .map_err(|e| format!("invalid strftime pattern: {e}"))?;
+    if items.iter().any(|i| matches!(i, Item::Error)) {
+        return Err("invalid strftime pattern".to_string());
+    }
+    Ok(())
+}
+
+/// 校验用作输出文件名 / 索引名的时间模式：在 [`validate_pattern`] 之外，
+/// 拒绝无法对应滚动周期的亚秒说明符（`%f` / `%.3f` / `%3f`），
+/// 以及日志时间（不带时区）无法展开的时区说明符（`%z` / `%:z` / `%Z` 等，展开时会 panic）
+pub fn validate_rollover_pattern(pattern: &str) -> std::result::Result<(), String> {
+    validate_pattern(pattern)?;
+    if let Some(spec) = specifiers(pattern).find(|c| matches!(c, 'z' | 'Z' | ':' | '#')) {
+        return Err(format!(
+            "timezone specifier '%{spec}...' cannot be used in an output name; log timestamps carry no offset"
+        ));
+    }
+    if let Some(spec) = specifiers(pattern).find(|c| *c == 'f' || *c == '.' || c.is_ascii_digit()) {
+        return Err(format!(
+            "sub-second specifier '%{spec}...' cannot be used in an output name; the finest rollover period is one second"
+        ));
+    }
+    Ok(())
+}
+
+/// 解析 DM 时间戳的秒级部分（`YYYY-MM-DD HH:MM:SS`）
+fn parse_ts(ts: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(ts.get(..19)?, "%Y-%m-%d %H:%M:%S").ok()
+}
+
+/// 周期滚动调度器：供 CSV 等文件型导出器共享。
+///
+/// 每条记录调用 [`RolloverScheduler::next_path`]，仅在进入新周期时返回新路径。
+#[derive(Debug, Clone)]
+pub struct RolloverScheduler {
+    pattern: String,
+    period: RolloverPeriod,
+    current_key: Option<String>,
+    current_path: Option<PathBuf>,
+    /// 下一条记录的原始日志时间（见 [`RolloverScheduler::set_source_ts`]）
+    source_ts: String,
+    has_source: bool,
+}
+
+impl RolloverScheduler {
+    #[must_use]
+    pub fn new(pattern: impl Into<String>) -> Self {
+        let pattern = pattern.into();
+        let period = RolloverPeriod::from_pattern(&pattern);
+        Self {
+            pattern,
+            period,
+            current_key: None,
+            current_path: None,
+            source_ts: String::new(),
+            has_source: false,
+        }
+    }
+
+    /// `[features.time]` 改写导出的 `ts` 后，改写后的格式（ISO 8601、毫秒时间戳、自定义模式）
+    /// 无法按日志时间解析；调用方在导出前传入原始日志时间，只对下一次 [`Self::next_path`] 生效
+    #[inline]
+    pub fn set_source_ts(&mut self, ts: &str) {
+        self.source_ts.clear();
+        self.source_ts.push_str(ts);
+        self.has_source = true;
+    }
+
+    /// 若 `ts` 进入新周期，返回该周期的输出路径；同一周期内返回 `None`。
+    ///
+    /// 时间戳前缀变化时重新展开模式，展开结果与当前路径相同（如 `%V` 在同一周内跨天）也返回 `None`。
+    #[inline]
+    pub fn next_path(&mut self, ts: &str) -> Option<PathBuf> {
+        if !std::mem::take(&mut self.has_source) {
+            return self.roll_to(ts);
+        }
+        let source = std::mem::take(&mut self.source_ts);
+        let next = self.roll_to(&source);
+        self.source_ts = source;
+        next
+    }
+
+    fn roll_to(&mut self, ts: &str) -> Option<PathBuf> {
+        let key = ts.get(..self.period.ts_prefix_len()).unwrap_or(ts);
+        if self.current_key.as_deref() == Some(key) {
+            return None;
+        }
+        self.current_key = Some(key.to_string());
+        let path = self.format_path(ts);
+        if self.current_path.as_ref() == Some(&path) {
+            return None;
+        }
+        self.current_path = Some(path.clone());
+        Some(path)
+    }
+
+    /// 按记录时间戳格式化输出路径；时间戳无法解析时退回当前本地时间。
+    #[must_use]
+    pub fn format_path(&self, ts: &str) -> PathBuf {
+        let dt = parse_ts(ts).unwrap_or_else(|| chrono::Local::now().naive_local());
+        PathBuf::from(dt.format(&self.pattern).to_string())
+    }
+
+    /// 以当前本地时间格式化路径（preflight 可写性检查使用）
+    #[must_use]
+    pub fn format_now(&self) -> PathBuf {
+        PathBuf::from(
+            chrono::Local::now()
+                .naive_local()
+                .format(&self.pattern)
+                .to_string(),
+        )
+    }
+}
+
+/// 解析 `max_file_size` 之类的容量值：纯数字为字节，单位 `K` / `M` / `G` / `T`
+/// （可带 `B` / `iB` 后缀，大小写不敏感）均按 1024 进制，如 `"512MB"`、`"2 GiB"`
+pub fn parse_size(value: &str) -> std::result::Result<u64, String> {
+    let v = value.trim();
+    let split = v.find(|c: char| !c.is_ascii_digit()).unwrap_or(v.len());
+    let (num, unit) = v.split_at(split);
+    let num: u64 = num
+        .parse()
+        .map_err(|_| "expected a size such as 512MB or 2GB".to_string())?;
+    let shift = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 0,
+        "K" | "KB" | "KIB" => 10,
+        "M" | "MB" | "MIB" => 20,
+        "G" | "GB" | "GIB" => 30,
+        "T" | "TB" | "TIB" => 40,
+        _ => return Err(format!("unknown size unit: {}", unit.trim())),
+    };
+    num.checked_mul(1 << shift)
+        .ok_or_else(|| "size is too large".to_string())
+}
+
+/// 分段文件路径：`<base>.part-NNNN`（序号从 1 开始）
+#[must_use]
+pub fn part_path(base: &Path, part: u32) -> PathBuf {
+    let mut name = base.as_os_str().to_owned();
+    name.push(format!(".part-{part:04}"));
+    PathBuf::from(name)
+}
+
+/// 按大小 / 记录数的分段调度器：当前分段达到任一上限后，下一条记录写入新分段。
+///
+/// 上限在写入后检查，单个分段最多超出一条记录；大小按写入前（压缩前）的字节数计算。
+#[derive(Debug, Clone)]
+pub struct PartRotation {
+    base: PathBuf,
+    max_bytes: Option<u64>,
+    max_records: Option<u64>,
+    part: u32,
+    bytes: u64,
+    records: u64,
+}
+
+impl PartRotation {
+    #[must_use]
+    pub fn new(base: impl Into<PathBuf>, max_bytes: Option<u64>, max_records: Option<u64>) -> Self {
+        Self {
+            base: base.into(),
+            max_bytes,
+            max_records,
+            part: 0,
+            bytes: 0,
+            records: 0,
+        }
+    }
+
+    /// 分段的命名基准（即配置的输出路径）
+    #[must_use]
+    pub fn base(&self) -> &Path {
+        &self.base
+    }
+
+    /// 首个分段路径：追加模式接在已有分段之后，否则从 `part-0001` 开始
+    pub fn first_path(&mut self, append: bool) -> PathBuf {
+        self.part = 1;
+        if append {
+            while part_path(&self.base, self.part).exists() {
+                self.part += 1;
+            }
+        }
+        part_path(&self.base, self.part)
+    }
+
+    /// 记录一次写入
+    #[inline]
+    pub fn add(&mut self, bytes: usize) {
+        self.bytes += bytes as u64;
+        self.records += 1;
+    }
+
+    /// 当前分段是否已达上限
+    #[inline]
+    #[must_use]
+    pub fn is_full(&self) -> bool {
+        self.max_records.is_some_and(|m| self.records >= m)
+            || self.max_bytes.is_some_and(|m| self.bytes >= m)
+    }
+
+    /// 切换到下一个分段，返回其路径
+    pub fn advance(&mut self) -> PathBuf {
+        self.part += 1;
+        self.bytes = 0;
+        self.records = 0;
+        part_path(&self.base, self.part)
+    }
+
+    /// 删除上次运行留下、序号大于当前分段的旧分段，返回删除数量
+    pub fn remove_stale(&self) -> std::io::Result<usize> {
+        let mut removed = 0;
+        let mut part = self.part + 1;
+        loop {
+            let path = part_path(&self.base, part);
+            if !path.exists() {
+                return Ok(removed);
+            }
+            std::fs::remove_file(crate::paths::for_io(&path))?;
+            removed += 1;
+            part += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_period_from_pattern() {
+        assert_eq!(
+            RolloverPeriod::from_pattern("out/sqllog.csv"),
+            RolloverPeriod::Once
+        );
+        assert_eq!(
+            RolloverPeriod::from_pattern("sqllog-%Y.csv"),
+            RolloverPeriod::Year
+        );
+        assert_eq!(
+            RolloverPeriod::from_pattern("sqllog-%Y%m%d.csv"),
+            RolloverPeriod::Day
+        );
+        assert_eq!(
+            RolloverPeriod::from_pattern("sqllog-%Y%m%d-%H.csv"),
+            RolloverPeriod::Hour
+        );
+        assert_eq!(
+            RolloverPeriod::from_pattern("sqllog-%F-%-H%M.csv"),
+            RolloverPeriod::Minute
+        );
+        assert_eq!(
+            RolloverPeriod::from_pattern("out-%Y-W%V.csv"),
+            RolloverPeriod::Week
+        );
+        assert_eq!(
+            RolloverPeriod::from_pattern("out-%Y%m-%W.csv"),
+            RolloverPeriod::Week
+        );
+        assert_eq!(
+            RolloverPeriod::from_pattern("out-%F-%H%M%S.csv"),
+            RolloverPeriod::Second
+        );
+        assert_eq!(
+            RolloverPeriod::from_pattern("out-%T.csv"),
+            RolloverPeriod::Second
+        );
+    }
+
+    #[test]
+    fn test_next_path_rolls_weekly() {
+        // 2025-01-12 为周日，2025-01-13 周一开始 ISO 第 3 周
+        let mut s = RolloverScheduler::new("out-%G-W%V.csv");
+        assert_eq!(
+            s.next_path("2025-01-06 00:00:00.000"),
+            Some(PathBuf::from("out-2025-W02.csv"))
+        );
+        assert_eq!(s.next_path("2025-01-08 12:00:00.000"), None);
+        assert_eq!(s.next_path("2025-01-12 23:59:59.999"), None);
+        assert_eq!(
+            s.next_path("2025-01-13 00:00:00.000"),
+            Some(PathBuf::from("out-2025-W03.csv"))
+        );
+        // ISO 周年在 12 月 29 日（周一）就进入 2026
+        assert_eq!(
+            s.next_path("2025-12-29 08:00:00.000"),
+            Some(PathBuf::from("out-2026-W01.csv"))
+        );
+    }
+
+    #[test]
+    fn test_next_path_rolls_every_second() {
+        let mut s = RolloverScheduler::new("out-%H%M%S.csv");
+        assert!(s.next_path("2025-01-15 10:30:28.001").is_some());
+        assert_eq!(s.next_path("2025-01-15 10:30:28.999"), None);
+        assert_eq!(
+            s.next_path("2025-01-15 10:30:29.000"),
+            Some(PathBuf::from("out-103029.csv"))
+        );
+    }
+
+    /// 导出的 `ts` 被 `[features.time]` 改写时按原始日志时间滚动；原始时间只对下一条生效
+    #[test]
+    fn test_next_path_uses_source_ts() {
+        let mut s = RolloverScheduler::new("out-%Y%m%d-%H.csv");
+        s.set_source_ts("2025-01-15 10:30:28.001");
+        assert_eq!(
+            s.next_path("1736908228001"),
+            Some(PathBuf::from("out-20250115-10.csv"))
+        );
+        s.set_source_ts("2025-01-15 11:00:00.000");
+        assert_eq!(
+            s.next_path("2025-01-15T03:00:00.000Z"),
+            Some(PathBuf::from("out-20250115-11.csv"))
+        );
+        assert_eq!(
+            s.next_path("2025-01-15 12:00:00.000"),
+            Some(PathBuf::from("out-20250115-12.csv"))
+        );
+    }
+
+    #[test]
+    fn test_validate_rollover_pattern_rejects_sub_second() {
+        assert!(validate_rollover_pattern("out-%Y-W%V.csv").is_ok());
+        assert!(validate_rollover_pattern("out-%H%M%S.csv").is_ok());
+        assert!(validate_rollover_pattern("out-%S%f.csv").is_err());
+        assert!(validate_rollover_pattern("out-%S%.3f.csv").is_err());
+        assert!(validate_rollover_pattern("out-%S%3f.csv").is_err());
+    }
+
+    /// 时区说明符在不带时区的日志时间上展开会 panic，校验阶段即拒绝
+    #[test]
+    fn test_validate_rollover_pattern_rejects_timezone() {
+        for pattern in [
+            "out-%Y%m%d%z.csv",
+            "out-%Y%m%d%Z.csv",
+            "out-%Y%m%d%:z.csv",
+            "out-%Y%m%d%::z.csv",
+            "out-%Y%m%d%:::z.csv",
+        ] {
+            let err = validate_rollover_pattern(pattern).unwrap_err();
+            assert!(err.contains("timezone specifier"), "{pattern}: {err}");
+        }
+        assert!(validate_rollover_pattern("out-%Y%m%d%%z.csv").is_ok());
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024"), Ok(1024));
+        assert_eq!(parse_size("512MB"), Ok(512 << 20));
+        assert_eq!(parse_size("2 GiB"), Ok(2 << 30));
+        assert_eq!(parse_size("4k"), Ok(4096));
+        assert!(parse_size("1.5GB").is_err());
+        assert!(parse_size("10XB").is_err());
+        assert!(parse_size("").is_err());
+    }
+
+    #[test]
+    fn test_part_rotation() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let base = dir.path().join("out.csv");
+        let mut r = PartRotation::new(&base, None, Some(2));
+        assert_eq!(r.first_path(false), dir.path().join("out.csv.part-0001"));
+        r.add(10);
+        assert!(!r.is_full());
+        r.add(10);
+        assert!(r.is_full());
+        assert_eq!(r.advance(), dir.path().join("out.csv.part-0002"));
+        assert!(!r.is_full());
+
+        // 追加模式接在已有分段之后；旧分段清理只删除更大的序号
+        std::fs::write(part_path(&base, 1), "").unwrap();
+        std::fs::write(part_path(&base, 2), "").unwrap();
+        std::fs::write(part_path(&base, 3), "").unwrap();
+        let mut r = PartRotation::new(&base, Some(1), None);
+        assert_eq!(r.first_path(true), part_path(&base, 4));
+        r.first_path(false);
+        assert_eq!(r.remove_stale().unwrap(), 2);
+        assert!(part_path(&base, 1).exists());
+        assert!(!part_path(&base, 2).exists());
+    }
+
+    #[test]
+    fn test_is_time_pattern() {
+        assert!(is_time_pattern("sqllog-%Y%m%d.csv"));
+        assert!(!is_time_pattern("outputs/sqllog.csv"));
+    }
+
+    #[test]
+    fn test_validate_pattern() {
+        assert!(validate_pattern("sqllog-%Y%m%d-%H.csv").is_ok());
+        assert!(validate_pattern("sqllog-%Q.csv").is_err());
+    }
+
+    #[test]
+    fn test_next_path_rolls_on_hour_boundary() {
+        let mut s = RolloverScheduler::new("sqllog-%Y%m%d-%H.csv");
+        assert_eq!(
+            s.next_path("2025-01-15 10:30:28.001"),
+            Some(PathBuf::from("sqllog-20250115-10.csv"))
+        );
+        assert_eq!(s.next_path("2025-01-15 10:59:59.999"), None);
+        assert_eq!(
+            s.next_path("2025-01-15 11:00:00.000"),
+            Some(PathBuf::from("sqllog-20250115-11.csv"))
+        );
+    }
+
+    #[test]
+    fn test_next_path_once_only_first_record() {
+        let mut s = RolloverScheduler::new("sqllog-%%.csv");
+        assert!(s.next_path("2025-01-15 10:30:28.001").is_some());
+        assert!(s.next_path("2026-01-15 10:30:28.001").is_none());
+    }
+
+    #[test]
+    fn test_format_path_invalid_ts_falls_back_to_now() {
+        let s = RolloverScheduler::new("sqllog-%Y.csv");
+        let p = s.format_path("garbage");
+        assert!(p.to_string_lossy().starts_with("sqllog-"));
+    }
+}
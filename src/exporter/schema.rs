@@ -0,0 +1,122 @@
+//! 目标表结构比对与迁移（数据库导出器）。
+//!
+//! 追加 / 清空模式下目标表可能由旧版本或不同列配置创建（如缺少 `exec_id`），直接写入会中途失败，
+//! 或按位置插入时列错位。初始化时按 `PRAGMA table_info` 读取现有列，与本次导出的列比对：
+//! 缺列时启用迁移（`run --migrate` / `exporter.sqlite.migrate`）则 `ALTER TABLE ... ADD COLUMN` 补齐，
+//! 否则报错并列出缺少的列；表中多出的列保留不动，写入时按列名插入。
+use rusqlite::Connection;
+
+/// 现有表中的一列
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExistingColumn {
+    pub name: String,
+    /// 不允许 NULL 且没有默认值：本次导出不写该列时插入会失败
+    pub required: bool,
+}
+
+/// 现有表与本次导出列的差异
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaDiff {
+    /// 表中缺少的列：`(列名, 列定义类型)`
+    pub missing: Vec<(String, String)>,
+    /// 表中多出、且必须赋值的列（本次导出不产生，插入必然失败）
+    pub required_extra: Vec<String>,
+    /// 现有列与导出列的名称和顺序完全一致，可按位置插入
+    pub same_layout: bool,
+}
+
+/// 读取表的现有列；表不存在时返回 `None`
+pub fn table_columns(
+    conn: &Connection,
+    table: &str,
+) -> rusqlite::Result<Option<Vec<ExistingColumn>>> {
+    let mut stmt =
+        conn.prepare("SELECT name, \"notnull\", dflt_value FROM pragma_table_info(?1)")?;
+    let columns = stmt
+        .query_map([table], |row| {
+            let notnull: bool = row.get(1)?;
+            let default: Option<String> = row.get(2)?;
+            Ok(ExistingColumn {
+                name: row.get(0)?,
+                required: notnull && default.is_none(),
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok((!columns.is_empty()).then_some(columns))
+}
+
+/// 比对现有列与本次导出的列（`(列名, 列定义类型)`，按写入顺序）；列名不区分大小写
+#[must_use]
+pub fn diff(existing: &[ExistingColumn], expected: &[(String, String)]) -> SchemaDiff {
+    let has = |name: &str| existing.iter().any(|c| c.name.eq_ignore_ascii_case(name));
+    let wants = |name: &str| expected.iter().any(|(n, _)| n.eq_ignore_ascii_case(name));
+    SchemaDiff {
+        missing: expected
+            .iter()
+            .filter(|(name, _)| !has(name))
+            .cloned()
+            .collect(),
+        required_extra: existing
+            .iter()
+            .filter(|c| c.required && !wants(&c.name))
+            .map(|c| c.name.clone())
+            .collect(),
+        same_layout: existing.len() == expected.len()
+            && existing
+                .iter()
+                .zip(expected)
+                .all(|(c, (name, _))| c.name.eq_ignore_ascii_case(name)),
+    }
+}
+
+/// 补列语句。`SQLite` 不能给已有行的表添加无默认值的 NOT NULL 列，补上的列一律允许 NULL
+#[must_use]
+pub fn add_column_sql(table: &str, name: &str, decl_type: &str) -> String {
+    let ty = decl_type.trim_end_matches(" NOT NULL");
+    format!("ALTER TABLE \"{table}\" ADD COLUMN {name} {ty}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expected(cols: &[&str]) -> Vec<(String, String)> {
+        cols.iter()
+            .map(|c| ((*c).to_string(), "INTEGER NOT NULL".to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_introspect_diff_and_migrate() {
+        let conn = Connection::open_in_memory().unwrap();
+        assert_eq!(table_columns(&conn, "t").unwrap(), None);
+        conn.execute_batch(
+            "CREATE TABLE t (ts TEXT NOT NULL, ep INTEGER NOT NULL, note TEXT, \
+             legacy TEXT NOT NULL, flag INTEGER NOT NULL DEFAULT 0);
+             INSERT INTO t VALUES ('x', 1, NULL, 'y', 0);",
+        )
+        .unwrap();
+        let existing = table_columns(&conn, "t").unwrap().unwrap();
+        assert_eq!(existing.len(), 5);
+
+        let d = diff(&existing, &expected(&["TS", "ep", "exec_id"]));
+        assert_eq!(d.missing, expected(&["exec_id"]));
+        assert_eq!(d.required_extra, ["legacy"]);
+        assert!(!d.same_layout);
+
+        conn.execute(&add_column_sql("t", "exec_id", "INTEGER NOT NULL"), [])
+            .unwrap();
+        let existing = table_columns(&conn, "t").unwrap().unwrap();
+        let d = diff(
+            &existing,
+            &expected(&["ts", "ep", "note", "legacy", "flag", "exec_id"]),
+        );
+        assert_eq!(
+            d,
+            SchemaDiff {
+                same_layout: true,
+                ..SchemaDiff::default()
+            }
+        );
+    }
+}
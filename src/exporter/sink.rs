@@ -0,0 +1,111 @@
+//! 文本导出器的输出目标（sink）：与编码（CSV 各方言）解耦，按 `exporter.csv.file` 的写法选择。
+//!
+//! - 普通路径：本地文件（可配合时间模式滚动、`split_by` 拆分）
+//! - `-`：标准输出
+//! - `tcp://host:port`：连接到远端并以流写出（如接 `nc -l`、Logstash / Vector 的 TCP 输入）
+//!
+//! 非本地文件的目标（[`is_stream`]）不支持追加、滚动、拆分与模板伴随文件。
+use super::is_stdout;
+use std::fs::File;
+use std::io::{self, Write};
+use std::net::TcpStream;
+use std::path::Path;
+
+/// TCP 输出目标的前缀
+pub const TCP_SCHEME: &str = "tcp://";
+
+/// `tcp://host:port` 形式的输出目标，返回 `host:port`
+#[must_use]
+pub fn tcp_addr(path: &Path) -> Option<&str> {
+    path.to_str()?.strip_prefix(TCP_SCHEME)
+}
+
+/// 输出目标是否为流（标准输出或 TCP），而非本地文件
+#[must_use]
+pub fn is_stream(path: &Path) -> bool {
+    is_stdout(path) || tcp_addr(path).is_some()
+}
+
+/// 校验 `tcp://` 目标的地址部分，返回错误原因
+pub fn validate_tcp_addr(addr: &str) -> std::result::Result<(), String> {
+    let port = addr
+        .rsplit_once(':')
+        .filter(|(host, _)| !host.is_empty())
+        .map(|(_, port)| port)
+        .ok_or_else(|| "expected tcp://host:port".to_string())?;
+    port.parse::<u16>()
+        .map(|_| ())
+        .map_err(|_| format!("invalid port '{port}'"))
+}
+
+/// 已打开的输出目标
+#[derive(Debug)]
+pub(crate) enum Sink {
+    File(File),
+    Stdout(io::Stdout),
+    Tcp(TcpStream),
+}
+
+impl Sink {
+    /// 打开流式目标；`path` 为本地文件时返回 `None`，由调用方按文件打开
+    pub(crate) fn open_stream(path: &Path) -> Option<io::Result<Self>> {
+        if is_stdout(path) {
+            return Some(Ok(Self::Stdout(io::stdout())));
+        }
+        tcp_addr(path).map(|addr| TcpStream::connect(addr).map(Self::Tcp))
+    }
+}
+
+impl Write for Sink {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::File(f) => f.write(buf),
+            Self::Stdout(s) => s.write(buf),
+            Self::Tcp(t) => t.write(buf),
+        }
+    }
+
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        match self {
+            Self::File(f) => f.write_all(buf),
+            Self::Stdout(s) => s.write_all(buf),
+            Self::Tcp(t) => t.write_all(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::File(f) => f.flush(),
+            Self::Stdout(s) => s.flush(),
+            Self::Tcp(t) => t.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_targets() {
+        assert!(is_stream(Path::new("-")));
+        assert_eq!(
+            tcp_addr(Path::new("tcp://127.0.0.1:5170")),
+            Some("127.0.0.1:5170")
+        );
+        assert!(is_stream(Path::new("tcp://logs:5170")));
+        assert!(!is_stream(Path::new("out/tcp.csv")));
+        assert!(Sink::open_stream(Path::new("out.csv")).is_none());
+
+        assert!(validate_tcp_addr("logs:5170").is_ok());
+        assert!(validate_tcp_addr("[::1]:5170").is_ok());
+        assert!(validate_tcp_addr("logs").is_err());
+        assert!(validate_tcp_addr(":5170").is_err());
+        assert_eq!(
+            validate_tcp_addr("logs:http").unwrap_err(),
+            "invalid port 'http'"
+        );
+    }
+}
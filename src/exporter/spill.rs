@@ -0,0 +1,142 @@
+//! 网络导出器的磁盘溢出队列（`exporter.elasticsearch.spill_dir`）。
+//!
+//! 目标暂时不可用（连接失败、超时、HTTP 429 / 5xx）时，待发送的批次原样写成队列目录中的一个文件，
+//! 解析继续进行，不阻塞也不丢弃。之后每次发送前先按写入顺序重放积压的批次，成功一个删除一个；
+//! 导出器初始化时同样先重放，上次运行（或 `run --watch` 上一轮）遗留的批次因此在重启后补发。
+//! 队列总大小有上限，写满后不再溢出，按原错误中止导出。
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// 队列文件扩展名
+const EXT: &str = "spill";
+
+/// 重放时被目标拒绝的批次改名追加的后缀，不再重放
+const REJECTED_SUFFIX: &str = ".rejected";
+
+/// 按目录保存的有界批次队列；文件名为递增序号，字典序即写入顺序
+#[derive(Debug)]
+pub struct SpillQueue {
+    dir: PathBuf,
+    max_bytes: u64,
+    next: u64,
+    /// 积压的总字节数；打开时统计一次，之后随写入与删除增减
+    bytes: u64,
+}
+
+impl SpillQueue {
+    /// 打开（必要时创建）队列目录，序号接在已有文件之后
+    pub fn open(dir: &Path, max_bytes: u64) -> io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let mut queue = Self {
+            dir: dir.to_path_buf(),
+            max_bytes,
+            next: 0,
+            bytes: 0,
+        };
+        let pending = queue.pending()?;
+        queue.next = pending
+            .last()
+            .and_then(|p| p.file_stem()?.to_str()?.parse::<u64>().ok())
+            .map_or(0, |n| n + 1);
+        queue.bytes = pending
+            .iter()
+            .map(|p| std::fs::metadata(p).map(|m| m.len()))
+            .sum::<io::Result<u64>>()?;
+        Ok(queue)
+    }
+
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 积压的批次文件，按写入顺序排列
+    pub fn pending(&self) -> io::Result<Vec<PathBuf>> {
+        let mut files: Vec<PathBuf> = std::fs::read_dir(&self.dir)?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|e| e == EXT))
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+
+    /// 积压的总字节数
+    #[must_use]
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// 追加一个批次；超过大小上限时不写入并返回 `false`
+    pub fn push(&mut self, batch: &[u8]) -> io::Result<bool> {
+        if self.bytes + batch.len() as u64 > self.max_bytes {
+            return Ok(false);
+        }
+        let path = self.dir.join(format!("{:020}.{EXT}", self.next));
+        crate::temp::write_atomic(&path, batch)?;
+        self.next += 1;
+        self.bytes += batch.len() as u64;
+        Ok(true)
+    }
+
+    /// 删除一个已重放的批次文件，并从积压字节数中扣除
+    pub fn remove(&mut self, path: &Path) -> io::Result<()> {
+        let len = std::fs::metadata(path)?.len();
+        std::fs::remove_file(path)?;
+        self.bytes = self.bytes.saturating_sub(len);
+        Ok(())
+    }
+
+    /// 把重放时被目标拒绝的批次改名为 `*.spill.rejected` 移出队列，返回新路径。
+    /// 文件保留供人工排查，不再重放，也不计入积压字节数
+    pub fn reject(&mut self, path: &Path) -> io::Result<PathBuf> {
+        let len = std::fs::metadata(path)?.len();
+        let mut name = path.as_os_str().to_owned();
+        name.push(REJECTED_SUFFIX);
+        let target = PathBuf::from(name);
+        std::fs::rename(path, &target)?;
+        self.bytes = self.bytes.saturating_sub(len);
+        Ok(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spill_queue_order_bound_and_reopen() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("queue");
+        let mut queue = SpillQueue::open(&path, 10).unwrap();
+        assert!(queue.push(b"first").unwrap());
+        assert!(queue.push(b"two").unwrap());
+        // 5 + 3 + 4 > 10：写满后拒绝
+        assert!(!queue.push(b"full").unwrap());
+        assert_eq!(queue.bytes(), 8);
+
+        let mut reopened = SpillQueue::open(&path, 10).unwrap();
+        assert_eq!(reopened.bytes(), 8);
+        let pending = reopened.pending().unwrap();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(std::fs::read(&pending[0]).unwrap(), b"first");
+        reopened.remove(&pending[0]).unwrap();
+        assert_eq!(reopened.bytes(), 3);
+        assert!(reopened.push(b"x").unwrap());
+        assert_eq!(reopened.bytes(), 4);
+        let pending = reopened.pending().unwrap();
+        assert_eq!(std::fs::read(&pending[1]).unwrap(), b"x");
+
+        // 被拒绝的批次移出队列，重新打开后不再出现，序号不与其冲突
+        let moved = reopened.reject(&pending[0]).unwrap();
+        assert!(moved.to_string_lossy().ends_with(".spill.rejected"));
+        assert_eq!(std::fs::read(&moved).unwrap(), b"two");
+        assert_eq!(reopened.bytes(), 1);
+        let mut reopened = SpillQueue::open(&path, 10).unwrap();
+        assert_eq!(
+            (reopened.bytes(), reopened.pending().unwrap().len()),
+            (1, 1)
+        );
+        assert!(reopened.push(b"y").unwrap());
+        assert_eq!(reopened.pending().unwrap().len(), 2);
+    }
+}
@@ -1,11 +1,15 @@
 use super::strip_ip_prefix;
 use super::{ExportStats, Exporter};
 use crate::error::{Error, ExportError, Result};
+use crate::features::{DerivedColumnSpec, DerivedValue, SplitBy};
+use ahash::HashMap as AHashMap;
 use dm_database_parser_sqllog::{MetaParts, PerformanceMetrics, Sqllog};
 use log::info;
 use rusqlite::{Connection, params};
 use std::path::Path;
+use std::time::Instant;
 
+#[allow(clippy::struct_excessive_bools)]
 pub struct SqliteExporter {
     database_url: String,
     table_name: String,
@@ -15,17 +19,106 @@ pub struct SqliteExporter {
     conn: Option<Connection>,
     stats: ExportStats,
     row_count: usize,
+    /// 最近一次成功提交时的 `stats.exported`，之后写入的记录仍在未提交的事务中
+    committed: usize,
     batch_size: usize,
     pub(super) normalize: bool,
     pub(super) field_mask: crate::features::FieldMask,
     pub(super) ordered_indices: Vec<usize>,
+    /// 追加在基础字段之后的派生列（`features.extra_fields`）
+    pub(super) extra_columns: Vec<DerivedColumnSpec>,
+    /// 重复装载检测模式（`off` / `warn` / `abort`）与自定义检测 SQL
+    duplicate_check: String,
+    duplicate_check_query: Option<String>,
+    /// 追加模式下首条记录到达前为 true；检测执行一次后清除
+    duplicate_check_pending: bool,
+    /// `split_by` 拆分导出：每个分片写入 `<table_name>_<分片>`，首次出现时建表
+    pub(crate) split_by: Option<SplitBy>,
+    /// 已建好的分片表：分片名 → INSERT SQL
+    split_tables: AHashMap<String, String>,
+    /// 装载前 / 后在同一连接上执行的自定义 SQL（`pre_sql` / `post_sql`）
+    pre_sql: Vec<String>,
+    post_sql: Vec<String>,
+    /// 装载完成后创建的友好视图（`view_name`）
+    view_name: Option<String>,
+    /// 目标表已存在但缺列时 `ALTER TABLE` 补齐（`migrate`），否则报错
+    migrate: bool,
 }
 
+/// 拆分导出时的语句缓存容量（默认 16，分片较多时避免反复编译 INSERT）
+const SPLIT_STATEMENT_CACHE: usize = 256;
+
+/// 模板统计表（`features.template_analysis`）
+const TEMPLATES_CREATE_SQL: &str = "CREATE TABLE IF NOT EXISTS sql_templates (
+    template_key TEXT NOT NULL PRIMARY KEY,
+    count INTEGER NOT NULL,
+    avg_us INTEGER NOT NULL,
+    min_us INTEGER NOT NULL,
+    max_us INTEGER NOT NULL,
+    p50_us INTEGER NOT NULL,
+    p95_us INTEGER NOT NULL,
+    p99_us INTEGER NOT NULL,
+    first_seen TEXT NOT NULL,
+    last_seen TEXT NOT NULL
+)";
+
+/// 装载记录表（`exporter.sqlite.load_metadata`）：跨运行保留，不随 `overwrite` 清空
+const LOADS_CREATE_SQL: &str = "CREATE TABLE IF NOT EXISTS sqllog2db_loads (
+    run_id TEXT NOT NULL PRIMARY KEY,
+    loaded_at TEXT NOT NULL,
+    tool_version TEXT NOT NULL,
+    target_table TEXT NOT NULL,
+    mode TEXT NOT NULL,
+    status TEXT NOT NULL,
+    file_count INTEGER NOT NULL,
+    source_files TEXT NOT NULL,
+    checksum TEXT,
+    records INTEGER NOT NULL,
+    errors INTEGER NOT NULL,
+    duration_ms INTEGER NOT NULL
+)";
+
+/// 友好视图中各基础字段的表达式（`{}` 为原始列名）与列名
+const VIEW_COLUMNS: &[&[(&str, &str)]] = &[
+    &[
+        ("{}", "executed_at"),
+        // julianday 解析 `YYYY-MM-DD HH:MM:SS.mmm`（可带时区偏移），换算为 Unix 毫秒
+        (
+            "CAST(ROUND((julianday({}) - 2440587.5) * 86400000) AS INTEGER)",
+            "executed_at_epoch_ms",
+        ),
+    ],
+    &[("{}", "ep")],
+    &[("{}", "session_id")],
+    &[("{}", "thread_id")],
+    &[("{}", "user_name")],
+    &[("{}", "transaction_id")],
+    &[("{}", "statement_handle")],
+    &[("{}", "application")],
+    &[("{}", "client_ip")],
+    &[
+        ("{}", "statement_tag"),
+        (
+            // 未知标签原样保留；PARAMS 记录无标签，为 NULL
+            "CASE {} WHEN 'SEL' THEN 'SELECT' WHEN 'INS' THEN 'INSERT' WHEN 'UPD' THEN 'UPDATE' \
+             WHEN 'DEL' THEN 'DELETE' ELSE {} END",
+            "statement_type",
+        ),
+    ],
+    &[("{}", "sql_text")],
+    &[("{} / 1000.0", "duration_sec")],
+    &[("{}", "rows_affected")],
+    &[("{}", "exec_id")],
+    &[("{}", "normalized_sql")],
+];
+
+/// 页缓存上限 256 MiB（负值单位为 KiB）：按页数设置时 64 KiB 页 × 100 万页近 64 GB，
+/// 大库导出时内存会随库大小增长
 fn initialize_pragmas(conn: &Connection) -> std::result::Result<(), rusqlite::Error> {
     conn.execute_batch(
         "PRAGMA journal_mode = OFF;
          PRAGMA synchronous = OFF;
-         PRAGMA cache_size = 1000000;
+         PRAGMA cache_size = -262144;
          PRAGMA locking_mode = EXCLUSIVE;
          PRAGMA temp_store = MEMORY;
          PRAGMA mmap_size = 30000000000;
@@ -35,6 +128,43 @@ fn initialize_pragmas(conn: &Connection) -> std::result::Result<(), rusqlite::Er
     Ok(())
 }
 
+/// 连接预热：一次往返查询确认连接可用，并取回目标库版本与关键设置（写入应用日志）。
+/// 以只读方式打开的库（文件或目录无写权限）在此直接报错，而不是等到首次提交
+fn probe_target(conn: &Connection) -> std::result::Result<String, String> {
+    let (one, version): (i64, String) = conn
+        .query_row("SELECT 1, sqlite_version()", [], |r| {
+            Ok((r.get(0)?, r.get(1)?))
+        })
+        .map_err(|e| format!("round-trip query failed: {e}"))?;
+    if one != 1 {
+        return Err(format!("round-trip query returned {one}"));
+    }
+    if conn
+        .is_readonly(rusqlite::MAIN_DB)
+        .map_err(|e| format!("cannot query access mode: {e}"))?
+    {
+        return Err("database is read-only (check file and directory permissions)".to_string());
+    }
+    let pragma = |name: &str| -> String {
+        conn.pragma_query_value(None, name, |r| r.get::<_, rusqlite::types::Value>(0))
+            .map_or_else(
+                |_| "?".to_string(),
+                |v| match v {
+                    rusqlite::types::Value::Integer(i) => i.to_string(),
+                    rusqlite::types::Value::Text(t) => t,
+                    _ => "?".to_string(),
+                },
+            )
+    };
+    Ok(format!(
+        "SQLite {version} (journal_mode={}, synchronous={}, page_size={}, encoding={})",
+        pragma("journal_mode"),
+        pragma("synchronous"),
+        pragma("page_size"),
+        pragma("encoding"),
+    ))
+}
+
 impl std::fmt::Debug for SqliteExporter {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("SqliteExporter")
@@ -60,32 +190,62 @@ impl SqliteExporter {
             conn: None,
             stats: ExportStats::new(),
             row_count: 0,
+            committed: 0,
             batch_size: 10_000,
             normalize: true,
             field_mask: crate::features::FieldMask::ALL,
             ordered_indices: (0..crate::features::FIELD_NAMES.len()).collect(),
+            extra_columns: Vec::new(),
+            duplicate_check: "off".to_string(),
+            duplicate_check_query: None,
+            duplicate_check_pending: false,
+            split_by: None,
+            split_tables: AHashMap::default(),
+            pre_sql: Vec::new(),
+            post_sql: Vec::new(),
+            view_name: None,
+            migrate: false,
         }
     }
 
     /// 根据有序字段索引列表生成 INSERT SQL
-    fn build_insert_sql(table_name: &str, ordered_indices: &[usize]) -> String {
+    fn build_insert_sql(
+        table_name: &str,
+        ordered_indices: &[usize],
+        extra_columns: &[DerivedColumnSpec],
+    ) -> String {
         use crate::features::FIELD_NAMES;
-        if ordered_indices.len() == FIELD_NAMES.len() {
+        if ordered_indices.len() == FIELD_NAMES.len() && extra_columns.is_empty() {
             // 全量快速路径：与 new() 的默认 insert_sql 一致
             return format!(
                 "INSERT INTO \"{table_name}\" VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
             );
         }
-        let cols: Vec<&str> = ordered_indices.iter().map(|&i| FIELD_NAMES[i]).collect();
-        let placeholders = vec!["?"; ordered_indices.len()].join(", ");
+        let cols: Vec<&str> = ordered_indices
+            .iter()
+            .map(|&i| FIELD_NAMES[i])
+            .chain(extra_columns.iter().map(|c| c.name.as_str()))
+            .collect();
+        let placeholders = vec!["?"; cols.len()].join(", ");
         format!(
             "INSERT INTO \"{table_name}\" ({}) VALUES ({placeholders})",
             cols.join(", ")
         )
     }
 
-    /// 根据有序字段索引列表生成 CREATE TABLE SQL
-    fn build_create_sql(table_name: &str, ordered_indices: &[usize]) -> String {
+    /// 根据有序字段索引列表生成列定义（`name TYPE`）
+    fn column_defs(ordered_indices: &[usize], extra_columns: &[DerivedColumnSpec]) -> Vec<String> {
+        Self::columns(ordered_indices, extra_columns)
+            .into_iter()
+            .map(|(name, ty)| format!("{name} {ty}"))
+            .collect()
+    }
+
+    /// 根据有序字段索引列表生成 `(列名, 列定义类型)`
+    fn columns(
+        ordered_indices: &[usize],
+        extra_columns: &[DerivedColumnSpec],
+    ) -> Vec<(String, String)> {
         use crate::features::FIELD_NAMES;
         const COL_TYPES: &[&str] = &[
             "TEXT NOT NULL",    // ts        0
@@ -104,16 +264,130 @@ impl SqliteExporter {
             "INTEGER",          // exec_id   13
             "TEXT",             // normalized_sql 14
         ];
-        let cols: Vec<String> = ordered_indices
+        ordered_indices
             .iter()
-            .map(|&i| format!("{} {}", FIELD_NAMES[i], COL_TYPES[i]))
-            .collect();
+            .map(|&i| (FIELD_NAMES[i].to_string(), COL_TYPES[i].to_string()))
+            .chain(
+                extra_columns
+                    .iter()
+                    .map(|c| (c.name.clone(), c.kind.sql_type().to_string())),
+            )
+            .collect()
+    }
+
+    /// 目标表已存在时比对列结构（见 [`super::schema`]）：缺列时按 `migrate` 补齐或报错，
+    /// 返回本次使用的 INSERT SQL（列布局与表不一致时按列名插入）
+    fn reconcile_table(&self, table: &str) -> Result<String> {
+        let conn = self
+            .conn
+            .as_ref()
+            .ok_or_else(|| Self::db_err("not initialized"))?;
+        let positional = Self::build_insert_sql(table, &self.ordered_indices, &self.extra_columns);
+        let existing = super::schema::table_columns(conn, table)
+            .map_err(|e| Self::db_err(format!("inspect table {table} failed: {e}")))?;
+        let Some(existing) = existing else {
+            return Ok(positional);
+        };
+        let expected = Self::columns(&self.ordered_indices, &self.extra_columns);
+        let diff = super::schema::diff(&existing, &expected);
+        if !diff.required_extra.is_empty() {
+            return Err(Self::db_err(format!(
+                "table \"{table}\" has NOT NULL column(s) this export does not write: {}; \
+                 drop them or set exporter.sqlite.overwrite = true to recreate the table",
+                diff.required_extra.join(", ")
+            )));
+        }
+        if !diff.missing.is_empty() {
+            let names: Vec<&str> = diff.missing.iter().map(|(n, _)| n.as_str()).collect();
+            if !self.migrate {
+                return Err(Self::db_err(format!(
+                    "table \"{table}\" is missing column(s) {} (created by an older version or a \
+                     different column set); rerun with --migrate to add them, or set \
+                     exporter.sqlite.overwrite = true to recreate the table",
+                    names.join(", ")
+                )));
+            }
+            for (name, ty) in &diff.missing {
+                conn.execute(&super::schema::add_column_sql(table, name, ty), [])
+                    .map_err(|e| Self::db_err(format!("migrate table {table} failed: {e}")))?;
+            }
+            info!(
+                "Migrated table {table}: added column(s) {}",
+                names.join(", ")
+            );
+        }
+        if diff.same_layout {
+            return Ok(positional);
+        }
+        let names: Vec<&str> = expected.iter().map(|(n, _)| n.as_str()).collect();
+        Ok(format!(
+            "INSERT INTO \"{table}\" ({}) VALUES ({})",
+            names.join(", "),
+            vec!["?"; names.len()].join(", ")
+        ))
+    }
+
+    /// 根据有序字段索引列表生成 CREATE TABLE SQL
+    fn build_create_sql(
+        table_name: &str,
+        ordered_indices: &[usize],
+        extra_columns: &[DerivedColumnSpec],
+    ) -> String {
         format!(
             "CREATE TABLE IF NOT EXISTS \"{table_name}\" ({})",
-            cols.join(", ")
+            Self::column_defs(ordered_indices, extra_columns).join(", ")
         )
     }
 
+    /// 友好视图的 DDL（`DROP VIEW` + `CREATE VIEW`），只包含实际导出的列；派生列原名透传
+    fn view_statements(&self) -> Option<[String; 2]> {
+        use crate::features::FIELD_NAMES;
+        let view = self.view_name.as_deref()?;
+        let columns: Vec<String> = self
+            .ordered_indices
+            .iter()
+            .flat_map(|&i| {
+                VIEW_COLUMNS[i].iter().map(move |(expr, alias)| {
+                    format!("{} AS {alias}", expr.replace("{}", FIELD_NAMES[i]))
+                })
+            })
+            .chain(self.extra_columns.iter().map(|c| c.name.clone()))
+            .collect();
+        Some([
+            format!("DROP VIEW IF EXISTS \"{view}\""),
+            format!(
+                "CREATE VIEW \"{view}\" AS SELECT\n    {}\nFROM \"{}\"",
+                columns.join(",\n    "),
+                self.table_name
+            ),
+        ])
+    }
+
+    /// 按执行顺序返回 `initialize()`（及模板统计写入）将执行的 DDL，供 `schema` 子命令审阅。
+    /// 列定义逐行排版；`DELETE FROM` 虽非 DDL，但同样会清空目标表，一并列出。
+    #[must_use]
+    pub fn ddl_statements(&self, with_templates: bool) -> Vec<String> {
+        let table = &self.table_name;
+        let mut stmts = Vec::with_capacity(4);
+        if self.overwrite {
+            stmts.push(format!("DROP TABLE IF EXISTS \"{table}\""));
+        } else if !self.append {
+            stmts.push(format!("DELETE FROM \"{table}\""));
+        }
+        stmts.push(format!(
+            "CREATE TABLE IF NOT EXISTS \"{table}\" (\n    {}\n)",
+            Self::column_defs(&self.ordered_indices, &self.extra_columns).join(",\n    ")
+        ));
+        if with_templates {
+            if self.overwrite {
+                stmts.push("DROP TABLE IF EXISTS sql_templates".to_string());
+            }
+            stmts.push(TEMPLATES_CREATE_SQL.to_string());
+        }
+        stmts.extend(self.view_statements().into_iter().flatten());
+        stmts
+    }
+
     #[must_use]
     pub fn from_config(config: &crate::config::SqliteExporter) -> Self {
         let mut exporter = Self::new(
@@ -123,9 +397,129 @@ impl SqliteExporter {
             config.append,
         );
         exporter.batch_size = config.batch_size;
+        exporter.duplicate_check.clone_from(&config.duplicate_check);
+        exporter
+            .duplicate_check_query
+            .clone_from(&config.duplicate_check_query);
+        exporter.split_by = config
+            .split_by
+            .as_deref()
+            .and_then(|v| SplitBy::parse("exporter.sqlite.split_by", v).ok());
+        exporter.pre_sql.clone_from(&config.pre_sql);
+        exporter.post_sql.clone_from(&config.post_sql);
+        exporter.view_name.clone_from(&config.view_name);
+        exporter.migrate = config.migrate;
         exporter
     }
 
+    /// 依次执行 `pre_sql` / `post_sql`（每项可含多条以 `;` 分隔的语句），任一失败即中止
+    fn run_hook_sql(&self, hook: &str, statements: &[String]) -> Result<()> {
+        let conn = self
+            .conn
+            .as_ref()
+            .ok_or_else(|| Self::db_err("not initialized"))?;
+        for (i, sql) in statements.iter().enumerate() {
+            conn.execute_batch(sql)
+                .map_err(|e| Self::db_err(format!("{hook}[{i}] failed: {e}")))?;
+        }
+        if !statements.is_empty() {
+            info!("SQLite {hook}: {} statements executed", statements.len());
+        }
+        Ok(())
+    }
+
+    /// 追加装载前的重复检测：以首条记录的日期为装载窗口查询目标表。
+    /// 未配置自定义 SQL 且投影中不含 `ts` 列时跳过。
+    fn check_duplicate_load(&self, table: &str, first_ts: &str) -> Result<()> {
+        let day = first_ts.get(..10).unwrap_or(first_ts);
+        let query = match &self.duplicate_check_query {
+            Some(q) => q.clone(),
+            None if self.ordered_indices.contains(&0) => format!(
+                // '~' 大于时间戳中出现的所有字符，区间恰好覆盖当天全部记录
+                "SELECT COUNT(*) FROM \"{table}\" WHERE ts >= :day AND ts < :day || '~'"
+            ),
+            None => {
+                log::debug!("duplicate check skipped: ts column not exported");
+                return Ok(());
+            }
+        };
+        let conn = self
+            .conn
+            .as_ref()
+            .ok_or_else(|| Self::db_err("not initialized"))?;
+        let mut stmt = conn
+            .prepare(&query)
+            .map_err(|e| Self::db_err(format!("duplicate check query failed: {e}")))?;
+        let mut params: Vec<(&str, &dyn rusqlite::ToSql)> = Vec::with_capacity(2);
+        for (name, value) in [(":day", &day), (":first_ts", &first_ts)] {
+            if stmt.parameter_index(name).ok().flatten().is_some() {
+                params.push((name, value as &dyn rusqlite::ToSql));
+            }
+        }
+        let rows: i64 = stmt
+            .query_row(params.as_slice(), |r| r.get(0))
+            .map_err(|e| Self::db_err(format!("duplicate check query failed: {e}")))?;
+        if rows == 0 {
+            return Ok(());
+        }
+        if self.duplicate_check == "abort" {
+            return Err(Error::Export(ExportError::DuplicateLoad {
+                table: table.to_string(),
+                window: day.to_string(),
+                rows,
+            }));
+        }
+        log::warn!(
+            "Table {table} already contains {rows} rows for {day}; this run may load duplicates"
+        );
+        Ok(())
+    }
+
+    /// 首条记录到达时执行一次重复检测（非拆分模式）
+    fn check_duplicate_once(&mut self, first_ts: &str) -> Result<()> {
+        if self.duplicate_check_pending {
+            self.duplicate_check_pending = false;
+            self.check_duplicate_load(&self.table_name, first_ts)?;
+        }
+        Ok(())
+    }
+
+    /// 拆分模式：准备分片表（按 overwrite/append 清理、建表、重复检测）并缓存其 INSERT SQL
+    fn open_split_table(&mut self, partition: &str, first_ts: &str) -> Result<()> {
+        let table = format!("{}_{partition}", self.table_name);
+        self.prepare_table(&table)?;
+        let insert_sql = self.reconcile_table(&table)?;
+        let conn = self
+            .conn
+            .as_ref()
+            .ok_or_else(|| Self::db_err("not initialized"))?;
+        conn.execute(
+            &Self::build_create_sql(&table, &self.ordered_indices, &self.extra_columns),
+            [],
+        )
+        .map_err(|e| Self::db_err(format!("create table failed: {e}")))?;
+        if self.append && self.duplicate_check != "off" {
+            self.check_duplicate_load(&table, first_ts)?;
+        }
+        info!("SQLite split table created: {table}");
+        self.split_tables.insert(partition.to_string(), insert_sql);
+        Ok(())
+    }
+
+    /// `validate --check-connectivity`：打开目标库做一次往返查询，返回版本与关键设置。
+    /// 目标库原本不存在时，探测创建的空文件随即删除
+    pub fn probe(database_url: &str) -> Result<String> {
+        let path = Path::new(database_url);
+        let existed = path.exists();
+        let probed = Connection::open(crate::paths::for_io(path))
+            .map_err(|e| format!("open failed: {e}"))
+            .and_then(|conn| probe_target(&conn));
+        if !existed {
+            let _ = std::fs::remove_file(crate::paths::for_io(path));
+        }
+        probed.map_err(|e| Self::db_err(format!("connection check on {database_url} failed: {e}")))
+    }
+
     fn db_err(reason: impl Into<String>) -> Error {
         Error::Export(ExportError::DatabaseFailed {
             reason: reason.into(),
@@ -137,13 +531,34 @@ impl SqliteExporter {
     fn batch_commit_if_needed(&mut self) -> Result<()> {
         self.row_count += 1;
         if self.row_count % self.batch_size == 0 {
-            let conn = self.conn.as_ref().unwrap();
-            conn.execute_batch("COMMIT; BEGIN")
-                .map_err(|e| Self::db_err(format!("batch commit failed: {e}")))?;
+            self.commit("batch commit")?;
         }
         Ok(())
     }
 
+    /// 提交当前事务并开启下一个。提交失败时回滚，未提交的记录改计为跳过
+    /// （是否中止由 `on_error` 决定），事务保持可用
+    fn commit(&mut self, what: &str) -> Result<()> {
+        let conn = self
+            .conn
+            .as_ref()
+            .ok_or_else(|| Self::db_err("not initialized"))?;
+        if let Err(e) = conn.execute_batch("COMMIT") {
+            // 部分错误下 SQLite 已自动回滚，ROLLBACK 失败可忽略
+            let _ = conn.execute_batch("ROLLBACK");
+            conn.execute_batch("BEGIN")
+                .map_err(|e| Self::db_err(format!("begin failed: {e}")))?;
+            self.stats
+                .record_skipped(self.stats.exported - self.committed);
+            self.committed = self.stats.exported;
+            return Err(Self::db_err(format!("{what} failed: {e}")));
+        }
+        conn.execute_batch("BEGIN")
+            .map_err(|e| Self::db_err(format!("begin failed: {e}")))?;
+        self.committed = self.stats.exported;
+        Ok(())
+    }
+
     /// 热路径：使用预解析的 `MetaParts` 和 `PerformanceMetrics` 直接插入。
     /// 全量掩码走 `params![]` 快速路径；投影掩码走动态 Value 路径。
     ///
@@ -158,6 +573,7 @@ impl SqliteExporter {
         normalized_sql: Option<&str>,
         field_mask: crate::features::FieldMask,
         ordered_indices: &[usize],
+        extras: &[DerivedValue],
     ) -> std::result::Result<(), rusqlite::Error> {
         let (exec_time_ms, row_count, exec_id) =
             if pm.exec_id != 0 || pm.exectime > 0.0 || pm.rowcount != 0 {
@@ -171,7 +587,7 @@ impl SqliteExporter {
                 (None, None, None)
             };
 
-        if field_mask == crate::features::FieldMask::ALL {
+        if field_mask == crate::features::FieldMask::ALL && extras.is_empty() {
             // 全量掩码快速路径：直接绑定全部 15 个参数
             stmt.execute(params![
                 sqllog.ts.as_ref(),
@@ -215,7 +631,19 @@ impl SqliteExporter {
             exec_id.map_or(Value::Null, Value::Integer),
             normalized_sql.map_or(Value::Null, |s| Value::Text(s.to_string())),
         ];
-        let selected: Vec<&Value> = ordered_indices.iter().map(|&i| &all[i]).collect();
+        let extra_values: Vec<Value> = extras
+            .iter()
+            .map(|v| match v {
+                DerivedValue::Null => Value::Null,
+                DerivedValue::Int(n) => Value::Integer(*n),
+                DerivedValue::Text(t) => Value::Text(t.to_string()),
+            })
+            .collect();
+        let selected: Vec<&Value> = ordered_indices
+            .iter()
+            .map(|&i| &all[i])
+            .chain(extra_values.iter())
+            .collect();
         stmt.execute(rusqlite::params_from_iter(selected))?;
         Ok(())
     }
@@ -236,19 +664,19 @@ impl SqliteExporter {
     }
 
     /// 根据 overwrite/append 模式准备目标表（清空或删除旧数据）。
-    fn prepare_target_table(&self) -> Result<()> {
+    fn prepare_table(&self, table: &str) -> Result<()> {
+        let conn = self
+            .conn
+            .as_ref()
+            .ok_or_else(|| Self::db_err("not initialized"))?;
         if self.overwrite {
-            let conn = self.conn.as_ref().unwrap();
-            conn.execute(&format!("DROP TABLE IF EXISTS \"{}\"", self.table_name), [])
+            conn.execute(&format!("DROP TABLE IF EXISTS \"{table}\""), [])
                 .map_err(|e| Self::db_err(format!("drop table failed: {e}")))?;
-            info!("Dropped existing table: {}", self.table_name);
+            info!("Dropped existing table: {table}");
         } else if !self.append {
             Self::handle_delete_clear_result(
-                self.conn
-                    .as_ref()
-                    .unwrap()
-                    .execute(&format!("DELETE FROM \"{}\"", self.table_name), []),
-                &self.table_name,
+                conn.execute(&format!("DELETE FROM \"{table}\""), []),
+                table,
             );
         }
         Ok(())
@@ -272,6 +700,7 @@ impl SqliteExporter {
             normalized_sql,
             field_mask,
             ordered_indices,
+            &[],
         )
     }
 }
@@ -282,28 +711,68 @@ impl Exporter for SqliteExporter {
 
         let path = Path::new(&self.database_url);
         if let Some(parent) = path.parent().filter(|p| !p.exists()) {
-            std::fs::create_dir_all(parent)
+            std::fs::create_dir_all(crate::paths::for_io(parent))
                 .map_err(|e| Self::db_err(format!("create dir failed: {e}")))?;
         }
 
-        let conn = Connection::open(&self.database_url)
+        let conn = Connection::open(crate::paths::for_io(path))
             .map_err(|e| Self::db_err(format!("open failed: {e}")))?;
 
         initialize_pragmas(&conn).map_err(|e| Self::db_err(format!("set PRAGMAs failed: {e}")))?;
-
+        let target = probe_target(&conn).map_err(|e| {
+            Self::db_err(format!(
+                "connection check on {} failed: {e}",
+                self.database_url
+            ))
+        })?;
+        info!("SQLite target: {target}");
+
+        if self.split_by.is_some() {
+            conn.set_prepared_statement_cache_capacity(SPLIT_STATEMENT_CACHE);
+        }
         self.conn = Some(conn);
         self.row_count = 0;
+        self.committed = self.stats.exported;
+
+        if self.split_by.is_some() {
+            // 分片表在首条记录到达时创建
+            self.split_tables.clear();
+            self.run_hook_sql("pre_sql", &self.pre_sql)?;
+            let conn = self
+                .conn
+                .as_ref()
+                .ok_or_else(|| Self::db_err("not initialized"))?;
+            conn.execute_batch("BEGIN TRANSACTION;")
+                .map_err(|e| Self::db_err(format!("begin transaction failed: {e}")))?;
+            info!(
+                "SQLite exporter initialized (split mode): {}",
+                self.database_url
+            );
+            return Ok(());
+        }
 
-        self.prepare_target_table()?;
+        self.prepare_table(&self.table_name)?;
+        self.duplicate_check_pending = self.append && self.duplicate_check != "off";
 
-        // 根据 ordered_indices 重新生成 insert_sql（可在 new() 后被外部修改）
-        self.insert_sql = Self::build_insert_sql(&self.table_name, &self.ordered_indices);
+        // 根据 ordered_indices 重新生成 insert_sql（可在 new() 后被外部修改）；已有表先比对列结构
+        self.insert_sql = self.reconcile_table(&self.table_name)?;
 
-        let conn = self.conn.as_ref().unwrap();
-        let create_sql = Self::build_create_sql(&self.table_name, &self.ordered_indices);
+        let conn = self
+            .conn
+            .as_ref()
+            .ok_or_else(|| Self::db_err("not initialized"))?;
+        let create_sql =
+            Self::build_create_sql(&self.table_name, &self.ordered_indices, &self.extra_columns);
         conn.execute(&create_sql, [])
             .map_err(|e| Self::db_err(format!("create table failed: {e}")))?;
 
+        // 目标表已存在，pre_sql 可引用它（如删除索引 / 触发器）
+        self.run_hook_sql("pre_sql", &self.pre_sql)?;
+
+        let conn = self
+            .conn
+            .as_ref()
+            .ok_or_else(|| Self::db_err("not initialized"))?;
         conn.execute_batch("BEGIN TRANSACTION;")
             .map_err(|e| Self::db_err(format!("begin transaction failed: {e}")))?;
 
@@ -312,6 +781,10 @@ impl Exporter for SqliteExporter {
     }
 
     fn export(&mut self, sqllog: &Sqllog<'_>) -> Result<()> {
+        if self.split_by.is_some() {
+            return self.export_one_normalized(sqllog, None);
+        }
+        self.check_duplicate_once(sqllog.ts.as_ref())?;
         {
             let conn = self
                 .conn
@@ -339,6 +812,12 @@ impl Exporter for SqliteExporter {
         sqllog: &Sqllog<'_>,
         normalized: Option<&str>,
     ) -> Result<()> {
+        if self.split_by.is_some() {
+            let meta = sqllog.parse_meta();
+            let pm = sqllog.parse_performance_metrics();
+            return self.export_one_derived(sqllog, &meta, &pm, normalized, &[]);
+        }
+        self.check_duplicate_once(sqllog.ts.as_ref())?;
         {
             let conn = self
                 .conn
@@ -369,13 +848,38 @@ impl Exporter for SqliteExporter {
         pm: &PerformanceMetrics<'_>,
         normalized: Option<&str>,
     ) -> Result<()> {
+        self.export_one_derived(sqllog, meta, pm, normalized, &[])
+    }
+
+    fn export_one_derived(
+        &mut self,
+        sqllog: &Sqllog<'_>,
+        meta: &MetaParts<'_>,
+        pm: &PerformanceMetrics<'_>,
+        normalized: Option<&str>,
+        extras: &[DerivedValue],
+    ) -> Result<()> {
+        let partition = if let Some(by) = self.split_by {
+            let partition = by.partition(meta, pm.sql.as_ref());
+            if !self.split_tables.contains_key(&partition) {
+                self.open_split_table(&partition, sqllog.ts.as_ref())?;
+            }
+            Some(partition)
+        } else {
+            self.check_duplicate_once(sqllog.ts.as_ref())?;
+            None
+        };
         {
+            let insert_sql = partition
+                .as_ref()
+                .and_then(|p| self.split_tables.get(p))
+                .unwrap_or(&self.insert_sql);
             let conn = self
                 .conn
                 .as_ref()
                 .ok_or_else(|| Self::db_err("not initialized"))?;
             let mut stmt = conn
-                .prepare_cached(&self.insert_sql)
+                .prepare_cached(insert_sql)
                 .map_err(|e| Self::db_err(format!("prepare failed: {e}")))?;
             let ns_ref = if self.normalize { normalized } else { None };
             Self::do_insert_preparsed(
@@ -386,6 +890,7 @@ impl Exporter for SqliteExporter {
                 ns_ref,
                 self.field_mask,
                 &self.ordered_indices,
+                extras,
             )
             .map_err(|e| Self::db_err(format!("insert failed: {e}")))?;
         } // stmt and conn dropped here, releasing borrow
@@ -398,14 +903,56 @@ impl Exporter for SqliteExporter {
         if let Some(conn) = &self.conn {
             conn.execute_batch("COMMIT;")
                 .map_err(|e| Self::db_err(format!("commit failed: {e}")))?;
+            // 视图先于 post_sql 创建，post_sql 可基于视图建汇总表
+            if let Some([drop, create]) = self.view_statements() {
+                conn.execute_batch(&format!("{drop}; {create}"))
+                    .map_err(|e| Self::db_err(format!("create view failed: {e}")))?;
+                info!(
+                    "SQLite view created: {}",
+                    self.view_name.as_deref().unwrap_or_default()
+                );
+            }
+            self.run_hook_sql("post_sql", &self.post_sql)?;
         }
         info!(
-            "SQLite export finished: {} (success: {}, failed: {})",
-            self.database_url, self.stats.exported, self.stats.failed
+            "SQLite export finished: {} ({} rows)",
+            self.database_url, self.stats.exported
         );
+        if !self.split_tables.is_empty() {
+            info!(
+                "SQLite split export: {} tables ({}_*)",
+                self.split_tables.len(),
+                self.table_name
+            );
+        }
         Ok(())
     }
 
+    fn flush_chunk(&mut self) -> Result<()> {
+        if self.conn.is_some() {
+            let started = Instant::now();
+            self.commit("chunk commit")?;
+            if let Some(conn) = &self.conn {
+                conn.release_memory()
+                    .map_err(|e| Self::db_err(format!("release memory failed: {e}")))?;
+            }
+            self.stats.record_flush(started.elapsed());
+        }
+        Ok(())
+    }
+
+    fn discard_batch(&mut self) -> Result<usize> {
+        let Some(conn) = &self.conn else {
+            return Ok(0);
+        };
+        conn.execute_batch("ROLLBACK; BEGIN")
+            .map_err(|e| Self::db_err(format!("rollback failed: {e}")))?;
+        let discarded = self.stats.exported - self.committed;
+        self.stats.record_skipped(discarded);
+        self.committed = self.stats.exported;
+        Ok(discarded)
+    }
+
     fn write_template_stats(
         &mut self,
         stats: &[crate::features::TemplateStats],
@@ -423,27 +970,18 @@ impl Exporter for SqliteExporter {
             conn.execute("DROP TABLE IF EXISTS sql_templates", [])
                 .map_err(|e| Self::db_err(format!("drop sql_templates failed: {e}")))?;
         }
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS sql_templates \
-             (template_key TEXT NOT NULL PRIMARY KEY, \
-              count INTEGER NOT NULL, \
-              avg_us INTEGER NOT NULL, \
-              min_us INTEGER NOT NULL, \
-              max_us INTEGER NOT NULL, \
-              p50_us INTEGER NOT NULL, \
-              p95_us INTEGER NOT NULL, \
-              p99_us INTEGER NOT NULL, \
-              first_seen TEXT NOT NULL, \
-              last_seen TEXT NOT NULL)",
-            [],
-        )
-        .map_err(|e| Self::db_err(format!("create sql_templates failed: {e}")))?;
+        conn.execute(TEMPLATES_CREATE_SQL, [])
+            .map_err(|e| Self::db_err(format!("create sql_templates failed: {e}")))?;
+        // 追加装载（含 `run --watch` 的后续各轮）再次出现的模板以最新一次统计为准
         #[allow(clippy::cast_possible_wrap)]
         for s in stats {
             #[rustfmt::skip]
             let p = rusqlite::params![s.template_key, s.count as i64, s.avg_us as i64, s.min_us as i64, s.max_us as i64, s.p50_us as i64, s.p95_us as i64, s.p99_us as i64, s.first_seen, s.last_seen];
-            conn.execute("INSERT INTO sql_templates VALUES (?,?,?,?,?,?,?,?,?,?)", p)
-                .map_err(|e| Self::db_err(format!("insert sql_templates failed: {e}")))?;
+            conn.execute(
+                "INSERT OR REPLACE INTO sql_templates VALUES (?,?,?,?,?,?,?,?,?,?)",
+                p,
+            )
+            .map_err(|e| Self::db_err(format!("insert sql_templates failed: {e}")))?;
         }
         conn.execute_batch("COMMIT;")
             .map_err(|e| Self::db_err(format!("commit sql_templates failed: {e}")))?;
@@ -455,6 +993,29 @@ impl Exporter for SqliteExporter {
         Ok(())
     }
 
+    fn write_load_record(&mut self, load: &super::LoadRecord) -> Result<()> {
+        let conn = self
+            .conn
+            .as_ref()
+            .ok_or_else(|| Self::db_err("write_load_record: not initialized"))?;
+        conn.execute(LOADS_CREATE_SQL, [])
+            .map_err(|e| Self::db_err(format!("create sqllog2db_loads failed: {e}")))?;
+        let mode = if self.append { "append" } else { "overwrite" };
+        #[allow(clippy::cast_possible_wrap, clippy::cast_possible_truncation)]
+        #[rustfmt::skip]
+        let p = params![load.run_id, chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(), load.tool_version, self.table_name, mode, load.status(), load.files.len() as i64, load.files_json(), load.checksum(), load.records as i64, load.errors as i64, load.duration.as_millis() as i64];
+        conn.execute(
+            "INSERT OR REPLACE INTO sqllog2db_loads VALUES (?,?,?,?,?,?,?,?,?,?,?,?)",
+            p,
+        )
+        .map_err(|e| Self::db_err(format!("insert sqllog2db_loads failed: {e}")))?;
+        info!(
+            "sqllog2db_loads: run {} recorded in {}",
+            load.run_id, self.database_url
+        );
+        Ok(())
+    }
+
     fn stats_snapshot(&self) -> Option<ExportStats> {
         Some(self.stats)
     }
@@ -479,6 +1040,25 @@ mod tests {
         std::fs::write(path, buf).unwrap();
     }
 
+    #[test]
+    fn test_probe_target_reports_version_and_rejects_read_only() {
+        let conn = Connection::open_in_memory().unwrap();
+        let info = probe_target(&conn).unwrap();
+        assert!(info.starts_with("SQLite 3."), "{info}");
+        assert!(info.contains("page_size="), "{info}");
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let dbfile = dir.path().join("ro.db");
+        Connection::open(&dbfile)
+            .unwrap()
+            .execute_batch("CREATE TABLE t (x INTEGER);")
+            .unwrap();
+        let ro = Connection::open_with_flags(&dbfile, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .unwrap();
+        let err = probe_target(&ro).unwrap_err();
+        assert!(err.contains("read-only"), "{err}");
+    }
+
     #[test]
     fn test_sqlite_basic_export() {
         let dir = tempfile::TempDir::new().unwrap();
@@ -592,6 +1172,7 @@ mod tests {
             overwrite: true,
             append: false,
             batch_size: 10_000,
+            ..crate::config::SqliteExporter::default()
         };
         let mut exporter = SqliteExporter::from_config(&cfg);
         exporter.initialize().unwrap();
@@ -599,6 +1180,108 @@ mod tests {
         assert!(dbfile.exists());
     }
 
+    #[test]
+    fn test_sqlite_pre_and_post_sql_wrap_load() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logfile = dir.path().join("test.log");
+        let dbfile = dir.path().join("hooks.db");
+        write_test_log(&logfile, 3);
+        let parser = LogParser::from_path(logfile.to_str().unwrap()).unwrap();
+        let records: Vec<_> = parser.iter().filter_map(std::result::Result::ok).collect();
+
+        let cfg = crate::config::SqliteExporter {
+            database_url: dbfile.to_string_lossy().into_owned(),
+            pre_sql: vec![
+                "CREATE TABLE load_log (phase TEXT); \
+                 INSERT INTO load_log SELECT 'pre:' || COUNT(*) FROM sqllog_records"
+                    .into(),
+            ],
+            post_sql: vec![
+                "INSERT INTO load_log SELECT 'post:' || COUNT(*) FROM sqllog_records".into(),
+            ],
+            ..crate::config::SqliteExporter::default()
+        };
+        {
+            let mut exporter = SqliteExporter::from_config(&cfg);
+            exporter.initialize().unwrap();
+            for r in &records {
+                exporter.export_one_normalized(r, None).unwrap();
+            }
+            exporter.finalize().unwrap();
+        }
+        let conn = rusqlite::Connection::open(&dbfile).unwrap();
+        let phases: Vec<String> = conn
+            .prepare("SELECT phase FROM load_log ORDER BY rowid")
+            .unwrap()
+            .query_map([], |r| r.get(0))
+            .unwrap()
+            .map(std::result::Result::unwrap)
+            .collect();
+        assert_eq!(phases, ["pre:0", "post:3"]);
+
+        // 失败的语句带上钩子名与序号
+        let cfg = crate::config::SqliteExporter {
+            database_url: dir.path().join("bad.db").to_string_lossy().into_owned(),
+            pre_sql: vec!["SELECT 1".into(), "NOT SQL".into()],
+            ..crate::config::SqliteExporter::default()
+        };
+        let err = SqliteExporter::from_config(&cfg).initialize().unwrap_err();
+        assert!(err.to_string().contains("pre_sql[1] failed"), "{err}");
+    }
+
+    #[test]
+    fn test_sqlite_friendly_view() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logfile = dir.path().join("test.log");
+        let dbfile = dir.path().join("view.db");
+        std::fs::write(
+            &logfile,
+            "2025-01-15 10:30:28.001 (EP[0] sess:0x1 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [UPD] UPDATE t SET a = 1. EXECTIME: 1500(ms) ROWCOUNT: 3(rows) EXEC_ID: 9.\n",
+        )
+        .unwrap();
+        let parser = LogParser::from_path(logfile.to_str().unwrap()).unwrap();
+        let records: Vec<_> = parser.iter().filter_map(std::result::Result::ok).collect();
+
+        let cfg = crate::config::SqliteExporter {
+            database_url: dbfile.to_string_lossy().into_owned(),
+            view_name: Some("sqllog".into()),
+            ..crate::config::SqliteExporter::default()
+        };
+        {
+            let mut exporter = SqliteExporter::from_config(&cfg);
+            // 投影去掉 sql 列：视图只包含实际导出的列
+            let names = ["ts", "tag", "exec_time_ms", "row_count"].map(String::from);
+            exporter.normalize = false;
+            exporter.field_mask = crate::features::FieldMask::from_names(&names).unwrap();
+            exporter.ordered_indices = vec![0, 9, 11, 12];
+            assert!(exporter.ddl_statements(false)[3].starts_with("CREATE VIEW \"sqllog\""));
+            exporter.initialize().unwrap();
+            for r in &records {
+                exporter.export_one_normalized(r, None).unwrap();
+            }
+            exporter.finalize().unwrap();
+        }
+        let conn = rusqlite::Connection::open(&dbfile).unwrap();
+        let row: (String, i64, String, f64, i64) = conn
+            .query_row(
+                "SELECT executed_at, executed_at_epoch_ms, statement_type, duration_sec, rows_affected FROM sqllog",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?)),
+            )
+            .unwrap();
+        assert_eq!(
+            row,
+            (
+                "2025-01-15 10:30:28.001".to_string(),
+                1_736_937_028_001,
+                "UPDATE".to_string(),
+                1.5,
+                3
+            )
+        );
+        assert!(conn.prepare("SELECT sql_text FROM sqllog").is_err());
+    }
+
     #[test]
     fn test_sqlite_export_method() {
         let dir = tempfile::TempDir::new().unwrap();
@@ -687,13 +1370,30 @@ mod tests {
 
     #[test]
     fn test_sqlite_build_insert_sql_ordered() {
-        let sql = SqliteExporter::build_insert_sql("t", &[10, 4]);
+        let sql = SqliteExporter::build_insert_sql("t", &[10, 4], &[]);
         assert_eq!(sql, "INSERT INTO \"t\" (sql, username) VALUES (?, ?)");
     }
 
+    #[test]
+    fn test_sqlite_build_sql_with_extra_columns() {
+        let extra = [DerivedColumnSpec {
+            name: "ts_epoch_ms".into(),
+            kind: crate::features::derived::DerivedKind::Integer,
+        }];
+        let all_indices: Vec<usize> = (0..15).collect();
+        let insert = SqliteExporter::build_insert_sql("t", &all_indices, &extra);
+        assert!(insert.contains("normalized_sql, ts_epoch_ms) VALUES ("));
+        assert_eq!(insert.matches('?').count(), 16);
+        let create = SqliteExporter::build_create_sql("t", &[0], &extra);
+        assert_eq!(
+            create,
+            "CREATE TABLE IF NOT EXISTS \"t\" (ts TEXT NOT NULL, ts_epoch_ms INTEGER)"
+        );
+    }
+
     #[test]
     fn test_sqlite_build_create_sql_ordered() {
-        let sql = SqliteExporter::build_create_sql("t", &[10, 4]);
+        let sql = SqliteExporter::build_create_sql("t", &[10, 4], &[]);
         assert_eq!(
             sql,
             "CREATE TABLE IF NOT EXISTS \"t\" (sql TEXT NOT NULL, username TEXT NOT NULL)"
@@ -703,7 +1403,7 @@ mod tests {
     #[test]
     fn test_sqlite_build_insert_sql_full_fast_path() {
         let all_indices: Vec<usize> = (0..15).collect();
-        let sql = SqliteExporter::build_insert_sql("t", &all_indices);
+        let sql = SqliteExporter::build_insert_sql("t", &all_indices, &[]);
         assert_eq!(
             sql,
             "INSERT INTO \"t\" VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
@@ -793,6 +1493,179 @@ mod tests {
         assert_eq!(count, 6);
     }
 
+    #[test]
+    fn test_sqlite_existing_table_missing_columns() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logfile = dir.path().join("test.log");
+        let dbfile = dir.path().join("old.db");
+        write_test_log(&logfile, 3);
+        let parser = LogParser::from_path(logfile.to_str().unwrap()).unwrap();
+        let records: Vec<_> = parser.iter().filter_map(std::result::Result::ok).collect();
+
+        // 旧版本建的表：没有 exec_id / normalized_sql，多一列可空的 note
+        let old_columns: Vec<String> =
+            SqliteExporter::column_defs(&(0..13).collect::<Vec<_>>(), &[]);
+        rusqlite::Connection::open(&dbfile)
+            .unwrap()
+            .execute_batch(&format!(
+                "CREATE TABLE tbl (note TEXT, {}); INSERT INTO tbl (note, ts, ep, sess_id, thrd_id, username, trx_id, sql) \
+                 VALUES ('old', '2024-01-01', 0, 's', 't', 'u', 'x', 'SELECT 0');",
+                old_columns.join(", ")
+            ))
+            .unwrap();
+        let load = |migrate: bool| {
+            let mut e = SqliteExporter::from_config(&crate::config::SqliteExporter {
+                database_url: dbfile.to_string_lossy().into(),
+                table_name: "tbl".into(),
+                overwrite: false,
+                append: true,
+                duplicate_check: "off".into(),
+                migrate,
+                ..crate::config::SqliteExporter::default()
+            });
+            e.initialize()?;
+            for r in &records {
+                e.export(r)?;
+            }
+            e.finalize()
+        };
+
+        let err = load(false).unwrap_err().to_string();
+        assert!(
+            err.contains("missing column(s) exec_id, normalized_sql") && err.contains("--migrate"),
+            "{err}"
+        );
+        load(true).unwrap();
+
+        let conn = rusqlite::Connection::open(&dbfile).unwrap();
+        let (rows, max_exec_id, notes): (i64, i64, i64) = conn
+            .query_row(
+                "SELECT COUNT(*), MAX(exec_id), COUNT(note) FROM tbl",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!((rows, max_exec_id, notes), (4, 2, 1));
+        // 补齐后的表结构与本次导出一致，再次装载无需迁移
+        load(false).unwrap();
+    }
+
+    fn load_append(
+        dbfile: &Path,
+        records: &[Sqllog<'_>],
+        mode: &str,
+        query: Option<&str>,
+    ) -> Result<()> {
+        let mut e = SqliteExporter::from_config(&crate::config::SqliteExporter {
+            database_url: dbfile.to_string_lossy().into(),
+            table_name: "tbl".into(),
+            overwrite: false,
+            append: true,
+            duplicate_check: mode.into(),
+            duplicate_check_query: query.map(str::to_string),
+            ..crate::config::SqliteExporter::default()
+        });
+        e.initialize()?;
+        for r in records {
+            e.export(r)?;
+        }
+        e.finalize()
+    }
+
+    #[test]
+    fn test_sqlite_duplicate_check_abort_and_warn() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logfile = dir.path().join("test.log");
+        let dbfile = dir.path().join("dup.db");
+        write_test_log(&logfile, 3);
+        let parser = LogParser::from_path(logfile.to_str().unwrap()).unwrap();
+        let records: Vec<_> = parser.iter().filter_map(std::result::Result::ok).collect();
+
+        // 首次装载：表不存在，无需告警
+        load_append(&dbfile, &records, "abort", None).unwrap();
+        // 同一天再次装载：abort 中止
+        let err = load_append(&dbfile, &records, "abort", None).unwrap_err();
+        assert!(
+            matches!(&err, Error::Export(ExportError::DuplicateLoad { rows: 3, window, .. }) if window == "2025-01-15"),
+            "{err}"
+        );
+        // warn 仅告警，照常写入
+        load_append(&dbfile, &records, "warn", None).unwrap();
+
+        let conn = rusqlite::Connection::open(&dbfile).unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM tbl", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(count, 6);
+    }
+
+    #[test]
+    fn test_sqlite_duplicate_check_custom_query() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logfile = dir.path().join("test.log");
+        let dbfile = dir.path().join("dup_q.db");
+        write_test_log(&logfile, 2);
+        let parser = LogParser::from_path(logfile.to_str().unwrap()).unwrap();
+        let records: Vec<_> = parser.iter().filter_map(std::result::Result::ok).collect();
+
+        load_append(&dbfile, &records, "abort", None).unwrap();
+        // 自定义窗口：只与首条记录时间戳完全相同的行视为重复
+        let q = "SELECT COUNT(*) FROM tbl WHERE ts = :first_ts AND username = 'NOBODY'";
+        load_append(&dbfile, &records, "abort", Some(q)).unwrap();
+        let q = "SELECT COUNT(*) FROM tbl WHERE ts = :first_ts";
+        assert!(load_append(&dbfile, &records, "abort", Some(q)).is_err());
+    }
+
+    #[test]
+    fn test_sqlite_split_by_username_tables() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logfile = dir.path().join("test.log");
+        let dbfile = dir.path().join("split.db");
+        let line = |user: &str, id: u32| {
+            format!(
+                "2025-01-15 10:30:28.001 (EP[0] sess:0x1 user:{user} trxid:{id} stmt:0x1 appname:App ip:10.0.0.1) [SEL] SELECT {id}. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: {id}.\n"
+            )
+        };
+        let log = [line("BILL_APP", 1), line("ETL", 2), line("BILL_APP", 3)].concat();
+        std::fs::write(&logfile, log).unwrap();
+        let parser = LogParser::from_path(logfile.to_str().unwrap()).unwrap();
+        let records: Vec<_> = parser.iter().filter_map(std::result::Result::ok).collect();
+
+        let cfg = crate::config::SqliteExporter {
+            database_url: dbfile.to_string_lossy().into(),
+            table_name: "tbl".into(),
+            split_by: Some("username".into()),
+            ..crate::config::SqliteExporter::default()
+        };
+        // 覆盖模式运行两次，分片表不应累积
+        for _ in 0..2 {
+            let mut e = SqliteExporter::from_config(&cfg);
+            e.initialize().unwrap();
+            for r in &records {
+                e.export(r).unwrap();
+            }
+            e.finalize().unwrap();
+        }
+
+        let conn = rusqlite::Connection::open(&dbfile).unwrap();
+        let count = |table: &str| -> i64 {
+            conn.query_row(&format!("SELECT COUNT(*) FROM \"{table}\""), [], |r| {
+                r.get(0)
+            })
+            .unwrap()
+        };
+        assert_eq!(count("tbl_BILL_APP"), 2);
+        assert_eq!(count("tbl_ETL"), 1);
+        let base: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE name = 'tbl'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(base, 0);
+    }
+
     #[test]
     fn test_sqlite_initialize_creates_quoted_table() {
         let dir = tempfile::TempDir::new().unwrap();
@@ -924,6 +1797,10 @@ mod tests {
             for r in &records {
                 exporter.export_one_normalized(r, None).unwrap();
             }
+            // 分块边界提交与批量提交交错也不丢数据
+            exporter.flush_chunk().unwrap();
+            let snap = exporter.stats_snapshot().unwrap();
+            assert_eq!((snap.flush_operations, snap.last_flush_size), (1, 5));
             exporter.finalize().unwrap();
         }
 
@@ -1104,4 +1981,73 @@ mod tests {
         };
         assert_eq!(keys, vec!["A", "B"], "expected keys [A, B], got {keys:?}");
     }
+
+    /// 装载记录：每次运行一行，跨 overwrite 运行保留，同一 `run_id` 覆盖
+    #[test]
+    fn test_sqlite_write_load_record_survives_overwrite() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logfile = dir.path().join("test.log");
+        let dbfile = dir.path().join("out.db");
+        write_test_log(&logfile, 3);
+        let metrics = crate::metrics::MetricsSnapshot {
+            exported: 3,
+            ..Default::default()
+        };
+
+        for run_id in ["run-1", "run-2", "run-2"] {
+            let load = super::super::LoadRecord::collect(
+                run_id,
+                [&logfile],
+                &metrics,
+                std::time::Duration::from_millis(1500),
+                true,
+            )
+            .unwrap();
+            let mut exporter =
+                SqliteExporter::new(dbfile.to_string_lossy().into(), "tbl".into(), true, false);
+            exporter.initialize().unwrap();
+            exporter.finalize().unwrap();
+            exporter.write_load_record(&load).unwrap();
+        }
+
+        let conn = rusqlite::Connection::open(&dbfile).unwrap();
+        let rows: Vec<(String, String, String, i64, i64, Option<String>)> = {
+            let mut stmt = conn
+                .prepare("SELECT run_id, target_table, status, records, duration_ms, checksum FROM sqllog2db_loads ORDER BY run_id")
+                .unwrap();
+            stmt.query_map([], |r| {
+                Ok((
+                    r.get(0)?,
+                    r.get(1)?,
+                    r.get(2)?,
+                    r.get(3)?,
+                    r.get(4)?,
+                    r.get(5)?,
+                ))
+            })
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect()
+        };
+        assert_eq!(rows.len(), 2, "{rows:?}");
+        assert_eq!(
+            (
+                &rows[0].0[..],
+                &rows[0].1[..],
+                &rows[0].2[..],
+                rows[0].3,
+                rows[0].4
+            ),
+            ("run-1", "tbl", "complete", 3, 1500)
+        );
+        assert_eq!(rows[0].5.as_deref().map(str::len), Some(64));
+        let files: String = conn
+            .query_row(
+                "SELECT source_files FROM sqllog2db_loads LIMIT 1",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert!(files.contains("test.log"), "{files}");
+    }
 }
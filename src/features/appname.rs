@@ -0,0 +1,165 @@
+//! `[features.appname]`：把写法不一的 `appname`（`JDBC Thin Client`、`jdbc`、空值……）归一为
+//! 稳定的应用名，通过 `extra_fields = ["appname_normalized"]` 作为派生列导出，便于按应用分组。
+//!
+//! 规则按配置顺序匹配，首条命中生效：`exact` 与去除首尾空白后的 appname 比较（ASCII 字母不区分大小写），
+//! `pattern` 为正则；都未命中时取 `default`，未设置 `default` 时保留原值（去除首尾空白，空值为 NULL）。
+use crate::error::{ConfigError, Error, Result};
+use regex::Regex;
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// `[features.appname]` 配置段
+#[derive(Debug, Deserialize, Clone, Default)]
+#[non_exhaustive]
+pub struct AppNameConfig {
+    /// 归一规则：`{ exact = "JDBC Thin Client", name = "jdbc" }` 或 `{ pattern = '(?i)^jdbc', name = "jdbc" }`
+    #[serde(default)]
+    pub rules: Vec<AppNameRule>,
+    /// 未命中任何规则时的应用名；未设置时保留原值
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AppNameRule {
+    /// 精确匹配的 appname（不区分大小写；`""` 匹配空值）
+    #[serde(default)]
+    pub exact: Option<String>,
+    /// 匹配 appname 的正则表达式
+    #[serde(default)]
+    pub pattern: Option<String>,
+    /// 归一后的应用名
+    #[serde(default)]
+    pub name: String,
+}
+
+impl AppNameConfig {
+    /// 编译规则表；`exact` / `pattern` 未恰好设置一个、正则非法或 `name` 为空时返回 `ConfigError::InvalidValue`
+    pub fn compile(&self) -> Result<AppNameTable> {
+        let mut rules = Vec::with_capacity(self.rules.len());
+        for (i, r) in self.rules.iter().enumerate() {
+            let field = format!("features.appname.rules[{i}]");
+            let matcher = match (&r.exact, &r.pattern) {
+                (Some(exact), None) => Matcher::Exact(exact.trim().to_string()),
+                (None, Some(pattern)) => Matcher::Pattern(Regex::new(pattern).map_err(|e| {
+                    Error::Config(ConfigError::InvalidValue {
+                        field: format!("{field}.pattern"),
+                        value: pattern.clone(),
+                        reason: e.to_string(),
+                    })
+                })?),
+                _ => {
+                    return Err(Error::Config(ConfigError::InvalidValue {
+                        field,
+                        value: r.name.clone(),
+                        reason: "set exactly one of exact or pattern".to_string(),
+                    }));
+                }
+            };
+            if r.name.trim().is_empty() {
+                return Err(Error::Config(ConfigError::InvalidValue {
+                    field: format!("{field}.name"),
+                    value: r.name.clone(),
+                    reason: "name cannot be empty".to_string(),
+                }));
+            }
+            rules.push((matcher, Arc::from(r.name.as_str())));
+        }
+        Ok(AppNameTable {
+            rules,
+            default: self.default.as_deref().map(Arc::from),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    /// 已去除首尾空白
+    Exact(String),
+    Pattern(Regex),
+}
+
+/// 已编译的 appname 归一规则表
+#[derive(Debug, Clone, Default)]
+pub struct AppNameTable {
+    rules: Vec<(Matcher, Arc<str>)>,
+    default: Option<Arc<str>>,
+}
+
+impl AppNameTable {
+    /// 归一 `appname`；命中规则或 `default` 时返回的名称与规则表共享存储
+    #[must_use]
+    pub fn normalize(&self, appname: &str) -> Option<Arc<str>> {
+        let value = appname.trim();
+        let hit = self.rules.iter().find(|(m, _)| match m {
+            Matcher::Exact(exact) => value.eq_ignore_ascii_case(exact),
+            Matcher::Pattern(re) => re.is_match(value),
+        });
+        match (hit, &self.default) {
+            (Some((_, name)), _) | (None, Some(name)) => Some(Arc::clone(name)),
+            (None, None) => (!value.is_empty()).then(|| Arc::from(value)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(exact: Option<&str>, pattern: Option<&str>, name: &str) -> AppNameRule {
+        AppNameRule {
+            exact: exact.map(str::to_string),
+            pattern: pattern.map(str::to_string),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_normalize_exact_then_pattern_then_default() {
+        let mut cfg = AppNameConfig {
+            rules: vec![
+                rule(Some("JDBC Thin Client"), None, "jdbc"),
+                rule(None, Some("(?i)^jdbc"), "jdbc"),
+                rule(Some(""), None, "unknown"),
+                rule(None, Some("^disql"), "disql"),
+            ],
+            default: None,
+        };
+        let table = cfg.compile().unwrap();
+        let name = |s: &str| table.normalize(s).as_deref().map(str::to_string);
+        assert_eq!(name(" jdbc thin client ").as_deref(), Some("jdbc"));
+        assert_eq!(name("JDBC").as_deref(), Some("jdbc"));
+        assert_eq!(name("").as_deref(), Some("unknown"));
+        assert_eq!(name("disql.exe").as_deref(), Some("disql"));
+        // 未命中：保留原值
+        assert_eq!(name(" ERP ").as_deref(), Some("ERP"));
+
+        cfg.rules.truncate(1);
+        let table = cfg.compile().unwrap();
+        assert_eq!(table.normalize("  "), None);
+        cfg.default = Some("other".to_string());
+        let table = cfg.compile().unwrap();
+        assert_eq!(table.normalize("ERP").as_deref(), Some("other"));
+    }
+
+    #[test]
+    fn test_compile_rejects_invalid_rules() {
+        let compile = |r: AppNameRule| {
+            AppNameConfig {
+                rules: vec![r],
+                default: None,
+            }
+            .compile()
+            .unwrap_err()
+            .to_string()
+        };
+        let err = compile(rule(Some("a"), Some("b"), "x"));
+        assert!(err.contains("exactly one of exact or pattern"), "{err}");
+        assert!(compile(rule(None, None, "x")).contains("features.appname.rules[0]"));
+        let err = compile(rule(None, Some("("), "x"));
+        assert!(err.contains("rules[0].pattern"), "{err}");
+        let err = compile(rule(Some("a"), None, " "));
+        assert!(err.contains("name cannot be empty"), "{err}");
+    }
+}
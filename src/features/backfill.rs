@@ -0,0 +1,187 @@
+//! `[features.session_backfill]`：会话内补全 `appname` / `client_ip`。
+//!
+//! 达梦通常只在会话开始的记录中写出应用名与客户端地址，同一会话后续记录常为空，按应用 / 来源归因时
+//! 大量记录落空。启用后按 EP + sess 记住最近一次出现的非空值，为同一会话中该字段为空的记录补上。
+//! 状态跨文件延续（午夜轮转拆开的会话同样补全），因此只走顺序路径；会话地址被复用时，
+//! 新会话首条记录带出的新值会覆盖旧值。补全发生在过滤之前，按 appname / IP 的过滤同样作用于补全后的值。
+use crate::error::{ConfigError, Error, Result};
+use ahash::HashMap as AHashMap;
+use compact_str::CompactString;
+use dm_database_parser_sqllog::MetaParts;
+use serde::Deserialize;
+use std::borrow::Cow;
+
+/// 可补全的字段
+pub const BACKFILL_FIELDS: &[&str] = &["appname", "client_ip"];
+
+/// `[features.session_backfill]` 配置段
+#[derive(Debug, Deserialize, Clone)]
+#[non_exhaustive]
+pub struct SessionBackfillConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 需要补全的字段（默认 `appname` 与 `client_ip`）
+    #[serde(default = "default_fields")]
+    pub fields: Vec<String>,
+}
+
+fn default_fields() -> Vec<String> {
+    BACKFILL_FIELDS.iter().map(|f| (*f).to_string()).collect()
+}
+
+impl Default for SessionBackfillConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            fields: default_fields(),
+        }
+    }
+}
+
+impl SessionBackfillConfig {
+    pub fn validate(&self) -> Result<()> {
+        for field in &self.fields {
+            if !BACKFILL_FIELDS.contains(&field.as_str()) {
+                return Err(Error::Config(ConfigError::InvalidValue {
+                    field: "features.session_backfill.fields".to_string(),
+                    value: field.clone(),
+                    reason: format!("must be one of: {}", BACKFILL_FIELDS.join(", ")),
+                }));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct Known {
+    appname: Option<CompactString>,
+    client_ip: Option<CompactString>,
+}
+
+/// 运行期补全状态（跨文件累计）
+#[derive(Debug, Default)]
+pub struct SessionBackfill {
+    appname: bool,
+    client_ip: bool,
+    sessions: AHashMap<(u8, CompactString), Known>,
+    /// 已补全的 appname / `client_ip` 个数
+    filled: (u64, u64),
+}
+
+/// 空值记入 / 补全：非空时记住并返回 `false`，为空且有已知值时补上并返回 `true`
+fn fill(value: &mut Cow<'_, str>, known: &mut Option<CompactString>) -> bool {
+    if value.trim().is_empty() {
+        if let Some(k) = known {
+            *value = Cow::Owned(k.to_string());
+            return true;
+        }
+    } else if known.as_deref() != Some(value.as_ref()) {
+        *known = Some(CompactString::from(value.as_ref()));
+    }
+    false
+}
+
+impl SessionBackfill {
+    /// 未启用时返回 `None`
+    #[must_use]
+    pub fn from_config(config: Option<&SessionBackfillConfig>) -> Option<Self> {
+        let config = config.filter(|c| c.enabled && !c.fields.is_empty())?;
+        let has = |f: &str| config.fields.iter().any(|n| n == f);
+        Some(Self {
+            appname: has("appname"),
+            client_ip: has("client_ip"),
+            ..Self::default()
+        })
+    }
+
+    /// 记住本条记录带出的值，并补全为空的字段
+    pub fn apply(&mut self, meta: &mut MetaParts<'_>) {
+        let known = self
+            .sessions
+            .entry((meta.ep, CompactString::from(meta.sess_id.as_ref())))
+            .or_default();
+        if self.appname && fill(&mut meta.appname, &mut known.appname) {
+            self.filled.0 += 1;
+        }
+        if self.client_ip && fill(&mut meta.client_ip, &mut known.client_ip) {
+            self.filled.1 += 1;
+        }
+    }
+
+    /// 已补全的 `(appname, client_ip)` 个数
+    #[must_use]
+    pub fn filled(&self) -> (u64, u64) {
+        self.filled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(ep: u8, sess: &'static str, app: &'static str, ip: &'static str) -> MetaParts<'static> {
+        MetaParts {
+            ep,
+            sess_id: Cow::Borrowed(sess),
+            appname: Cow::Borrowed(app),
+            client_ip: Cow::Borrowed(ip),
+            ..MetaParts::default()
+        }
+    }
+
+    #[test]
+    fn test_backfill_from_session_history() {
+        let cfg = SessionBackfillConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        let mut b = SessionBackfill::from_config(Some(&cfg)).unwrap();
+
+        let mut first = meta(0, "0x1", "ERP", "10.0.0.1");
+        b.apply(&mut first);
+        let mut later = meta(0, "0x1", "", "");
+        b.apply(&mut later);
+        assert_eq!(
+            (later.appname.as_ref(), later.client_ip.as_ref()),
+            ("ERP", "10.0.0.1")
+        );
+        // 其他 EP 的同名会话互不影响
+        let mut other = meta(1, "0x1", "", "");
+        b.apply(&mut other);
+        assert_eq!(other.appname, "");
+        // 会话地址被复用：新值覆盖旧值
+        let mut reused = meta(0, "0x1", "disql", "");
+        b.apply(&mut reused);
+        let mut after = meta(0, "0x1", " ", "");
+        b.apply(&mut after);
+        assert_eq!(after.appname, "disql");
+        assert_eq!(b.filled(), (2, 3));
+    }
+
+    #[test]
+    fn test_backfill_config() {
+        assert!(SessionBackfill::from_config(None).is_none());
+        assert!(SessionBackfill::from_config(Some(&SessionBackfillConfig::default())).is_none());
+
+        let cfg = SessionBackfillConfig {
+            enabled: true,
+            fields: vec!["client_ip".to_string()],
+        };
+        let mut b = SessionBackfill::from_config(Some(&cfg)).unwrap();
+        b.apply(&mut meta(0, "0x1", "ERP", "10.0.0.1"));
+        let mut later = meta(0, "0x1", "", "");
+        b.apply(&mut later);
+        assert_eq!(
+            (later.appname.as_ref(), later.client_ip.as_ref()),
+            ("", "10.0.0.1")
+        );
+
+        let bad = SessionBackfillConfig {
+            enabled: true,
+            fields: vec!["username".to_string()],
+        };
+        let err = bad.validate().unwrap_err().to_string();
+        assert!(err.contains("features.session_backfill.fields"), "{err}");
+    }
+}
@@ -0,0 +1,207 @@
+//! `[features.client_zone]`：按 CIDR 子网表把 `client_ip` 映射为网络区域标签（如 `app-tier`、
+//! `office-vpn`），通过 `extra_fields = ["client_zone"]` 作为派生列导出。
+//!
+//! 多个子网同时命中时取前缀最长（最具体）的一条；IPv4 映射的 IPv6 地址（`::ffff:10.0.0.1`）按 IPv4 匹配。
+use crate::error::{ConfigError, Error, Result};
+use serde::Deserialize;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+/// `[features.client_zone]` 配置段
+#[derive(Debug, Deserialize, Clone, Default)]
+#[non_exhaustive]
+pub struct ClientZoneConfig {
+    /// 子网表：`{ cidr = "10.1.0.0/16", label = "app-tier" }`
+    #[serde(default)]
+    pub subnets: Vec<SubnetLabel>,
+    /// 未命中任何子网（或 IP 无法解析）时的标签；未设置时输出 NULL
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SubnetLabel {
+    #[serde(default)]
+    pub cidr: String,
+    #[serde(default)]
+    pub label: String,
+}
+
+impl ClientZoneConfig {
+    /// 编译子网表；CIDR 非法或标签为空返回 `ConfigError::InvalidValue`
+    pub fn compile(&self) -> Result<ZoneTable> {
+        let mut entries = Vec::with_capacity(self.subnets.len());
+        for (i, s) in self.subnets.iter().enumerate() {
+            let field = format!("features.client_zone.subnets[{i}]");
+            let (net, prefix) = parse_cidr(&s.cidr).map_err(|reason| {
+                Error::Config(ConfigError::InvalidValue {
+                    field: format!("{field}.cidr"),
+                    value: s.cidr.clone(),
+                    reason,
+                })
+            })?;
+            if s.label.trim().is_empty() {
+                return Err(Error::Config(ConfigError::InvalidValue {
+                    field: format!("{field}.label"),
+                    value: s.label.clone(),
+                    reason: "label cannot be empty".to_string(),
+                }));
+            }
+            entries.push(ZoneEntry {
+                net,
+                prefix,
+                label: Arc::from(s.label.as_str()),
+            });
+        }
+        // 前缀长的排在前面：首个命中即最具体的子网
+        entries.sort_by_key(|e| std::cmp::Reverse(e.prefix));
+        Ok(ZoneTable {
+            entries,
+            default: self.default.as_deref().map(Arc::from),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ZoneEntry {
+    net: IpAddr,
+    prefix: u8,
+    label: Arc<str>,
+}
+
+impl ZoneEntry {
+    fn contains(&self, ip: IpAddr) -> bool {
+        match (self.net, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// 已编译的子网表
+#[derive(Debug, Clone, Default)]
+pub struct ZoneTable {
+    entries: Vec<ZoneEntry>,
+    default: Option<Arc<str>>,
+}
+
+impl ZoneTable {
+    /// 查找 `client_ip` 所属区域；返回的标签与子网表共享存储，克隆开销为一次引用计数
+    #[must_use]
+    pub fn lookup(&self, client_ip: &str) -> Option<Arc<str>> {
+        let hit = client_ip
+            .trim()
+            .parse::<IpAddr>()
+            .ok()
+            .map(|ip| ip.to_canonical())
+            .and_then(|ip| self.entries.iter().find(|e| e.contains(ip)));
+        hit.map(|e| Arc::clone(&e.label))
+            .or_else(|| self.default.clone())
+    }
+}
+
+/// 解析 `10.0.0.0/8`、`2001:db8::/32`；省略前缀长度时视为单个主机
+fn parse_cidr(s: &str) -> std::result::Result<(IpAddr, u8), String> {
+    let (addr, prefix) = match s.trim().split_once('/') {
+        Some((a, p)) => (a, Some(p)),
+        None => (s.trim(), None),
+    };
+    let net = addr
+        .parse::<IpAddr>()
+        .map_err(|_| format!("invalid IP address '{addr}'"))?
+        .to_canonical();
+    let max = if net.is_ipv4() { 32 } else { 128 };
+    let prefix = match prefix {
+        Some(p) => p
+            .parse::<u8>()
+            .ok()
+            .filter(|&p| p <= max)
+            .ok_or_else(|| format!("prefix length must be 0-{max}"))?,
+        None => max,
+    };
+    Ok((net, prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(subnets: &[(&str, &str)], default: Option<&str>) -> ZoneTable {
+        ClientZoneConfig {
+            subnets: subnets
+                .iter()
+                .map(|(cidr, label)| SubnetLabel {
+                    cidr: (*cidr).to_string(),
+                    label: (*label).to_string(),
+                })
+                .collect(),
+            default: default.map(str::to_string),
+        }
+        .compile()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_longest_prefix_wins() {
+        let t = table(
+            &[("10.0.0.0/8", "intranet"), ("10.1.0.0/16", "app-tier")],
+            None,
+        );
+        assert_eq!(t.lookup("10.1.2.3").as_deref(), Some("app-tier"));
+        assert_eq!(t.lookup("10.2.0.1").as_deref(), Some("intranet"));
+        assert_eq!(t.lookup("192.168.0.1"), None);
+    }
+
+    #[test]
+    fn test_mapped_ipv6_default_and_garbage() {
+        let t = table(
+            &[("192.168.10.0/24", "office-vpn"), ("fd00::/8", "v6-lab")],
+            Some("external"),
+        );
+        assert_eq!(
+            t.lookup("::ffff:192.168.10.7").as_deref(),
+            Some("office-vpn")
+        );
+        assert_eq!(t.lookup("fd12::1").as_deref(), Some("v6-lab"));
+        assert_eq!(t.lookup("8.8.8.8").as_deref(), Some("external"));
+        assert_eq!(t.lookup("").as_deref(), Some("external"));
+    }
+
+    #[test]
+    fn test_host_and_zero_prefix() {
+        let t = table(&[("0.0.0.0/0", "any"), ("10.0.0.5", "db-host")], None);
+        assert_eq!(t.lookup("10.0.0.5").as_deref(), Some("db-host"));
+        assert_eq!(t.lookup("10.0.0.6").as_deref(), Some("any"));
+    }
+
+    #[test]
+    fn test_invalid_cidr_rejected() {
+        for cidr in ["10.0.0.0/33", "10.0.0/8", "fd00::/129"] {
+            let cfg = ClientZoneConfig {
+                subnets: vec![SubnetLabel {
+                    cidr: cidr.to_string(),
+                    label: "x".to_string(),
+                }],
+                default: None,
+            };
+            let err = cfg.compile().unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains("features.client_zone.subnets[0].cidr"),
+                "{err}"
+            );
+        }
+    }
+}
@@ -0,0 +1,256 @@
+//! `[features.dedup]`：跳过跨文件重复出现的记录。
+//!
+//! 达梦重写 / 轮转日志文件时，同一条记录（同一 `EXEC_ID`）可能同时出现在新旧两个文件中。
+//! 按配置的键（默认 `exec_id` + `ts` + `sess_id`）计算 64 位哈希，已见过的记录不再导出，
+//! 计入导出统计的 `duplicates`。
+//!
+//! 内存有界：哈希分两代保存，当前代写满（`max_memory_mb` 的一半）后整体降为上一代、旧的上一代丢弃，
+//! 只与最近的约 `max_memory_mb` × 6.5 万条记录比较。重复记录出现在相邻文件中，这个窗口足以覆盖；
+//! 相隔更远的重复不会被识别，但不会误删不同的记录（哈希碰撞概率可忽略）。
+use crate::error::{ConfigError, Error, Result};
+use ahash::HashSet as AHashSet;
+use dm_database_parser_sqllog::{MetaParts, PerformanceMetrics, Sqllog};
+use serde::Deserialize;
+use std::hash::{BuildHasher, Hash, Hasher};
+
+/// 可用作去重键的字段
+pub const DEDUP_KEY_FIELDS: &[&str] = &[
+    "ts",
+    "ep",
+    "sess_id",
+    "thrd_id",
+    "username",
+    "trx_id",
+    "statement",
+    "appname",
+    "client_ip",
+    "sql",
+    "exec_id",
+];
+
+/// 每个哈希项估算占用的字节数（8 字节哈希 + 哈希表负载余量）
+const BYTES_PER_ENTRY: u64 = 16;
+
+/// `[features.dedup]` 配置段
+#[derive(Debug, Deserialize, Clone)]
+#[non_exhaustive]
+pub struct DedupConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 判定重复的字段组合（见 [`DEDUP_KEY_FIELDS`]）
+    #[serde(default = "default_key")]
+    pub key: Vec<String>,
+    /// 去重哈希表的内存上限（MiB）
+    #[serde(default = "default_max_memory_mb")]
+    pub max_memory_mb: u64,
+}
+
+fn default_key() -> Vec<String> {
+    ["exec_id", "ts", "sess_id"]
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn default_max_memory_mb() -> u64 {
+    64
+}
+
+impl Default for DedupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            key: default_key(),
+            max_memory_mb: default_max_memory_mb(),
+        }
+    }
+}
+
+impl DedupConfig {
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |field: &str, value: String, reason: String| {
+            Err(Error::Config(ConfigError::InvalidValue {
+                field: format!("features.dedup.{field}"),
+                value,
+                reason,
+            }))
+        };
+        if self.key.is_empty() {
+            return invalid(
+                "key",
+                "[]".to_string(),
+                "must list at least one field".into(),
+            );
+        }
+        if let Some(bad) = self
+            .key
+            .iter()
+            .find(|k| !DEDUP_KEY_FIELDS.contains(&k.as_str()))
+        {
+            return invalid(
+                "key",
+                bad.clone(),
+                format!("must be one of: {}", DEDUP_KEY_FIELDS.join(", ")),
+            );
+        }
+        if self.max_memory_mb == 0 {
+            return invalid(
+                "max_memory_mb",
+                "0".to_string(),
+                "must be greater than 0".into(),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// 运行期去重状态（跨文件累计）
+#[derive(Debug)]
+pub struct Deduplicator {
+    /// 键字段在 [`DEDUP_KEY_FIELDS`] 中的下标
+    key: Vec<usize>,
+    hasher: ahash::RandomState,
+    current: AHashSet<u64>,
+    previous: AHashSet<u64>,
+    /// 每一代最多保存的哈希数
+    generation_size: usize,
+}
+
+impl Deduplicator {
+    /// 未启用去重时返回 `None`；键字段已在配置校验阶段检查
+    #[must_use]
+    pub fn from_config(config: Option<&DedupConfig>) -> Option<Self> {
+        let config = config.filter(|c| c.enabled)?;
+        let entries = config.max_memory_mb.saturating_mul(1024 * 1024) / BYTES_PER_ENTRY;
+        Some(Self {
+            key: config
+                .key
+                .iter()
+                .filter_map(|k| DEDUP_KEY_FIELDS.iter().position(|f| f == k))
+                .collect(),
+            hasher: ahash::RandomState::new(),
+            current: AHashSet::default(),
+            previous: AHashSet::default(),
+            generation_size: usize::try_from(entries / 2).unwrap_or(usize::MAX).max(1),
+        })
+    }
+
+    fn hash(&self, record: &Sqllog<'_>, meta: &MetaParts<'_>, pm: &PerformanceMetrics<'_>) -> u64 {
+        let mut h = self.hasher.build_hasher();
+        for &i in &self.key {
+            match DEDUP_KEY_FIELDS[i] {
+                "ts" => record.ts.as_ref().hash(&mut h),
+                "ep" => meta.ep.hash(&mut h),
+                "sess_id" => meta.sess_id.as_ref().hash(&mut h),
+                "thrd_id" => meta.thrd_id.as_ref().hash(&mut h),
+                "username" => meta.username.as_ref().hash(&mut h),
+                "trx_id" => meta.trxid.as_ref().hash(&mut h),
+                "statement" => meta.statement.as_ref().hash(&mut h),
+                "appname" => meta.appname.as_ref().hash(&mut h),
+                "client_ip" => meta.client_ip.as_ref().hash(&mut h),
+                "sql" => pm.sql.as_ref().hash(&mut h),
+                _ => pm.exec_id.hash(&mut h),
+            }
+        }
+        h.finish()
+    }
+
+    /// 判定记录是否已出现过：是则返回 `true`，否则登记该记录
+    pub fn is_duplicate(
+        &mut self,
+        record: &Sqllog<'_>,
+        meta: &MetaParts<'_>,
+        pm: &PerformanceMetrics<'_>,
+    ) -> bool {
+        let hash = self.hash(record, meta, pm);
+        if self.current.contains(&hash) || self.previous.contains(&hash) {
+            return true;
+        }
+        if self.current.len() >= self.generation_size {
+            self.previous = std::mem::take(&mut self.current);
+        }
+        self.current.insert(hash);
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt::Write as _;
+
+    const LOG: &str = "2025-01-15 10:30:28.001 (EP[0] sess:0x1 thrd:1 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 7.\n\
+2025-01-15 10:30:28.001 (EP[0] sess:0x2 thrd:1 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 7.\n\
+2025-01-15 10:30:28.001 (EP[0] sess:0x1 thrd:1 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 7.\n\
+2025-01-15 10:30:29.001 (EP[0] sess:0x1 thrd:1 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 8.\n";
+
+    fn run(config: &DedupConfig) -> Vec<bool> {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("t.log");
+        std::fs::write(&path, LOG).unwrap();
+        let parser = dm_database_parser_sqllog::LogParser::from_path(&path).unwrap();
+        let mut dedup = Deduplicator::from_config(Some(config)).unwrap();
+        parser
+            .iter()
+            .flatten()
+            .map(|r| dedup.is_duplicate(&r, &r.parse_meta(), &r.parse_performance_metrics()))
+            .collect()
+    }
+
+    #[test]
+    fn test_dedup_on_default_key() {
+        assert!(Deduplicator::from_config(Some(&DedupConfig::default())).is_none());
+        let config = DedupConfig {
+            enabled: true,
+            ..DedupConfig::default()
+        };
+        assert_eq!(run(&config), [false, false, true, false]);
+
+        // 只按 exec_id 去重：不同会话的同号记录同样视为重复
+        let by_exec_id = DedupConfig {
+            key: vec!["exec_id".to_string()],
+            ..config
+        };
+        assert_eq!(run(&by_exec_id), [false, true, true, false]);
+    }
+
+    #[test]
+    fn test_generations_bound_memory() {
+        let mut dedup = Deduplicator::from_config(Some(&DedupConfig {
+            enabled: true,
+            ..DedupConfig::default()
+        }))
+        .unwrap();
+        dedup.generation_size = 2;
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("t.log");
+        let mut log = String::new();
+        for id in [1, 2, 3, 4, 5, 1, 5] {
+            writeln!(
+                log,
+                "2025-01-15 10:30:28.001 (EP[0] sess:0x1 thrd:1 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: {id}."
+            )
+            .unwrap();
+        }
+        std::fs::write(&path, log).unwrap();
+        let parser = dm_database_parser_sqllog::LogParser::from_path(&path).unwrap();
+        let dup: Vec<bool> = parser
+            .iter()
+            .flatten()
+            .map(|r| dedup.is_duplicate(&r, &r.parse_meta(), &r.parse_performance_metrics()))
+            .collect();
+        // 最多保留两代（4 个哈希）：1 已被淘汰，5 仍在窗口内
+        assert_eq!(dup, [false, false, false, false, false, false, true]);
+        assert!(dedup.current.len() + dedup.previous.len() <= 4);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut config = DedupConfig::default();
+        assert!(config.validate().is_ok());
+        config.key = vec!["exec_time_ms".to_string()];
+        assert!(config.validate().is_err());
+        config.key = Vec::new();
+        assert!(config.validate().is_err());
+    }
+}
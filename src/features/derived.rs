@@ -0,0 +1,907 @@
+//! 派生列：由记录已有字段计算、追加在 15 个基础字段之后的可选列。
+//!
+//! 通过 `[features] extra_fields = ["ts_epoch_ms"]` 启用，`[[features.extract]]` 规则提取的列追加在其后；
+//! 未配置时不产生任何列，热循环走原有 `export_one_preparsed` 路径，零额外开销。
+use super::FeaturesConfig;
+use super::appname::AppNameTable;
+use super::client_zone::ZoneTable;
+use super::extract::Extractor;
+use super::replace_parameters::{ParamValue, count_placeholders, format_params};
+use super::statements::{sql_hash, statement_id};
+use super::time::{Tz, parse_dm_ts, to_utc};
+use super::trace::trace_context;
+use super::user_owner::OwnerTable;
+use crate::error::{ConfigError, Error, Result};
+use ahash::HashMap as AHashMap;
+use compact_str::CompactString;
+use dm_database_parser_sqllog::{MetaParts, PerformanceMetrics, Sqllog};
+use std::sync::Arc;
+
+/// 可通过 `features.extra_fields` 启用的派生列名
+pub const DERIVED_FIELD_NAMES: &[&str] = &[
+    "ts_epoch_ms",     // ts 对应的 Unix 毫秒时间戳（按 features.time.input_timezone 解释）
+    "client_zone",     // client_ip 所属网络区域（按 features.client_zone 子网表映射）
+    "owner_team",      // username 归属团队（按 features.user_owner 查找文件关联）
+    "app_service",     // username 归属应用服务（同上）
+    "recovered",       // 经 lenient 解析容错修复的记录为 1，否则为 0（见 sqllog.parse_mode）
+    "seq",             // 导出顺序编号（从 1 开始），按 features.seq_scope 在文件或会话内递增
+    "statement_id",    // SQL 指纹的稳定 ID（见 features.statements），PARAMS 记录为空
+    "trace_id",        // SQL 注释中的链路 trace id（sqlcommenter traceparent 或 trace_id 标签）
+    "span_id",         // 同上，span id
+    "session_id",      // 拼接后的会话标识：EP + sess + 第几次出现（见 features.session_idle_secs）
+    "run_id",          // 本次运行的标识（与应用日志、完成摘要中的一致），区分多次运行写入的同一输出
+    "sql_fingerprint", // SQL 指纹：字面量替换为 `?`、空白折叠（见 features.normalize_sql），PARAMS 记录为空
+    "sql_hash",        // SQL 指纹的稳定哈希（16 位十六进制），按语句形态 GROUP BY 用
+    "sql_length",      // SQL 文本的字符数
+    "param_count",     // SQL 中的参数占位符个数（`?` 个数或 `:N` 最大序号），PARAMS 记录为空
+    "line_count",      // SQL 文本的行数
+    "appname_normalized", // 归一后的应用名（按 features.appname 规则映射）
+    "sql_params", // 执行记录绑定的参数值（合并自同一会话语句句柄的 PARAMS 记录），启用后 PARAMS 记录不再单独导出
+];
+
+/// `[features.normalize_sql]` 启用的派生列
+const NORMALIZE_SQL_FIELDS: &[&str] = &["sql_fingerprint", "sql_hash"];
+
+/// `features.seq_scope` 可选值
+pub const SEQ_SCOPES: &[&str] = &["file", "session"];
+
+/// `seq` 派生列的编号范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SeqScope {
+    /// 每个输入文件从 1 开始
+    #[default]
+    File,
+    /// 每个会话（EP + sess）从 1 开始，跨文件延续
+    Session,
+}
+
+/// 记录在所属会话中的位置
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionPos {
+    /// 会话内编号（从 1 开始）
+    pub seq: u64,
+    /// 同一 EP + sess 的第几个会话（从 1 开始；会话地址被复用时递增）
+    pub session: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SessionState {
+    pos: SessionPos,
+    /// 最近一条记录的时间（毫秒）
+    last_ms: Option<i64>,
+}
+
+/// 会话拼接：按 EP + sess 跨文件延续（午夜轮转拆开的会话在下一个文件中接续），
+/// 相邻记录间隔超过空闲超时时视为会话地址被复用，开始新会话
+#[derive(Debug, Default)]
+pub struct SessionSeq {
+    idle_ms: Option<i64>,
+    sessions: AHashMap<(u8, CompactString), SessionState>,
+}
+
+impl SessionSeq {
+    /// `idle_secs` 为 `None` 时不按空闲切分
+    #[must_use]
+    pub fn new(idle_secs: Option<u64>) -> Self {
+        Self {
+            idle_ms: idle_secs.map(|s| i64::try_from(s.saturating_mul(1000)).unwrap_or(i64::MAX)),
+            sessions: AHashMap::default(),
+        }
+    }
+
+    /// 返回该记录在会话中的位置；时间戳无法解析或早于上一条（乱序落盘）时不切分
+    pub fn next(&mut self, meta: &MetaParts<'_>, ts: &str) -> SessionPos {
+        let now = parse_dm_ts(ts).map(|t| t.and_utc().timestamp_millis());
+        let state = self
+            .sessions
+            .entry((meta.ep, CompactString::from(meta.sess_id.as_ref())))
+            .or_insert(SessionState {
+                pos: SessionPos { seq: 0, session: 1 },
+                last_ms: None,
+            });
+        let idle = match (self.idle_ms, state.last_ms, now) {
+            (Some(limit), Some(last), Some(now)) => now - last > limit,
+            _ => false,
+        };
+        if idle {
+            state.pos = SessionPos {
+                seq: 0,
+                session: state.pos.session + 1,
+            };
+        }
+        state.pos.seq += 1;
+        if now > state.last_ms {
+            state.last_ms = now;
+        }
+        state.pos
+    }
+}
+
+/// 派生列计算所需的逐条运行期信息（不来自记录本身）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RowInfo<'a> {
+    /// 记录是否经解析容错修复
+    pub recovered: bool,
+    /// `seq` 编号
+    pub seq: u64,
+    /// 语句登记表已计算出的 `statement_id`（未启用登记表时为 `None`，由派生列自行计算）
+    pub statement_id: Option<i64>,
+    /// 同一 EP + sess 的第几个会话（未启用会话拼接时为 0）
+    pub session: u64,
+    /// 执行记录绑定的参数值（未启用 `sql_params` 列或没有对应 PARAMS 记录时为 `None`）
+    pub params: Option<&'a [ParamValue]>,
+}
+
+/// 派生列的存储类型（决定建表时的列类型）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DerivedKind {
+    Integer,
+    Text,
+}
+
+impl DerivedKind {
+    #[must_use]
+    pub fn sql_type(self) -> &'static str {
+        match self {
+            Self::Integer => "INTEGER",
+            Self::Text => "TEXT",
+        }
+    }
+}
+
+/// 派生列定义（导出器用于生成 header / 建表语句）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivedColumnSpec {
+    pub name: String,
+    pub kind: DerivedKind,
+}
+
+/// 单条记录的派生列值
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DerivedValue {
+    Null,
+    Int(i64),
+    /// 文本值（与配置共享存储，逐条克隆仅增加引用计数）
+    Text(Arc<str>),
+}
+
+#[derive(Debug, Clone)]
+enum Deriver {
+    TsEpochMs(Tz),
+    ClientZone(ZoneTable),
+    AppName(AppNameTable),
+    OwnerTeam(Arc<OwnerTable>),
+    AppService(Arc<OwnerTable>),
+    Recovered,
+    Seq,
+    StatementId,
+    TraceId,
+    SpanId,
+    SessionId,
+    RunId(Arc<str>),
+    SqlFingerprint,
+    SqlHash,
+    SqlLength,
+    ParamCount,
+    LineCount,
+    SqlParams,
+    Extract(Extractor),
+}
+
+impl Deriver {
+    fn kind(&self) -> DerivedKind {
+        match self {
+            Self::TsEpochMs(_)
+            | Self::Recovered
+            | Self::Seq
+            | Self::StatementId
+            | Self::SqlLength
+            | Self::ParamCount
+            | Self::LineCount => DerivedKind::Integer,
+            Self::ClientZone(_)
+            | Self::AppName(_)
+            | Self::OwnerTeam(_)
+            | Self::AppService(_)
+            | Self::TraceId
+            | Self::SpanId
+            | Self::SessionId
+            | Self::RunId(_)
+            | Self::SqlFingerprint
+            | Self::SqlHash
+            | Self::SqlParams
+            | Self::Extract(_) => DerivedKind::Text,
+        }
+    }
+
+    #[inline]
+    fn compute(
+        &self,
+        sqllog: &Sqllog<'_>,
+        meta: &MetaParts<'_>,
+        pm: &PerformanceMetrics<'_>,
+        row: RowInfo<'_>,
+        fp: &mut Option<Arc<str>>,
+    ) -> DerivedValue {
+        match self {
+            Self::TsEpochMs(tz) => to_utc(sqllog.ts.as_ref(), *tz)
+                .map_or(DerivedValue::Null, |d| {
+                    DerivedValue::Int(d.timestamp_millis())
+                }),
+            Self::ClientZone(table) => table
+                .lookup(meta.client_ip.as_ref())
+                .map_or(DerivedValue::Null, DerivedValue::Text),
+            Self::AppName(table) => table
+                .normalize(meta.appname.as_ref())
+                .map_or(DerivedValue::Null, DerivedValue::Text),
+            Self::OwnerTeam(table) => text(
+                table
+                    .lookup(meta.username.as_ref())
+                    .and_then(|e| e.owner_team.as_ref()),
+            ),
+            Self::AppService(table) => text(
+                table
+                    .lookup(meta.username.as_ref())
+                    .and_then(|e| e.app_service.as_ref()),
+            ),
+            Self::Recovered => DerivedValue::Int(i64::from(row.recovered)),
+            Self::Seq => DerivedValue::Int(i64::try_from(row.seq).unwrap_or(i64::MAX)),
+            // PARAMS 记录没有可指纹化的语句
+            Self::StatementId | Self::SqlFingerprint | Self::SqlHash | Self::ParamCount
+                if sqllog.tag.is_none() =>
+            {
+                DerivedValue::Null
+            }
+            Self::StatementId => DerivedValue::Int(
+                row.statement_id
+                    .unwrap_or_else(|| statement_id(&super::fingerprint(pm.sql.as_ref()))),
+            ),
+            Self::TraceId => text_str(trace_context(pm.sql.as_ref()).trace_id),
+            Self::SpanId => text_str(trace_context(pm.sql.as_ref()).span_id),
+            Self::SessionId => DerivedValue::Text(Arc::from(format!(
+                "EP[{}] {} #{}",
+                meta.ep, meta.sess_id, row.session
+            ))),
+            Self::RunId(id) => DerivedValue::Text(Arc::clone(id)),
+            Self::SqlFingerprint => DerivedValue::Text(Arc::clone(
+                fp.get_or_insert_with(|| Arc::from(super::fingerprint(pm.sql.as_ref()))),
+            )),
+            Self::SqlHash => DerivedValue::Text(Arc::from(sql_hash(
+                fp.get_or_insert_with(|| Arc::from(super::fingerprint(pm.sql.as_ref()))),
+            ))),
+            Self::SqlLength => count(pm.sql.chars().count()),
+            Self::ParamCount => count(count_placeholders(pm.sql.as_ref()).0),
+            Self::LineCount => count(sql_lines(pm.sql.as_ref())),
+            Self::SqlParams => row.params.map_or(DerivedValue::Null, |p| {
+                DerivedValue::Text(Arc::from(format_params(p)))
+            }),
+            Self::Extract(x) => text_str(x.extract(meta, pm)),
+        }
+    }
+}
+
+fn count(n: usize) -> DerivedValue {
+    DerivedValue::Int(i64::try_from(n).unwrap_or(i64::MAX))
+}
+
+/// SQL 文本的行数：空文本为 0，末尾换行不单独成行
+fn sql_lines(sql: &str) -> usize {
+    let sql = sql.strip_suffix('\n').unwrap_or(sql);
+    if sql.is_empty() {
+        0
+    } else {
+        memchr::memchr_iter(b'\n', sql.as_bytes()).count() + 1
+    }
+}
+
+fn text(value: Option<&Arc<str>>) -> DerivedValue {
+    value.map_or(DerivedValue::Null, |v| DerivedValue::Text(Arc::clone(v)))
+}
+
+fn text_str(value: Option<&str>) -> DerivedValue {
+    value.map_or(DerivedValue::Null, |v| DerivedValue::Text(Arc::from(v)))
+}
+
+/// 已编译的派生列集合
+#[derive(Debug, Clone, Default)]
+pub struct DerivedColumns {
+    columns: Vec<(String, Deriver)>,
+    /// 启用 `seq` 列时的编号范围
+    seq_scope: Option<SeqScope>,
+    /// 会话空闲超时（秒）
+    session_idle_secs: Option<u64>,
+}
+
+impl DerivedColumns {
+    /// 从 `[features]` 配置编译派生列；未知列名返回 `ConfigError::InvalidValue`
+    pub fn from_config(features: &FeaturesConfig) -> Result<Self> {
+        let mut columns = Vec::new();
+        let mut seq_scope = None;
+        // owner_team / app_service 共用同一份查找表，只加载一次
+        let mut owners: Option<Arc<OwnerTable>> = None;
+        let normalize_sql = features.normalize_sql.as_ref().is_some_and(|n| n.enabled);
+        let extra = features.extra_fields.iter().flatten().map(String::as_str);
+        // normalize_sql 追加的列排在 extra_fields 之后，已显式列出的不重复
+        let implied = NORMALIZE_SQL_FIELDS
+            .iter()
+            .copied()
+            .filter(|f| normalize_sql && !features.extra_fields.iter().flatten().any(|n| n == f));
+        for name in extra.chain(implied) {
+            let name = name.to_string();
+            let deriver = match name.as_str() {
+                "ts_epoch_ms" => {
+                    let tz = match &features.time {
+                        Some(t) => t.input_tz()?,
+                        None => Tz::Local,
+                    };
+                    Deriver::TsEpochMs(tz)
+                }
+                "client_zone" => {
+                    let zones = features.client_zone.as_ref().ok_or_else(|| {
+                        Error::Config(ConfigError::InvalidValue {
+                            field: "features.extra_fields".to_string(),
+                            value: name.clone(),
+                            reason: "client_zone requires a [features.client_zone] subnet table"
+                                .to_string(),
+                        })
+                    })?;
+                    Deriver::ClientZone(zones.compile()?)
+                }
+                "appname_normalized" => {
+                    let rules = features.appname.as_ref().ok_or_else(|| {
+                        Error::Config(ConfigError::InvalidValue {
+                            field: "features.extra_fields".to_string(),
+                            value: name.clone(),
+                            reason: "appname_normalized requires a [features.appname] rule table"
+                                .to_string(),
+                        })
+                    })?;
+                    Deriver::AppName(rules.compile()?)
+                }
+                "owner_team" | "app_service" => {
+                    let table = if let Some(t) = &owners {
+                        Arc::clone(t)
+                    } else {
+                        let cfg = features.user_owner.as_ref().ok_or_else(|| {
+                            Error::Config(ConfigError::InvalidValue {
+                                field: "features.extra_fields".to_string(),
+                                value: name.clone(),
+                                reason: format!(
+                                    "{name} requires a [features.user_owner] lookup file"
+                                ),
+                            })
+                        })?;
+                        Arc::clone(owners.insert(Arc::new(cfg.load()?)))
+                    };
+                    if name == "owner_team" {
+                        Deriver::OwnerTeam(table)
+                    } else {
+                        Deriver::AppService(table)
+                    }
+                }
+                "recovered" => Deriver::Recovered,
+                "statement_id" => Deriver::StatementId,
+                "trace_id" => Deriver::TraceId,
+                "span_id" => Deriver::SpanId,
+                "session_id" => Deriver::SessionId,
+                "run_id" => Deriver::RunId(crate::run_id::current()),
+                "sql_fingerprint" => Deriver::SqlFingerprint,
+                "sql_hash" => Deriver::SqlHash,
+                "sql_length" => Deriver::SqlLength,
+                "param_count" => Deriver::ParamCount,
+                "line_count" => Deriver::LineCount,
+                "sql_params" => Deriver::SqlParams,
+                "seq" => {
+                    seq_scope = Some(match features.seq_scope.as_deref() {
+                        None | Some("file") => SeqScope::File,
+                        Some("session") => SeqScope::Session,
+                        Some(other) => {
+                            return Err(Error::Config(ConfigError::InvalidValue {
+                                field: "features.seq_scope".to_string(),
+                                value: other.to_string(),
+                                reason: format!("must be one of: {}", SEQ_SCOPES.join(", ")),
+                            }));
+                        }
+                    });
+                    Deriver::Seq
+                }
+                _ => {
+                    return Err(Error::Config(ConfigError::InvalidValue {
+                        field: "features.extra_fields".to_string(),
+                        value: name.clone(),
+                        reason: format!(
+                            "unknown derived field '{name}'; valid fields: {}",
+                            DERIVED_FIELD_NAMES.join(", ")
+                        ),
+                    }));
+                }
+            };
+            columns.push((name, deriver));
+        }
+        for rule in features.extract.iter().flatten() {
+            let taken: Vec<&str> = columns.iter().map(|(n, _)| n.as_str()).collect();
+            let extractor = rule.compile(&taken)?;
+            columns.push((rule.name.clone(), Deriver::Extract(extractor)));
+        }
+        if features.session_idle_secs == Some(0) {
+            return Err(Error::Config(ConfigError::InvalidValue {
+                field: "features.session_idle_secs".to_string(),
+                value: "0".to_string(),
+                reason: "must be greater than 0; omit it to disable idle splitting".to_string(),
+            }));
+        }
+        Ok(Self {
+            columns,
+            seq_scope,
+            session_idle_secs: features.session_idle_secs,
+        })
+    }
+
+    /// 设定 `run_id` 列的值（编译时取 [`crate::run_id::current`]，运行开始后换成本次运行的标识）
+    pub fn set_run_id(&mut self, run_id: &Arc<str>) {
+        for (_, deriver) in &mut self.columns {
+            if let Deriver::RunId(id) = deriver {
+                *id = Arc::clone(run_id);
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// 启用 `seq` 列时的编号范围
+    #[must_use]
+    pub fn seq_scope(&self) -> Option<SeqScope> {
+        self.seq_scope
+    }
+
+    /// 是否需要逐条跟踪会话（会话级 `seq` 或 `session_id` 列）；跟踪状态跨文件延续，只能走顺序路径
+    #[must_use]
+    pub fn tracks_sessions(&self) -> bool {
+        self.seq_scope == Some(SeqScope::Session)
+            || self
+                .columns
+                .iter()
+                .any(|(_, d)| matches!(d, Deriver::SessionId))
+    }
+
+    /// 是否启用 `sql_params` 列：PARAMS 记录合并到所属执行记录，不再单独导出
+    #[must_use]
+    pub fn merges_params(&self) -> bool {
+        self.columns
+            .iter()
+            .any(|(_, d)| matches!(d, Deriver::SqlParams))
+    }
+
+    /// 按配置的空闲超时创建会话拼接状态
+    #[must_use]
+    pub fn sessions(&self) -> SessionSeq {
+        SessionSeq::new(self.session_idle_secs)
+    }
+
+    /// 导出器所需的列定义（按配置顺序）
+    #[must_use]
+    pub fn specs(&self) -> Vec<DerivedColumnSpec> {
+        self.columns
+            .iter()
+            .map(|(name, d)| DerivedColumnSpec {
+                name: name.clone(),
+                kind: d.kind(),
+            })
+            .collect()
+    }
+
+    /// 计算单条记录的全部派生列，写入 `out`（先清空，复用容量）
+    #[inline]
+    pub fn compute_into(
+        &self,
+        sqllog: &Sqllog<'_>,
+        meta: &MetaParts<'_>,
+        pm: &PerformanceMetrics<'_>,
+        row: RowInfo<'_>,
+        out: &mut Vec<DerivedValue>,
+    ) {
+        out.clear();
+        // sql_fingerprint / sql_hash 同时启用时指纹只算一次
+        let mut fp = None;
+        out.extend(
+            self.columns
+                .iter()
+                .map(|(_, d)| d.compute(sqllog, meta, pm, row, &mut fp)),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::TimeConfig;
+    use std::borrow::Cow;
+
+    fn features(extra: &[&str], time: Option<TimeConfig>) -> FeaturesConfig {
+        FeaturesConfig {
+            extra_fields: Some(extra.iter().map(|s| (*s).to_string()).collect()),
+            time,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_empty_when_not_configured() {
+        let cols = DerivedColumns::from_config(&FeaturesConfig::default()).unwrap();
+        assert!(cols.is_empty());
+        assert!(cols.specs().is_empty());
+    }
+
+    #[test]
+    fn test_unknown_field_rejected() {
+        let err = DerivedColumns::from_config(&features(&["nope"], None)).unwrap_err();
+        assert!(err.to_string().contains("features.extra_fields"));
+    }
+
+    #[test]
+    fn test_seq_scope() {
+        let mut f = features(&["seq"], None);
+        let cols = DerivedColumns::from_config(&f).unwrap();
+        assert_eq!(cols.seq_scope(), Some(SeqScope::File));
+        f.seq_scope = Some("thread".into());
+        let err = DerivedColumns::from_config(&f).unwrap_err();
+        assert!(err.to_string().contains("features.seq_scope"), "{err}");
+
+        let mut seq = SessionSeq::default();
+        let meta = |sess: &'static str| MetaParts {
+            sess_id: Cow::Borrowed(sess),
+            ..MetaParts::default()
+        };
+        let ts = "2025-01-15 10:00:00.000";
+        assert_eq!(seq.next(&meta("0x1"), ts).seq, 1);
+        assert_eq!(seq.next(&meta("0x2"), ts).seq, 1);
+        assert_eq!(seq.next(&meta("0x1"), ts).seq, 2);
+    }
+
+    #[test]
+    fn test_session_stitching_with_idle_timeout() {
+        let mut f = features(&["session_id"], None);
+        f.session_idle_secs = Some(0);
+        let err = DerivedColumns::from_config(&f).unwrap_err();
+        assert!(
+            err.to_string().contains("features.session_idle_secs"),
+            "{err}"
+        );
+        f.session_idle_secs = Some(600);
+        let cols = DerivedColumns::from_config(&f).unwrap();
+        assert!(cols.tracks_sessions());
+
+        let meta = MetaParts {
+            sess_id: Cow::Borrowed("0x1"),
+            ..MetaParts::default()
+        };
+        let mut sessions = cols.sessions();
+        // 跨午夜（文件轮转）间隔 2 分钟：同一会话
+        let first = sessions.next(&meta, "2025-01-15 23:59:00.000");
+        let next_day = sessions.next(&meta, "2025-01-16 00:01:00.000");
+        assert_eq!((next_day.seq, next_day.session), (2, 1));
+        // 乱序落盘的记录不切分
+        let late = sessions.next(&meta, "2025-01-15 23:58:00.000");
+        assert_eq!((late.seq, late.session), (3, 1));
+        // 空闲超过 10 分钟：会话地址被复用，开始新会话
+        let reused = sessions.next(&meta, "2025-01-16 00:30:00.000");
+        assert_eq!((reused.seq, reused.session), (1, 2));
+
+        let mut out = Vec::new();
+        let row = RowInfo {
+            session: reused.session,
+            ..RowInfo::default()
+        };
+        cols.compute_into(
+            &Sqllog::default(),
+            &meta,
+            &PerformanceMetrics::default(),
+            row,
+            &mut out,
+        );
+        assert_eq!(out, [DerivedValue::Text("EP[0] 0x1 #2".into())]);
+        assert_eq!(first.session, 1);
+
+        // 未配置超时时不切分
+        let mut unlimited = SessionSeq::new(None);
+        unlimited.next(&meta, "2025-01-15 00:00:00.000");
+        assert_eq!(unlimited.next(&meta, "2025-01-20 00:00:00.000").session, 1);
+    }
+
+    #[test]
+    fn test_statement_id_matches_registry() {
+        let cols = DerivedColumns::from_config(&features(&["statement_id"], None)).unwrap();
+        let pm = PerformanceMetrics {
+            sql: Cow::Borrowed("SELECT * FROM t WHERE id = 7"),
+            ..PerformanceMetrics::default()
+        };
+        let mut dml = Sqllog::default();
+        dml.tag = Some(Cow::Borrowed("SEL"));
+        let expected = crate::features::StatementRegistry::default()
+            .observe("SELECT * FROM t WHERE id = 1", "2025-01-15 10:00:00.000");
+        let mut out = Vec::new();
+        cols.compute_into(
+            &dml,
+            &MetaParts::default(),
+            &pm,
+            RowInfo::default(),
+            &mut out,
+        );
+        assert_eq!(out, [DerivedValue::Int(expected)]);
+        // PARAMS 记录不参与登记，列为空
+        cols.compute_into(
+            &Sqllog::default(),
+            &MetaParts::default(),
+            &pm,
+            RowInfo::default(),
+            &mut out,
+        );
+        assert_eq!(out, [DerivedValue::Null]);
+    }
+
+    #[test]
+    fn test_normalize_sql_columns() {
+        let mut f = features(&["sql_hash", "seq"], None);
+        f.normalize_sql = Some(crate::features::NormalizeSqlConfig { enabled: true });
+        let cols = DerivedColumns::from_config(&f).unwrap();
+        let names: Vec<_> = cols.specs().into_iter().map(|s| s.name).collect();
+        assert_eq!(names, ["sql_hash", "seq", "sql_fingerprint"]);
+
+        let mut dml = Sqllog::default();
+        dml.tag = Some(Cow::Borrowed("SEL"));
+        let mut out = Vec::new();
+        let mut hash_of = |sql: &'static str| {
+            let pm = PerformanceMetrics {
+                sql: Cow::Borrowed(sql),
+                ..PerformanceMetrics::default()
+            };
+            cols.compute_into(
+                &dml,
+                &MetaParts::default(),
+                &pm,
+                RowInfo::default(),
+                &mut out,
+            );
+            (out[0].clone(), out[2].clone())
+        };
+        let (hash, fp) = hash_of("SELECT * FROM t WHERE id = 7 AND name = 'a'");
+        assert_eq!(
+            fp,
+            DerivedValue::Text(Arc::from("SELECT * FROM t WHERE id = ? AND name = ?"))
+        );
+        let DerivedValue::Text(h) = &hash else {
+            panic!("expected text hash");
+        };
+        assert_eq!(h.len(), 16);
+        assert_eq!(
+            hash_of("SELECT * FROM t WHERE id = 42 AND name = 'b'").0,
+            hash
+        );
+        assert_ne!(hash_of("SELECT * FROM u WHERE id = 7").0, hash);
+    }
+
+    #[test]
+    fn test_appname_normalized_column() {
+        let mut f = features(&["appname_normalized"], None);
+        let err = DerivedColumns::from_config(&f).unwrap_err();
+        assert!(err.to_string().contains("[features.appname]"), "{err}");
+
+        f.appname = Some(crate::features::AppNameConfig {
+            rules: vec![crate::features::appname::AppNameRule {
+                pattern: Some("(?i)jdbc".to_string()),
+                name: "jdbc".to_string(),
+                ..Default::default()
+            }],
+            default: None,
+        });
+        let cols = DerivedColumns::from_config(&f).unwrap();
+        let mut out = Vec::new();
+        for (appname, expected) in [
+            ("JDBC Thin Client", DerivedValue::Text(Arc::from("jdbc"))),
+            ("", DerivedValue::Null),
+        ] {
+            let meta = MetaParts {
+                appname: Cow::Borrowed(appname),
+                ..MetaParts::default()
+            };
+            cols.compute_into(
+                &Sqllog::default(),
+                &meta,
+                &PerformanceMetrics::default(),
+                RowInfo::default(),
+                &mut out,
+            );
+            assert_eq!(out, [expected]);
+        }
+    }
+
+    #[test]
+    fn test_sql_metric_columns() {
+        let cols = DerivedColumns::from_config(&features(
+            &["sql_length", "param_count", "line_count"],
+            None,
+        ))
+        .unwrap();
+        assert!(cols.specs().iter().all(|s| s.kind == DerivedKind::Integer));
+
+        let mut rec = Sqllog::default();
+        rec.tag = Some(Cow::Borrowed("INS"));
+        let mut out = Vec::new();
+        let mut metrics = |rec: &Sqllog<'_>, sql: &'static str| {
+            let pm = PerformanceMetrics {
+                sql: Cow::Borrowed(sql),
+                ..PerformanceMetrics::default()
+            };
+            cols.compute_into(
+                rec,
+                &MetaParts::default(),
+                &pm,
+                RowInfo::default(),
+                &mut out,
+            );
+            out.clone()
+        };
+        // 字符数而非字节数；字面量中的 `?` 不计为占位符
+        assert_eq!(
+            metrics(&rec, "INSERT INTO t\nVALUES (?, ?, '表?')\n"),
+            [
+                DerivedValue::Int(34),
+                DerivedValue::Int(2),
+                DerivedValue::Int(2)
+            ]
+        );
+        assert_eq!(
+            metrics(&rec, "UPDATE t SET a = :1 WHERE b = :3"),
+            [
+                DerivedValue::Int(32),
+                DerivedValue::Int(3),
+                DerivedValue::Int(1)
+            ]
+        );
+        assert_eq!(
+            metrics(&rec, ""),
+            [
+                DerivedValue::Int(0),
+                DerivedValue::Int(0),
+                DerivedValue::Int(0)
+            ]
+        );
+        // PARAMS 记录没有占位符可数
+        assert_eq!(
+            metrics(
+                &Sqllog::default(),
+                "PARAMS(SEQNO, TYPE, DATA)={(0, INT, 1)}"
+            )[1],
+            DerivedValue::Null
+        );
+    }
+
+    #[test]
+    fn test_trace_columns_from_sql_comment() {
+        let cols = DerivedColumns::from_config(&features(&["trace_id", "span_id"], None)).unwrap();
+        assert!(cols.specs().iter().all(|s| s.kind == DerivedKind::Text));
+        let mut pm = PerformanceMetrics {
+            sql: Cow::Borrowed(
+                "SELECT 1 /*traceparent='00-5bd66ef5095369c7b0d1f8f4bd33716a-c532cb4098ac3dd2-01'*/",
+            ),
+            ..PerformanceMetrics::default()
+        };
+        let rec = Sqllog::default();
+        let meta = MetaParts::default();
+        let mut out = Vec::new();
+        cols.compute_into(&rec, &meta, &pm, RowInfo::default(), &mut out);
+        assert_eq!(
+            out,
+            [
+                DerivedValue::Text("5bd66ef5095369c7b0d1f8f4bd33716a".into()),
+                DerivedValue::Text("c532cb4098ac3dd2".into())
+            ]
+        );
+        pm.sql = Cow::Borrowed("SELECT 1");
+        cols.compute_into(&rec, &meta, &pm, RowInfo::default(), &mut out);
+        assert_eq!(out, [DerivedValue::Null, DerivedValue::Null]);
+    }
+
+    #[test]
+    fn test_client_zone_requires_table_and_maps_ip() {
+        let err = DerivedColumns::from_config(&features(&["client_zone"], None)).unwrap_err();
+        assert!(err.to_string().contains("[features.client_zone]"), "{err}");
+
+        let mut f = features(&["client_zone"], None);
+        f.client_zone = Some(crate::features::ClientZoneConfig {
+            subnets: vec![crate::features::client_zone::SubnetLabel {
+                cidr: "10.1.0.0/16".into(),
+                label: "app-tier".into(),
+            }],
+            default: None,
+        });
+        let cols = DerivedColumns::from_config(&f).unwrap();
+        assert_eq!(cols.specs()[0].kind, DerivedKind::Text);
+
+        let rec = Sqllog::default();
+        let pm = PerformanceMetrics::default();
+        let mut meta = MetaParts {
+            client_ip: Cow::Borrowed("10.1.0.9"),
+            ..MetaParts::default()
+        };
+        let mut out = Vec::new();
+        cols.compute_into(&rec, &meta, &pm, RowInfo::default(), &mut out);
+        assert_eq!(out, vec![DerivedValue::Text("app-tier".into())]);
+        meta.client_ip = Cow::Borrowed("172.16.0.1");
+        cols.compute_into(&rec, &meta, &pm, RowInfo::default(), &mut out);
+        assert_eq!(out, vec![DerivedValue::Null]);
+    }
+
+    #[test]
+    fn test_owner_columns_share_lookup_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("owners.csv");
+        std::fs::write(
+            &path,
+            "username,owner_team,app_service\nBILL,Finance,billing\n",
+        )
+        .unwrap();
+        let mut f = features(&["app_service", "owner_team"], None);
+        f.user_owner = Some(crate::features::UserOwnerConfig {
+            file: path.to_string_lossy().into_owned(),
+        });
+        let cols = DerivedColumns::from_config(&f).unwrap();
+
+        let rec = Sqllog::default();
+        let pm = PerformanceMetrics::default();
+        let mut meta = MetaParts {
+            username: Cow::Borrowed("BILL"),
+            ..MetaParts::default()
+        };
+        let mut out = Vec::new();
+        cols.compute_into(&rec, &meta, &pm, RowInfo::default(), &mut out);
+        assert_eq!(
+            out,
+            vec![
+                DerivedValue::Text("billing".into()),
+                DerivedValue::Text("Finance".into())
+            ]
+        );
+        meta.username = Cow::Borrowed("OTHER");
+        cols.compute_into(&rec, &meta, &pm, RowInfo::default(), &mut out);
+        assert_eq!(out, vec![DerivedValue::Null, DerivedValue::Null]);
+
+        f.user_owner = None;
+        assert!(DerivedColumns::from_config(&f).is_err());
+    }
+
+    #[test]
+    fn test_ts_epoch_ms_uses_input_timezone() {
+        let time = TimeConfig {
+            input_timezone: Some("+08:00".into()),
+            ..Default::default()
+        };
+        let cols = DerivedColumns::from_config(&features(&["ts_epoch_ms"], Some(time))).unwrap();
+        assert_eq!(
+            cols.specs(),
+            vec![DerivedColumnSpec {
+                name: "ts_epoch_ms".into(),
+                kind: DerivedKind::Integer
+            }]
+        );
+
+        let mut rec = Sqllog::default();
+        rec.ts = Cow::Borrowed("1970-01-01 08:00:01.250");
+        let meta = MetaParts::default();
+        let pm = PerformanceMetrics::default();
+        let mut out = Vec::new();
+        cols.compute_into(&rec, &meta, &pm, RowInfo::default(), &mut out);
+        assert_eq!(out, vec![DerivedValue::Int(1250)]);
+
+        rec.ts = Cow::Borrowed("garbage");
+        cols.compute_into(&rec, &meta, &pm, RowInfo::default(), &mut out);
+        assert_eq!(out, vec![DerivedValue::Null]);
+    }
+}
@@ -0,0 +1,241 @@
+//! `[features.exec_id_audit]`：按会话 / EP 追踪 `EXEC_ID` 序列的连续性，在运行摘要中报告缺口，
+//! 用于核查达梦异步 sqllog 写入在高负载下是否丢失日志行。
+//!
+//! 异步写入时同一序列内的记录可能乱序落盘，因此不逐条比较相邻 ID，而是统计每个序列的
+//! 最小 / 最大 ID 与记录数：缺失数 = 跨度 − 记录数。未过滤前的全部记录都参与统计。
+use crate::error::{ConfigError, Error, Result};
+use ahash::HashMap as AHashMap;
+use compact_str::CompactString;
+use dm_database_parser_sqllog::Sqllog;
+use serde::Deserialize;
+use std::cmp::Reverse;
+
+/// `scope` 可选值
+pub const EXEC_ID_SCOPES: &[&str] = &["session", "ep"];
+
+/// `[features.exec_id_audit]` 配置段
+#[derive(Debug, Deserialize, Clone)]
+#[non_exhaustive]
+pub struct ExecIdAuditConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 序列划分：`session`（默认，EP + 会话）或 `ep`（每个 EP 一条序列）
+    #[serde(default = "default_scope")]
+    pub scope: String,
+}
+
+fn default_scope() -> String {
+    "session".to_string()
+}
+
+impl Default for ExecIdAuditConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            scope: default_scope(),
+        }
+    }
+}
+
+impl ExecIdAuditConfig {
+    pub fn validate(&self) -> Result<()> {
+        if EXEC_ID_SCOPES.contains(&self.scope.as_str()) {
+            Ok(())
+        } else {
+            Err(Error::Config(ConfigError::InvalidValue {
+                field: "features.exec_id_audit.scope".to_string(),
+                value: self.scope.clone(),
+                reason: format!("must be one of: {}", EXEC_ID_SCOPES.join(", ")),
+            }))
+        }
+    }
+}
+
+/// 单条存在缺口的 `EXEC_ID` 序列
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecIdGap {
+    /// 序列标识，如 `EP[0] 0x7f01`（`ep` 范围时为 `EP[0]`）
+    pub sequence: String,
+    pub first: i64,
+    pub last: i64,
+    /// 实际出现的记录数
+    pub seen: u64,
+    /// 跨度内缺失的 `EXEC_ID` 数
+    pub missing: u64,
+}
+
+/// 运行摘要中的 `EXEC_ID` 审计结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecIdAudit {
+    /// 参与统计的序列数
+    pub sequences: usize,
+    /// 存在缺口的序列，按缺失数降序
+    pub gaps: Vec<ExecIdGap>,
+}
+
+impl ExecIdAudit {
+    #[must_use]
+    pub fn missing(&self) -> u64 {
+        self.gaps.iter().map(|g| g.missing).sum()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Span {
+    min: i64,
+    max: i64,
+    count: u64,
+}
+
+/// 运行期 `EXEC_ID` 统计（跨文件累计，日志轮转不影响序列）
+#[derive(Debug, Default)]
+pub struct ExecIdTracker {
+    per_session: bool,
+    spans: AHashMap<(u8, CompactString), Span>,
+}
+
+impl ExecIdTracker {
+    /// 未启用审计时返回 `None`
+    #[must_use]
+    pub fn from_config(config: Option<&ExecIdAuditConfig>) -> Option<Self> {
+        let config = config.filter(|c| c.enabled)?;
+        Some(Self {
+            per_session: config.scope != "ep",
+            ..Self::default()
+        })
+    }
+
+    /// 记录一条日志；无性能指标（PARAMS 或缺失 EXECTIME）的记录不参与统计
+    pub fn observe(&mut self, record: &Sqllog<'_>) {
+        let Some(exec_id) = record
+            .parse_indicators()
+            .map(|pm| pm.exec_id)
+            .filter(|id| *id > 0)
+        else {
+            return;
+        };
+        let meta = record.parse_meta();
+        let session = if self.per_session {
+            CompactString::from(meta.sess_id.as_ref())
+        } else {
+            CompactString::default()
+        };
+        self.spans
+            .entry((meta.ep, session))
+            .and_modify(|s| {
+                s.min = s.min.min(exec_id);
+                s.max = s.max.max(exec_id);
+                s.count += 1;
+            })
+            .or_insert(Span {
+                min: exec_id,
+                max: exec_id,
+                count: 1,
+            });
+    }
+
+    #[must_use]
+    pub fn report(&self) -> ExecIdAudit {
+        let mut gaps: Vec<ExecIdGap> = self
+            .spans
+            .iter()
+            .filter_map(|((ep, session), s)| {
+                let span = s.max.abs_diff(s.min) + 1;
+                // 记录数超过跨度说明有重复 ID，不计为缺口
+                let missing = span.saturating_sub(s.count);
+                (missing > 0).then(|| ExecIdGap {
+                    sequence: if session.is_empty() {
+                        format!("EP[{ep}]")
+                    } else {
+                        format!("EP[{ep}] {session}")
+                    },
+                    first: s.min,
+                    last: s.max,
+                    seen: s.count,
+                    missing,
+                })
+            })
+            .collect();
+        gaps.sort_by(|a, b| {
+            Reverse(a.missing)
+                .cmp(&Reverse(b.missing))
+                .then_with(|| a.sequence.cmp(&b.sequence))
+        });
+        ExecIdAudit {
+            sequences: self.spans.len(),
+            gaps,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt::Write as _;
+
+    fn records(ids: &[(&str, i64)]) -> String {
+        let mut log = String::new();
+        for (sess, id) in ids {
+            writeln!(
+                log,
+                "2025-01-15 10:30:28.001 (EP[0] sess:{sess} thrd:1 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: {id}."
+            )
+            .unwrap();
+        }
+        log
+    }
+
+    fn audit(scope: &str, ids: &[(&str, i64)]) -> ExecIdAudit {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("t.log");
+        std::fs::write(&path, records(ids)).unwrap();
+        let parser = dm_database_parser_sqllog::LogParser::from_path(&path).unwrap();
+        let mut tracker = ExecIdTracker::from_config(Some(&ExecIdAuditConfig {
+            enabled: true,
+            scope: scope.to_string(),
+        }))
+        .unwrap();
+        for record in parser.iter().flatten() {
+            tracker.observe(&record);
+        }
+        tracker.report()
+    }
+
+    #[test]
+    fn test_gaps_per_session_tolerate_reordering() {
+        // 0x1 乱序但连续；0x2 缺失 11、12
+        let r = audit(
+            "session",
+            &[("0x1", 5), ("0x2", 10), ("0x1", 7), ("0x1", 6), ("0x2", 13)],
+        );
+        assert_eq!(r.sequences, 2);
+        assert_eq!(
+            r.gaps,
+            vec![ExecIdGap {
+                sequence: "EP[0] 0x2".into(),
+                first: 10,
+                last: 13,
+                seen: 2,
+                missing: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_ep_scope_merges_sessions() {
+        let r = audit("ep", &[("0x1", 1), ("0x2", 2), ("0x1", 4)]);
+        assert_eq!(r.sequences, 1);
+        assert_eq!(r.missing(), 1);
+        assert_eq!(r.gaps[0].sequence, "EP[0]");
+    }
+
+    #[test]
+    fn test_disabled_and_invalid_scope() {
+        assert!(ExecIdTracker::from_config(Some(&ExecIdAuditConfig::default())).is_none());
+        let cfg = ExecIdAuditConfig {
+            enabled: true,
+            scope: "thread".into(),
+        };
+        assert!(cfg.validate().is_err());
+    }
+}
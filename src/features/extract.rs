@@ -0,0 +1,204 @@
+//! `[[features.extract]]`：读时建模的自定义字段。按正则从 SQL 正文或 appname 中提取值，
+//! 作为派生列追加在 `extra_fields` 之后，例如提取应用注入的 `/* traceid=... */` 注释标签，
+//! 与 APM 链路关联。
+//!
+//! 取名为 `value` 的捕获组，没有时取第一个捕获组，再没有时取整个匹配；不匹配时列为空。
+use super::FIELD_NAMES;
+use crate::error::{ConfigError, Error, Result};
+use dm_database_parser_sqllog::{MetaParts, PerformanceMetrics};
+use regex::Regex;
+use serde::Deserialize;
+
+/// `source` 可选值
+pub const EXTRACT_SOURCES: &[&str] = &["sql", "appname"];
+
+/// `[[features.extract]]` 中的一条提取规则
+#[derive(Debug, Deserialize, Clone)]
+#[non_exhaustive]
+pub struct ExtractRule {
+    /// 输出列名（字母 / 数字 / 下划线，不能以数字开头，不能与其他列重名）
+    #[serde(default)]
+    pub name: String,
+    /// 提取来源：`sql`（默认）或 `appname`
+    #[serde(default = "default_source")]
+    pub source: String,
+    /// 正则表达式
+    #[serde(default)]
+    pub pattern: String,
+}
+
+fn default_source() -> String {
+    "sql".to_string()
+}
+
+impl Default for ExtractRule {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            source: default_source(),
+            pattern: String::new(),
+        }
+    }
+}
+
+fn invalid(field: &str, value: &str, reason: String) -> Error {
+    Error::Config(ConfigError::InvalidValue {
+        field: format!("features.extract.{field}"),
+        value: value.to_string(),
+        reason,
+    })
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    Sql,
+    Appname,
+}
+
+/// 编译后的提取规则
+#[derive(Debug, Clone)]
+pub struct Extractor {
+    source: Source,
+    regex: Regex,
+    /// 取值的捕获组下标（0 为整个匹配）
+    group: usize,
+}
+
+impl ExtractRule {
+    /// 校验并编译规则；`taken` 为已启用的派生列名（基础字段名始终占用）
+    pub fn compile(&self, taken: &[&str]) -> Result<Extractor> {
+        if !is_identifier(&self.name) {
+            return Err(invalid(
+                "name",
+                &self.name,
+                "must be letters, digits or underscores and not start with a digit".to_string(),
+            ));
+        }
+        if FIELD_NAMES.contains(&self.name.as_str()) || taken.contains(&self.name.as_str()) {
+            return Err(invalid(
+                "name",
+                &self.name,
+                "duplicates an existing column name".to_string(),
+            ));
+        }
+        let source = match self.source.as_str() {
+            "sql" => Source::Sql,
+            "appname" => Source::Appname,
+            other => {
+                return Err(invalid(
+                    "source",
+                    other,
+                    format!("must be one of: {}", EXTRACT_SOURCES.join(", ")),
+                ));
+            }
+        };
+        let regex = Regex::new(&self.pattern)
+            .map_err(|e| invalid("pattern", &self.pattern, e.to_string()))?;
+        let group = regex
+            .capture_names()
+            .position(|n| n == Some("value"))
+            .unwrap_or(usize::from(regex.captures_len() > 1));
+        Ok(Extractor {
+            source,
+            regex,
+            group,
+        })
+    }
+}
+
+impl Extractor {
+    /// 从记录中提取值；不匹配或捕获组未参与匹配时返回 `None`
+    #[inline]
+    #[must_use]
+    pub fn extract<'a>(
+        &self,
+        meta: &'a MetaParts<'_>,
+        pm: &'a PerformanceMetrics<'_>,
+    ) -> Option<&'a str> {
+        let haystack = match self.source {
+            Source::Sql => pm.sql.as_ref(),
+            Source::Appname => meta.appname.as_ref(),
+        };
+        if self.group == 0 {
+            return self.regex.find(haystack).map(|m| m.as_str());
+        }
+        self.regex
+            .captures(haystack)
+            .and_then(|c| c.get(self.group))
+            .map(|m| m.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+
+    fn rule(name: &str, source: &str, pattern: &str) -> ExtractRule {
+        ExtractRule {
+            name: name.to_string(),
+            source: source.to_string(),
+            pattern: pattern.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_extract_capture_groups() {
+        let pm = PerformanceMetrics {
+            sql: Cow::Borrowed("/* traceid=ab12 span=7 */ SELECT 1"),
+            ..PerformanceMetrics::default()
+        };
+        let meta = MetaParts {
+            appname: Cow::Borrowed("order-svc@v2.3"),
+            ..MetaParts::default()
+        };
+        let trace = rule("trace_id", "sql", r"traceid=(\w+)")
+            .compile(&[])
+            .unwrap();
+        assert_eq!(trace.extract(&meta, &pm), Some("ab12"));
+        let named = rule("span", "sql", r"(traceid)=\w+ span=(?P<value>\d+)")
+            .compile(&[])
+            .unwrap();
+        assert_eq!(named.extract(&meta, &pm), Some("7"));
+        let whole = rule("app_version", "appname", r"v[\d.]+")
+            .compile(&[])
+            .unwrap();
+        assert_eq!(whole.extract(&meta, &pm), Some("v2.3"));
+        let miss = rule("tenant", "sql", r"tenant=(\w+)").compile(&[]).unwrap();
+        assert_eq!(miss.extract(&meta, &pm), None);
+    }
+
+    #[test]
+    fn test_compile_rejects_invalid_rules() {
+        let field = |r: ExtractRule, taken: &[&str]| match r.compile(taken).unwrap_err() {
+            Error::Config(ConfigError::InvalidValue { field, .. }) => field,
+            e => panic!("{e}"),
+        };
+        assert_eq!(field(rule("1x", "sql", "a"), &[]), "features.extract.name");
+        assert_eq!(field(rule("sql", "sql", "a"), &[]), "features.extract.name");
+        assert_eq!(
+            field(rule("seq", "sql", "a"), &["seq"]),
+            "features.extract.name"
+        );
+        assert_eq!(
+            field(rule("trace", "sql", "a"), &["trace"]),
+            "features.extract.name"
+        );
+        assert_eq!(
+            field(rule("trace", "user", "a"), &[]),
+            "features.extract.source"
+        );
+        assert_eq!(
+            field(rule("trace", "sql", "("), &[]),
+            "features.extract.pattern"
+        );
+    }
+}
@@ -40,10 +40,12 @@ where
 
 /// 过滤器配置 (重构后)
 #[derive(Debug, Deserialize, Clone, Default)]
+#[non_exhaustive]
 pub struct FiltersFeature {
     /// 是否启用过滤器
+    #[serde(default)]
     pub enable: bool,
-    /// 元数据过滤器 (记录级: 只要命中其中一个就保留该记录 - OR 逻辑)
+    /// 元数据过滤器 (记录级: 已配置的字段全部命中才保留，字段内任一匹配即可；exclude 任一命中即丢弃)
     #[serde(flatten)]
     pub meta: MetaFilters,
     /// 指标过滤器 (事务级: 命中即保留整笔事务 - 需要预扫描)
@@ -58,9 +60,17 @@ pub struct FiltersFeature {
 }
 
 /// 元数据过滤器 (Record-level)
+///
+/// 除 `trxids` 外均为正则；`statements` 匹配语句句柄（`stmt:0x...`），
+/// 语句类型（`SEL` / `INS` / `UPD` / `DEL` 等，日志中的 `[SEL]` 去掉方括号）用 `tags` 过滤。
 #[derive(Debug, Deserialize, Clone, Default)]
+#[non_exhaustive]
 pub struct MetaFilters {
+    /// 时间下界（含），日志时间戳的前缀，如 `2025-01-15 10:30`；也可写作 `start_time`
+    #[serde(alias = "start_time")]
     pub start_ts: Option<String>,
+    /// 时间上界（含，按前缀匹配：`2025-01-15 11:00` 包含 11:00 这一分钟）；也可写作 `end_time`
+    #[serde(alias = "end_time")]
     pub end_ts: Option<String>,
     pub sess_ids: Option<Vec<String>>,
     pub thrd_ids: Option<Vec<String>>,
@@ -70,6 +80,7 @@ pub struct MetaFilters {
     pub statements: Option<Vec<String>>,
     pub appnames: Option<Vec<String>>,
     pub client_ips: Option<Vec<String>>,
+    /// 语句类型（不含方括号），如 `["^(INS|UPD|DEL)$"]`
     pub tags: Option<Vec<String>>,
     pub exclude_usernames: Option<Vec<String>>,
     pub exclude_client_ips: Option<Vec<String>>,
@@ -82,6 +93,7 @@ pub struct MetaFilters {
 
 /// 指标过滤器 (Transaction-level)
 #[derive(Debug, Deserialize, Clone, Default)]
+#[non_exhaustive]
 pub struct IndicatorFilters {
     /// 使用 `AHashSet<i64>` 代替 `Vec<i64>`，将 `matches()` 热路径中的
     /// `.contains()` 从 O(n) 降为 O(1)。
@@ -99,6 +111,7 @@ pub struct IndicatorFilters {
 ///
 /// 如需正则匹配，请使用记录级过滤器 `record_sql`，它由 `CompiledSqlFilters` 处理，支持正则。
 #[derive(Debug, Deserialize, Clone, Default)]
+#[non_exhaustive]
 pub struct SqlFilters {
     /// 字面子串包含列表：SQL 必须包含其中之一才会被选中（未配置 = 全部通过）。
     /// 仅支持字面字符串，不支持正则表达式。
@@ -254,6 +267,52 @@ impl MetaFilters {
     }
 }
 
+/// 时间边界格式：日志时间戳 `YYYY-MM-DD HH:MM:SS.mmm` 从日期起按段截取的前缀
+const TS_TEMPLATE: &[u8] = b"0000-00-00 00:00:00.000";
+
+fn check_time_bound(field: &str, value: &str) -> crate::error::Result<()> {
+    let b = value.as_bytes();
+    let shape_ok = matches!(b.len(), 10 | 13 | 16 | 19 | 21..=23)
+        && b.iter().zip(TS_TEMPLATE).all(|(c, t)| match t {
+            b'0' => c.is_ascii_digit(),
+            _ => c == t,
+        });
+    if shape_ok {
+        Ok(())
+    } else {
+        Err(crate::error::Error::Config(
+            crate::error::ConfigError::InvalidValue {
+                field: field.to_string(),
+                value: value.to_string(),
+                reason: "expected 'YYYY-MM-DD[ HH[:MM[:SS[.mmm]]]]' (log timestamp format)"
+                    .to_string(),
+            },
+        ))
+    }
+}
+
+/// 校验时间窗口：两端格式合法且起点不晚于终点（终点按前缀包含，`2025-01-15 10:00` 落在 `2025-01-15` 内）
+fn check_time_range(start: Option<&str>, end: Option<&str>) -> crate::error::Result<()> {
+    if let Some(s) = start {
+        check_time_bound("features.filters.start_ts", s)?;
+    }
+    if let Some(e) = end {
+        check_time_bound("features.filters.end_ts", e)?;
+    }
+    if let (Some(s), Some(e)) = (start, end) {
+        if s > e && !s.starts_with(e) {
+            return Err(crate::error::Error::Config(
+                crate::error::ConfigError::InvalidValue {
+                    field: "features.filters.start_ts".to_string(),
+                    value: s.to_string(),
+                    reason: format!("start is after end_ts '{e}'"),
+                },
+            ));
+        }
+    }
+    Ok(())
+}
+
 /// 将正则字符串列表编译为 `Vec<Regex>`。None 或空列表返回 `Ok(None)`（未配置）。
 /// 遇到非法正则时返回 `ConfigError::InvalidValue`，field 参数用于错误消息。
 fn compile_patterns(
@@ -312,6 +371,7 @@ pub struct CompiledMetaFilters {
 impl CompiledMetaFilters {
     /// 从 `MetaFilters` 编译所有正则，遇到非法 pattern 返回 `ConfigError::InvalidValue`。
     pub fn try_from_meta(meta: &MetaFilters) -> crate::error::Result<Self> {
+        check_time_range(meta.start_ts.as_deref(), meta.end_ts.as_deref())?;
         Ok(Self {
             usernames: compile_patterns("features.filters.usernames", meta.usernames.as_deref())?,
             client_ips: compile_patterns(
@@ -700,6 +760,64 @@ mod tests {
         }
     }
 
+    // ── config: 用户 + 应用 + IP + 语句类型组合 ───────────────────
+    #[test]
+    fn test_toml_user_app_ip_statement_type_filters() {
+        let f: FiltersFeature = toml::from_str(
+            r#"
+            enable = true
+            usernames = ["^(APP_ORDER|APP_PAY)$"]
+            exclude_appnames = ["^monitor"]
+            exclude_client_ips = ["^10\\.9\\."]
+            tags = ["^(INS|UPD|DEL)$"]
+            "#,
+        )
+        .unwrap();
+        let compiled = CompiledMetaFilters::try_from_meta(&f.meta).unwrap();
+        let rec = |user, app, ip, tag| RecordMeta {
+            trxid: "1",
+            ip,
+            sess: "0x1",
+            thrd: "1",
+            user,
+            stmt: "0x1",
+            app,
+            tag,
+        };
+        assert!(compiled.should_keep(&rec("APP_PAY", "svc", "10.0.0.1", Some("UPD"))));
+        // 解析器给出的 tag 不含方括号
+        assert!(!compiled.should_keep(&rec("APP_PAY", "svc", "10.0.0.1", Some("SEL"))));
+        assert!(!compiled.should_keep(&rec("SYSDBA", "svc", "10.0.0.1", Some("INS"))));
+        assert!(!compiled.should_keep(&rec("APP_ORDER", "monitor-1", "10.0.0.1", Some("INS"))));
+        assert!(!compiled.should_keep(&rec("APP_ORDER", "svc", "10.9.0.7", Some("DEL"))));
+        assert!(!compiled.should_keep(&rec("APP_ORDER", "svc", "10.0.0.1", None)));
+    }
+
+    #[test]
+    fn test_toml_time_window_aliases_and_validation() {
+        let f: FiltersFeature = toml::from_str(
+            r#"
+            enable = true
+            start_time = "2025-01-15 10:30"
+            end_time = "2025-01-15 11:00"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(f.meta.start_ts.as_deref(), Some("2025-01-15 10:30"));
+        assert_eq!(f.meta.end_ts.as_deref(), Some("2025-01-15 11:00"));
+        assert!(CompiledMetaFilters::try_from_meta(&f.meta).is_ok());
+
+        assert!(check_time_range(Some("2025-01-15 10:00:00.123"), Some("2025-01-15")).is_ok());
+        for bad in ["2025-1-15", "2025-01-15T10:30", "10:30", "2025-01-15 10:3"] {
+            let err = check_time_range(Some(bad), None).unwrap_err().to_string();
+            assert!(err.contains("features.filters.start_ts"), "{bad}: {err}");
+        }
+        let err = check_time_range(Some("2025-01-15 11:00"), Some("2025-01-15 10:30"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("after end_ts"), "{err}");
+    }
+
     // ── should_keep: time range ────────────────────────────────
     #[test]
     fn test_should_keep_no_filters_passes_all() {
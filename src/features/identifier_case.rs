@@ -0,0 +1,136 @@
+//! `[features.identifier_case]`：统一用户名与语句类型（tag）的大小写。
+//!
+//! 不同版本 / 实例的达梦对同一用户可能分别记录为 `SYSDBA` 与 `sysdba`，按用户分组统计时会拆成两组。
+//! 按字段配置 `upper` / `lower` 后，配额、模板统计、派生列与导出均使用统一后的值；
+//! 过滤与参数替换仍按原值进行（过滤规则需要不区分大小写时可用 `(?i)` 正则）。无状态，不影响并行路径。
+use crate::error::{ConfigError, Error, Result};
+use dm_database_parser_sqllog::MetaParts;
+use serde::Deserialize;
+use std::borrow::Cow;
+
+/// 大小写可选值
+pub const CASE_MODES: &[&str] = &["upper", "lower", "preserve"];
+
+/// `[features.identifier_case]` 配置段
+#[derive(Debug, Deserialize, Clone, Default)]
+#[non_exhaustive]
+pub struct IdentifierCaseConfig {
+    /// `username` 的大小写：`upper` / `lower` / `preserve`（默认，保持原样）
+    #[serde(default)]
+    pub username: Option<String>,
+    /// 语句类型 `tag`（`SEL`、`INS` 等）的大小写，取值同上
+    #[serde(default)]
+    pub tag: Option<String>,
+}
+
+impl IdentifierCaseConfig {
+    pub fn validate(&self) -> Result<()> {
+        Case::parse("username", self.username.as_deref())?;
+        Case::parse("tag", self.tag.as_deref())?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Case {
+    Upper,
+    Lower,
+}
+
+impl Case {
+    /// `preserve` 或未配置时返回 `None`
+    fn parse(field: &str, value: Option<&str>) -> Result<Option<Self>> {
+        match value {
+            None | Some("preserve") => Ok(None),
+            Some("upper") => Ok(Some(Self::Upper)),
+            Some("lower") => Ok(Some(Self::Lower)),
+            Some(other) => Err(Error::Config(ConfigError::InvalidValue {
+                field: format!("features.identifier_case.{field}"),
+                value: other.to_string(),
+                reason: format!("must be one of: {}", CASE_MODES.join(", ")),
+            })),
+        }
+    }
+
+    /// 转换后的值；已符合目标大小写时返回 `None`，不分配
+    fn convert(self, value: &str) -> Option<String> {
+        match self {
+            Self::Upper if value.chars().any(char::is_lowercase) => Some(value.to_uppercase()),
+            Self::Lower if value.chars().any(char::is_uppercase) => Some(value.to_lowercase()),
+            _ => None,
+        }
+    }
+}
+
+/// 编译后的大小写规则
+#[derive(Debug, Clone, Copy)]
+pub struct CaseNormalizer {
+    username: Option<Case>,
+    tag: Option<Case>,
+}
+
+impl CaseNormalizer {
+    /// 未配置或各字段均保持原样时返回 `None`（配置已在 `validate()` 阶段校验）
+    #[must_use]
+    pub fn from_config(config: Option<&IdentifierCaseConfig>) -> Option<Self> {
+        let config = config?;
+        let normalizer = Self {
+            username: Case::parse("username", config.username.as_deref()).ok()?,
+            tag: Case::parse("tag", config.tag.as_deref()).ok()?,
+        };
+        (normalizer.username.is_some() || normalizer.tag.is_some()).then_some(normalizer)
+    }
+
+    /// 统一 `meta.username` 的大小写
+    pub fn apply(self, meta: &mut MetaParts<'_>) {
+        if let Some(name) = self.username.and_then(|c| c.convert(&meta.username)) {
+            meta.username = Cow::Owned(name);
+        }
+    }
+
+    /// 统一后的 tag；无需改动时返回 `None`
+    #[must_use]
+    pub fn tag(self, tag: Option<&str>) -> Option<String> {
+        self.tag.zip(tag).and_then(|(c, t)| c.convert(t))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalizes_configured_fields_only() {
+        let cfg = IdentifierCaseConfig {
+            username: Some("upper".into()),
+            tag: Some("preserve".into()),
+        };
+        let n = CaseNormalizer::from_config(Some(&cfg)).unwrap();
+        let mut meta = MetaParts {
+            username: Cow::Borrowed("SysDba"),
+            ..MetaParts::default()
+        };
+        n.apply(&mut meta);
+        assert_eq!(meta.username, "SYSDBA");
+        assert_eq!(n.tag(Some("sel")), None);
+
+        let lower = CaseNormalizer::from_config(Some(&IdentifierCaseConfig {
+            tag: Some("lower".into()),
+            ..Default::default()
+        }))
+        .unwrap();
+        assert_eq!(lower.tag(Some("SEL")).as_deref(), Some("sel"));
+        assert_eq!(lower.tag(Some("sel")), None);
+        assert_eq!(lower.tag(None), None);
+
+        assert!(CaseNormalizer::from_config(Some(&IdentifierCaseConfig::default())).is_none());
+        assert!(
+            IdentifierCaseConfig {
+                username: Some("title".into()),
+                ..Default::default()
+            }
+            .validate()
+            .is_err()
+        );
+    }
+}
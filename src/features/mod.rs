@@ -1,9 +1,53 @@
+pub mod appname;
+pub use appname::AppNameConfig;
+
+pub mod backfill;
+pub use backfill::{SessionBackfill, SessionBackfillConfig};
+
+pub mod client_zone;
+pub use client_zone::ClientZoneConfig;
+
+pub mod dedup;
+pub use dedup::{DedupConfig, Deduplicator};
+
+pub mod derived;
+pub use derived::{
+    DerivedColumnSpec, DerivedColumns, DerivedValue, RowInfo, SeqScope, SessionPos, SessionSeq,
+};
+
+pub mod exec_id;
+pub use exec_id::{ExecIdAudit, ExecIdAuditConfig, ExecIdTracker};
+
+pub mod extract;
+pub use extract::ExtractRule;
+
 pub mod filters;
 pub use filters::{CompiledMetaFilters, CompiledSqlFilters, FiltersFeature};
 
+pub mod identifier_case;
+pub use identifier_case::{CaseNormalizer, IdentifierCaseConfig};
+
+pub mod obfuscate;
+pub use obfuscate::{IdObfuscator, ObfuscateIdsConfig};
+
+pub mod quota;
+pub use quota::{QuotaConfig, QuotaTracker};
+
+pub mod redact;
+pub use redact::{RedactConfig, redact_credentials};
+
 pub mod replace_parameters;
 pub use replace_parameters::compute_normalized;
 
+pub mod split;
+pub use split::SplitBy;
+
+pub mod unmeasured;
+pub use unmeasured::{UnmeasuredConfig, UnmeasuredPolicy};
+
+pub mod statements;
+pub use statements::{StatementInfo, StatementRegistry, StatementsConfig};
+
 pub mod sql_fingerprint;
 pub use sql_fingerprint::fingerprint;
 pub use sql_fingerprint::normalize_template;
@@ -13,6 +57,14 @@ pub use template_aggregator::ChartEntry;
 pub use template_aggregator::TemplateAggregator;
 pub use template_aggregator::TemplateStats;
 
+pub mod trace;
+
+pub mod time;
+pub use time::{TimeConfig, TsNormalizer};
+
+pub mod user_owner;
+pub use user_owner::UserOwnerConfig;
+
 use dm_database_parser_sqllog::{MetaParts, Sqllog};
 use serde::Deserialize;
 
@@ -78,6 +130,7 @@ impl Default for FieldMask {
 
 /// `[features.replace_parameters]` 配置段
 #[derive(Debug, Deserialize, Clone)]
+#[non_exhaustive]
 pub struct ReplaceParametersConfig {
     /// 是否在导出结果中写入 `normalized_sql` 列（默认 true）
     #[serde(default = "default_true")]
@@ -122,23 +175,40 @@ fn default_true() -> bool {
     true
 }
 
+fn default_charts_dir() -> String {
+    "charts/".to_string()
+}
+
 fn default_top_n() -> usize {
     10
 }
 
 /// `[features.template_analysis]` 配置段
 #[derive(Debug, Deserialize, Clone, Default)]
+#[non_exhaustive]
 pub struct TemplateAnalysisConfig {
     /// 是否启用 SQL 模板归一化（默认 false）
     #[serde(default)]
     pub enabled: bool,
 }
 
+/// `[features.normalize_sql]` 配置段
+#[derive(Debug, Deserialize, Clone, Default)]
+#[non_exhaustive]
+pub struct NormalizeSqlConfig {
+    /// 追加 `sql_fingerprint`（字面量替换为 `?`）与 `sql_hash`（指纹的稳定哈希）两列，
+    /// 等同于在 `extra_fields` 中列出二者（默认 false）
+    #[serde(default)]
+    pub enabled: bool,
+}
+
 /// `[features.charts]` 配置段
 #[derive(Debug, Deserialize, Clone)]
 #[allow(clippy::struct_excessive_bools)]
+#[non_exhaustive]
 pub struct ChartsConfig {
-    /// 图表输出目录（必填，无默认值）
+    /// 图表输出目录（默认 `charts/`）
+    #[serde(default = "default_charts_dir")]
     pub output_dir: String,
     /// 频率 Top-N 数量（默认 10）
     #[serde(default = "default_top_n")]
@@ -160,7 +230,7 @@ pub struct ChartsConfig {
 impl Default for ChartsConfig {
     fn default() -> Self {
         Self {
-            output_dir: "charts/".to_string(),
+            output_dir: default_charts_dir(),
             top_n: 10,
             frequency_bar: true,
             latency_hist: true,
@@ -172,6 +242,7 @@ impl Default for ChartsConfig {
 
 /// 功能开关配置
 #[derive(Debug, Deserialize, Clone, Default)]
+#[non_exhaustive]
 pub struct FeaturesConfig {
     pub filters: Option<FiltersFeature>,
     pub replace_parameters: Option<ReplaceParametersConfig>,
@@ -179,9 +250,60 @@ pub struct FeaturesConfig {
     pub fields: Option<Vec<String>>,
     pub template_analysis: Option<TemplateAnalysisConfig>,
     pub charts: Option<ChartsConfig>,
+    /// 导出时的时间戳时区归一化（`[features.time]`），未配置时 ts 原样输出
+    pub time: Option<TimeConfig>,
+    /// 追加在基础字段之后的派生列，如 `["ts_epoch_ms"]`（见 `derived::DERIVED_FIELD_NAMES`）
+    pub extra_fields: Option<Vec<String>>,
+    /// `seq` 派生列的编号范围：`file`（默认）或 `session`
+    pub seq_scope: Option<String>,
+    /// 会话拼接的空闲超时（秒）：同一 EP + sess 相邻记录间隔超过该值时视为新会话（会话地址被复用）；
+    /// 未配置时按 EP + sess 跨文件一直延续
+    pub session_idle_secs: Option<u64>,
+    /// 按用户 / SQL 指纹限制导出条数（`[features.quota]`）
+    pub quota: Option<QuotaConfig>,
+    /// `client_zone` 派生列使用的 CIDR 子网表（`[features.client_zone]`）
+    pub client_zone: Option<ClientZoneConfig>,
+    /// `appname_normalized` 派生列使用的应用名归一规则（`[features.appname]`）
+    pub appname: Option<AppNameConfig>,
+    /// 按会话补全为空的 `appname` / `client_ip`（`[features.session_backfill]`）
+    pub session_backfill: Option<SessionBackfillConfig>,
+    /// 把 `sess_id` / `thrd_id` 等指针值替换为本次运行内稳定的小整数（`[features.obfuscate_ids]`）
+    pub obfuscate_ids: Option<ObfuscateIdsConfig>,
+    /// 统一用户名 / 语句类型的大小写（`[features.identifier_case]`）
+    pub identifier_case: Option<IdentifierCaseConfig>,
+    /// `owner_team` / `app_service` 派生列使用的用户归属查找文件（`[features.user_owner]`）
+    pub user_owner: Option<UserOwnerConfig>,
+    /// 导出前抹去 SQL 中的明文凭据（`[features.redact]`），未配置时默认启用
+    pub redact: Option<RedactConfig>,
+    /// 按会话 / EP 审计 `EXEC_ID` 连续性（`[features.exec_id_audit]`）
+    pub exec_id_audit: Option<ExecIdAuditConfig>,
+    /// 跳过跨文件重复出现的记录（`[features.dedup]`）
+    pub dedup: Option<DedupConfig>,
+    /// 按 SQL 指纹维护的语句登记表（`[features.statements]`）
+    pub statements: Option<StatementsConfig>,
+    /// SQL 指纹与哈希列（`[features.normalize_sql]`），便于按语句形态分组
+    pub normalize_sql: Option<NormalizeSqlConfig>,
+    /// 按正则从 SQL / appname 提取的自定义列（`[[features.extract]]`），追加在 `extra_fields` 之后
+    pub extract: Option<Vec<ExtractRule>>,
+    /// 无性能指标记录（DDL、工具类语句等）的处理策略（`[features.unmeasured]`）
+    pub unmeasured: Option<UnmeasuredConfig>,
 }
 
 impl FeaturesConfig {
+    /// 无性能指标记录的处理策略（未配置时照常导出）
+    #[must_use]
+    pub fn unmeasured_policy(&self) -> UnmeasuredPolicy {
+        self.unmeasured
+            .as_ref()
+            .map_or(UnmeasuredPolicy::Include, UnmeasuredConfig::policy)
+    }
+
+    /// 是否在导出前抹去明文凭据（未配置 `[features.redact]` 时默认启用）
+    #[must_use]
+    pub fn redacts_credentials(&self) -> bool {
+        self.redact.as_ref().is_none_or(|r| r.credentials)
+    }
+
     /// 计算字段投影掩码。字段名在 `validate()` 阶段已验证，无效名称静默退化为全量掩码。
     #[must_use]
     pub fn field_mask(&self) -> FieldMask {
@@ -0,0 +1,259 @@
+//! `[features.obfuscate_ids]`：把 `sess_id` / `thrd_id` 等内存地址替换为本次运行内稳定的小整数。
+//!
+//! 达梦日志中的会话、线程、语句句柄是服务器进程内的指针（如 `sess:0x7f3a5c012a88`），重启后即变化，
+//! 导出结果对外分享时还会暴露内存布局。启用后按 EP + 原值查字典，首次出现时依次编号（从 1 开始），
+//! 同一运行内同一会话的所有记录得到同一编号，输出也更短。编号跨文件延续，因此只走顺序路径；
+//! 替换发生在过滤之后（按 `sess_ids` 等过滤仍使用原值），参数替换的 PARAMS 匹配与导出均使用编号。
+//! 配置 `dictionary_file` 时运行结束写出对照表（`field,ep,original,id`），供内部排障反查，不随导出分享。
+use crate::error::{ConfigError, Error, FileError, Result};
+use ahash::HashMap as AHashMap;
+use compact_str::CompactString;
+use dm_database_parser_sqllog::MetaParts;
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// 可替换的字段
+pub const OBFUSCATE_FIELDS: &[&str] = &["sess_id", "thrd_id", "statement"];
+
+/// `[features.obfuscate_ids]` 配置段
+#[derive(Debug, Deserialize, Clone)]
+#[non_exhaustive]
+pub struct ObfuscateIdsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 替换为编号的字段（默认 `sess_id` 与 `thrd_id`）
+    #[serde(default = "default_fields")]
+    pub fields: Vec<String>,
+    /// 原值与编号的对照表（CSV），未配置时不写出
+    #[serde(default)]
+    pub dictionary_file: Option<String>,
+}
+
+fn default_fields() -> Vec<String> {
+    ["sess_id", "thrd_id"]
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+impl Default for ObfuscateIdsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            fields: default_fields(),
+            dictionary_file: None,
+        }
+    }
+}
+
+impl ObfuscateIdsConfig {
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |field: &str, value: String, reason: String| {
+            Err(Error::Config(ConfigError::InvalidValue {
+                field: format!("features.obfuscate_ids.{field}"),
+                value,
+                reason,
+            }))
+        };
+        if let Some(bad) = self
+            .fields
+            .iter()
+            .find(|f| !OBFUSCATE_FIELDS.contains(&f.as_str()))
+        {
+            return invalid(
+                "fields",
+                bad.clone(),
+                format!("must be one of: {}", OBFUSCATE_FIELDS.join(", ")),
+            );
+        }
+        if self
+            .dictionary_file
+            .as_deref()
+            .is_some_and(|f| f.trim().is_empty())
+        {
+            return invalid(
+                "dictionary_file",
+                String::new(),
+                "dictionary_file cannot be empty".into(),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// 单个字段的编号字典
+#[derive(Debug, Default)]
+struct Dictionary {
+    ids: AHashMap<(u8, CompactString), u64>,
+}
+
+impl Dictionary {
+    fn remap(&mut self, ep: u8, value: &mut Cow<'_, str>) {
+        // 空值（部分记录不带线程号）保持为空，不占编号
+        if value.is_empty() {
+            return;
+        }
+        let next = self.ids.len() as u64 + 1;
+        let id = *self
+            .ids
+            .entry((ep, CompactString::from(value.as_ref())))
+            .or_insert(next);
+        *value = Cow::Owned(id.to_string());
+    }
+}
+
+/// 运行期编号状态（跨文件累计）
+#[derive(Debug, Default)]
+pub struct IdObfuscator {
+    /// 与 [`OBFUSCATE_FIELDS`] 一一对应；未启用的字段为 `None`
+    dictionaries: [Option<Dictionary>; 3],
+    dictionary_file: Option<String>,
+}
+
+impl IdObfuscator {
+    /// 未启用时返回 `None`
+    #[must_use]
+    pub fn from_config(config: Option<&ObfuscateIdsConfig>) -> Option<Self> {
+        let config = config.filter(|c| c.enabled && !c.fields.is_empty())?;
+        let mut obfuscator = Self {
+            dictionary_file: config.dictionary_file.clone(),
+            ..Self::default()
+        };
+        for (name, dictionary) in OBFUSCATE_FIELDS.iter().zip(&mut obfuscator.dictionaries) {
+            if config.fields.iter().any(|f| f == name) {
+                *dictionary = Some(Dictionary::default());
+            }
+        }
+        Some(obfuscator)
+    }
+
+    /// 把已启用字段替换为编号
+    pub fn apply(&mut self, meta: &mut MetaParts<'_>) {
+        let [sess, thrd, stmt] = &mut self.dictionaries;
+        if let Some(d) = sess {
+            d.remap(meta.ep, &mut meta.sess_id);
+        }
+        if let Some(d) = thrd {
+            d.remap(meta.ep, &mut meta.thrd_id);
+        }
+        if let Some(d) = stmt {
+            d.remap(meta.ep, &mut meta.statement);
+        }
+    }
+
+    /// 各字段已分配的编号数，按 [`OBFUSCATE_FIELDS`] 顺序（未启用的字段不列出）
+    #[must_use]
+    pub fn counts(&self) -> Vec<(&'static str, usize)> {
+        OBFUSCATE_FIELDS
+            .iter()
+            .zip(&self.dictionaries)
+            .filter_map(|(name, d)| d.as_ref().map(|d| (*name, d.ids.len())))
+            .collect()
+    }
+
+    /// 对照表 CSV：`field,ep,original,id`，按字段、编号排序
+    #[must_use]
+    pub fn render_dictionary(&self) -> String {
+        let mut out = String::from("field,ep,original,id\n");
+        for (name, d) in OBFUSCATE_FIELDS.iter().zip(&self.dictionaries) {
+            let Some(d) = d else { continue };
+            let mut entries: Vec<_> = d.ids.iter().collect();
+            entries.sort_by_key(|(_, id)| **id);
+            for ((ep, original), id) in entries {
+                let original = if original.contains([',', '"', '\n']) {
+                    format!("\"{}\"", original.replace('"', "\"\""))
+                } else {
+                    original.to_string()
+                };
+                let _ = writeln!(out, "{name},{ep},{original},{id}");
+            }
+        }
+        out
+    }
+
+    /// 写出对照表（配置了 `dictionary_file` 时），返回写出的路径
+    pub fn write_dictionary(&self) -> Result<Option<&str>> {
+        let Some(file) = self.dictionary_file.as_deref() else {
+            return Ok(None);
+        };
+        let path = Path::new(file);
+        let write_err = |e: std::io::Error| {
+            Error::File(FileError::WriteFailed {
+                path: path.to_path_buf(),
+                reason: e.to_string(),
+            })
+        };
+        if let Some(dir) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(write_err)?;
+        }
+        crate::temp::write_atomic(path, self.render_dictionary().as_bytes()).map_err(write_err)?;
+        Ok(Some(file))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(ep: u8, sess: &'static str, thrd: &'static str) -> MetaParts<'static> {
+        MetaParts {
+            ep,
+            sess_id: Cow::Borrowed(sess),
+            thrd_id: Cow::Borrowed(thrd),
+            statement: Cow::Borrowed("0x7f00dead"),
+            ..MetaParts::default()
+        }
+    }
+
+    #[test]
+    fn test_stable_small_ids_per_ep() {
+        let cfg = ObfuscateIdsConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        let mut o = IdObfuscator::from_config(Some(&cfg)).unwrap();
+        let ids: Vec<(String, String, String)> = [
+            meta(0, "0x7f3a5c012a88", "4411"),
+            meta(0, "0x7f3a5c019f10", "4412"),
+            meta(0, "0x7f3a5c012a88", "4411"),
+            // 不同 EP 的同一地址是不同的会话
+            meta(1, "0x7f3a5c012a88", ""),
+        ]
+        .into_iter()
+        .map(|mut m| {
+            o.apply(&mut m);
+            (
+                m.sess_id.into_owned(),
+                m.thrd_id.into_owned(),
+                m.statement.into_owned(),
+            )
+        })
+        .collect();
+        let expected = [("1", "1"), ("2", "2"), ("1", "1"), ("3", "")];
+        for ((sess, thrd, stmt), (want_sess, want_thrd)) in ids.iter().zip(expected) {
+            assert_eq!((sess.as_str(), thrd.as_str()), (want_sess, want_thrd));
+            // 未启用的字段保持原值
+            assert_eq!(stmt, "0x7f00dead");
+        }
+        assert_eq!(o.counts(), [("sess_id", 3), ("thrd_id", 2)]);
+        assert_eq!(
+            o.render_dictionary().lines().take(3).collect::<Vec<_>>(),
+            [
+                "field,ep,original,id",
+                "sess_id,0,0x7f3a5c012a88,1",
+                "sess_id,0,0x7f3a5c019f10,2"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut cfg = ObfuscateIdsConfig::default();
+        assert!(cfg.validate().is_ok());
+        assert!(IdObfuscator::from_config(Some(&cfg)).is_none());
+        cfg.fields = vec!["username".to_string()];
+        assert!(cfg.validate().is_err());
+    }
+}
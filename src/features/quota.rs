@@ -0,0 +1,163 @@
+//! `[features.quota]`：按用户 / SQL 指纹限制导出条数，避免批处理账号的海量记录淹没导出结果。
+//!
+//! 配额按记录出现顺序先到先得；超出配额的记录计为跳过，按用户汇总后在运行摘要中展示。
+use super::fingerprint;
+use crate::error::{ConfigError, Error, Result};
+use ahash::HashMap as AHashMap;
+use compact_str::CompactString;
+use serde::Deserialize;
+
+/// `[features.quota]` 配置段
+#[derive(Debug, Deserialize, Clone, Default)]
+#[non_exhaustive]
+pub struct QuotaConfig {
+    /// 每个用户最多导出的记录数
+    #[serde(default)]
+    pub max_records_per_user: Option<u64>,
+    /// 每个 SQL 指纹（字面量替换为 `?`）最多导出的记录数
+    #[serde(default)]
+    pub max_records_per_fingerprint: Option<u64>,
+}
+
+impl QuotaConfig {
+    pub fn validate(&self) -> Result<()> {
+        for (field, value) in [
+            ("max_records_per_user", self.max_records_per_user),
+            (
+                "max_records_per_fingerprint",
+                self.max_records_per_fingerprint,
+            ),
+        ] {
+            if value == Some(0) {
+                return Err(Error::Config(ConfigError::InvalidValue {
+                    field: format!("features.quota.{field}"),
+                    value: "0".to_string(),
+                    reason: "must be greater than 0 (omit the key to disable)".to_string(),
+                }));
+            }
+        }
+        Ok(())
+    }
+
+    /// 是否配置了任一配额
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.max_records_per_user.is_some() || self.max_records_per_fingerprint.is_some()
+    }
+}
+
+/// 运行期配额计数器（跨文件累计）
+#[derive(Debug, Default)]
+pub struct QuotaTracker {
+    max_per_user: Option<u64>,
+    max_per_fingerprint: Option<u64>,
+    user_counts: AHashMap<CompactString, u64>,
+    fingerprint_counts: AHashMap<String, u64>,
+    /// 被配额跳过的记录数（按用户）
+    skipped: AHashMap<CompactString, u64>,
+}
+
+impl QuotaTracker {
+    /// 未配置任何配额时返回 `None`
+    #[must_use]
+    pub fn from_config(config: Option<&QuotaConfig>) -> Option<Self> {
+        let config = config.filter(|c| c.is_active())?;
+        Some(Self {
+            max_per_user: config.max_records_per_user,
+            max_per_fingerprint: config.max_records_per_fingerprint,
+            ..Self::default()
+        })
+    }
+
+    /// 判定一条记录是否仍在配额内：是则计入配额并返回 `true`，否则计入该用户的跳过数
+    pub fn admit(&mut self, username: &str, sql: &str) -> bool {
+        let user_ok = self
+            .max_per_user
+            .is_none_or(|max| self.user_counts.get(username).copied().unwrap_or(0) < max);
+        let fp = self.max_per_fingerprint.map(|_| fingerprint(sql));
+        let fp_ok = match (&fp, self.max_per_fingerprint) {
+            (Some(fp), Some(max)) => self.fingerprint_counts.get(fp).copied().unwrap_or(0) < max,
+            _ => true,
+        };
+        if !(user_ok && fp_ok) {
+            *self.skipped.entry(username.into()).or_default() += 1;
+            return false;
+        }
+        if self.max_per_user.is_some() {
+            *self.user_counts.entry(username.into()).or_default() += 1;
+        }
+        if let Some(fp) = fp {
+            *self.fingerprint_counts.entry(fp).or_default() += 1;
+        }
+        true
+    }
+
+    /// 按跳过数降序（同数按用户名）排列的 `(用户, 跳过数)`
+    #[must_use]
+    pub fn skipped_by_user(&self) -> Vec<(String, u64)> {
+        let mut v: Vec<(String, u64)> = self
+            .skipped
+            .iter()
+            .map(|(u, n)| (u.to_string(), *n))
+            .collect();
+        v.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        v
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker(user: Option<u64>, fp: Option<u64>) -> QuotaTracker {
+        QuotaTracker::from_config(Some(&QuotaConfig {
+            max_records_per_user: user,
+            max_records_per_fingerprint: fp,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_inactive_config_has_no_tracker() {
+        assert!(QuotaTracker::from_config(None).is_none());
+        assert!(QuotaTracker::from_config(Some(&QuotaConfig::default())).is_none());
+    }
+
+    #[test]
+    fn test_per_user_quota_counts_skips() {
+        let mut q = tracker(Some(2), None);
+        let admitted: Vec<bool> = ["A", "A", "B", "A", "A", "B"]
+            .iter()
+            .map(|u| q.admit(u, "SELECT 1"))
+            .collect();
+        assert_eq!(admitted, [true, true, true, false, false, true]);
+        assert_eq!(q.skipped_by_user(), vec![("A".to_string(), 2)]);
+    }
+
+    #[test]
+    fn test_per_fingerprint_quota_ignores_literals() {
+        let mut q = tracker(None, Some(1));
+        assert!(q.admit("A", "SELECT * FROM t WHERE id = 1"));
+        assert!(!q.admit("B", "SELECT * FROM t WHERE id = 2"));
+        assert!(q.admit("B", "DELETE FROM t WHERE id = 2"));
+        assert_eq!(q.skipped_by_user(), vec![("B".to_string(), 1)]);
+    }
+
+    #[test]
+    fn test_skipped_record_does_not_consume_user_quota() {
+        // 被指纹配额拒绝的记录不占用该用户配额
+        let mut q = tracker(Some(1), Some(1));
+        assert!(q.admit("A", "SELECT 1"));
+        assert!(!q.admit("B", "SELECT 2"));
+        assert!(q.admit("B", "UPDATE t SET x = 1"));
+    }
+
+    #[test]
+    fn test_zero_quota_rejected() {
+        let cfg = QuotaConfig {
+            max_records_per_user: Some(0),
+            max_records_per_fingerprint: None,
+        };
+        assert!(cfg.validate().is_err());
+    }
+}
@@ -316,6 +316,69 @@ fn apply_params(sql: &str, params: &[ParamValue], colon_style: bool) -> String {
     String::from_utf8(buf).expect("apply_params produced invalid UTF-8")
 }
 
+/// 识别 PARAMS 记录（无 tag 的记录，`pm_sql` 即记录正文）并把参数值存入 `buffer`，
+/// 供同一 `(sess_id, stmt)` 随后的执行记录使用。
+///
+/// 返回记录是否为 PARAMS 记录；格式无法解析的 PARAMS 记录同样返回 `true`，但不更新 `buffer`。
+pub fn record_params<S: std::hash::BuildHasher>(
+    meta: &dm_database_parser_sqllog::MetaParts<'_>,
+    pm_sql: &str,
+    buffer: &mut HashMap<(CompactString, CompactString), SmallVec<[ParamValue; 6]>, S>,
+) -> bool {
+    if !pm_sql.starts_with("PARAMS(") {
+        return false;
+    }
+    if let Some(params) = parse_params(pm_sql) {
+        // CompactString 对短字符串（≤23 字节）内联存储，消除堆分配。
+        // sess_id（指针如 "0xfffb81a474a0"）和 statement（如 "0x1"）通常都满足此条件。
+        buffer.insert(
+            (
+                CompactString::from(meta.sess_id.as_ref()),
+                CompactString::from(meta.statement.as_ref()),
+            ),
+            params,
+        );
+    }
+    true
+}
+
+/// 执行记录绑定的参数值：同一 `(sess_id, stmt)` 最近一次 PARAMS 记录的值。
+///
+/// 只对含占位符的 `[INS]`/`[DEL]`/`[UPD]`/`[SEL]` 记录返回；参数个数与占位符个数不符时同样返回，
+/// 由调用方原样记录（与 [`compute_normalized`] 不同，这里不做替换）。
+#[must_use]
+pub fn bound_params<'b, S: std::hash::BuildHasher>(
+    record: &dm_database_parser_sqllog::Sqllog<'_>,
+    meta: &dm_database_parser_sqllog::MetaParts<'_>,
+    pm_sql: &str,
+    buffer: &'b HashMap<(CompactString, CompactString), SmallVec<[ParamValue; 6]>, S>,
+) -> Option<&'b [ParamValue]> {
+    if buffer.is_empty() || !matches!(record.tag.as_deref()?, "INS" | "DEL" | "UPD" | "SEL") {
+        return None;
+    }
+    if count_placeholders(pm_sql).0 == 0 {
+        return None;
+    }
+    let key = (
+        CompactString::from(meta.sess_id.as_ref()),
+        CompactString::from(meta.statement.as_ref()),
+    );
+    buffer.get(&key).map(SmallVec::as_slice)
+}
+
+/// 参数值按 SQL 字面量写法以 `, ` 连接，如 `3, 'send ok', NULL`
+#[must_use]
+pub fn format_params(params: &[ParamValue]) -> String {
+    let mut out = String::with_capacity(params.iter().map(|p| p.as_sql().len() + 2).sum());
+    for (i, p) in params.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        out.push_str(p.as_sql());
+    }
+    out
+}
+
 /// Helper used in `cli/run.rs` to update the params buffer and compute the
 /// `normalized_sql` value for a single log record.
 ///
@@ -358,19 +421,7 @@ pub fn compute_normalized<'a, S: std::hash::BuildHasher>(
         // 无 tag → 可能是 PARAMS 记录。
         // pm_sql 对于 PARAMS 记录等价于 body()（无性能指标时两者相同），
         // 直接复用，节省一次 find_indicators_split() 后向扫描。
-        if pm_sql.starts_with("PARAMS(") {
-            if let Some(params) = parse_params(pm_sql) {
-                // CompactString 对短字符串（≤23 字节）内联存储，消除堆分配。
-                // sess_id（指针如 "0xfffb81a474a0"）和 statement（如 "0x1"）通常都满足此条件。
-                buffer.insert(
-                    (
-                        CompactString::from(meta.sess_id.as_ref()),
-                        CompactString::from(meta.statement.as_ref()),
-                    ),
-                    params,
-                );
-            }
-        }
+        record_params(meta, pm_sql, buffer);
         return None;
     }
 
@@ -445,6 +496,31 @@ mod tests {
         ParamValue::Quoted(CompactString::new(s))
     }
 
+    #[test]
+    fn test_record_and_bound_params() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = dir.path().join("a.log");
+        std::fs::write(
+            &log,
+            "2025-01-15 10:30:28.001 (EP[0] sess:0x1 user:U trxid:1 stmt:0x1 appname:App ip:10.0.0.1) PARAMS(SEQNO, TYPE, DATA)={(0, INT, 5), (1, BLOB, )}\n\
+             2025-01-15 10:30:28.002 (EP[0] sess:0x1 user:U trxid:1 stmt:0x1 appname:App ip:10.0.0.1) [SEL] SELECT * FROM t WHERE a = ? AND b = ?. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n",
+        )
+        .unwrap();
+        let parser = dm_database_parser_sqllog::LogParser::from_path(&log).unwrap();
+        let records: Vec<_> = parser.iter().filter_map(Result::ok).collect();
+        let mut buffer = ParamBuffer::default();
+
+        let (params, exec) = (&records[0], &records[1]);
+        let meta = params.parse_meta();
+        assert!(record_params(&meta, params.body().as_ref(), &mut buffer));
+        let meta = exec.parse_meta();
+        let pm = exec.parse_performance_metrics();
+        assert!(!record_params(&meta, pm.sql.as_ref(), &mut buffer));
+        let bound = bound_params(exec, &meta, pm.sql.as_ref(), &buffer).unwrap();
+        assert_eq!(format_params(bound), "5, NULL");
+        assert!(bound_params(exec, &meta, "SELECT 1", &buffer).is_none());
+    }
+
     // ── parse_params ──────────────────────────────────────────────────────────
 
     #[test]
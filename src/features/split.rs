@@ -0,0 +1,128 @@
+//! 多租户拆分导出（`exporter.<x>.split_by`）：按用户名或 SQL 中的 schema 把记录分流到
+//! 各自的输出文件 / 表，每个应用团队只拿到自己的日志切片。
+use crate::error::{ConfigError, Error, Result};
+use dm_database_parser_sqllog::MetaParts;
+use regex::Regex;
+use std::borrow::Cow;
+use std::sync::LazyLock;
+
+/// `split_by` 可选值
+pub const SPLIT_BY_VALUES: &[&str] = &["username", "schema"];
+
+/// 无法确定拆分键时使用的分片名
+pub const UNKNOWN_PARTITION: &str = "_unknown";
+
+/// 拆分维度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitBy {
+    Username,
+    /// SQL 中首个 `schema.object` 限定名的 schema；未限定时退回用户名（达梦默认 schema 与用户同名）
+    Schema,
+}
+
+impl SplitBy {
+    /// 解析配置值；`field` 用于错误信息（如 `exporter.csv.split_by`）
+    pub fn parse(field: &str, value: &str) -> Result<Self> {
+        match value {
+            "username" => Ok(Self::Username),
+            "schema" => Ok(Self::Schema),
+            _ => Err(Error::Config(ConfigError::InvalidValue {
+                field: field.to_string(),
+                value: value.to_string(),
+                reason: format!("must be one of: {}", SPLIT_BY_VALUES.join(", ")),
+            })),
+        }
+    }
+
+    /// 计算记录所属分片名（已做文件名 / 表名安全化处理）
+    #[must_use]
+    pub fn partition(self, meta: &MetaParts<'_>, sql: &str) -> String {
+        let key = match self {
+            Self::Username => Cow::Borrowed(meta.username.as_ref()),
+            Self::Schema => {
+                schema_of(sql).map_or_else(|| Cow::Borrowed(meta.username.as_ref()), Cow::Owned)
+            }
+        };
+        sanitize(&key)
+    }
+}
+
+static QUALIFIED_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?i)\b(?:from|join|into|update|table|view|index|procedure|function|trigger|sequence|call|exec|execute)\s+(?:"([^"]+)"|([A-Za-z_][\w$#]*))\s*\."#,
+    )
+    .expect("schema pattern is valid")
+});
+
+/// SQL 中首个限定对象名的 schema（大写，带引号的保持原样）
+fn schema_of(sql: &str) -> Option<String> {
+    let c = QUALIFIED_RE.captures(sql)?;
+    c.get(1)
+        .map(|m| m.as_str().to_string())
+        .or_else(|| c.get(2).map(|m| m.as_str().to_ascii_uppercase()))
+}
+
+/// 只保留字母、数字、`_`、`-`，其余字符替换为 `_`；空串返回 [`UNKNOWN_PARTITION`]
+fn sanitize(key: &str) -> String {
+    let key = key.trim();
+    if key.is_empty() {
+        return UNKNOWN_PARTITION.to_string();
+    }
+    key.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(user: &'static str) -> MetaParts<'static> {
+        MetaParts {
+            username: Cow::Borrowed(user),
+            ..MetaParts::default()
+        }
+    }
+
+    #[test]
+    fn test_partition_by_username_sanitized() {
+        let by = SplitBy::Username;
+        assert_eq!(by.partition(&meta("BILL_APP"), "SELECT 1"), "BILL_APP");
+        assert_eq!(by.partition(&meta("a/b c"), "SELECT 1"), "a_b_c");
+        assert_eq!(by.partition(&meta(""), "SELECT 1"), UNKNOWN_PARTITION);
+    }
+
+    #[test]
+    fn test_partition_by_schema() {
+        let by = SplitBy::Schema;
+        assert_eq!(
+            by.partition(
+                &meta("U"),
+                "select * from sales.orders o join hr.emp e on 1=1"
+            ),
+            "SALES"
+        );
+        assert_eq!(
+            by.partition(&meta("U"), "INSERT INTO \"Fin\".\"Ledger\" VALUES (1)"),
+            "Fin"
+        );
+        // 未限定 schema：退回用户名
+        assert_eq!(by.partition(&meta("ETL"), "UPDATE t SET a = 1"), "ETL");
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown() {
+        assert_eq!(
+            SplitBy::parse("exporter.csv.split_by", "schema").unwrap(),
+            SplitBy::Schema
+        );
+        let err = SplitBy::parse("exporter.csv.split_by", "appname").unwrap_err();
+        assert!(err.to_string().contains("exporter.csv.split_by"));
+    }
+}
@@ -0,0 +1,237 @@
+//! `[features.statements]`：按 SQL 指纹维护内存中的语句登记表（首次 / 末次出现时间与次数），
+//! 运行结束后可写出为字典表，与事实表的 `statement_id` 派生列关联查询。
+//!
+//! `statement_id` 为指纹的 FNV-1a 64 位哈希（清除最高位，保证非负），跨运行稳定；
+//! `sql_hash` 派生列是同一哈希的 16 位十六进制形式。
+use super::fingerprint;
+use crate::error::{ConfigError, Error, ExportError, Result};
+use ahash::HashMap as AHashMap;
+use serde::{Deserialize, Serialize};
+use std::io::Write as _;
+use std::path::Path;
+
+/// `[features.statements]` 配置段
+#[derive(Debug, Deserialize, Clone, Default)]
+#[non_exhaustive]
+pub struct StatementsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 字典表输出路径（CSV）；未设置时只在内存中维护，供运行摘要与报告使用
+    #[serde(default)]
+    pub file: Option<String>,
+}
+
+impl StatementsConfig {
+    pub fn validate(&self) -> Result<()> {
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+        let reason = if file.trim().is_empty() {
+            Some("output file path cannot be empty")
+        } else if Path::new(file)
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("parquet"))
+        {
+            Some("Parquet output is not supported; use a .csv path")
+        } else {
+            None
+        };
+        match reason {
+            Some(reason) => Err(Error::Config(ConfigError::InvalidValue {
+                field: "features.statements.file".to_string(),
+                value: file.clone(),
+                reason: reason.to_string(),
+            })),
+            None => Ok(()),
+        }
+    }
+}
+
+fn fnv1a(fingerprint: &str) -> u64 {
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in fingerprint.as_bytes() {
+        h ^= u64::from(*b);
+        h = h.wrapping_mul(0x0100_0000_01b3);
+    }
+    h
+}
+
+/// 指纹对应的稳定 ID
+#[must_use]
+pub fn statement_id(fingerprint: &str) -> i64 {
+    i64::try_from(fnv1a(fingerprint) >> 1).unwrap_or(i64::MAX)
+}
+
+/// 指纹对应的稳定哈希（16 位小写十六进制）
+#[must_use]
+pub fn sql_hash(fingerprint: &str) -> String {
+    format!("{:016x}", fnv1a(fingerprint))
+}
+
+/// 登记表中的一条语句
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StatementInfo {
+    pub statement_id: i64,
+    pub fingerprint: String,
+    pub count: u64,
+    pub first_seen: String,
+    pub last_seen: String,
+}
+
+#[derive(Debug)]
+struct Entry {
+    id: i64,
+    count: u64,
+    first_seen: String,
+    last_seen: String,
+}
+
+/// 运行期语句登记表（跨文件累计）
+#[derive(Debug, Default)]
+pub struct StatementRegistry {
+    entries: AHashMap<String, Entry>,
+}
+
+impl StatementRegistry {
+    /// 未启用时返回 `None`
+    #[must_use]
+    pub fn from_config(config: Option<&StatementsConfig>) -> Option<Self> {
+        config.filter(|c| c.enabled).map(|_| Self::default())
+    }
+
+    /// 登记一条 DML 记录，返回其 `statement_id`
+    pub fn observe(&mut self, sql: &str, ts: &str) -> i64 {
+        let fp = fingerprint(sql);
+        if let Some(e) = self.entries.get_mut(fp.as_str()) {
+            e.count += 1;
+            if ts < e.first_seen.as_str() {
+                e.first_seen = ts.to_string();
+            }
+            if ts > e.last_seen.as_str() {
+                e.last_seen = ts.to_string();
+            }
+            return e.id;
+        }
+        let id = statement_id(&fp);
+        self.entries.insert(
+            fp,
+            Entry {
+                id,
+                count: 1,
+                first_seen: ts.to_string(),
+                last_seen: ts.to_string(),
+            },
+        );
+        id
+    }
+
+    /// 转换为语句列表，按次数降序（次数相同按指纹升序）
+    #[must_use]
+    pub fn finalize(self) -> Vec<StatementInfo> {
+        let mut out: Vec<StatementInfo> = self
+            .entries
+            .into_iter()
+            .map(|(fingerprint, e)| StatementInfo {
+                statement_id: e.id,
+                fingerprint,
+                count: e.count,
+                first_seen: e.first_seen,
+                last_seen: e.last_seen,
+            })
+            .collect();
+        out.sort_unstable_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| a.fingerprint.cmp(&b.fingerprint))
+        });
+        out
+    }
+}
+
+/// 写出字典表 CSV：`statement_id,fingerprint,count,first_seen,last_seen`
+pub fn write_statements_csv(path: &Path, statements: &[StatementInfo]) -> Result<()> {
+    let write_err = |reason: String| {
+        Error::Export(ExportError::WriteFailed {
+            path: path.to_path_buf(),
+            reason,
+        })
+    };
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| write_err(format!("create dir failed: {e}")))?;
+    }
+    let file = std::fs::File::create(path).map_err(|e| write_err(format!("open failed: {e}")))?;
+    let mut w = std::io::BufWriter::new(file);
+    let mut buf = String::from("statement_id,fingerprint,count,first_seen,last_seen\n");
+    for s in statements {
+        buf.push_str(&s.statement_id.to_string());
+        buf.push_str(",\"");
+        buf.push_str(&s.fingerprint.replace('"', "\"\""));
+        buf.push_str("\",");
+        buf.push_str(&s.count.to_string());
+        buf.push(',');
+        buf.push_str(&s.first_seen);
+        buf.push(',');
+        buf.push_str(&s.last_seen);
+        buf.push('\n');
+    }
+    w.write_all(buf.as_bytes())
+        .and_then(|()| w.flush())
+        .map_err(|e| write_err(format!("write failed: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_groups_by_fingerprint() {
+        let mut reg = StatementRegistry::default();
+        let a = reg.observe("SELECT * FROM t WHERE id = 1", "2025-01-15 10:00:00.000");
+        let b = reg.observe("SELECT * FROM t WHERE id = 2", "2025-01-15 09:00:00.000");
+        reg.observe("DELETE FROM t", "2025-01-15 11:00:00.000");
+        assert_eq!(a, b);
+        assert!(a >= 0);
+        assert_eq!(
+            a,
+            statement_id(&fingerprint("SELECT * FROM t WHERE id = 3"))
+        );
+
+        let stmts = reg.finalize();
+        assert_eq!(stmts.len(), 2);
+        assert_eq!(stmts[0].statement_id, a);
+        assert_eq!(stmts[0].count, 2);
+        assert_eq!(stmts[0].first_seen, "2025-01-15 09:00:00.000");
+        assert_eq!(stmts[0].last_seen, "2025-01-15 10:00:00.000");
+    }
+
+    #[test]
+    fn test_write_statements_csv() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("dict").join("statements.csv");
+        let mut reg = StatementRegistry::default();
+        let id = reg.observe("SELECT \"A\" FROM t", "2025-01-15 10:00:00.000");
+        write_statements_csv(&path, &reg.finalize()).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            content,
+            format!(
+                "statement_id,fingerprint,count,first_seen,last_seen\n{id},\"SELECT \"\"A\"\" FROM t\",1,2025-01-15 10:00:00.000,2025-01-15 10:00:00.000\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_parquet() {
+        let cfg = StatementsConfig {
+            enabled: true,
+            file: Some("outputs/statements.parquet".into()),
+        };
+        assert!(cfg.validate().is_err());
+        let cfg = StatementsConfig {
+            file: Some("outputs/statements.csv".into()),
+            ..cfg
+        };
+        assert!(cfg.validate().is_ok());
+    }
+}
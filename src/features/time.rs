@@ -0,0 +1,282 @@
+//! `[features.time]`：导出时的时间戳时区归一化与格式化。
+//!
+//! DM 日志的 `ts` 为服务器本地时间（无时区信息）。跨时区服务器的日志合并分析时，
+//! 可将 `ts` 列按 `input_timezone` 解释后转换到 `output_timezone`，并按 `output_format` 输出。
+//! 仅影响导出列，过滤器（`start_ts`/`end_ts`）仍按原始日志时间比较。
+use crate::error::{ConfigError, Error, Result};
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, Offset, TimeZone, Utc};
+use serde::Deserialize;
+
+/// `[features.time]` 配置段
+#[derive(Debug, Deserialize, Clone, Default)]
+#[non_exhaustive]
+pub struct TimeConfig {
+    /// 日志时间所在时区：`local`（默认）/ `UTC` / 固定偏移如 `+08:00`
+    #[serde(default)]
+    pub input_timezone: Option<String>,
+    /// 输出时区：`UTC`（默认）/ `local` / 固定偏移如 `+08:00`
+    #[serde(default)]
+    pub output_timezone: Option<String>,
+    /// 输出格式：`iso8601`（默认）/ `epoch_ms` / `original` / 任意 strftime 模式
+    #[serde(default)]
+    pub output_format: Option<String>,
+}
+
+/// 解析后的时区
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tz {
+    Local,
+    Fixed(FixedOffset),
+}
+
+impl Tz {
+    /// 支持 `local`、`UTC`/`Z`、`+08:00`/`+0800`/`+08`/`UTC+8` 形式
+    pub fn parse(s: &str) -> std::result::Result<Self, String> {
+        let t = s.trim();
+        if t.eq_ignore_ascii_case("local") {
+            return Ok(Self::Local);
+        }
+        if t.eq_ignore_ascii_case("utc") || t.eq_ignore_ascii_case("z") || t == "GMT" {
+            return Ok(Self::Fixed(Utc.fix()));
+        }
+        let rest = t
+            .strip_prefix("UTC")
+            .or_else(|| t.strip_prefix("GMT"))
+            .unwrap_or(t);
+        let (sign, body) = match rest.as_bytes().first() {
+            Some(b'+') => (1, &rest[1..]),
+            Some(b'-') => (-1, &rest[1..]),
+            _ => return Err(format!("unsupported timezone '{s}'")),
+        };
+        let (h, m) = match body.split_once(':') {
+            Some((h, m)) => (h, m),
+            None if body.len() == 4 => body.split_at(2),
+            None => (body, "0"),
+        };
+        let h: i32 = h
+            .parse()
+            .map_err(|_| format!("unsupported timezone '{s}'"))?;
+        let m: i32 = m
+            .parse()
+            .map_err(|_| format!("unsupported timezone '{s}'"))?;
+        if h > 23 || m > 59 {
+            return Err(format!("timezone offset out of range: '{s}'"));
+        }
+        FixedOffset::east_opt(sign * (h * 3600 + m * 60))
+            .map(Self::Fixed)
+            .ok_or_else(|| format!("timezone offset out of range: '{s}'"))
+    }
+
+    fn is_utc(self) -> bool {
+        matches!(self, Self::Fixed(o) if o.local_minus_utc() == 0)
+    }
+}
+
+/// 输出格式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TsFormat {
+    /// `2025-01-15T02:30:28.001Z` / `2025-01-15T10:30:28.001+08:00`
+    Iso8601,
+    /// Unix 毫秒时间戳
+    EpochMs,
+    /// 保持 DM 原始格式 `YYYY-MM-DD HH:MM:SS.mmm`（仅做时区转换）
+    Original,
+    /// 自定义 strftime 模式
+    Pattern(String),
+}
+
+impl TsFormat {
+    pub fn parse(s: &str) -> std::result::Result<Self, String> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "iso8601" | "rfc3339" => Ok(Self::Iso8601),
+            "epoch_ms" | "epoch_millis" => Ok(Self::EpochMs),
+            "original" => Ok(Self::Original),
+            _ => {
+                crate::exporter::rollover::validate_pattern(s)?;
+                Ok(Self::Pattern(s.to_string()))
+            }
+        }
+    }
+}
+
+/// 预编译的时间戳归一化器（热路径使用）
+#[derive(Debug, Clone)]
+pub struct TsNormalizer {
+    input: Tz,
+    output: Tz,
+    format: TsFormat,
+}
+
+impl TimeConfig {
+    /// 日志时间所在时区（未配置时为 `local`）
+    pub fn input_tz(&self) -> Result<Tz> {
+        let s = self.input_timezone.as_deref().unwrap_or("local");
+        Tz::parse(s).map_err(|r| invalid("input_timezone", s, r))
+    }
+
+    /// 校验并编译配置，非法值返回 `ConfigError::InvalidValue`
+    pub fn compile(&self) -> Result<TsNormalizer> {
+        let output_s = self.output_timezone.as_deref().unwrap_or("UTC");
+        let format_s = self.output_format.as_deref().unwrap_or("iso8601");
+        Ok(TsNormalizer {
+            input: self.input_tz()?,
+            output: Tz::parse(output_s).map_err(|r| invalid("output_timezone", output_s, r))?,
+            format: TsFormat::parse(format_s).map_err(|r| invalid("output_format", format_s, r))?,
+        })
+    }
+}
+
+fn invalid(key: &str, value: &str, reason: String) -> Error {
+    Error::Config(ConfigError::InvalidValue {
+        field: format!("features.time.{key}"),
+        value: value.to_string(),
+        reason,
+    })
+}
+
+impl TsNormalizer {
+    /// 将 DM 时间戳按 `input` 时区解释并转换为 UTC；无法解析时返回 `None`
+    #[must_use]
+    pub fn to_utc(&self, ts: &str) -> Option<DateTime<Utc>> {
+        to_utc(ts, self.input)
+    }
+
+    /// 归一化 `ts`，写入 `out`（先清空）。无法解析的时间戳返回 `false`，调用方保留原值。
+    pub fn normalize_into(&self, ts: &str, out: &mut String) -> bool {
+        use std::fmt::Write as _;
+        let Some(utc) = self.to_utc(ts) else {
+            return false;
+        };
+        out.clear();
+        let dt = match self.output {
+            Tz::Fixed(off) => utc.with_timezone(&off),
+            Tz::Local => utc.with_timezone(&Local).fixed_offset(),
+        };
+        let _ = match &self.format {
+            TsFormat::Iso8601 if self.output.is_utc() => {
+                write!(out, "{}", dt.format("%Y-%m-%dT%H:%M:%S%.3fZ"))
+            }
+            TsFormat::Iso8601 => write!(out, "{}", dt.format("%Y-%m-%dT%H:%M:%S%.3f%:z")),
+            TsFormat::Original => write!(out, "{}", dt.format("%Y-%m-%d %H:%M:%S%.3f")),
+            TsFormat::Pattern(p) => write!(out, "{}", dt.format(p)),
+            TsFormat::EpochMs => write!(out, "{}", utc.timestamp_millis()),
+        };
+        true
+    }
+}
+
+/// 将 DM 时间戳按时区 `tz` 解释并转换为 UTC；无法解析时返回 `None`
+#[must_use]
+pub fn to_utc(ts: &str, tz: Tz) -> Option<DateTime<Utc>> {
+    let naive = parse_dm_ts(ts)?;
+    match tz {
+        Tz::Fixed(off) => off
+            .from_local_datetime(&naive)
+            .single()
+            .map(|d| d.with_timezone(&Utc)),
+        Tz::Local => Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|d| d.with_timezone(&Utc)),
+    }
+}
+
+/// 解析 DM 时间戳（`YYYY-MM-DD HH:MM:SS[.fff]`）
+#[must_use]
+pub fn parse_dm_ts(ts: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(ts, "%Y-%m-%d %H:%M:%S%.f")
+        .or_else(|_| NaiveDateTime::parse_from_str(ts, "%Y-%m-%d %H:%M:%S"))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compile(input: &str, output: &str, format: &str) -> TsNormalizer {
+        TimeConfig {
+            input_timezone: Some(input.into()),
+            output_timezone: Some(output.into()),
+            output_format: Some(format.into()),
+        }
+        .compile()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_tz_parse_variants() {
+        assert!(Tz::parse("UTC").unwrap().is_utc());
+        assert_eq!(Tz::parse("local").unwrap(), Tz::Local);
+        let east8 = Tz::Fixed(FixedOffset::east_opt(8 * 3600).unwrap());
+        assert_eq!(Tz::parse("+08:00").unwrap(), east8);
+        assert_eq!(Tz::parse("+0800").unwrap(), east8);
+        assert_eq!(Tz::parse("UTC+8").unwrap(), east8);
+        assert_eq!(
+            Tz::parse("-05:30").unwrap(),
+            Tz::Fixed(FixedOffset::west_opt(5 * 3600 + 1800).unwrap())
+        );
+        assert!(Tz::parse("Asia/Shanghai").is_err());
+        assert!(Tz::parse("+25:00").is_err());
+    }
+
+    #[test]
+    fn test_normalize_to_utc_iso8601() {
+        let n = compile("+08:00", "UTC", "iso8601");
+        let mut out = String::new();
+        assert!(n.normalize_into("2025-01-15 10:30:28.001", &mut out));
+        assert_eq!(out, "2025-01-15T02:30:28.001Z");
+    }
+
+    #[test]
+    fn test_normalize_epoch_ms() {
+        let n = compile("UTC", "UTC", "epoch_ms");
+        let mut out = String::new();
+        assert!(n.normalize_into("1970-01-01 00:00:01.500", &mut out));
+        assert_eq!(out, "1500");
+    }
+
+    #[test]
+    fn test_normalize_fixed_output_offset_and_original() {
+        let n = compile("UTC", "+08:00", "original");
+        let mut out = String::new();
+        assert!(n.normalize_into("2025-01-15 20:00:00.000", &mut out));
+        assert_eq!(out, "2025-01-16 04:00:00.000");
+
+        let n = compile("UTC", "+08:00", "iso8601");
+        assert!(n.normalize_into("2025-01-15 20:00:00.000", &mut out));
+        assert_eq!(out, "2025-01-16T04:00:00.000+08:00");
+    }
+
+    #[test]
+    fn test_normalize_custom_pattern() {
+        let n = compile("UTC", "UTC", "%Y/%m/%d %H");
+        let mut out = String::new();
+        assert!(n.normalize_into("2025-01-15 20:00:00.000", &mut out));
+        assert_eq!(out, "2025/01/15 20");
+    }
+
+    #[test]
+    fn test_normalize_unparseable_returns_false() {
+        let n = compile("UTC", "UTC", "iso8601");
+        let mut out = String::from("keep");
+        assert!(!n.normalize_into("not a ts", &mut out));
+        assert_eq!(out, "keep");
+    }
+
+    #[test]
+    fn test_compile_rejects_bad_values() {
+        let cfg = TimeConfig {
+            input_timezone: Some("Mars/Olympus".into()),
+            ..Default::default()
+        };
+        let msg = cfg.compile().unwrap_err().to_string();
+        assert!(msg.contains("features.time.input_timezone"), "{msg}");
+
+        let cfg = TimeConfig {
+            output_format: Some("%Q".into()),
+            ..Default::default()
+        };
+        let msg = cfg.compile().unwrap_err().to_string();
+        assert!(msg.contains("features.time.output_format"), "{msg}");
+    }
+}
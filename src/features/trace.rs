@@ -0,0 +1,107 @@
+//! `trace_id` / `span_id` 派生列：识别应用注入 SQL 注释中的链路上下文，用于与分布式追踪关联。
+//!
+//! 只在 `/* ... */` 注释（含 `/*+ ... */` 提示）内查找，支持：
+//!
+//! - sqlcommenter：`/*traceparent='00-<32 位 trace id>-<16 位 span id>-01'*/`（W3C Trace Context）；
+//! - 键值标签：`trace_id=...`、`traceid:...`、`span-id=...`、`TRACE_ID(...)` 等，值可带引号。
+//!
+//! 同一条 SQL 中 `traceparent` 优先，其次取第一个匹配的键值标签。
+use regex::Regex;
+use std::sync::LazyLock;
+
+static COMMENT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)/\*.*?\*/").expect("comment pattern is valid"));
+
+static TRACEPARENT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\btraceparent\s*=\s*'?[0-9a-f]{2}-([0-9a-f]{32})-([0-9a-f]{16})-[0-9a-f]{2}")
+        .expect("traceparent pattern is valid")
+});
+
+static TAG_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)\b(trace|span)[_-]?id\s*[=:(]\s*['"]?([0-9a-z][0-9a-z_.-]*)"#)
+        .expect("trace tag pattern is valid")
+});
+
+/// 从 SQL 注释中识别出的链路上下文（值借用自 SQL 原文）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TraceContext<'a> {
+    pub trace_id: Option<&'a str>,
+    pub span_id: Option<&'a str>,
+}
+
+/// 识别 SQL 中的链路上下文；没有注释时不做正则匹配
+#[must_use]
+pub fn trace_context(sql: &str) -> TraceContext<'_> {
+    let mut ctx = TraceContext::default();
+    if !sql.contains("/*") {
+        return ctx;
+    }
+    for comment in COMMENT_RE.find_iter(sql).map(|m| m.as_str()) {
+        if let Some(c) = TRACEPARENT_RE.captures(comment) {
+            let trace = c.get(1).map(|m| m.as_str());
+            // 全零 trace id 在 W3C 规范中无效
+            if trace.is_some_and(|t| t.bytes().any(|b| b != b'0')) {
+                return TraceContext {
+                    trace_id: trace,
+                    span_id: c.get(2).map(|m| m.as_str()),
+                };
+            }
+        }
+        for c in TAG_RE.captures_iter(comment) {
+            let slot = if c[1].eq_ignore_ascii_case("trace") {
+                &mut ctx.trace_id
+            } else {
+                &mut ctx.span_id
+            };
+            if slot.is_none() {
+                *slot = c.get(2).map(|m| m.as_str());
+            }
+        }
+    }
+    ctx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sqlcommenter_traceparent() {
+        let sql = "SELECT * FROM t /*action='list',traceparent='00-5bd66ef5095369c7b0d1f8f4bd33716a-c532cb4098ac3dd2-01'*/";
+        assert_eq!(
+            trace_context(sql),
+            TraceContext {
+                trace_id: Some("5bd66ef5095369c7b0d1f8f4bd33716a"),
+                span_id: Some("c532cb4098ac3dd2"),
+            }
+        );
+        // 全零 trace id 忽略
+        let zero = format!(
+            "SELECT 1 /*traceparent='00-{}-c532cb4098ac3dd2-01'*/",
+            "0".repeat(32)
+        );
+        assert_eq!(trace_context(&zero), TraceContext::default());
+    }
+
+    #[test]
+    fn test_tag_conventions() {
+        let ctx = trace_context("/*+ TRACE_ID(4bf92f35) */ SELECT 1 /* span-id: 'a1b2' */");
+        assert_eq!(ctx.trace_id, Some("4bf92f35"));
+        assert_eq!(ctx.span_id, Some("a1b2"));
+        let ctx = trace_context("/* traceid=abc-123 */ UPDATE t SET a = 1");
+        assert_eq!(ctx.trace_id, Some("abc-123"));
+        assert_eq!(ctx.span_id, None);
+    }
+
+    #[test]
+    fn test_only_comments_are_scanned() {
+        assert_eq!(
+            trace_context("SELECT trace_id FROM spans WHERE trace_id = 'x'"),
+            TraceContext::default()
+        );
+        assert_eq!(
+            trace_context("SELECT '/* not closed' FROM t WHERE trace_id = 1"),
+            TraceContext::default()
+        );
+    }
+}
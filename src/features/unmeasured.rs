@@ -0,0 +1,120 @@
+//! `[features.unmeasured]`：无性能指标记录的处理策略。
+//!
+//! DDL、工具类语句与 PARAMS 记录没有 `EXECTIME` / `ROWCOUNT` / `EXEC_ID` 后缀，默认与 DML 一同导出、
+//! 性能指标列为空；按耗时统计时混入这类记录会拉低延迟分布。可选择排除，或写入单独的 CSV 文件。
+use crate::error::{ConfigError, Error, Result};
+use dm_database_parser_sqllog::PerformanceMetrics;
+use serde::Deserialize;
+
+/// `features.unmeasured.policy` 可选值
+pub const UNMEASURED_POLICIES: &[&str] = &["include", "exclude", "separate"];
+
+/// `[features.unmeasured]` 配置段
+#[derive(Debug, Deserialize, Clone)]
+#[non_exhaustive]
+pub struct UnmeasuredConfig {
+    /// `include`（默认，照常导出）/ `exclude`（不导出）/ `separate`（写入 `file`）
+    #[serde(default = "default_policy")]
+    pub policy: String,
+    /// `separate` 时的输出文件（CSV，方言等设置沿用 `[exporter.csv]`）
+    #[serde(default)]
+    pub file: Option<String>,
+}
+
+fn default_policy() -> String {
+    "include".to_string()
+}
+
+impl Default for UnmeasuredConfig {
+    fn default() -> Self {
+        Self {
+            policy: default_policy(),
+            file: None,
+        }
+    }
+}
+
+/// 已校验的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnmeasuredPolicy {
+    #[default]
+    Include,
+    Exclude,
+    Separate,
+}
+
+impl UnmeasuredConfig {
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |field: &str, value: &str, reason: &str| {
+            Err(Error::Config(ConfigError::InvalidValue {
+                field: format!("features.unmeasured.{field}"),
+                value: value.to_string(),
+                reason: reason.to_string(),
+            }))
+        };
+        if !UNMEASURED_POLICIES.contains(&self.policy.as_str()) {
+            return invalid(
+                "policy",
+                &self.policy,
+                &format!("must be one of: {}", UNMEASURED_POLICIES.join(", ")),
+            );
+        }
+        match (self.policy.as_str(), self.file.as_deref()) {
+            ("separate", None) => invalid("file", "", "required when policy = \"separate\""),
+            ("separate", Some(f)) if f.trim().is_empty() => {
+                invalid("file", f, "output file path cannot be empty")
+            }
+            ("separate", Some(f)) if crate::exporter::sink::is_stream(std::path::Path::new(f)) => {
+                invalid("file", f, "must be a local file")
+            }
+            ("include" | "exclude", Some(f)) => {
+                invalid("file", f, "only applies to policy = \"separate\"")
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// 已校验的策略（未知值按 `include` 处理）
+    #[must_use]
+    pub fn policy(&self) -> UnmeasuredPolicy {
+        match self.policy.as_str() {
+            "exclude" => UnmeasuredPolicy::Exclude,
+            "separate" => UnmeasuredPolicy::Separate,
+            _ => UnmeasuredPolicy::Include,
+        }
+    }
+}
+
+/// 记录是否缺少性能指标（与各导出器写空指标列的判定一致）
+#[inline]
+#[must_use]
+pub fn is_unmeasured(pm: &PerformanceMetrics<'_>) -> bool {
+    pm.exec_id == 0 && pm.exectime <= 0.0 && pm.rowcount == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_policy_and_file() {
+        let parse = |s: &str| toml::from_str::<UnmeasuredConfig>(s).unwrap();
+        assert_eq!(parse("").policy(), UnmeasuredPolicy::Include);
+        assert!(parse("policy = \"exclude\"").validate().is_ok());
+        assert!(parse("policy = \"drop\"").validate().is_err());
+        assert!(parse("policy = \"separate\"").validate().is_err());
+        assert!(
+            parse("policy = \"separate\"\nfile = \"-\"")
+                .validate()
+                .is_err()
+        );
+        assert!(
+            parse("policy = \"exclude\"\nfile = \"ddl.csv\"")
+                .validate()
+                .is_err()
+        );
+        let separate = parse("policy = \"separate\"\nfile = \"ddl.csv\"");
+        assert!(separate.validate().is_ok());
+        assert_eq!(separate.policy(), UnmeasuredPolicy::Separate);
+    }
+}
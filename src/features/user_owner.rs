@@ -0,0 +1,235 @@
+//! `[features.user_owner]`：从查找文件加载「数据库用户 → 归属团队 / 应用服务」映射，
+//! 通过 `extra_fields = ["owner_team", "app_service"]` 在导出时关联为派生列，便于按业务归属汇总。
+//!
+//! 查找文件按扩展名识别格式：
+//! - `.csv`：首行为表头，需含 `username`、`owner_team`、`app_service` 列（顺序任意，其余列忽略）
+//! - `.toml`：以用户名为表名，如 `[SYSDBA]\nowner_team = "DBA"\napp_service = "ops"`
+//!
+//! 用户名匹配不区分大小写；未命中的用户两列均为 NULL。
+use crate::error::{ConfigError, Error, FileError, Result};
+use ahash::HashMap as AHashMap;
+use serde::Deserialize;
+use std::path::Path;
+use std::sync::Arc;
+
+/// `[features.user_owner]` 配置段
+#[derive(Debug, Deserialize, Clone, Default)]
+#[non_exhaustive]
+pub struct UserOwnerConfig {
+    /// 查找文件路径（`.csv` 或 `.toml`）
+    #[serde(default)]
+    pub file: String,
+}
+
+/// 单个用户的归属信息（缺失的列为 `None`）
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OwnerEntry {
+    pub owner_team: Option<Arc<str>>,
+    pub app_service: Option<Arc<str>>,
+}
+
+/// TOML 查找文件中的单个用户表
+#[derive(Debug, Deserialize)]
+struct TomlOwner {
+    #[serde(default)]
+    owner_team: Option<String>,
+    #[serde(default)]
+    app_service: Option<String>,
+}
+
+/// 已加载的用户归属表（键为大写用户名）
+#[derive(Debug, Clone, Default)]
+pub struct OwnerTable {
+    users: AHashMap<String, OwnerEntry>,
+}
+
+impl UserOwnerConfig {
+    /// 读取并解析查找文件
+    pub fn load(&self) -> Result<OwnerTable> {
+        if self.file.trim().is_empty() {
+            return Err(Error::Config(ConfigError::InvalidValue {
+                field: "features.user_owner.file".to_string(),
+                value: self.file.clone(),
+                reason: "lookup file path cannot be empty".to_string(),
+            }));
+        }
+        let path = Path::new(&self.file);
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            Error::File(FileError::ReadFailed {
+                path: path.to_path_buf(),
+                reason: e.to_string(),
+            })
+        })?;
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase);
+        let parsed = match ext.as_deref() {
+            Some("csv") => parse_csv(&content),
+            Some("toml") => parse_toml(&content),
+            _ => Err("lookup file must have a .csv or .toml extension".to_string()),
+        };
+        let users = parsed.map_err(|reason| {
+            Error::Config(ConfigError::InvalidValue {
+                field: "features.user_owner.file".to_string(),
+                value: self.file.clone(),
+                reason,
+            })
+        })?;
+        Ok(OwnerTable {
+            users: users
+                .into_iter()
+                .map(|(user, entry)| (user.trim().to_ascii_uppercase(), entry))
+                .collect(),
+        })
+    }
+}
+
+impl OwnerTable {
+    /// 按用户名查找（不区分大小写；全大写用户名不产生额外分配）
+    #[must_use]
+    pub fn lookup(&self, username: &str) -> Option<&OwnerEntry> {
+        if username.bytes().any(|b| b.is_ascii_lowercase()) {
+            self.users.get(&username.to_ascii_uppercase())
+        } else {
+            self.users.get(username)
+        }
+    }
+}
+
+/// 解析带表头的 CSV；字段可用双引号包裹（`""` 转义引号），空值视为缺失
+fn parse_csv(content: &str) -> std::result::Result<AHashMap<String, OwnerEntry>, String> {
+    let mut lines = content
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty());
+    let (_, header) = lines.next().ok_or("lookup file is empty")?;
+    let header = split_csv_line(header.trim_start_matches('\u{feff}'));
+    let column = |name: &str| header.iter().position(|h| h.eq_ignore_ascii_case(name));
+    let user_col = column("username").ok_or("CSV header must contain a 'username' column")?;
+    let team_col = column("owner_team");
+    let app_col = column("app_service");
+    if team_col.is_none() && app_col.is_none() {
+        return Err("CSV header must contain 'owner_team' and/or 'app_service'".to_string());
+    }
+
+    let mut users = AHashMap::default();
+    for (idx, line) in lines {
+        let fields = split_csv_line(line);
+        let get = |col: Option<usize>| {
+            col.and_then(|c| fields.get(c))
+                .filter(|v| !v.is_empty())
+                .map(|v| Arc::from(v.as_str()))
+        };
+        let user = fields
+            .get(user_col)
+            .filter(|u| !u.is_empty())
+            .ok_or_else(|| format!("line {}: missing username", idx + 1))?;
+        users.insert(
+            user.clone(),
+            OwnerEntry {
+                owner_team: get(team_col),
+                app_service: get(app_col),
+            },
+        );
+    }
+    Ok(users)
+}
+
+fn parse_toml(content: &str) -> std::result::Result<AHashMap<String, OwnerEntry>, String> {
+    let raw: AHashMap<String, TomlOwner> =
+        toml::from_str(content).map_err(|e| e.message().to_string())?;
+    Ok(raw
+        .into_iter()
+        .map(|(user, o)| {
+            let entry = OwnerEntry {
+                owner_team: o.owner_team.as_deref().map(Arc::from),
+                app_service: o.app_service.as_deref().map(Arc::from),
+            };
+            (user, entry)
+        })
+        .collect())
+}
+
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut cur = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                cur.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut cur).trim().to_string()),
+            _ => cur.push(c),
+        }
+    }
+    fields.push(cur.trim().to_string());
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(name: &str, content: &str) -> Result<OwnerTable> {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(name);
+        std::fs::write(&path, content).unwrap();
+        UserOwnerConfig {
+            file: path.to_string_lossy().into_owned(),
+        }
+        .load()
+    }
+
+    #[test]
+    fn test_csv_lookup_case_insensitive() {
+        let table = load(
+            "owners.csv",
+            "app_service,username,owner_team,note\n\
+             billing,BILL_APP,\"Finance, Core\",x\n\
+             ,etl_user,Data,\n",
+        )
+        .unwrap();
+        let bill = table.lookup("bill_app").unwrap();
+        assert_eq!(bill.owner_team.as_deref(), Some("Finance, Core"));
+        assert_eq!(bill.app_service.as_deref(), Some("billing"));
+        let etl = table.lookup("ETL_USER").unwrap();
+        assert_eq!(etl.app_service, None);
+        assert!(table.lookup("SYSDBA").is_none());
+    }
+
+    #[test]
+    fn test_toml_lookup() {
+        let table = load(
+            "owners.toml",
+            "[SYSDBA]\nowner_team = \"DBA\"\napp_service = \"ops\"\n[\"report\"]\nowner_team = \"BI\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            table.lookup("SYSDBA").unwrap().app_service.as_deref(),
+            Some("ops")
+        );
+        assert_eq!(
+            table.lookup("REPORT").unwrap().owner_team.as_deref(),
+            Some("BI")
+        );
+    }
+
+    #[test]
+    fn test_bad_files_rejected() {
+        let err = load("owners.csv", "user,team\nA,B\n").unwrap_err();
+        assert!(err.to_string().contains("username"), "{err}");
+        let err = load("owners.txt", "x").unwrap_err();
+        assert!(err.to_string().contains(".csv or .toml"), "{err}");
+        let err = UserOwnerConfig {
+            file: "/nonexistent/owners.csv".into(),
+        }
+        .load()
+        .unwrap_err();
+        assert!(matches!(err, Error::File(FileError::ReadFailed { .. })));
+    }
+}
@@ -84,11 +84,47 @@ pub fn apply_zh(cmd: Command) -> Command {
             a.help("界面语言：zh | en（默认自动检测 LANG 环境变量）")
         })
         .mut_subcommand("run", zh_run)
+        .mut_subcommand("report", |s| {
+            zh_common_config_args(s)
+                .about("运行导出任务后，按 minijinja 模板渲染自定义报告")
+                .mut_arg("from", |a| a.help("只保留此时间戳之后（含）的记录"))
+                .mut_arg("to", |a| a.help("只保留此时间戳之前（含）的记录"))
+                .mut_arg("dry_run", |a| a.help("只解析不写导出文件（统计记录数）"))
+                .mut_arg("jobs", |a| a.help("多文件并行处理线程数（默认 CPU 核数）"))
+                .mut_arg("template", |a| a.help("报告模板文件（minijinja 语法）"))
+                .mut_arg("output", |a| a.help("报告输出文件（默认输出到 stdout）"))
+        })
+        .mut_subcommand("run-all", |s| {
+            s.about("批量执行多个配置文件的导出任务并输出汇总")
+                .mut_arg("configs", |a| {
+                    a.help("配置文件路径或 glob 模式，如 configs/*.toml")
+                })
+                .mut_arg("parallel", |a| {
+                    a.help("最多同时运行 N 个任务（默认 1，顺序执行）")
+                })
+                .mut_arg("jobs", |a| {
+                    a.help("每个任务内部的文件并行线程数（默认 CPU 核数 / 并发数）")
+                })
+                .mut_arg("dry_run", |a| a.help("只解析不写文件（统计记录数）"))
+                .mut_arg("fail_fast", |a| a.help("任一任务失败后不再启动新任务"))
+                .mut_arg("set", |a| a.help("对每个任务覆盖配置字段"))
+        })
         .mut_subcommand("init", zh_init)
         .mut_subcommand("validate", zh_validate)
         .mut_subcommand("show-config", zh_show_config)
+        .mut_subcommand("schema", zh_schema)
+        .mut_subcommand("preview", zh_preview)
         .mut_subcommand("stats", zh_stats)
         .mut_subcommand("digest", zh_digest)
+        .mut_subcommand("diff", |s| {
+            s.about("比较两次运行的摘要清单（digest --json 输出），报告负载变化")
+                .mut_arg("run_a", |a| a.help("基准清单（如升级前）"))
+                .mut_arg("run_b", |a| a.help("待比较的清单"))
+                .mut_arg("slow_ms", |a| {
+                    a.help("最大耗时达到该阈值（毫秒）的指纹视为慢查询")
+                })
+                .mut_arg("top", |a| a.help("指纹 / 慢查询列表最多显示 N 条"))
+        })
         .mut_subcommand("completions", |s| {
             s.about("生成 Shell 自动补全脚本")
                 .mut_arg("shell", |a| a.help("目标 Shell 类型"))
@@ -117,6 +153,9 @@ fn zh_run(s: Command) -> Command {
         .mut_arg("output", |a| {
             a.help("CSV 输出文件（等同于 --set exporter.csv.file=<FILE>）")
         })
+        .mut_arg("stdin", |a| {
+            a.help("从标准输入读取日志（等同于 --set sqllog.path=-）")
+        })
         .mut_arg("progress_interval", |a| {
             a.help("进度条刷新间隔（毫秒，默认 80）")
         })
@@ -142,6 +181,24 @@ fn zh_show_config(s: Command) -> Command {
         .mut_arg("diff", |a| a.help("高亮与默认配置不同的字段"))
 }
 
+fn zh_schema(s: Command) -> Command {
+    zh_common_config_args(s)
+        .about("打印导出器将执行的建表 DDL（供导入前审阅）")
+        .mut_arg("exporter", |a| a.help("目标导出器（默认 sqlite）"))
+}
+
+fn zh_preview(s: Command) -> Command {
+    zh_common_config_args(s)
+        .about("预览前 N 条记录（经过滤、脱敏与派生列处理，不写输出）")
+        .mut_arg("from", |a| a.help("只保留此时间戳之后（含）的记录"))
+        .mut_arg("to", |a| a.help("只保留此时间戳之前（含）的记录"))
+        .mut_arg("limit", |a| a.help("显示的记录条数（默认 20）"))
+        .mut_arg("json", |a| a.help("以 JSON 数组输出（到 stdout）"))
+        .mut_arg("width", |a| {
+            a.help("表格模式下单个值超过 N 个字符时截断（0 表示不截断，默认 120）")
+        })
+}
+
 fn zh_stats(s: Command) -> Command {
     zh_common_config_args(s)
         .about("统计日志记录数（无需导出）")
@@ -153,6 +210,12 @@ fn zh_stats(s: Command) -> Command {
             a.help("按字段聚合统计：user、app、ip（可叠加，逗号分隔）")
         })
         .mut_arg("bucket", |a| a.help("按时间粒度分桶统计：hour 或 minute"))
+        .mut_arg("capacity", |a| {
+            a.help("采样导出各输出格式，预估输出体积与写入吞吐（容量规划）")
+        })
+        .mut_arg("sample", |a| {
+            a.help("--capacity 每种格式采样的记录数（默认 10000）")
+        })
 }
 
 fn zh_digest(s: Command) -> Command {
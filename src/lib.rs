@@ -1,3 +1,5 @@
+pub mod api;
+pub use api::{ExportOptions, ExportReport, run_export, run_export_with};
 pub mod charts;
 pub mod cli;
 pub mod color;
@@ -8,5 +10,13 @@ pub mod features;
 pub use exporter::*;
 pub mod lang;
 pub mod logging;
+pub mod metrics;
+pub mod notify;
 pub mod parser;
+pub mod paths;
 pub mod resume;
+pub mod run_id;
+pub mod run_report;
+pub mod temp;
+pub mod testing;
+pub mod warnings;
@@ -101,9 +101,10 @@ pub fn init_logging(config: &LoggingConfig, log_to_stdout: bool) -> Result<()> {
             }
             let now = Local::now().format("%Y-%m-%d %H:%M:%S");
             let msg = format!(
-                "[{}][{}] {} - {}\n",
+                "[{}][{}][run={}] {} - {}\n",
                 now,
                 record.level(),
+                crate::run_id::current(),
                 record.target(),
                 record.args()
             );
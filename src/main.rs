@@ -12,8 +12,18 @@ mod exporter;
 mod features;
 mod lang;
 mod logging;
+mod metrics;
+mod notify;
 mod parser;
+mod paths;
 mod resume;
+mod run_id;
+mod run_report;
+mod temp;
+// 库用法的测试夹具；二进制目标只有 `bench` 用到合成日志文本
+#[allow(dead_code)]
+mod testing;
+mod warnings;
 
 use config::Config;
 use error::Result;
@@ -28,18 +38,24 @@ use std::sync::atomic::{AtomicBool, Ordering};
 // 2  = 配置错误
 // 3  = 输入/文件/解析错误
 // 4  = 导出错误
+// 5  = 解析错误率超过 `error.max_parse_error_rate`（运行本身已完成）
 // 130 = 被用户中断（Ctrl+C），遵循 Unix 128+SIGINT(2) 惯例
+// 141 = 输出到 stdout 时下游关闭管道，遵循 128+SIGPIPE(13) 惯例
 const EXIT_CONFIG: i32 = 2;
 const EXIT_IO: i32 = 3;
 const EXIT_EXPORT: i32 = 4;
+const EXIT_ERROR_BUDGET: i32 = 5;
 const EXIT_INTERRUPTED: i32 = 130;
+const EXIT_PIPE: i32 = 141;
 
 fn exit_code_for(e: &error::Error) -> i32 {
     match e {
         error::Error::Config(_) => EXIT_CONFIG,
         error::Error::File(_) | error::Error::Parser(_) | error::Error::Io(_) => EXIT_IO,
+        error::Error::Export(error::ExportError::OutputClosed) => EXIT_PIPE,
         error::Error::Export(_) => EXIT_EXPORT,
         error::Error::Interrupted => EXIT_INTERRUPTED,
+        error::Error::BudgetExceeded { .. } => EXIT_ERROR_BUDGET,
         error::Error::Update(_) => 1,
     }
 }
@@ -90,12 +106,15 @@ fn apply_date_range(cfg: &mut Config, from: Option<&str>, to: Option<&str>) {
 }
 
 fn main() {
-    match run() {
+    let result = run();
+    parser::input::remove_stdin_spool();
+    match result {
         Ok(()) => {}
         Err(e) => {
             let code = exit_code_for(&e);
-            // Interrupted：静默退出，进度条已清除，用户清楚自己按了 Ctrl+C
-            if code != EXIT_INTERRUPTED {
+            // Interrupted：静默退出，进度条已清除，用户清楚自己按了 Ctrl+C；
+            // 下游关闭管道（如 `| head`）同样是正常的管道用法，静默退出
+            if code != EXIT_INTERRUPTED && code != EXIT_PIPE {
                 eprintln!("{} {e}", color::red("Error:"));
             }
             std::process::exit(code);
@@ -123,13 +142,18 @@ fn run() -> Result<()> {
     // 尽早初始化颜色开关，后续所有输出均依赖此状态
     color::init(cli.no_color);
 
-    // run/stats/digest 命令不走 env_logger，避免与进度条冲突；其他命令用 env_logger 输出到终端
+    // run/stats/digest/preview/schema 命令不走 env_logger，避免与进度条冲突；其他命令用 env_logger 输出到终端
     let needs_simple_logging = !matches!(
         &cli.command,
         Some(
             cli::opts::Commands::Run { .. }
+                | cli::opts::Commands::RunAll { .. }
+                | cli::opts::Commands::Report { .. }
                 | cli::opts::Commands::Stats { .. }
                 | cli::opts::Commands::Digest { .. }
+                | cli::opts::Commands::Preview { .. }
+                | cli::opts::Commands::Schema { .. }
+                | cli::opts::Commands::Bench { .. }
         )
     );
     if needs_simple_logging {
@@ -165,43 +189,111 @@ fn run() -> Result<()> {
         Some(cli::opts::Commands::Run {
             config,
             limit,
+            max_duration,
             dry_run,
             set,
             from,
             to,
             output,
+            stdin,
             progress_interval,
             resume,
             state_file,
             jobs,
+            profile_stages,
+            metrics_file,
+            report_junit,
+            junit_max_errors,
+            migrate,
+            auto_tune,
+            watch,
+            watch_interval,
         }) => {
-            let mut cfg = load_config(config)?;
-            // --output is a shorthand applied before --set so --set can override
+            // --output / --stdin / --migrate are shorthands applied before --set so --set can override
             let mut all_set = Vec::new();
             if let Some(out) = output {
                 all_set.push(format!("exporter.csv.file={out}"));
             }
+            if *stdin {
+                all_set.push(format!("sqllog.path={}", parser::input::STDIN_PATH));
+            }
+            if *migrate {
+                all_set.push("exporter.sqlite.migrate=true".to_string());
+            }
             all_set.extend_from_slice(set);
-            cfg.apply_overrides(&all_set)?;
-            apply_date_range(&mut cfg, from.as_deref(), to.as_deref());
-            // 替换：validate() → validate_and_compile()，消除 run 路径中的双重 regex 编译（SC-2）
-            let compiled_filters = cfg.validate_and_compile()?;
-
-            apply_cli_flags_to_config(&mut cfg, cli.verbose, cli.quiet);
-            // run 命令使用进度条，日志只写文件不写 stdout
-            logging::init_logging(&cfg.logging, false)?;
-            info!("Application started");
-            info!("Configuration validation passed");
-
-            // preflight：日志目录 + 输出可写性
-            if !*dry_run {
-                let pf = cli::preflight::check(&cfg);
-                if pf.print_and_check() {
-                    std::process::exit(EXIT_CONFIG);
+            execute_run(
+                &cli,
+                config,
+                &all_set,
+                from.as_deref(),
+                to.as_deref(),
+                *limit,
+                *max_duration,
+                *profile_stages,
+                *dry_run,
+                *progress_interval,
+                *resume,
+                state_file.as_deref(),
+                *jobs,
+                *auto_tune,
+                watch.then_some(*watch_interval),
+            )
+            .and_then(|(_, summary)| {
+                if let Some(path) = metrics_file {
+                    summary.metrics.write_prometheus(Path::new(path))?;
                 }
-            }
+                match report_junit {
+                    Some(path) => {
+                        run_report::write_junit(Path::new(path), &summary, *junit_max_errors)
+                    }
+                    None => Ok(()),
+                }
+            })
+        }
+        Some(cli::opts::Commands::Report {
+            config,
+            set,
+            from,
+            to,
+            dry_run,
+            jobs,
+            template,
+            output,
+        }) => {
+            let (cfg, summary) = execute_run(
+                &cli,
+                config,
+                set,
+                from.as_deref(),
+                to.as_deref(),
+                None,
+                None,
+                false,
+                *dry_run,
+                80,
+                false,
+                None,
+                *jobs,
+                false,
+                None,
+            )?;
+            cli::report::handle_report(&cfg, config, template, output.as_deref(), &summary)
+        }
+        Some(cli::opts::Commands::RunAll {
+            configs,
+            parallel,
+            jobs,
+            dry_run,
+            fail_fast,
+            set,
+        }) => {
+            let paths = cli::run_all::expand_config_paths(configs)?;
+            // 全局日志只能初始化一次：使用第一个配置文件的 [logging] 段
+            let mut log_cfg = load_config(&paths[0].to_string_lossy())?;
+            apply_cli_flags_to_config(&mut log_cfg, cli.verbose, cli.quiet);
+            logging::init_logging(&log_cfg.logging, false)?;
+            info!("Application started (run-all, {} jobs)", paths.len());
 
-            // 注册 Ctrl+C 处理器：设置中断标志，让处理循环在下一个 batch 结束时优雅退出
             let interrupted = Arc::new(AtomicBool::new(false));
             let interrupted_flag = Arc::clone(&interrupted);
             ctrlc::set_handler(move || {
@@ -209,23 +301,33 @@ fn run() -> Result<()> {
             })
             .ok();
 
+            let parallel = (*parallel).max(1);
+            // --jobs 等同于对每个任务 --set pipeline.workers=N
+            let mut set = set.clone();
+            if let Some(j) = jobs {
+                set.push(format!("pipeline.workers={j}"));
+            }
             let jobs = jobs.unwrap_or_else(|| {
-                std::thread::available_parallelism().map_or(1, std::num::NonZero::get)
+                std::thread::available_parallelism()
+                    .map_or(1, std::num::NonZero::get)
+                    .div_ceil(parallel)
             });
-            cli::run::handle_run(
-                &cfg,
-                *limit,
+            cli::run_all::handle_run_all(
+                &paths,
+                &set,
+                parallel,
+                jobs,
                 *dry_run,
                 cli.quiet,
+                *fail_fast,
                 &interrupted,
-                *progress_interval,
-                *resume,
-                state_file.as_deref(),
-                jobs,
-                compiled_filters, // 新增：传递预编译结果
             )
         }
-        Some(cli::opts::Commands::Validate { config, set }) => {
+        Some(cli::opts::Commands::Validate {
+            config,
+            set,
+            check_connectivity,
+        }) => {
             let mut cfg = load_config(config)?;
             cfg.apply_overrides(set)?;
             cfg.validate()?;
@@ -237,6 +339,11 @@ fn run() -> Result<()> {
             info!("Configuration validation passed");
 
             cli::validate::handle_validate(&cfg);
+            // 深度检查：日志路径、输出可写性，以及（--check-connectivity）导出目标连通性
+            if cli::validate::probe(&cfg, *check_connectivity).print_and_check() {
+                std::process::exit(EXIT_CONFIG);
+            }
+            info!("Target checks passed");
             Ok(())
         }
         Some(cli::opts::Commands::ShowConfig { config, set, diff }) => {
@@ -245,6 +352,115 @@ fn run() -> Result<()> {
             cli::show_config::handle_show_config(&cfg, config, *diff);
             Ok(())
         }
+        Some(cli::opts::Commands::CleanTemp {
+            config,
+            set,
+            dirs,
+            older_than,
+            dry_run,
+        }) => {
+            let mut cfg = load_config(config)?;
+            cfg.apply_overrides(set)?;
+            cli::clean_temp::handle_clean_temp(
+                &cli::clean_temp::scan_dirs(&cfg, dirs),
+                older_than.unwrap_or(cli::clean_temp::DEFAULT_OLDER_THAN),
+                *dry_run,
+            )
+            .map(|_| ())
+        }
+        Some(cli::opts::Commands::Doctor {
+            config,
+            set,
+            check_dm_config,
+        }) => {
+            let mut cfg = load_config(config)?;
+            cfg.apply_overrides(set)?;
+            let path = Path::new(check_dm_config);
+            let result = cli::doctor::check_dm_config(&cfg, path)?;
+            if cli::doctor::print_result(&result, path) {
+                std::process::exit(EXIT_CONFIG);
+            }
+            Ok(())
+        }
+        Some(cli::opts::Commands::Sample {
+            config,
+            set,
+            records,
+            output,
+            anonymize,
+        }) => {
+            let mut cfg = load_config(config)?;
+            cfg.apply_overrides(set)?;
+            cli::sample::handle_sample(&cfg, *records, output.as_deref(), *anonymize).map(|_| ())
+        }
+        Some(cli::opts::Commands::Top {
+            target,
+            by,
+            group,
+            table,
+            limit,
+            json,
+        }) => cli::top::handle_top(target, by, group, table.as_deref(), *limit, *json),
+        Some(cli::opts::Commands::Features { config, json }) => {
+            cli::features::handle_features(config.as_deref(), *json)
+        }
+        Some(cli::opts::Commands::Diff {
+            run_a,
+            run_b,
+            slow_ms,
+            top,
+        }) => cli::diff::handle_diff(run_a, run_b, *slow_ms, *top),
+        Some(cli::opts::Commands::Schema {
+            config,
+            set,
+            exporter,
+        }) => {
+            let mut cfg = load_config(config)?;
+            cfg.apply_overrides(set)?;
+            cfg.validate()?;
+            cli::schema::handle_schema(&cfg, exporter.as_deref())
+        }
+        Some(cli::opts::Commands::Bench {
+            config,
+            set,
+            compare_paths,
+            records,
+            json,
+        }) => {
+            let mut cfg = load_config(config)?;
+            cfg.apply_overrides(set)?;
+            cfg.validate()?;
+            cli::bench::handle_bench(&cfg, *records, *compare_paths, *json)
+        }
+        Some(cli::opts::Commands::Preview {
+            config,
+            set,
+            from,
+            to,
+            limit,
+            json,
+            width,
+        }) => {
+            let mut cfg = load_config(config)?;
+            cfg.apply_overrides(set)?;
+            apply_date_range(&mut cfg, from.as_deref(), to.as_deref());
+            let compiled_filters = cfg.validate_and_compile()?;
+            let interrupted = Arc::new(AtomicBool::new(false));
+            let interrupted_flag = Arc::clone(&interrupted);
+            ctrlc::set_handler(move || {
+                interrupted_flag.store(true, Ordering::Relaxed);
+            })
+            .ok();
+            cli::preview::handle_preview(
+                &cfg,
+                *limit,
+                *json,
+                *width,
+                cli.quiet,
+                &interrupted,
+                compiled_filters,
+            )
+        }
         Some(cli::opts::Commands::Stats {
             config,
             set,
@@ -256,6 +472,8 @@ fn run() -> Result<()> {
             bucket,
             resume,
             state_file,
+            capacity,
+            sample,
         }) => {
             let mut cfg = load_config(config)?;
             cfg.apply_overrides(set)?;
@@ -278,6 +496,7 @@ fn run() -> Result<()> {
                 group_by,
                 bucket.as_deref(),
                 resume_state_file,
+                capacity.then_some(*sample),
             );
             Ok(())
         }
@@ -331,6 +550,141 @@ fn run() -> Result<()> {
     }
 }
 
+/// run / report 共用流程：加载配置 → 覆盖 → 校验编译 → 初始化日志 → preflight → 注册 Ctrl+C
+/// → （`--auto-tune`）样本试跑调参 → 导出
+#[allow(clippy::fn_params_excessive_bools)]
+fn execute_run(
+    cli: &cli::opts::Cli,
+    config: &str,
+    set: &[String],
+    from: Option<&str>,
+    to: Option<&str>,
+    limit: Option<usize>,
+    max_duration: Option<std::time::Duration>,
+    profile_stages: bool,
+    dry_run: bool,
+    progress_interval: u64,
+    resume: bool,
+    state_file: Option<&str>,
+    jobs: Option<usize>,
+    auto_tune: bool,
+    watch: Option<std::time::Duration>,
+) -> Result<(Config, cli::run::RunSummary)> {
+    let started = chrono::Local::now();
+    let mut cfg = load_config(config)?;
+    cfg.apply_overrides(set)?;
+    apply_date_range(&mut cfg, from, to);
+    // 替换：validate() → validate_and_compile()，消除 run 路径中的双重 regex 编译（SC-2）
+    let compiled_filters = cfg.validate_and_compile()?;
+
+    apply_cli_flags_to_config(&mut cfg, cli.verbose, cli.quiet);
+    // run 命令使用进度条，日志只写文件不写 stdout
+    logging::init_logging(&cfg.logging, false)?;
+    info!("Application started");
+    info!("Configuration validation passed");
+
+    // 运行开始时固定文件清单：预检、--auto-tune 与正式运行看到同一批文件（--watch 每轮重新扫描）；
+    // 扫描失败时不设快照，由后续步骤照常报错
+    if watch.is_none() {
+        if let Ok(listing) = parser::ListingSnapshot::take(&cfg.sqllog) {
+            cfg.sqllog.listing = Some(Arc::new(listing));
+        }
+    }
+
+    // preflight：日志目录 + 输出可写性
+    if !dry_run {
+        let pf = cli::preflight::check(&cfg);
+        if pf.print_and_check() {
+            std::process::exit(EXIT_CONFIG);
+        }
+    }
+
+    // 注册 Ctrl+C 处理器：设置中断标志，让处理循环在下一个 batch 结束时优雅退出
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let interrupted_flag = Arc::clone(&interrupted);
+    ctrlc::set_handler(move || {
+        interrupted_flag.store(true, Ordering::Relaxed);
+    })
+    .ok();
+
+    // --jobs 等同于显式设置 pipeline.workers（非 CSV 导出器只在显式设置时走并发队列）
+    if jobs.is_some() {
+        cfg.pipeline.workers = jobs;
+    }
+    let mut jobs = cfg
+        .pipeline
+        .workers
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, std::num::NonZero::get));
+    // --auto-tune：先在输入样本上试跑，选出的参数用于全量运行
+    let tuning = if auto_tune {
+        if !cli.quiet {
+            eprintln!(
+                "{} Auto-tuning on a sample of the input...",
+                color::cyan("▶")
+            );
+        }
+        cli::tune::auto_tune(&cfg, jobs, &interrupted)?
+    } else {
+        None
+    };
+    if let Some(t) = &tuning {
+        t.apply(&mut cfg);
+        // 与完成摘要打印的等价命令行（--jobs N）一致
+        cfg.pipeline.workers = Some(t.jobs);
+        jobs = t.jobs;
+    }
+    let result = if let Some(interval) = watch {
+        cli::watch::watch(
+            &cfg,
+            interval,
+            max_duration,
+            cli.quiet,
+            &interrupted,
+            progress_interval,
+            jobs,
+        )
+    } else {
+        cli::run::handle_run_until(
+            &cfg,
+            limit,
+            max_duration,
+            profile_stages,
+            dry_run,
+            cli.quiet,
+            &interrupted,
+            progress_interval,
+            resume,
+            state_file,
+            jobs,
+            compiled_filters, // 新增：传递预编译结果
+        )
+    };
+    // 错误预算：超限的运行按失败处理（通知与运行报告同样记为失败）
+    let result = result.and_then(|mut s| {
+        s.tuning.clone_from(&tuning);
+        cfg.error.check(&s.metrics)?;
+        Ok(s)
+    });
+    if let Some(notify) = &cfg.notify {
+        notify::notify_run(notify, config, &cfg.sqllog.path, &result);
+    }
+    if let Some(report) = &cfg.report {
+        run_report::report_run(report, config, &cfg.sqllog.path, started, &result);
+    }
+    let summary = result?;
+    if let Some(t) = &tuning {
+        if !cli.quiet {
+            eprintln!(
+                "  auto-tune: {} ({} trials on {} sample records)",
+                color::cyan(t.reproduce_args()),
+                t.trials.len(),
+                t.sample_records
+            );
+        }
+    }
+    Ok((cfg, summary))
+}
+
 fn load_config(config_path: &str) -> Result<Config> {
     let path = Path::new(config_path);
     match Config::from_file(path) {
@@ -342,7 +696,7 @@ fn load_config(config_path: &str) -> Result<Config> {
             if let error::Error::Config(error::ConfigError::NotFound(_)) = &e {
                 warn!("Configuration file not found: {config_path}, using default configuration");
                 info!("Tip: run 'sqllog2db init' to generate a configuration file");
-                Ok(Config::default())
+                Config::from_env()
             } else {
                 Err(e)
             }
@@ -395,6 +749,17 @@ mod tests {
     #[test]
     fn test_exit_code_interrupted() {
         assert_eq!(exit_code_for(&error::Error::Interrupted), EXIT_INTERRUPTED);
+        assert_eq!(
+            exit_code_for(&error::Error::Export(ExportError::OutputClosed)),
+            EXIT_PIPE
+        );
+        let budget = error::Error::BudgetExceeded {
+            errors: 4,
+            parsed: 10,
+            rate: 40.0,
+            max: 5.0,
+        };
+        assert_eq!(exit_code_for(&budget), EXIT_ERROR_BUDGET);
     }
 
     #[test]
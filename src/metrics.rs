@@ -0,0 +1,334 @@
+//! 运行指标登记表：进度条、完成摘要、导出统计日志与 Prometheus 文本输出读取同一组原子计数器，
+//! 各处显示的计数始终一致。
+//!
+//! 并行任务共享同一个 [`Metrics`]，无需在结束时再汇总；[`Metrics::snapshot`] 取某一时刻的只读副本。
+use crate::error::{Error, FileError, Result};
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+/// 计数器
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Counter {
+    /// 处理完成的日志文件数
+    Files,
+    /// 解析出的记录数（含被过滤、出错的记录）
+    Parsed,
+    /// 导出（或 dry-run 统计）的记录数
+    Exported,
+    /// 解析失败或因校验和不符被拒绝的记录数
+    Errors,
+    /// 经容错修复的记录数
+    Recovered,
+    /// 溢出或带杂散字符、经容错修正的 `EXEC_ID` 数
+    ExecIdCoerced,
+}
+
+impl Counter {
+    const ALL: [Self; 6] = [
+        Self::Files,
+        Self::Parsed,
+        Self::Exported,
+        Self::Errors,
+        Self::Recovered,
+        Self::ExecIdCoerced,
+    ];
+
+    /// Prometheus 指标名与说明
+    fn prometheus(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Files => ("sqllog2db_files_processed_total", "Log files processed"),
+            Self::Parsed => ("sqllog2db_records_parsed_total", "Records parsed"),
+            Self::Exported => ("sqllog2db_records_exported_total", "Records exported"),
+            Self::Errors => (
+                "sqllog2db_records_errors_total",
+                "Records rejected with errors",
+            ),
+            Self::Recovered => (
+                "sqllog2db_records_recovered_total",
+                "Records repaired by lenient parsing",
+            ),
+            Self::ExecIdCoerced => (
+                "sqllog2db_exec_id_coerced_total",
+                "EXEC_ID values saturated or cleaned by lenient parsing",
+            ),
+        }
+    }
+}
+
+/// 单个文件的处理计数（运行报告的逐文件明细）
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FileCounts {
+    /// 文件在本次运行清单中的序号（从 1 开始），并行处理时据此恢复清单顺序
+    #[serde(skip)]
+    pub index: usize,
+    pub path: String,
+    pub records: u64,
+    pub parsed: u64,
+    pub errors: u64,
+    pub recovered: u64,
+    pub elapsed_ms: u64,
+}
+
+/// 样例原文保留的最大字符数
+const EXAMPLE_MAX_CHARS: usize = 200;
+
+/// 一类解析错误：次数与首次出现的样例
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ErrorSample {
+    /// 错误类别，如 `invalid record start line`
+    pub kind: String,
+    pub count: u64,
+    /// 样例所在文件
+    pub file: String,
+    /// 样例原文的首行（截断到 200 个字符）
+    pub example: String,
+}
+
+/// 按类别汇总的解析错误；处理循环先在文件内累计，文件结束时并入 [`Metrics`]，避免逐条加锁
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ErrorSamples(Vec<ErrorSample>);
+
+impl ErrorSamples {
+    pub fn add(&mut self, kind: &str, file: &str, raw: &str) {
+        if let Some(s) = self.0.iter_mut().find(|s| s.kind == kind) {
+            s.count += 1;
+            return;
+        }
+        let line = raw.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+        let mut example: String = line.chars().take(EXAMPLE_MAX_CHARS).collect();
+        if example.len() < line.len() {
+            example.push('…');
+        }
+        self.0.push(ErrorSample {
+            kind: kind.to_string(),
+            count: 1,
+            file: file.to_string(),
+            example,
+        });
+    }
+
+    /// 合并另一份汇总：次数累加，样例保留先出现的
+    pub fn merge(&mut self, other: Vec<ErrorSample>) {
+        for o in other {
+            match self.0.iter_mut().find(|s| s.kind == o.kind) {
+                Some(s) => s.count += o.count,
+                None => self.0.push(o),
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// 按次数降序（同次数按类别名）
+    #[must_use]
+    pub fn into_sorted(mut self) -> Vec<ErrorSample> {
+        self.0
+            .sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.kind.cmp(&b.kind)));
+        self.0
+    }
+}
+
+/// 线程安全的计数器登记表
+#[derive(Debug, Default)]
+pub struct Metrics {
+    counters: [AtomicU64; 6],
+    files: Mutex<Vec<FileCounts>>,
+    parse_errors: Mutex<ErrorSamples>,
+}
+
+impl Metrics {
+    /// 累加计数，返回累加后的值
+    #[inline]
+    pub fn add(&self, counter: Counter, n: u64) -> u64 {
+        self.counters[counter as usize].fetch_add(n, Ordering::Relaxed) + n
+    }
+
+    #[must_use]
+    pub fn get(&self, counter: Counter) -> u64 {
+        self.counters[counter as usize].load(Ordering::Relaxed)
+    }
+
+    /// 登记一个处理完的文件
+    pub fn record_file(&self, counts: FileCounts) {
+        self.files
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(counts);
+    }
+
+    /// 已登记的文件明细，按运行清单顺序
+    #[must_use]
+    pub fn file_counts(&self) -> Vec<FileCounts> {
+        let mut files = self
+            .files
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        files.sort_by_key(|f| f.index);
+        files
+    }
+
+    /// 并入一个文件的解析错误汇总
+    pub fn record_parse_errors(&self, samples: ErrorSamples) {
+        if samples.is_empty() {
+            return;
+        }
+        self.parse_errors
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .merge(samples.0);
+    }
+
+    /// 各类解析错误，按次数降序
+    #[must_use]
+    pub fn parse_errors(&self) -> Vec<ErrorSample> {
+        self.parse_errors
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+            .into_sorted()
+    }
+
+    #[must_use]
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            files: self.get(Counter::Files),
+            parsed: self.get(Counter::Parsed),
+            exported: self.get(Counter::Exported),
+            errors: self.get(Counter::Errors),
+            recovered: self.get(Counter::Recovered),
+            exec_id_coerced: self.get(Counter::ExecIdCoerced),
+        }
+    }
+}
+
+/// 某一时刻的计数快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct MetricsSnapshot {
+    pub files: u64,
+    pub parsed: u64,
+    pub exported: u64,
+    pub errors: u64,
+    pub recovered: u64,
+    pub exec_id_coerced: u64,
+}
+
+impl MetricsSnapshot {
+    #[must_use]
+    pub fn get(&self, counter: Counter) -> u64 {
+        match counter {
+            Counter::Files => self.files,
+            Counter::Parsed => self.parsed,
+            Counter::Exported => self.exported,
+            Counter::Errors => self.errors,
+            Counter::Recovered => self.recovered,
+            Counter::ExecIdCoerced => self.exec_id_coerced,
+        }
+    }
+
+    /// Prometheus 文本格式（exposition format 0.0.4）
+    #[must_use]
+    pub fn render_prometheus(&self) -> String {
+        let mut out = String::new();
+        for counter in Counter::ALL {
+            let (name, help) = counter.prometheus();
+            let _ = writeln!(
+                out,
+                "# HELP {name} {help}\n# TYPE {name} counter\n{name} {}",
+                self.get(counter)
+            );
+        }
+        out
+    }
+
+    /// 写出 Prometheus 文本文件（供 `node_exporter` textfile collector 采集）；
+    /// 原子写出，避免采集到写了一半的内容
+    pub fn write_prometheus(&self, path: &Path) -> Result<()> {
+        crate::temp::write_atomic(path, self.render_prometheus().as_bytes()).map_err(|e| {
+            Error::File(FileError::WriteFailed {
+                path: path.to_path_buf(),
+                reason: e.to_string(),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concurrent_adds_and_snapshot() {
+        let metrics = Metrics::default();
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..1000 {
+                        metrics.add(Counter::Exported, 2);
+                    }
+                    metrics.add(Counter::Files, 1);
+                });
+            }
+        });
+        assert_eq!(metrics.add(Counter::Errors, 3), 3);
+        let snap = metrics.snapshot();
+        assert_eq!(snap.exported, 8000);
+        assert_eq!(snap.files, 4);
+        assert_eq!(snap.errors, 3);
+        assert_eq!(snap.get(Counter::Parsed), 0);
+
+        for index in [2, 1] {
+            metrics.record_file(FileCounts {
+                index,
+                ..FileCounts::default()
+            });
+        }
+        let order: Vec<_> = metrics.file_counts().iter().map(|f| f.index).collect();
+        assert_eq!(order, [1, 2]);
+
+        let mut a = ErrorSamples::default();
+        a.add("invalid format", "a.log", "\nfirst line\nsecond");
+        let mut b = ErrorSamples::default();
+        b.add("bad integer in EP", "b.log", &"x".repeat(300));
+        b.add("invalid format", "b.log", "other");
+        b.add("invalid format", "b.log", "other");
+        metrics.record_parse_errors(a);
+        metrics.record_parse_errors(b);
+        let errors = metrics.parse_errors();
+        assert_eq!(
+            (
+                errors[0].kind.as_str(),
+                errors[0].count,
+                errors[0].file.as_str()
+            ),
+            ("invalid format", 3, "a.log")
+        );
+        assert_eq!(errors[0].example, "first line");
+        assert_eq!(errors[1].example.chars().count(), 201);
+    }
+
+    #[test]
+    fn test_prometheus_textfile() {
+        let snap = MetricsSnapshot {
+            exported: 42,
+            ..MetricsSnapshot::default()
+        };
+        let text = snap.render_prometheus();
+        assert!(text.contains(
+            "# TYPE sqllog2db_records_exported_total counter\nsqllog2db_records_exported_total 42\n"
+        ));
+        assert_eq!(text.lines().count(), 18);
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("sqllog2db.prom");
+        snap.write_prometheus(&path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), text);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
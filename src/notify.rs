@@ -0,0 +1,676 @@
+//! `[notify]`：运行结束后的通知钩子（webhook / SMTP 邮件 / shell 命令）。
+//!
+//! 成功与失败分别配置（`[notify.on_success]` / `[notify.on_failure]`）。通知失败只输出警告，
+//! 不改变运行本身的退出码。
+use crate::cli::run::RunSummary;
+use crate::color;
+use crate::error::{ConfigError, Error, Result};
+use crate::warnings::Warning;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::io::Write as _;
+use std::time::{Duration, Instant};
+
+/// `[notify]` 配置段
+#[derive(Debug, Deserialize, Clone)]
+#[non_exhaustive]
+pub struct NotifyConfig {
+    #[serde(default)]
+    pub on_success: Option<NotifyTarget>,
+    /// 失败与被中断（Ctrl+C）时触发
+    #[serde(default)]
+    pub on_failure: Option<NotifyTarget>,
+    /// 每个通知动作的超时秒数
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_timeout_secs() -> u64 {
+    10
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        Self {
+            on_success: None,
+            on_failure: None,
+            timeout_secs: default_timeout_secs(),
+        }
+    }
+}
+
+/// 一组通知动作；可同时配置多种
+#[derive(Debug, Deserialize, Clone, Default)]
+#[non_exhaustive]
+pub struct NotifyTarget {
+    /// POST JSON payload 到该 URL
+    #[serde(default)]
+    pub webhook: Option<String>,
+    /// 通过 `sh -c`（Windows 为 `cmd /C`）执行；payload JSON 写入 stdin，关键字段同时以环境变量传入
+    #[serde(default)]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub email: Option<EmailTarget>,
+}
+
+/// SMTP 邮件目标
+#[derive(Debug, Deserialize, Clone)]
+#[non_exhaustive]
+pub struct EmailTarget {
+    #[serde(default)]
+    pub smtp_host: String,
+    /// 默认按 `tls` 取 587（starttls）/ 465（tls）/ 25（none）
+    #[serde(default)]
+    pub smtp_port: Option<u16>,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// 从该环境变量读取密码（优先于 `password`）
+    #[serde(default)]
+    pub password_env: Option<String>,
+    #[serde(default)]
+    pub from: String,
+    #[serde(default)]
+    pub to: Vec<String>,
+    /// `starttls`（默认）/ `tls` / `none`
+    #[serde(default = "default_tls")]
+    pub tls: String,
+}
+
+fn default_tls() -> String {
+    "starttls".to_string()
+}
+
+const TLS_MODES: &[&str] = &["starttls", "tls", "none"];
+
+fn invalid(field: &str, value: &str, reason: impl Into<String>) -> Error {
+    Error::Config(ConfigError::InvalidValue {
+        field: field.to_string(),
+        value: value.to_string(),
+        reason: reason.into(),
+    })
+}
+
+impl NotifyConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.timeout_secs == 0 {
+            return Err(invalid(
+                "notify.timeout_secs",
+                "0",
+                "timeout_secs must be greater than 0",
+            ));
+        }
+        for (key, target) in [
+            ("on_success", &self.on_success),
+            ("on_failure", &self.on_failure),
+        ] {
+            if let Some(t) = target {
+                t.validate(key)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl NotifyTarget {
+    fn validate(&self, key: &str) -> Result<()> {
+        if let Some(url) = &self.webhook {
+            if !(url.starts_with("http://") || url.starts_with("https://")) {
+                return Err(invalid(
+                    &format!("notify.{key}.webhook"),
+                    url,
+                    "webhook must be an http:// or https:// URL",
+                ));
+            }
+        }
+        if let Some(cmd) = &self.command {
+            if cmd.trim().is_empty() {
+                return Err(invalid(
+                    &format!("notify.{key}.command"),
+                    cmd,
+                    "command cannot be empty",
+                ));
+            }
+        }
+        if let Some(email) = &self.email {
+            email.validate(key)?;
+        }
+        Ok(())
+    }
+}
+
+impl EmailTarget {
+    fn validate(&self, key: &str) -> Result<()> {
+        let field = |f: &str| format!("notify.{key}.email.{f}");
+        if self.smtp_host.trim().is_empty() {
+            return Err(invalid(
+                &field("smtp_host"),
+                &self.smtp_host,
+                "smtp_host cannot be empty",
+            ));
+        }
+        if !TLS_MODES.contains(&self.tls.as_str()) {
+            return Err(invalid(
+                &field("tls"),
+                &self.tls,
+                format!("valid values: {}", TLS_MODES.join(", ")),
+            ));
+        }
+        self.from
+            .parse::<lettre::message::Mailbox>()
+            .map_err(|e| invalid(&field("from"), &self.from, e.to_string()))?;
+        if self.to.is_empty() {
+            return Err(invalid(
+                &field("to"),
+                "",
+                "at least one recipient is required",
+            ));
+        }
+        for to in &self.to {
+            to.parse::<lettre::message::Mailbox>()
+                .map_err(|e| invalid(&field("to"), to, e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    fn port(&self) -> u16 {
+        self.smtp_port.unwrap_or(match self.tls.as_str() {
+            "tls" => 465,
+            "none" => 25,
+            _ => 587,
+        })
+    }
+
+    fn password(&self) -> Option<String> {
+        self.password_env
+            .as_deref()
+            .and_then(|k| std::env::var(k).ok())
+            .or_else(|| self.password.clone())
+    }
+}
+
+/// 通知 payload（webhook body / command stdin 的 JSON）
+#[derive(Debug, Serialize)]
+pub struct NotifyPayload<'a> {
+    /// `success` / `failure` / `interrupted`
+    pub status: &'static str,
+    pub version: &'static str,
+    pub run_id: String,
+    pub config_path: &'a str,
+    pub sqllog_path: &'a str,
+    pub files: usize,
+    pub skipped_files: usize,
+    pub records: usize,
+    pub elapsed_secs: f64,
+    pub dry_run: bool,
+    /// 运行中登记的非致命警告（见 `crate::warnings`）
+    pub warnings: Vec<Warning>,
+    pub error: Option<String>,
+    pub finished_at: String,
+}
+
+impl<'a> NotifyPayload<'a> {
+    #[must_use]
+    pub fn new(config_path: &'a str, sqllog_path: &'a str, result: &Result<RunSummary>) -> Self {
+        let (status, summary, error) = match result {
+            Ok(s) => ("success", Some(s), None),
+            Err(Error::Interrupted) => ("interrupted", None, Some(Error::Interrupted.to_string())),
+            Err(e) => ("failure", None, Some(e.to_string())),
+        };
+        Self {
+            status,
+            version: env!("CARGO_PKG_VERSION"),
+            run_id: summary.map_or_else(
+                || crate::run_id::current().to_string(),
+                |s| s.run_id.clone(),
+            ),
+            config_path,
+            sqllog_path,
+            files: summary.map_or(0, |s| s.files),
+            skipped_files: summary.map_or(0, |s| s.skipped_files),
+            records: summary.map_or(0, |s| s.records),
+            elapsed_secs: summary.map_or(0.0, |s| s.elapsed.as_secs_f64()),
+            dry_run: summary.is_some_and(|s| s.dry_run),
+            warnings: summary.map(|s| s.warnings.clone()).unwrap_or_default(),
+            error,
+            finished_at: chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+        }
+    }
+
+    fn subject(&self) -> String {
+        match self.status {
+            "success" => format!(
+                "[sqllog2db] run succeeded: {} records ({})",
+                self.records, self.config_path
+            ),
+            status => format!("[sqllog2db] run {status} ({})", self.config_path),
+        }
+    }
+
+    fn text(&self) -> String {
+        let mut s = format!(
+            "status: {}\nrun_id: {}\nconfig: {}\nsqllog.path: {}\nfiles: {} ({} skipped)\nrecords: {}\nelapsed: {:.2}s\nfinished_at: {}\n",
+            self.status,
+            self.run_id,
+            self.config_path,
+            self.sqllog_path,
+            self.files,
+            self.skipped_files,
+            self.records,
+            self.elapsed_secs,
+            self.finished_at
+        );
+        if !self.warnings.is_empty() {
+            use std::fmt::Write as _;
+            let _ = writeln!(s, "warnings: {}", self.warnings.len());
+            for w in &self.warnings {
+                let _ = writeln!(s, "  - [{}] {}", w.kind, w.message);
+            }
+        }
+        if let Some(e) = &self.error {
+            s.push_str("error: ");
+            s.push_str(e);
+            s.push('\n');
+        }
+        s
+    }
+}
+
+/// 按运行结果触发对应的通知动作，返回失败描述（调用方决定如何展示）
+#[must_use]
+pub fn dispatch(cfg: &NotifyConfig, payload: &NotifyPayload<'_>) -> Vec<String> {
+    let target = if payload.status == "success" {
+        cfg.on_success.as_ref()
+    } else {
+        cfg.on_failure.as_ref()
+    };
+    let Some(target) = target else {
+        return Vec::new();
+    };
+    let timeout = Duration::from_secs(cfg.timeout_secs);
+    let mut failures = Vec::new();
+    if let Some(url) = &target.webhook {
+        if let Err(e) = send_webhook(url, payload, timeout) {
+            failures.push(format!("webhook {url}: {e}"));
+        }
+    }
+    if let Some(email) = &target.email {
+        if let Err(e) = send_email(email, payload, timeout) {
+            failures.push(format!("email via {}: {e}", email.smtp_host));
+        }
+    }
+    if let Some(cmd) = &target.command {
+        if let Err(e) = run_command(cmd, payload, timeout) {
+            failures.push(format!("command `{cmd}`: {e}"));
+        }
+    }
+    failures
+}
+
+/// `dispatch` 的便捷封装：失败以警告输出到 stderr 与日志
+pub fn notify_run(
+    cfg: &NotifyConfig,
+    config_path: &str,
+    sqllog_path: &str,
+    result: &Result<RunSummary>,
+) {
+    let payload = NotifyPayload::new(config_path, sqllog_path, result);
+    for failure in dispatch(cfg, &payload) {
+        warn!("Notification failed: {failure}");
+        eprintln!(
+            "{} notification failed: {failure}",
+            color::yellow("Warning:")
+        );
+    }
+}
+
+fn send_webhook(
+    url: &str,
+    payload: &NotifyPayload<'_>,
+    timeout: Duration,
+) -> std::result::Result<(), String> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(timeout)
+        .build()
+        .map_err(|e| e.to_string())?;
+    client
+        .post(url)
+        .json(payload)
+        .send()
+        .and_then(reqwest::blocking::Response::error_for_status)
+        .map_err(|e| e.to_string())?;
+    info!("Notification webhook sent: {url}");
+    Ok(())
+}
+
+fn send_email(
+    email: &EmailTarget,
+    payload: &NotifyPayload<'_>,
+    timeout: Duration,
+) -> std::result::Result<(), String> {
+    use lettre::message::header::ContentType;
+    use lettre::transport::smtp::authentication::Credentials;
+    use lettre::{Message, SmtpTransport, Transport};
+
+    let mut builder = Message::builder()
+        .from(email.from.parse().map_err(|e| format!("{e}"))?)
+        .subject(payload.subject())
+        .header(ContentType::TEXT_PLAIN);
+    for to in &email.to {
+        builder = builder.to(to.parse().map_err(|e| format!("{e}"))?);
+    }
+    let message = builder.body(payload.text()).map_err(|e| e.to_string())?;
+
+    let mut transport = match email.tls.as_str() {
+        "tls" => SmtpTransport::relay(&email.smtp_host).map_err(|e| e.to_string())?,
+        "none" => SmtpTransport::builder_dangerous(&email.smtp_host),
+        _ => SmtpTransport::starttls_relay(&email.smtp_host).map_err(|e| e.to_string())?,
+    }
+    .port(email.port())
+    .timeout(Some(timeout));
+    if let Some(user) = &email.username {
+        transport = transport.credentials(Credentials::new(
+            user.clone(),
+            email.password().unwrap_or_default(),
+        ));
+    }
+    transport
+        .build()
+        .send(&message)
+        .map_err(|e| e.to_string())?;
+    info!("Notification email sent to {}", email.to.join(", "));
+    Ok(())
+}
+
+fn run_command(
+    cmd: &str,
+    payload: &NotifyPayload<'_>,
+    timeout: Duration,
+) -> std::result::Result<(), String> {
+    use std::process::{Command, Stdio};
+
+    let mut command = if cfg!(windows) {
+        let mut c = Command::new("cmd");
+        c.args(["/C", cmd]);
+        c
+    } else {
+        let mut c = Command::new("sh");
+        c.args(["-c", cmd]);
+        c
+    };
+    // 独立进程组：超时时连同 shell 派生的子进程（如 `curl`）一起终止
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    let mut child = command
+        .env("SQLLOG2DB_STATUS", payload.status)
+        .env(crate::run_id::RUN_ID_ENV, &payload.run_id)
+        .env("SQLLOG2DB_RECORDS", payload.records.to_string())
+        .env("SQLLOG2DB_FILES", payload.files.to_string())
+        .env(
+            "SQLLOG2DB_ELAPSED_SECS",
+            format!("{:.3}", payload.elapsed_secs),
+        )
+        .env("SQLLOG2DB_CONFIG", payload.config_path)
+        .env("SQLLOG2DB_ERROR", payload.error.as_deref().unwrap_or(""))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let json = serde_json::to_vec(payload).map_err(|e| e.to_string())?;
+        // 单独线程写入：命令不读 stdin 且负载超过管道缓冲区时写入会阻塞，不能拖住下面的超时。
+        // 命令退出后写入以 BrokenPipe 结束，忽略即可；线程不 join
+        std::thread::spawn(move || {
+            let _ = stdin.write_all(&json);
+        });
+    }
+
+    let start = Instant::now();
+    loop {
+        match child.try_wait().map_err(|e| e.to_string())? {
+            Some(status) if status.success() => return Ok(()),
+            Some(status) => return Err(format!("exited with {status}")),
+            None if start.elapsed() >= timeout => {
+                kill_tree(&mut child);
+                return Err(format!("timed out after {}s", timeout.as_secs()));
+            }
+            None => std::thread::sleep(Duration::from_millis(20)),
+        }
+    }
+}
+
+/// 终止命令及其派生的全部子进程：只杀 `sh -c` / `cmd /C` 外壳时，子进程继续运行并占住管道
+fn kill_tree(child: &mut std::process::Child) {
+    use std::process::{Command, Stdio};
+    let pid = child.id().to_string();
+    let mut kill = if cfg!(windows) {
+        let mut c = Command::new("taskkill");
+        c.args(["/T", "/F", "/PID", &pid]);
+        c
+    } else {
+        // 进程组号即外壳进程号（见 `process_group(0)`），负号表示整个进程组
+        let mut c = Command::new("kill");
+        c.args(["-KILL", "--", &format!("-{pid}")]);
+        c
+    };
+    let _ = kill.stdout(Stdio::null()).stderr(Stdio::null()).status();
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary() -> RunSummary {
+        RunSummary {
+            files: 2,
+            records: 42,
+            elapsed: Duration::from_millis(1500),
+            ..RunSummary::default()
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_targets() {
+        let cfg = NotifyConfig {
+            on_failure: Some(NotifyTarget {
+                webhook: Some("ftp://x".into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let msg = cfg.validate().unwrap_err().to_string();
+        assert!(msg.contains("notify.on_failure.webhook"), "{msg}");
+
+        let cfg = NotifyConfig {
+            on_success: Some(NotifyTarget {
+                email: Some(EmailTarget {
+                    smtp_host: "smtp.example.com".into(),
+                    smtp_port: None,
+                    username: None,
+                    password: None,
+                    password_env: None,
+                    from: "not an address".into(),
+                    to: vec!["ops@example.com".into()],
+                    tls: default_tls(),
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let msg = cfg.validate().unwrap_err().to_string();
+        assert!(msg.contains("notify.on_success.email.from"), "{msg}");
+    }
+
+    #[test]
+    fn test_payload_status_from_result() {
+        let ok = NotifyPayload::new("c.toml", "logs", &Ok(summary()));
+        assert_eq!(ok.status, "success");
+        assert_eq!(ok.records, 42);
+        assert!(ok.error.is_none());
+
+        let mut warned = summary();
+        warned.warnings.push(Warning {
+            kind: crate::warnings::WarningKind::ParseErrors,
+            message: "a.log: 3 records failed to parse".to_string(),
+        });
+        let payload = NotifyPayload::new("c.toml", "logs", &Ok(warned));
+        assert!(
+            payload
+                .text()
+                .contains("warnings: 1\n  - [parse_errors] a.log: 3 records failed to parse\n")
+        );
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json["warnings"][0]["kind"], "parse_errors");
+
+        let interrupted = NotifyPayload::new("c.toml", "logs", &Err(Error::Interrupted));
+        assert_eq!(interrupted.status, "interrupted");
+
+        let failed = NotifyPayload::new(
+            "c.toml",
+            "logs",
+            &Err(Error::Config(ConfigError::NoExporters)),
+        );
+        assert_eq!(failed.status, "failure");
+        assert!(failed.text().contains("error: Configuration error"));
+    }
+
+    #[test]
+    fn test_dispatch_picks_target_by_status() {
+        // 只配置 on_failure：成功时不触发任何动作
+        let cfg = NotifyConfig {
+            on_failure: Some(NotifyTarget {
+                webhook: Some("http://127.0.0.1:9/unreachable".into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let payload = NotifyPayload::new("c.toml", "logs", &Ok(summary()));
+        assert!(dispatch(&cfg, &payload).is_empty());
+    }
+
+    #[test]
+    fn test_webhook_posts_json_payload() {
+        use std::io::{BufRead, BufReader, Read};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut content_length = 0usize;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                if let Some(v) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    content_length = v.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0u8; content_length];
+            reader.read_exact(&mut body).unwrap();
+            reader
+                .get_mut()
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                .unwrap();
+            String::from_utf8(body).unwrap()
+        });
+
+        let cfg = NotifyConfig {
+            on_success: Some(NotifyTarget {
+                webhook: Some(format!("http://{addr}/hook")),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let payload = NotifyPayload::new("c.toml", "logs", &Ok(summary()));
+        assert!(dispatch(&cfg, &payload).is_empty());
+        let body: serde_json::Value = serde_json::from_str(&server.join().unwrap()).unwrap();
+        assert_eq!(body["status"], "success");
+        assert_eq!(body["records"], 42);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_receives_env_and_stdin() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let out = dir.path().join("out.txt");
+        let cfg = NotifyConfig {
+            on_failure: Some(NotifyTarget {
+                command: Some(format!(
+                    "echo \"$SQLLOG2DB_STATUS\" > {0} && cat >> {0}",
+                    out.display()
+                )),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let payload = NotifyPayload::new("c.toml", "logs", &Err(Error::Interrupted));
+        assert!(dispatch(&cfg, &payload).is_empty());
+        let content = std::fs::read_to_string(&out).unwrap();
+        assert!(content.starts_with("interrupted\n"), "{content}");
+        assert!(content.contains("\"status\":\"interrupted\""), "{content}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_timeout_when_stdin_not_read() {
+        // 负载远大于管道缓冲区，命令不读 stdin：超时仍须生效
+        let logs = "x".repeat(4 << 20);
+        let payload = NotifyPayload::new("c.toml", &logs, &Ok(summary()));
+        let start = Instant::now();
+        let err = run_command("sleep 5", &payload, Duration::from_millis(300)).unwrap_err();
+        assert!(err.contains("timed out"), "{err}");
+        assert!(start.elapsed() < Duration::from_secs(4));
+    }
+
+    /// 超时终止整个进程组：后台子进程不会在外壳被杀后继续运行
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_command_timeout_kills_background_children() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let pid_file = dir.path().join("pid");
+        let payload = NotifyPayload::new("c.toml", "logs", &Ok(summary()));
+        let cmd = format!("sleep 30 & echo $! > {}; wait", pid_file.display());
+        let err = run_command(&cmd, &payload, Duration::from_millis(500)).unwrap_err();
+        assert!(err.contains("timed out"), "{err}");
+
+        let pid = std::fs::read_to_string(&pid_file).unwrap();
+        let stat = format!("/proc/{}/stat", pid.trim());
+        // 已退出（或只剩待回收的僵尸进程）
+        let alive = || {
+            std::fs::read_to_string(&stat).is_ok_and(|s| {
+                s.rsplit(')')
+                    .next()
+                    .is_some_and(|r| !r.trim_start().starts_with('Z'))
+            })
+        };
+        let start = Instant::now();
+        while alive() && start.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert!(!alive(), "background sleep {} still running", pid.trim());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_failure_reported() {
+        let cfg = NotifyConfig {
+            on_success: Some(NotifyTarget {
+                command: Some("exit 3".into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let payload = NotifyPayload::new("c.toml", "logs", &Ok(summary()));
+        let failures = dispatch(&cfg, &payload);
+        assert_eq!(failures.len(), 1);
+        assert!(failures[0].contains("exit"), "{failures:?}");
+    }
+}
@@ -1,31 +1,281 @@
 /// SQL 日志解析模块
 /// 使用 dm-database-parser-sqllog 库解析达梦数据库的 SQL 日志文件
-use crate::error::{Error, ParserError, Result};
+use crate::config::SqllogConfig;
+use crate::error::{ConfigError, Error, ParserError, Result};
+use crate::warnings::Warnings;
+use chrono::NaiveDate;
 use log::{debug, info, warn};
-use std::path::{Path, PathBuf};
+use regex::Regex;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, LazyLock};
 
 /// SQL 日志解析器
 #[derive(Debug)]
 pub struct SqllogParser {
-    /// 日志路径（文件、目录或 glob 模式）
+    /// 日志路径（文件、目录、glob 模式或 `-` 表示标准输入）
     path: PathBuf,
+    /// 按文件名日期筛选的范围（含边界）
+    date_from: Option<NaiveDate>,
+    date_to: Option<NaiveDate>,
+    /// 文件处理顺序
+    order: FileOrder,
+    /// 仍在写入的文件：扫描阶段跳过或等待
+    active: ActiveFilePolicy,
+    /// 扫描时排除的文件 / 子目录（`sqllog.ignore`）
+    ignore: Vec<glob::Pattern>,
+    /// 运行开始时固定的文件清单；存在时不再扫描
+    listing: Option<Arc<ListingSnapshot>>,
+}
+
+/// 运行开始时固定的文件清单（已应用 `sqllog.ignore`，未应用文件日期筛选与活动文件策略）。
+///
+/// 预检、`--auto-tune` 与正式运行共用同一份清单：运行期间新出现的文件（编辑器临时文件、
+/// 轮转出的新日志）不会中途混入。`sqllog.path` 或 `ignore` 被改写（如调优的样本目录）时不再适用
+#[derive(Debug)]
+pub struct ListingSnapshot {
+    path: String,
+    ignore: Vec<String>,
+    files: Vec<PathBuf>,
+}
+
+impl ListingSnapshot {
+    /// 按当前配置扫描一次
+    pub fn take(config: &SqllogConfig) -> Result<Self> {
+        let parser = SqllogParser {
+            listing: None,
+            ..SqllogParser::from_config(config)?
+        };
+        Ok(Self {
+            path: config.path.clone(),
+            ignore: config.ignore.clone(),
+            files: parser.scan_log_files()?,
+        })
+    }
+
+    fn applies_to(&self, config: &SqllogConfig) -> bool {
+        self.path == config.path && self.ignore == config.ignore
+    }
+}
+
+/// glob 匹配选项：`*` 不跨目录，`**` 匹配任意层级
+const IGNORE_MATCH: glob::MatchOptions = glob::MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// 编译 `sqllog.ignore` 模式
+pub fn ignore_patterns(patterns: &[String]) -> Result<Vec<glob::Pattern>> {
+    patterns
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            glob::Pattern::new(&p.replace('\\', "/")).map_err(|e| {
+                Error::Config(ConfigError::InvalidValue {
+                    field: format!("sqllog.ignore[{i}]"),
+                    value: p.clone(),
+                    reason: format!("invalid glob pattern: {e}"),
+                })
+            })
+        })
+        .collect()
+}
+
+/// `rel`（相对扫描根目录的路径）是否命中任一排除模式
+fn is_ignored(patterns: &[glob::Pattern], rel: &Path) -> bool {
+    patterns.iter().any(|p| {
+        if p.as_str().contains('/') {
+            p.matches_path_with(rel, IGNORE_MATCH)
+        } else {
+            rel.components().any(|c| match c {
+                Component::Normal(name) => name
+                    .to_str()
+                    .is_some_and(|n| p.matches_with(n, IGNORE_MATCH)),
+                _ => false,
+            })
+        }
+    })
+}
+
+/// `file_order` 可选值
+pub const FILE_ORDERS: &[&str] = &["name", "mtime", "size_asc", "size_desc"];
+
+/// 日志文件处理顺序（`sqllog.file_order`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FileOrder {
+    /// 按路径排序（默认）
+    #[default]
+    Name,
+    /// 最近修改的在前，便于排障时先拿到最新数据
+    Mtime,
+    /// 小文件在前，尽快得到部分结果
+    SizeAsc,
+    SizeDesc,
+}
+
+impl FileOrder {
+    /// 解析配置值
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "name" => Ok(Self::Name),
+            "mtime" => Ok(Self::Mtime),
+            "size_asc" => Ok(Self::SizeAsc),
+            "size_desc" => Ok(Self::SizeDesc),
+            _ => Err(Error::Config(ConfigError::InvalidValue {
+                field: "sqllog.file_order".to_string(),
+                value: value.to_string(),
+                reason: format!("must be one of: {}", FILE_ORDERS.join(", ")),
+            })),
+        }
+    }
+
+    /// 对已按路径排序的文件列表重排；稳定排序，同值保持路径顺序。
+    /// 读不到元数据的文件按最旧 / 最小处理
+    fn apply(self, files: &mut [PathBuf]) {
+        let meta = |p: &PathBuf| std::fs::metadata(p).ok();
+        match self {
+            Self::Name => {}
+            Self::Mtime => files.sort_by_cached_key(|p| {
+                std::cmp::Reverse(
+                    meta(p)
+                        .and_then(|m| m.modified().ok())
+                        .unwrap_or(std::time::UNIX_EPOCH),
+                )
+            }),
+            Self::SizeAsc => files.sort_by_cached_key(|p| meta(p).map_or(0, |m| m.len())),
+            Self::SizeDesc => {
+                files.sort_by_cached_key(|p| std::cmp::Reverse(meta(p).map_or(0, |m| m.len())));
+            }
+        }
+    }
+}
+
+/// 文件名中的日期：`20250115` 或 `2025-01-15`，前后不与其他数字相连
+static FILE_DATE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:^|\D)(\d{4})-?(\d{2})-?(\d{2})(?:\D|$)").expect("file date pattern is valid")
+});
+
+/// 提取文件名中首个合法日期
+fn file_name_date(path: &Path) -> Option<NaiveDate> {
+    let name = path.file_name()?.to_str()?;
+    FILE_DATE_RE.captures_iter(name).find_map(|c| {
+        NaiveDate::from_ymd_opt(c[1].parse().ok()?, c[2].parse().ok()?, c[3].parse().ok()?)
+    })
 }
 
 impl SqllogParser {
     /// 创建新的 SQL 日志解析器
     pub fn new(path: impl AsRef<Path>) -> Self {
         Self {
-            path: path.as_ref().to_path_buf(),
+            path: crate::paths::normalize_input(path.as_ref()),
+            date_from: None,
+            date_to: None,
+            order: FileOrder::Name,
+            active: ActiveFilePolicy::default(),
+            ignore: Vec::new(),
+            listing: None,
         }
     }
 
+    /// 按 `[sqllog]` 配置创建（含 `file_date_from` / `file_date_to` 文件筛选与 `file_order`）
+    pub fn from_config(config: &SqllogConfig) -> Result<Self> {
+        let (date_from, date_to) = config.file_date_range()?;
+        Ok(Self {
+            date_from,
+            date_to,
+            order: FileOrder::parse(&config.file_order)?,
+            active: ActiveFilePolicy::from_config(config)?,
+            ignore: ignore_patterns(&config.ignore)?,
+            listing: config.listing.clone().filter(|l| l.applies_to(config)),
+            ..Self::new(&config.path)
+        })
+    }
+
     /// 返回所有日志文件的路径列表（已按路径排序）
     pub fn log_files(&self) -> Result<Vec<PathBuf>> {
-        self.scan_log_files()
+        self.log_files_reporting(&Warnings::default())
+    }
+
+    /// 同 [`Self::log_files`]，扫描阶段跳过的文件（仍在写入）登记到 `warnings`
+    pub fn log_files_reporting(&self, warnings: &Warnings) -> Result<Vec<PathBuf>> {
+        let mut files = self.scan_log_files()?;
+        if self.date_from.is_some() || self.date_to.is_some() {
+            let before = files.len();
+            files.retain(|f| self.in_date_range(f));
+            if files.len() < before {
+                info!(
+                    "Skipped {} log files outside the file date range",
+                    before - files.len()
+                );
+            }
+        }
+        if !input::is_stdin(&self.path) {
+            files.retain(|f| self.active.admit(f, warnings));
+        }
+        self.order.apply(&mut files);
+        Ok(files)
     }
 
-    /// 扫描并获取所有需要解析的日志文件
+    /// 文件名不含日期时保留（无法判断，不冒丢数据的风险）
+    fn in_date_range(&self, path: &Path) -> bool {
+        let Some(date) = file_name_date(path) else {
+            debug!("No date in file name, keeping: {}", path.display());
+            return true;
+        };
+        self.date_from.is_none_or(|f| date >= f) && self.date_to.is_none_or(|t| date <= t)
+    }
+
+    /// 扫描并获取所有需要解析的日志文件（已排除 `sqllog.ignore`）；有快照时直接返回快照
     fn scan_log_files(&self) -> Result<Vec<PathBuf>> {
+        if let Some(listing) = &self.listing {
+            debug!(
+                "Using file listing snapshot ({} files)",
+                listing.files.len()
+            );
+            return Ok(listing.files.clone());
+        }
+        let mut files = self.scan_path()?;
+        if !self.ignore.is_empty() {
+            let root = self.scan_root();
+            let before = files.len();
+            files.retain(|f| !is_ignored(&self.ignore, f.strip_prefix(&root).unwrap_or(f)));
+            if files.len() < before {
+                info!(
+                    "Ignored {} files matching sqllog.ignore",
+                    before - files.len()
+                );
+            }
+        }
+        Ok(files)
+    }
+
+    /// 排除模式的相对基准：目录本身、单文件所在目录，或 glob 模式中首个通配段之前的部分
+    fn scan_root(&self) -> PathBuf {
+        let has_wildcard = |s: &str| s.contains(['*', '?', '[']);
+        if has_wildcard(&self.path.to_string_lossy()) {
+            return self
+                .path
+                .components()
+                .take_while(|c| !has_wildcard(&c.as_os_str().to_string_lossy()))
+                .collect();
+        }
+        if self.path.is_file() {
+            return self
+                .path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+        }
+        self.path.clone()
+    }
+
+    fn scan_path(&self) -> Result<Vec<PathBuf>> {
+        // `-`：标准输入，作为单个"文件"由 open_log 落盘后解析
+        if input::is_stdin(&self.path) {
+            info!("Reading log from stdin");
+            return Ok(vec![self.path.clone()]);
+        }
+
         let path_str = self.path.to_string_lossy();
 
         // Glob 模式检测
@@ -68,7 +318,7 @@ impl SqllogParser {
 
                 let entry_path = entry.path();
 
-                if entry_path.is_file() && entry_path.extension().is_some_and(|ext| ext == "log") {
+                if entry_path.is_file() && input::is_log_file_name(&entry_path) {
                     debug!("Found log file: {}", entry_path.display());
                     log_files.push(entry_path);
                 }
@@ -107,7 +357,7 @@ impl SqllogParser {
                 })
             })?
             .filter_map(std::result::Result::ok)
-            .filter(|p| p.is_file() && p.extension().is_some_and(|ext| ext == "log"))
+            .filter(|p| p.is_file() && input::is_log_file_name(p))
             .collect();
 
         log_files.sort();
@@ -125,10 +375,166 @@ impl SqllogParser {
     }
 }
 
+pub mod input;
+pub use input::open_log;
+
+pub mod recover;
+pub use recover::{ParseMode, Recovering, coerce_exec_id};
+
+pub mod active;
+pub use active::ActiveFilePolicy;
+
+pub mod checksum;
+
+// 库用法的流式记录 API；二进制目标不直接使用
+#[allow(dead_code)]
+mod records;
+#[allow(unused_imports)]
+pub use records::{OwnedSqllog, RecordIter, parse_dir};
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_file_name_date() {
+        let date = |name: &str| file_name_date(Path::new(name)).map(|d| d.to_string());
+        assert_eq!(
+            date("dmsql_DMSERVER_20250115_103028.log").as_deref(),
+            Some("2025-01-15")
+        );
+        assert_eq!(date("sqllog-2025-01-16.log").as_deref(), Some("2025-01-16"));
+        // 非法日期与更长的数字串不算日期
+        assert_eq!(date("dmsql_20251399.log"), None);
+        assert_eq!(date("trace_1234567890.log"), None);
+        assert_eq!(date("dmsql.log"), None);
+    }
+
+    #[test]
+    fn test_log_files_filtered_by_file_date() {
+        let dir = tempfile::TempDir::new().unwrap();
+        for name in [
+            "dmsql_DM_20250114_230000.log",
+            "dmsql_DM_20250115_000000.log",
+            "dmsql_DM_20250116_000000.log",
+            "dmsql_DM_20250117_000000.log",
+            "undated.log",
+        ] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+        let cfg = SqllogConfig {
+            path: dir.path().to_string_lossy().into_owned(),
+            file_date_from: Some("2025-01-15".into()),
+            file_date_to: Some("2025-01-16".into()),
+            ..SqllogConfig::default()
+        };
+        let files = SqllogParser::from_config(&cfg)
+            .unwrap()
+            .log_files()
+            .unwrap();
+        let names: Vec<String> = files
+            .iter()
+            .map(|f| f.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "dmsql_DM_20250115_000000.log",
+                "dmsql_DM_20250116_000000.log",
+                "undated.log"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_log_files_ignore_patterns_and_snapshot() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("backup")).unwrap();
+        std::fs::create_dir_all(root.join("node1/old")).unwrap();
+        for name in [
+            "a.log",
+            "a_copy.log",
+            "backup/b.log",
+            "node1/c.log",
+            "node1/old/d.log",
+        ] {
+            std::fs::write(root.join(name), "").unwrap();
+        }
+        let names = |cfg: &SqllogConfig| {
+            SqllogParser::from_config(cfg)
+                .unwrap()
+                .log_files()
+                .unwrap()
+                .iter()
+                .map(|f| {
+                    f.strip_prefix(root)
+                        .unwrap()
+                        .to_string_lossy()
+                        .replace('\\', "/")
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut cfg = SqllogConfig {
+            path: format!("{}/**/*.log", root.to_string_lossy()),
+            ignore: vec!["*_copy.log".into(), "backup/**".into(), "old".into()],
+            ..SqllogConfig::default()
+        };
+        assert_eq!(names(&cfg), ["a.log", "node1/c.log"]);
+
+        // 快照之后新出现的文件不进入本次运行；改写 path 后快照不再适用
+        cfg.listing = Some(Arc::new(ListingSnapshot::take(&cfg).unwrap()));
+        std::fs::write(root.join("late.log"), "").unwrap();
+        assert_eq!(names(&cfg), ["a.log", "node1/c.log"]);
+        cfg.path = root.to_string_lossy().into_owned();
+        assert_eq!(names(&cfg), ["a.log", "late.log"]);
+
+        let bad = SqllogConfig {
+            ignore: vec!["[".into()],
+            ..SqllogConfig::default()
+        };
+        let err = bad.validate().unwrap_err();
+        assert!(err.to_string().contains("sqllog.ignore[0]"), "{err}");
+    }
+
+    #[test]
+    fn test_log_files_file_order() {
+        let dir = tempfile::TempDir::new().unwrap();
+        for (name, size) in [("a.log", 30), ("b.log", 10), ("c.log", 20), ("d.log", 10)] {
+            std::fs::write(dir.path().join(name), "x".repeat(size)).unwrap();
+        }
+        let names = |order: &str| {
+            let cfg = SqllogConfig {
+                path: dir.path().to_string_lossy().into_owned(),
+                file_order: order.to_string(),
+                ..SqllogConfig::default()
+            };
+            SqllogParser::from_config(&cfg)
+                .unwrap()
+                .log_files()
+                .unwrap()
+                .iter()
+                .map(|f| f.file_name().unwrap().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names("name"), ["a.log", "b.log", "c.log", "d.log"]);
+        // 同大小按路径
+        assert_eq!(names("size_asc"), ["b.log", "d.log", "c.log", "a.log"]);
+        assert_eq!(names("size_desc"), ["a.log", "c.log", "b.log", "d.log"]);
+
+        let old = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+        for name in ["a.log", "b.log", "d.log"] {
+            std::fs::File::options()
+                .write(true)
+                .open(dir.path().join(name))
+                .unwrap()
+                .set_modified(old)
+                .unwrap();
+        }
+        assert_eq!(names("mtime")[0], "c.log");
+        assert!(FileOrder::parse("random").is_err());
+    }
+
     #[test]
     fn test_log_files_nonexistent_path() {
         let p = SqllogParser::new("/this/does/not/exist/at/all");
@@ -218,4 +624,77 @@ mod tests {
         let result = p.log_files();
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_log_files_stdin_is_single_entry() {
+        let files = SqllogParser::new("-").log_files().unwrap();
+        assert_eq!(files, vec![PathBuf::from("-")]);
+    }
+
+    use crate::features::FiltersFeature;
+
+    const LINE: &str = "2025-01-15 10:30:28.001 (EP[0] sess:0x1 user:ALICE trxid:1 stmt:0x1 appname:App ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 5(ms) ROWCOUNT: 2(rows) EXEC_ID: 9.\n";
+
+    #[test]
+    fn test_parse_dir_yields_owned_records() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.log"), LINE.repeat(3)).unwrap();
+        std::fs::write(dir.path().join("b.log"), LINE).unwrap();
+
+        let records: Vec<OwnedSqllog> = parse_dir(dir.path()).map(|r| r.unwrap()).collect();
+        assert_eq!(records.len(), 4);
+        let r = &records[0];
+        assert_eq!(r.ts, "2025-01-15 10:30:28.001");
+        assert_eq!(r.username, "ALICE");
+        assert_eq!(r.tag.as_deref(), Some("SEL"));
+        assert_eq!(r.exec_time_ms, Some(5.0));
+        assert_eq!(r.row_count, Some(2));
+        assert_eq!(r.exec_id, Some(9));
+    }
+
+    #[test]
+    fn test_parse_dir_nonexistent_path_yields_error() {
+        let mut it = parse_dir("/this/does/not/exist/at/all");
+        assert!(it.next().unwrap().is_err());
+        assert!(it.next().is_none());
+    }
+
+    #[test]
+    fn test_parse_dir_with_filters() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let other = LINE.replace("ALICE", "BOB");
+        std::fs::write(dir.path().join("a.log"), format!("{LINE}{other}{LINE}")).unwrap();
+
+        let mut filters = FiltersFeature {
+            enable: true,
+            ..Default::default()
+        };
+        filters.meta.usernames = Some(vec!["^BOB$".to_string()]);
+        let users: Vec<String> = parse_dir(dir.path())
+            .with_filters(&filters)
+            .unwrap()
+            .map(|r| r.unwrap().username)
+            .collect();
+        assert_eq!(users, vec!["BOB"]);
+    }
+
+    #[test]
+    fn test_parse_dir_with_cancel_stops_iteration() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.log"), LINE.repeat(4)).unwrap();
+
+        let cancel = Arc::new(AtomicBool::new(true));
+        assert_eq!(
+            parse_dir(dir.path())
+                .with_cancel(Arc::clone(&cancel))
+                .count(),
+            0
+        );
+
+        cancel.store(false, Ordering::Relaxed);
+        assert_eq!(parse_dir(dir.path()).with_cancel(cancel).count(), 4);
+    }
 }
@@ -0,0 +1,176 @@
+//! 仍在被达梦写入的日志文件（`sqllog.active_file`）。
+//!
+//! 修改时间落在 `active_file_secs` 窗口内的文件视为"活动文件"，其末尾可能是写了一半的记录：
+//!
+//! - `read`（默认）：照常读取，截断的末条记录按解析结果处理（通常计为错误或"已修复"）；
+//! - `skip`：跳过活动文件并告警，留待下次运行；
+//! - `wait`：等待文件静默（最多 `active_file_wait_secs` 秒），超时后按 `complete` 处理；
+//! - `complete`：读取到最后一条完整记录，缺少 EXECTIME 后缀的末条记录留待下次运行。
+use crate::config::SqllogConfig;
+use crate::error::{ConfigError, Error, Result};
+use crate::warnings::{WarningKind, Warnings};
+use log::info;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+/// `active_file` 可选值
+pub const ACTIVE_FILE_MODES: &[&str] = &["read", "skip", "wait", "complete"];
+
+/// 等待活动文件静默时的检查间隔
+const WAIT_POLL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ActiveFileMode {
+    #[default]
+    Read,
+    Skip,
+    Wait,
+    Complete,
+}
+
+/// 活动文件的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ActiveFilePolicy {
+    mode: ActiveFileMode,
+    /// 修改时间距今不足该时长即视为活动文件
+    quiet: Duration,
+    /// `wait` 模式的最长等待时间
+    max_wait: Duration,
+}
+
+impl ActiveFilePolicy {
+    pub fn from_config(config: &SqllogConfig) -> Result<Self> {
+        let mode = match config.active_file.as_str() {
+            "read" => ActiveFileMode::Read,
+            "skip" => ActiveFileMode::Skip,
+            "wait" => ActiveFileMode::Wait,
+            "complete" => ActiveFileMode::Complete,
+            other => {
+                return Err(Error::Config(ConfigError::InvalidValue {
+                    field: "sqllog.active_file".to_string(),
+                    value: other.to_string(),
+                    reason: format!("must be one of: {}", ACTIVE_FILE_MODES.join(", ")),
+                }));
+            }
+        };
+        if config.active_file_secs == 0 {
+            return Err(Error::Config(ConfigError::InvalidValue {
+                field: "sqllog.active_file_secs".to_string(),
+                value: "0".to_string(),
+                reason: "must be greater than 0".to_string(),
+            }));
+        }
+        Ok(Self {
+            mode,
+            quiet: Duration::from_secs(config.active_file_secs),
+            max_wait: Duration::from_secs(config.active_file_wait_secs),
+        })
+    }
+
+    /// 文件最近是否仍在写入；读不到修改时间时按非活动处理
+    fn is_active(self, path: &Path) -> bool {
+        std::fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| SystemTime::now().duration_since(t).ok())
+            .is_some_and(|age| age < self.quiet)
+    }
+
+    /// 扫描阶段：`skip` 模式下返回 `false` 表示跳过；`wait` 模式下阻塞直到文件静默或超时
+    pub(super) fn admit(self, path: &Path, warnings: &Warnings) -> bool {
+        match self.mode {
+            ActiveFileMode::Read | ActiveFileMode::Complete => true,
+            ActiveFileMode::Skip => {
+                let active = self.is_active(path);
+                if active {
+                    warnings.push(
+                        WarningKind::SkippedFile,
+                        format!(
+                            "Skipping {}: still being written (modified within {}s)",
+                            path.display(),
+                            self.quiet.as_secs()
+                        ),
+                    );
+                }
+                !active
+            }
+            ActiveFileMode::Wait => {
+                let start = std::time::Instant::now();
+                if self.is_active(path) {
+                    info!("Waiting for {} to stop growing", path.display());
+                }
+                while self.is_active(path) && start.elapsed() < self.max_wait {
+                    std::thread::sleep(WAIT_POLL);
+                }
+                true
+            }
+        }
+    }
+
+    /// 打开文件时：是否丢弃不完整的末条记录（`complete`，或 `wait` 超时后仍在写入）
+    #[must_use]
+    pub fn holds_tail(self, path: &Path) -> bool {
+        matches!(self.mode, ActiveFileMode::Complete | ActiveFileMode::Wait) && self.is_active(path)
+    }
+}
+
+/// `buf` 中最后一条完整记录的结束位置：最后一个"换行 + 时间戳"之前；找不到时返回 `None`
+#[must_use]
+pub fn record_boundary(buf: &[u8]) -> Option<usize> {
+    let starts_record =
+        |s: &[u8]| s.len() >= 5 && s[..4].iter().all(u8::is_ascii_digit) && s[4] == b'-';
+    memchr::memrchr_iter(b'\n', buf)
+        .find(|&p| starts_record(&buf[p + 1..]))
+        .map(|p| p + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(mode: &str, secs: u64) -> Result<ActiveFilePolicy> {
+        ActiveFilePolicy::from_config(&SqllogConfig {
+            active_file: mode.to_string(),
+            active_file_secs: secs,
+            active_file_wait_secs: 0,
+            ..SqllogConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_active_file_policy() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let fresh = dir.path().join("fresh.log");
+        let old = dir.path().join("old.log");
+        std::fs::write(&fresh, "").unwrap();
+        std::fs::write(&old, "").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&old)
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(600))
+            .unwrap();
+
+        let skip = policy("skip", 60).unwrap();
+        let warnings = Warnings::default();
+        assert!(!skip.admit(&fresh, &warnings));
+        assert!(skip.admit(&old, &warnings));
+        assert!(!skip.holds_tail(&fresh));
+
+        let complete = policy("complete", 60).unwrap();
+        assert!(complete.admit(&fresh, &warnings));
+        assert!(complete.holds_tail(&fresh));
+        assert!(!complete.holds_tail(&old));
+
+        // 等待超时（0 秒）后按 complete 处理
+        let wait = policy("wait", 60).unwrap();
+        assert!(wait.admit(&fresh, &warnings));
+        assert!(wait.holds_tail(&fresh));
+        // 只有 skip 模式跳过的文件登记为警告
+        assert_eq!(warnings.into_vec().len(), 1);
+
+        assert!(!policy("read", 60).unwrap().holds_tail(&fresh));
+        assert!(policy("tail", 60).is_err());
+        assert!(policy("skip", 0).is_err());
+    }
+}
@@ -0,0 +1,123 @@
+//! 归档日志的校验清单（`sqllog.verify_checksums`）。
+//!
+//! 日志文件所在目录存在 `SHA256SUMS`（`sha256sum` 输出格式：`<hex>  <文件名>`，文件名前可带 `*`），
+//! 或文件旁存在同名 `.sha256` 文件时，按原始字节（压缩文件即压缩后的字节）流式计算 SHA-256 并比对。
+//! 清单中未列出的文件不校验。
+use crate::error::{Error, ParserError, Result};
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// 目录级校验清单文件名
+const MANIFEST: &str = "SHA256SUMS";
+
+/// 流式读取的块大小
+const CHUNK: usize = 1 << 16;
+
+/// 从清单内容中查找 `name` 的期望摘要（小写十六进制）
+fn lookup(manifest: &str, name: &str) -> Option<String> {
+    manifest.lines().find_map(|line| {
+        let (hash, file) = line.trim().split_once(char::is_whitespace)?;
+        let file = file.trim_start();
+        let file = file.strip_prefix('*').unwrap_or(file);
+        (file == name && hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_hexdigit()))
+            .then(|| hash.to_ascii_lowercase())
+    })
+}
+
+/// 文件的期望摘要：优先 `<文件>.sha256`，其次目录下的 `SHA256SUMS`
+fn expected(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_str()?;
+    let sidecar = path.with_file_name(format!("{name}.sha256"));
+    if let Ok(content) = std::fs::read_to_string(&sidecar) {
+        // 单文件清单可只写摘要，省略文件名
+        let first = content.split_whitespace().next().unwrap_or_default();
+        return lookup(&content, name).or_else(|| lookup(&format!("{first} {name}"), name));
+    }
+    let manifest = std::fs::read_to_string(path.with_file_name(MANIFEST)).ok()?;
+    lookup(&manifest, name)
+}
+
+/// 流式计算文件的 SHA-256
+pub(crate) fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut file = File::open(crate::paths::for_io(path))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; CHUNK];
+    loop {
+        match file.read(&mut buf)? {
+            0 => break,
+            n => hasher.update(&buf[..n]),
+        }
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .fold(String::with_capacity(64), |mut s, b| {
+            let _ = write!(s, "{b:02x}");
+            s
+        }))
+}
+
+/// 按校验清单验证文件；未列出的文件直接通过
+pub fn verify(path: &Path) -> Result<()> {
+    let Some(expected) = expected(path) else {
+        return Ok(());
+    };
+    let corrupted = |reason: String| {
+        Error::Parser(ParserError::Corrupted {
+            path: path.to_path_buf(),
+            reason,
+        })
+    };
+    let actual =
+        sha256_file(path).map_err(|e| corrupted(format!("cannot read for checksum: {e}")))?;
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(corrupted(format!(
+            "sha256 mismatch (expected {expected}, got {actual})"
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMPTY_SHA256: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    #[test]
+    fn test_lookup_manifest_formats() {
+        let manifest = format!("{EMPTY_SHA256}  a.log\n{}  *b.log.gz\n", "A".repeat(64));
+        assert_eq!(lookup(&manifest, "a.log").as_deref(), Some(EMPTY_SHA256));
+        assert_eq!(lookup(&manifest, "b.log.gz"), Some("a".repeat(64)));
+        assert_eq!(lookup(&manifest, "c.log"), None);
+        assert_eq!(lookup("xyz  a.log", "a.log"), None);
+    }
+
+    #[test]
+    fn test_verify() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let good = dir.path().join("good.log");
+        let bad = dir.path().join("bad.log");
+        let unlisted = dir.path().join("unlisted.log");
+        for p in [&good, &bad, &unlisted] {
+            std::fs::write(p, "").unwrap();
+        }
+        std::fs::write(
+            dir.path().join(MANIFEST),
+            format!("{EMPTY_SHA256}  good.log\n{}  bad.log\n", "0".repeat(64)),
+        )
+        .unwrap();
+        assert!(verify(&good).is_ok());
+        assert!(verify(&unlisted).is_ok());
+        let err = verify(&bad).unwrap_err().to_string();
+        assert!(err.contains("sha256 mismatch"), "{err}");
+
+        // 旁路 .sha256 文件优先，且可只写摘要
+        std::fs::write(dir.path().join("bad.log.sha256"), EMPTY_SHA256).unwrap();
+        assert!(verify(&bad).is_ok());
+    }
+}
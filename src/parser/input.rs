@@ -0,0 +1,332 @@
+//! 输入文件压缩格式探测：按文件头魔数（而非扩展名）识别 gzip / zstd / xz，
+//! 解压到临时文件后交给 mmap 解析器；未压缩文件直接 mmap，零额外开销。
+//!
+//! 临时文件位于系统临时目录（遵循 `TMPDIR`），`LogSource` 释放时自动删除。
+//!
+//! 路径 `-` 表示标准输入：首次打开时整体落盘（压缩输入同样按魔数解压）为进程级临时文件，
+//! 之后的打开（如事务过滤预扫描 + 正式处理）复用同一份内容；进程结束前由 `remove_stdin_spool()` 清理。
+use crate::error::{Error, ParserError, Result};
+use dm_database_parser_sqllog::LogParser;
+use log::debug;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+
+/// 输入文件的压缩格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Plain,
+    Gzip,
+    Zstd,
+    Xz,
+}
+
+/// 魔数表：新增格式只需追加一项并在 `decompress_to` 中补充解码器
+const MAGICS: &[(Compression, &[u8])] = &[
+    (Compression::Gzip, &[0x1f, 0x8b]),
+    (Compression::Zstd, &[0x28, 0xb5, 0x2f, 0xfd]),
+    (Compression::Xz, &[0xfd, b'7', b'z', b'X', b'Z', 0x00]),
+];
+
+/// 目录 / glob 扫描时接受的文件名后缀（压缩文件仍按魔数判定实际格式）
+const LOG_SUFFIXES: &[&str] = &[".log", ".log.gz", ".log.zst", ".log.zstd", ".log.xz"];
+
+impl Compression {
+    /// 按文件头判定格式；不匹配任何魔数时视为未压缩
+    #[must_use]
+    pub fn detect(header: &[u8]) -> Self {
+        MAGICS
+            .iter()
+            .find(|(_, magic)| header.starts_with(magic))
+            .map_or(Self::Plain, |(c, _)| *c)
+    }
+
+    /// 读取文件头并判定格式
+    pub fn sniff(path: &Path) -> io::Result<Self> {
+        let mut header = [0u8; 8];
+        let mut file = File::open(path)?;
+        let mut n = 0;
+        while n < header.len() {
+            match file.read(&mut header[n..])? {
+                0 => break,
+                m => n += m,
+            }
+        }
+        Ok(Self::detect(&header[..n]))
+    }
+
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Plain => "plain",
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+            Self::Xz => "xz",
+        }
+    }
+
+    fn decompress_to(self, input: File, out: &mut impl Write) -> io::Result<()> {
+        self.decode(&mut BufReader::new(input), out)
+    }
+
+    fn decode(self, reader: &mut impl BufRead, out: &mut impl Write) -> io::Result<()> {
+        match self {
+            Self::Plain => {
+                io::copy(reader, out)?;
+            }
+            Self::Gzip => {
+                io::copy(&mut flate2::read::MultiGzDecoder::new(reader), out)?;
+            }
+            Self::Zstd => {
+                // 逐帧解码：支持多帧拼接的 .zst 文件
+                while !reader.fill_buf()?.is_empty() {
+                    let mut frame = ruzstd::decoding::StreamingDecoder::new(&mut *reader)
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                    io::copy(&mut frame, out)?;
+                }
+            }
+            Self::Xz => {
+                lzma_rs::xz_decompress(reader, out)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 表示标准输入的日志路径
+pub const STDIN_PATH: &str = "-";
+
+/// 标准输入落盘后的临时文件（进程内只读取一次 stdin）
+static STDIN_SPOOL: Mutex<Option<tempfile::TempPath>> = Mutex::new(None);
+
+/// 日志路径是否指向标准输入
+#[must_use]
+pub fn is_stdin(path: &Path) -> bool {
+    path.as_os_str() == STDIN_PATH
+}
+
+/// 文件名是否为可解析的日志文件（`.log` 或 `.log.gz` / `.log.zst` / `.log.xz`）
+#[must_use]
+pub fn is_log_file_name(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| LOG_SUFFIXES.iter().any(|s| n.ends_with(s)))
+}
+
+/// 已打开的日志输入：解引用为 `LogParser`；压缩输入持有解压后的临时文件
+pub struct LogSource {
+    parser: LogParser,
+    decompressed: Option<tempfile::TempPath>,
+}
+
+impl std::fmt::Debug for LogSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LogSource")
+            .field("decompressed", &self.decompressed)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Deref for LogSource {
+    type Target = LogParser;
+
+    fn deref(&self) -> &LogParser {
+        &self.parser
+    }
+}
+
+fn invalid_path(path: &Path, reason: impl std::fmt::Display) -> Error {
+    Error::Parser(ParserError::InvalidPath {
+        path: path.to_path_buf(),
+        reason: reason.to_string(),
+    })
+}
+
+/// 创建解压 / 落盘用的临时文件
+fn spool_file(path: &Path) -> Result<tempfile::NamedTempFile> {
+    crate::temp::spool_file()
+        .map_err(|e| invalid_path(path, format!("cannot create temp file: {e}")))
+}
+
+/// 返回标准输入落盘后的路径；首次调用时读取全部 stdin（按魔数解压）
+fn stdin_spool_path() -> Result<PathBuf> {
+    let path = Path::new(STDIN_PATH);
+    let mut spool = STDIN_SPOOL.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(tmp) = spool.as_ref() {
+        return Ok(tmp.to_path_buf());
+    }
+
+    let mut stdin = io::stdin().lock();
+    let header = stdin.fill_buf().map_err(|e| invalid_path(path, e))?;
+    let compression = Compression::detect(header);
+    debug!("Spooling {} log input from stdin", compression.name());
+    let mut tmp = spool_file(path)?;
+    {
+        let mut out = io::BufWriter::new(tmp.as_file_mut());
+        compression
+            .decode(&mut stdin, &mut out)
+            .and_then(|()| out.flush())
+            .map_err(|e| {
+                invalid_path(path, format!("{} decode failed: {e}", compression.name()))
+            })?;
+    }
+    let tmp_path = tmp.into_temp_path();
+    let spooled = tmp_path.to_path_buf();
+    *spool = Some(tmp_path);
+    Ok(spooled)
+}
+
+/// 删除标准输入的落盘文件（未读取过 stdin 时为空操作）
+pub fn remove_stdin_spool() {
+    STDIN_SPOOL
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take();
+}
+
+/// 打开日志文件：按魔数探测压缩格式，必要时解压到临时文件；`-` 表示标准输入
+pub fn open_log(path: &Path) -> Result<LogSource> {
+    if is_stdin(path) {
+        let spooled = stdin_spool_path()?;
+        let parser = LogParser::from_path(&spooled).map_err(|e| invalid_path(path, e))?;
+        return Ok(LogSource {
+            parser,
+            decompressed: None,
+        });
+    }
+    let io_path = crate::paths::for_io(path);
+    let compression = Compression::sniff(&io_path).map_err(|e| invalid_path(path, e))?;
+    if compression == Compression::Plain {
+        let parser = LogParser::from_path(&io_path).map_err(|e| invalid_path(path, e))?;
+        return Ok(LogSource {
+            parser,
+            decompressed: None,
+        });
+    }
+
+    debug!(
+        "Decompressing {} input: {}",
+        compression.name(),
+        path.display()
+    );
+    let input = File::open(&io_path).map_err(|e| invalid_path(path, e))?;
+    let mut tmp = spool_file(path)?;
+    {
+        let mut out = io::BufWriter::new(tmp.as_file_mut());
+        compression
+            .decompress_to(input, &mut out)
+            .and_then(|()| out.flush())
+            .map_err(|e| {
+                invalid_path(path, format!("{} decode failed: {e}", compression.name()))
+            })?;
+    }
+    let tmp_path = tmp.into_temp_path();
+    let parser = LogParser::from_path(&tmp_path).map_err(|e| invalid_path(path, e))?;
+    Ok(LogSource {
+        parser,
+        decompressed: Some(tmp_path),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINE: &str = "2025-01-15 10:30:28.001 (EP[0] sess:0x0001 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.\n";
+
+    fn count(path: &Path) -> usize {
+        open_log(path).unwrap().iter().flatten().count()
+    }
+
+    #[test]
+    fn test_detect_magic() {
+        assert_eq!(Compression::detect(&[0x1f, 0x8b, 8]), Compression::Gzip);
+        assert_eq!(
+            Compression::detect(&[0x28, 0xb5, 0x2f, 0xfd, 0]),
+            Compression::Zstd
+        );
+        assert_eq!(Compression::detect(b"\xfd7zXZ\x00\x00"), Compression::Xz);
+        assert_eq!(Compression::detect(b"2025-01-15"), Compression::Plain);
+        assert_eq!(Compression::detect(b""), Compression::Plain);
+    }
+
+    #[test]
+    fn test_is_log_file_name() {
+        assert!(is_log_file_name(Path::new("a/dmsql_1.log")));
+        assert!(is_log_file_name(Path::new("dmsql_1.log.gz")));
+        assert!(is_log_file_name(Path::new("dmsql_1.log.zst")));
+        assert!(!is_log_file_name(Path::new("dmsql_1.txt")));
+        assert!(!is_log_file_name(Path::new("archive.tar.gz")));
+    }
+
+    #[test]
+    fn test_open_gzip_with_wrong_extension() {
+        use flate2::write::GzEncoder;
+        let dir = tempfile::TempDir::new().unwrap();
+        // 扩展名为 .log，内容实为 gzip
+        let path = dir.path().join("mislabeled.log");
+        let mut enc = GzEncoder::new(File::create(&path).unwrap(), flate2::Compression::fast());
+        enc.write_all(LINE.repeat(3).as_bytes()).unwrap();
+        enc.finish().unwrap();
+        assert_eq!(Compression::sniff(&path).unwrap(), Compression::Gzip);
+        assert_eq!(count(&path), 3);
+    }
+
+    #[test]
+    fn test_open_zstd_and_xz() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let data = LINE.repeat(4);
+
+        let zst = dir.path().join("a.log.zst");
+        let compressed = ruzstd::encoding::compress_to_vec(
+            data.as_bytes(),
+            ruzstd::encoding::CompressionLevel::Fastest,
+        );
+        std::fs::write(&zst, compressed).unwrap();
+        assert_eq!(count(&zst), 4);
+
+        let xz = dir.path().join("b.log.xz");
+        let mut out = Vec::new();
+        lzma_rs::xz_compress(&mut data.as_bytes(), &mut out).unwrap();
+        std::fs::write(&xz, out).unwrap();
+        assert_eq!(count(&xz), 4);
+    }
+
+    #[test]
+    fn test_open_plain_and_plain_named_gz() {
+        let dir = tempfile::TempDir::new().unwrap();
+        // 扩展名为 .log.gz，内容实为纯文本
+        let path = dir.path().join("plain.log.gz");
+        std::fs::write(&path, LINE.repeat(2)).unwrap();
+        assert_eq!(count(&path), 2);
+    }
+
+    #[test]
+    fn test_decode_from_buffered_reader() {
+        // stdin 落盘走的路径：从 BufRead 直接解码
+        use flate2::write::GzEncoder;
+        let mut enc = GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        enc.write_all(LINE.as_bytes()).unwrap();
+        let gz = enc.finish().unwrap();
+        assert_eq!(Compression::detect(&gz), Compression::Gzip);
+        let mut out = Vec::new();
+        Compression::Gzip
+            .decode(&mut gz.as_slice(), &mut out)
+            .unwrap();
+        assert_eq!(out, LINE.as_bytes());
+        assert!(is_stdin(Path::new("-")));
+        assert!(!is_stdin(Path::new("./-")));
+    }
+
+    #[test]
+    fn test_corrupt_gzip_is_error() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("bad.log");
+        std::fs::write(&path, [0x1f, 0x8b, 0x08, 0x00, 0x01]).unwrap();
+        let err = open_log(&path).unwrap_err().to_string();
+        assert!(err.contains("gzip decode failed"), "{err}");
+    }
+}
@@ -0,0 +1,235 @@
+//! 库用法：`parse_dir` 流式产出 `OwnedSqllog`，无需构造导出器。
+use super::SqllogParser;
+use crate::error::{Error, ParserError, Result};
+use crate::features::filters::RecordMeta;
+use crate::features::{CompiledMetaFilters, CompiledSqlFilters, FiltersFeature};
+use dm_database_parser_sqllog::{MetaParts, PerformanceMetrics, Sqllog};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, SyncSender, sync_channel};
+
+/// 脱离 mmap 生命周期的已解析记录（库用法）。
+///
+/// 字段与导出列一一对应；无性能指标的记录 `exec_time_ms`/`row_count`/`exec_id` 为 `None`。
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct OwnedSqllog {
+    pub ts: String,
+    pub ep: u8,
+    pub sess_id: String,
+    pub thrd_id: String,
+    pub username: String,
+    pub trx_id: String,
+    pub statement: String,
+    pub appname: String,
+    pub client_ip: String,
+    pub tag: Option<String>,
+    pub sql: String,
+    pub exec_time_ms: Option<f32>,
+    pub row_count: Option<u32>,
+    pub exec_id: Option<i64>,
+}
+
+impl OwnedSqllog {
+    #[must_use]
+    pub fn from_record(record: &Sqllog<'_>) -> Self {
+        Self::from_parts(
+            record,
+            &record.parse_meta(),
+            &record.parse_performance_metrics(),
+        )
+    }
+
+    /// 由调用方已解析的 meta / 性能指标构造（导出热路径已解析过，避免重复解析）
+    #[must_use]
+    pub fn from_parts(
+        record: &Sqllog<'_>,
+        meta: &MetaParts<'_>,
+        pm: &PerformanceMetrics<'_>,
+    ) -> Self {
+        let has_metrics = pm.exec_id != 0 || pm.exectime > 0.0 || pm.rowcount != 0;
+        Self {
+            ts: record.ts.as_ref().to_string(),
+            ep: meta.ep,
+            sess_id: meta.sess_id.as_ref().to_string(),
+            thrd_id: meta.thrd_id.as_ref().to_string(),
+            username: meta.username.as_ref().to_string(),
+            trx_id: meta.trxid.as_ref().to_string(),
+            statement: meta.statement.as_ref().to_string(),
+            appname: meta.appname.as_ref().to_string(),
+            client_ip: meta.client_ip.as_ref().to_string(),
+            tag: record.tag.as_deref().map(str::to_string),
+            sql: pm.sql.as_ref().to_string(),
+            exec_time_ms: has_metrics.then_some(pm.exectime),
+            row_count: has_metrics.then_some(pm.rowcount),
+            exec_id: has_metrics.then_some(pm.exec_id),
+        }
+    }
+}
+
+/// 记录级过滤条件（时间范围 + 元数据 + `record_sql`），由 `FiltersFeature` 编译而来。
+/// 事务级过滤（`indicators` / `sql`）需要预扫描，库迭代器不支持。
+#[derive(Debug)]
+struct RecordFilter {
+    start_ts: Option<String>,
+    end_ts: Option<String>,
+    meta: CompiledMetaFilters,
+    record_sql: CompiledSqlFilters,
+}
+
+impl RecordFilter {
+    fn keep(&self, record: &Sqllog<'_>) -> bool {
+        let ts = record.ts.as_ref();
+        if let Some(start) = &self.start_ts {
+            if ts < start.as_str() && !ts.starts_with(start.as_str()) {
+                return false;
+            }
+        }
+        if let Some(end) = &self.end_ts {
+            if ts > end.as_str() && !ts.starts_with(end.as_str()) {
+                return false;
+            }
+        }
+        if self.meta.has_any_filters() {
+            let meta = record.parse_meta();
+            let keep = self.meta.should_keep(&RecordMeta {
+                trxid: meta.trxid.as_ref(),
+                ip: meta.client_ip.as_ref(),
+                sess: meta.sess_id.as_ref(),
+                thrd: meta.thrd_id.as_ref(),
+                user: meta.username.as_ref(),
+                stmt: meta.statement.as_ref(),
+                app: meta.appname.as_ref(),
+                tag: record.tag.as_deref(),
+            });
+            if !keep {
+                return false;
+            }
+        }
+        !(self.record_sql.has_filters()
+            && record.tag.is_some()
+            && !self.record_sql.matches(record.body().as_ref()))
+    }
+}
+
+/// 通道容量：生产线程最多领先消费方的记录数
+const RECORD_CHANNEL_CAPACITY: usize = 1024;
+
+/// 流式记录迭代器：后台线程逐文件解析并通过有界通道产出 `OwnedSqllog`。
+///
+/// 首次调用 `next()` 时才启动解析；迭代器被丢弃后后台线程在下一次发送失败时退出。
+#[derive(Debug)]
+pub struct RecordIter {
+    path: PathBuf,
+    filter: Option<RecordFilter>,
+    cancel: Option<Arc<AtomicBool>>,
+    rx: Option<Receiver<Result<OwnedSqllog>>>,
+}
+
+impl RecordIter {
+    /// 附加记录级过滤条件；`enable = false` 时不过滤。非法正则返回 `ConfigError::InvalidValue`。
+    pub fn with_filters(mut self, filters: &FiltersFeature) -> Result<Self> {
+        if !filters.enable {
+            self.filter = None;
+            return Ok(self);
+        }
+        self.filter = Some(RecordFilter {
+            start_ts: filters.meta.start_ts.clone(),
+            end_ts: filters.meta.end_ts.clone(),
+            meta: CompiledMetaFilters::try_from_meta(&filters.meta)?,
+            record_sql: CompiledSqlFilters::try_from_sql_filters(&filters.record_sql)?,
+        });
+        Ok(self)
+    }
+
+    /// 附加协作式取消标志：置为 `true` 后后台线程停止解析，迭代器随即结束。
+    #[must_use]
+    pub fn with_cancel(mut self, cancel: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    fn start(&mut self) -> Receiver<Result<OwnedSqllog>> {
+        let (tx, rx) = sync_channel(RECORD_CHANNEL_CAPACITY);
+        let path = self.path.clone();
+        let filter = self.filter.take();
+        let cancel = self.cancel.take();
+        std::thread::spawn(move || produce_records(&path, filter.as_ref(), cancel.as_deref(), &tx));
+        rx
+    }
+}
+
+/// 后台解析线程主体：接收端关闭或取消标志置位时提前返回
+fn produce_records(
+    path: &Path,
+    filter: Option<&RecordFilter>,
+    cancel: Option<&AtomicBool>,
+    tx: &SyncSender<Result<OwnedSqllog>>,
+) {
+    let files = match SqllogParser::new(path).log_files() {
+        Ok(files) => files,
+        Err(e) => {
+            let _ = tx.send(Err(e));
+            return;
+        }
+    };
+    let cancelled = || cancel.is_some_and(|c| c.load(Ordering::Relaxed));
+    for file in files {
+        if cancelled() {
+            return;
+        }
+        let parser = match super::open_log(&file) {
+            Ok(p) => p,
+            Err(err) => {
+                if tx.send(Err(err)).is_err() {
+                    return;
+                }
+                continue;
+            }
+        };
+        for result in parser.iter() {
+            if cancelled() {
+                return;
+            }
+            let item = match result {
+                Ok(record) => {
+                    if filter.is_some_and(|f| !f.keep(&record)) {
+                        continue;
+                    }
+                    Ok(OwnedSqllog::from_record(&record))
+                }
+                Err(e) => Err(Error::Parser(ParserError::InvalidRecord {
+                    path: file.clone(),
+                    reason: e.to_string(),
+                })),
+            };
+            if tx.send(item).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+impl Iterator for RecordIter {
+    type Item = Result<OwnedSqllog>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rx.is_none() {
+            self.rx = Some(self.start());
+        }
+        self.rx.as_ref()?.recv().ok()
+    }
+}
+
+/// 库入口：按文件名顺序流式解析目录 / 单文件 / glob 下的全部记录，无需构造导出器。
+///
+/// 路径错误与单条记录解析错误均以 `Err` 项产出，调用方可选择跳过或中止。
+#[must_use]
+pub fn parse_dir(path: impl AsRef<Path>) -> RecordIter {
+    RecordIter {
+        path: path.as_ref().to_path_buf(),
+        filter: None,
+        cancel: None,
+        rx: None,
+    }
+}
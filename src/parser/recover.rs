@@ -0,0 +1,408 @@
+//! 解析容错级别（`sqllog.parse_mode`）。
+//!
+//! - `lenient`（默认）：带垃圾前缀的记录行不以时间戳开头，会被解析器拼进上一条记录的正文；
+//!   这里按内嵌的 `时间戳 (EP[` 把它切分出来重新解析。缺少 EXECTIME 后缀的记录照常导出
+//!   （性能指标为 0）。两类记录均标记为"已修复"。
+//!   `EXEC_ID` 超出 i64 范围（部分 DM 版本）时饱和为 `i64::MAX`，值前带杂散字符时跳过，
+//!   由 [`coerce_exec_id`] 在导出前修正并计入 `exec_id_coerced`；否则解析器会静默写成 0。
+//! - `strict`：上述任何不合规的记录都计为解析错误，写入错误日志，不导出。
+//!
+//! 两种模式下时间戳本身损坏的记录（只可能出现在文件开头）都无法还原，一律计为错误。
+use crate::error::{ConfigError, Error, Result};
+use dm_database_parser_sqllog::{ParseError, PerformanceMetrics, Sqllog, parse_record};
+use regex::bytes::Regex;
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::iter::Peekable;
+use std::sync::LazyLock;
+
+/// `parse_mode` 可选值
+pub const PARSE_MODES: &[&str] = &["strict", "lenient"];
+
+/// 解析容错级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    Strict,
+    #[default]
+    Lenient,
+}
+
+impl ParseMode {
+    /// 解析配置值
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "strict" => Ok(Self::Strict),
+            "lenient" => Ok(Self::Lenient),
+            _ => Err(Error::Config(ConfigError::InvalidValue {
+                field: "sqllog.parse_mode".to_string(),
+                value: value.to_string(),
+                reason: format!("must be one of: {}", PARSE_MODES.join(", ")),
+            })),
+        }
+    }
+}
+
+/// 正文中内嵌的记录起始：`YYYY-MM-DD HH:MM:SS.mmm (EP[`
+static EMBEDDED_START_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"20\d\d-\d\d-\d\d \d\d:\d\d:\d\d\.\d{3} \(EP\[").expect("record start is valid")
+});
+
+fn is_timestamp(ts: &str) -> bool {
+    let b = ts.as_bytes();
+    b.len() == 23
+        && b.iter().enumerate().all(|(i, c)| match i {
+            4 | 7 => *c == b'-',
+            10 => *c == b' ',
+            13 | 16 => *c == b':',
+            19 => *c == b'.',
+            _ => c.is_ascii_digit(),
+        })
+}
+
+/// 指标后缀只会出现在正文末尾这一窗口内（与解析器的指标搜索窗口一致）
+const INDICATORS_WINDOW: usize = 256;
+
+static EXECTIME_MARKER: LazyLock<memchr::memmem::FinderRev<'static>> =
+    LazyLock::new(|| memchr::memmem::FinderRev::new(b"EXECTIME:"));
+
+/// 带标签（DML）但缺少 EXECTIME 后缀；PARAMS 记录本就没有后缀，不计入
+fn missing_indicators(record: &Sqllog<'_>) -> bool {
+    let content = &record.content_raw;
+    record.tag.is_some()
+        && EXECTIME_MARKER
+            .rfind(&content[content.len().saturating_sub(INDICATORS_WINDOW)..])
+            .is_none()
+}
+
+static EXEC_ID_MARKER: LazyLock<memchr::memmem::FinderRev<'static>> =
+    LazyLock::new(|| memchr::memmem::FinderRev::new(b"EXEC_ID:"));
+
+/// `EXEC_ID:` 之后的原始取值（到 `.` 或正文末尾）
+fn exec_id_raw<'r>(record: &'r Sqllog<'_>) -> Option<&'r [u8]> {
+    let content: &[u8] = &record.content_raw;
+    let window = content.len().saturating_sub(INDICATORS_WINDOW);
+    let start = window + EXEC_ID_MARKER.rfind(&content[window..])? + b"EXEC_ID:".len();
+    let value = &content[start..];
+    let end = memchr::memchr(b'.', value).unwrap_or(value.len());
+    Some(value[..end].trim_ascii())
+}
+
+/// 容错解析 `EXEC_ID`：取首段数字，超出 i64 范围时饱和。
+/// 返回 `(值, 是否经过修正)`；解析器能直接解析的取值（可带符号、尾随字符）不算修正
+fn lenient_exec_id(raw: &[u8]) -> Option<(i64, bool)> {
+    let start = raw.iter().position(u8::is_ascii_digit)?;
+    let negative = start > 0 && raw[start - 1] == b'-';
+    let value = raw[start..]
+        .iter()
+        .take_while(|b| b.is_ascii_digit())
+        .try_fold(0i64, |acc, b| {
+            acc.checked_mul(10)?.checked_add(i64::from(b - b'0'))
+        });
+    let stray = start > usize::from(negative || raw[0] == b'+');
+    Some(match value {
+        Some(v) => (if negative { -v } else { v }, stray),
+        None if negative => (i64::MIN, true),
+        None => (i64::MAX, true),
+    })
+}
+
+/// `EXEC_ID` 取值不合规（溢出或带前导杂散字符）
+fn malformed_exec_id(record: &Sqllog<'_>) -> bool {
+    exec_id_raw(record)
+        .and_then(lenient_exec_id)
+        .is_some_and(|(_, coerced)| coerced)
+}
+
+/// 修正解析器未能解析的 `EXEC_ID`（解析器在溢出或前导杂散字符时静默置 0），返回是否做了修正
+pub fn coerce_exec_id(record: &Sqllog<'_>, pm: &mut PerformanceMetrics<'_>) -> bool {
+    if pm.exec_id != 0 {
+        return false;
+    }
+    match exec_id_raw(record).and_then(lenient_exec_id) {
+        Some((value, true)) => {
+            pm.exec_id = value;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// 内嵌记录的必要子串，先用 memmem 预筛，绝大多数记录无需进入正则
+static EMBEDDED_MARKER: LazyLock<memchr::memmem::Finder<'static>> =
+    LazyLock::new(|| memchr::memmem::Finder::new(b" (EP["));
+
+/// 正文中首个内嵌记录的起始偏移
+fn embedded_start(record: &Sqllog<'_>) -> Option<usize> {
+    EMBEDDED_MARKER.find(&record.content_raw)?;
+    EMBEDDED_START_RE
+        .find(&record.content_raw)
+        .map(|m| m.start())
+}
+
+/// 在 `at` 处把记录切成两段：前段截去内嵌记录之前的垃圾前缀所在行，后段重新解析。
+/// 正文已是自有数据（非 mmap 借用）时不做切分。
+fn split_at<'a>(record: &Sqllog<'a>, at: usize) -> Option<(Sqllog<'a>, Sqllog<'a>)> {
+    let Cow::Borrowed(content) = record.content_raw else {
+        return None;
+    };
+    let tail = parse_record(&content[at..]).ok()?;
+    // 垃圾前缀独占一行时截到上一个换行；与上一条记录同行时截到内嵌时间戳之前
+    let cut = memchr::memrchr(b'\n', &content[..at]).unwrap_or(at);
+    let mut head = record.clone();
+    head.content_raw = Cow::Borrowed(content[..cut].trim_ascii_end());
+    Some((head, tail))
+}
+
+fn invalid(record: &Sqllog<'_>, reason: &str) -> ParseError {
+    let tag = record
+        .tag
+        .as_ref()
+        .map_or_else(String::new, |t| format!("[{t}] "));
+    ParseError::InvalidFormat {
+        raw: format!(
+            "{reason}: {} ({}) {tag}{}",
+            record.ts,
+            record.meta_raw,
+            String::from_utf8_lossy(&record.content_raw)
+        ),
+    }
+}
+
+/// 按解析容错级别包装记录迭代器，产出 `(记录, 是否经过修复)`
+#[derive(Debug)]
+pub struct Recovering<'a, I: Iterator<Item = std::result::Result<Sqllog<'a>, ParseError>>> {
+    inner: Peekable<I>,
+    mode: ParseMode,
+    /// 丢弃缺少 EXECTIME 后缀的末条记录（仍在写入的文件，见 `sqllog.active_file`）
+    hold_tail: bool,
+    held_tail: bool,
+    /// 是否已越过首条记录：解析器只在时间戳行首切分记录，时间戳损坏只可能出现在首条
+    past_first: bool,
+    /// 从上一条记录正文中切分出、尚未产出的记录
+    pending: VecDeque<Sqllog<'a>>,
+}
+
+impl<'a, I> Recovering<'a, I>
+where
+    I: Iterator<Item = std::result::Result<Sqllog<'a>, ParseError>>,
+{
+    pub fn new(inner: I, mode: ParseMode) -> Self {
+        Self {
+            inner: inner.peekable(),
+            mode,
+            hold_tail: false,
+            held_tail: false,
+            past_first: false,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// 是否丢弃不完整的末条记录
+    #[must_use]
+    pub fn holding_tail(mut self, hold: bool) -> Self {
+        self.hold_tail = hold;
+        self
+    }
+
+    /// 末条记录是否因不完整而被丢弃
+    pub fn held_tail(&self) -> bool {
+        self.held_tail
+    }
+
+    fn strict(
+        record: Sqllog<'a>,
+        first: bool,
+    ) -> std::result::Result<(Sqllog<'a>, bool), ParseError> {
+        if first && !is_timestamp(&record.ts) {
+            Err(invalid(&record, "malformed timestamp"))
+        } else if embedded_start(&record).is_some() {
+            Err(invalid(&record, "embedded record"))
+        } else if missing_indicators(&record) {
+            Err(invalid(&record, "missing EXECTIME"))
+        } else if malformed_exec_id(&record) {
+            Err(invalid(&record, "malformed EXEC_ID"))
+        } else {
+            Ok((record, false))
+        }
+    }
+
+    /// 切出正文中的内嵌记录排队，返回截断后的前段
+    fn lenient(
+        &mut self,
+        mut record: Sqllog<'a>,
+        recovered: bool,
+        first: bool,
+    ) -> std::result::Result<(Sqllog<'a>, bool), ParseError> {
+        if let Some((head, tail)) = embedded_start(&record).and_then(|at| split_at(&record, at)) {
+            record = head;
+            self.pending.push_back(tail);
+        }
+        if !first || is_timestamp(&record.ts) {
+            let flagged = recovered || missing_indicators(&record);
+            Ok((record, flagged))
+        } else {
+            Err(invalid(&record, "malformed timestamp"))
+        }
+    }
+}
+
+impl<'a, I> Iterator for Recovering<'a, I>
+where
+    I: Iterator<Item = std::result::Result<Sqllog<'a>, ParseError>>,
+{
+    type Item = std::result::Result<(Sqllog<'a>, bool), ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(record) = self.pending.pop_front() {
+            return Some(self.lenient(record, true, false));
+        }
+        let first = !std::mem::replace(&mut self.past_first, true);
+        let next = self.inner.next()?;
+        if self.hold_tail && self.inner.peek().is_none() {
+            if let Ok(record) = &next {
+                if missing_indicators(record) && embedded_start(record).is_none() {
+                    self.held_tail = true;
+                    return None;
+                }
+            }
+        }
+        Some(match (next, self.mode) {
+            (Ok(record), ParseMode::Strict) => Self::strict(record, first),
+            (Ok(record), ParseMode::Lenient) => self.lenient(record, false, first),
+            (Err(e), _) => Err(e),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GOOD: &str = "2025-01-15 10:30:28.001 (EP[0] sess:0x1 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1(ms) ROWCOUNT: 1(rows) EXEC_ID: 1.";
+
+    fn run(log: &str, mode: ParseMode) -> Vec<std::result::Result<(String, String, bool), String>> {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("t.log");
+        std::fs::write(&path, log).unwrap();
+        let parser = dm_database_parser_sqllog::LogParser::from_path(&path).unwrap();
+        Recovering::new(parser.iter(), mode)
+            .map(|r| {
+                r.map(|(rec, recovered)| (rec.ts.to_string(), rec.body().to_string(), recovered))
+                    .map_err(|e| e.to_string())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_mode_values() {
+        assert_eq!(ParseMode::parse("strict").unwrap(), ParseMode::Strict);
+        assert_eq!(ParseMode::default(), ParseMode::Lenient);
+        let err = ParseMode::parse("loose").unwrap_err();
+        assert!(err.to_string().contains("sqllog.parse_mode"));
+    }
+
+    #[test]
+    fn test_lenient_splits_garbage_prefixed_record() {
+        let second = GOOD
+            .replace("SELECT 1", "SELECT 2")
+            .replace(":28.001", ":29.002");
+        // 崩溃残留的 NUL 填充 + 下一条记录，被解析器拼进了上一条记录
+        let log = format!("{GOOD}\n\0\0\0{second}\n{GOOD}\n");
+        let out = run(&log, ParseMode::Lenient);
+        assert_eq!(out.len(), 3, "{out:?}");
+        // 前段截掉垃圾行后与完好记录一致
+        assert_eq!(out[0], out[2]);
+        assert!(!out[0].as_ref().unwrap().2);
+        let (ts, body, recovered) = out[1].clone().unwrap();
+        assert_eq!(ts, "2025-01-15 10:30:29.002");
+        assert!(body.starts_with("SELECT 2"), "{body}");
+        assert!(recovered);
+
+        let strict = run(&log, ParseMode::Strict);
+        assert_eq!(strict.len(), 2);
+        assert!(strict[0].as_ref().unwrap_err().contains("embedded record"));
+    }
+
+    #[test]
+    fn test_missing_exectime_tolerated_or_rejected() {
+        let truncated = "2025-01-15 10:30:28.001 (EP[0] sess:0x1 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 1";
+        let log = format!("{truncated}\n{GOOD}\n");
+        let out = run(&log, ParseMode::Lenient);
+        assert!(out[0].as_ref().unwrap().2);
+        assert!(!out[1].as_ref().unwrap().2);
+
+        let strict = run(&log, ParseMode::Strict);
+        assert!(strict[0].as_ref().unwrap_err().contains("missing EXECTIME"));
+        assert!(strict[1].is_ok());
+    }
+
+    #[test]
+    fn test_exec_id_overflow_and_stray_characters() {
+        assert_eq!(lenient_exec_id(b"42"), Some((42, false)));
+        assert_eq!(lenient_exec_id(b"-7"), Some((-7, false)));
+        assert_eq!(lenient_exec_id(b"42,"), Some((42, false)));
+        assert_eq!(lenient_exec_id(b"#42"), Some((42, true)));
+        assert_eq!(
+            lenient_exec_id(b"99999999999999999999"),
+            Some((i64::MAX, true))
+        );
+        assert_eq!(lenient_exec_id(b"n/a"), None);
+
+        let overflow = GOOD.replace("EXEC_ID: 1.", "EXEC_ID: 18446744073709551616.");
+        let stray = GOOD.replace("EXEC_ID: 1.", "EXEC_ID: '12'.");
+        let log = format!("{GOOD}\n{overflow}\n{stray}\n");
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("t.log");
+        std::fs::write(&path, &log).unwrap();
+        let parser = dm_database_parser_sqllog::LogParser::from_path(&path).unwrap();
+        let fixed: Vec<(i64, bool)> = parser
+            .iter()
+            .flatten()
+            .map(|r| {
+                let mut pm = r.parse_performance_metrics();
+                let coerced = coerce_exec_id(&r, &mut pm);
+                (pm.exec_id, coerced)
+            })
+            .collect();
+        assert_eq!(fixed, [(1, false), (i64::MAX, true), (12, true)]);
+
+        let strict = run(&log, ParseMode::Strict);
+        assert!(strict[0].is_ok());
+        assert!(
+            strict[1]
+                .as_ref()
+                .unwrap_err()
+                .contains("malformed EXEC_ID")
+        );
+        assert!(strict[2].is_err());
+    }
+
+    #[test]
+    fn test_hold_tail_drops_incomplete_last_record() {
+        let truncated = "2025-01-15 10:30:29.001 (EP[0] sess:0x1 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 2 FR";
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("t.log");
+        std::fs::write(&path, format!("{GOOD}\n{truncated}")).unwrap();
+        let parser = dm_database_parser_sqllog::LogParser::from_path(&path).unwrap();
+        let mut it = Recovering::new(parser.iter(), ParseMode::Strict).holding_tail(true);
+        assert_eq!(it.by_ref().flatten().count(), 1);
+        assert!(it.held_tail());
+
+        // 末条完整时不丢弃
+        std::fs::write(&path, format!("{GOOD}\n{GOOD}\n")).unwrap();
+        let parser = dm_database_parser_sqllog::LogParser::from_path(&path).unwrap();
+        let mut it = Recovering::new(parser.iter(), ParseMode::Strict).holding_tail(true);
+        assert_eq!(it.by_ref().count(), 2);
+        assert!(!it.held_tail());
+    }
+
+    #[test]
+    fn test_malformed_leading_timestamp_is_error() {
+        let log = format!("\0\0{GOOD}\n{GOOD}\n");
+        for mode in [ParseMode::Lenient, ParseMode::Strict] {
+            let out = run(&log, mode);
+            assert!(out[0].as_ref().unwrap_err().contains("malformed timestamp"));
+            assert!(out[1].is_ok());
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//! Windows 路径处理：
+//!
+//! - 输入：配置或命令行中的扩展长度路径（`\\?\C:\logs`、`\\?\UNC\srv\share\logs`）去掉前缀，
+//!   还原为普通路径 / UNC 共享路径，避免其中的 `?` 被当作 glob 通配符；
+//! - 打开文件：超过 `MAX_PATH`（260 字符）的路径补上扩展长度前缀，UNC 共享改写为 `\\?\UNC\` 形式。
+//!
+//! 字符串层面的改写与平台无关（便于测试）；[`normalize_input`] / [`for_io`] 只在 Windows 上生效，
+//! 其他平台原样返回。
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+
+/// Windows 经典 API 的路径长度上限（含结尾 NUL）
+const MAX_PATH: usize = 260;
+
+const VERBATIM: &str = r"\\?\";
+const VERBATIM_UNC: &str = r"\\?\UNC\";
+
+/// 去掉扩展长度前缀：`\\?\C:\x` → `C:\x`，`\\?\UNC\srv\share\x` → `\\srv\share\x`
+fn strip_verbatim(path: &str) -> Cow<'_, str> {
+    if let Some(rest) = path.strip_prefix(VERBATIM_UNC) {
+        Cow::Owned(format!(r"\\{rest}"))
+    } else if let Some(rest) = path.strip_prefix(VERBATIM) {
+        Cow::Borrowed(rest)
+    } else {
+        Cow::Borrowed(path)
+    }
+}
+
+/// 超长绝对路径补扩展长度前缀。扩展长度路径不做分隔符转换，`/` 统一换成 `\`；
+/// 未超长、已带前缀或非绝对路径（盘符 / UNC）时返回 `None`
+fn extend_long(path: &str) -> Option<String> {
+    if path.len() < MAX_PATH || path.starts_with(VERBATIM) {
+        return None;
+    }
+    let path = path.replace('/', "\\");
+    if let Some(share) = path.strip_prefix(r"\\") {
+        return Some(format!("{VERBATIM_UNC}{share}"));
+    }
+    let b = path.as_bytes();
+    let drive_absolute = b.len() > 2 && b[0].is_ascii_alphabetic() && b[1] == b':' && b[2] == b'\\';
+    drive_absolute.then(|| format!("{VERBATIM}{path}"))
+}
+
+/// 规范化用户给出的输入路径（Windows 上去掉扩展长度前缀）
+#[must_use]
+pub fn normalize_input(path: &Path) -> PathBuf {
+    if !cfg!(windows) {
+        return path.to_path_buf();
+    }
+    match path.to_str().map(strip_verbatim) {
+        Some(Cow::Owned(s)) => PathBuf::from(s),
+        Some(Cow::Borrowed(s)) => PathBuf::from(s),
+        None => path.to_path_buf(),
+    }
+}
+
+/// 打开 / 创建文件时使用的路径：Windows 上超长路径补扩展长度前缀（相对路径先转为绝对路径）
+#[must_use]
+pub fn for_io(path: &Path) -> Cow<'_, Path> {
+    if !cfg!(windows) || path.as_os_str().len() < MAX_PATH {
+        return Cow::Borrowed(path);
+    }
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    match absolute.to_str().and_then(extend_long) {
+        Some(long) => Cow::Owned(PathBuf::from(long)),
+        None => Cow::Owned(absolute),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_verbatim() {
+        assert_eq!(strip_verbatim(r"\\?\C:\dm\log"), r"C:\dm\log");
+        assert_eq!(
+            strip_verbatim(r"\\?\UNC\dbhost\logs\*.log"),
+            r"\\dbhost\logs\*.log"
+        );
+        assert_eq!(strip_verbatim(r"\\dbhost\logs"), r"\\dbhost\logs");
+        assert_eq!(strip_verbatim("sqllogs/*.log"), "sqllogs/*.log");
+    }
+
+    #[test]
+    fn test_extend_long() {
+        let long_dir = "d".repeat(300);
+        assert_eq!(
+            extend_long(&format!("C:/out/{long_dir}/a.csv")),
+            Some(format!(r"\\?\C:\out\{long_dir}\a.csv"))
+        );
+        assert_eq!(
+            extend_long(&format!(r"\\srv\share\{long_dir}")),
+            Some(format!(r"\\?\UNC\srv\share\{long_dir}"))
+        );
+        // 未超长、已带前缀、相对路径不改写
+        assert_eq!(extend_long(r"C:\out\a.csv"), None);
+        assert_eq!(extend_long(&format!(r"\\?\C:\{long_dir}")), None);
+        assert_eq!(extend_long(&format!(r"out\{long_dir}")), None);
+    }
+
+    #[test]
+    fn test_non_windows_passthrough() {
+        if cfg!(windows) {
+            return;
+        }
+        let p = Path::new(r"\\?\C:\dm\log");
+        assert_eq!(normalize_input(p), p);
+        let long = PathBuf::from("d".repeat(300));
+        assert!(matches!(for_io(&long), Cow::Borrowed(_)));
+    }
+}
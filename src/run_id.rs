@@ -0,0 +1,95 @@
+//! 运行标识：每次运行开始时分配一个（UUID v4 文本形式），写入应用日志、完成摘要、
+//! 通知与可选的 `run_id` 派生列，共享主机上多次运行的输出可据此区分。
+//!
+//! 同一进程内的多次运行（`run-all` 的各个任务、库调用方重复调用 `api::run_export`）各自分配标识，
+//! 经 `RunContext` 传到派生列与装载记录。应用日志按行带上最近开始的运行的标识；
+//! 首次运行沿用进程启动后日志已使用的标识，加载配置等前置日志因此与第一次运行一致。
+//!
+//! 设置环境变量 `SQLLOG2DB_RUN_ID` 可沿用外部调度器分配的标识（进程内的所有运行共用该值）。
+use std::sync::{Arc, LazyLock, Mutex, PoisonError};
+
+/// 覆盖运行标识的环境变量
+pub const RUN_ID_ENV: &str = "SQLLOG2DB_RUN_ID";
+
+/// 当前标识；`claimed` 表示已被某次运行占用，下一次运行需重新分配
+struct Current {
+    id: Arc<str>,
+    claimed: bool,
+}
+
+static CURRENT: LazyLock<Mutex<Current>> = LazyLock::new(|| {
+    Mutex::new(Current {
+        id: assign(),
+        claimed: false,
+    })
+});
+
+/// 外部指定的标识，否则随机生成
+fn assign() -> Arc<str> {
+    std::env::var(RUN_ID_ENV)
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(generate)
+        .into()
+}
+
+/// 为一次新运行分配标识，并作为之后日志行的当前标识
+#[must_use]
+pub fn begin() -> Arc<str> {
+    let mut current = CURRENT.lock().unwrap_or_else(PoisonError::into_inner);
+    if current.claimed {
+        current.id = assign();
+    }
+    current.claimed = true;
+    Arc::clone(&current.id)
+}
+
+/// 最近开始的运行的标识（尚无运行时为进程启动后的预分配标识）
+#[must_use]
+pub fn current() -> Arc<str> {
+    Arc::clone(&CURRENT.lock().unwrap_or_else(PoisonError::into_inner).id)
+}
+
+/// 随机生成 UUID v4（版本位 4、变体位 10xx）
+fn generate() -> String {
+    let bits = fastrand::u128(..) & !(0xf << 76) & !(0x3 << 62) | (0x4 << 76) | (0x2 << 62);
+    let hex = format!("{bits:032x}");
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_uuid_v4() {
+        let id = generate();
+        assert_eq!(id.len(), 36);
+        let groups: Vec<&str> = id.split('-').collect();
+        assert_eq!(
+            groups.iter().map(|g| g.len()).collect::<Vec<_>>(),
+            [8, 4, 4, 4, 12]
+        );
+        assert!(groups[2].starts_with('4'));
+        assert!(matches!(&groups[3][..1], "8" | "9" | "a" | "b"));
+        assert_ne!(id, generate());
+    }
+
+    #[test]
+    fn test_each_run_gets_its_own_id() {
+        let first = begin();
+        let second = begin();
+        // 环境变量固定标识时各次运行共用（测试进程中通常未设置）
+        if std::env::var(RUN_ID_ENV).is_err() {
+            assert_ne!(first, second);
+        }
+    }
+}
@@ -0,0 +1,408 @@
+//! `[report]`：运行结束时写出结构化 JSON 运行报告（`report.file`），供调度系统与审计归档读取。
+//!
+//! 报告包含处理的输入文件与逐文件计数、解析错误数、各导出器的写出统计与各阶段耗时。成功、失败与被中断
+//! 的运行都会写出；失败时只有状态与错误信息。报告原子写出，写入失败只输出警告，不改变运行本身的退出码。
+//!
+//! `run --report-junit` 另写一份 `JUnit` 风格的 XML：每个输入文件一个 testcase，解析错误数超过
+//! `--junit-max-errors` 的文件判为失败，CI（如 Jenkins）可直接在界面中列出出问题的文件。
+use crate::cli::run::{ExporterStats, RunSummary};
+use crate::cli::tune::Tuning;
+use crate::color;
+use crate::error::{ConfigError, Error, FileError, Result};
+use crate::metrics::{ErrorSample, FileCounts, MetricsSnapshot};
+use crate::warnings::Warning;
+use chrono::{DateTime, Local, SecondsFormat};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// `[report]` 配置段
+#[derive(Debug, Deserialize, Clone)]
+#[non_exhaustive]
+pub struct RunReportConfig {
+    /// 报告文件路径（JSON），每次运行覆盖
+    #[serde(default = "default_file")]
+    pub file: String,
+}
+
+fn default_file() -> String {
+    "export/run_report.json".to_string()
+}
+
+impl Default for RunReportConfig {
+    fn default() -> Self {
+        Self {
+            file: default_file(),
+        }
+    }
+}
+
+impl RunReportConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.file.trim().is_empty() {
+            return Err(Error::Config(ConfigError::InvalidValue {
+                field: "report.file".to_string(),
+                value: self.file.clone(),
+                reason: "report file cannot be empty".to_string(),
+            }));
+        }
+        Ok(())
+    }
+}
+
+/// 文件数汇总
+#[derive(Debug, Serialize)]
+pub struct FileTotals {
+    /// 发现的日志文件数（含被 `--resume` 跳过的文件）
+    pub discovered: usize,
+    pub processed: u64,
+    pub skipped: usize,
+}
+
+/// 单个处理阶段的累计耗时
+#[derive(Debug, Serialize)]
+pub struct StageTiming {
+    pub stage: &'static str,
+    pub elapsed_ms: u64,
+}
+
+/// 运行报告（`report.file` 的内容）
+#[derive(Debug, Serialize)]
+pub struct RunReport<'a> {
+    pub version: &'static str,
+    pub run_id: String,
+    /// `success` / `failure` / `interrupted`（与通知 payload 一致）
+    pub status: &'static str,
+    pub error: Option<String>,
+    pub started_at: String,
+    pub finished_at: String,
+    pub elapsed_ms: u64,
+    pub config_path: &'a str,
+    pub sqllog_path: &'a str,
+    pub dry_run: bool,
+    pub parallel: bool,
+    /// 因 `--max-records` / `--max-duration` 提前结束时的原因
+    pub truncated: Option<String>,
+    pub files: FileTotals,
+    pub totals: MetricsSnapshot,
+    pub per_file: &'a [FileCounts],
+    /// 各类解析错误的次数与样例，按次数降序
+    pub parse_errors: &'a [ErrorSample],
+    pub exporters: &'a [ExporterStats],
+    /// 各处理阶段耗时，按处理顺序；并行模式下为各线程累加值
+    pub stages: Vec<StageTiming>,
+    pub warnings: &'a [Warning],
+    pub tuning: Option<&'a Tuning>,
+}
+
+impl<'a> RunReport<'a> {
+    #[must_use]
+    pub fn new(
+        config_path: &'a str,
+        sqllog_path: &'a str,
+        started: DateTime<Local>,
+        result: &'a Result<RunSummary>,
+    ) -> Self {
+        match result {
+            Ok(s) => Self::from_summary(config_path, sqllog_path, started, s),
+            Err(Error::Interrupted) => Self::build(
+                config_path,
+                sqllog_path,
+                started,
+                "interrupted",
+                None,
+                Some(Error::Interrupted.to_string()),
+            ),
+            Err(e) => Self::build(
+                config_path,
+                sqllog_path,
+                started,
+                "failure",
+                None,
+                Some(e.to_string()),
+            ),
+        }
+    }
+
+    /// 成功运行的报告
+    #[must_use]
+    pub fn from_summary(
+        config_path: &'a str,
+        sqllog_path: &'a str,
+        started: DateTime<Local>,
+        summary: &'a RunSummary,
+    ) -> Self {
+        Self::build(
+            config_path,
+            sqllog_path,
+            started,
+            "success",
+            Some(summary),
+            None,
+        )
+    }
+
+    fn build(
+        config_path: &'a str,
+        sqllog_path: &'a str,
+        started: DateTime<Local>,
+        status: &'static str,
+        summary: Option<&'a RunSummary>,
+        error: Option<String>,
+    ) -> Self {
+        let finished = Local::now();
+        #[allow(clippy::cast_possible_truncation)]
+        let ms = |d: std::time::Duration| d.as_millis() as u64;
+        let elapsed = summary.map_or_else(
+            || (finished - started).to_std().unwrap_or_default(),
+            |s| s.elapsed,
+        );
+        let stages = summary
+            .and_then(|s| s.stages.as_ref())
+            .map(|p| {
+                p.breakdown()
+                    .map(|(stage, t)| StageTiming {
+                        stage,
+                        elapsed_ms: ms(t),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            run_id: summary.map_or_else(
+                || crate::run_id::current().to_string(),
+                |s| s.run_id.clone(),
+            ),
+            status,
+            error,
+            started_at: started.to_rfc3339_opts(SecondsFormat::Millis, false),
+            finished_at: finished.to_rfc3339_opts(SecondsFormat::Millis, false),
+            elapsed_ms: ms(elapsed),
+            config_path,
+            sqllog_path,
+            dry_run: summary.is_some_and(|s| s.dry_run),
+            parallel: summary.is_some_and(|s| s.parallel),
+            truncated: summary.and_then(|s| s.truncated).map(|t| t.to_string()),
+            files: FileTotals {
+                discovered: summary.map_or(0, |s| s.files),
+                processed: summary.map_or(0, |s| s.metrics.files),
+                skipped: summary.map_or(0, |s| s.skipped_files),
+            },
+            totals: summary.map(|s| s.metrics).unwrap_or_default(),
+            per_file: summary.map_or(&[], |s| &s.per_file),
+            parse_errors: summary.map_or(&[], |s| &s.parse_errors),
+            exporters: summary.map_or(&[], |s| &s.exporters),
+            stages,
+            warnings: summary.map_or(&[], |s| &s.warnings),
+            tuning: summary.and_then(|s| s.tuning.as_ref()),
+        }
+    }
+
+    /// 原子写出到 `path`，按需创建上级目录
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let write_err = |e: std::io::Error| {
+            Error::File(FileError::WriteFailed {
+                path: path.to_path_buf(),
+                reason: e.to_string(),
+            })
+        };
+        if let Some(dir) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(write_err)?;
+        }
+        let mut json = serde_json::to_vec_pretty(self).map_err(|e| write_err(e.into()))?;
+        json.push(b'\n');
+        crate::temp::write_atomic(path, &json).map_err(write_err)
+    }
+}
+
+/// 写出本次运行的报告；失败以警告输出到 stderr 与日志
+pub fn report_run(
+    cfg: &RunReportConfig,
+    config_path: &str,
+    sqllog_path: &str,
+    started: DateTime<Local>,
+    result: &Result<RunSummary>,
+) {
+    let report = RunReport::new(config_path, sqllog_path, started, result);
+    match report.write(Path::new(&cfg.file)) {
+        Ok(()) => info!("Run report written: {}", cfg.file),
+        Err(e) => {
+            warn!("Failed to write run report: {e}");
+            eprintln!(
+                "{} failed to write run report: {e}",
+                color::yellow("Warning:")
+            );
+        }
+    }
+}
+
+/// 渲染 `JUnit` 风格的 XML：每个处理过的文件一个 testcase，解析错误数超过 `max_errors` 时失败
+#[must_use]
+pub fn render_junit(summary: &RunSummary, max_errors: u64) -> String {
+    let failures = summary
+        .per_file
+        .iter()
+        .filter(|f| f.errors > max_errors)
+        .count();
+    let tests = summary.per_file.len();
+    let time = summary.elapsed.as_secs_f64();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"sqllog2db\" tests=\"{tests}\" failures=\"{failures}\" time=\"{time:.3}\">"
+    );
+    let _ = writeln!(
+        xml,
+        "  <testsuite name=\"sqllog2db run {}\" tests=\"{tests}\" failures=\"{failures}\" errors=\"0\" skipped=\"0\" time=\"{time:.3}\">",
+        escape(&summary.run_id)
+    );
+    for f in &summary.per_file {
+        #[allow(clippy::cast_precision_loss)]
+        let secs = f.elapsed_ms as f64 / 1000.0;
+        let _ = write!(
+            xml,
+            "    <testcase classname=\"sqllog2db.parse\" name=\"{}\" time=\"{secs:.3}\"",
+            escape(&f.path)
+        );
+        if f.errors > max_errors {
+            let _ = writeln!(
+                xml,
+                ">\n      <failure type=\"parse_errors\" message=\"{} parse errors (threshold {max_errors})\">{} of {} records failed to parse; see the application log for details</failure>\n    </testcase>",
+                f.errors, f.errors, f.parsed
+            );
+        } else {
+            xml.push_str("/>\n");
+        }
+    }
+    let _ = writeln!(
+        xml,
+        "    <system-out>records exported: {}; parse errors: {}; skipped files: {}</system-out>",
+        summary.metrics.exported, summary.metrics.errors, summary.skipped_files
+    );
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+/// 原子写出 `JUnit` 报告，按需创建上级目录
+pub fn write_junit(path: &Path, summary: &RunSummary, max_errors: u64) -> Result<()> {
+    let write_err = |e: std::io::Error| {
+        Error::File(FileError::WriteFailed {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        })
+    };
+    if let Some(dir) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(write_err)?;
+    }
+    crate::temp::write_atomic(path, render_junit(summary, max_errors).as_bytes()).map_err(write_err)
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::stages::{Stage, StageProfile};
+
+    #[test]
+    fn test_report_success_and_failure() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("nested/run_report.json");
+        let mut stages = StageProfile::default();
+        stages.add(Stage::Parse, std::time::Duration::from_millis(40));
+        let result = Ok(RunSummary {
+            files: 2,
+            records: 5,
+            elapsed: std::time::Duration::from_millis(1500),
+            stages: Some(stages),
+            metrics: MetricsSnapshot {
+                files: 2,
+                exported: 5,
+                errors: 1,
+                ..MetricsSnapshot::default()
+            },
+            per_file: vec![FileCounts {
+                index: 1,
+                path: "a.log".to_string(),
+                records: 5,
+                errors: 1,
+                ..FileCounts::default()
+            }],
+            exporters: vec![ExporterStats {
+                name: "CSV".to_string(),
+                target: "out.csv".to_string(),
+                exported: 5,
+                flush_operations: Some(1),
+                duplicates: 0,
+                flush_latency: None,
+                skipped: 0,
+                failed: 0,
+            }],
+            ..RunSummary::default()
+        });
+        RunReport::new("config.toml", "sqllogs", Local::now(), &result)
+            .write(&path)
+            .unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json["status"], "success");
+        assert_eq!(json["elapsed_ms"], 1500);
+        assert_eq!(json["files"]["processed"], 2);
+        assert_eq!(json["totals"]["errors"], 1);
+        assert_eq!(json["per_file"][0]["path"], "a.log");
+        assert!(json["per_file"][0].get("index").is_none());
+        assert_eq!(json["exporters"][0]["flush_operations"], 1);
+        assert_eq!(json["stages"][1]["stage"], "parse");
+        assert_eq!(json["stages"][1]["elapsed_ms"], 40);
+
+        let failed: Result<RunSummary> = Err(Error::Interrupted);
+        RunReport::new("config.toml", "sqllogs", Local::now(), &failed)
+            .write(&path)
+            .unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json["status"], "interrupted");
+        assert!(json["per_file"].as_array().unwrap().is_empty());
+        assert!(json["error"].is_string());
+    }
+
+    #[test]
+    fn test_junit_fails_files_over_threshold() {
+        let file = |path: &str, errors| FileCounts {
+            path: path.to_string(),
+            parsed: 10,
+            errors,
+            elapsed_ms: 1500,
+            ..FileCounts::default()
+        };
+        let summary = RunSummary {
+            per_file: vec![file("a.log", 0), file("b&c.log", 2), file("d.log", 1)],
+            ..RunSummary::default()
+        };
+        let xml = render_junit(&summary, 1);
+        assert!(xml.contains("tests=\"3\" failures=\"1\""), "{xml}");
+        assert!(xml.contains("name=\"a.log\" time=\"1.500\"/>"), "{xml}");
+        assert!(xml.contains("name=\"b&amp;c.log\""), "{xml}");
+        assert!(
+            xml.contains("message=\"2 parse errors (threshold 1)\""),
+            "{xml}"
+        );
+        assert_eq!(xml.matches("<failure").count(), 1);
+        assert_eq!(render_junit(&summary, 0).matches("<failure").count(), 2);
+    }
+}
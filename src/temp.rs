@@ -0,0 +1,166 @@
+//! 临时产物的统一命名与清理（`clean-temp`）。
+//!
+//! 运行过程中创建的临时文件 / 目录都经由本模块命名，正常结束或出错返回时随句柄 drop 删除；
+//! 进程崩溃或被强杀时残留的产物可按命名约定识别：
+//!
+//! - 并行 CSV 分片目录：输出文件旁的 `.<stem>.sqllog2db-parts-XXXXXX`（父目录不可写时位于系统临时目录）；
+//! - 解压 / 标准输入落盘文件：系统临时目录下的 `sqllog2db-spool-XXXXXX.log`；
+//! - 原子写出的暂存文件：目标文件旁的 `.<name>.XXXXXX.sqllog2db-tmp`。
+//!
+//! 名称带随机后缀，同一输出的重试（包括容器内 PID 相同的重跑）不会与残留产物冲突。
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tempfile::{Builder, NamedTempFile, TempDir};
+
+const PARTS_MARKER: &str = ".sqllog2db-parts-";
+const SPOOL_PREFIX: &str = "sqllog2db-spool-";
+const STAGING_SUFFIX: &str = ".sqllog2db-tmp";
+
+/// 并行 CSV 分片目录：优先建在输出文件旁（避免跨设备拷贝），父目录不可写时（如 `/dev/null`）
+/// 退回系统临时目录
+pub fn parts_dir(output: &Path) -> io::Result<TempDir> {
+    let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+    let prefix = format!(".{stem}{PARTS_MARKER}");
+    let parent = output.parent().filter(|p| !p.as_os_str().is_empty());
+    if let Some(parent) = parent {
+        std::fs::create_dir_all(parent)?;
+    }
+    Builder::new()
+        .prefix(&prefix)
+        .tempdir_in(parent.unwrap_or(Path::new(".")))
+        .or_else(|_| Builder::new().prefix(&prefix).tempdir())
+}
+
+/// 解压 / 标准输入落盘用的临时文件（系统临时目录）
+pub fn spool_file() -> io::Result<NamedTempFile> {
+    Builder::new()
+        .prefix(SPOOL_PREFIX)
+        .suffix(".log")
+        .tempfile()
+}
+
+/// 原子写出：先写到目标旁的暂存文件，再改名为 `target`，读取方不会看到写了一半的内容
+pub fn write_atomic(target: &Path, contents: &[u8]) -> io::Result<()> {
+    let name = target.file_name().unwrap_or_default().to_string_lossy();
+    let dir = target
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let mut staging = Builder::new()
+        .prefix(&format!(".{name}."))
+        .suffix(STAGING_SUFFIX)
+        .tempfile_in(dir)?;
+    io::Write::write_all(&mut staging, contents)?;
+    staging.persist(target).map(drop).map_err(|e| e.error)
+}
+
+/// 旧版本的分片目录：`.<stem>_parts_<pid>`
+fn is_legacy_parts_dir(name: &str) -> bool {
+    name.starts_with('.')
+        && name
+            .rsplit_once("_parts_")
+            .is_some_and(|(_, pid)| !pid.is_empty() && pid.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// 按命名约定判断是否为本工具的临时产物
+#[must_use]
+pub fn is_artifact(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    if path.is_dir() {
+        name.contains(PARTS_MARKER) || is_legacy_parts_dir(name)
+    } else {
+        (name.starts_with(SPOOL_PREFIX) && path.extension().is_some_and(|e| e == "log"))
+            || name.ends_with(STAGING_SUFFIX)
+    }
+}
+
+/// 最近一次修改距今的时长；目录取自身及直接子项中最新的修改时间（分片仍在写入时不算陈旧）
+fn idle_for(path: &Path) -> Option<Duration> {
+    let modified = |p: &Path| std::fs::metadata(p).and_then(|m| m.modified()).ok();
+    let mut newest = modified(path)?;
+    if path.is_dir() {
+        for entry in std::fs::read_dir(path).ok()?.flatten() {
+            newest = newest.max(modified(&entry.path()).unwrap_or(newest));
+        }
+    }
+    Some(SystemTime::now().duration_since(newest).unwrap_or_default())
+}
+
+/// 在 `dirs` 中查找闲置超过 `min_age` 的临时产物（不递归），按路径排序去重
+#[must_use]
+pub fn find_stale(dirs: &[PathBuf], min_age: Duration) -> Vec<PathBuf> {
+    let mut found: Vec<PathBuf> = dirs
+        .iter()
+        .filter_map(|d| std::fs::read_dir(d).ok())
+        .flat_map(|entries| entries.flatten().map(|e| e.path()))
+        .filter(|p| is_artifact(p) && idle_for(p).is_some_and(|idle| idle >= min_age))
+        .collect();
+    found.sort();
+    found.dedup();
+    found
+}
+
+/// 删除临时产物（目录递归删除）
+pub fn remove(path: &Path) -> io::Result<()> {
+    if path.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_artifact_naming() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let output = dir.path().join("out.csv");
+        let parts = parts_dir(&output).unwrap();
+        assert!(is_artifact(parts.path()));
+        // 重试时名称不冲突
+        let retry = parts_dir(&output).unwrap();
+        assert_ne!(parts.path(), retry.path());
+
+        let spool = spool_file().unwrap();
+        assert!(is_artifact(spool.path()));
+
+        let legacy = dir.path().join(".out_parts_4242");
+        std::fs::create_dir(&legacy).unwrap();
+        assert!(is_artifact(&legacy));
+        assert!(!is_artifact(&output));
+        std::fs::write(&output, "").unwrap();
+        assert!(!is_artifact(&output));
+        assert!(!is_artifact(&dir.path().join("sqllog2db-spool-x.csv")));
+    }
+
+    #[test]
+    fn test_write_atomic_and_find_stale() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let target = dir.path().join("m.prom");
+        write_atomic(&target, b"x 1\n").unwrap();
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "x 1\n");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        // 崩溃残留：暂存文件与分片目录
+        let staging = dir.path().join(".m.prom.abc123.sqllog2db-tmp");
+        std::fs::write(&staging, "").unwrap();
+        let parts = parts_dir(&dir.path().join("out.csv")).unwrap().keep();
+        std::fs::write(parts.join("00000000.csv"), "a\n").unwrap();
+
+        let dirs = [dir.path().to_path_buf()];
+        assert!(find_stale(&dirs, Duration::from_secs(3600)).is_empty());
+        let mut expected = vec![staging, parts];
+        expected.sort();
+        assert_eq!(find_stale(&dirs, Duration::ZERO), expected);
+        for p in &expected {
+            remove(p).unwrap();
+        }
+        assert!(find_stale(&dirs, Duration::ZERO).is_empty());
+        assert!(target.exists());
+    }
+}
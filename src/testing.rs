@@ -0,0 +1,109 @@
+//! 测试夹具：确定性的合成 `Sqllog` 记录，供自定义导出器的作者编写单元测试，
+//! 无需手工拼装解析器 crate 的结构体（`Sqllog` 含私有字段，无法用结构体字面量构造）。
+//!
+//! 同一序号总是生成同一条记录；用户 / 应用 / IP / SQL 长度按序号轮换，贴近真实分布。
+//! [`fixture_log`] 输出与 [`sqllog_fixtures`] 逐条对应的日志文本，可写入文件交给 `LogParser` 或 `run`。
+use dm_database_parser_sqllog::Sqllog;
+use std::borrow::Cow;
+use std::fmt::Write as _;
+
+/// 第 `i` 条记录的各组成部分：时间戳、元数据、标签、正文（SQL + 性能指标）
+fn fixture_parts(i: usize) -> (String, String, &'static str, String) {
+    let ts = format!(
+        "2025-01-15 10:{:02}:{:02}.{:03}",
+        (i / 60_000) % 60,
+        (i / 1000) % 60,
+        i % 1000
+    );
+    let meta = format!(
+        "EP[{}] sess:0x{i:04x} user:USER{} trxid:{i} stmt:0x1 appname:App{} ip:10.0.{}.{}",
+        i % 2,
+        i % 17,
+        i % 5,
+        (i / 256) % 256,
+        i % 256,
+    );
+    let content = format!(
+        "SELECT id, name, amount FROM orders_{} WHERE id = {i} AND status IN ('A', 'B'){}. EXECTIME: {}(ms) ROWCOUNT: {}(rows) EXEC_ID: {i}.",
+        i % 11,
+        " AND note IS NOT NULL".repeat(i % 4),
+        (i * 13) % 5000,
+        i % 1000,
+    );
+    (ts, meta, "SEL", content)
+}
+
+/// 第 `i` 条合成记录（`'static`：各字段为自有数据，不依赖日志文件的生命周期）
+#[must_use]
+pub fn sqllog_fixture_at(i: usize) -> Sqllog<'static> {
+    let (ts, meta, tag, content) = fixture_parts(i);
+    let mut record = Sqllog::default();
+    record.ts = Cow::Owned(ts);
+    record.meta_raw = Cow::Owned(meta);
+    record.content_raw = Cow::Owned(content.into_bytes());
+    record.tag = Some(Cow::Borrowed(tag));
+    record
+}
+
+/// 一条典型的合成记录（序号 0）
+#[must_use]
+pub fn sqllog_fixture() -> Sqllog<'static> {
+    sqllog_fixture_at(0)
+}
+
+/// 序号 `0..count` 的合成记录
+pub fn sqllog_fixtures(count: usize) -> impl Iterator<Item = Sqllog<'static>> {
+    (0..count).map(sqllog_fixture_at)
+}
+
+/// 与 [`sqllog_fixtures`] 逐条对应的日志文本（每条一行）
+#[must_use]
+pub fn fixture_log(count: usize) -> String {
+    let mut buf = String::with_capacity(count * 200);
+    for i in 0..count {
+        let (ts, meta, tag, content) = fixture_parts(i);
+        let _ = writeln!(buf, "{ts} ({meta}) [{tag}] {content}");
+    }
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dm_database_parser_sqllog::LogParser;
+
+    #[test]
+    fn test_fixtures_match_parsed_log() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = dir.path().join("fixture.log");
+        std::fs::write(&log, fixture_log(50)).unwrap();
+        let parser = LogParser::from_path(&log).unwrap();
+        let parsed: Vec<_> = parser.iter().filter_map(std::result::Result::ok).collect();
+        assert_eq!(parsed.len(), 50);
+
+        for (built, parsed) in sqllog_fixtures(50).zip(&parsed) {
+            assert_eq!(built.ts, parsed.ts);
+            assert_eq!(built.tag, parsed.tag);
+            assert_eq!(built.parse_meta(), parsed.parse_meta());
+            let (a, b) = (
+                built.parse_performance_metrics(),
+                parsed.parse_performance_metrics(),
+            );
+            assert_eq!(
+                (a.exec_id, a.rowcount, a.sql),
+                (b.exec_id, b.rowcount, b.sql)
+            );
+        }
+    }
+
+    #[test]
+    fn test_sqllog_fixture_is_deterministic() {
+        let record = sqllog_fixture();
+        assert_eq!(record, sqllog_fixture());
+        let meta = record.parse_meta();
+        assert_eq!(meta.username, "USER0");
+        assert_eq!(meta.client_ip, "10.0.0.0");
+        assert!(record.body().starts_with("SELECT id, name"));
+        assert_ne!(sqllog_fixture_at(1), record);
+    }
+}
@@ -0,0 +1,136 @@
+//! 运行警告通道：不致命但需要运维关注的问题（跳过的文件、不完整或解析失败的记录、被截断的运行、
+//! 被忽略的导出器等）在产生处写入应用日志的同时登记到 [`Warnings`]，
+//! 运行结束后汇总在完成摘要、`report` 模板与通知负载中，不会淹没在滚动的日志里。
+//!
+//! 并行任务共享同一个 [`Warnings`]，与 [`crate::metrics::Metrics`] 一样按引用传递。
+use log::warn;
+use serde::Serialize;
+use std::sync::{Mutex, PoisonError};
+
+/// 警告类别（JSON 中为 `snake_case`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningKind {
+    /// 输入问题：未找到日志文件、标准输入下忽略 `--resume` 等
+    Input,
+    /// 跳过的文件（`--resume` 已处理、仍在写入）
+    SkippedFile,
+    /// 文件校验和与清单不符，记录全部拒绝
+    ChecksumMismatch,
+    /// 解析失败的记录
+    ParseErrors,
+    /// 仍在写入的文件末尾不完整、被暂缓的记录
+    IncompleteRecord,
+    /// 运行因 `--max-records` / `--max-duration` 提前结束，输出不完整
+    Truncated,
+    /// 已配置但未使用的导出器
+    ExporterIgnored,
+    /// 溢出或带杂散字符、经容错修正的 `EXEC_ID`
+    ExecIdCoerced,
+    /// 写入失败、按导出器的 `on_error` 跳过的记录
+    ExportSkipped,
+}
+
+impl WarningKind {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Input => "input",
+            Self::SkippedFile => "skipped_file",
+            Self::ChecksumMismatch => "checksum_mismatch",
+            Self::ParseErrors => "parse_errors",
+            Self::IncompleteRecord => "incomplete_record",
+            Self::Truncated => "truncated",
+            Self::ExporterIgnored => "exporter_ignored",
+            Self::ExecIdCoerced => "exec_id_coerced",
+            Self::ExportSkipped => "export_skipped",
+        }
+    }
+}
+
+impl std::fmt::Display for WarningKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 一条运行警告
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Warning {
+    pub kind: WarningKind,
+    pub message: String,
+}
+
+/// 线程安全的警告收集器
+#[derive(Debug, Default)]
+pub struct Warnings {
+    items: Mutex<Vec<Warning>>,
+}
+
+impl Warnings {
+    /// 登记一条警告，同时以 `warn` 级别写入应用日志
+    pub fn push(&self, kind: WarningKind, message: impl Into<String>) {
+        let message = message.into();
+        warn!("{message}");
+        self.items
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(Warning { kind, message });
+    }
+
+    /// 取出全部警告：按类别分组，同类保持登记顺序（并行任务的登记顺序不确定，分组后输出稳定）
+    #[must_use]
+    pub fn into_vec(self) -> Vec<Warning> {
+        let mut items = self
+            .items
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+        items.sort_by_key(|w| w.kind);
+        items
+    }
+}
+
+/// 按类别计数（类别顺序同 [`WarningKind`]）
+#[must_use]
+pub fn count_by_kind(warnings: &[Warning]) -> Vec<(WarningKind, usize)> {
+    let mut counts: Vec<(WarningKind, usize)> = Vec::new();
+    for w in warnings {
+        match counts.last_mut() {
+            Some((kind, n)) if *kind == w.kind => *n += 1,
+            _ => counts.push((w.kind, 1)),
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_warnings_grouped_by_kind() {
+        let w = Warnings::default();
+        w.push(WarningKind::Truncated, "max-records reached");
+        w.push(WarningKind::SkippedFile, "a.log skipped");
+        w.push(WarningKind::SkippedFile, "b.log skipped");
+        let items = w.into_vec();
+        let kinds: Vec<_> = items.iter().map(|w| w.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                WarningKind::SkippedFile,
+                WarningKind::SkippedFile,
+                WarningKind::Truncated
+            ]
+        );
+        assert_eq!(items[1].message, "b.log skipped");
+        assert_eq!(
+            count_by_kind(&items),
+            [(WarningKind::SkippedFile, 2), (WarningKind::Truncated, 1)]
+        );
+        assert_eq!(
+            serde_json::to_string(&items[2]).unwrap(),
+            r#"{"kind":"truncated","message":"max-records reached"}"#
+        );
+    }
+}
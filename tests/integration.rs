@@ -6,11 +6,8 @@ use dm_database_sqllog2db::cli::run::handle_run;
 use dm_database_sqllog2db::cli::show_config::handle_show_config;
 use dm_database_sqllog2db::cli::stats::handle_stats;
 use dm_database_sqllog2db::cli::validate::handle_validate;
-use dm_database_sqllog2db::config::{
-    Config, CsvExporter, ExporterConfig, SqliteExporter, SqllogConfig,
-};
-use dm_database_sqllog2db::features::filters::MetaFilters;
-use dm_database_sqllog2db::features::{FeaturesConfig, FiltersFeature, ReplaceParametersConfig};
+use dm_database_sqllog2db::config::{Config, CsvExporter, SqliteExporter};
+use dm_database_sqllog2db::features::{FiltersFeature, ReplaceParametersConfig};
 use dm_database_sqllog2db::lang::Lang;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
@@ -32,22 +29,29 @@ fn write_test_log(path: &std::path::Path, count: usize) {
     std::fs::write(path, buf).unwrap();
 }
 
+/// 配置结构体为 `#[non_exhaustive]`：从默认值出发逐字段赋值
+fn log_config(path: &str) -> Config {
+    let mut cfg = Config::default();
+    cfg.sqllog.path = path.to_string();
+    cfg
+}
+
 fn make_run_config(log_dir: &std::path::Path, csv_file: &std::path::Path) -> Config {
-    Config {
-        sqllog: SqllogConfig {
-            path: log_dir.to_str().unwrap().to_string(),
-        },
-        exporter: ExporterConfig {
-            csv: Some(CsvExporter {
-                file: csv_file.to_str().unwrap().to_string(),
-                overwrite: true,
-                append: false,
-                ..CsvExporter::default()
-            }),
-            ..Default::default()
-        },
-        ..Default::default()
-    }
+    let mut cfg = log_config(log_dir.to_str().unwrap());
+    let mut csv = CsvExporter::default();
+    csv.file = csv_file.to_str().unwrap().to_string();
+    csv.overwrite = true;
+    csv.append = false;
+    cfg.exporter.csv = Some(csv);
+    cfg
+}
+
+/// 启用的过滤器配置，由 `f` 填写具体条件
+fn enabled_filters(f: impl FnOnce(&mut FiltersFeature)) -> FiltersFeature {
+    let mut filters = FiltersFeature::default();
+    filters.enable = true;
+    f(&mut filters);
+    filters
 }
 
 // ── handle_run tests ─────────────────────────────────────────────────────────
@@ -58,12 +62,7 @@ fn test_handle_run_dry_run_empty_dir() {
     let log_dir = dir.path().join("logs");
     std::fs::create_dir_all(&log_dir).unwrap();
     // No log files → handle_run returns Ok early
-    let cfg = Config {
-        sqllog: SqllogConfig {
-            path: log_dir.to_str().unwrap().to_string(),
-        },
-        ..Default::default()
-    };
+    let cfg = log_config(log_dir.to_str().unwrap());
     let interrupted = Arc::new(AtomicBool::new(false));
     handle_run(
         &cfg,
@@ -88,12 +87,7 @@ fn test_handle_run_dry_run_with_log_files() {
     write_test_log(&log_dir.join("a.log"), 20);
     write_test_log(&log_dir.join("b.log"), 10);
 
-    let cfg = Config {
-        sqllog: SqllogConfig {
-            path: log_dir.to_str().unwrap().to_string(),
-        },
-        ..Default::default()
-    };
+    let cfg = log_config(log_dir.to_str().unwrap());
 
     let interrupted = Arc::new(AtomicBool::new(false));
     handle_run(
@@ -118,12 +112,7 @@ fn test_handle_run_dry_run_with_limit() {
     std::fs::create_dir_all(&log_dir).unwrap();
     write_test_log(&log_dir.join("test.log"), 50);
 
-    let cfg = Config {
-        sqllog: SqllogConfig {
-            path: log_dir.to_str().unwrap().to_string(),
-        },
-        ..Default::default()
-    };
+    let cfg = log_config(log_dir.to_str().unwrap());
 
     let interrupted = Arc::new(AtomicBool::new(false));
     // limit to 5 records
@@ -172,6 +161,44 @@ fn test_handle_run_real_csv_export() {
     assert!(content.lines().count() >= 10);
 }
 
+#[test]
+fn test_run_numeric_output_ignores_locale() {
+    // 以逗号作小数点的区域设置下运行二进制，数值列仍使用 `.`、无千分位分隔符
+    let dir = tempfile::TempDir::new().unwrap();
+    let log_dir = dir.path().join("logs");
+    std::fs::create_dir_all(&log_dir).unwrap();
+    std::fs::write(
+        log_dir.join("test.log"),
+        "2025-01-15 10:30:28.001 (EP[0] sess:0x1 user:U trxid:1 stmt:0x1 appname:A ip:10.0.0.1) [SEL] SELECT 1. EXECTIME: 1234.5(ms) ROWCOUNT: 12345(rows) EXEC_ID: 1234567.\n",
+    )
+    .unwrap();
+    let csv_file = dir.path().join("out.csv");
+    let config = dir.path().join("config.toml");
+    std::fs::write(
+        &config,
+        format!(
+            "[sqllog]\npath = {:?}\n[logging]\nfile = {:?}\n[exporter.csv]\nfile = {:?}\nexectime_format = \"decimal\"\n",
+            log_dir.to_str().unwrap(),
+            dir.path().join("app.log").to_str().unwrap(),
+            csv_file.to_str().unwrap(),
+        ),
+    )
+    .unwrap();
+
+    for locale in ["de_DE.UTF-8", "zh_CN.UTF-8"] {
+        let status = std::process::Command::new(env!("CARGO_BIN_EXE_sqllog2db"))
+            .args(["run", "-c", config.to_str().unwrap(), "--quiet"])
+            .env("LC_ALL", locale)
+            .env("LANG", locale)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let content = std::fs::read_to_string(&csv_file).unwrap();
+        let row = content.lines().nth(1).unwrap();
+        assert!(row.ends_with(",1234.500,12345,1234567,"), "{locale}: {row}");
+    }
+}
+
 #[test]
 fn test_handle_run_interrupted() {
     let dir = tempfile::TempDir::new().unwrap();
@@ -179,12 +206,7 @@ fn test_handle_run_interrupted() {
     std::fs::create_dir_all(&log_dir).unwrap();
     write_test_log(&log_dir.join("test.log"), 100);
 
-    let cfg = Config {
-        sqllog: SqllogConfig {
-            path: log_dir.to_str().unwrap().to_string(),
-        },
-        ..Default::default()
-    };
+    let cfg = log_config(log_dir.to_str().unwrap());
 
     // Pre-set interrupted flag — run should return Err(Interrupted)
     let interrupted = Arc::new(AtomicBool::new(true));
@@ -204,6 +226,26 @@ fn test_handle_run_interrupted() {
     let _ = result;
 }
 
+#[test]
+fn test_handle_run_cancelled_finalizes_csv() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let log_dir = dir.path().join("logs");
+    std::fs::create_dir_all(&log_dir).unwrap();
+    write_test_log(&log_dir.join("test.log"), 100);
+    let csv_file = dir.path().join("out.csv");
+    let cfg = make_run_config(&log_dir, &csv_file);
+
+    let cancel = Arc::new(AtomicBool::new(true));
+    let result = handle_run(&cfg, None, false, true, &cancel, 80, false, None, 1, None);
+    assert!(matches!(
+        result,
+        Err(dm_database_sqllog2db::error::Error::Interrupted)
+    ));
+    // 取消后导出器仍完成 finalize：文件存在且只有表头
+    let content = std::fs::read_to_string(&csv_file).unwrap();
+    assert_eq!(content.lines().count(), 1, "{content}");
+}
+
 // ── resume tests ─────────────────────────────────────────────────────────────
 
 #[test]
@@ -336,14 +378,9 @@ fn test_handle_stats_empty_dir() {
     let dir = tempfile::TempDir::new().unwrap();
     let log_dir = dir.path().join("logs");
     std::fs::create_dir_all(&log_dir).unwrap();
-    let cfg = Config {
-        sqllog: SqllogConfig {
-            path: log_dir.to_str().unwrap().to_string(),
-        },
-        ..Default::default()
-    };
+    let cfg = log_config(log_dir.to_str().unwrap());
     // No log files → prints "No log files found" and returns without panic
-    handle_stats(&cfg, true, false, None, false, &[], None, None);
+    handle_stats(&cfg, true, false, None, false, &[], None, None, None);
 }
 
 #[test]
@@ -353,25 +390,15 @@ fn test_handle_stats_with_log_files() {
     std::fs::create_dir_all(&log_dir).unwrap();
     write_test_log(&log_dir.join("data.log"), 15);
 
-    let cfg = Config {
-        sqllog: SqllogConfig {
-            path: log_dir.to_str().unwrap().to_string(),
-        },
-        ..Default::default()
-    };
-    handle_stats(&cfg, true, false, None, false, &[], None, None); // quiet=true to suppress progress bar
+    let cfg = log_config(log_dir.to_str().unwrap());
+    handle_stats(&cfg, true, false, None, false, &[], None, None, None); // quiet=true to suppress progress bar
 }
 
 #[test]
 fn test_handle_stats_nonexistent_dir() {
-    let cfg = Config {
-        sqllog: SqllogConfig {
-            path: "/no/such/directory/at/all".to_string(),
-        },
-        ..Default::default()
-    };
+    let cfg = log_config("/no/such/directory/at/all");
     // Should not panic — prints an error and returns
-    handle_stats(&cfg, true, false, None, false, &[], None, None);
+    handle_stats(&cfg, true, false, None, false, &[], None, None, None);
 }
 
 fn write_test_log_multi_ts(path: &std::path::Path, timestamps: &[&str]) {
@@ -391,12 +418,7 @@ fn write_test_log_multi_ts(path: &std::path::Path, timestamps: &[&str]) {
 }
 
 fn make_stats_cfg(log_dir: &std::path::Path) -> Config {
-    Config {
-        sqllog: SqllogConfig {
-            path: log_dir.to_str().unwrap().to_string(),
-        },
-        ..Default::default()
-    }
+    log_config(log_dir.to_str().unwrap())
 }
 
 #[test]
@@ -414,6 +436,7 @@ fn test_handle_stats_group_by_user() {
         &["user".to_string()],
         None,
         None,
+        None,
     );
 }
 
@@ -424,7 +447,7 @@ fn test_handle_stats_group_by_multiple() {
     write_test_log(&dir.path().join("data.log"), 20);
     let cfg = make_stats_cfg(dir.path());
     let fields = vec!["user".to_string(), "app".to_string(), "ip".to_string()];
-    handle_stats(&cfg, true, false, None, false, &fields, None, None);
+    handle_stats(&cfg, true, false, None, false, &fields, None, None, None);
 }
 
 #[test]
@@ -443,6 +466,7 @@ fn test_handle_stats_group_by_invalid() {
         &["badfield".to_string()],
         None,
         None,
+        None,
     );
 }
 
@@ -452,7 +476,7 @@ fn test_handle_stats_top_slow() {
     std::fs::create_dir_all(dir.path()).unwrap();
     write_test_log(&dir.path().join("data.log"), 30);
     let cfg = make_stats_cfg(dir.path());
-    handle_stats(&cfg, true, false, Some(5), false, &[], None, None);
+    handle_stats(&cfg, true, false, Some(5), false, &[], None, None, None);
 }
 
 #[test]
@@ -469,7 +493,17 @@ fn test_handle_stats_bucket_hour() {
     ];
     write_test_log_multi_ts(&dir.path().join("data.log"), ts);
     let cfg = make_stats_cfg(dir.path());
-    handle_stats(&cfg, true, false, None, false, &[], Some("hour"), None);
+    handle_stats(
+        &cfg,
+        true,
+        false,
+        None,
+        false,
+        &[],
+        Some("hour"),
+        None,
+        None,
+    );
 }
 
 #[test]
@@ -484,7 +518,17 @@ fn test_handle_stats_bucket_minute() {
     ];
     write_test_log_multi_ts(&dir.path().join("data.log"), ts);
     let cfg = make_stats_cfg(dir.path());
-    handle_stats(&cfg, true, false, None, false, &[], Some("minute"), None);
+    handle_stats(
+        &cfg,
+        true,
+        false,
+        None,
+        false,
+        &[],
+        Some("minute"),
+        None,
+        None,
+    );
 }
 
 #[test]
@@ -494,7 +538,17 @@ fn test_handle_stats_bucket_invalid() {
     write_test_log(&dir.path().join("data.log"), 5);
     let cfg = make_stats_cfg(dir.path());
     // invalid granularity — should print error and return without panic
-    handle_stats(&cfg, true, false, None, false, &[], Some("week"), None);
+    handle_stats(
+        &cfg,
+        true,
+        false,
+        None,
+        false,
+        &[],
+        Some("week"),
+        None,
+        None,
+    );
 }
 
 #[test]
@@ -503,7 +557,7 @@ fn test_handle_stats_json_basic() {
     std::fs::create_dir_all(dir.path()).unwrap();
     write_test_log(&dir.path().join("data.log"), 10);
     let cfg = make_stats_cfg(dir.path());
-    handle_stats(&cfg, true, false, None, true, &[], None, None);
+    handle_stats(&cfg, true, false, None, true, &[], None, None, None);
 }
 
 #[test]
@@ -528,6 +582,7 @@ fn test_handle_stats_json_with_group_and_bucket() {
         &fields,
         Some("hour"),
         None,
+        None,
     );
 }
 
@@ -538,7 +593,7 @@ fn test_handle_stats_verbose() {
     write_test_log(&dir.path().join("data.log"), 10);
     let cfg = make_stats_cfg(dir.path());
     // quiet=false, verbose=true — exercises the file table path
-    handle_stats(&cfg, false, true, None, false, &[], None, None);
+    handle_stats(&cfg, false, true, None, false, &[], None, None, None);
 }
 
 #[test]
@@ -563,6 +618,7 @@ fn test_handle_stats_group_and_bucket_non_quiet() {
         &fields,
         Some("hour"),
         None,
+        None,
     );
 }
 
@@ -572,12 +628,7 @@ fn test_handle_stats_group_and_bucket_non_quiet() {
 fn test_handle_digest_empty_dir() {
     let dir = tempfile::TempDir::new().unwrap();
     let log_dir = dir.path().join("nologs");
-    let cfg = Config {
-        sqllog: SqllogConfig {
-            path: log_dir.to_str().unwrap().to_string(),
-        },
-        ..Default::default()
-    };
+    let cfg = log_config(log_dir.to_str().unwrap());
     // No log files → prints message and returns without panic
     handle_digest(&cfg, true, None, SortBy::Count, 1, false, None);
 }
@@ -630,12 +681,7 @@ fn test_handle_digest_json() {
 
 #[test]
 fn test_handle_digest_nonexistent_dir() {
-    let cfg = Config {
-        sqllog: SqllogConfig {
-            path: "/nonexistent_dir_xyz".to_string(),
-        },
-        ..Default::default()
-    };
+    let cfg = log_config("/nonexistent_dir_xyz");
     // Should not panic
     handle_digest(&cfg, true, None, SortBy::Count, 1, false, None);
 }
@@ -721,114 +767,69 @@ fn test_handle_validate_default_config() {
 
 #[test]
 fn test_handle_validate_with_sqlite_exporter() {
-    let cfg = Config {
-        exporter: ExporterConfig {
-            csv: None,
-            sqlite: Some(SqliteExporter {
-                database_url: "/tmp/test.db".to_string(),
-                table_name: "records".to_string(),
-                overwrite: true,
-                append: false,
-                batch_size: 10_000,
-            }),
-        },
-        ..Default::default()
-    };
+    let mut sqlite = SqliteExporter::default();
+    sqlite.database_url = "/tmp/test.db".to_string();
+    sqlite.table_name = "records".to_string();
+    sqlite.overwrite = true;
+    sqlite.append = false;
+    sqlite.batch_size = 10_000;
+    let mut cfg = Config::default();
+    cfg.exporter.csv = None;
+    cfg.exporter.sqlite = Some(sqlite);
     handle_validate(&cfg); // hits sqlite branch
 }
 
 #[test]
 fn test_handle_validate_with_replace_parameters_none() {
-    let cfg = Config {
-        features: FeaturesConfig {
-            replace_parameters: None,
-            ..Default::default()
-        },
-        ..Default::default()
-    };
+    let mut cfg = Config::default();
+    cfg.features.replace_parameters = None;
     handle_validate(&cfg); // hits replace_parameters None branch
 }
 
 #[test]
 fn test_handle_validate_with_replace_parameters_some() {
-    let cfg = Config {
-        features: FeaturesConfig {
-            replace_parameters: Some(ReplaceParametersConfig {
-                enable: true,
-                placeholders: vec!["?".to_string()],
-            }),
-            ..Default::default()
-        },
-        ..Default::default()
-    };
+    let mut replace = ReplaceParametersConfig::default();
+    replace.enable = true;
+    replace.placeholders = vec!["?".to_string()];
+    let mut cfg = Config::default();
+    cfg.features.replace_parameters = Some(replace);
     handle_validate(&cfg); // hits replace_parameters Some branch
 }
 
 #[test]
 fn test_handle_validate_with_filters_none() {
-    let cfg = Config {
-        features: FeaturesConfig {
-            filters: None,
-            ..Default::default()
-        },
-        ..Default::default()
-    };
+    let mut cfg = Config::default();
+    cfg.features.filters = None;
     handle_validate(&cfg); // hits filters None branch
 }
 
 #[test]
 fn test_handle_validate_with_filters_all_fields() {
-    use dm_database_sqllog2db::features::filters::{IndicatorFilters, SqlFilters};
-    let cfg = Config {
-        features: FeaturesConfig {
-            filters: Some(FiltersFeature {
-                enable: true,
-                meta: MetaFilters {
-                    start_ts: Some("2025-01-01".to_string()),
-                    end_ts: Some("2025-12-31".to_string()),
-                    usernames: Some(vec!["admin".to_string()]),
-                    client_ips: Some(vec!["10.0.0.1".to_string()]),
-                    trxids: Some(
-                        ["tx1"]
-                            .iter()
-                            .map(|s| compact_str::CompactString::new(s))
-                            .collect(),
-                    ),
-                    ..Default::default()
-                },
-                indicators: IndicatorFilters {
-                    exec_ids: Some([42_i64].into_iter().collect()),
-                    min_runtime_ms: Some(100),
-                    min_row_count: Some(10),
-                },
-                sql: SqlFilters {
-                    include_patterns: Some(vec!["SELECT".to_string()]),
-                    exclude_patterns: Some(vec!["DROP".to_string()]),
-                },
-                record_sql: SqlFilters::default(),
-            }),
-            ..Default::default()
-        },
-        ..Default::default()
-    };
+    let mut cfg = Config::default();
+    cfg.features.filters = Some(enabled_filters(|f| {
+        f.meta.start_ts = Some("2025-01-01".to_string());
+        f.meta.end_ts = Some("2025-12-31".to_string());
+        f.meta.usernames = Some(vec!["admin".to_string()]);
+        f.meta.client_ips = Some(vec!["10.0.0.1".to_string()]);
+        f.meta.trxids = Some(
+            ["tx1"]
+                .iter()
+                .map(|s| compact_str::CompactString::new(s))
+                .collect(),
+        );
+        f.indicators.exec_ids = Some([42_i64].into_iter().collect());
+        f.indicators.min_runtime_ms = Some(100);
+        f.indicators.min_row_count = Some(10);
+        f.sql.include_patterns = Some(vec!["SELECT".to_string()]);
+        f.sql.exclude_patterns = Some(vec!["DROP".to_string()]);
+    }));
     handle_validate(&cfg); // hits all filter sub-branches
 }
 
 #[test]
 fn test_handle_validate_filters_disabled() {
-    use dm_database_sqllog2db::features::filters::IndicatorFilters;
-    let cfg = Config {
-        features: FeaturesConfig {
-            filters: Some(FiltersFeature {
-                enable: false,
-                meta: MetaFilters::default(),
-                indicators: IndicatorFilters::default(),
-                ..Default::default()
-            }),
-            ..Default::default()
-        },
-        ..Default::default()
-    };
+    let mut cfg = Config::default();
+    cfg.features.filters = Some(FiltersFeature::default());
     handle_validate(&cfg); // hits "配置但未明确启用" branch
 }
 
@@ -868,14 +869,9 @@ fn test_handle_run_with_filters_builds_pipeline() {
     let csv_file = dir.path().join("out.csv");
     let mut cfg = make_run_config(&log_dir, &csv_file);
     // Enable a record-level filter — exercises build_pipeline and FilterProcessor
-    cfg.features.filters = Some(FiltersFeature {
-        enable: true,
-        meta: MetaFilters {
-            usernames: Some(vec!["TESTUSER".to_string()]),
-            ..Default::default()
-        },
-        ..Default::default()
-    });
+    cfg.features.filters = Some(enabled_filters(|f| {
+        f.meta.usernames = Some(vec!["TESTUSER".to_string()]);
+    }));
     let compiled_filters = cfg.validate_and_compile().unwrap();
     let interrupted = Arc::new(AtomicBool::new(false));
     handle_run(
@@ -930,16 +926,9 @@ fn test_handle_run_with_transaction_filters_prescans() {
     let csv_file = dir.path().join("out.csv");
     let mut cfg = make_run_config(&log_dir, &csv_file);
     // exec_ids filter triggers transaction pre-scan path
-    cfg.features.filters = Some(FiltersFeature {
-        enable: true,
-        meta: MetaFilters::default(),
-        indicators: dm_database_sqllog2db::features::filters::IndicatorFilters {
-            exec_ids: Some([0_i64, 1, 2].into_iter().collect()),
-            min_runtime_ms: None,
-            min_row_count: None,
-        },
-        ..Default::default()
-    });
+    cfg.features.filters = Some(enabled_filters(|f| {
+        f.indicators.exec_ids = Some([0_i64, 1, 2].into_iter().collect());
+    }));
     let interrupted = Arc::new(AtomicBool::new(false));
     handle_run(
         &cfg,
@@ -964,16 +953,9 @@ fn test_handle_run_with_min_runtime_filter() {
     write_test_log(&log_dir.join("data.log"), 20);
     let csv_file = dir.path().join("out.csv");
     let mut cfg = make_run_config(&log_dir, &csv_file);
-    cfg.features.filters = Some(FiltersFeature {
-        enable: true,
-        meta: MetaFilters::default(),
-        indicators: dm_database_sqllog2db::features::filters::IndicatorFilters {
-            exec_ids: None,
-            min_runtime_ms: Some(1),
-            min_row_count: None,
-        },
-        ..Default::default()
-    });
+    cfg.features.filters = Some(enabled_filters(|f| {
+        f.indicators.min_runtime_ms = Some(1);
+    }));
     let interrupted = Arc::new(AtomicBool::new(false));
     handle_run(
         &cfg,